@@ -17,29 +17,65 @@
 use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::Sha512Trunc256Sum;
 
 use crate::vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use crate::vm::analysis::type_checker::ContractAnalysis;
+use crate::vm::costs::ExecutionCost;
+use crate::vm::database::clarity_store::{make_contract_hash_key, ContractCommitment};
 use crate::vm::database::{
-    ClarityBackingStore, ClarityDeserializable, ClaritySerializable, RollbackWrapper,
+    ClarityBackingStore, ClarityDatabase, ClarityDeserializable, ClaritySerializable,
+    RollbackWrapper, StoreType,
 };
 use crate::vm::representations::ClarityName;
 use crate::vm::types::signatures::FunctionSignature;
 use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 use crate::vm::ClarityVersion;
 
+/// The version of the `AnalysisArchive` format written by this node
+pub const ANALYSIS_ARCHIVE_VERSION: u32 = 1;
+
+/// A portable copy of every contract analysis stored in an `AnalysisDatabase`, which can be
+/// imported into a fresh one without replaying the contracts' deployments.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisArchive {
+    pub version: u32,
+    pub entries: Vec<AnalysisArchiveEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisArchiveEntry {
+    pub contract_identifier: QualifiedContractIdentifier,
+    /// The hash of the contract's source that the chain state commits to
+    pub contract_hash: Sha512Trunc256Sum,
+    pub analysis: ContractAnalysis,
+    /// The contract's dependencies, if they were recorded on the exporting database
+    pub contract_dependencies: Option<Vec<QualifiedContractIdentifier>>,
+    /// The contract's source, if it was stored (see `STORE_CONTRACT_SRC_INTERFACE`)
+    pub contract_src: Option<String>,
+}
+
 pub struct AnalysisDatabase<'a> {
     store: RollbackWrapper<'a>,
+    /// Dependencies recorded with `insert_dependencies`, which take the place of the stored ones.
+    /// This is only kept in memory, for developer tools that re-analyze contracts incrementally.
+    dependencies: BTreeMap<QualifiedContractIdentifier, BTreeSet<QualifiedContractIdentifier>>,
+    /// The cost bounds of the functions of each contract saved through this database. This is
+    /// only kept in memory, so that analyzing a set of contracts together can bound the cost of
+    /// `contract-call?`s between them.
+    cost_bounds: BTreeMap<QualifiedContractIdentifier, BTreeMap<ClarityName, ExecutionCost>>,
 }
 
 impl<'a> AnalysisDatabase<'a> {
     pub fn new(store: &'a mut dyn ClarityBackingStore) -> AnalysisDatabase<'a> {
-        AnalysisDatabase {
-            store: RollbackWrapper::new(store),
-        }
+        AnalysisDatabase::new_with_rollback_wrapper(RollbackWrapper::new(store))
     }
     pub fn new_with_rollback_wrapper(store: RollbackWrapper<'a>) -> AnalysisDatabase<'a> {
-        AnalysisDatabase { store }
+        AnalysisDatabase {
+            store,
+            dependencies: BTreeMap::new(),
+            cost_bounds: BTreeMap::new(),
+        }
     }
 
     pub fn execute<F, T, E>(&mut self, f: F) -> Result<T, E>
@@ -78,6 +114,12 @@ impl<'a> AnalysisDatabase<'a> {
         "analysis"
     }
 
+    /// The contracts a contract depends on are stored under their own key, so that the stored
+    /// `ContractAnalysis` is unchanged.
+    pub fn dependencies_storage_key() -> &'static str {
+        "analysis-dependencies"
+    }
+
     // used by tests to ensure that
     //   the contract -> contract hash key exists in the marf
     //    even if the contract isn't published.
@@ -136,10 +178,24 @@ impl<'a> AnalysisDatabase<'a> {
             }))
     }
 
+    /// Store the analysis of a contract, and the dependencies the type checker found for it.
     pub fn insert_contract(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
         contract: &ContractAnalysis,
+    ) -> CheckResult<()> {
+        self.insert_analysis(
+            contract_identifier,
+            contract,
+            Some(contract.get_dependencies()),
+        )
+    }
+
+    fn insert_analysis(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        contract: &ContractAnalysis,
+        dependencies: Option<&BTreeSet<QualifiedContractIdentifier>>,
     ) -> CheckResult<()> {
         let key = AnalysisDatabase::storage_key();
         if self.store.has_metadata_entry(contract_identifier, key) {
@@ -149,9 +205,236 @@ impl<'a> AnalysisDatabase<'a> {
         self.store
             .insert_metadata(contract_identifier, key, &contract.serialize())
             .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+        if let Some(dependencies) = dependencies {
+            let dependencies = serde_json::to_string(dependencies)
+                .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+            self.store
+                .insert_metadata(
+                    contract_identifier,
+                    AnalysisDatabase::dependencies_storage_key(),
+                    &dependencies,
+                )
+                .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+        }
         Ok(())
     }
 
+    /// Copy every contract analysis stored in this database, along with the contract's source
+    /// and dependencies, into an archive ordered by contract identifier.
+    pub fn export_archive(&mut self) -> CheckResult<AnalysisArchive> {
+        let contracts = self
+            .store
+            .get_metadata_contracts(AnalysisDatabase::storage_key())
+            .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+        let contract_src_key =
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-src");
+
+        let mut entries = vec![];
+        for contract_identifier in contracts.into_iter() {
+            // the metadata of contracts on other forks is not visible from here
+            let analysis = match self.load_contract_non_canonical(&contract_identifier)? {
+                Some(analysis) => analysis,
+                None => continue,
+            };
+            let contract_hash = self
+                .store
+                .get::<ContractCommitment>(&make_contract_hash_key(&contract_identifier))
+                .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?
+                .ok_or_else(|| CheckErrors::NoSuchContract(contract_identifier.to_string()))?
+                .hash;
+            let contract_dependencies = self
+                .get_dependencies(&contract_identifier)?
+                .map(|dependencies| dependencies.into_iter().collect());
+            let contract_src = self
+                .store
+                .get_metadata(&contract_identifier, &contract_src_key)
+                .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+            entries.push(AnalysisArchiveEntry {
+                contract_identifier,
+                contract_hash,
+                analysis,
+                contract_dependencies,
+                contract_src,
+            });
+        }
+        entries.sort_by(|a, b| a.contract_identifier.cmp(&b.contract_identifier));
+
+        Ok(AnalysisArchive {
+            version: ANALYSIS_ARCHIVE_VERSION,
+            entries,
+        })
+    }
+
+    /// Store every contract analysis in `archive`. Contracts that the chain state does not
+    /// know about yet are committed to at the current block, so that their analyses can be
+    /// found. Fails if any of the contracts already has an analysis.
+    pub fn import_archive(&mut self, archive: &AnalysisArchive) -> CheckResult<()> {
+        if archive.version != ANALYSIS_ARCHIVE_VERSION {
+            return Err(CheckErrors::Expects(format!(
+                "Unsupported analysis archive version {}",
+                archive.version
+            ))
+            .into());
+        }
+        let contract_src_key =
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-src");
+
+        for entry in archive.entries.iter() {
+            let contract_identifier = &entry.contract_identifier;
+            let has_commitment = self
+                .store
+                .has_entry(&make_contract_hash_key(contract_identifier))
+                .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+            if !has_commitment {
+                self.store
+                    .prepare_for_contract_metadata(contract_identifier, entry.contract_hash)
+                    .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+            }
+            let dependencies: Option<BTreeSet<_>> = entry
+                .contract_dependencies
+                .as_ref()
+                .map(|dependencies| dependencies.iter().cloned().collect());
+            self.insert_analysis(contract_identifier, &entry.analysis, dependencies.as_ref())?;
+            if let Some(contract_src) = entry.contract_src.as_ref() {
+                self.store
+                    .insert_metadata(contract_identifier, &contract_src_key, contract_src)
+                    .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Overwrite the analysis of a contract that may already exist. The backing store cannot
+    /// overwrite metadata, so this must only be used in a nested context that is rolled back.
+    pub(crate) fn replace_contract(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        contract: &ContractAnalysis,
+    ) -> CheckResult<()> {
+        self.store
+            .insert_metadata(
+                contract_identifier,
+                AnalysisDatabase::storage_key(),
+                &contract.serialize(),
+            )
+            .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+        Ok(())
+    }
+
+    /// Record the contracts that `contract_identifier` depends on on this database only,
+    /// replacing the stored ones and any earlier record. Used for contracts that are re-analyzed
+    /// without being saved.
+    pub fn insert_dependencies(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        dependencies: BTreeSet<QualifiedContractIdentifier>,
+    ) {
+        self.dependencies
+            .insert(contract_identifier.clone(), dependencies);
+    }
+
+    /// Record the cost bounds of the functions of `contract_identifier`, replacing any earlier
+    /// record.
+    pub fn insert_cost_bounds(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        cost_bounds: BTreeMap<ClarityName, ExecutionCost>,
+    ) {
+        self.cost_bounds
+            .insert(contract_identifier.clone(), cost_bounds);
+    }
+
+    pub fn get_cost_bound(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        function_name: &str,
+    ) -> Option<&ExecutionCost> {
+        self.cost_bounds
+            .get(contract_identifier)?
+            .get(function_name)
+    }
+
+    /// The contracts that `contract_identifier` depends on, as recorded with
+    /// `insert_dependencies` or else as stored with its analysis. `None` if neither has them,
+    /// e.g. for contracts saved before dependencies were stored.
+    pub fn get_dependencies(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> CheckResult<Option<BTreeSet<QualifiedContractIdentifier>>> {
+        if let Some(dependencies) = self.dependencies.get(contract_identifier) {
+            return Ok(Some(dependencies.clone()));
+        }
+        self.store
+            .get_metadata(
+                contract_identifier,
+                AnalysisDatabase::dependencies_storage_key(),
+            )
+            // like `load_contract`, a contract that does not exist has no dependencies
+            .ok()
+            .flatten()
+            .map(|x| {
+                serde_json::from_str(&x).map_err(|_| {
+                    CheckErrors::Expects("Bad data deserialized from DB".into()).into()
+                })
+            })
+            .transpose()
+    }
+
+    /// Every contract with known dependencies that depends on `contract_identifier`, directly
+    /// or through other contracts. Each contract is listed after the contracts it depends on.
+    pub fn get_dependents(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> CheckResult<Vec<QualifiedContractIdentifier>> {
+        let mut contracts: BTreeSet<_> = self
+            .store
+            .get_metadata_contracts(AnalysisDatabase::dependencies_storage_key())
+            .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?
+            .into_iter()
+            .collect();
+        contracts.extend(self.dependencies.keys().cloned());
+        let mut graph = BTreeMap::new();
+        for contract in contracts.into_iter() {
+            if let Some(dependencies) = self.get_dependencies(&contract)? {
+                graph.insert(contract, dependencies);
+            }
+        }
+
+        let mut remaining = BTreeSet::new();
+        let mut frontier = vec![contract_identifier];
+        while let Some(dependency) = frontier.pop() {
+            for (contract, dependencies) in graph.iter() {
+                if dependencies.contains(dependency) && remaining.insert(contract.clone()) {
+                    frontier.push(contract);
+                }
+            }
+        }
+        remaining.remove(contract_identifier);
+
+        let mut dependents = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready: Vec<_> = remaining
+                .iter()
+                .filter(|contract| {
+                    graph[*contract]
+                        .iter()
+                        .all(|dependency| !remaining.contains(dependency))
+                })
+                .cloned()
+                .collect();
+            if ready.is_empty() {
+                // Deployed contracts cannot depend on each other in a cycle
+                dependents.extend(remaining);
+                break;
+            }
+            for contract in ready {
+                remaining.remove(&contract);
+                dependents.push(contract);
+            }
+        }
+        Ok(dependents)
+    }
+
     pub fn get_clarity_version(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
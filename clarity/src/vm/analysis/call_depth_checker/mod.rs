@@ -0,0 +1,146 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use stacks_common::types::StacksEpochId;
+
+use super::errors::{CheckError, CheckErrors, CheckResult, CheckWarning, CheckWarnings};
+use super::AnalysisDatabase;
+use crate::vm::analysis::types::{AnalysisPass, ContractAnalysis};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::SymbolicExpressionType::{Atom, List};
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::{ClarityVersion, MAX_CALL_STACK_DEPTH};
+
+#[cfg(test)]
+mod tests;
+
+/// `CallDepthChecker` computes, for every function defined in the contract, a lower bound on the
+/// call stack depth needed to evaluate it, and records it in `ContractAnalysis::call_depths`.
+///
+/// Only calls to the contract's own functions and `contract-call?`s are counted, so the bound is
+/// exact for chains of user-defined functions and conservative otherwise. A `contract-call?`
+/// counts as the two frames it always pushes, since the callee's own depth is not known here.
+///
+/// A function whose bound exceeds `MAX_CALL_STACK_DEPTH` fails at runtime whenever that call chain
+/// is taken. This is an error in Clarity 3, and a warning in earlier versions, where such
+/// contracts were already accepted. No epoch runs Clarity 3 yet, so this pass is only run by
+/// developer tools (see `AnalysisPassRegistry::tooling`), and the network still accepts such
+/// contracts.
+pub struct CallDepthChecker {
+    clarity_version: ClarityVersion,
+    /// Lower bound on the call stack depth needed by each function checked so far
+    call_depths: BTreeMap<ClarityName, u64>,
+    warnings: Vec<CheckWarning>,
+}
+
+impl AnalysisPass for CallDepthChecker {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        let mut checker = CallDepthChecker::new(contract_analysis.clarity_version);
+        // Top-level expressions are sorted so that a function is defined before its callers
+        for expr in contract_analysis.expressions.iter() {
+            checker.check_top_level_expression(expr)?;
+        }
+        contract_analysis.call_depths = checker.call_depths;
+        contract_analysis.warnings.extend(checker.warnings);
+        Ok(())
+    }
+}
+
+impl CallDepthChecker {
+    fn new(clarity_version: ClarityVersion) -> CallDepthChecker {
+        CallDepthChecker {
+            clarity_version,
+            call_depths: BTreeMap::new(),
+            warnings: vec![],
+        }
+    }
+
+    fn check_top_level_expression(&mut self, expr: &SymbolicExpression) -> CheckResult<()> {
+        let (name, body) = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body }))
+            | Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }))
+            | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => {
+                match signature.first().and_then(|name| name.match_atom()) {
+                    Some(name) => (name, body),
+                    None => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+        // Calling the function pushes its own frame
+        let depth = 1 + self.expression_depth(body);
+        self.call_depths.insert(name.clone(), depth);
+
+        if depth > MAX_CALL_STACK_DEPTH as u64 {
+            if self.clarity_version >= ClarityVersion::Clarity3 {
+                let mut error =
+                    CheckError::new(CheckErrors::MaxCallDepthExceeded(name.to_string(), depth));
+                error.set_expression(expr);
+                return Err(error);
+            }
+            let mut warning =
+                CheckWarning::new(CheckWarnings::MaxCallDepthExceeded(name.to_string(), depth));
+            warning.set_expression(expr);
+            self.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    /// Lower bound on the number of frames pushed while evaluating `expr`
+    fn expression_depth(&self, expr: &SymbolicExpression) -> u64 {
+        match &expr.expr {
+            // A function passed to `map`, `filter`, or `fold` is called from within it
+            Atom(name) => self.call_depths.get(name).copied().unwrap_or(0),
+            List(exprs) => {
+                let function_name = exprs.first().and_then(|expr| expr.match_atom());
+                let args = if function_name.is_some() {
+                    &exprs[1..]
+                } else {
+                    &exprs[..]
+                };
+                let args_depth = args
+                    .iter()
+                    .map(|expr| self.expression_depth(expr))
+                    .max()
+                    .unwrap_or(0);
+                let Some(function_name) = function_name else {
+                    return args_depth;
+                };
+                if let Some(depth) = self.call_depths.get(function_name) {
+                    // The arguments are evaluated above the function's frame
+                    return cmp::max(*depth, 1 + args_depth);
+                }
+                match NativeFunctions::lookup_by_name_at_version(
+                    function_name,
+                    &self.clarity_version,
+                ) {
+                    // `contract-call?` pushes its own frame, and then the callee's
+                    Some(NativeFunctions::ContractCall) => 1 + cmp::max(1, args_depth),
+                    _ => args_depth,
+                }
+            }
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::errors::{CheckErrors, CheckWarnings};
+use crate::vm::analysis::{mem_type_check, run_analysis};
+use crate::vm::ast::parse;
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::errors::RuntimeErrorType;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::{execute, ClarityVersion, Value, MAX_CALL_STACK_DEPTH};
+
+/// A contract with private functions `f-1` through `f-<length>`, each calling the previous one
+fn call_chain(length: usize) -> String {
+    let mut contract = "(define-private (f-1) u1)".to_string();
+    for i in 2..=length {
+        contract.push_str(&format!("\n(define-private (f-{}) (f-{}))", i, i - 1));
+    }
+    contract
+}
+
+#[test]
+fn test_call_depths() {
+    let contract = "(define-trait callee ((foo () (response bool uint))))
+        (define-private (leaf) u1)
+        (define-private (inc (x uint)) (+ x u1))
+        (define-private (nested-args) (inc (inc (leaf))))
+        (define-private (mapped (x uint)) (leaf))
+        (define-private (higher-order) (map mapped (list u1 u2)))
+        (define-private (with-let) (let ((x (higher-order))) x))
+        (define-public (dynamic (c <callee>)) (contract-call? c foo))";
+    let (_, analysis) =
+        mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::latest()).unwrap();
+
+    let depth = |name: &str| analysis.call_depths.get(name).copied();
+    assert_eq!(depth("leaf"), Some(1));
+    // Arguments are evaluated above the frame of the function they are passed to
+    assert_eq!(depth("nested-args"), Some(4));
+    assert_eq!(depth("mapped"), Some(2));
+    assert_eq!(depth("higher-order"), Some(3));
+    assert_eq!(depth("with-let"), Some(4));
+    // `contract-call?` and the callee
+    assert_eq!(depth("dynamic"), Some(3));
+    assert_eq!(depth("callee"), None);
+}
+
+#[test]
+fn test_max_call_depth() {
+    let contract = call_chain(MAX_CALL_STACK_DEPTH);
+    let (_, analysis) =
+        mem_type_check(&contract, ClarityVersion::Clarity3, StacksEpochId::latest()).unwrap();
+    assert_eq!(
+        analysis.call_depths.get("f-64").copied(),
+        Some(MAX_CALL_STACK_DEPTH as u64)
+    );
+
+    let contract = call_chain(MAX_CALL_STACK_DEPTH + 1);
+    let err =
+        mem_type_check(&contract, ClarityVersion::Clarity3, StacksEpochId::latest()).unwrap_err();
+    assert_eq!(
+        err.err,
+        CheckErrors::MaxCallDepthExceeded("f-65".into(), 65)
+    );
+    assert!(err.diagnostic.message.contains("at least 65 nested calls"));
+}
+
+/// The interpreter agrees: the longest accepted chain runs, and one more call overflows the stack
+#[test]
+fn test_max_call_depth_at_runtime() {
+    let program = format!("{}\n(f-64)", call_chain(MAX_CALL_STACK_DEPTH));
+    assert_eq!(execute(&program).unwrap(), Some(Value::UInt(1)));
+
+    let program = format!("{}\n(f-65)", call_chain(MAX_CALL_STACK_DEPTH + 1));
+    assert_eq!(
+        execute(&program).unwrap_err(),
+        RuntimeErrorType::MaxStackDepthReached.into()
+    );
+}
+
+#[test]
+fn test_max_call_depth_warning_before_clarity3() {
+    let contract = call_chain(MAX_CALL_STACK_DEPTH + 2);
+    let (_, analysis) =
+        mem_type_check(&contract, ClarityVersion::Clarity2, StacksEpochId::latest()).unwrap();
+
+    let exceeded: Vec<_> = analysis
+        .warnings
+        .into_iter()
+        .filter_map(|warning| match warning.warning {
+            CheckWarnings::MaxCallDepthExceeded(name, depth) => Some((name, depth)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        exceeded,
+        vec![("f-65".to_string(), 65), ("f-66".to_string(), 66)]
+    );
+}
+
+/// Contracts are not checked for their call depth when they are deployed
+#[test]
+fn test_max_call_depth_not_checked_on_deploy() {
+    let contract_identifier = QualifiedContractIdentifier::local("deep").unwrap();
+    let mut contract = parse(
+        &contract_identifier,
+        &call_chain(MAX_CALL_STACK_DEPTH + 1),
+        ClarityVersion::Clarity3,
+        StacksEpochId::latest(),
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let analysis = run_analysis(
+        &contract_identifier,
+        &mut contract,
+        &mut marf.as_analysis_db(),
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::latest(),
+        ClarityVersion::Clarity3,
+    )
+    .unwrap();
+    assert!(analysis.call_depths.is_empty());
+    assert!(analysis.warnings.is_empty());
+}
@@ -50,6 +50,13 @@ pub fn build_contract_interface(
         type_map: _,
         cost_track: _,
         contract_interface: _,
+        warnings: _,
+        call_depths: _,
+        cost_bounds: _,
+        custom_pass_warnings: _,
+        function_purity: _,
+        write_call_chains: _,
+        dependencies: _,
         is_cost_contract_eligible: _,
     } = contract_analysis;
 
@@ -404,6 +411,231 @@ impl ContractInterface {
             CheckErrors::Expects("Failed to serialize contract interface".into()).into()
         })
     }
+
+    /// Serialize the contract interface in the given format
+    pub fn serialize_as(&self, format: ContractInterfaceFormat) -> CheckResult<String> {
+        match format {
+            ContractInterfaceFormat::JsonAbi => self.serialize(),
+            ContractInterfaceFormat::TypeScript => Ok(self.to_typescript()),
+            ContractInterfaceFormat::JsonSchema => serde_json::to_string(&self.to_json_schema())
+                .map_err(|_| {
+                    CheckErrors::Expects("Failed to serialize contract interface".into()).into()
+                }),
+        }
+    }
+
+    /// Functions that can be called from outside the contract, in the given access
+    fn callable_functions(
+        &self,
+        access: ContractInterfaceFunctionAccess,
+    ) -> impl Iterator<Item = &ContractInterfaceFunction> {
+        self.functions
+            .iter()
+            .filter(move |function| function.access == access)
+    }
+
+    /// TypeScript declarations for the public and read-only functions. Each function takes its
+    /// arguments as an object keyed by argument name, since Clarity names are not valid
+    /// TypeScript identifiers in general.
+    pub fn to_typescript(&self) -> String {
+        let mut output = String::new();
+        for (interface_name, access) in [
+            ("PublicFunctions", ContractInterfaceFunctionAccess::public),
+            (
+                "ReadOnlyFunctions",
+                ContractInterfaceFunctionAccess::read_only,
+            ),
+        ] {
+            output.push_str(&format!("export interface {} {{\n", interface_name));
+            for function in self.callable_functions(access) {
+                let args: Vec<_> = function
+                    .args
+                    .iter()
+                    .map(|arg| {
+                        format!(
+                            "{}: {}",
+                            typescript_string(&arg.name),
+                            arg.type_f.to_typescript()
+                        )
+                    })
+                    .collect();
+                output.push_str(&format!(
+                    "  {}: (args: {{ {} }}) => {};\n",
+                    typescript_string(&function.name),
+                    args.join("; "),
+                    function.outputs.type_f.to_typescript()
+                ));
+            }
+            output.push_str("}\n");
+        }
+        output
+    }
+
+    /// A JSON Schema document with a definition, under `$defs`, of the arguments object of each
+    /// public and read-only function
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        let definitions: serde_json::Map<_, _> = self
+            .callable_functions(ContractInterfaceFunctionAccess::public)
+            .chain(self.callable_functions(ContractInterfaceFunctionAccess::read_only))
+            .map(|function| {
+                let properties: serde_json::Map<_, _> = function
+                    .args
+                    .iter()
+                    .map(|arg| (arg.name.clone(), arg.type_f.to_json_schema()))
+                    .collect();
+                let required: Vec<_> = function.args.iter().map(|arg| arg.name.clone()).collect();
+                let schema = json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                });
+                (function.name.clone(), schema)
+            })
+            .collect();
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": definitions,
+        })
+    }
+}
+
+/// Output formats for a `ContractInterface`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContractInterfaceFormat {
+    /// The JSON ABI, i.e. the serde serialization of `ContractInterface`
+    JsonAbi,
+    /// TypeScript declarations of the public and read-only functions
+    TypeScript,
+    /// JSON Schema for the arguments of the public and read-only functions
+    JsonSchema,
+}
+
+/// Quote a Clarity name so it can be used as a TypeScript property name
+fn typescript_string(name: &str) -> String {
+    // a JSON string is a valid TypeScript string literal
+    serde_json::Value::from(name).to_string()
+}
+
+impl ContractInterfaceAtomType {
+    /// The TypeScript type of a Clarity value of this type. Integers are `bigint`s, buffers are
+    /// `Uint8Array`s, and principals and strings are `string`s.
+    pub fn to_typescript(&self) -> String {
+        match self {
+            ContractInterfaceAtomType::none => "null".into(),
+            ContractInterfaceAtomType::int128 | ContractInterfaceAtomType::uint128 => {
+                "bigint".into()
+            }
+            ContractInterfaceAtomType::bool => "boolean".into(),
+            ContractInterfaceAtomType::principal
+            | ContractInterfaceAtomType::trait_reference
+            | ContractInterfaceAtomType::string_ascii { .. }
+            | ContractInterfaceAtomType::string_utf8 { .. } => "string".into(),
+            ContractInterfaceAtomType::buffer { .. } => "Uint8Array".into(),
+            ContractInterfaceAtomType::tuple(entries) => {
+                let entries: Vec<_> = entries
+                    .iter()
+                    .map(|entry| {
+                        format!(
+                            "{}: {}",
+                            typescript_string(&entry.name),
+                            entry.type_f.to_typescript()
+                        )
+                    })
+                    .collect();
+                format!("{{ {} }}", entries.join("; "))
+            }
+            ContractInterfaceAtomType::optional(type_f) => {
+                format!("{} | null", type_f.to_typescript_operand())
+            }
+            ContractInterfaceAtomType::response { ok, error } => format!(
+                "{{ ok: true; value: {} }} | {{ ok: false; value: {} }}",
+                ok.to_typescript(),
+                error.to_typescript()
+            ),
+            ContractInterfaceAtomType::list { type_f, .. } => {
+                format!("{}[]", type_f.to_typescript_operand())
+            }
+        }
+    }
+
+    /// `to_typescript()`, parenthesized if it is a union
+    fn to_typescript_operand(&self) -> String {
+        match self {
+            ContractInterfaceAtomType::optional(_) | ContractInterfaceAtomType::response { .. } => {
+                format!("({})", self.to_typescript())
+            }
+            _ => self.to_typescript(),
+        }
+    }
+
+    /// The JSON Schema of the JSON representation of a Clarity value of this type. Integers are
+    /// decimal strings (they do not fit in a JSON number), buffers are hex strings, and responses
+    /// are objects with a single `ok` or `error` property.
+    pub fn to_json_schema(&self) -> serde_json::Value {
+        match self {
+            ContractInterfaceAtomType::none => json!({ "type": "null" }),
+            ContractInterfaceAtomType::int128 => json!({
+                "type": "string",
+                "pattern": "^-?[0-9]+$",
+            }),
+            ContractInterfaceAtomType::uint128 => json!({
+                "type": "string",
+                "pattern": "^[0-9]+$",
+            }),
+            ContractInterfaceAtomType::bool => json!({ "type": "boolean" }),
+            ContractInterfaceAtomType::principal | ContractInterfaceAtomType::trait_reference => {
+                json!({ "type": "string" })
+            }
+            ContractInterfaceAtomType::buffer { length } => json!({
+                "type": "string",
+                "pattern": "^([0-9a-fA-F]{2})*$",
+                "maxLength": 2 * u64::from(*length),
+            }),
+            ContractInterfaceAtomType::string_ascii { length }
+            | ContractInterfaceAtomType::string_utf8 { length } => json!({
+                "type": "string",
+                "maxLength": length,
+            }),
+            ContractInterfaceAtomType::tuple(entries) => {
+                let properties: serde_json::Map<_, _> = entries
+                    .iter()
+                    .map(|entry| (entry.name.clone(), entry.type_f.to_json_schema()))
+                    .collect();
+                let required: Vec<_> = entries.iter().map(|entry| entry.name.clone()).collect();
+                json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                    "additionalProperties": false,
+                })
+            }
+            ContractInterfaceAtomType::optional(type_f) => json!({
+                "anyOf": [{ "type": "null" }, type_f.to_json_schema()],
+            }),
+            ContractInterfaceAtomType::response { ok, error } => json!({
+                "oneOf": [
+                    {
+                        "type": "object",
+                        "properties": { "ok": ok.to_json_schema() },
+                        "required": ["ok"],
+                        "additionalProperties": false,
+                    },
+                    {
+                        "type": "object",
+                        "properties": { "error": error.to_json_schema() },
+                        "required": ["error"],
+                        "additionalProperties": false,
+                    },
+                ],
+            }),
+            ContractInterfaceAtomType::list { type_f, length } => json!({
+                "type": "array",
+                "items": type_f.to_json_schema(),
+                "maxItems": length,
+            }),
+        }
+    }
 }
 
 #[test]
@@ -429,3 +661,90 @@ fn test_string_rename_utf8() {
         "{\"name\":\"test-utf8\",\"type\":{\"string-utf8\":{\"length\":32}}}"
     );
 }
+
+#[cfg(test)]
+fn interface_of(contract: &str) -> ContractInterface {
+    let (_, analysis) = crate::vm::analysis::mem_type_check(
+        contract,
+        ClarityVersion::Clarity2,
+        StacksEpochId::latest(),
+    )
+    .unwrap();
+    build_contract_interface(&analysis).unwrap()
+}
+
+#[test]
+fn test_typescript_interface() {
+    let interface = interface_of(
+        "(define-private (helper) u1)
+        (define-read-only (get-owner (id uint)) (some tx-sender))
+        (define-public (set-info (info { name: (string-ascii 10), tags: (list 3 (buff 2)) }))
+          (ok (helper)))",
+    );
+    assert_eq!(
+        interface.serialize_as(ContractInterfaceFormat::TypeScript).unwrap(),
+        "export interface PublicFunctions {
+  \"set-info\": (args: { \"info\": { \"name\": string; \"tags\": Uint8Array[] } }) => { ok: true; value: bigint } | { ok: false; value: null };
+}
+export interface ReadOnlyFunctions {
+  \"get-owner\": (args: { \"id\": bigint }) => string | null;
+}
+"
+    );
+}
+
+#[test]
+fn test_json_schema_interface() {
+    let interface = interface_of(
+        "(define-private (helper) u1)
+        (define-read-only (get-owner (id uint) (owner (optional principal))) (ok (helper)))
+        (define-public (set-tags (tags (list 3 (buff 2)))) (ok true))",
+    );
+    let schema: serde_json::Value = serde_json::from_str(
+        &interface
+            .serialize_as(ContractInterfaceFormat::JsonSchema)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        schema,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": {
+                "get-owner": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string", "pattern": "^[0-9]+$" },
+                        "owner": { "anyOf": [{ "type": "null" }, { "type": "string" }] },
+                    },
+                    "required": ["id", "owner"],
+                    "additionalProperties": false,
+                },
+                "set-tags": {
+                    "type": "object",
+                    "properties": {
+                        "tags": {
+                            "type": "array",
+                            "items": {
+                                "type": "string",
+                                "pattern": "^([0-9a-fA-F]{2})*$",
+                                "maxLength": 4,
+                            },
+                            "maxItems": 3,
+                        },
+                    },
+                    "required": ["tags"],
+                    "additionalProperties": false,
+                },
+            },
+        })
+    );
+
+    // the JSON ABI is unchanged
+    assert_eq!(
+        interface
+            .serialize_as(ContractInterfaceFormat::JsonAbi)
+            .unwrap(),
+        interface.serialize().unwrap()
+    );
+}
@@ -0,0 +1,349 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+
+use stacks_common::types::StacksEpochId;
+
+use super::errors::CheckResult;
+use super::AnalysisDatabase;
+use crate::vm::analysis::type_checker::v2_1::natives::stx_delegation_info_type;
+use crate::vm::analysis::types::{AnalysisPass, ContractAnalysis};
+use crate::vm::callables::CallableType;
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::representations::SymbolicExpressionType::{AtomValue, List, LiteralValue};
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::types::signatures::{SequenceSubtype, StringSubtype};
+use crate::vm::types::{PrincipalData, TypeSignature, Value};
+use crate::vm::ClarityVersion;
+
+#[cfg(test)]
+mod tests;
+
+/// `CostBoundChecker` computes, for every function defined in the contract, an upper bound on the
+/// execution cost of calling it, and records it in `ContractAnalysis::cost_bounds`.
+///
+/// The bound charges every function application in the body with the contract's cost function,
+/// evaluated at the largest input the argument types allow, charges both branches of every
+/// conditional, and charges `map`, `filter`, and `fold` for the maximum length of their sequence
+/// arguments. Interpreter overhead that is not tied to a function application (such as variable
+/// lookups) is not counted.
+///
+/// A function has no bound if its bound overflows, or if it (transitively) uses a `contract-call?`
+/// whose callee's bound is not known: a dynamic `contract-call?`, or one to a contract whose
+/// bounds were not given to the `AnalysisDatabase` with `insert_cost_bounds`. This pass never
+/// fails, and it computes zero costs if the analysis is not tracking costs. It is only run by
+/// developer tools (see `AnalysisPassRegistry::tooling`).
+pub struct CostBoundChecker<'a, 'b> {
+    contract_analysis: &'a ContractAnalysis,
+    analysis_db: &'a AnalysisDatabase<'b>,
+    cost_tracker: &'a mut LimitedCostTracker,
+    /// Cost function results, by cost function and input size
+    costs: HashMap<(ClarityCostFunction, u64), Option<ExecutionCost>>,
+    /// Bounds of the functions checked so far, or `None` if a function has no bound
+    cost_bounds: BTreeMap<ClarityName, Option<ExecutionCost>>,
+}
+
+impl<'a, 'b> AnalysisPass for CostBoundChecker<'a, 'b> {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        let mut cost_tracker = contract_analysis.take_contract_cost_tracker();
+        let mut checker = CostBoundChecker::new(contract_analysis, analysis_db, &mut cost_tracker);
+        // Top-level expressions are sorted so that a function is defined before its callers
+        for expr in contract_analysis.expressions.iter() {
+            checker.check_top_level_expression(expr);
+        }
+        let cost_bounds = checker
+            .cost_bounds
+            .into_iter()
+            .filter_map(|(name, bound)| Some((name, bound?)))
+            .collect();
+        contract_analysis.cost_bounds = cost_bounds;
+        contract_analysis.replace_contract_cost_tracker(cost_tracker);
+        Ok(())
+    }
+}
+
+impl<'a, 'b> CostBoundChecker<'a, 'b> {
+    fn new(
+        contract_analysis: &'a ContractAnalysis,
+        analysis_db: &'a AnalysisDatabase<'b>,
+        cost_tracker: &'a mut LimitedCostTracker,
+    ) -> CostBoundChecker<'a, 'b> {
+        CostBoundChecker {
+            contract_analysis,
+            analysis_db,
+            cost_tracker,
+            costs: HashMap::new(),
+            cost_bounds: BTreeMap::new(),
+        }
+    }
+
+    fn check_top_level_expression(&mut self, expr: &SymbolicExpression) {
+        let (name, body) = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(DefineFunctionsParsed::PrivateFunction { signature, body }))
+            | Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, body }))
+            | Ok(Some(DefineFunctionsParsed::PublicFunction { signature, body })) => {
+                match signature.first().and_then(|name| name.match_atom()) {
+                    Some(name) => (name, body),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let bound = self.expression_cost(body);
+        self.cost_bounds.insert(name.clone(), bound);
+    }
+
+    /// Upper bound on the cost of evaluating `expr`, or `None` if it has no bound
+    fn expression_cost(&mut self, expr: &SymbolicExpression) -> Option<ExecutionCost> {
+        let List(exprs) = &expr.expr else {
+            return Some(ExecutionCost::zero());
+        };
+        let function_name = exprs.first().and_then(|expr| expr.match_atom());
+        let args = if function_name.is_some() {
+            &exprs[1..]
+        } else {
+            &exprs[..]
+        };
+        let mut cost = ExecutionCost::zero();
+        for arg in args.iter() {
+            cost.add(&self.expression_cost(arg)?).ok()?;
+        }
+        if let Some(function_name) = function_name {
+            cost.add(&self.application_cost(function_name, args)?)
+                .ok()?;
+        }
+        Some(cost)
+    }
+
+    /// Upper bound on the cost of applying the function `function_name` to `args`, not counting
+    /// the evaluation of the arguments themselves
+    fn application_cost(
+        &mut self,
+        function_name: &str,
+        args: &[SymbolicExpression],
+    ) -> Option<ExecutionCost> {
+        if let Some(bound) = self.cost_bounds.get(function_name) {
+            let mut cost = bound.clone()?;
+            cost.add(&self.cost(
+                ClarityCostFunction::UserFunctionApplication,
+                args.len() as u64,
+            )?)
+            .ok()?;
+            return Some(cost);
+        }
+        let version = self.contract_analysis.clarity_version;
+        let Some(native_function) =
+            NativeFunctions::lookup_by_name_at_version(function_name, &version)
+        else {
+            // not a function application, e.g. a `let` binding or a tuple entry
+            return Some(ExecutionCost::zero());
+        };
+        if let NativeFunctions::ContractCall = native_function {
+            return self.contract_call_cost(args);
+        }
+        let cost_function = native_cost_function(native_function, &version)?;
+        let mut input_size = self.input_size(args);
+        if let NativeFunctions::StxGetDelegationInfo = native_function {
+            // the entry it fetches from the PoX contract's `delegation-state` map
+            let entry_size = stx_delegation_info_type().ok()?.size().ok()?;
+            input_size = input_size.saturating_add(u64::from(entry_size));
+        }
+        let mut cost = self.cost(cost_function, input_size)?;
+        if let NativeFunctions::Map | NativeFunctions::Filter | NativeFunctions::Fold =
+            native_function
+        {
+            let (function, sequences) = args.split_first()?;
+            let mut iteration_cost = self.application_cost(function.match_atom()?, sequences)?;
+            iteration_cost
+                .multiply(self.max_sequence_length(sequences))
+                .ok()?;
+            cost.add(&iteration_cost).ok()?;
+        }
+        Some(cost)
+    }
+
+    /// Upper bound on the cost of a `contract-call?` to a function with a known bound
+    fn contract_call_cost(&mut self, args: &[SymbolicExpression]) -> Option<ExecutionCost> {
+        let (contract_identifier, function_name, function_args) = match args {
+            [callee, function_name, function_args @ ..] => match &callee.expr {
+                LiteralValue(Value::Principal(PrincipalData::Contract(contract_identifier)))
+                | AtomValue(Value::Principal(PrincipalData::Contract(contract_identifier))) => (
+                    contract_identifier,
+                    function_name.match_atom()?,
+                    function_args,
+                ),
+                // a trait reference: the callee is not known
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let mut cost = self
+            .analysis_db
+            .get_cost_bound(contract_identifier, function_name)?
+            .clone();
+        cost.add(&self.cost(
+            ClarityCostFunction::ContractCall,
+            self.input_size(function_args),
+        )?)
+        .ok()?;
+        Some(cost)
+    }
+
+    /// The largest input size a cost function could be evaluated at for these arguments: the
+    /// number of arguments, plus the sizes of their types, plus the sizes of any data var, map, or
+    /// NFT they name.
+    fn input_size(&self, args: &[SymbolicExpression]) -> u64 {
+        let analysis = self.contract_analysis;
+        let mut size = args.len() as u64;
+        for arg in args.iter() {
+            let arg_type = analysis
+                .type_map
+                .as_ref()
+                .and_then(|type_map| type_map.get_type(arg));
+            let storage_types = match arg.match_atom() {
+                Some(name) => match (
+                    analysis.persisted_variable_types.get(name),
+                    analysis.map_types.get(name),
+                    analysis.non_fungible_tokens.get(name),
+                ) {
+                    (Some(var_type), ..) => vec![var_type],
+                    (_, Some((key_type, value_type)), _) => vec![key_type, value_type],
+                    (.., Some(asset_type)) => vec![asset_type],
+                    _ => vec![],
+                },
+                None => vec![],
+            };
+            for type_signature in arg_type.into_iter().chain(storage_types) {
+                size = size.saturating_add(type_signature.size().map_or(0, u64::from));
+            }
+        }
+        size
+    }
+
+    /// The largest number of elements in any of these sequence arguments
+    fn max_sequence_length(&self, sequences: &[SymbolicExpression]) -> u64 {
+        let type_map = self.contract_analysis.type_map.as_ref();
+        sequences
+            .iter()
+            .filter_map(|sequence| type_map.and_then(|type_map| type_map.get_type(sequence)))
+            .map(|sequence_type| match sequence_type {
+                TypeSignature::SequenceType(SequenceSubtype::ListType(list_type)) => {
+                    list_type.get_max_len()
+                }
+                TypeSignature::SequenceType(SequenceSubtype::BufferType(len))
+                | TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                    len,
+                ))) => len.into(),
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(
+                    len,
+                ))) => len.into(),
+                _ => 0,
+            })
+            .max()
+            .map_or(0, u64::from)
+    }
+
+    fn cost(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        input_size: u64,
+    ) -> Option<ExecutionCost> {
+        let cost_tracker = &mut self.cost_tracker;
+        self.costs
+            .entry((cost_function, input_size))
+            .or_insert_with(|| cost_tracker.compute_cost(cost_function, &[input_size]).ok())
+            .clone()
+    }
+}
+
+/// The cost function charged when applying a native function, or `None` if it is not known here
+fn native_cost_function(
+    native_function: NativeFunctions,
+    version: &ClarityVersion,
+) -> Option<ClarityCostFunction> {
+    use crate::vm::functions::NativeFunctions::*;
+
+    match lookup_reserved_functions(native_function.get_name_str(), version)? {
+        CallableType::NativeFunction(_, _, cost_function)
+        | CallableType::NativeFunction205(_, _, cost_function, _) => return Some(cost_function),
+        _ => {}
+    }
+    let cost_function = match native_function {
+        CmpGeq => ClarityCostFunction::Geq,
+        CmpLeq => ClarityCostFunction::Leq,
+        CmpLess => ClarityCostFunction::Le,
+        CmpGreater => ClarityCostFunction::Ge,
+        And => ClarityCostFunction::And,
+        Or => ClarityCostFunction::Or,
+        If => ClarityCostFunction::If,
+        Let => ClarityCostFunction::Let,
+        FetchVar => ClarityCostFunction::FetchVar,
+        SetVar => ClarityCostFunction::SetVar,
+        Map => ClarityCostFunction::Map,
+        Filter => ClarityCostFunction::Filter,
+        Fold => ClarityCostFunction::Fold,
+        IsStandard => ClarityCostFunction::IsStandard,
+        PrincipalDestruct => ClarityCostFunction::PrincipalDestruct,
+        // `contract-principal-construct?` reuses the cost of `principal-construct?`
+        PrincipalConstruct | ContractPrincipalConstruct => ClarityCostFunction::PrincipalConstruct,
+        Concat | ConcatMany => ClarityCostFunction::Concat,
+        AsMaxLen => ClarityCostFunction::AsMaxLen,
+        Append => ClarityCostFunction::Append,
+        Slice => ClarityCostFunction::Slice,
+        ListCons => ClarityCostFunction::ListCons,
+        FetchEntry => ClarityCostFunction::FetchEntry,
+        SetEntry | InsertEntry | DeleteEntry => ClarityCostFunction::SetEntry,
+        TupleCons => ClarityCostFunction::TupleCons,
+        TupleGet => ClarityCostFunction::TupleGet,
+        Secp256k1Recover => ClarityCostFunction::Secp256k1recover,
+        Secp256k1Verify => ClarityCostFunction::Secp256k1verify,
+        Print => ClarityCostFunction::Print,
+        AsContract => ClarityCostFunction::AsContract,
+        ContractOf => ClarityCostFunction::ContractOf,
+        PrincipalOf => ClarityCostFunction::PrincipalOf,
+        GetBlockInfo => ClarityCostFunction::BlockInfo,
+        GetBurnBlockInfo => ClarityCostFunction::GetBurnBlockInfo,
+        Asserts => ClarityCostFunction::Asserts,
+        Match => ClarityCostFunction::Match,
+        MintAsset => ClarityCostFunction::NftMint,
+        MintToken => ClarityCostFunction::FtMint,
+        TransferAsset => ClarityCostFunction::NftTransfer,
+        TransferToken => ClarityCostFunction::FtTransfer,
+        GetTokenBalance => ClarityCostFunction::FtBalance,
+        GetAssetOwner => ClarityCostFunction::NftOwner,
+        BurnAsset => ClarityCostFunction::NftBurn,
+        BurnToken => ClarityCostFunction::FtBurn,
+        GetTokenSupply => ClarityCostFunction::FtSupply,
+        AtBlock => ClarityCostFunction::AtBlock,
+        GetStxBalance => ClarityCostFunction::StxBalance,
+        StxTransfer | StxBurn => ClarityCostFunction::StxTransfer,
+        StxTransferMemo => ClarityCostFunction::StxTransferMemo,
+        StxGetAccount | StxGetAccountInfo => ClarityCostFunction::StxGetAccount,
+        StxGetDelegationInfo => ClarityCostFunction::FetchEntry,
+        FromConsensusBuff => ClarityCostFunction::FromConsensusBuff,
+        ReplaceAt => ClarityCostFunction::ReplaceAt,
+        _ => return None,
+    };
+    Some(cost_function)
+}
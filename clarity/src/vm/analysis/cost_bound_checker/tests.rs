@@ -0,0 +1,42 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::mem_type_check;
+use crate::vm::costs::ExecutionCost;
+use crate::vm::ClarityVersion;
+
+#[test]
+fn test_cost_bounds_without_cost_tracking() {
+    let contract = "(define-trait callee ((foo () (response bool uint))))
+        (define-data-var count uint u0)
+        (define-private (inc (x uint)) (+ x u1))
+        (define-public (bump) (ok (var-set count (inc (var-get count)))))
+        (define-read-only (sum (xs (list 10 uint))) (fold + xs u0))
+        (define-public (dynamic (c <callee>)) (contract-call? c foo))
+        (define-public (calls-dynamic (c <callee>)) (dynamic c))";
+    let (_, analysis) =
+        mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::latest()).unwrap();
+
+    // `mem_type_check` does not track costs
+    for name in ["inc", "bump", "sum"] {
+        assert_eq!(analysis.cost_bounds.get(name), Some(&ExecutionCost::zero()));
+    }
+    // the cost of a `contract-call?` is not known statically
+    assert!(analysis.cost_bounds.get("dynamic").is_none());
+    assert!(analysis.cost_bounds.get("calls-dynamic").is_none());
+}
@@ -0,0 +1,542 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Analysis of a set of contracts that are deployed together, such as the contracts of a project,
+//! and estimates of what deploying a contract costs.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use stacks_common::types::StacksEpochId;
+
+use super::errors::{CheckError, CheckErrors};
+use super::types::{collect_dependencies, ContractAnalysis};
+use super::{run_analysis, run_analysis_with_passes, AnalysisDatabase, AnalysisPassRegistry};
+use crate::vm::ast::{build_ast_with_rules, ASTRules};
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::{runtime_cost, ExecutionCost, LimitedCostTracker};
+use crate::vm::database::{
+    ClarityBackingStore, ClarityDatabase, NULL_BURN_STATE_DB, NULL_HEADER_DB,
+};
+use crate::vm::diagnostic::Diagnostic;
+use crate::vm::representations::ClarityName;
+use crate::vm::types::{QualifiedContractIdentifier, TraitIdentifier};
+use crate::vm::ClarityVersion;
+
+/// A contract that conforms to a trait defined in the deployment set
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitConformance {
+    pub contract_identifier: QualifiedContractIdentifier,
+    pub trait_identifier: TraitIdentifier,
+    /// Whether the contract declares the trait with `impl-trait`
+    pub declared: bool,
+}
+
+/// The result of `analyze_deployment`
+#[derive(Debug)]
+pub struct DeploymentReport {
+    /// The contracts each contract of the set depends on, within the set
+    pub dependencies: BTreeMap<QualifiedContractIdentifier, BTreeSet<QualifiedContractIdentifier>>,
+    /// Groups of contracts that depend on each other in a cycle, and so cannot be deployed
+    pub dependency_cycles: Vec<BTreeSet<QualifiedContractIdentifier>>,
+    /// The contracts that passed analysis, in an order they can be deployed in
+    pub deployment_order: Vec<QualifiedContractIdentifier>,
+    pub analyses: BTreeMap<QualifiedContractIdentifier, ContractAnalysis>,
+    /// Why each contract that is not in a dependency cycle failed to parse or pass analysis
+    pub failures: BTreeMap<QualifiedContractIdentifier, Diagnostic>,
+    /// Every contract that conforms to a trait defined in the set, whether or not it declares it
+    pub trait_conformance: Vec<TraitConformance>,
+}
+
+impl DeploymentReport {
+    /// Whether every contract in the set can be deployed
+    pub fn is_deployable(&self) -> bool {
+        self.dependency_cycles.is_empty() && self.failures.is_empty()
+    }
+
+    /// Upper bound on the cost of calling each public and read-only function of the contracts that
+    /// passed analysis, including the cost of any `contract-call?`s to other contracts in the
+    /// set, or `None` if the function has no bound.
+    pub fn callable_function_costs(
+        &self,
+    ) -> BTreeMap<(QualifiedContractIdentifier, ClarityName), Option<ExecutionCost>> {
+        let mut costs = BTreeMap::new();
+        for (contract_identifier, analysis) in self.analyses.iter() {
+            let functions = analysis
+                .public_function_types
+                .keys()
+                .chain(analysis.read_only_function_types.keys());
+            for function_name in functions {
+                costs.insert(
+                    (contract_identifier.clone(), function_name.clone()),
+                    analysis.cost_bounds.get(function_name).cloned(),
+                );
+            }
+        }
+        costs
+    }
+}
+
+/// The cost of deploying a contract, by the stage of deployment that incurs it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeployCostEstimate {
+    /// Parsing the contract source
+    pub parse: ExecutionCost,
+    /// Running the analysis passes
+    pub analysis: ExecutionCost,
+    /// Storing the contract source
+    pub storage: ExecutionCost,
+    pub total: ExecutionCost,
+}
+
+impl DeployCostEstimate {
+    /// Whether a contract with this estimate can be deployed in a block with `limit`
+    pub fn fits_within(&self, limit: &ExecutionCost) -> bool {
+        !self.total.exceeds(limit)
+    }
+}
+
+/// Estimate the cost of deploying `source` without deploying it, as charged by `cost_tracker`.
+/// The contracts it depends on must already be in `store`, which is not modified. The estimate
+/// leaves out the cost of evaluating the contract's top-level expressions, such as the initial
+/// values of its data vars, so it is a lower bound on the cost of the deployment transaction.
+pub fn estimate_deploy_cost(
+    contract_identifier: &QualifiedContractIdentifier,
+    source: &str,
+    store: &mut dyn ClarityBackingStore,
+    mut cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> Result<DeployCostEstimate, Diagnostic> {
+    reset_cost(&mut cost_tracker);
+    let mut expressions = build_ast_with_rules(
+        contract_identifier,
+        source,
+        &mut cost_tracker,
+        version,
+        epoch,
+        ASTRules::PrecheckSize,
+    )
+    .map_err(|e| e.diagnostic)?
+    .expressions;
+    let parse = cost_tracker.get_total();
+
+    let mut analysis_db = AnalysisDatabase::new(store);
+    let mut analysis = run_analysis(
+        contract_identifier,
+        &mut expressions,
+        &mut analysis_db,
+        false,
+        cost_tracker,
+        epoch,
+        version,
+    )
+    .map_err(|(e, _)| e.diagnostic)?;
+    let mut cost_tracker = analysis.take_contract_cost_tracker();
+    let mut analysis_cost = cost_tracker.get_total();
+    analysis_cost
+        .sub(&parse)
+        .map_err(|e| CheckError::from(e).diagnostic)?;
+
+    runtime_cost(
+        ClarityCostFunction::ContractStorage,
+        &mut cost_tracker,
+        source.len(),
+    )
+    .map_err(|e| CheckError::from(e).diagnostic)?;
+    let total = cost_tracker.get_total();
+    let mut storage = total.clone();
+    storage
+        .sub(&parse)
+        .and_then(|_| storage.sub(&analysis_cost))
+        .map_err(|e| CheckError::from(e).diagnostic)?;
+
+    Ok(DeployCostEstimate {
+        parse,
+        analysis: analysis_cost,
+        storage,
+        total,
+    })
+}
+
+/// Analyze `contracts` together, deploying each one that passes analysis into `store` once the
+/// contracts it depends on have been deployed. Contracts may refer to each other, and to contracts
+/// already in `store`. The analysis of each contract is charged to `cost_tracker` separately,
+/// which also determines the costs in the bounds of `ContractAnalysis::cost_bounds`.
+pub fn analyze_deployment(
+    contracts: &[(QualifiedContractIdentifier, &str)],
+    store: &mut dyn ClarityBackingStore,
+    mut cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> DeploymentReport {
+    let mut report = DeploymentReport {
+        dependencies: BTreeMap::new(),
+        dependency_cycles: vec![],
+        deployment_order: vec![],
+        analyses: BTreeMap::new(),
+        failures: BTreeMap::new(),
+        trait_conformance: vec![],
+    };
+
+    let mut sources = BTreeMap::new();
+    for (contract_identifier, source) in contracts {
+        reset_cost(&mut cost_tracker);
+        match build_ast_with_rules(
+            contract_identifier,
+            source,
+            &mut cost_tracker,
+            version,
+            epoch,
+            ASTRules::PrecheckSize,
+        ) {
+            Ok(ast) => {
+                sources.insert(contract_identifier.clone(), (ast.expressions, *source));
+            }
+            Err(e) => {
+                report
+                    .failures
+                    .insert(contract_identifier.clone(), e.diagnostic);
+            }
+        }
+    }
+
+    for (contract_identifier, (expressions, _)) in sources.iter() {
+        let mut dependencies = BTreeSet::new();
+        for expr in expressions.iter() {
+            collect_dependencies(expr, &mut dependencies);
+        }
+        dependencies.remove(contract_identifier);
+        dependencies.retain(|dependency| sources.contains_key(dependency));
+        report
+            .dependencies
+            .insert(contract_identifier.clone(), dependencies);
+    }
+    report.dependency_cycles = find_cycles(&report.dependencies);
+
+    let mut remaining: BTreeSet<_> = sources.keys().cloned().collect();
+    for cycle in report.dependency_cycles.iter() {
+        for contract_identifier in cycle.iter() {
+            remaining.remove(contract_identifier);
+        }
+    }
+    // Analyze each contract once the contracts it depends on have been analyzed
+    loop {
+        let ready: Vec<_> = remaining
+            .iter()
+            .filter(|contract_identifier| {
+                report.dependencies[*contract_identifier]
+                    .iter()
+                    .all(|dependency| !remaining.contains(dependency))
+            })
+            .cloned()
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for contract_identifier in ready {
+            remaining.remove(&contract_identifier);
+            let (expressions, source) = sources
+                .remove(&contract_identifier)
+                .expect("BUG: contract source already taken");
+            reset_cost(&mut cost_tracker);
+            cost_tracker = match deploy_contract(
+                &contract_identifier,
+                expressions,
+                source,
+                &report.analyses,
+                store,
+                cost_tracker,
+                epoch,
+                version,
+            ) {
+                Ok(mut analysis) => {
+                    let cost_tracker = analysis.take_contract_cost_tracker();
+                    report.deployment_order.push(contract_identifier.clone());
+                    report.analyses.insert(contract_identifier, analysis);
+                    cost_tracker
+                }
+                Err((e, cost_tracker)) => {
+                    report.failures.insert(contract_identifier, e.diagnostic);
+                    cost_tracker
+                }
+            };
+        }
+    }
+    // What is left depends on a contract in a dependency cycle
+    for contract_identifier in remaining {
+        let dependency = report.dependencies[&contract_identifier]
+            .iter()
+            .find(|dependency| !report.analyses.contains_key(*dependency))
+            .map(|dependency| dependency.to_string())
+            .unwrap_or_default();
+        let error = CheckError::new(CheckErrors::NoSuchContract(dependency));
+        report
+            .failures
+            .insert(contract_identifier, error.diagnostic);
+    }
+
+    for (trait_contract, trait_analysis) in report.analyses.iter() {
+        for (trait_name, trait_definition) in trait_analysis.defined_traits.iter() {
+            let trait_identifier = TraitIdentifier {
+                name: trait_name.clone(),
+                contract_identifier: trait_contract.clone(),
+            };
+            for (contract_identifier, analysis) in report.analyses.iter() {
+                if analysis
+                    .check_trait_compliance(&epoch, &trait_identifier, trait_definition)
+                    .is_ok()
+                {
+                    report.trait_conformance.push(TraitConformance {
+                        contract_identifier: contract_identifier.clone(),
+                        trait_identifier: trait_identifier.clone(),
+                        declared: analysis.implemented_traits.contains(&trait_identifier),
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Charge each contract of the set separately against the tracker's limit
+fn reset_cost(cost_tracker: &mut LimitedCostTracker) {
+    if let LimitedCostTracker::Limited(_) = cost_tracker {
+        cost_tracker.set_total(ExecutionCost::zero());
+    }
+}
+
+/// Analyze a contract, including with the passes of `AnalysisPassRegistry::tooling`, and save it
+/// to `store`. The cost bounds of the contracts already analyzed are made available, so that the
+/// bounds of this contract include its `contract-call?`s to them.
+#[allow(clippy::too_many_arguments)]
+fn deploy_contract(
+    contract_identifier: &QualifiedContractIdentifier,
+    mut expressions: Vec<crate::vm::SymbolicExpression>,
+    source: &str,
+    analyses: &BTreeMap<QualifiedContractIdentifier, ContractAnalysis>,
+    store: &mut dyn ClarityBackingStore,
+    cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    let mut clarity_db = ClarityDatabase::new(store, &NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+    clarity_db.begin();
+    if let Err(e) = clarity_db
+        .insert_contract_hash(contract_identifier, source)
+        .and_then(|_| clarity_db.commit())
+    {
+        return Err((CheckErrors::Expects(format!("{e:?}")).into(), cost_tracker));
+    }
+
+    let mut analysis_db = AnalysisDatabase::new(store);
+    for (dependency, analysis) in analyses.iter() {
+        analysis_db.insert_cost_bounds(dependency, analysis.cost_bounds.clone());
+    }
+    run_analysis_with_passes(
+        contract_identifier,
+        &mut expressions,
+        &mut analysis_db,
+        true,
+        cost_tracker,
+        epoch,
+        version,
+        &AnalysisPassRegistry::tooling(),
+        false,
+    )
+}
+
+/// The groups of contracts that depend on each other in a cycle, i.e. the strongly connected
+/// components of the dependency graph with more than one contract
+fn find_cycles(
+    dependencies: &BTreeMap<QualifiedContractIdentifier, BTreeSet<QualifiedContractIdentifier>>,
+) -> Vec<BTreeSet<QualifiedContractIdentifier>> {
+    // the contracts each contract reaches through its dependencies
+    let mut reachable: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+    for contract_identifier in dependencies.keys() {
+        let mut reached = BTreeSet::new();
+        let mut frontier = vec![contract_identifier];
+        while let Some(contract) = frontier.pop() {
+            for dependency in dependencies[contract].iter() {
+                if reached.insert(dependency) {
+                    frontier.push(dependency);
+                }
+            }
+        }
+        reachable.insert(contract_identifier, reached);
+    }
+
+    let mut cycles: Vec<BTreeSet<QualifiedContractIdentifier>> = vec![];
+    for (contract_identifier, reached) in reachable.iter() {
+        if !reached.contains(contract_identifier)
+            || cycles
+                .iter()
+                .any(|cycle| cycle.contains(*contract_identifier))
+        {
+            continue;
+        }
+        let cycle = reached
+            .iter()
+            .filter(|other| reachable[**other].contains(contract_identifier))
+            .map(|other| (*other).clone())
+            .collect();
+        cycles.push(cycle);
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::analysis::errors::CheckErrors;
+    use crate::vm::database::MemoryBackingStore;
+
+    fn analyze(contracts: &[(&str, &str)]) -> DeploymentReport {
+        let contracts: Vec<_> = contracts
+            .iter()
+            .map(|(name, source)| (QualifiedContractIdentifier::local(name).unwrap(), *source))
+            .collect();
+        analyze_deployment(
+            &contracts,
+            &mut MemoryBackingStore::new(),
+            LimitedCostTracker::new_free(),
+            StacksEpochId::latest(),
+            ClarityVersion::Clarity2,
+        )
+    }
+
+    fn id(name: &str) -> QualifiedContractIdentifier {
+        QualifiedContractIdentifier::local(name).unwrap()
+    }
+
+    #[test]
+    fn test_estimate_deploy_cost() {
+        let mut store = MemoryBackingStore::new();
+        let estimate = estimate_deploy_cost(
+            &id("counter"),
+            "(define-data-var count uint u0)
+            (define-public (increment) (ok (var-set count (+ u1 (var-get count)))))",
+            &mut store,
+            LimitedCostTracker::new_free(),
+            StacksEpochId::latest(),
+            ClarityVersion::Clarity2,
+        )
+        .unwrap();
+        assert!(estimate.fits_within(&ExecutionCost::zero()));
+        // the contract is not deployed
+        assert!(!AnalysisDatabase::new(&mut store).has_contract(&id("counter")));
+
+        let diagnostic = estimate_deploy_cost(
+            &id("caller"),
+            "(define-public (call) (contract-call? .counter increment))",
+            &mut store,
+            LimitedCostTracker::new_free(),
+            StacksEpochId::latest(),
+            ClarityVersion::Clarity2,
+        )
+        .unwrap_err();
+        assert_eq!(diagnostic.code, Some("NoSuchContract".into()));
+    }
+
+    #[test]
+    fn test_analyze_deployment() {
+        let report = analyze(&[
+            (
+                "token",
+                "(impl-trait .traits.transferable)
+                (define-public (transfer (amount uint)) (ok amount))",
+            ),
+            (
+                "wallet",
+                "(define-public (pay (amount uint)) (contract-call? .token transfer amount))
+                (define-public (pay-any (token <transferable>) (amount uint))
+                  (contract-call? token transfer amount))
+                (use-trait transferable .traits.transferable)",
+            ),
+            (
+                "traits",
+                "(define-trait transferable ((transfer (uint) (response uint uint))))",
+            ),
+            (
+                "lookalike",
+                "(define-public (transfer (amount uint)) (ok u0))",
+            ),
+        ]);
+
+        assert!(report.is_deployable());
+        assert_eq!(
+            report.deployment_order,
+            vec![id("lookalike"), id("traits"), id("token"), id("wallet")]
+        );
+        assert_eq!(
+            report.dependencies[&id("wallet")],
+            BTreeSet::from([id("token"), id("traits")])
+        );
+
+        let transferable = TraitIdentifier {
+            name: "transferable".into(),
+            contract_identifier: id("traits"),
+        };
+        assert_eq!(
+            report.trait_conformance,
+            vec![
+                TraitConformance {
+                    contract_identifier: id("lookalike"),
+                    trait_identifier: transferable.clone(),
+                    declared: false,
+                },
+                TraitConformance {
+                    contract_identifier: id("token"),
+                    trait_identifier: transferable.clone(),
+                    declared: true,
+                },
+            ]
+        );
+
+        // the static `contract-call?` is bounded by the callee's bound, the dynamic one is not
+        let costs = report.callable_function_costs();
+        assert_eq!(
+            costs[&(id("wallet"), "pay".into())],
+            Some(ExecutionCost::zero())
+        );
+        assert_eq!(costs[&(id("wallet"), "pay-any".into())], None);
+    }
+
+    #[test]
+    fn test_analyze_deployment_with_cycle() {
+        let report = analyze(&[
+            ("a", "(define-public (f) (contract-call? .b g))"),
+            ("b", "(define-public (g) (contract-call? .a f))"),
+            ("c", "(define-public (h) (contract-call? .a f))"),
+            ("d", "(define-public (k) (ok true))"),
+        ]);
+
+        assert!(!report.is_deployable());
+        assert_eq!(
+            report.dependency_cycles,
+            vec![BTreeSet::from([id("a"), id("b")])]
+        );
+        assert_eq!(report.deployment_order, vec![id("d")]);
+        // `c` is not in the cycle, but cannot be deployed either
+        assert_eq!(
+            report.failures[&id("c")].message,
+            CheckError::new(CheckErrors::NoSuchContract(id("a").to_string()))
+                .diagnostic
+                .message
+        );
+        assert_eq!(report.failures.len(), 1);
+    }
+}
@@ -17,9 +17,10 @@
 use std::{error, fmt};
 
 use crate::vm::costs::{CostErrors, ExecutionCost};
-use crate::vm::diagnostic::{DiagnosableError, Diagnostic};
+use crate::vm::diagnostic::{DiagnosableError, Diagnostic, Level};
 use crate::vm::representations::SymbolicExpression;
 use crate::vm::types::{TraitIdentifier, TupleTypeSignature, TypeSignature, Value};
+use crate::vm::MAX_CALL_STACK_DEPTH;
 
 pub type CheckResult<T> = Result<T, CheckError>;
 
@@ -44,6 +45,7 @@ pub enum CheckErrors {
     BadMatchOptionSyntax(Box<CheckErrors>),
     BadMatchResponseSyntax(Box<CheckErrors>),
     BadMatchInput(TypeSignature),
+    NonExhaustiveMatch(String),
 
     // list typing errors
     UnknownListConstructionFailure,
@@ -151,6 +153,7 @@ pub enum CheckErrors {
     BadSyntaxExpectedListOfPairs,
 
     MaxContextDepthReached,
+    MaxCallDepthExceeded(String, u64),
     UndefinedFunction(String),
     UndefinedVariable(String),
 
@@ -188,6 +191,9 @@ pub enum CheckErrors {
     InvalidSecp65k1Signature,
 
     WriteAttemptedInReadOnly,
+    /// A read-only function writes, through the calls from it to the writing operation. Only
+    /// reported by developer tools, in place of `WriteAttemptedInReadOnly`.
+    ReadOnlyFunctionWrites(Vec<String>),
     AtBlockClosureMustBeReadOnly,
 }
 
@@ -252,6 +258,120 @@ impl fmt::Display for CheckError {
     }
 }
 
+/// Non-fatal issues found while analyzing a contract. These never cause a contract to be
+/// rejected; they are reported on `ContractAnalysis::warnings` for tooling to surface.
+#[derive(Debug, PartialEq, Clone)]
+pub enum CheckWarnings {
+    UnusedPrivateFunction(String),
+    UnusedConstant(String),
+    UnusedDataVar(String),
+    UnusedMap(String),
+    MaxCallDepthExceeded(String, u64),
+    /// Reported by a pass registered with an `AnalysisPassRegistry`
+    CustomPass(String),
+    /// The `none` or `err` arm of a `match` only panics (strict checks only)
+    PanickingMatchArm(String),
+    /// An `unwrap!` or `unwrap-err!` can never return early (strict checks only)
+    UnreachableUnwrapReturn(String),
+    /// A `match` is missing the named arm, in an epoch where that is still reported as an
+    /// argument count error
+    NonExhaustiveMatch(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CheckWarning {
+    pub warning: CheckWarnings,
+    pub expressions: Option<Vec<SymbolicExpression>>,
+    pub diagnostic: Diagnostic,
+}
+
+impl CheckWarning {
+    pub fn new(warning: CheckWarnings) -> CheckWarning {
+        let diagnostic = Diagnostic::new(&warning);
+        CheckWarning {
+            warning,
+            expressions: None,
+            diagnostic,
+        }
+    }
+
+    pub fn set_expression(&mut self, expr: &SymbolicExpression) {
+        self.diagnostic.spans = vec![expr.span().clone()];
+        self.expressions.replace(vec![expr.clone()]);
+    }
+}
+
+impl fmt::Display for CheckWarnings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl fmt::Display for CheckWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.warning)?;
+
+        if let Some(ref e) = self.expressions {
+            write!(f, "\nNear:\n{:?}", e)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DiagnosableError for CheckWarnings {
+    fn message(&self) -> String {
+        match &self {
+            CheckWarnings::UnusedPrivateFunction(name) => {
+                format!("private function '{}' is never called", name)
+            }
+            CheckWarnings::UnusedConstant(name) => format!("constant '{}' is never used", name),
+            CheckWarnings::UnusedDataVar(name) => format!("data var '{}' is never used", name),
+            CheckWarnings::UnusedMap(name) => format!("map '{}' is never used", name),
+            CheckWarnings::MaxCallDepthExceeded(name, depth) => {
+                CheckErrors::MaxCallDepthExceeded(name.clone(), *depth).message()
+            }
+            CheckWarnings::CustomPass(message) => message.clone(),
+            CheckWarnings::PanickingMatchArm(arm) => {
+                format!("the '{}' arm of this match always panics", arm)
+            }
+            CheckWarnings::UnreachableUnwrapReturn(function) => {
+                format!("the early return of this '{}' is unreachable", function)
+            }
+            CheckWarnings::NonExhaustiveMatch(variant) => {
+                CheckErrors::NonExhaustiveMatch(variant.clone()).message()
+            }
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        match &self {
+            CheckWarnings::MaxCallDepthExceeded(name, depth) => {
+                CheckErrors::MaxCallDepthExceeded(name.clone(), *depth).suggestion()
+            }
+            CheckWarnings::CustomPass(_) => None,
+            CheckWarnings::PanickingMatchArm(_) => {
+                Some("handle the error, or use unwrap-panic instead of match".into())
+            }
+            CheckWarnings::UnreachableUnwrapReturn(_) => {
+                Some("use unwrap-panic or unwrap-err-panic instead".into())
+            }
+            CheckWarnings::NonExhaustiveMatch(variant) => {
+                CheckErrors::NonExhaustiveMatch(variant.clone()).suggestion()
+            }
+            _ => Some("remove the definition, or reference it from another definition".into()),
+        }
+    }
+
+    fn level(&self) -> Level {
+        Level::Warning
+    }
+
+    fn code(&self) -> Option<String> {
+        Some(variant_name(self))
+    }
+}
+
 impl From<CostErrors> for CheckError {
     fn from(err: CostErrors) -> Self {
         CheckError::from(CheckErrors::from(err))
@@ -333,7 +453,20 @@ fn formatted_expected_types(expected_types: &Vec<TypeSignature>) -> String {
     expected_types_joined
 }
 
+/// The name of an error variant, taken from its `Debug` representation
+fn variant_name<T: fmt::Debug>(error: &T) -> String {
+    let repr = format!("{:?}", error);
+    repr.split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
 impl DiagnosableError for CheckErrors {
+    fn code(&self) -> Option<String> {
+        Some(variant_name(self))
+    }
+
     fn message(&self) -> String {
         match &self {
             CheckErrors::ExpectedLiteral => "expected a literal argument".into(),
@@ -347,6 +480,7 @@ impl DiagnosableError for CheckErrors {
                         source.message()),
             CheckErrors::BadMatchInput(t) =>
                 format!("match requires an input of either a response or optional, found input: '{}'", t),
+            CheckErrors::NonExhaustiveMatch(variant) => format!("match is not exhaustive: missing an arm for '{}'", variant),
             CheckErrors::TypeAnnotationExpectedFailure => "analysis expected type to already be annotated for expression".into(),
             CheckErrors::CostOverflow => "contract execution cost overflowed cost counter".into(),
             CheckErrors::CostBalanceExceeded(a, b) => format!("contract execution cost exceeded budget: {:?} > {:?}", a, b),
@@ -416,6 +550,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::CircularReference(references) => format!("detected circular reference: ({})", references.join(", ")),
             CheckErrors::BadSyntaxBinding => "invalid syntax binding".into(),
             CheckErrors::MaxContextDepthReached => "reached depth limit".into(),
+            CheckErrors::MaxCallDepthExceeded(name, depth) => format!("function '{}' makes at least {} nested calls, exceeding the maximum call stack depth of {}", name, depth, MAX_CALL_STACK_DEPTH),
             CheckErrors::UndefinedVariable(var_name) => format!("use of unresolved variable '{}'", var_name),
             CheckErrors::UndefinedFunction(var_name) => format!("use of unresolved function '{}'", var_name),
             CheckErrors::RequiresAtLeastArguments(expected, found) => format!("expecting >= {} arguments, got {}", expected, found),
@@ -429,6 +564,7 @@ impl DiagnosableError for CheckErrors {
             CheckErrors::UnknownFunction(function_name) => format!("use of unresolved function '{}'", function_name),
             CheckErrors::TraitBasedContractCallInReadOnly => "use of trait based contract calls are not allowed in read-only context".into(),
             CheckErrors::WriteAttemptedInReadOnly => "expecting read-only statements, detected a writing operation".into(),
+            CheckErrors::ReadOnlyFunctionWrites(call_chain) => format!("expecting read-only statements, detected a writing operation: {}", call_chain.join(" -> ")),
             CheckErrors::AtBlockClosureMustBeReadOnly => "(at-block ...) closures expect read-only statements, but detected a writing operation".into(),
             CheckErrors::BadTokenName => "expecting an token name as an argument".into(),
             CheckErrors::DefineFTBadSignature => "(define-token ...) expects a token name as an argument".into(),
@@ -471,9 +607,24 @@ impl DiagnosableError for CheckErrors {
                 "traits should be either defined, with define-trait, or imported, with use-trait."
                     .into(),
             ),
+            CheckErrors::NonExhaustiveMatch(variant) => match variant.as_str() {
+                "none" => Some(
+                    "optional match syntax: (match input some-name if-some-expression if-none-expression)".into(),
+                ),
+                _ => Some(
+                    "response match syntax: (match input ok-name if-ok-expression err-name if-err-expression)".into(),
+                ),
+            },
             CheckErrors::NoSuchBlockInfoProperty(_) => Some(
                 "properties available: time, header-hash, burnchain-header-hash, vrf-seed".into(),
             ),
+            CheckErrors::NoSuchDataVariable(_) => {
+                Some("persisted variables should be defined with define-data-var.".into())
+            }
+            CheckErrors::NoSuchMap(_) => Some("maps should be defined with define-map.".into()),
+            CheckErrors::MaxCallDepthExceeded(..) => {
+                Some("flatten the chain of calls made by this function, or split it across transactions.".into())
+            }
             _ => None,
         }
     }
@@ -16,21 +16,31 @@
 
 pub mod analysis_db;
 pub mod arithmetic_checker;
+pub mod call_depth_checker;
 pub mod contract_interface_builder;
+pub mod cost_bound_checker;
+pub mod deployment;
 #[allow(clippy::result_large_err)]
 pub mod errors;
+pub mod pass_registry;
 pub mod read_only_checker;
+pub mod sarif;
 pub mod trait_checker;
 pub mod type_checker;
 pub mod types;
+pub mod unused_checker;
+pub mod version_downgrade;
+
+use std::collections::BTreeMap;
 
 use stacks_common::types::StacksEpochId;
 
-pub use self::analysis_db::AnalysisDatabase;
+pub use self::analysis_db::{AnalysisArchive, AnalysisArchiveEntry, AnalysisDatabase};
 use self::arithmetic_checker::ArithmeticOnlyChecker;
 use self::contract_interface_builder::build_contract_interface;
-pub use self::errors::{CheckError, CheckErrors, CheckResult};
-use self::read_only_checker::ReadOnlyChecker;
+pub use self::errors::{CheckError, CheckErrors, CheckResult, CheckWarning, CheckWarnings};
+pub use self::pass_registry::AnalysisPassRegistry;
+use self::read_only_checker::{read_only_violation_call_chain, ReadOnlyChecker};
 use self::trait_checker::TraitChecker;
 use self::type_checker::v2_05::TypeChecker as TypeChecker2_05;
 use self::type_checker::v2_1::TypeChecker as TypeChecker2_1;
@@ -38,6 +48,7 @@ pub use self::types::{AnalysisPass, ContractAnalysis};
 use crate::vm::ast::{build_ast_with_rules, ASTRules};
 use crate::vm::costs::LimitedCostTracker;
 use crate::vm::database::{MemoryBackingStore, STORE_CONTRACT_SRC_INTERFACE};
+use crate::vm::diagnostic::{DiagnosableError, Diagnostic};
 use crate::vm::representations::SymbolicExpression;
 use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
 use crate::vm::ClarityVersion;
@@ -48,6 +59,54 @@ pub fn mem_type_check(
     version: ClarityVersion,
     epoch: StacksEpochId,
 ) -> CheckResult<(Option<TypeSignature>, ContractAnalysis)> {
+    mem_type_check_with_contracts(snippet, &[], version, epoch)
+}
+
+/// Used by CLI and test tools. Like `mem_type_check`, but first deploys each of `contracts`, in
+/// order, into the memory store, so that the snippet can call them and use their traits.
+/// Contracts created with `QualifiedContractIdentifier::local` can be referred to from the
+/// snippet with a leading `.`, e.g. `(contract-call? .foo bar)`. An error analyzing any of
+/// `contracts` is returned as is. The snippet is also checked by the passes of
+/// `AnalysisPassRegistry::tooling`. Not used in production
+pub fn mem_type_check_with_contracts(
+    snippet: &str,
+    contracts: &[(QualifiedContractIdentifier, &str)],
+    version: ClarityVersion,
+    epoch: StacksEpochId,
+) -> CheckResult<(Option<TypeSignature>, ContractAnalysis)> {
+    let mut marf = MemoryBackingStore::new();
+    for (contract_identifier, source) in contracts {
+        let mut contract = build_ast_with_rules(
+            contract_identifier,
+            source,
+            &mut (),
+            version,
+            epoch,
+            ASTRules::PrecheckSize,
+        )
+        .map_err(|_| CheckErrors::Expects(format!("Failed to build AST of {contract_identifier}")))?
+        .expressions;
+
+        let mut clarity_db = marf.as_clarity_db();
+        clarity_db.begin();
+        clarity_db
+            .insert_contract_hash(contract_identifier, source)
+            .and_then(|_| clarity_db.commit())
+            .map_err(|e| CheckErrors::Expects(format!("{e:?}")))?;
+
+        let mut analysis_db = marf.as_analysis_db();
+        run_analysis(
+            contract_identifier,
+            &mut contract,
+            &mut analysis_db,
+            true,
+            LimitedCostTracker::new_free(),
+            epoch,
+            version,
+        )
+        .map_err(|(e, _)| e)?;
+    }
+
     let contract_identifier = QualifiedContractIdentifier::transient();
     let mut contract = build_ast_with_rules(
         &contract_identifier,
@@ -60,10 +119,9 @@ pub fn mem_type_check(
     .map_err(|_| CheckErrors::Expects("Failed to build AST".into()))?
     .expressions;
 
-    let mut marf = MemoryBackingStore::new();
     let mut analysis_db = marf.as_analysis_db();
     let cost_tracker = LimitedCostTracker::new_free();
-    match run_analysis(
+    match run_analysis_with_passes(
         &QualifiedContractIdentifier::transient(),
         &mut contract,
         &mut analysis_db,
@@ -71,6 +129,8 @@ pub fn mem_type_check(
         cost_tracker,
         epoch,
         version,
+        &AnalysisPassRegistry::tooling(),
+        false,
     ) {
         Ok(x) => {
             // return the first type result of the type checker
@@ -90,6 +150,20 @@ pub fn mem_type_check(
     }
 }
 
+/// The contracts that `contract_analysis` depends on (see `ContractAnalysis::get_dependencies`),
+/// in order. These must be published before it. They are found by the type checker, and stored
+/// apart from the analysis, so for a contract loaded from the analysis database use
+/// `AnalysisDatabase::get_dependencies` instead.
+pub fn contract_dependencies(
+    contract_analysis: &ContractAnalysis,
+) -> Vec<QualifiedContractIdentifier> {
+    contract_analysis
+        .get_dependencies()
+        .iter()
+        .cloned()
+        .collect()
+}
+
 // Legacy function
 // The analysis is not just checking type.
 #[cfg(test)]
@@ -124,6 +198,217 @@ pub fn run_analysis(
     epoch: StacksEpochId,
     version: ClarityVersion,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    run_analysis_with_passes(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        save_contract,
+        cost_tracker,
+        epoch,
+        version,
+        &AnalysisPassRegistry::new(),
+        false,
+    )
+}
+
+/// Like `run_analysis`, but also runs the passes registered on `passes` after the built-in ones.
+/// If `strict_checks` is set, the type checker also warns about error handling that is probably
+/// a mistake, such as a `match` arm that panics on `err` (Clarity 2.1+ epochs only).
+#[allow(clippy::too_many_arguments)]
+pub fn run_analysis_with_passes(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    save_contract: bool,
+    cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+    passes: &AnalysisPassRegistry,
+    strict_checks: bool,
+) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    let (result, mut contract_analysis) = inner_run_analysis(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        save_contract,
+        cost_tracker,
+        epoch,
+        version,
+        passes,
+        strict_checks,
+    );
+    match result {
+        Ok(_) => Ok(contract_analysis),
+        Err(e) => Err((e, contract_analysis.take_contract_cost_tracker())),
+    }
+}
+
+/// Used by developer tools only. Runs the passes of `run_analysis` and of
+/// `AnalysisPassRegistry::tooling`, and also returns every diagnostic they produced: the fatal
+/// `CheckError` first (if the analysis failed), followed by any warnings. A read-only function
+/// that writes is reported with the calls that lead to the write.
+pub fn run_analysis_with_diagnostics(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    save_contract: bool,
+    cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> (
+    Result<ContractAnalysis, (CheckError, LimitedCostTracker)>,
+    Vec<Diagnostic>,
+) {
+    let (result, mut contract_analysis) = inner_run_analysis(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        save_contract,
+        cost_tracker,
+        epoch,
+        version,
+        &AnalysisPassRegistry::tooling(),
+        false,
+    );
+    let mut diagnostics: Vec<_> = contract_analysis
+        .warnings
+        .iter()
+        .map(|warning| warning.diagnostic.clone())
+        .collect();
+    match result {
+        Ok(_) => (Ok(contract_analysis), diagnostics),
+        Err(e) => {
+            let mut diagnostic = e.diagnostic.clone();
+            if let CheckErrors::WriteAttemptedInReadOnly = e.err {
+                if let Some(call_chain) = read_only_violation_call_chain(&contract_analysis) {
+                    let error = CheckErrors::ReadOnlyFunctionWrites(call_chain.clone());
+                    diagnostic.message = error.message();
+                    diagnostic.code = error.code();
+                }
+            }
+            diagnostics.insert(0, diagnostic);
+            (
+                Err((e, contract_analysis.take_contract_cost_tracker())),
+                diagnostics,
+            )
+        }
+    }
+}
+
+/// The outcome of `run_analysis_incremental`
+pub struct IncrementalAnalysis {
+    /// The analysis of the changed contract
+    pub analysis: ContractAnalysis,
+    /// The dependents that were re-analyzed against the change, in dependency order
+    pub reanalyzed: Vec<QualifiedContractIdentifier>,
+    /// The re-analyzed dependents that no longer pass analysis
+    pub failed_dependents: Vec<(QualifiedContractIdentifier, CheckError)>,
+}
+
+/// Used by developer tools only. Analyzes a new version of an already-saved contract, and then
+/// re-analyzes only the contracts that depend on it, according to the dependency graph recorded
+/// on `analysis_db`, reporting which of them the change breaks.
+///
+/// Each dependent is re-analyzed from its entry in `dependent_expressions`, in the epoch and
+/// Clarity version it was saved with. Dependents without an entry are skipped.
+///
+/// The backing store cannot overwrite saved analyses, so the database is left unchanged; only
+/// the recorded dependencies of the changed contract are updated.
+pub fn run_analysis_incremental(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    dependent_expressions: &mut BTreeMap<QualifiedContractIdentifier, Vec<SymbolicExpression>>,
+    analysis_db: &mut AnalysisDatabase,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> CheckResult<IncrementalAnalysis> {
+    analysis_db.begin();
+    let result = inner_run_analysis_incremental(
+        contract_identifier,
+        expressions,
+        dependent_expressions,
+        analysis_db,
+        epoch,
+        version,
+    );
+    analysis_db.roll_back()?;
+    let incremental = result?;
+    analysis_db.insert_dependencies(
+        contract_identifier,
+        incremental.analysis.get_dependencies().clone(),
+    );
+    Ok(incremental)
+}
+
+fn inner_run_analysis_incremental(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    dependent_expressions: &mut BTreeMap<QualifiedContractIdentifier, Vec<SymbolicExpression>>,
+    analysis_db: &mut AnalysisDatabase,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+) -> CheckResult<IncrementalAnalysis> {
+    let analysis = run_analysis(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        epoch,
+        version,
+    )
+    .map_err(|(e, _)| e)?;
+    // Dependents are checked against the new version of the contract
+    analysis_db.replace_contract(contract_identifier, &analysis)?;
+
+    let mut reanalyzed = vec![];
+    let mut failed_dependents = vec![];
+    for dependent in analysis_db.get_dependents(contract_identifier)? {
+        let Some(dependent_expressions) = dependent_expressions.get_mut(&dependent) else {
+            continue;
+        };
+        let (dependent_epoch, dependent_version) =
+            match analysis_db.load_contract_non_canonical(&dependent)? {
+                Some(saved) => (saved.epoch, saved.clarity_version),
+                None => (epoch, version),
+            };
+        let result = run_analysis(
+            &dependent,
+            dependent_expressions,
+            analysis_db,
+            false,
+            LimitedCostTracker::new_free(),
+            dependent_epoch,
+            dependent_version,
+        );
+        match result {
+            // Transitive dependents are checked against the re-analyzed contract
+            Ok(dependent_analysis) => {
+                analysis_db.replace_contract(&dependent, &dependent_analysis)?
+            }
+            Err((e, _)) => failed_dependents.push((dependent.clone(), e)),
+        }
+        reanalyzed.push(dependent);
+    }
+    Ok(IncrementalAnalysis {
+        analysis,
+        reanalyzed,
+        failed_dependents,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn inner_run_analysis(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    save_contract: bool,
+    cost_tracker: LimitedCostTracker,
+    epoch: StacksEpochId,
+    version: ClarityVersion,
+    passes: &AnalysisPassRegistry,
+    strict_checks: bool,
+) -> (CheckResult<()>, ContractAnalysis) {
     let mut contract_analysis = ContractAnalysis::new(
         contract_identifier.clone(),
         expressions.to_vec(),
@@ -140,9 +425,12 @@ pub fn run_analysis(
             StacksEpochId::Epoch21
             | StacksEpochId::Epoch22
             | StacksEpochId::Epoch23
-            | StacksEpochId::Epoch24 => {
-                TypeChecker2_1::run_pass(&epoch, &mut contract_analysis, db)
-            }
+            | StacksEpochId::Epoch24 => TypeChecker2_1::run_pass_with_strict_checks(
+                &epoch,
+                &mut contract_analysis,
+                db,
+                strict_checks,
+            ),
             StacksEpochId::Epoch10 => {
                 return Err(CheckErrors::Expects(
                     "Epoch 1.0 is not a valid epoch for analysis".into(),
@@ -152,6 +440,7 @@ pub fn run_analysis(
         }?;
         TraitChecker::run_pass(&epoch, &mut contract_analysis, db)?;
         ArithmeticOnlyChecker::check_contract_cost_eligible(&mut contract_analysis);
+        passes.run_passes(&epoch, &mut contract_analysis, db)?;
 
         if STORE_CONTRACT_SRC_INTERFACE {
             let interface = build_contract_interface(&contract_analysis)?;
@@ -162,10 +451,7 @@ pub fn run_analysis(
         }
         Ok(())
     });
-    match result {
-        Ok(_) => Ok(contract_analysis),
-        Err(e) => Err((e, contract_analysis.take_contract_cost_tracker())),
-    }
+    (result, contract_analysis)
 }
 
 #[cfg(test)]
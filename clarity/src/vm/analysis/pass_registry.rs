@@ -0,0 +1,94 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use super::call_depth_checker::CallDepthChecker;
+use super::cost_bound_checker::CostBoundChecker;
+use super::errors::CheckResult;
+use super::types::{AnalysisPass, ContractAnalysis};
+use super::unused_checker::UnusedDefinitionChecker;
+use super::AnalysisDatabase;
+
+type RunPass = fn(&StacksEpochId, &mut ContractAnalysis, &mut AnalysisDatabase) -> CheckResult<()>;
+
+/// Additional passes for `run_analysis_with_passes` to run after the built-in ones, such as
+/// linters or security checkers. Passes run in the order they were registered, and a pass that
+/// returns an error fails the analysis, just like a built-in pass. Passes report non-fatal
+/// findings by pushing `CheckWarnings::CustomPass` warnings onto `ContractAnalysis::warnings`.
+#[derive(Debug, Clone, Default)]
+pub struct AnalysisPassRegistry {
+    passes: Vec<(String, RunPass)>,
+}
+
+impl AnalysisPassRegistry {
+    pub fn new() -> AnalysisPassRegistry {
+        AnalysisPassRegistry::default()
+    }
+
+    /// The passes that developer tools run on top of the built-in ones. They are never run when
+    /// a contract is deployed, so they can be changed without changing which contracts the
+    /// network accepts.
+    pub fn tooling() -> AnalysisPassRegistry {
+        let mut passes = AnalysisPassRegistry::new();
+        passes.register::<UnusedDefinitionChecker>("unused-definitions");
+        passes.register::<CallDepthChecker>("call-depth");
+        passes.register::<CostBoundChecker>("cost-bounds");
+        passes
+    }
+
+    /// Register the pass `P` under `name`. Registering a name again replaces its pass, which
+    /// keeps its place in the order.
+    pub fn register<P: AnalysisPass>(&mut self, name: &str) -> &mut AnalysisPassRegistry {
+        match self
+            .passes
+            .iter_mut()
+            .find(|(pass_name, _)| pass_name == name)
+        {
+            Some((_, run_pass)) => *run_pass = P::run_pass,
+            None => self.passes.push((name.to_string(), P::run_pass)),
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// The names of the registered passes, in the order they run
+    pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Run each registered pass, recording the warnings it reports on
+    /// `ContractAnalysis::custom_pass_warnings`
+    pub(crate) fn run_passes(
+        &self,
+        epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        for (name, run_pass) in self.passes.iter() {
+            let num_warnings = contract_analysis.warnings.len();
+            run_pass(epoch, contract_analysis, analysis_db)?;
+            let warnings = contract_analysis.warnings[num_warnings..].to_vec();
+            contract_analysis
+                .custom_pass_warnings
+                .insert(name.clone(), warnings);
+        }
+        Ok(())
+    }
+}
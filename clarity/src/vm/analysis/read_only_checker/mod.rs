@@ -47,6 +47,10 @@ mod tests;
 pub struct ReadOnlyChecker<'a, 'b> {
     db: &'a mut AnalysisDatabase<'b>,
     defined_functions: HashMap<ClarityName, bool>,
+    /// For each defined function that writes, the calls that lead to its first writing operation
+    write_call_chains: HashMap<ClarityName, Vec<String>>,
+    /// The calls that lead to the first writing operation of the function being checked
+    current_write_chain: Option<Vec<String>>,
     epoch: StacksEpochId,
     clarity_version: ClarityVersion,
 }
@@ -59,11 +63,34 @@ impl<'a, 'b> AnalysisPass for ReadOnlyChecker<'a, 'b> {
     ) -> CheckResult<()> {
         let mut command =
             ReadOnlyChecker::new(analysis_db, epoch, &contract_analysis.clarity_version);
-        command.run(contract_analysis)?;
-        Ok(())
+        let result = command.run(contract_analysis);
+        contract_analysis.function_purity = command.defined_functions.into_iter().collect();
+        // also kept when the check fails, for `read_only_violation_call_chain`
+        contract_analysis.write_call_chains = command.write_call_chains.into_iter().collect();
+        result
     }
 }
 
+/// The calls that lead the read-only function that failed the check of `contract_analysis` to
+/// its first writing operation. Only developer tools report these, as
+/// `CheckErrors::ReadOnlyFunctionWrites`: the analysis itself still fails with
+/// `CheckErrors::WriteAttemptedInReadOnly`, whose message is part of the receipts of mined
+/// transactions.
+pub fn read_only_violation_call_chain(
+    contract_analysis: &ContractAnalysis,
+) -> Option<&Vec<String>> {
+    // The check stops at the first read-only function that writes
+    contract_analysis.expressions.iter().find_map(|expr| {
+        match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(DefineFunctionsParsed::ReadOnlyFunction { signature, .. })) => {
+                let function_name = signature.first()?.match_atom()?;
+                contract_analysis.write_call_chains.get(function_name)
+            }
+            _ => None,
+        }
+    })
+}
+
 impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
     fn new(
         db: &'a mut AnalysisDatabase<'b>,
@@ -73,6 +100,8 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
         Self {
             db,
             defined_functions: HashMap::new(),
+            write_call_chains: HashMap::new(),
+            current_write_chain: None,
             epoch: *epoch,
             clarity_version: *version,
         }
@@ -174,11 +203,25 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             .match_atom()
             .ok_or(CheckErrors::BadFunctionName)?;
 
+        self.current_write_chain = None;
         let is_read_only = self.check_read_only(body)?;
+        if let Some(mut call_chain) = self.current_write_chain.take() {
+            call_chain.insert(0, function_name.to_string());
+            self.write_call_chains
+                .insert(function_name.clone(), call_chain);
+        }
 
         Ok((function_name.clone(), is_read_only))
     }
 
+    /// Record `call_chain` as the way the function being checked writes, unless it is already
+    /// known to write
+    fn note_write(&mut self, call_chain: Vec<String>) {
+        if self.current_write_chain.is_none() {
+            self.current_write_chain = Some(call_chain);
+        }
+    }
+
     fn check_reads_only_valid(&mut self, expr: &SymbolicExpression) -> CheckResult<()> {
         use crate::vm::functions::define::DefineFunctionsParsed::*;
         if let Some(define_type) = DefineFunctionsParsed::try_parse(expr)? {
@@ -282,19 +325,100 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
         use crate::vm::functions::NativeFunctions::*;
 
         match function {
-            Add | Subtract | Divide | Multiply | CmpGeq | CmpLeq | CmpLess | CmpGreater
-            | Modulo | Power | Sqrti | Log2 | BitwiseXor | And | Or | Not | Hash160 | Sha256
-            | Keccak256 | Equals | If | Sha512 | Sha512Trunc256 | Secp256k1Recover
-            | Secp256k1Verify | ConsSome | ConsOkay | ConsError | DefaultTo | UnwrapRet
-            | UnwrapErrRet | IsOkay | IsNone | Asserts | Unwrap | UnwrapErr | Match | IsErr
-            | IsSome | TryRet | ToUInt | ToInt | BuffToIntLe | BuffToUIntLe | BuffToIntBe
-            | BuffToUIntBe | IntToAscii | IntToUtf8 | StringToInt | StringToUInt | IsStandard
-            | ToConsensusBuff | PrincipalDestruct | PrincipalConstruct | Append | Concat
-            | AsMaxLen | ContractOf | PrincipalOf | ListCons | GetBlockInfo | GetBurnBlockInfo
-            | TupleGet | TupleMerge | Len | Print | AsContract | Begin | FetchVar
-            | GetStxBalance | StxGetAccount | GetTokenBalance | GetAssetOwner | GetTokenSupply
-            | ElementAt | IndexOf | Slice | ReplaceAt | BitwiseAnd | BitwiseOr | BitwiseNot
-            | BitwiseLShift | BitwiseRShift | BitwiseXor2 | ElementAtAlias | IndexOfAlias => {
+            Add
+            | Subtract
+            | Divide
+            | Multiply
+            | CmpGeq
+            | CmpLeq
+            | CmpLess
+            | CmpGreater
+            | Modulo
+            | Power
+            | Sqrti
+            | Log2
+            | BitwiseXor
+            | And
+            | Or
+            | Not
+            | Hash160
+            | Sha256
+            | Keccak256
+            | Equals
+            | If
+            | Sha512
+            | Sha512Trunc256
+            | Secp256k1Recover
+            | Secp256k1Verify
+            | ConsSome
+            | ConsOkay
+            | ConsError
+            | DefaultTo
+            | UnwrapRet
+            | UnwrapErrRet
+            | IsOkay
+            | IsNone
+            | Asserts
+            | Unwrap
+            | UnwrapErr
+            | Match
+            | IsErr
+            | IsSome
+            | TryRet
+            | ToUInt
+            | ToInt
+            | BuffToIntLe
+            | BuffToUIntLe
+            | BuffToIntBe
+            | BuffToUIntBe
+            | IntToAscii
+            | IntToUtf8
+            | StringToInt
+            | StringToUInt
+            | IsStandard
+            | ToConsensusBuff
+            | PrincipalDestruct
+            | PrincipalConstruct
+            | ContractPrincipalConstruct
+            | Append
+            | Concat
+            | AsMaxLen
+            | ContractOf
+            | PrincipalOf
+            | ListCons
+            | GetBlockInfo
+            | GetBurnBlockInfo
+            | TupleGet
+            | TupleMerge
+            | Len
+            | Print
+            | AsContract
+            | Begin
+            | FetchVar
+            | GetStxBalance
+            | StxGetAccount
+            | StxGetAccountInfo
+            | StxGetDelegationInfo
+            | GetTokenBalance
+            | GetAssetOwner
+            | GetTokenSupply
+            | ElementAt
+            | IndexOf
+            | Slice
+            | ReplaceAt
+            | BitwiseAnd
+            | BitwiseOr
+            | BitwiseNot
+            | BitwiseLShift
+            | BitwiseRShift
+            | BitwiseLRotate
+            | BitwiseRRotate
+            | Log2UInt
+            | UIntToAscii
+            | ConcatMany
+            | BitwiseXor2
+            | ElementAtAlias
+            | IndexOfAlias => {
                 // Check all arguments.
                 self.check_each_expression_is_read_only(args)
             }
@@ -321,6 +445,7 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
             | SetVar | MintAsset | MintToken | TransferAsset | TransferToken | BurnAsset
             | BurnToken => {
                 self.check_each_expression_is_read_only(args)?;
+                self.note_write(vec![function.get_name()]);
                 Ok(false)
             }
             Let => {
@@ -388,25 +513,30 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
                     .match_atom()
                     .ok_or(CheckErrors::ContractCallExpectName)?;
 
-                let is_function_read_only = match &args[0].expr {
+                let (is_function_read_only, callee) = match &args[0].expr {
                     SymbolicExpressionType::LiteralValue(Value::Principal(
                         PrincipalData::Contract(ref contract_identifier),
-                    )) => self
-                        .db
-                        .get_read_only_function_type(
-                            contract_identifier,
-                            function_name,
-                            &self.epoch,
-                        )?
-                        .is_some(),
-                    SymbolicExpressionType::Atom(_trait_reference) => {
+                    )) => (
+                        self.db
+                            .get_read_only_function_type(
+                                contract_identifier,
+                                function_name,
+                                &self.epoch,
+                            )?
+                            .is_some(),
+                        contract_identifier.to_string(),
+                    ),
+                    SymbolicExpressionType::Atom(trait_reference) => {
                         // Dynamic dispatch from a readonly-function can only be guaranteed at runtime,
                         // which would defeat granting a static readonly stamp.
                         // As such dynamic dispatch is currently forbidden.
-                        false
+                        (false, trait_reference.to_string())
                     }
                     _ => return Err(CheckError::new(CheckErrors::ContractCallExpectName)),
                 };
+                if !is_function_read_only {
+                    self.note_write(vec![format!("{}.{}", callee, function_name)]);
+                }
 
                 self.check_each_expression_is_read_only(&args[2..])
                     .map(|args_read_only| args_read_only && is_function_read_only)
@@ -446,6 +576,9 @@ impl<'a, 'b> ReadOnlyChecker<'a, 'b> {
                 .defined_functions
                 .get(function_name)
                 .ok_or(CheckErrors::UnknownFunction(function_name.to_string()))?;
+            if let Some(call_chain) = self.write_call_chains.get(function_name) {
+                self.note_write(call_chain.clone());
+            }
             self.check_each_expression_is_read_only(args)
                 .map(|args_read_only| args_read_only && is_function_read_only)
         }
@@ -21,8 +21,9 @@ use rstest_reuse::{self, *};
 use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::type_checker::v2_1::tests::mem_type_check;
-use crate::vm::analysis::{type_check, CheckError, CheckErrors};
+use crate::vm::analysis::{run_analysis_with_diagnostics, type_check, CheckError, CheckErrors};
 use crate::vm::ast::parse;
+use crate::vm::costs::LimitedCostTracker;
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::tests::test_clarity_versions;
 use crate::vm::types::QualifiedContractIdentifier;
@@ -247,3 +248,58 @@ fn test_contract_call_read_only_violations(
     })
     .unwrap();
 }
+
+#[test]
+fn test_read_only_violation_call_chain() {
+    let contract = "(define-map tokens { account: principal } { balance: int })
+         (define-private (set-balance) (map-set tokens (tuple (account tx-sender)) (tuple (balance 10))))
+         (define-private (reset) (begin (set-balance) true))
+         (define-read-only (get-balance) (begin (reset) (map-get? tokens (tuple (account tx-sender)))))";
+    // the error is unchanged, since its message is part of the receipts of mined transactions
+    let err = mem_type_check(contract).unwrap_err();
+    assert_eq!(err.err, CheckErrors::WriteAttemptedInReadOnly);
+
+    // developer tools report the call chain instead
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let mut expressions = parse(
+        &contract_identifier,
+        contract,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let (result, diagnostics) = run_analysis_with_diagnostics(
+        &contract_identifier,
+        &mut expressions,
+        &mut marf.as_analysis_db(),
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+    );
+    let (err, _) = result.unwrap_err();
+    assert_eq!(err.err, CheckErrors::WriteAttemptedInReadOnly);
+    assert_eq!(diagnostics[0].code, Some("ReadOnlyFunctionWrites".into()));
+    assert!(diagnostics[0]
+        .message
+        .contains("get-balance -> reset -> set-balance -> map-set"));
+
+    let contract = "(define-map tokens { account: principal } { balance: int })
+         (define-private (set-balance) (map-set tokens (tuple (account tx-sender)) (tuple (balance 10))))
+         (define-public (reset) (ok (set-balance)))
+         (define-read-only (get-balance) (map-get? tokens (tuple (account tx-sender))))";
+    let (_, analysis) = mem_type_check(contract).unwrap();
+    assert_eq!(
+        analysis.function_purity.into_iter().collect::<Vec<_>>(),
+        vec![
+            ("get-balance".into(), true),
+            ("reset".into(), false),
+            ("set-balance".into(), false)
+        ]
+    );
+    assert_eq!(
+        analysis.write_call_chains["reset"],
+        vec!["reset".to_string(), "set-balance".into(), "map-set".into()]
+    );
+}
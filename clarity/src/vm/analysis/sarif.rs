@@ -0,0 +1,289 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Analysis diagnostics as a SARIF 2.1.0 log, the format read by code scanning tools such as
+//! GitHub code scanning.
+
+use std::collections::BTreeSet;
+
+use crate::vm::diagnostic::{Diagnostic, Level};
+use crate::vm::representations::Span;
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: String,
+    pub version: String,
+    pub runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifRun {
+    pub tool: SarifTool,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifTool {
+    pub driver: SarifDriver,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifDriver {
+    pub name: String,
+    pub version: String,
+    pub information_uri: String,
+    pub rules: Vec<SarifRule>,
+}
+
+/// A kind of diagnostic, named by its diagnostic code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifRule {
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<String>,
+    pub level: String,
+    pub message: SarifMessage,
+    pub locations: Vec<SarifLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifMessage {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifLocation {
+    pub physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifPhysicalLocation {
+    pub artifact_location: SarifArtifactLocation,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SarifArtifactLocation {
+    pub uri: String,
+}
+
+/// A range of source text. Lines and columns are 1-based, and `end_column` is the column just
+/// after the range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifRegion {
+    pub start_line: u32,
+    pub start_column: u32,
+    pub end_line: u32,
+    pub end_column: u32,
+}
+
+impl SarifRegion {
+    /// The region of a span, or `None` if the span has no location
+    pub fn from_span(span: &Span) -> Option<SarifRegion> {
+        if span.start_line == 0 {
+            return None;
+        }
+        Some(SarifRegion {
+            start_line: span.start_line,
+            start_column: span.start_column.max(1),
+            end_line: span.end_line.max(span.start_line),
+            // Clarity spans include their last column
+            end_column: span.end_column.max(span.start_column.max(1)) + 1,
+        })
+    }
+}
+
+fn sarif_level(level: &Level) -> &'static str {
+    match level {
+        Level::Error => "error",
+        Level::Warning => "warning",
+        Level::Note => "note",
+    }
+}
+
+impl SarifResult {
+    /// The result for a diagnostic of the source file at `uri`
+    pub fn from_diagnostic(uri: &str, diagnostic: &Diagnostic) -> SarifResult {
+        let mut text = diagnostic.message.clone();
+        if let Some(suggestion) = &diagnostic.suggestion {
+            text = format!("{}\n{}", text, suggestion);
+        }
+        let regions: Vec<_> = diagnostic
+            .spans
+            .iter()
+            .filter_map(SarifRegion::from_span)
+            .collect();
+        let locations = if regions.is_empty() {
+            vec![None]
+        } else {
+            regions.into_iter().map(Some).collect()
+        }
+        .into_iter()
+        .map(|region| SarifLocation {
+            physical_location: SarifPhysicalLocation {
+                artifact_location: SarifArtifactLocation {
+                    uri: uri.to_string(),
+                },
+                region,
+            },
+        })
+        .collect();
+        SarifResult {
+            rule_id: diagnostic.code.clone(),
+            level: sarif_level(&diagnostic.level).to_string(),
+            message: SarifMessage { text },
+            locations,
+        }
+    }
+}
+
+impl SarifLog {
+    /// A log of one run of `tool_name`, with the diagnostics found in each source file, given by
+    /// its URI
+    pub fn new(
+        tool_name: &str,
+        tool_version: &str,
+        diagnostics: &[(String, Vec<Diagnostic>)],
+    ) -> SarifLog {
+        let mut rule_ids = BTreeSet::new();
+        let mut results = vec![];
+        for (uri, file_diagnostics) in diagnostics.iter() {
+            for diagnostic in file_diagnostics.iter() {
+                if let Some(code) = &diagnostic.code {
+                    rule_ids.insert(code.clone());
+                }
+                results.push(SarifResult::from_diagnostic(uri, diagnostic));
+            }
+        }
+        SarifLog {
+            schema: SARIF_SCHEMA.to_string(),
+            version: SARIF_VERSION.to_string(),
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: tool_name.to_string(),
+                        version: tool_version.to_string(),
+                        information_uri: "https://github.com/stacks-network/stacks-blockchain"
+                            .to_string(),
+                        rules: rule_ids.into_iter().map(|id| SarifRule { id }).collect(),
+                    },
+                },
+                results,
+            }],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_to_sarif() {
+        let error = Diagnostic {
+            level: Level::Error,
+            message: "expecting expression of type 'int', found 'bool'".to_string(),
+            spans: vec![Span {
+                start_line: 3,
+                start_column: 5,
+                end_line: 3,
+                end_column: 12,
+            }],
+            suggestion: Some("try converting the value".to_string()),
+            code: Some("TypeError".to_string()),
+        };
+        let warning = Diagnostic {
+            level: Level::Warning,
+            message: "private function 'helper' is never called".to_string(),
+            spans: vec![],
+            suggestion: None,
+            code: Some("UnusedPrivateFunction".to_string()),
+        };
+        let note = Diagnostic {
+            level: Level::Note,
+            message: "a note without a code".to_string(),
+            spans: vec![Span::ZERO],
+            suggestion: None,
+            code: None,
+        };
+
+        let log = SarifLog::new(
+            "clarity-cli",
+            "1.0.0",
+            &[
+                ("contracts/a.clar".to_string(), vec![error, warning]),
+                ("contracts/b.clar".to_string(), vec![note]),
+            ],
+        );
+        let json = serde_json::to_value(&log).unwrap();
+
+        assert_eq!(json["version"], "2.1.0");
+        assert_eq!(json["$schema"], SARIF_SCHEMA);
+        let run = &json["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "clarity-cli");
+        assert_eq!(
+            run["tool"]["driver"]["rules"],
+            serde_json::json!([{"id": "TypeError"}, {"id": "UnusedPrivateFunction"}])
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["ruleId"], "TypeError");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["message"]["text"],
+            "expecting expression of type 'int', found 'bool'\ntry converting the value"
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"],
+            serde_json::json!({
+                "artifactLocation": {"uri": "contracts/a.clar"},
+                "region": {"startLine": 3, "startColumn": 5, "endLine": 3, "endColumn": 13},
+            })
+        );
+
+        // diagnostics without a location still name their file
+        assert_eq!(results[1]["level"], "warning");
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"],
+            serde_json::json!({"artifactLocation": {"uri": "contracts/a.clar"}})
+        );
+
+        assert_eq!(results[2]["level"], "note");
+        assert!(results[2].get("ruleId").is_none());
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"],
+            serde_json::json!({"artifactLocation": {"uri": "contracts/b.clar"}})
+        );
+
+        let decoded: SarifLog = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, log);
+    }
+}
@@ -14,10 +14,24 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::vm::analysis::errors::CheckErrors;
+use std::collections::{BTreeMap, BTreeSet};
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::errors::{CheckErrors, CheckResult, CheckWarning, CheckWarnings};
 use crate::vm::analysis::type_checker::v2_1::tests::mem_type_check;
-use crate::vm::analysis::{type_check, AnalysisDatabase, ContractAnalysis};
+use crate::vm::analysis::unused_checker::UnusedDefinitionChecker;
+use crate::vm::analysis::{
+    contract_dependencies, mem_type_check_with_contracts, run_analysis_incremental,
+    run_analysis_with_diagnostics, run_analysis_with_passes, type_check, AnalysisArchive,
+    AnalysisDatabase, AnalysisPass, AnalysisPassRegistry, ContractAnalysis,
+};
 use crate::vm::ast::parse;
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::{ClarityDeserializable, ClaritySerializable, MemoryBackingStore};
+use crate::vm::diagnostic::{Diagnostic, Level};
+use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TypeSignature};
+use crate::vm::ClarityVersion;
 
 #[test]
 fn test_list_types_must_match() {
@@ -296,3 +310,584 @@ fn test_write_attempt_in_readonly() {
     assert!(format!("{}", err.diagnostic)
         .contains("expecting read-only statements, detected a writing operation"));
 }
+
+#[test]
+fn test_diagnostic_code() {
+    let snippet = "(+ 1 true)";
+    let err = mem_type_check(snippet).unwrap_err();
+    assert_eq!(err.diagnostic.code, Some("TypeError".into()));
+    assert_eq!(err.diagnostic.level, Level::Error);
+
+    let snippet = "(var-get no-such-var)";
+    let err = mem_type_check(snippet).unwrap_err();
+    assert_eq!(err.diagnostic.code, Some("NoSuchDataVariable".into()));
+    assert_eq!(
+        err.diagnostic.suggestion,
+        Some("persisted variables should be defined with define-data-var.".into())
+    );
+}
+
+fn diagnostics_for(snippet: &str) -> (bool, Vec<Diagnostic>) {
+    let contract_identifier = QualifiedContractIdentifier::transient();
+    let mut contract = parse(
+        &contract_identifier,
+        snippet,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    let (result, diagnostics) = run_analysis_with_diagnostics(
+        &contract_identifier,
+        &mut contract,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+    );
+    (result.is_ok(), diagnostics)
+}
+
+#[test]
+fn test_run_analysis_with_diagnostics() {
+    let (ok, diagnostics) = diagnostics_for("(define-public (foo) (ok true))");
+    assert!(ok);
+    assert!(diagnostics.is_empty());
+
+    let (ok, diagnostics) =
+        diagnostics_for("(define-constant unused u1) (define-public (foo) (ok true))");
+    assert!(ok);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, Level::Warning);
+    assert_eq!(diagnostics[0].code, Some("UnusedConstant".into()));
+
+    // the fatal error always comes first
+    let (ok, diagnostics) = diagnostics_for("(define-public (foo) (ok (+ 1 true)))");
+    assert!(!ok);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].level, Level::Error);
+    assert_eq!(diagnostics[0].code, Some("TypeError".into()));
+}
+
+#[test]
+fn test_non_exhaustive_match_warning() {
+    // existing epochs still reject this with the argument count error, and name the missing arm
+    // in a warning
+    let (ok, diagnostics) =
+        diagnostics_for("(define-read-only (foo (x (optional uint))) (match x v v))");
+    assert!(!ok);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].code, Some("BadMatchOptionSyntax".into()));
+    assert!(diagnostics[0]
+        .message
+        .contains("expecting 4 arguments, got 3"));
+    assert_eq!(diagnostics[1].level, Level::Warning);
+    assert_eq!(diagnostics[1].code, Some("NonExhaustiveMatch".into()));
+    assert_eq!(
+        diagnostics[1].message,
+        "match is not exhaustive: missing an arm for 'none'"
+    );
+
+    let (ok, diagnostics) =
+        diagnostics_for("(define-read-only (foo (x (response uint uint))) (match x v v e))");
+    assert!(!ok);
+    assert_eq!(diagnostics[0].code, Some("BadMatchResponseSyntax".into()));
+    assert_eq!(
+        diagnostics[1].message,
+        "match is not exhaustive: missing an arm for 'err'"
+    );
+}
+
+#[test]
+fn test_run_analysis_incremental() {
+    let epoch = StacksEpochId::Epoch21;
+    let version = ClarityVersion::Clarity2;
+    let contracts = [
+        ("a", "(define-read-only (get-value) u1)"),
+        (
+            "b",
+            "(define-read-only (double) (* u2 (contract-call? .a get-value)))",
+        ),
+        (
+            "c",
+            "(define-read-only (quadruple) (* u2 (contract-call? .b double)))",
+        ),
+        ("d", "(define-read-only (other) u1)"),
+    ];
+    let id = |name: &str| QualifiedContractIdentifier::local(name).unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+    let mut sources = BTreeMap::new();
+    for (name, src) in contracts {
+        let mut expressions = parse(&id(name), src, version, epoch).unwrap();
+        analysis_db
+            .execute(|db| {
+                db.test_insert_contract_hash(&id(name));
+                type_check(&id(name), &mut expressions, db, true, &epoch, &version)
+            })
+            .unwrap();
+        sources.insert(id(name), expressions);
+    }
+    assert_eq!(
+        analysis_db
+            .execute(|db| db.get_dependencies(&id("c")))
+            .unwrap(),
+        Some([id("b")].into_iter().collect())
+    );
+    assert_eq!(
+        analysis_db
+            .execute(|db| db.get_dependents(&id("a")))
+            .unwrap(),
+        vec![id("b"), id("c")]
+    );
+    assert!(analysis_db
+        .execute(|db| db.get_dependents(&id("d")))
+        .unwrap()
+        .is_empty());
+
+    // Only `b` calls `get-value` directly, so only it breaks
+    let mut changed = parse(
+        &id("a"),
+        "(define-read-only (get-value) true)",
+        version,
+        epoch,
+    )
+    .unwrap();
+    let incremental = run_analysis_incremental(
+        &id("a"),
+        &mut changed,
+        &mut sources,
+        &mut analysis_db,
+        epoch,
+        version,
+    )
+    .unwrap();
+    assert_eq!(incremental.reanalyzed, vec![id("b"), id("c")]);
+    assert_eq!(incremental.failed_dependents.len(), 1);
+    assert_eq!(incremental.failed_dependents[0].0, id("b"));
+
+    // The saved analyses are unchanged
+    let saved = analysis_db
+        .execute(|db| db.get_read_only_function_type(&id("a"), "get-value", &epoch))
+        .unwrap()
+        .unwrap();
+    let FunctionType::Fixed(saved) = saved else {
+        panic!("Expected fixed function type");
+    };
+    assert_eq!(saved.returns, TypeSignature::UIntType);
+
+    let mut changed = parse(
+        &id("a"),
+        "(define-read-only (get-value) u2)",
+        version,
+        epoch,
+    )
+    .unwrap();
+    let incremental = run_analysis_incremental(
+        &id("a"),
+        &mut changed,
+        &mut sources,
+        &mut analysis_db,
+        epoch,
+        version,
+    )
+    .unwrap();
+    assert_eq!(incremental.reanalyzed.len(), 2);
+    assert!(incremental.failed_dependents.is_empty());
+
+    // A change that fails analysis itself is reported as an error
+    let mut changed = parse(
+        &id("a"),
+        "(define-read-only (get-value) (+ u1 true))",
+        version,
+        epoch,
+    )
+    .unwrap();
+    assert!(run_analysis_incremental(
+        &id("a"),
+        &mut changed,
+        &mut sources,
+        &mut analysis_db,
+        epoch,
+        version
+    )
+    .is_err());
+}
+
+#[test]
+fn test_mem_type_check_with_contracts() {
+    let contracts = [
+        (
+            QualifiedContractIdentifier::local("trait-def").unwrap(),
+            "(define-trait getter ((get-value () (response uint uint))))",
+        ),
+        (
+            QualifiedContractIdentifier::local("impl").unwrap(),
+            "(impl-trait .trait-def.getter)
+             (define-public (get-value) (ok u1))",
+        ),
+    ];
+    let snippet = "(use-trait getter .trait-def.getter)
+        (define-public (call (g <getter>)) (contract-call? g get-value))
+        (contract-call? .impl get-value)";
+    let (type_sig, _) = mem_type_check_with_contracts(
+        snippet,
+        &contracts,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    assert_eq!(
+        type_sig,
+        // The type of `(ok u1)`
+        Some(TypeSignature::new_response(TypeSignature::UIntType, TypeSignature::NoType).unwrap())
+    );
+
+    // Without the contracts, the snippet cannot be checked
+    let err = mem_type_check_with_contracts(
+        snippet,
+        &[],
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap_err();
+    assert!(matches!(err.err, CheckErrors::NoSuchContract(_)));
+}
+
+#[test]
+fn test_contract_dependencies() {
+    let contracts = [
+        (
+            "trait-def",
+            "(define-trait getter ((get-value () (response uint uint))))",
+        ),
+        (
+            "impl",
+            "(impl-trait .trait-def.getter)
+             (define-public (get-value) (ok u1))",
+        ),
+        (
+            "user",
+            "(use-trait getter .trait-def.getter)
+             (define-constant owner .not-called)
+             (define-public (call (g <getter>)) (contract-call? g get-value))
+             (define-public (call-impl) (contract-call? .impl get-value))",
+        ),
+    ];
+    let id = |name: &str| QualifiedContractIdentifier::local(name).unwrap();
+
+    for (epoch, version) in [
+        (StacksEpochId::Epoch2_05, ClarityVersion::Clarity1),
+        (StacksEpochId::Epoch21, ClarityVersion::Clarity2),
+    ] {
+        let mut marf = MemoryBackingStore::new();
+        let mut analysis_db = marf.as_analysis_db();
+        let mut analyses = BTreeMap::new();
+        for (name, src) in contracts {
+            let mut expressions = parse(&id(name), src, version, epoch).unwrap();
+            let analysis = analysis_db
+                .execute(|db| {
+                    db.test_insert_contract_hash(&id(name));
+                    type_check(&id(name), &mut expressions, db, true, &epoch, &version)
+                })
+                .unwrap();
+            analyses.insert(name, analysis);
+        }
+
+        assert!(contract_dependencies(&analyses["trait-def"]).is_empty());
+        assert_eq!(
+            contract_dependencies(&analyses["impl"]),
+            vec![id("trait-def")]
+        );
+        // Neither principal literals that are not called nor dynamic dispatch are dependencies
+        assert_eq!(
+            contract_dependencies(&analyses["user"]),
+            vec![id("impl"), id("trait-def")]
+        );
+
+        // The dependencies are stored with the contract, so a new database over the same store
+        // finds them
+        let mut analysis_db = marf.as_analysis_db();
+        assert_eq!(
+            analysis_db
+                .execute(|db| db.get_dependencies(&id("user")))
+                .unwrap(),
+            Some(analyses["user"].get_dependencies().clone())
+        );
+        assert_eq!(
+            analysis_db
+                .execute(|db| db.get_dependencies(&id("not-called")))
+                .unwrap(),
+            None
+        );
+    }
+}
+
+#[test]
+fn test_analysis_archive_round_trip() {
+    let epoch = StacksEpochId::Epoch21;
+    let version = ClarityVersion::Clarity2;
+    let contracts = [
+        ("a", "(define-read-only (get-value) u1)"),
+        (
+            "b",
+            "(define-read-only (double) (* u2 (contract-call? .a get-value)))",
+        ),
+    ];
+    let id = |name: &str| QualifiedContractIdentifier::local(name).unwrap();
+
+    let mut marf = MemoryBackingStore::new();
+    for (name, src) in contracts {
+        let mut clarity_db = marf.as_clarity_db();
+        clarity_db.begin();
+        clarity_db.insert_contract_hash(&id(name), src).unwrap();
+        clarity_db.commit().unwrap();
+    }
+    let mut analysis_db = marf.as_analysis_db();
+    for (name, src) in contracts {
+        let mut expressions = parse(&id(name), src, version, epoch).unwrap();
+        analysis_db
+            .execute(|db| type_check(&id(name), &mut expressions, db, true, &epoch, &version))
+            .unwrap();
+    }
+
+    let archive = analysis_db.execute(|db| db.export_archive()).unwrap();
+    let names: Vec<_> = archive
+        .entries
+        .iter()
+        .map(|entry| entry.contract_identifier.clone())
+        .collect();
+    assert_eq!(names, vec![id("a"), id("b")]);
+    assert_eq!(
+        archive.entries[0].contract_src.as_deref(),
+        Some(contracts[0].1)
+    );
+    assert_eq!(
+        archive.entries[1].contract_dependencies,
+        Some(vec![id("a")])
+    );
+    let archive = AnalysisArchive::deserialize(&archive.serialize()).unwrap();
+
+    let mut fresh = MemoryBackingStore::new();
+    let mut analysis_db = fresh.as_analysis_db();
+    analysis_db
+        .execute(|db| db.import_archive(&archive))
+        .unwrap();
+    let loaded = analysis_db
+        .execute(|db| db.load_contract_non_canonical(&id("b")))
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded, archive.entries[1].analysis);
+    assert_eq!(
+        analysis_db
+            .execute(|db| db.get_dependencies(&id("b")))
+            .unwrap(),
+        Some(BTreeSet::from([id("a")]))
+    );
+    let mut clarity_db = fresh.as_clarity_db();
+    clarity_db.begin();
+    assert_eq!(
+        clarity_db.get_contract_src(&id("a")).as_deref(),
+        Some(contracts[0].1)
+    );
+    clarity_db.roll_back().unwrap();
+
+    // The imported analyses are enough to check a new contract against
+    let mut expressions = parse(
+        &id("c"),
+        "(define-read-only (quadruple) (* u2 (contract-call? .b double)))",
+        version,
+        epoch,
+    )
+    .unwrap();
+    let mut analysis_db = fresh.as_analysis_db();
+    analysis_db
+        .execute(|db| type_check(&id("c"), &mut expressions, db, false, &epoch, &version))
+        .unwrap();
+
+    // Analyses are never overwritten
+    let err = analysis_db
+        .execute(|db| db.import_archive(&archive))
+        .unwrap_err();
+    assert!(matches!(err.err, CheckErrors::ContractAlreadyExists(_)));
+}
+
+/// Warns about public functions whose names start with `unsafe-`
+struct UnsafeNameLinter;
+
+impl AnalysisPass for UnsafeNameLinter {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        let names: Vec<_> = contract_analysis
+            .public_function_types
+            .keys()
+            .filter(|name| name.starts_with("unsafe-"))
+            .cloned()
+            .collect();
+        for name in names {
+            contract_analysis
+                .warnings
+                .push(CheckWarning::new(CheckWarnings::CustomPass(format!(
+                    "public function '{name}' is marked unsafe"
+                ))));
+        }
+        Ok(())
+    }
+}
+
+/// Rejects every contract
+struct RejectingPass;
+
+impl AnalysisPass for RejectingPass {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        _contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        Err(CheckErrors::Expects("rejected".into()).into())
+    }
+}
+
+#[test]
+fn test_run_analysis_with_passes() {
+    let contract_identifier = QualifiedContractIdentifier::local("linted").unwrap();
+    let snippet = "(define-constant unused u1)
+        (define-public (unsafe-withdraw) (ok true))
+        (define-public (deposit) (ok true))";
+    let mut contract = parse(
+        &contract_identifier,
+        snippet,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+
+    let mut passes = AnalysisPassRegistry::new();
+    passes.register::<UnusedDefinitionChecker>("unused-definitions");
+    passes.register::<UnsafeNameLinter>("unsafe-names");
+    let analysis = run_analysis_with_passes(
+        &contract_identifier,
+        &mut contract,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+        &passes,
+        false,
+    )
+    .unwrap();
+    // each pass's warnings are attributed to it
+    assert_eq!(analysis.warnings.len(), 2);
+    assert_eq!(analysis.custom_pass_warnings["unused-definitions"].len(), 1);
+    let warnings = &analysis.custom_pass_warnings["unsafe-names"];
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].diagnostic.message,
+        "public function 'unsafe-withdraw' is marked unsafe"
+    );
+    assert_eq!(warnings[0].diagnostic.code, Some("CustomPass".into()));
+
+    // a failing pass fails the analysis, so the contract is not saved
+    passes.register::<RejectingPass>("reject");
+    assert_eq!(
+        passes.pass_names().collect::<Vec<_>>(),
+        vec!["unused-definitions", "unsafe-names", "reject"]
+    );
+    let (err, _) = run_analysis_with_passes(
+        &contract_identifier,
+        &mut contract,
+        &mut analysis_db,
+        true,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+        &passes,
+        false,
+    )
+    .unwrap_err();
+    assert_eq!(err.err, CheckErrors::Expects("rejected".into()));
+    assert!(!analysis_db.has_contract(&contract_identifier));
+
+    // registering a name again replaces its pass in place
+    passes.register::<UnsafeNameLinter>("reject");
+    assert_eq!(
+        passes.pass_names().collect::<Vec<_>>(),
+        vec!["unused-definitions", "unsafe-names", "reject"]
+    );
+}
+
+#[test]
+fn test_run_analysis_with_strict_checks() {
+    let contract_identifier = QualifiedContractIdentifier::local("strict").unwrap();
+    let snippet =
+        "(define-read-only (get-ok (x (response uint uint))) (match x v v e (unwrap-panic x)))
+        (define-read-only (get-some (x (optional uint))) (match x v v (unwrap-panic x)))
+        (define-public (get-one) (ok (unwrap! (ok u1) (err u2))))
+        (define-public (get-two) (ok (unwrap-err! (err u2) (err u3))))
+        (define-read-only (handled (x (response uint uint))) (match x v v e e))";
+    let mut contract = parse(
+        &contract_identifier,
+        snippet,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut analysis_db = marf.as_analysis_db();
+
+    let analysis = run_analysis_with_passes(
+        &contract_identifier,
+        &mut contract,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+        &AnalysisPassRegistry::new(),
+        false,
+    )
+    .unwrap();
+    assert!(analysis.warnings.is_empty());
+
+    let analysis = run_analysis_with_passes(
+        &contract_identifier,
+        &mut contract,
+        &mut analysis_db,
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+        &AnalysisPassRegistry::new(),
+        true,
+    )
+    .unwrap();
+    let warnings: Vec<_> = analysis
+        .warnings
+        .iter()
+        .map(|warning| warning.warning.clone())
+        .collect();
+    assert_eq!(
+        warnings,
+        vec![
+            CheckWarnings::PanickingMatchArm("err".into()),
+            CheckWarnings::PanickingMatchArm("none".into()),
+            CheckWarnings::UnreachableUnwrapReturn("unwrap!".into()),
+            CheckWarnings::UnreachableUnwrapReturn("unwrap-err!".into()),
+        ]
+    );
+    // the warning points at the offending arm
+    let arm = &analysis.warnings[0].expressions.as_ref().unwrap()[0];
+    assert_eq!(
+        arm.match_list().unwrap()[0].match_atom().unwrap().as_str(),
+        "unwrap-panic"
+    );
+}
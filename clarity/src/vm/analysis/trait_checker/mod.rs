@@ -25,7 +25,7 @@ use crate::vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
 use crate::vm::functions::NativeFunctions;
 use crate::vm::representations::SymbolicExpressionType::{Atom, AtomValue, List, LiteralValue};
 use crate::vm::representations::{ClarityName, SymbolicExpression};
-use crate::vm::types::{FunctionType, TraitIdentifier, TypeSignature, Value};
+use crate::vm::types::{FunctionSignature, FunctionType, TraitIdentifier, TypeSignature, Value};
 
 pub struct TraitChecker {
     epoch: StacksEpochId,
@@ -77,5 +77,98 @@ impl TraitChecker {
     }
 }
 
+/// A trait function that a contract defines with a signature the trait does not admit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraitFunctionMismatch {
+    pub name: ClarityName,
+    pub expected: FunctionSignature,
+    pub actual: FunctionSignature,
+}
+
+/// How a contract measures up against a trait, function by function
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraitConformanceReport {
+    pub trait_identifier: TraitIdentifier,
+    /// trait functions the contract implements
+    pub implemented: Vec<ClarityName>,
+    /// trait functions the contract does not define as public or read-only functions
+    pub missing: Vec<ClarityName>,
+    /// trait functions the contract defines with the wrong signature
+    pub mismatched: Vec<TraitFunctionMismatch>,
+}
+
+impl TraitConformanceReport {
+    /// Does the contract implement every function of the trait?
+    pub fn conforms(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Report which of the functions of the trait `trait_identifier` the contract implements, which
+/// it is missing, and which it defines with the wrong signature.  Unlike the trait checker pass,
+/// this does not stop at the first function that does not conform, and the contract does not
+/// need to declare that it implements the trait.
+pub fn check_trait_conformance(
+    contract_analysis: &ContractAnalysis,
+    trait_identifier: &TraitIdentifier,
+    analysis_db: &mut AnalysisDatabase,
+) -> CheckResult<TraitConformanceReport> {
+    let epoch = contract_analysis.epoch;
+    let trait_name = trait_identifier.name.to_string();
+    let contract_defining_trait =
+        if trait_identifier.contract_identifier == contract_analysis.contract_identifier {
+            None
+        } else {
+            Some(
+                analysis_db
+                    .load_contract(&trait_identifier.contract_identifier, &epoch)?
+                    .ok_or(CheckErrors::TraitReferenceUnknown(trait_name.clone()))?,
+            )
+        };
+    let trait_definition = contract_defining_trait
+        .as_ref()
+        .unwrap_or(contract_analysis)
+        .get_defined_trait(&trait_name)
+        .ok_or(CheckErrors::TraitReferenceUnknown(trait_name.clone()))?;
+
+    let mut report = TraitConformanceReport {
+        trait_identifier: trait_identifier.clone(),
+        implemented: vec![],
+        missing: vec![],
+        mismatched: vec![],
+    };
+    for (func_name, expected_sig) in trait_definition.iter() {
+        let func = match (
+            contract_analysis.get_public_function_type(func_name),
+            contract_analysis.get_read_only_function_type(func_name),
+        ) {
+            (Some(FunctionType::Fixed(func)), None) | (None, Some(FunctionType::Fixed(func))) => {
+                func
+            }
+            (_, _) => {
+                report.missing.push(func_name.clone());
+                continue;
+            }
+        };
+
+        let args_sig: Vec<_> = func.args.iter().map(|a| a.signature.clone()).collect();
+        if expected_sig.check_args_trait_compliance(&epoch, args_sig.clone())?
+            && expected_sig.returns.admits_type(&epoch, &func.returns)?
+        {
+            report.implemented.push(func_name.clone());
+        } else {
+            report.mismatched.push(TraitFunctionMismatch {
+                name: func_name.clone(),
+                expected: expected_sig.clone(),
+                actual: FunctionSignature {
+                    args: args_sig,
+                    returns: func.returns.clone(),
+                },
+            });
+        }
+    }
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests;
@@ -22,13 +22,14 @@ use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::contract_interface_builder::build_contract_interface;
 use crate::vm::analysis::errors::CheckErrors;
+use crate::vm::analysis::trait_checker::check_trait_conformance;
 use crate::vm::analysis::{type_check, AnalysisDatabase, CheckError};
 use crate::vm::ast::errors::ParseErrors;
 use crate::vm::ast::{build_ast, parse};
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::tests::test_clarity_versions;
-use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
-use crate::vm::ClarityVersion;
+use crate::vm::types::{QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
+use crate::vm::{ClarityName, ClarityVersion};
 
 #[apply(test_clarity_versions)]
 fn test_dynamic_dispatch_by_defining_trait(
@@ -1820,3 +1821,82 @@ fn test_trait_contract_not_found(#[case] version: ClarityVersion, #[case] epoch:
         res => panic!("{}: {:?}", version, res),
     }
 }
+
+#[apply(test_clarity_versions)]
+fn test_check_trait_conformance(#[case] version: ClarityVersion, #[case] epoch: StacksEpochId) {
+    let trait_contract_src = "(define-trait trait-1 (
+            (get-1 (uint) (response uint uint))
+            (get-2 (uint) (response uint uint))
+            (get-3 (uint) (response uint uint))))";
+    let impl_contract_src = "(define-public (get-1 (x uint)) (ok u1))
+        (define-read-only (get-2 (x int)) (ok u2))
+        (define-private (get-3 (x uint)) (ok u3))";
+
+    let trait_contract_id = QualifiedContractIdentifier::local("trait-contract").unwrap();
+    let impl_contract_id = QualifiedContractIdentifier::local("impl-contract").unwrap();
+    let trait_id = TraitIdentifier {
+        name: "trait-1".into(),
+        contract_identifier: trait_contract_id.clone(),
+    };
+
+    let mut trait_contract = parse(&trait_contract_id, trait_contract_src, version, epoch).unwrap();
+    let mut impl_contract = parse(&impl_contract_id, impl_contract_src, version, epoch).unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let mut db = marf.as_analysis_db();
+
+    let report = db
+        .execute(|db| {
+            type_check(
+                &trait_contract_id,
+                &mut trait_contract,
+                db,
+                true,
+                &epoch,
+                &version,
+            )?;
+            let impl_analysis = type_check(
+                &impl_contract_id,
+                &mut impl_contract,
+                db,
+                false,
+                &epoch,
+                &version,
+            )?;
+            check_trait_conformance(&impl_analysis, &trait_id, db)
+        })
+        .unwrap();
+
+    assert!(!report.conforms());
+    assert_eq!(report.implemented, vec![ClarityName::from("get-1")]);
+    assert_eq!(report.missing, vec![ClarityName::from("get-3")]);
+    assert_eq!(report.mismatched.len(), 1);
+    assert_eq!(report.mismatched[0].name, ClarityName::from("get-2"));
+    assert_eq!(
+        report.mismatched[0].expected.args,
+        vec![TypeSignature::UIntType]
+    );
+    assert_eq!(
+        report.mismatched[0].actual.args,
+        vec![TypeSignature::IntType]
+    );
+
+    // a trait the contract does not know of
+    let unknown_trait_id = TraitIdentifier {
+        name: "trait-2".into(),
+        contract_identifier: trait_contract_id.clone(),
+    };
+    let err = db
+        .execute(|db| {
+            let impl_analysis = type_check(
+                &impl_contract_id,
+                &mut impl_contract,
+                db,
+                false,
+                &epoch,
+                &version,
+            )?;
+            check_trait_conformance(&impl_analysis, &unknown_trait_id, db)
+        })
+        .unwrap_err();
+    assert!(matches!(err.err, CheckErrors::TraitReferenceUnknown(_)));
+}
@@ -21,7 +21,7 @@ use crate::vm::analysis::types::ContractAnalysis;
 use crate::vm::contexts::MAX_CONTEXT_DEPTH;
 use crate::vm::representations::{ClarityName, SymbolicExpression};
 use crate::vm::types::signatures::FunctionSignature;
-use crate::vm::types::{FunctionType, TraitIdentifier, TypeSignature};
+use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
 
 pub struct ContractContext {
     map_types: HashMap<ClarityName, (TypeSignature, TypeSignature)>,
@@ -34,6 +34,7 @@ pub struct ContractContext {
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: HashMap<ClarityName, BTreeMap<ClarityName, FunctionSignature>>,
     pub implemented_traits: HashSet<TraitIdentifier>,
+    dependencies: HashSet<QualifiedContractIdentifier>,
 }
 
 impl Default for ContractContext {
@@ -55,6 +56,7 @@ impl ContractContext {
             non_fungible_tokens: HashMap::new(),
             traits: HashMap::new(),
             implemented_traits: HashSet::new(),
+            dependencies: HashSet::new(),
         }
     }
 
@@ -179,6 +181,12 @@ impl ContractContext {
         Ok(())
     }
 
+    /// Record that the contract refers to `contract_identifier`, with a static `contract-call?`
+    /// or by using or implementing one of its traits
+    pub fn add_dependency(&mut self, contract_identifier: QualifiedContractIdentifier) {
+        self.dependencies.insert(contract_identifier);
+    }
+
     pub fn get_trait(&self, trait_name: &str) -> Option<&BTreeMap<ClarityName, FunctionSignature>> {
         self.traits.get(trait_name)
     }
@@ -247,5 +255,9 @@ impl ContractContext {
         for trait_identifier in self.implemented_traits.drain() {
             contract_analysis.add_implemented_trait(trait_identifier);
         }
+
+        for contract_identifier in self.dependencies.drain() {
+            contract_analysis.add_dependency(contract_identifier);
+        }
     }
 }
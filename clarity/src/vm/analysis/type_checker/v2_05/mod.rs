@@ -946,6 +946,8 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     name,
                     trait_identifier,
                 } => {
+                    self.contract_context
+                        .add_dependency(trait_identifier.contract_identifier.clone());
                     let result = self.db.get_defined_trait(
                         &trait_identifier.contract_identifier,
                         &trait_identifier.name,
@@ -971,6 +973,8 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     }
                 }
                 DefineFunctionsParsed::ImplTrait { trait_identifier } => {
+                    self.contract_context
+                        .add_dependency(trait_identifier.contract_identifier.clone());
                     self.contract_context
                         .add_implemented_trait(trait_identifier.clone())?;
                 }
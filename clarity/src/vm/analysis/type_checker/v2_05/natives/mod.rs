@@ -369,6 +369,9 @@ fn check_contract_call(
             ref contract_identifier,
         ))) => {
             // Static dispatch
+            checker
+                .contract_context
+                .add_dependency(contract_identifier.clone());
             let contract_call_function = {
                 if let Some(FunctionType::Fixed(function)) = checker.db.get_public_function_type(
                     contract_identifier,
@@ -784,6 +787,19 @@ impl TypedNativeFunction {
                 )
                 .into())
             }
+            StxGetAccountInfo
+            | StxGetDelegationInfo
+            | ContractPrincipalConstruct
+            | BitwiseLRotate
+            | BitwiseRRotate
+            | Log2UInt
+            | UIntToAscii
+            | ConcatMany => {
+                return Err(CheckErrors::Expects(
+                    "Clarity 3 keywords should not show up in 2.05".into(),
+                )
+                .into())
+            }
         };
 
         Ok(out)
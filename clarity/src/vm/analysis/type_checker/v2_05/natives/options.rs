@@ -301,6 +301,9 @@ fn check_special_match_opt(
     args: &[SymbolicExpression],
     context: &TypingContext,
 ) -> TypeResult {
+    // Unlike the 2.1 checker, this does not name a missing `none` or `err` arm: it only analyzes
+    // contracts for Stacks 2.05 and earlier, which never get a `NonExhaustiveMatch` error, and
+    // the 2.05 checker reports no warnings.
     if args.len() != 3 {
         Err(CheckErrors::BadMatchOptionSyntax(Box::new(
             CheckErrors::IncorrectArgumentCount(4, args.len() + 1),
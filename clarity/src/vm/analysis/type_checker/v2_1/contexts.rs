@@ -40,7 +40,7 @@ impl TraitContext {
     pub fn new(clarity_version: ClarityVersion) -> TraitContext {
         match clarity_version {
             ClarityVersion::Clarity1 => Self::Clarity1(HashMap::new()),
-            ClarityVersion::Clarity2 => Self::Clarity2 {
+            ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => Self::Clarity2 {
                 defined: HashSet::new(),
                 all: HashMap::new(),
             },
@@ -137,6 +137,7 @@ pub struct ContractContext {
     non_fungible_tokens: HashMap<ClarityName, TypeSignature>,
     traits: TraitContext,
     pub implemented_traits: HashSet<TraitIdentifier>,
+    dependencies: HashSet<QualifiedContractIdentifier>,
 }
 
 impl ContractContext {
@@ -156,6 +157,7 @@ impl ContractContext {
             non_fungible_tokens: HashMap::new(),
             traits: TraitContext::new(clarity_version),
             implemented_traits: HashSet::new(),
+            dependencies: HashSet::new(),
         }
     }
 
@@ -298,6 +300,12 @@ impl ContractContext {
         Ok(())
     }
 
+    /// Record that the contract refers to `contract_identifier`, with a static `contract-call?`
+    /// or by using or implementing one of its traits
+    pub fn add_dependency(&mut self, contract_identifier: QualifiedContractIdentifier) {
+        self.dependencies.insert(contract_identifier);
+    }
+
     pub fn get_trait(
         &self,
         trait_id: &TraitIdentifier,
@@ -367,5 +375,9 @@ impl ContractContext {
         for trait_identifier in self.implemented_traits.drain() {
             contract_analysis.add_implemented_trait(trait_identifier);
         }
+
+        for contract_identifier in self.dependencies.drain() {
+            contract_analysis.add_dependency(contract_identifier);
+        }
     }
 }
@@ -28,7 +28,7 @@ use super::contexts::{TypeMap, TypingContext};
 use super::{AnalysisPass, ContractAnalysis};
 pub use crate::vm::analysis::errors::{
     check_argument_count, check_arguments_at_least, check_arguments_at_most, CheckError,
-    CheckErrors, CheckResult,
+    CheckErrors, CheckResult, CheckWarning, CheckWarnings,
 };
 use crate::vm::analysis::AnalysisDatabase;
 use crate::vm::contexts::Environment;
@@ -81,6 +81,13 @@ pub struct TypeChecker<'a, 'b> {
     db: &'a mut AnalysisDatabase<'b>,
     pub cost_track: LimitedCostTracker,
     clarity_version: ClarityVersion,
+    /// Whether to warn about error handling that is probably a mistake (see
+    /// `run_pass_with_strict_checks`)
+    strict_checks: bool,
+    /// Whether a `match` missing its `none` or `err` arm is rejected with `NonExhaustiveMatch`
+    /// (see `non_exhaustive_match_is_error`)
+    non_exhaustive_match_is_error: bool,
+    warnings: Vec<CheckWarning>,
 }
 
 impl CostTracker for TypeChecker<'_, '_> {
@@ -117,9 +124,23 @@ impl CostTracker for TypeChecker<'_, '_> {
 
 impl AnalysisPass for TypeChecker<'_, '_> {
     fn run_pass(
-        _epoch: &StacksEpochId,
+        epoch: &StacksEpochId,
         contract_analysis: &mut ContractAnalysis,
         analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        TypeChecker::run_pass_with_strict_checks(epoch, contract_analysis, analysis_db, false)
+    }
+}
+
+impl TypeChecker<'_, '_> {
+    /// Like `run_pass`, but if `strict_checks` is set, also warns about `match` arms that turn an
+    /// `err` or `none` into a runtime panic with `unwrap-panic` or `unwrap-err-panic`, and about
+    /// `unwrap!` and `unwrap-err!` inputs that can never take the early-return path.
+    pub fn run_pass_with_strict_checks(
+        epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        analysis_db: &mut AnalysisDatabase,
+        strict_checks: bool,
     ) -> CheckResult<()> {
         let cost_track = contract_analysis.take_contract_cost_tracker();
         let mut command = TypeChecker::new(
@@ -128,6 +149,8 @@ impl AnalysisPass for TypeChecker<'_, '_> {
             &contract_analysis.contract_identifier,
             &contract_analysis.clarity_version,
         );
+        command.strict_checks = strict_checks;
+        command.non_exhaustive_match_is_error = non_exhaustive_match_is_error(epoch);
         // run the analysis, and replace the cost tracker whether or not the
         //   analysis succeeded.
         match command.run(contract_analysis) {
@@ -137,14 +160,35 @@ impl AnalysisPass for TypeChecker<'_, '_> {
                 Ok(())
             }
             err => {
-                let TypeChecker { cost_track, .. } = command;
+                let TypeChecker {
+                    cost_track,
+                    warnings,
+                    ..
+                } = command;
                 contract_analysis.replace_contract_cost_tracker(cost_track);
+                contract_analysis.warnings.extend(warnings);
                 err
             }
         }
     }
 }
 
+/// Whether a `match` that is missing its `none` or `err` arm is rejected with
+/// `NonExhaustiveMatch`. Contracts like this were rejected with an argument count error before
+/// that variant existed, so in the existing epochs they still are, and the missing arm is only
+/// named in a `CheckWarnings::NonExhaustiveMatch` warning. A new epoch can opt in here.
+fn non_exhaustive_match_is_error(epoch: &StacksEpochId) -> bool {
+    match epoch {
+        StacksEpochId::Epoch10
+        | StacksEpochId::Epoch20
+        | StacksEpochId::Epoch2_05
+        | StacksEpochId::Epoch21
+        | StacksEpochId::Epoch22
+        | StacksEpochId::Epoch23
+        | StacksEpochId::Epoch24 => false,
+    }
+}
+
 pub type TypeResult = CheckResult<TypeSignature>;
 
 impl FunctionType {
@@ -888,6 +932,23 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
             function_return_tracker: None,
             type_map: TypeMap::new(),
             clarity_version: *clarity_version,
+            strict_checks: false,
+            non_exhaustive_match_is_error: false,
+            warnings: vec![],
+        }
+    }
+
+    /// Report a warning about `expr`
+    fn add_warning(&mut self, warning: CheckWarnings, expr: &SymbolicExpression) {
+        let mut warning = CheckWarning::new(warning);
+        warning.set_expression(expr);
+        self.warnings.push(warning);
+    }
+
+    /// Report a strict check warning about `expr`, if strict checks are on
+    fn add_strict_warning(&mut self, warning: CheckWarnings, expr: &SymbolicExpression) {
+        if self.strict_checks {
+            self.add_warning(warning, expr);
         }
     }
 
@@ -898,6 +959,7 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         self.contract_context
             .into_contract_analysis(contract_analysis);
         contract_analysis.type_map = Some(self.type_map);
+        contract_analysis.warnings.extend(self.warnings);
         self.cost_track
     }
 
@@ -1549,6 +1611,8 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     name,
                     trait_identifier,
                 } => {
+                    self.contract_context
+                        .add_dependency(trait_identifier.contract_identifier.clone());
                     let result = self.db.get_defined_trait(
                         &trait_identifier.contract_identifier,
                         &trait_identifier.name,
@@ -1577,6 +1641,8 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
                     }
                 }
                 DefineFunctionsParsed::ImplTrait { trait_identifier } => {
+                    self.contract_context
+                        .add_dependency(trait_identifier.contract_identifier.clone());
                     self.contract_context
                         .add_implemented_trait(trait_identifier.clone())?;
                 }
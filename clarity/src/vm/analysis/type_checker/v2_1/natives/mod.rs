@@ -375,6 +375,9 @@ fn check_contract_call(
             ref contract_identifier,
         ))) => {
             // Static dispatch
+            checker
+                .contract_context
+                .add_dependency(contract_identifier.clone());
             let contract_call_function = {
                 if let Some(FunctionType::Fixed(function)) = checker.db.get_public_function_type(
                     contract_identifier,
@@ -610,6 +613,48 @@ fn check_principal_construct(
     )
 }
 
+/// Form:
+/// (define-public (contract-principal-construct? principal (string-ascii CONTRACT_MAX_NAME_LENGTH))
+///     (response principal { error_code: uint, principal: (option principal) }))
+fn check_contract_principal_construct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+    checker.type_check_expects(&args[0], context, &TypeSignature::PrincipalType)?;
+    checker.type_check_expects(
+        &args[1],
+        context,
+        &TypeSignature::contract_name_string_ascii_type()?,
+    )?;
+    Ok(TypeSignature::new_response(
+        TypeSignature::PrincipalType,
+        TupleTypeSignature::try_from(vec![
+            ("error_code".into(), TypeSignature::UIntType),
+            (
+                "value".into(),
+                TypeSignature::new_option(TypeSignature::PrincipalType).map_err(|_| {
+                    CheckErrors::Expects(
+                        "FATAL: failed to create (optional principal) type signature".into(),
+                    )
+                })?,
+            ),
+        ])
+        .map_err(|_| {
+            CheckErrors::Expects(
+                "FAIL: ContractPrincipalConstruct failed to initialize type signature".into(),
+            )
+        })?
+        .into(),
+    )
+    .map_err(|_| {
+        CheckErrors::Expects(
+            "FATAL: failed to create `(response principal { error_code: uint, value: (optional principal) })` type signature".into(),
+        )
+    })?)
+}
+
 fn check_secp256k1_recover(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -686,6 +731,25 @@ fn check_get_burn_block_info(
     )?)
 }
 
+/// The type of a PoX `delegation-state` map entry, as returned by `stx-delegation-info`
+pub(crate) fn stx_delegation_info_type() -> Result<TypeSignature, CheckErrors> {
+    let pox_addr_type: TypeSignature = TupleTypeSignature::try_from(vec![
+        ("version".into(), BUFF_1.clone()),
+        ("hashbytes".into(), BUFF_32.clone()),
+    ])?
+    .into();
+    Ok(TupleTypeSignature::try_from(vec![
+        ("amount-ustx".into(), TypeSignature::UIntType),
+        ("delegated-to".into(), TypeSignature::PrincipalType),
+        (
+            "until-burn-ht".into(),
+            TypeSignature::new_option(TypeSignature::UIntType)?,
+        ),
+        ("pox-addr".into(), TypeSignature::new_option(pox_addr_type)?),
+    ])?
+    .into())
+}
+
 impl TypedNativeFunction {
     pub fn type_check_application(
         &self,
@@ -721,11 +785,16 @@ impl TypedNativeFunction {
             Sqrti | Log2 | BitwiseNot => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary))
             }
-            BitwiseLShift | BitwiseRShift => Simple(SimpleNativeFunction(FunctionType::Binary(
-                FunctionArgSignature::Union(vec![TypeSignature::IntType, TypeSignature::UIntType]),
-                FunctionArgSignature::Single(TypeSignature::UIntType),
-                FunctionReturnsSignature::TypeOfArgAtPosition(0),
-            ))),
+            BitwiseLShift | BitwiseRShift | BitwiseLRotate | BitwiseRRotate => {
+                Simple(SimpleNativeFunction(FunctionType::Binary(
+                    FunctionArgSignature::Union(vec![
+                        TypeSignature::IntType,
+                        TypeSignature::UIntType,
+                    ]),
+                    FunctionArgSignature::Single(TypeSignature::UIntType),
+                    FunctionReturnsSignature::TypeOfArgAtPosition(0),
+                )))
+            }
             Modulo | Power | BitwiseXor => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticBinary))
             }
@@ -817,6 +886,22 @@ impl TypedNativeFunction {
                 // 40 is the longest string one can get from int->string conversion.
                 ASCII_40,
             ))),
+            UIntToAscii => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::UIntType,
+                    ClarityName::try_from("value".to_owned()).map_err(|_| {
+                        CheckErrors::Expects(
+                            "FAIL: ClarityName failed to accept default arg name".into(),
+                        )
+                    })?,
+                )],
+                // 39 is the longest string one can get from uint->string conversion.
+                returns: TypeSignature::bound_string_ascii_type(39)?,
+            }))),
+            Log2UInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::OptionalType(Box::new(TypeSignature::UIntType)),
+            ))),
             IntToUtf8 => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
                 vec![TypeSignature::IntType, TypeSignature::UIntType],
                 // 40 is the longest string one can get from int->string conversion.
@@ -887,6 +972,9 @@ impl TypedNativeFunction {
                 returns: TypeSignature::UIntType,
             }))),
             PrincipalConstruct => Special(SpecialNativeFunction(&check_principal_construct)),
+            ContractPrincipalConstruct => {
+                Special(SpecialNativeFunction(&check_contract_principal_construct))
+            }
             PrincipalDestruct => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![FunctionArg::new(
                     TypeSignature::PrincipalType,
@@ -945,6 +1033,48 @@ impl TypedNativeFunction {
                 })?
                 .into(),
             }))),
+            StxGetAccountInfo => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("owner".to_owned()).map_err(|_| {
+                        CheckErrors::Expects(
+                            "FAIL: ClarityName failed to accept default arg name".into(),
+                        )
+                    })?,
+                )],
+                returns: TupleTypeSignature::try_from(vec![
+                    ("unlocked".into(), TypeSignature::UIntType),
+                    ("locked".into(), TypeSignature::UIntType),
+                    ("unlock-height".into(), TypeSignature::UIntType),
+                    ("pox-version".into(), TypeSignature::UIntType),
+                ])
+                .map_err(|_| {
+                    CheckErrors::Expects(
+                        "FAIL: StxGetAccountInfo failed to initialize type signature".into(),
+                    )
+                })?
+                .into(),
+            }))),
+            StxGetDelegationInfo => {
+                Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                    args: vec![FunctionArg::new(
+                        TypeSignature::PrincipalType,
+                        ClarityName::try_from("owner".to_owned()).map_err(|_| {
+                            CheckErrors::Expects(
+                                "FAIL: ClarityName failed to accept default arg name".into(),
+                            )
+                        })?,
+                    )],
+                    returns: TypeSignature::new_option(stx_delegation_info_type()?).map_err(
+                        |_| {
+                            CheckErrors::Expects(
+                                "FAIL: StxGetDelegationInfo failed to initialize type signature"
+                                    .into(),
+                            )
+                        },
+                    )?,
+                })))
+            }
             StxBurn => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
                 args: vec![
                     FunctionArg::new(
@@ -995,6 +1125,7 @@ impl TypedNativeFunction {
             Fold => Special(SpecialNativeFunction(&sequences::check_special_fold)),
             Append => Special(SpecialNativeFunction(&sequences::check_special_append)),
             Concat => Special(SpecialNativeFunction(&sequences::check_special_concat)),
+            ConcatMany => Special(SpecialNativeFunction(&sequences::check_special_concat_many)),
             AsMaxLen => Special(SpecialNativeFunction(&sequences::check_special_as_max_len)),
             Len => Special(SpecialNativeFunction(&sequences::check_special_len)),
             ElementAt | ElementAtAlias => {
@@ -20,6 +20,7 @@ use super::{
     check_argument_count, check_arguments_at_least, no_type, CheckError, CheckErrors, TypeChecker,
     TypeResult,
 };
+use crate::vm::analysis::errors::CheckWarnings;
 use crate::vm::analysis::type_checker::contexts::TypingContext;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::costs::{analysis_typecheck_cost, cost_functions, runtime_cost};
@@ -192,6 +193,15 @@ pub fn check_special_unwrap_or_ret(
 
     checker.track_return_type(on_error)?;
 
+    if let TypeSignature::ResponseType(ref response_type) = input {
+        if response_type.1.is_no_type() {
+            checker.add_strict_warning(
+                CheckWarnings::UnreachableUnwrapReturn("unwrap!".into()),
+                &args[0],
+            );
+        }
+    }
+
     inner_unwrap(input, checker)
 }
 
@@ -207,6 +217,15 @@ pub fn check_special_unwrap_err_or_ret(
 
     checker.track_return_type(on_error)?;
 
+    if let TypeSignature::ResponseType(ref response_type) = input {
+        if response_type.0.is_no_type() {
+            checker.add_strict_warning(
+                CheckWarnings::UnreachableUnwrapReturn("unwrap-err!".into()),
+                &args[0],
+            );
+        }
+    }
+
     inner_unwrap_err(input, checker)
 }
 
@@ -272,6 +291,21 @@ pub fn check_special_unwrap_err(
     inner_unwrap_err(input, checker)
 }
 
+/// Warn if a `match` arm does nothing but panic with `unwrap-panic` or `unwrap-err-panic`
+fn check_panicking_arm(checker: &mut TypeChecker, arm_name: &str, arm: &SymbolicExpression) {
+    let panics = arm
+        .match_list()
+        .and_then(|list| list.first())
+        .and_then(|function| function.match_atom())
+        .map(|function| {
+            function.as_str() == "unwrap-panic" || function.as_str() == "unwrap-err-panic"
+        })
+        .unwrap_or(false);
+    if panics {
+        checker.add_strict_warning(CheckWarnings::PanickingMatchArm(arm_name.into()), arm);
+    }
+}
+
 fn eval_with_new_binding(
     body: &SymbolicExpression,
     bind_name: ClarityName,
@@ -304,6 +338,15 @@ fn check_special_match_opt(
     args: &[SymbolicExpression],
     context: &TypingContext,
 ) -> TypeResult {
+    if args.len() == 2 && args[0].match_atom().is_some() {
+        // (match input some-name if-some-expression) -- the `none` arm is absent
+        if checker.non_exhaustive_match_is_error {
+            Err(CheckErrors::BadMatchOptionSyntax(Box::new(
+                CheckErrors::NonExhaustiveMatch("none".into()),
+            )))?;
+        }
+        checker.add_warning(CheckWarnings::NonExhaustiveMatch("none".into()), &args[1]);
+    }
     if args.len() != 3 {
         Err(CheckErrors::BadMatchOptionSyntax(Box::new(
             CheckErrors::IncorrectArgumentCount(4, args.len() + 1),
@@ -324,6 +367,7 @@ fn check_special_match_opt(
     let some_branch_type =
         eval_with_new_binding(some_branch, bind_name, option_type, checker, context)?;
     let none_branch_type = checker.type_check(none_branch, context)?;
+    check_panicking_arm(checker, "none", none_branch);
 
     analysis_typecheck_cost(checker, &some_branch_type, &none_branch_type)?;
 
@@ -341,6 +385,15 @@ fn check_special_match_resp(
     args: &[SymbolicExpression],
     context: &TypingContext,
 ) -> TypeResult {
+    if (args.len() == 2 || args.len() == 3) && args[0].match_atom().is_some() {
+        // (match input ok-name if-ok-expression [err-name]) -- the `err` arm is absent
+        if checker.non_exhaustive_match_is_error {
+            Err(CheckErrors::BadMatchResponseSyntax(Box::new(
+                CheckErrors::NonExhaustiveMatch("err".into()),
+            )))?;
+        }
+        checker.add_warning(CheckWarnings::NonExhaustiveMatch("err".into()), &args[1]);
+    }
     if args.len() != 4 {
         Err(CheckErrors::BadMatchResponseSyntax(Box::new(
             CheckErrors::IncorrectArgumentCount(5, args.len() + 1),
@@ -367,6 +420,7 @@ fn check_special_match_resp(
     let ok_branch_type = eval_with_new_binding(ok_branch, ok_bind_name, ok_type, checker, context)?;
     let err_branch_type =
         eval_with_new_binding(err_branch, err_bind_name, err_type, checker, context)?;
+    check_panicking_arm(checker, "err", err_branch);
 
     analysis_typecheck_cost(checker, &ok_branch_type, &err_branch_type)?;
 
@@ -20,12 +20,13 @@ use stacks_common::types::StacksEpochId;
 
 use super::{SimpleNativeFunction, TypedNativeFunction};
 use crate::vm::analysis::type_checker::v2_1::{
-    check_argument_count, check_arguments_at_least, CheckErrors, CheckResult, TypeChecker,
-    TypeResult, TypingContext,
+    check_argument_count, check_arguments_at_least, check_arguments_at_most, CheckErrors,
+    CheckResult, TypeChecker, TypeResult, TypingContext,
 };
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::costs::{analysis_typecheck_cost, cost_functions, runtime_cost};
 use crate::vm::functions::NativeFunctions;
+use crate::vm::functions::MAX_CONCAT_MANY_ARGS;
 use crate::vm::representations::{SymbolicExpression, SymbolicExpressionType};
 pub use crate::vm::types::signatures::{BufferLength, ListTypeData, StringUTF8Length, BUFF_1};
 use crate::vm::types::SequenceSubtype::*;
@@ -210,7 +211,13 @@ pub fn check_special_concat(
 
     analysis_typecheck_cost(checker, &lhs_type, &rhs_type)?;
 
-    let res = match (&lhs_type, &rhs_type) {
+    concat_type(&lhs_type, &rhs_type)
+}
+
+/// The type of the sequence produced by concatenating sequences of types `lhs_type` and
+/// `rhs_type`
+fn concat_type(lhs_type: &TypeSignature, rhs_type: &TypeSignature) -> TypeResult {
+    let res = match (lhs_type, rhs_type) {
         (TypeSignature::SequenceType(lhs_seq), TypeSignature::SequenceType(rhs_seq)) => {
             match (lhs_seq, rhs_seq) {
                 (ListType(lhs_list), ListType(rhs_list)) => {
@@ -257,6 +264,32 @@ pub fn check_special_concat(
     Ok(res)
 }
 
+pub fn check_special_concat_many(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_arguments_at_least(1, args)?;
+    check_arguments_at_most(MAX_CONCAT_MANY_ARGS, args)?;
+
+    let mut res = checker.type_check(&args[0], context)?;
+
+    runtime_cost(ClarityCostFunction::AnalysisIterableFunc, checker, 0)?;
+
+    if args.len() == 1 {
+        if !matches!(res, TypeSignature::SequenceType(_)) {
+            return Err(CheckErrors::ExpectedSequence(res).into());
+        }
+        return Ok(res);
+    }
+    for arg in args[1..].iter() {
+        let rhs_type = checker.type_check(arg, context)?;
+        analysis_typecheck_cost(checker, &res, &rhs_type)?;
+        res = concat_type(&res, &rhs_type)?;
+    }
+    Ok(res)
+}
+
 pub fn check_special_append(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
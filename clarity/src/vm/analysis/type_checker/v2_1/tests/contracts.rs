@@ -2739,7 +2739,9 @@ fn clarity_trait_experiments_downcast_literal_2(
         })
         .unwrap_err();
     match version {
-        ClarityVersion::Clarity2 => assert!(err.starts_with("ExpectedCallableType(PrincipalType)")),
+        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => {
+            assert!(err.starts_with("ExpectedCallableType(PrincipalType)"))
+        }
         ClarityVersion::Clarity1 => {
             assert!(err.starts_with("TraitReferenceUnknown(\"principal-value\")"))
         }
@@ -2934,7 +2936,9 @@ fn clarity_trait_experiments_trait_cast_incompatible(
                 assert!(err.starts_with("TypeError(CallableType(Trait(TraitIdentifier"))
             }
         }
-        ClarityVersion::Clarity2 => assert!(err.starts_with("IncompatibleTrait")),
+        ClarityVersion::Clarity2 | ClarityVersion::Clarity3 => {
+            assert!(err.starts_with("IncompatibleTrait"))
+        }
     }
 }
 
@@ -412,6 +412,49 @@ fn test_impl_trait(#[case] version: ClarityVersion, #[case] epoch: StacksEpochId
     }
 }
 
+#[test]
+fn test_stx_account_info_ops() {
+    let good = [
+        "(stx-account-info 'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G)",
+        "(stx-delegation-info tx-sender)",
+    ];
+    let expected = [
+        "(tuple (locked uint) (pox-version uint) (unlock-height uint) (unlocked uint))",
+        "(optional (tuple (amount-ustx uint) (delegated-to principal) (pox-addr (optional (tuple (hashbytes (buff 32)) (version (buff 1))))) (until-burn-ht (optional uint))))",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        let type_sig =
+            mem_run_analysis(good_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+                .map(|(type_sig_opt, _)| type_sig_opt.unwrap())
+                .unwrap();
+        assert_eq!(expected, &format!("{}", type_sig));
+
+        // not available before Clarity 3
+        let err = mem_run_analysis(good_test, ClarityVersion::Clarity2, StacksEpochId::latest())
+            .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+    }
+
+    let bad = [
+        "(stx-account-info true)",
+        "(stx-account-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        "(stx-delegation-info u1)",
+        "(stx-delegation-info)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(PrincipalType, BoolType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(PrincipalType, UIntType),
+        CheckErrors::IncorrectArgumentCount(1, 0),
+    ];
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        let err = mem_run_analysis(bad_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+            .unwrap_err();
+        assert_eq!(expected, &err.err);
+    }
+}
+
 #[test]
 fn test_stx_ops() {
     let good = [
@@ -629,6 +672,16 @@ fn test_destructuring_opts(#[case] version: ClarityVersion, #[case] epoch: Stack
                 5, 3,
             ))),
         ),
+        (
+            "(match (ok 1) inner-value (+ 1 inner-value) err-val)",
+            CheckErrors::BadMatchResponseSyntax(Box::new(CheckErrors::IncorrectArgumentCount(
+                5, 4,
+            ))),
+        ),
+        (
+            "(match (some 1) inner-value)",
+            CheckErrors::BadMatchOptionSyntax(Box::new(CheckErrors::IncorrectArgumentCount(4, 2))),
+        ),
         (
             "(match (ok 1) 1 (+ 1 1) err-val (+ 2 err-val))",
             CheckErrors::BadMatchResponseSyntax(Box::new(CheckErrors::ExpectedName)),
@@ -822,6 +875,129 @@ fn test_bitwise_bad_checks() {
     }
 }
 
+#[test]
+fn test_bitwise_rotate_and_log2_uint() {
+    let good = [
+        "(bit-rotate-left 1 u2)",
+        "(bit-rotate-right u1 u2)",
+        "(log2-uint 8)",
+        "(log2-uint u8)",
+    ];
+    let expected = ["int", "uint", "(optional uint)", "(optional uint)"];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        let type_sig =
+            mem_run_analysis(good_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+                .map(|(type_sig_opt, _)| type_sig_opt.unwrap())
+                .unwrap();
+        assert_eq!(expected, &format!("{}", type_sig));
+
+        // not available before Clarity 3, or before epoch 2.1
+        let err = mem_run_analysis(good_test, ClarityVersion::Clarity2, StacksEpochId::latest())
+            .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+        let err = mem_run_analysis(
+            good_test,
+            ClarityVersion::Clarity3,
+            StacksEpochId::Epoch2_05,
+        )
+        .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+    }
+
+    let bad = [
+        "(bit-rotate-left 1)",
+        "(bit-rotate-right true u1)",
+        "(bit-rotate-left 2 1)",
+        "(log2-uint 1 2)",
+        "(log2-uint \"hello\")",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::UnionTypeError(
+            vec![IntType, UIntType],
+            SequenceType(StringType(ASCII(BufferLength::try_from(5u32).unwrap()))),
+        ),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        let err = mem_run_analysis(bad_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+            .unwrap_err();
+        assert_eq!(expected, &err.err);
+    }
+}
+
+#[test]
+fn test_uint_to_ascii_and_concat_many() {
+    let good = [
+        "(uint-to-ascii u1)",
+        "(concat-many (list 1 2) (list 3) (list 4 5))",
+        "(concat-many 0x01)",
+        "(concat-many 0x01 0x0203 0x04)",
+        r#"(concat-many "balance: " (uint-to-ascii u100) " STX")"#,
+        r#"(concat-many u"a" u"b")"#,
+    ];
+    let expected = [
+        "(string-ascii 39)",
+        "(list 5 int)",
+        "(buff 1)",
+        "(buff 4)",
+        "(string-ascii 52)",
+        "(string-utf8 2)",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        let type_sig =
+            mem_run_analysis(good_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+                .map(|(type_sig_opt, _)| type_sig_opt.unwrap())
+                .unwrap();
+        assert_eq!(expected, &format!("{}", type_sig));
+
+        // not available before Clarity 3, or before epoch 2.1
+        let err = mem_run_analysis(good_test, ClarityVersion::Clarity2, StacksEpochId::latest())
+            .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+        let err = mem_run_analysis(
+            good_test,
+            ClarityVersion::Clarity3,
+            StacksEpochId::Epoch2_05,
+        )
+        .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+    }
+
+    let bad = [
+        "(uint-to-ascii 1)",
+        "(uint-to-ascii)",
+        "(concat-many)",
+        "(concat-many 1)",
+        "(concat-many 0x01 \"a\")",
+        "(concat-many (list 1) (list u1))",
+        "(concat-many 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(1, 0),
+        CheckErrors::RequiresAtLeastArguments(1, 0),
+        CheckErrors::ExpectedSequence(IntType),
+        CheckErrors::TypeError(
+            BUFF_1.clone(),
+            TypeSignature::bound_string_ascii_type(1).unwrap(),
+        ),
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::RequiresAtMostArguments(16, 17),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        let err = mem_run_analysis(bad_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+            .unwrap_err();
+        assert_eq!(expected, &err.err);
+    }
+}
+
 #[test]
 fn test_simple_arithmetic_checks() {
     let good = [
@@ -3347,6 +3523,52 @@ fn test_principal_construct() {
     }
 }
 
+#[test]
+fn test_contract_principal_construct() {
+    let expected_type =
+        "(response principal (tuple (error_code uint) (value (optional principal))))";
+    let good = [
+        r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo")"#,
+        r#"(contract-principal-construct? tx-sender "foo")"#,
+    ];
+    for good_test in good.iter() {
+        let type_sig =
+            mem_run_analysis(good_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+                .map(|(type_sig_opt, _)| type_sig_opt.unwrap())
+                .unwrap();
+        assert_eq!(expected_type, &format!("{}", type_sig));
+
+        // not available before Clarity 3
+        let err = mem_run_analysis(good_test, ClarityVersion::Clarity2, StacksEpochId::latest())
+            .unwrap_err();
+        assert!(matches!(err.err, CheckErrors::UnknownFunction(_)));
+    }
+
+    let bad = [
+        r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)"#,
+        r#"(contract-principal-construct? 0x1a "foo")"#,
+        r#"(contract-principal-construct? tx-sender u1)"#,
+        r#"(contract-principal-construct? tx-sender "foooooooooooooooooooooooooooooooooooooooo")"#,
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::TypeError(PrincipalType, BUFF_1.clone()),
+        CheckErrors::TypeError(
+            TypeSignature::contract_name_string_ascii_type().unwrap(),
+            UIntType,
+        ),
+        CheckErrors::TypeError(
+            TypeSignature::contract_name_string_ascii_type().unwrap(),
+            TypeSignature::bound_string_ascii_type(41).unwrap(),
+        ),
+    ];
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        let err = mem_run_analysis(bad_test, ClarityVersion::Clarity3, StacksEpochId::latest())
+            .unwrap_err();
+        assert_eq!(expected, &err.err);
+    }
+}
+
 #[test]
 fn test_trait_args() {
     let good = [
@@ -19,13 +19,19 @@ use std::collections::{BTreeMap, BTreeSet, HashMap};
 use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::analysis_db::AnalysisDatabase;
-use crate::vm::analysis::contract_interface_builder::ContractInterface;
-use crate::vm::analysis::errors::{CheckErrors, CheckResult};
+use crate::vm::analysis::contract_interface_builder::{
+    ContractInterface, ContractInterfaceFunctionAccess,
+};
+use crate::vm::analysis::errors::{CheckErrors, CheckResult, CheckWarning};
 use crate::vm::analysis::type_checker::contexts::TypeMap;
 use crate::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use crate::vm::representations::TraitDefinition;
 use crate::vm::types::signatures::FunctionSignature;
-use crate::vm::types::{FunctionType, QualifiedContractIdentifier, TraitIdentifier, TypeSignature};
-use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression};
+use crate::vm::types::{
+    FunctionArg, FunctionType, PrincipalData, QualifiedContractIdentifier, TraitIdentifier,
+    TypeSignature, Value,
+};
+use crate::vm::{ClarityName, ClarityVersion, SymbolicExpression, SymbolicExpressionType};
 
 const DESERIALIZE_FAIL_MESSAGE: &str =
     "PANIC: Failed to deserialize bad database data in contract analysis.";
@@ -40,6 +46,15 @@ pub trait AnalysisPass {
     ) -> CheckResult<()>;
 }
 
+/// The type signature of a function defined by a contract, with its arguments named and in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DefinedFunctionSignature {
+    pub name: ClarityName,
+    pub access: ContractInterfaceFunctionAccess,
+    pub args: Vec<FunctionArg>,
+    pub returns: TypeSignature,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ContractAnalysis {
     pub contract_identifier: QualifiedContractIdentifier,
@@ -63,6 +78,33 @@ pub struct ContractAnalysis {
     pub type_map: Option<TypeMap>,
     #[serde(skip)]
     pub cost_track: Option<LimitedCostTracker>,
+    /// Non-fatal issues found during analysis. These are not stored with the contract.
+    #[serde(skip)]
+    pub warnings: Vec<CheckWarning>,
+    /// Lower bound on the call stack depth needed by each function. Not stored with the contract.
+    #[serde(skip)]
+    pub call_depths: BTreeMap<ClarityName, u64>,
+    /// Upper bound on the execution cost of each function that has one. Not stored with the
+    /// contract.
+    #[serde(skip)]
+    pub cost_bounds: BTreeMap<ClarityName, ExecutionCost>,
+    /// The warnings each pass of the `AnalysisPassRegistry` reported, by the name it was
+    /// registered with. These are also in `warnings`. Not stored with the contract.
+    #[serde(skip)]
+    pub custom_pass_warnings: BTreeMap<String, Vec<CheckWarning>>,
+    /// Whether each function defined by the contract is read-only, i.e. never writes. Not stored
+    /// with the contract.
+    #[serde(skip)]
+    pub function_purity: BTreeMap<ClarityName, bool>,
+    /// For each function that writes, the calls from it that lead to its first writing
+    /// operation, e.g. `["f", "g", "map-set"]`. Also recorded if the read-only check fails. Not
+    /// stored with the contract.
+    #[serde(skip)]
+    pub write_call_chains: BTreeMap<ClarityName, Vec<String>>,
+    /// The other contracts this contract refers to, as found by the type checker. Stored apart
+    /// from the contract's analysis (see `AnalysisDatabase::get_dependencies`).
+    #[serde(skip)]
+    pub dependencies: BTreeSet<QualifiedContractIdentifier>,
 }
 
 impl ContractAnalysis {
@@ -92,6 +134,13 @@ impl ContractAnalysis {
             is_cost_contract_eligible: false,
             epoch,
             clarity_version,
+            warnings: vec![],
+            call_depths: BTreeMap::new(),
+            cost_bounds: BTreeMap::new(),
+            custom_pass_warnings: BTreeMap::new(),
+            function_purity: BTreeMap::new(),
+            write_call_chains: BTreeMap::new(),
+            dependencies: BTreeSet::new(),
         }
     }
 
@@ -161,6 +210,18 @@ impl ContractAnalysis {
         self.implemented_traits.insert(trait_identifier);
     }
 
+    pub fn add_dependency(&mut self, contract_identifier: QualifiedContractIdentifier) {
+        if contract_identifier != self.contract_identifier {
+            self.dependencies.insert(contract_identifier);
+        }
+    }
+
+    /// The other contracts this contract refers to: the targets of its static `contract-call?`s,
+    /// and the contracts of the traits it uses or implements.
+    pub fn get_dependencies(&self) -> &BTreeSet<QualifiedContractIdentifier> {
+        &self.dependencies
+    }
+
     pub fn get_public_function_type(&self, name: &str) -> Option<&FunctionType> {
         self.public_function_types.get(name)
     }
@@ -173,6 +234,41 @@ impl ContractAnalysis {
         self.private_function_types.get(name)
     }
 
+    /// The signatures of the public, read-only and private functions of the contract, in that
+    /// order and then by name, as recorded by the type checker. These come from the function
+    /// types, so they are also available for an analysis loaded from the database.
+    pub fn function_signatures(&self) -> Vec<DefinedFunctionSignature> {
+        let functions = [
+            (
+                &self.public_function_types,
+                ContractInterfaceFunctionAccess::public,
+            ),
+            (
+                &self.read_only_function_types,
+                ContractInterfaceFunctionAccess::read_only,
+            ),
+            (
+                &self.private_function_types,
+                ContractInterfaceFunctionAccess::private,
+            ),
+        ];
+        let mut signatures = vec![];
+        for (function_types, access) in functions.iter() {
+            for (name, function_type) in function_types.iter() {
+                // functions defined by a contract always have a fixed signature
+                if let FunctionType::Fixed(function) = function_type {
+                    signatures.push(DefinedFunctionSignature {
+                        name: name.clone(),
+                        access: access.clone(),
+                        args: function.args.clone(),
+                        returns: function.returns.clone(),
+                    });
+                }
+            }
+        }
+        signatures
+    }
+
     pub fn get_map_type(&self, name: &str) -> Option<&(TypeSignature, TypeSignature)> {
         self.map_types.get(name)
     }
@@ -268,16 +364,80 @@ impl ContractAnalysis {
     }
 }
 
+pub(crate) fn collect_dependencies(
+    expr: &SymbolicExpression,
+    dependencies: &mut BTreeSet<QualifiedContractIdentifier>,
+) {
+    match &expr.expr {
+        SymbolicExpressionType::AtomValue(Value::Principal(PrincipalData::Contract(contract)))
+        | SymbolicExpressionType::LiteralValue(Value::Principal(PrincipalData::Contract(
+            contract,
+        ))) => {
+            dependencies.insert(contract.clone());
+        }
+        SymbolicExpressionType::Field(trait_identifier)
+        | SymbolicExpressionType::TraitReference(_, TraitDefinition::Imported(trait_identifier)) => {
+            dependencies.insert(trait_identifier.contract_identifier.clone());
+        }
+        SymbolicExpressionType::List(exprs) => {
+            for expr in exprs.iter() {
+                collect_dependencies(expr, dependencies);
+            }
+        }
+        _ => {}
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::vm::analysis::ContractAnalysis;
+    use crate::vm::analysis::{mem_type_check, ContractAnalysis};
     use crate::vm::costs::LimitedCostTracker;
+    use crate::vm::database::{ClarityDeserializable, ClaritySerializable};
     use crate::vm::types::signatures::CallableSubtype;
     use crate::vm::types::{
         FixedFunction, FunctionArg, QualifiedContractIdentifier, StandardPrincipalData,
     };
 
+    #[test]
+    fn test_function_signatures() {
+        let contract = "(define-public (transfer (amount uint) (recipient principal))
+                (ok (is-eq recipient tx-sender)))
+            (define-read-only (get-balance (who principal)) u0)
+            (define-private (helper) (ok u1))";
+        let (_, analysis) =
+            mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::Epoch21).unwrap();
+        let signatures = analysis.function_signatures();
+        let names: Vec<_> = signatures
+            .iter()
+            .map(|signature| (signature.name.as_str(), signature.access.clone()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("transfer", ContractInterfaceFunctionAccess::public),
+                ("get-balance", ContractInterfaceFunctionAccess::read_only),
+                ("helper", ContractInterfaceFunctionAccess::private),
+            ]
+        );
+        assert_eq!(
+            signatures[0].args,
+            vec![
+                FunctionArg::new(TypeSignature::UIntType, "amount".into()),
+                FunctionArg::new(TypeSignature::PrincipalType, "recipient".into()),
+            ]
+        );
+        assert_eq!(
+            signatures[0].returns,
+            TypeSignature::new_response(TypeSignature::BoolType, TypeSignature::NoType).unwrap()
+        );
+        assert!(signatures[2].args.is_empty());
+
+        // The signatures survive the analysis being stored
+        let loaded = ContractAnalysis::deserialize(&analysis.serialize()).unwrap();
+        assert_eq!(loaded.function_signatures(), signatures);
+    }
+
     #[test]
     fn test_canonicalize_contract_analysis() {
         let mut contract_analysis = ContractAnalysis::new(
@@ -0,0 +1,146 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use stacks_common::types::StacksEpochId;
+
+use super::errors::{CheckResult, CheckWarning, CheckWarnings};
+use super::AnalysisDatabase;
+use crate::vm::analysis::types::{AnalysisPass, ContractAnalysis};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::representations::SymbolicExpressionType::{Atom, List};
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+
+#[cfg(test)]
+mod tests;
+
+/// `UnusedDefinitionChecker` looks for private functions, constants, data vars, and maps that
+/// are defined but never referenced anywhere in the contract, and records a `CheckWarning` on
+/// the contract analysis for each of them.
+///
+/// This pass never fails: unused definitions are legal Clarity. Any symbol with the same name as
+/// a definition counts as a reference to it (e.g. a tuple key), so the pass may miss some unused
+/// definitions, but it never warns about a used one.
+pub struct UnusedDefinitionChecker {
+    /// Definitions under consideration, in contract order
+    definitions: Vec<(ClarityName, CheckWarnings, SymbolicExpression)>,
+    /// Every symbol referenced outside of a definition's own name
+    references: HashSet<ClarityName>,
+}
+
+impl AnalysisPass for UnusedDefinitionChecker {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        let mut checker = UnusedDefinitionChecker::new();
+        for expr in contract_analysis.expressions.iter() {
+            checker.check_top_level_expression(expr);
+        }
+        contract_analysis.warnings.extend(checker.into_warnings());
+        Ok(())
+    }
+}
+
+impl UnusedDefinitionChecker {
+    fn new() -> UnusedDefinitionChecker {
+        UnusedDefinitionChecker {
+            definitions: vec![],
+            references: HashSet::new(),
+        }
+    }
+
+    fn check_top_level_expression(&mut self, expr: &SymbolicExpression) {
+        let define_type = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(define_type)) => define_type,
+            // not a definition, or a malformed one (which the type checker would have rejected)
+            _ => return self.add_references(expr),
+        };
+        match define_type {
+            DefineFunctionsParsed::PrivateFunction { signature, body } => {
+                if let Some(name) = signature.first().and_then(|name| name.match_atom()) {
+                    self.add_definition(
+                        name,
+                        CheckWarnings::UnusedPrivateFunction(name.to_string()),
+                        expr,
+                    );
+                }
+                self.add_references(body);
+            }
+            DefineFunctionsParsed::Constant { name, value } => {
+                self.add_definition(name, CheckWarnings::UnusedConstant(name.to_string()), expr);
+                self.add_references(value);
+            }
+            DefineFunctionsParsed::PersistedVariable { name, initial, .. } => {
+                self.add_definition(name, CheckWarnings::UnusedDataVar(name.to_string()), expr);
+                self.add_references(initial);
+            }
+            DefineFunctionsParsed::Map { name, .. } => {
+                self.add_definition(name, CheckWarnings::UnusedMap(name.to_string()), expr);
+            }
+            DefineFunctionsParsed::ReadOnlyFunction { body, .. }
+            | DefineFunctionsParsed::PublicFunction { body, .. } => {
+                self.add_references(body);
+            }
+            DefineFunctionsParsed::BoundedFungibleToken { max_supply, .. } => {
+                self.add_references(max_supply);
+            }
+            DefineFunctionsParsed::NonFungibleToken { .. }
+            | DefineFunctionsParsed::UnboundedFungibleToken { .. }
+            | DefineFunctionsParsed::Trait { .. }
+            | DefineFunctionsParsed::UseTrait { .. }
+            | DefineFunctionsParsed::ImplTrait { .. } => {}
+        }
+    }
+
+    fn add_definition(
+        &mut self,
+        name: &ClarityName,
+        warning: CheckWarnings,
+        expr: &SymbolicExpression,
+    ) {
+        self.definitions.push((name.clone(), warning, expr.clone()));
+    }
+
+    fn add_references(&mut self, expr: &SymbolicExpression) {
+        match &expr.expr {
+            Atom(name) => {
+                self.references.insert(name.clone());
+            }
+            List(exprs) => {
+                for expr in exprs.iter() {
+                    self.add_references(expr);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn into_warnings(self) -> Vec<CheckWarning> {
+        let references = self.references;
+        self.definitions
+            .into_iter()
+            .filter(|(name, ..)| !references.contains(name))
+            .map(|(_, warning, expr)| {
+                let mut warning = CheckWarning::new(warning);
+                warning.set_expression(&expr);
+                warning
+            })
+            .collect()
+    }
+}
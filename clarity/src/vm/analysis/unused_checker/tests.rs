@@ -0,0 +1,130 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::errors::CheckWarnings;
+use crate::vm::analysis::run_analysis;
+use crate::vm::analysis::type_checker::v2_1::tests::mem_type_check;
+use crate::vm::ast::parse;
+use crate::vm::costs::LimitedCostTracker;
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::diagnostic::Level;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::ClarityVersion;
+
+fn unused_definitions(contract: &str) -> Vec<CheckWarnings> {
+    let (_, analysis) = mem_type_check(contract).unwrap();
+    analysis
+        .warnings
+        .into_iter()
+        .map(|warning| {
+            assert_eq!(warning.diagnostic.level, Level::Warning);
+            warning.warning
+        })
+        .collect()
+}
+
+#[test]
+fn test_unused_definitions() {
+    let contract = "(define-constant unused-const u1)
+        (define-data-var unused-var uint u0)
+        (define-map unused-map uint uint)
+        (define-private (unused-fn) u1)
+        (define-public (foo) (ok true))";
+
+    assert_eq!(
+        unused_definitions(contract),
+        vec![
+            CheckWarnings::UnusedConstant("unused-const".into()),
+            CheckWarnings::UnusedDataVar("unused-var".into()),
+            CheckWarnings::UnusedMap("unused-map".into()),
+            CheckWarnings::UnusedPrivateFunction("unused-fn".into()),
+        ]
+    );
+}
+
+#[test]
+fn test_used_definitions() {
+    let contract = "(define-constant max-count u10)
+        (define-data-var count uint u0)
+        (define-map counts principal uint)
+        (define-private (add-one (x uint)) (+ x u1))
+        (define-private (is-small (x uint)) (< x max-count))
+        (define-public (inc)
+          (begin
+            (var-set count (add-one (var-get count)))
+            (map-set counts tx-sender (var-get count))
+            (ok (filter is-small (list u1 u20)))))";
+
+    assert_eq!(unused_definitions(contract), vec![]);
+}
+
+#[test]
+fn test_definitions_used_only_by_definitions() {
+    // definitions referenced from other definitions (even unused ones, and even in a
+    // constant or data var initializer) count as used
+    let contract = "(define-constant base u1)
+        (define-data-var start uint base)
+        (define-private (helper) (var-get start))
+        (define-private (unused-caller) (helper))";
+
+    assert_eq!(
+        unused_definitions(contract),
+        vec![CheckWarnings::UnusedPrivateFunction("unused-caller".into())]
+    );
+}
+
+#[test]
+fn test_unused_definitions_not_checked_on_deploy() {
+    let contract_identifier = QualifiedContractIdentifier::local("unused").unwrap();
+    let mut contract = parse(
+        &contract_identifier,
+        "(define-constant unused-const u1) (define-public (foo) (ok true))",
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let mut marf = MemoryBackingStore::new();
+    let analysis = run_analysis(
+        &contract_identifier,
+        &mut contract,
+        &mut marf.as_analysis_db(),
+        false,
+        LimitedCostTracker::new_free(),
+        StacksEpochId::Epoch21,
+        ClarityVersion::Clarity2,
+    )
+    .unwrap();
+    assert!(analysis.warnings.is_empty());
+}
+
+#[cfg(feature = "developer-mode")]
+#[test]
+fn test_unused_definition_spans() {
+    let contract = "(define-public (foo) (ok true))
+(define-private (unused-fn) u1)";
+    let (_, analysis) = mem_type_check(contract).unwrap();
+
+    assert_eq!(analysis.warnings.len(), 1);
+    let span = &analysis.warnings[0].diagnostic.spans[0];
+    assert_eq!(span.start_line, 2);
+    assert_eq!(span.start_column, 1);
+    assert!(analysis.warnings[0]
+        .diagnostic
+        .message
+        .contains("'unused-fn' is never called"));
+}
@@ -0,0 +1,358 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Checks whether a contract written for one Clarity version could be deployed with an earlier
+//! one, so that deployers can decide whether they can target an older epoch.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::diagnostic::{DiagnosableError, Diagnostic};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::NativeFunctions;
+use crate::vm::representations::SymbolicExpression;
+use crate::vm::representations::SymbolicExpressionType::{Atom, List, TraitReference};
+use crate::vm::variables::NativeVariables;
+use crate::vm::ClarityVersion;
+
+/// A language feature that is not available in every Clarity version
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionedFeature {
+    NativeFunction(String),
+    /// A native variable, such as `chain-id`
+    Keyword(String),
+    /// A trait reference inside a compound type, such as `(list 10 <my-trait>)`
+    NestedTraitReference(String),
+}
+
+/// An expression that uses a feature the target Clarity version does not have
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionIncompatibility {
+    pub feature: VersionedFeature,
+    /// The earliest Clarity version with the feature
+    pub required_version: ClarityVersion,
+    pub expression: SymbolicExpression,
+}
+
+impl DiagnosableError for VersionIncompatibility {
+    fn message(&self) -> String {
+        match &self.feature {
+            VersionedFeature::NativeFunction(name) => {
+                format!("function '{}' requires {}", name, self.required_version)
+            }
+            VersionedFeature::Keyword(name) => {
+                format!("keyword '{}' requires {}", name, self.required_version)
+            }
+            VersionedFeature::NestedTraitReference(name) => format!(
+                "trait reference '{}' inside a compound type requires {}",
+                name, self.required_version
+            ),
+        }
+    }
+
+    fn suggestion(&self) -> Option<String> {
+        None
+    }
+
+    fn code(&self) -> Option<String> {
+        Some("VersionIncompatibility".into())
+    }
+}
+
+impl VersionIncompatibility {
+    pub fn diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::err(self);
+        diagnostic.spans = vec![self.expression.span().clone()];
+        diagnostic
+    }
+}
+
+/// Report every expression of a contract written for `contract_version` that would not be
+/// accepted under `target_version`, in contract order. The contract is expected to pass analysis
+/// under `contract_version`.
+pub fn check_version_downgrade(
+    expressions: &[SymbolicExpression],
+    contract_version: ClarityVersion,
+    target_version: ClarityVersion,
+) -> Vec<VersionIncompatibility> {
+    let mut checker = VersionDowngradeChecker {
+        contract_version,
+        target_version,
+        incompatibilities: vec![],
+    };
+    for expr in expressions.iter() {
+        checker.check_top_level_expression(expr);
+    }
+    checker.incompatibilities
+}
+
+/// Like `check_version_downgrade`, for deploying the contract in `epoch` with its default Clarity
+/// version.
+pub fn check_epoch_downgrade(
+    expressions: &[SymbolicExpression],
+    contract_version: ClarityVersion,
+    epoch: StacksEpochId,
+) -> Vec<VersionIncompatibility> {
+    check_version_downgrade(
+        expressions,
+        contract_version,
+        ClarityVersion::default_for_epoch(epoch),
+    )
+}
+
+struct VersionDowngradeChecker {
+    contract_version: ClarityVersion,
+    target_version: ClarityVersion,
+    incompatibilities: Vec<VersionIncompatibility>,
+}
+
+impl VersionDowngradeChecker {
+    fn report(
+        &mut self,
+        feature: VersionedFeature,
+        required_version: ClarityVersion,
+        expr: &SymbolicExpression,
+    ) {
+        if required_version > self.target_version {
+            self.incompatibilities.push(VersionIncompatibility {
+                feature,
+                required_version,
+                expression: expr.clone(),
+            });
+        }
+    }
+
+    fn check_top_level_expression(&mut self, expr: &SymbolicExpression) {
+        let define_type = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(define_type)) => define_type,
+            _ => return self.check_expression(expr),
+        };
+        match define_type {
+            DefineFunctionsParsed::PrivateFunction { signature, body }
+            | DefineFunctionsParsed::ReadOnlyFunction { signature, body }
+            | DefineFunctionsParsed::PublicFunction { signature, body } => {
+                for arg in signature.iter().skip(1) {
+                    if let Some([_, arg_type]) = arg.match_list() {
+                        self.check_type(arg_type, false);
+                    }
+                }
+                self.check_expression(body);
+            }
+            DefineFunctionsParsed::Trait { functions, .. } => {
+                for signatures in functions.iter().filter_map(|expr| expr.match_list()) {
+                    for signature in signatures.iter().filter_map(|expr| expr.match_list()) {
+                        if let [_, args, returns] = signature {
+                            for arg_type in args.match_list().unwrap_or_default() {
+                                self.check_type(arg_type, false);
+                            }
+                            self.check_type(returns, false);
+                        }
+                    }
+                }
+            }
+            DefineFunctionsParsed::Constant { value, .. } => self.check_expression(value),
+            DefineFunctionsParsed::PersistedVariable { initial, .. } => {
+                self.check_expression(initial)
+            }
+            DefineFunctionsParsed::BoundedFungibleToken { max_supply, .. } => {
+                self.check_expression(max_supply)
+            }
+            DefineFunctionsParsed::NonFungibleToken { .. }
+            | DefineFunctionsParsed::UnboundedFungibleToken { .. }
+            | DefineFunctionsParsed::Map { .. }
+            | DefineFunctionsParsed::UseTrait { .. }
+            | DefineFunctionsParsed::ImplTrait { .. } => {}
+        }
+    }
+
+    /// Trait references are allowed at the top level of a function argument type in every
+    /// version, but only Clarity 2 allows them inside lists, tuples, optionals and responses.
+    fn check_type(&mut self, expr: &SymbolicExpression, nested: bool) {
+        match &expr.expr {
+            TraitReference(name, _) if nested => self.report(
+                VersionedFeature::NestedTraitReference(name.to_string()),
+                ClarityVersion::Clarity2,
+                expr,
+            ),
+            List(exprs) => {
+                for expr in exprs.iter() {
+                    self.check_type(expr, true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_expression(&mut self, expr: &SymbolicExpression) {
+        match &expr.expr {
+            Atom(name) => {
+                if let Some(variable) =
+                    NativeVariables::lookup_by_name_at_version(name, &self.contract_version)
+                {
+                    self.report(
+                        VersionedFeature::Keyword(name.to_string()),
+                        variable.get_version(),
+                        expr,
+                    );
+                }
+            }
+            List(exprs) => {
+                let (function_name, args) = match exprs.split_first() {
+                    Some((head, args)) => (head.match_atom(), args),
+                    None => return,
+                };
+                let function = function_name.and_then(|name| {
+                    NativeFunctions::lookup_by_name_at_version(name, &self.contract_version)
+                });
+                if let (Some(name), Some(function)) = (function_name, function) {
+                    self.report(
+                        VersionedFeature::NativeFunction(name.to_string()),
+                        function.get_version(),
+                        expr,
+                    );
+                }
+                match function {
+                    // tuple keys and the called function's name are not expressions, and may
+                    //  share a name with a native function or keyword
+                    Some(NativeFunctions::TupleCons) => {
+                        for pair in args.iter().filter_map(|arg| arg.match_list()) {
+                            pair.iter()
+                                .skip(1)
+                                .for_each(|arg| self.check_expression(arg))
+                        }
+                    }
+                    Some(NativeFunctions::TupleGet) => args
+                        .iter()
+                        .skip(1)
+                        .for_each(|arg| self.check_expression(arg)),
+                    Some(NativeFunctions::ContractCall) => args
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != 1)
+                        .for_each(|(_, arg)| self.check_expression(arg)),
+                    _ => {
+                        if function_name.is_none() {
+                            self.check_expression(&exprs[0]);
+                        }
+                        args.iter().for_each(|arg| self.check_expression(arg))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::ast::parse;
+    use crate::vm::types::QualifiedContractIdentifier;
+
+    fn incompatibilities(
+        contract: &str,
+        contract_version: ClarityVersion,
+        target_version: ClarityVersion,
+    ) -> Vec<(VersionedFeature, ClarityVersion)> {
+        let expressions = parse(
+            &QualifiedContractIdentifier::transient(),
+            contract,
+            contract_version,
+            StacksEpochId::latest(),
+        )
+        .unwrap();
+        check_version_downgrade(&expressions, contract_version, target_version)
+            .into_iter()
+            .map(|incompatibility| (incompatibility.feature, incompatibility.required_version))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_version_downgrade() {
+        let contract = "(define-trait getter ((get-value () (response uint uint))))
+            (define-read-only (id) chain-id)
+            (define-read-only (first (l (list 10 uint))) (element-at? l u0))
+            (define-public (call-all (getters (list 2 <getter>)) (g <getter>))
+                (contract-call? g get-value))
+            (define-read-only (keys)
+                (get chain-id { chain-id: (+ u1 u2), bit-and: block-height }))
+            (define-read-only (call-other) (contract-call? .other chain-id))";
+
+        assert_eq!(
+            incompatibilities(contract, ClarityVersion::Clarity2, ClarityVersion::Clarity1),
+            vec![
+                (
+                    VersionedFeature::Keyword("chain-id".into()),
+                    ClarityVersion::Clarity2
+                ),
+                (
+                    VersionedFeature::NativeFunction("element-at?".into()),
+                    ClarityVersion::Clarity2
+                ),
+                (
+                    VersionedFeature::NestedTraitReference("getter".into()),
+                    ClarityVersion::Clarity2
+                ),
+            ]
+        );
+        assert!(
+            incompatibilities(contract, ClarityVersion::Clarity2, ClarityVersion::Clarity2)
+                .is_empty()
+        );
+
+        // Only features newer than the target are reported
+        let contract = "(define-read-only (f) (bit-rotate-left (bit-and u1 u2) u1))";
+        assert_eq!(
+            incompatibilities(contract, ClarityVersion::Clarity3, ClarityVersion::Clarity2),
+            vec![(
+                VersionedFeature::NativeFunction("bit-rotate-left".into()),
+                ClarityVersion::Clarity3
+            )]
+        );
+        assert_eq!(
+            incompatibilities(contract, ClarityVersion::Clarity3, ClarityVersion::Clarity1).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_check_epoch_downgrade() {
+        let expressions = parse(
+            &QualifiedContractIdentifier::transient(),
+            "(define-read-only (sponsor) tx-sponsor?)",
+            ClarityVersion::Clarity2,
+            StacksEpochId::latest(),
+        )
+        .unwrap();
+        let incompatibilities = check_epoch_downgrade(
+            &expressions,
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch2_05,
+        );
+        assert_eq!(incompatibilities.len(), 1);
+        let diagnostic = incompatibilities[0].diagnostic();
+        assert_eq!(
+            diagnostic.message,
+            "keyword 'tx-sponsor?' requires Clarity 2"
+        );
+        assert_eq!(diagnostic.code, Some("VersionIncompatibility".into()));
+        assert!(check_epoch_downgrade(
+            &expressions,
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21
+        )
+        .is_empty());
+    }
+}
@@ -26,11 +26,15 @@ pub mod types;
 use stacks_common::types::StacksEpochId;
 
 use self::definition_sorter::DefinitionSorter;
-use self::errors::ParseResult;
+use self::errors::{ParseError, ParseErrors, ParseResult};
 use self::expression_identifier::ExpressionIdentifier;
 use self::parser::v1::{parse as parse_v1, parse_no_stack_limit as parse_v1_no_stack_limit};
-use self::parser::v2::parse as parse_v2;
-use self::stack_depth_checker::{StackDepthChecker, VaryStackDepthChecker};
+use self::parser::v2::{
+    parse as parse_v2, parse_with_max_nesting_depth as parse_v2_with_max_depth,
+};
+use self::stack_depth_checker::{
+    check_max_depth, check_max_expressions, StackDepthChecker, VaryStackDepthChecker, AST_MAX_DEPTH,
+};
 use self::sugar_expander::SugarExpander;
 use self::traits_resolver::TraitsResolver;
 use self::types::BuildASTPass;
@@ -55,11 +59,61 @@ pub fn parse(
     Ok(ast.expressions)
 }
 
+/// Limits on the size of an AST, for embedders that need to parse contracts larger than a node
+/// accepts, such as generated contracts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct ASTLimits {
+    /// The longest source code, in bytes
+    pub max_source_length: u64,
+    /// The most expressions, counting every expression nested in a list or tuple
+    pub max_expressions: u64,
+    /// The depth lists and tuples must nest less than, like `AST_MAX_DEPTH`. Much deeper than
+    /// `AST_MAX_DEPTH` risks overflowing the stack of the passes that walk the AST.
+    pub max_depth: u64,
+}
+
+impl Default for ASTLimits {
+    /// The limits of `ASTRules::PrecheckSize`
+    fn default() -> Self {
+        ASTLimits {
+            max_source_length: u64::MAX,
+            max_expressions: u64::MAX,
+            max_depth: AST_MAX_DEPTH,
+        }
+    }
+}
+
 // AST parser rulesets to apply.
-define_u8_enum!(ASTRules {
-    Typical = 0,
-    PrecheckSize = 1
-});
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum ASTRules {
+    Typical,
+    PrecheckSize,
+    /// The `PrecheckSize` rules, with limits chosen by the embedder. Nodes never use these, so
+    /// they are never stored.
+    Custom(ASTLimits),
+}
+
+impl ASTRules {
+    /// The rulesets a node can use
+    pub const ALL: &'static [ASTRules] = &[ASTRules::Typical, ASTRules::PrecheckSize];
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            ASTRules::Typical => 0,
+            ASTRules::PrecheckSize => 1,
+            ASTRules::Custom(_) => 2,
+        }
+    }
+
+    /// Custom rules cannot be recovered from their number, since it does not record the limits
+    pub fn from_u8(v: u8) -> Option<Self> {
+        match v {
+            0 => Some(ASTRules::Typical),
+            1 => Some(ASTRules::PrecheckSize),
+            _ => None,
+        }
+    }
+}
 
 /// Parse a program based on which epoch is active
 fn parse_in_epoch(
@@ -110,6 +164,18 @@ pub fn build_ast_with_rules<T: CostTracker>(
             clarity_version,
             epoch,
         ),
+        ASTRules::Custom(_) => {
+            let (contract, _, _) = inner_build_ast(
+                contract_identifier,
+                source_code,
+                cost_track,
+                clarity_version,
+                epoch,
+                ruleset,
+                true,
+            )?;
+            Ok(contract)
+        }
         _ => build_ast_precheck_size(
             contract_identifier,
             source_code,
@@ -183,16 +249,34 @@ fn inner_build_ast<T: CostTracker>(
         _ => None,
     };
 
+    if let ASTRules::Custom(limits) = ast_rules {
+        if source_code.len() as u64 > limits.max_source_length {
+            let error = ParseError::new(ParseErrors::ProgramTooLarge);
+            if error_early {
+                return Err(error);
+            }
+            let contract_ast = ContractAST::new(contract_identifier.clone(), vec![]);
+            return Ok((contract_ast, vec![error.diagnostic], false));
+        }
+    }
+
     let (pre_expressions, mut diagnostics, mut success) = if epoch >= StacksEpochId::Epoch21 {
         if error_early {
-            let exprs = parser::v2::parse(source_code)?;
+            let exprs = match ast_rules {
+                // the parser's nesting depth counts the top level, like `MAX_NESTING_DEPTH`
+                ASTRules::Custom(limits) => {
+                    parse_v2_with_max_depth(source_code, limits.max_depth.saturating_add(1))?
+                }
+                _ => parser::v2::parse(source_code)?,
+            };
             (exprs, Vec::new(), true)
         } else {
             parser::v2::parse_collect_diagnostics(source_code)
         }
     } else {
         let parse_result = match ast_rules {
-            ASTRules::Typical => parse_v1_no_stack_limit(source_code),
+            // the depth of custom rules is checked below
+            ASTRules::Typical | ASTRules::Custom(_) => parse_v1_no_stack_limit(source_code),
             ASTRules::PrecheckSize => parse_v1(source_code),
         };
         match parse_result {
@@ -210,23 +294,27 @@ fn inner_build_ast<T: CostTracker>(
                 message: format!("runtime_cost error: {:?}", e),
                 spans: vec![],
                 suggestion: None,
+                code: None,
             },
         );
     }
 
     let mut contract_ast = ContractAST::new(contract_identifier.clone(), pre_expressions);
-    match StackDepthChecker::run_pass(&mut contract_ast, clarity_version) {
-        Err(e) if error_early => return Err(e),
-        Err(e) => {
-            diagnostics.push(e.diagnostic);
-            success = false;
+    if let ASTRules::Custom(limits) = ast_rules {
+        let limits_check = check_max_depth(&contract_ast.pre_expressions, limits.max_depth)
+            .and_then(|_| {
+                check_max_expressions(&contract_ast.pre_expressions, limits.max_expressions)
+            });
+        match limits_check {
+            Err(e) if error_early => return Err(e),
+            Err(e) => {
+                diagnostics.push(e.diagnostic);
+                success = false;
+            }
+            _ => (),
         }
-        _ => (),
-    }
-
-    if ast_rules != ASTRules::Typical {
-        // run extra stack-depth pass for tuples
-        match VaryStackDepthChecker::run_pass(&mut contract_ast, clarity_version) {
+    } else {
+        match StackDepthChecker::run_pass(&mut contract_ast, clarity_version) {
             Err(e) if error_early => return Err(e),
             Err(e) => {
                 diagnostics.push(e.diagnostic);
@@ -234,6 +322,18 @@ fn inner_build_ast<T: CostTracker>(
             }
             _ => (),
         }
+
+        if ast_rules != ASTRules::Typical {
+            // run extra stack-depth pass for tuples
+            match VaryStackDepthChecker::run_pass(&mut contract_ast, clarity_version) {
+                Err(e) if error_early => return Err(e),
+                Err(e) => {
+                    diagnostics.push(e.diagnostic);
+                    success = false;
+                }
+                _ => (),
+            }
+        }
     }
 
     match ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast, clarity_version) {
@@ -324,8 +424,8 @@ mod test {
     use stacks_common::types::StacksEpochId;
 
     use crate::vm::ast::errors::ParseErrors;
-    use crate::vm::ast::stack_depth_checker::AST_CALL_STACK_DEPTH_BUFFER;
-    use crate::vm::ast::{build_ast, build_ast_with_rules, ASTRules};
+    use crate::vm::ast::stack_depth_checker::{AST_CALL_STACK_DEPTH_BUFFER, AST_MAX_DEPTH};
+    use crate::vm::ast::{build_ast, build_ast_with_rules, ASTLimits, ASTRules};
     use crate::vm::costs::{LimitedCostTracker, *};
     use crate::vm::representations::depth_traverse;
     use crate::vm::types::QualifiedContractIdentifier;
@@ -622,4 +722,97 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_custom_ast_limits() {
+        let deeper_than_default = AST_MAX_DEPTH as usize + 10;
+        let deep_list = format!(
+            "{}u1 {}",
+            "(list ".repeat(deeper_than_default),
+            ")".repeat(deeper_than_default)
+        );
+        // the limit is exclusive, so a list exactly `max_depth` deep is too deep
+        let exact_limits = ASTLimits {
+            max_depth: deeper_than_default as u64,
+            ..ASTLimits::default()
+        };
+        let deeper_limits = ASTLimits {
+            max_depth: deeper_than_default as u64 + 1,
+            ..ASTLimits::default()
+        };
+
+        for epoch in &[StacksEpochId::Epoch2_05, StacksEpochId::Epoch21] {
+            for limits in &[ASTLimits::default(), exact_limits] {
+                let err = build_ast_with_rules(
+                    &QualifiedContractIdentifier::transient(),
+                    &deep_list,
+                    &mut UnitTestTracker::new(),
+                    ClarityVersion::Clarity1,
+                    *epoch,
+                    ASTRules::Custom(*limits),
+                )
+                .expect_err("Contract should error in parsing with a lower depth limit");
+                assert!(matches!(
+                    err.err,
+                    ParseErrors::ExpressionStackDepthTooDeep
+                        | ParseErrors::VaryExpressionStackDepthTooDeep
+                ));
+            }
+
+            build_ast_with_rules(
+                &QualifiedContractIdentifier::transient(),
+                &deep_list,
+                &mut UnitTestTracker::new(),
+                ClarityVersion::Clarity1,
+                *epoch,
+                ASTRules::Custom(deeper_limits),
+            )
+            .expect("Contract should parse with a larger depth limit");
+        }
+
+        let program = "(define-constant a (list u1 u2 u3))";
+        // both lists, `define-constant`, `a`, `list` and its three elements
+        let limits = ASTLimits {
+            max_expressions: 8,
+            ..ASTLimits::default()
+        };
+        build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            program,
+            &mut UnitTestTracker::new(),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            ASTRules::Custom(limits),
+        )
+        .unwrap();
+        let limits = ASTLimits {
+            max_expressions: 7,
+            ..ASTLimits::default()
+        };
+        let err = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            program,
+            &mut UnitTestTracker::new(),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            ASTRules::Custom(limits),
+        )
+        .unwrap_err();
+        assert_eq!(err.err, ParseErrors::TooManyExpressions);
+
+        let limits = ASTLimits {
+            max_source_length: program.len() as u64 - 1,
+            ..ASTLimits::default()
+        };
+        let err = build_ast_with_rules(
+            &QualifiedContractIdentifier::transient(),
+            program,
+            &mut UnitTestTracker::new(),
+            ClarityVersion::Clarity2,
+            StacksEpochId::Epoch21,
+            ASTRules::Custom(limits),
+        )
+        .unwrap_err();
+        assert_eq!(err.err, ParseErrors::ProgramTooLarge);
+    }
 }
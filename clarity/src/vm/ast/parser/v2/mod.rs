@@ -31,6 +31,7 @@ pub struct Parser<'a> {
     // context of a stacks-node, while normal mode is useful for developers.
     fail_fast: bool,
     nesting_depth: u64,
+    max_nesting_depth: u64,
 }
 
 pub const MAX_STRING_LEN: usize = 128;
@@ -83,6 +84,7 @@ impl<'a> Parser<'a> {
             success: true,
             fail_fast,
             nesting_depth: 0,
+            max_nesting_depth: MAX_NESTING_DEPTH,
         };
 
         loop {
@@ -825,7 +827,7 @@ impl<'a> Parser<'a> {
                     match &token.token {
                         Token::Lparen => {
                             self.nesting_depth += 1;
-                            if self.nesting_depth > MAX_NESTING_DEPTH {
+                            if self.nesting_depth > self.max_nesting_depth {
                                 self.add_diagnostic(
                                     ParseErrors::ExpressionStackDepthTooDeep,
                                     token.span.clone(),
@@ -844,7 +846,7 @@ impl<'a> Parser<'a> {
                         }
                         Token::Lbrace => {
                             // This sugared syntax for tuple becomes a list of pairs, so depth is increased by 2.
-                            if self.nesting_depth + 2 > MAX_NESTING_DEPTH {
+                            if self.nesting_depth + 2 > self.max_nesting_depth {
                                 self.add_diagnostic(
                                     ParseErrors::ExpressionStackDepthTooDeep,
                                     token.span.clone(),
@@ -1095,10 +1097,20 @@ impl<'a> Parser<'a> {
 }
 
 pub fn parse(input: &str) -> ParseResult<Vec<PreSymbolicExpression>> {
+    parse_with_max_nesting_depth(input, MAX_NESTING_DEPTH)
+}
+
+/// Like `parse`, but allowing lists and tuples to nest up to `max_nesting_depth` deep instead of
+/// `MAX_NESTING_DEPTH`
+pub fn parse_with_max_nesting_depth(
+    input: &str,
+    max_nesting_depth: u64,
+) -> ParseResult<Vec<PreSymbolicExpression>> {
     let mut parser = match Parser::new(input, true) {
         Ok(parser) => parser,
         Err(e) => return Err(ParseError::new(e)),
     };
+    parser.max_nesting_depth = max_nesting_depth;
     let stmts = parser.parse()?;
     if parser.success {
         Ok(stmts)
@@ -1125,6 +1137,7 @@ pub fn parse_collect_diagnostics(
             message: e.e.message(),
             spans: vec![e.span.clone()],
             suggestion: None,
+            code: e.e.code(),
         })
         .collect();
     (stmts, diagnostics, parser.success)
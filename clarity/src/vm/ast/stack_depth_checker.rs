@@ -25,13 +25,16 @@ use crate::vm::{ClarityVersion, MAX_CALL_STACK_DEPTH};
 //    AST depth, without impacting the stack depth).
 pub const AST_CALL_STACK_DEPTH_BUFFER: u64 = 5;
 
-fn check(args: &[PreSymbolicExpression], depth: u64) -> ParseResult<()> {
-    if depth >= (AST_CALL_STACK_DEPTH_BUFFER + MAX_CALL_STACK_DEPTH as u64) {
+/// The deepest lists may nest, unless the AST rules say otherwise
+pub const AST_MAX_DEPTH: u64 = AST_CALL_STACK_DEPTH_BUFFER + MAX_CALL_STACK_DEPTH as u64;
+
+fn check(args: &[PreSymbolicExpression], depth: u64, max_depth: u64) -> ParseResult<()> {
+    if depth >= max_depth {
         return Err(ParseErrors::ExpressionStackDepthTooDeep.into());
     }
     for expression in args.iter() {
         match expression.pre_expr {
-            List(ref exprs) => check(exprs, depth + 1),
+            List(ref exprs) => check(exprs, depth + 1, max_depth),
             _ => {
                 // Other symbolic expressions don't have depth
                 //  impacts.
@@ -46,18 +49,18 @@ pub struct StackDepthChecker;
 
 impl BuildASTPass for StackDepthChecker {
     fn run_pass(contract_ast: &mut ContractAST, _version: ClarityVersion) -> ParseResult<()> {
-        check(&contract_ast.pre_expressions, 0)
+        check(&contract_ast.pre_expressions, 0, AST_MAX_DEPTH)
     }
 }
 
-fn check_vary(args: &[PreSymbolicExpression], depth: u64) -> ParseResult<()> {
-    if depth >= (AST_CALL_STACK_DEPTH_BUFFER + MAX_CALL_STACK_DEPTH as u64) {
+fn check_vary(args: &[PreSymbolicExpression], depth: u64, max_depth: u64) -> ParseResult<()> {
+    if depth >= max_depth {
         return Err(ParseErrors::VaryExpressionStackDepthTooDeep.into());
     }
     for expression in args.iter() {
         match expression.pre_expr {
-            List(ref exprs) => check_vary(exprs, depth + 1),
-            Tuple(ref exprs) => check_vary(exprs, depth + 1),
+            List(ref exprs) => check_vary(exprs, depth + 1, max_depth),
+            Tuple(ref exprs) => check_vary(exprs, depth + 1, max_depth),
             _ => {
                 // Other symbolic expressions don't have depth
                 //  impacts.
@@ -72,6 +75,38 @@ pub struct VaryStackDepthChecker;
 
 impl BuildASTPass for VaryStackDepthChecker {
     fn run_pass(contract_ast: &mut ContractAST, _version: ClarityVersion) -> ParseResult<()> {
-        check_vary(&contract_ast.pre_expressions, 0)
+        check_vary(&contract_ast.pre_expressions, 0, AST_MAX_DEPTH)
     }
 }
+
+/// Check that neither lists nor tuples nest `max_depth` or more deep, for the AST rules of an
+/// embedder that chose its own limits
+pub fn check_max_depth(
+    pre_expressions: &[PreSymbolicExpression],
+    max_depth: u64,
+) -> ParseResult<()> {
+    check(pre_expressions, 0, max_depth)?;
+    check_vary(pre_expressions, 0, max_depth)
+}
+
+fn count_expressions(pre_expressions: &[PreSymbolicExpression]) -> u64 {
+    pre_expressions
+        .iter()
+        .map(|expression| match expression.pre_expr {
+            List(ref exprs) | Tuple(ref exprs) => 1 + count_expressions(exprs),
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Check that there are at most `max_expressions` expressions in the AST, counting every
+/// expression nested in a list or tuple. The depth should be checked first.
+pub fn check_max_expressions(
+    pre_expressions: &[PreSymbolicExpression],
+    max_expressions: u64,
+) -> ParseResult<()> {
+    if count_expressions(pre_expressions) > max_expressions {
+        return Err(ParseErrors::TooManyExpressions.into());
+    }
+    Ok(())
+}
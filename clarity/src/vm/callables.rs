@@ -37,7 +37,7 @@ use crate::vm::types::{
     QualifiedContractIdentifier, ResponseData, SequenceData, SequenceSubtype, TraitIdentifier,
     TupleData, TupleTypeSignature, TypeSignature,
 };
-use crate::vm::{eval, Environment, LocalContext, Value};
+use crate::vm::{eval, run_eval_hooks, Environment, LocalContext, Value};
 
 pub enum CallableType {
     UserFunction(DefinedFunction),
@@ -153,6 +153,21 @@ impl DefinedFunction {
     }
 
     pub fn execute_apply(&self, args: &[Value], env: &mut Environment) -> Result<Value> {
+        run_eval_hooks(env, |hook, env| {
+            hook.will_call_function(env, &self.identifier, args)
+        });
+        env.global_context
+            .cost_track
+            .profile_enter_function(&self.identifier);
+        let res = self.inner_execute_apply(args, env);
+        env.global_context.cost_track.profile_exit_function();
+        run_eval_hooks(env, |hook, env| {
+            hook.did_return_from_function(env, &self.identifier, &res)
+        });
+        res
+    }
+
+    fn inner_execute_apply(&self, args: &[Value], env: &mut Environment) -> Result<Value> {
         runtime_cost(
             ClarityCostFunction::UserFunctionApplication,
             env,
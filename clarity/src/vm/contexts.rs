@@ -24,11 +24,12 @@ use stacks_common::consts::CHAIN_ID_TESTNET;
 use stacks_common::types::chainstate::StacksBlockId;
 use stacks_common::types::StacksEpochId;
 
-use super::EvalHook;
+use super::{run_eval_hooks, EvalHook};
 use crate::vm::ast::{ASTRules, ContractAST};
 use crate::vm::callables::{DefinedFunction, FunctionIdentifier};
 use crate::vm::contracts::Contract;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::profile::CostProfileNode;
 use crate::vm::costs::{
     cost_functions, runtime_cost, ClarityCostFunctionReference, CostErrors, CostTracker,
     ExecutionCost, LimitedCostTracker,
@@ -830,6 +831,15 @@ impl<'a, 'hooks> OwnedEnvironment<'a, 'hooks> {
         self.context.cost_track.get_total()
     }
 
+    /// Start profiling the costs charged from now on. See `LimitedCostTracker::enable_profiling`.
+    pub fn enable_cost_profiling(&mut self) {
+        self.context.cost_track.enable_profiling()
+    }
+
+    pub fn get_cost_profile(&self) -> Option<&CostProfileNode> {
+        self.context.cost_track.get_cost_profile()
+    }
+
     /// Destroys this environment, returning ownership of its database reference.
     ///  If the context wasn't top-level (i.e., it had uncommitted data), return None,
     ///   because the database is not guaranteed to be in a sane state.
@@ -1163,7 +1173,9 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
             }
             self.call_stack.insert(&func_identifier, true);
 
+            run_eval_hooks(self, |hook, env| hook.will_call_contract(env, contract_identifier, tx_name, &args));
             let res = self.execute_function_as_transaction(&func, &args, Some(&contract.contract_context));
+            run_eval_hooks(self, |hook, env| hook.did_finish_contract_call(env, contract_identifier, tx_name, &res));
             self.call_stack.remove(&func_identifier, true)?;
 
             match res {
@@ -1396,9 +1408,7 @@ impl<'a, 'b, 'hooks> Environment<'a, 'b, 'hooks> {
     }
 
     pub fn push_to_event_batch(&mut self, event: StacksTransactionEvent) {
-        if let Some(batch) = self.global_context.event_batches.last_mut() {
-            batch.events.push(event);
-        }
+        self.global_context.push_to_event_batch(event)
     }
 
     pub fn construct_print_transaction_event(
@@ -1583,6 +1593,18 @@ impl<'a, 'hooks> GlobalContext<'a, 'hooks> {
         self.asset_maps.len() == 0
     }
 
+    /// Record an event in the current event batch, and report it to the eval hooks
+    pub fn push_to_event_batch(&mut self, event: StacksTransactionEvent) {
+        if let Some(batch) = self.event_batches.last_mut() {
+            if let Some(hooks) = self.eval_hooks.as_mut() {
+                for hook in hooks.iter_mut() {
+                    hook.did_emit_event(&event);
+                }
+            }
+            batch.events.push(event);
+        }
+    }
+
     fn get_asset_map(&mut self) -> Result<&mut AssetMap> {
         self.asset_maps
             .last_mut()
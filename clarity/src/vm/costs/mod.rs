@@ -22,8 +22,10 @@ use rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 use stacks_common::types::StacksEpochId;
 
+use self::profile::{CostProfileNode, CostProfiler};
 use crate::boot_util::boot_code_id;
 use crate::vm::ast::ContractAST;
+use crate::vm::callables::FunctionIdentifier;
 use crate::vm::contexts::{ContractContext, Environment, GlobalContext, OwnedEnvironment};
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::database::clarity_store::NullBackingStore;
@@ -40,6 +42,7 @@ use crate::vm::{ast, eval_all, ClarityName, SymbolicExpression, Value};
 
 pub mod constants;
 pub mod cost_functions;
+pub mod profile;
 
 type Result<T> = std::result::Result<T, CostErrors>;
 
@@ -251,6 +254,8 @@ pub struct TrackerData {
     epoch: StacksEpochId,
     mainnet: bool,
     chain_id: u32,
+    /// if profiling is enabled, this attributes the charged costs to functions and native ops
+    profiler: Option<CostProfiler>,
 }
 
 #[derive(Clone)]
@@ -712,6 +717,7 @@ impl LimitedCostTracker {
             epoch,
             mainnet,
             chain_id,
+            profiler: None,
         };
         assert!(clarity_db.is_stack_empty());
         cost_tracker.load_costs(clarity_db, true)?;
@@ -736,6 +742,7 @@ impl LimitedCostTracker {
             epoch,
             mainnet,
             chain_id,
+            profiler: None,
         };
         cost_tracker.load_costs(clarity_db, false)?;
         Ok(Self::Limited(cost_tracker))
@@ -902,6 +909,41 @@ impl LimitedCostTracker {
             Self::Free => u64::MAX,
         }
     }
+    /// Start attributing the costs charged from now on to the user-defined functions and native
+    ///  ops that incur them. A free tracker charges nothing, so this does nothing for it.
+    pub fn enable_profiling(&mut self) {
+        if let Self::Limited(ref mut data) = self {
+            data.profiler = Some(CostProfiler::new());
+        }
+    }
+    /// The cost profile recorded since profiling was enabled
+    pub fn get_cost_profile(&self) -> Option<&CostProfileNode> {
+        match self {
+            Self::Limited(TrackerData {
+                profiler: Some(profiler),
+                ..
+            }) => Some(profiler.profile()),
+            _ => None,
+        }
+    }
+    pub fn profile_enter_function(&mut self, function: &FunctionIdentifier) {
+        if let Self::Limited(TrackerData {
+            profiler: Some(ref mut profiler),
+            ..
+        }) = self
+        {
+            profiler.enter_function(&function.to_string());
+        }
+    }
+    pub fn profile_exit_function(&mut self) {
+        if let Self::Limited(TrackerData {
+            profiler: Some(ref mut profiler),
+            ..
+        }) = self
+        {
+            profiler.exit_function();
+        }
+    }
 }
 
 fn parse_cost(
@@ -1000,6 +1042,9 @@ fn compute_cost(
 
 fn add_cost(s: &mut TrackerData, cost: ExecutionCost) -> std::result::Result<(), CostErrors> {
     s.total.add(&cost)?;
+    if let Some(ref mut profiler) = s.profiler {
+        profiler.charge(&cost);
+    }
     if cfg!(feature = "disable-costs") {
         // Disable check for exceeding the cost limit to allow mining large blocks for profiling purposes.
         return Ok(());
@@ -1057,6 +1102,9 @@ impl CostTracker for LimitedCostTracker {
                     )))?
                     .clone();
 
+                if let Some(ref mut profiler) = data.profiler {
+                    profiler.will_charge_op(cost_function);
+                }
                 compute_cost(data, cost_function_ref, input, data.epoch)
             }
         }
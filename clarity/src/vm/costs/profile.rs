@@ -0,0 +1,222 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::cost_functions::ClarityCostFunction;
+use super::ExecutionCost;
+
+/// One frame of a hierarchical cost profile. The root frame covers everything charged while
+/// profiling. Its descendants are the user-defined functions that were called (named by their
+/// function identifiers) and the native ops charged in them (named by their cost functions).
+/// Calls of the same function from the same caller are merged into one frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostProfileNode {
+    /// The cost charged in this frame, including in its children
+    pub cost: ExecutionCost,
+    /// How many times the function was called, or the op charged
+    pub count: u64,
+    pub children: BTreeMap<String, CostProfileNode>,
+}
+
+fn saturating_add(total: &mut ExecutionCost, cost: &ExecutionCost) {
+    total.runtime = total.runtime.saturating_add(cost.runtime);
+    total.write_length = total.write_length.saturating_add(cost.write_length);
+    total.write_count = total.write_count.saturating_add(cost.write_count);
+    total.read_length = total.read_length.saturating_add(cost.read_length);
+    total.read_count = total.read_count.saturating_add(cost.read_count);
+}
+
+impl CostProfileNode {
+    fn new() -> CostProfileNode {
+        CostProfileNode {
+            cost: ExecutionCost::zero(),
+            count: 0,
+            children: BTreeMap::new(),
+        }
+    }
+
+    fn child(&mut self, name: &str) -> &mut CostProfileNode {
+        if !self.children.contains_key(name) {
+            self.children
+                .insert(name.to_string(), CostProfileNode::new());
+        }
+        self.children
+            .get_mut(name)
+            .expect("BUG: profile child was just inserted")
+    }
+
+    /// The cost charged in this frame outside of its children
+    pub fn self_cost(&self) -> ExecutionCost {
+        let mut self_cost = self.cost.clone();
+        for child in self.children.values() {
+            self_cost.runtime = self_cost.runtime.saturating_sub(child.cost.runtime);
+            self_cost.write_length = self_cost
+                .write_length
+                .saturating_sub(child.cost.write_length);
+            self_cost.write_count = self_cost.write_count.saturating_sub(child.cost.write_count);
+            self_cost.read_length = self_cost.read_length.saturating_sub(child.cost.read_length);
+            self_cost.read_count = self_cost.read_count.saturating_sub(child.cost.read_count);
+        }
+        self_cost
+    }
+
+    /// The profile in the "folded stacks" format read by flame graph tools: one
+    /// `root;frame;...;frame runtime` line per frame with a non-zero self runtime cost.
+    pub fn folded_stacks(&self, root_name: &str) -> Vec<String> {
+        let mut lines = vec![];
+        self.fold_into(root_name.to_string(), &mut lines);
+        lines
+    }
+
+    fn fold_into(&self, stack: String, lines: &mut Vec<String>) {
+        let self_runtime = self.self_cost().runtime;
+        if self_runtime > 0 {
+            lines.push(format!("{} {}", &stack, self_runtime));
+        }
+        for (name, child) in self.children.iter() {
+            child.fold_into(format!("{};{}", &stack, name), lines);
+        }
+    }
+}
+
+/// Attributes the costs charged to a cost tracker to the function calls and native ops that
+/// incurred them
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostProfiler {
+    root: CostProfileNode,
+    /// The frames of the user-defined functions currently being executed, outermost first
+    stack: Vec<String>,
+    /// The op whose cost was just computed, and is about to be charged
+    pending_op: Option<ClarityCostFunction>,
+}
+
+impl CostProfiler {
+    pub fn new() -> CostProfiler {
+        CostProfiler {
+            root: CostProfileNode::new(),
+            stack: vec![],
+            pending_op: None,
+        }
+    }
+
+    fn current_frame(&mut self) -> &mut CostProfileNode {
+        let mut node = &mut self.root;
+        for frame in self.stack.iter() {
+            node = node.child(frame);
+        }
+        node
+    }
+
+    pub fn enter_function(&mut self, function: &str) {
+        self.stack.push(function.to_string());
+        self.current_frame().count += 1;
+    }
+
+    pub fn exit_function(&mut self) {
+        self.stack.pop();
+    }
+
+    pub fn will_charge_op(&mut self, op: ClarityCostFunction) {
+        self.pending_op = Some(op);
+    }
+
+    /// Attribute a charged cost to the current frame and its callers, and to the op it was
+    /// computed for, if any
+    pub fn charge(&mut self, cost: &ExecutionCost) {
+        let op = self.pending_op.take();
+
+        let mut node = &mut self.root;
+        saturating_add(&mut node.cost, cost);
+        for frame in self.stack.iter() {
+            node = node.child(frame);
+            saturating_add(&mut node.cost, cost);
+        }
+        if let Some(op) = op {
+            let op_node = node.child(op.get_name_str());
+            op_node.count += 1;
+            saturating_add(&mut op_node.cost, cost);
+        }
+    }
+
+    pub fn profile(&self) -> &CostProfileNode {
+        &self.root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_attributes_costs_to_frames() {
+        let mut profiler = CostProfiler::new();
+
+        // charged outside of any function
+        profiler.charge(&ExecutionCost::runtime(1));
+
+        profiler.enter_function("outer");
+        profiler.will_charge_op(ClarityCostFunction::Add);
+        profiler.charge(&ExecutionCost::runtime(10));
+        profiler.enter_function("inner");
+        profiler.will_charge_op(ClarityCostFunction::FetchVar);
+        profiler.charge(&ExecutionCost {
+            runtime: 100,
+            write_length: 0,
+            write_count: 0,
+            read_length: 1,
+            read_count: 1,
+        });
+        profiler.exit_function();
+        profiler.enter_function("inner");
+        profiler.will_charge_op(ClarityCostFunction::FetchVar);
+        profiler.charge(&ExecutionCost::runtime(100));
+        // charged without computing an op cost, e.g. for storage
+        profiler.charge(&ExecutionCost::runtime(5));
+        profiler.exit_function();
+        profiler.exit_function();
+
+        let profile = profiler.profile();
+        assert_eq!(profile.cost.runtime, 216);
+        assert_eq!(profile.self_cost().runtime, 1);
+
+        let outer = &profile.children["outer"];
+        assert_eq!(outer.count, 1);
+        assert_eq!(outer.cost.runtime, 215);
+        assert_eq!(outer.children["cost_add"].count, 1);
+        assert_eq!(outer.children["cost_add"].cost.runtime, 10);
+
+        let inner = &outer.children["inner"];
+        assert_eq!(inner.count, 2);
+        assert_eq!(inner.cost.runtime, 205);
+        assert_eq!(inner.cost.read_count, 1);
+        assert_eq!(inner.self_cost().runtime, 5);
+        assert_eq!(inner.children["cost_fetch_var"].count, 2);
+        assert_eq!(inner.children["cost_fetch_var"].cost.runtime, 200);
+
+        assert_eq!(
+            profile.folded_stacks("tx"),
+            vec![
+                "tx 1".to_string(),
+                "tx;outer;cost_add 10".to_string(),
+                "tx;outer;inner 5".to_string(),
+                "tx;outer;inner;cost_fetch_var 200".to_string(),
+            ]
+        );
+    }
+}
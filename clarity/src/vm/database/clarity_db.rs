@@ -33,7 +33,7 @@ use stacks_common::types::{
 };
 use stacks_common::util::hash::{to_hex, Hash160, Sha256Sum, Sha512Trunc256Sum};
 
-use super::clarity_store::SpecialCaseHandler;
+use super::clarity_store::{SpecialCaseHandler, SpeculativeBackingStore};
 use super::key_value_wrapper::ValueResult;
 use crate::vm::analysis::{AnalysisDatabase, ContractAnalysis};
 use crate::vm::ast::ASTRules;
@@ -449,6 +449,28 @@ impl<'a> ClarityDatabase<'a> {
         self.store.rollback().map_err(|e| e.into())
     }
 
+    /// Open a copy-on-write overlay over this database's backing store, to execute
+    ///  transactions against the current chain tip and throw their results away. Use the
+    ///  overlay's `as_clarity_db()` to execute against it: nothing written through it reaches
+    ///  this database. The overlay does not see this database's pending edits, so it can only
+    ///  be opened while none are pending.
+    pub fn begin_speculative(&mut self) -> Result<SpeculativeBackingStore> {
+        if !self.is_stack_empty() {
+            return Err(InterpreterError::Expect(
+                "ERROR: Clarity VM attempted to begin a speculative overlay with pending edits."
+                    .into(),
+            )
+            .into());
+        }
+        let headers_db = self.headers_db;
+        let burn_state_db = self.burn_state_db;
+        Ok(SpeculativeBackingStore::new(
+            self.store.backing_store(),
+            headers_db,
+            burn_state_db,
+        ))
+    }
+
     pub fn set_block_hash(
         &mut self,
         bhh: StacksBlockId,
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::path::PathBuf;
 
@@ -170,6 +171,17 @@ pub trait ClarityBackingStore {
         }
         Ok(())
     }
+
+    /// The contracts that have a metadata entry named `key`. The side store keeps the metadata
+    ///  of every fork, so callers must still look each one up with `get_metadata` to find out
+    ///  whether it exists at the current chain tip.
+    fn get_metadata_contracts(&mut self, key: &str) -> Result<Vec<QualifiedContractIdentifier>> {
+        let contracts = SqliteConnection::get_metadata_contracts(self.get_side_store(), key)?;
+        Ok(contracts
+            .iter()
+            .filter_map(|contract| QualifiedContractIdentifier::parse(contract).ok())
+            .collect())
+    }
 }
 
 // TODO: Figure out where this belongs
@@ -345,3 +357,175 @@ impl ClarityBackingStore for MemoryBackingStore {
         Ok(())
     }
 }
+
+/// A copy-on-write overlay over another backing store. Reads fall through to the underlying
+///  store, but writes (including contract metadata) are kept in memory, and are dropped with
+///  the overlay. This lets callers execute transactions against the underlying store's chain
+///  tip and throw away their effects without touching it.
+pub struct SpeculativeBackingStore<'a> {
+    store: &'a mut dyn ClarityBackingStore,
+    headers_db: &'a dyn HeadersDB,
+    burn_state_db: &'a dyn BurnStateDB,
+    data: HashMap<String, String>,
+    metadata: HashMap<(QualifiedContractIdentifier, String), String>,
+    // the block the overlay's writes are made at, set once evaluation is first time-shifted
+    speculative_block: Option<StacksBlockId>,
+    // false while evaluation is time-shifted away from `speculative_block`, since the
+    //  overlay's writes are not visible from other blocks
+    query_overlay: bool,
+}
+
+impl<'a> SpeculativeBackingStore<'a> {
+    pub fn new(
+        store: &'a mut dyn ClarityBackingStore,
+        headers_db: &'a dyn HeadersDB,
+        burn_state_db: &'a dyn BurnStateDB,
+    ) -> SpeculativeBackingStore<'a> {
+        SpeculativeBackingStore {
+            store,
+            headers_db,
+            burn_state_db,
+            data: HashMap::new(),
+            metadata: HashMap::new(),
+            speculative_block: None,
+            query_overlay: true,
+        }
+    }
+
+    pub fn as_clarity_db(&mut self) -> ClarityDatabase {
+        let headers_db = self.headers_db;
+        let burn_state_db = self.burn_state_db;
+        ClarityDatabase::new(self, headers_db, burn_state_db)
+    }
+
+    pub fn as_analysis_db(&mut self) -> AnalysisDatabase {
+        AnalysisDatabase::new(self)
+    }
+
+    fn overlay_get(&self, key: &str) -> Option<&String> {
+        if self.query_overlay {
+            self.data.get(key)
+        } else {
+            None
+        }
+    }
+
+    // Contracts deployed in the overlay are unknown to the underlying store, so their metadata
+    //  must be answered from the overlay alone.
+    fn overlay_get_metadata(
+        &self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Option<Option<String>> {
+        if !self.query_overlay {
+            return None;
+        }
+        if let Some(value) = self.metadata.get(&(contract.clone(), key.to_string())) {
+            return Some(Some(value.clone()));
+        }
+        if self.data.contains_key(&make_contract_hash_key(contract)) {
+            return Some(None);
+        }
+        None
+    }
+}
+
+impl<'a> ClarityBackingStore for SpeculativeBackingStore<'a> {
+    fn put_all(&mut self, items: Vec<(String, String)>) -> Result<()> {
+        self.data.extend(items);
+        Ok(())
+    }
+
+    fn get(&mut self, key: &str) -> Result<Option<String>> {
+        match self.overlay_get(key) {
+            Some(value) => Ok(Some(value.clone())),
+            None => self.store.get(key),
+        }
+    }
+
+    /// Values written to the overlay are not in the underlying trie, so they are returned
+    ///  with an empty proof.
+    fn get_with_proof(&mut self, key: &str) -> Result<Option<(String, Vec<u8>)>> {
+        match self.overlay_get(key) {
+            Some(value) => Ok(Some((value.clone(), vec![]))),
+            None => self.store.get_with_proof(key),
+        }
+    }
+
+    fn set_block_hash(&mut self, bhh: StacksBlockId) -> Result<StacksBlockId> {
+        let prior = self.store.set_block_hash(bhh)?;
+        let speculative_block = *self.speculative_block.get_or_insert(prior);
+        self.query_overlay = bhh == speculative_block;
+        Ok(prior)
+    }
+
+    fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
+        self.store.get_block_at_height(height)
+    }
+
+    fn get_current_block_height(&mut self) -> u32 {
+        self.store.get_current_block_height()
+    }
+
+    fn get_open_chain_tip_height(&mut self) -> u32 {
+        self.store.get_open_chain_tip_height()
+    }
+
+    fn get_open_chain_tip(&mut self) -> StacksBlockId {
+        self.store.get_open_chain_tip()
+    }
+
+    fn get_side_store(&mut self) -> &Connection {
+        self.store.get_side_store()
+    }
+
+    fn get_cc_special_cases_handler(&self) -> Option<SpecialCaseHandler> {
+        self.store.get_cc_special_cases_handler()
+    }
+
+    fn insert_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+        value: &str,
+    ) -> Result<()> {
+        self.metadata
+            .insert((contract.clone(), key.to_string()), value.to_string());
+        Ok(())
+    }
+
+    fn get_metadata(
+        &mut self,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Result<Option<String>> {
+        match self.overlay_get_metadata(contract, key) {
+            Some(value) => Ok(value),
+            None => self.store.get_metadata(contract, key),
+        }
+    }
+
+    fn get_metadata_manual(
+        &mut self,
+        at_height: u32,
+        contract: &QualifiedContractIdentifier,
+        key: &str,
+    ) -> Result<Option<String>> {
+        match self.overlay_get_metadata(contract, key) {
+            Some(value) => Ok(value),
+            None => self.store.get_metadata_manual(at_height, contract, key),
+        }
+    }
+
+    fn get_metadata_contracts(&mut self, key: &str) -> Result<Vec<QualifiedContractIdentifier>> {
+        let mut contracts = self.store.get_metadata_contracts(key)?;
+        if self.query_overlay {
+            for (contract, _) in self.metadata.keys().filter(|(_, k)| k == key) {
+                if !contracts.contains(contract) {
+                    contracts.push(contract.clone());
+                }
+            }
+        }
+        Ok(contracts)
+    }
+}
@@ -234,6 +234,11 @@ impl<'a> RollbackWrapper<'a> {
         self.store.get_cc_special_cases_handler()
     }
 
+    /// The underlying key-value storage, without any of the pending edits
+    pub(crate) fn backing_store(&mut self) -> &mut dyn ClarityBackingStore {
+        self.store
+    }
+
     pub fn nest(&mut self) {
         self.stack.push(RollbackContext {
             edits: Vec::new(),
@@ -557,4 +562,21 @@ impl<'a> RollbackWrapper<'a> {
     ) -> bool {
         matches!(self.get_metadata(contract, key), Ok(Some(_)))
     }
+
+    /// The contracts that have a metadata entry named `key`, including pending ones. Some may
+    ///  not exist at the current chain tip; see `ClarityBackingStore::get_metadata_contracts`.
+    pub fn get_metadata_contracts(
+        &mut self,
+        key: &str,
+    ) -> InterpreterResult<Vec<QualifiedContractIdentifier>> {
+        let mut contracts = self.store.get_metadata_contracts(key)?;
+        if self.query_pending_data {
+            for (contract, _) in self.metadata_lookup_map.keys().filter(|(_, k)| k == key) {
+                if !contracts.contains(contract) {
+                    contracts.push(contract.clone());
+                }
+            }
+        }
+        Ok(contracts)
+    }
 }
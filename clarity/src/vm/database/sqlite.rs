@@ -160,6 +160,31 @@ impl SqliteConnection {
     pub fn has_entry(conn: &Connection, key: &str) -> Result<bool> {
         sqlite_has_entry(conn, key)
     }
+
+    /// The contracts with a metadata entry named `key`, at any block.
+    pub fn get_metadata_contracts(conn: &Connection, key: &str) -> Result<Vec<String>> {
+        let suffix = format!("::{}", key);
+        let query = |conn: &Connection| -> rusqlite::Result<Vec<String>> {
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT key FROM metadata_table WHERE key LIKE 'clr-meta::%' ORDER BY key",
+            )?;
+            let keys = stmt.query_map(NO_PARAMS, |row| row.get::<_, String>(0))?;
+            keys.collect()
+        };
+        let keys = query(conn).map_err(|e| {
+            error!("Failed to query metadata contracts ({}): {:?}", key, &e);
+            InterpreterError::DBError(SQL_FAIL_MESSAGE.into())
+        })?;
+        Ok(keys
+            .iter()
+            .filter_map(|metadata_key| {
+                metadata_key
+                    .strip_prefix("clr-meta::")?
+                    .strip_suffix(&suffix)
+                    .map(String::from)
+            })
+            .collect())
+    }
 }
 
 impl SqliteConnection {
@@ -20,7 +20,7 @@ use std::io::Write;
 use serde::Deserialize;
 use stacks_common::util::hash::{hex_bytes, to_hex};
 
-use crate::vm::analysis::ContractAnalysis;
+use crate::vm::analysis::{AnalysisArchive, ContractAnalysis};
 use crate::vm::contracts::Contract;
 use crate::vm::database::ClarityDatabase;
 use crate::vm::errors::{
@@ -127,6 +127,7 @@ clarity_serializable!(u128);
 clarity_serializable!(u64);
 clarity_serializable!(Contract);
 clarity_serializable!(ContractAnalysis);
+clarity_serializable!(AnalysisArchive);
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum STXBalance {
@@ -33,6 +33,11 @@ pub trait DiagnosableError {
     fn level(&self) -> Level {
         Level::Error
     }
+    /// Stable, machine-readable identifier for this kind of diagnostic (e.g. `TypeError`), so
+    /// tooling can match on it without parsing the message.
+    fn code(&self) -> Option<String> {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -41,6 +46,8 @@ pub struct Diagnostic {
     pub message: String,
     pub spans: Vec<Span>,
     pub suggestion: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
 }
 
 impl Diagnostic {
@@ -50,6 +57,15 @@ impl Diagnostic {
             level: Level::Error,
             message: error.message(),
             suggestion: error.suggestion(),
+            code: error.code(),
+        }
+    }
+
+    /// Build a diagnostic at the error's own level
+    pub fn new(error: &dyn DiagnosableError) -> Diagnostic {
+        Diagnostic {
+            level: error.level(),
+            ..Diagnostic::err(error)
         }
     }
 
@@ -402,6 +402,19 @@ Note: This function is only available starting with Stacks 2.1.",
 "#,
 };
 
+const UINT_TO_ASCII_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "uint-to-ascii ${1:num}",
+    signature: "(uint-to-ascii uint)",
+    description:
+        "Converts a `uint` to its decimal `string-ascii` representation. Unlike `int-to-ascii`,
+only a `uint` is accepted, so the result is a `(string-ascii 39)`.",
+    example: r#"
+(uint-to-ascii u1) ;; Returns "1"
+(uint-to-ascii u340282366920938463463374607431768211455) ;; Returns "340282366920938463463374607431768211455"
+"#,
+};
+
 const INT_TO_UTF8_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     snippet: "int-to-utf8 ${1:num}",
@@ -514,6 +527,23 @@ down to the nearest integer. Fails on a negative numbers.
 ",
 };
 
+const LOG2_UINT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "log2-uint ${1:expr-1}",
+    signature: "(log2-uint n)",
+    description:
+        "Returns the power to which the number 2 must be raised to to obtain the value `n`, rounded
+down to the nearest integer, as a `(some uint)`. Unlike `log2`, this function does not fail on
+inputs that are not positive, and returns `none` for them instead.
+",
+    example: "(log2-uint u8) ;; Returns (some u3)
+(log2-uint 1000) ;; Returns (some u9)
+(log2-uint u1) ;; Returns (some u0)
+(log2-uint u0) ;; Returns none
+(log2-uint -8) ;; Returns none
+",
+};
+
 const XOR_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     snippet: "xor ${1:expr-1} ${2:expr-2}",
@@ -620,6 +650,35 @@ Note that there is a deliberate choice made to ignore arithmetic overflow for th
 "
 };
 
+const BITWISE_LEFT_ROTATE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "bit-rotate-left ${1:expr-1} ${2:expr-2}",
+    signature: "(bit-rotate-left i1 shamt)",
+    description: "Rotates all the bits in `i1` to the left by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+Bits shifted out on the left are filled back in on the right, so no bits are lost. For an `int` (signed), the sign-bit is rotated like any other bit.
+",
+    example: "(bit-rotate-left u1 u2) ;; Returns u4
+(bit-rotate-left u2 u127) ;; Returns u1
+(bit-rotate-left u1 u128) ;; Returns u1
+(bit-rotate-left -1 u5) ;; Returns -1
+(bit-rotate-left -170141183460469231731687303715884105728 u1) ;; Returns 1
+"
+};
+
+const BITWISE_RIGHT_ROTATE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "bit-rotate-right ${1:expr-1} ${2:expr-2}",
+    signature: "(bit-rotate-right i1 shamt)",
+    description: "Rotates all the bits in `i1` to the right by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+Bits shifted out on the right are filled back in on the left, so no bits are lost. For an `int` (signed), the sign-bit is rotated like any other bit.
+",
+    example: "(bit-rotate-right u4 u2) ;; Returns u1
+(bit-rotate-right u1 u1) ;; Returns u170141183460469231731687303715884105728
+(bit-rotate-right u1 u128) ;; Returns u1
+(bit-rotate-right 1 u1) ;; Returns -170141183460469231731687303715884105728
+"
+};
+
 const AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     snippet: "and ${1:expr-1} ${2:expr-2}",
@@ -1022,6 +1081,24 @@ Applicable sequence types are `(list A)`, `buff`, `string-ascii` and `string-utf
 "#,
 };
 
+const CONCAT_MANY_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, ...",
+    snippet: "concat-many ${1:sequence-1} ${2:sequence-2}",
+    output_type: "sequence_A",
+    signature: "(concat-many sequence1 sequence2 ...)",
+    description: "The `concat-many` function takes between 1 and 16 sequences of the same type,
+and returns a single sequence of that type that joins them in order. The length of the result
+is the sum of the lengths of the inputs. This is cheaper than nesting `concat` calls, since
+the result is only built once.
+Applicable sequence types are `(list A)`, `buff`, `string-ascii` and `string-utf8`.
+",
+    example: r#"
+(concat-many (list 1 2) (list 3) (list 4 5)) ;; Returns (1 2 3 4 5)
+(concat-many "balance: " (uint-to-ascii u100) " STX") ;; Returns "balance: 100 STX"
+(concat-many 0x01 0x0203 0x04) ;; Returns 0x01020304
+"#,
+};
+
 const APPEND_API: SpecialAPI = SpecialAPI {
     input_type: "list A, A",
     snippet: "append ${1:list} ${2:element}",
@@ -1832,6 +1909,36 @@ Note: This function is only available starting with Stacks 2.1.",
 "#,
 };
 
+const CONTRACT_PRINCIPAL_CONSTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "principal, (string-ascii 40)",
+    output_type: "(response principal { error_code: uint, value: (optional principal) })",
+    snippet: "contract-principal-construct? ${1:issuer} ${2:contract-name}",
+    signature: "(contract-principal-construct? issuer contract-name)",
+    description: "The `contract-principal-construct?` function creates the contract principal for the
+contract named `contract-name` deployed by the standard principal `issuer`. This allows a contract
+to refer to other contracts whose addresses and names are provided as data, for example in a
+registry, rather than as trait arguments.  The contract does not need to exist.
+
+This function returns a `Response`. On success, the `ok` value is a contract principal.
+The `err` value is a value tuple with the form `{ error_code: uint, value: (optional principal) }`,
+using the same error codes as `principal-construct?`.
+
+If `issuer` is not a principal on the current network, then the error will be `u0`, and `value`
+will contain `(some principal)`, where the wrapped value is the contract principal.
+
+If `contract-name` is either an empty string or contains ASCII characters
+that are not allowed in contract names, then `error_code` will be `u2` and `value` will be `none`.
+
+If `issuer` is itself a contract principal, then `error_code` will be `u3` and `value` will be `none`.
+",
+    example: r#"
+(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo") ;; Returns (ok ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)
+(contract-principal-construct? 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY "foo") ;; Returns (err (tuple (error_code u0) (value (some SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo))))
+(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo[") ;; Returns (err (tuple (error_code u2) (value none)))
+(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo "bar") ;; Returns (err (tuple (error_code u3) (value none)))
+"#,
+};
+
 const DEFINE_TOKEN_API: DefineAPI = DefineAPI {
     input_type: "TokenName, <uint>",
     snippet: "define-fungible-token ${1:token-name} ${2:total-supply}",
@@ -2286,6 +2393,42 @@ unlock height for any locked STX, all denominated in microstacks.
 "#,
 };
 
+const STX_GET_ACCOUNT_INFO: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "stx-account-info ${1:owner}",
+    signature: "(stx-account-info owner)",
+    description: "`stx-account-info` is used to query the STX account of the `owner` principal,
+including which PoX contract holds its locked STX.
+
+This function returns the same tuple as `stx-account`, with an additional `pox-version` field.
+`pox-version` is `u1`, `u2`, or `u3` if the account's STX are locked by `pox`, `pox-2`, or `pox-3`
+respectively, and `u0` if the account has no locked STX.
+",
+    example: r#"
+(stx-account-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns (tuple (locked u0) (pox-version u0) (unlock-height u0) (unlocked u0))
+(stx-account-info (as-contract tx-sender)) ;; Returns (tuple (locked u0) (pox-version u0) (unlock-height u0) (unlocked u1000))
+"#,
+};
+
+const STX_GET_DELEGATION_INFO: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    snippet: "stx-delegation-info ${1:owner}",
+    signature: "(stx-delegation-info owner)",
+    description:
+        "`stx-delegation-info` is used to query the delegation state of the `owner` principal
+in the newest PoX contract of the current epoch: `pox` before Stacks 2.1, `pox-2` in Stacks 2.1
+through 2.3, and `pox-3` from Stacks 2.4.
+
+This function returns `none` if `owner` has not delegated its STX. Otherwise, it returns
+`(some delegation)`, where `delegation` is the PoX contract's `delegation-state` entry for `owner`.
+This entry is returned as-is, so callers must check `until-burn-ht` to see whether the
+delegation has expired.
+",
+    example: r#"
+(stx-delegation-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns none
+"#,
+};
+
 const STX_TRANSFER: SpecialAPI = SpecialAPI {
     input_type: "uint, principal, principal, buff",
     snippet: "stx-transfer? ${1:amount} ${2:sender} ${3:recipient}",
@@ -2441,9 +2584,13 @@ pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         IsStandard => make_for_simple_native(&IS_STANDARD_API, &function, name),
         PrincipalDestruct => make_for_simple_native(&PRINCPIPAL_DESTRUCT_API, &function, name),
         PrincipalConstruct => make_for_special(&PRINCIPAL_CONSTRUCT_API, &function),
+        ContractPrincipalConstruct => {
+            make_for_special(&CONTRACT_PRINCIPAL_CONSTRUCT_API, &function)
+        }
         StringToInt => make_for_simple_native(&STRING_TO_INT_API, &function, name),
         StringToUInt => make_for_simple_native(&STRING_TO_UINT_API, &function, name),
         IntToAscii => make_for_simple_native(&INT_TO_ASCII_API, &function, name),
+        UIntToAscii => make_for_simple_native(&UINT_TO_ASCII_API, &function, name),
         IntToUtf8 => make_for_simple_native(&INT_TO_UTF8_API, &function, name),
         CmpGeq => make_for_simple_native(&GEQ_API, &function, name),
         CmpLeq => make_for_simple_native(&LEQ_API, &function, name),
@@ -2467,6 +2614,7 @@ pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Fold => make_for_special(&FOLD_API, function),
         Append => make_for_special(&APPEND_API, function),
         Concat => make_for_special(&CONCAT_API, function),
+        ConcatMany => make_for_special(&CONCAT_MANY_API, function),
         AsMaxLen => make_for_special(&ASSERTS_MAX_LEN_API, function),
         Len => make_for_special(&LEN_API, function),
         ElementAt | ElementAtAlias => make_for_special(&ELEMENT_AT_API, function),
@@ -2522,6 +2670,8 @@ pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         AtBlock => make_for_special(&AT_BLOCK, function),
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &function, name),
         StxGetAccount => make_for_simple_native(&STX_GET_ACCOUNT, &function, name),
+        StxGetAccountInfo => make_for_simple_native(&STX_GET_ACCOUNT_INFO, &function, name),
+        StxGetDelegationInfo => make_for_simple_native(&STX_GET_DELEGATION_INFO, &function, name),
         StxTransfer => make_for_special(&STX_TRANSFER, function),
         StxTransferMemo => make_for_special(&STX_TRANSFER_MEMO, function),
         StxBurn => make_for_simple_native(&STX_BURN, &function, name),
@@ -2534,6 +2684,9 @@ pub fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         BitwiseNot => make_for_simple_native(&BITWISE_NOT_API, &function, name),
         BitwiseLShift => make_for_simple_native(&BITWISE_LEFT_SHIFT_API, &function, name),
         BitwiseRShift => make_for_simple_native(&BITWISE_RIGHT_SHIFT_API, &function, name),
+        BitwiseLRotate => make_for_simple_native(&BITWISE_LEFT_ROTATE_API, &function, name),
+        BitwiseRRotate => make_for_simple_native(&BITWISE_RIGHT_ROTATE_API, &function, name),
+        Log2UInt => make_for_simple_native(&LOG2_UINT_API, &function, name),
     }
 }
 
@@ -2991,6 +3144,13 @@ mod test {
                 );
                 continue;
             }
+            if func_api.version > ClarityVersion::latest() {
+                eprintln!(
+                    "Skipping {}, because it is not available in the latest Clarity version",
+                    func_api.name
+                );
+                continue;
+            }
 
             let mut store = MemoryBackingStore::new();
             // first, load the samples for contract-call
@@ -638,6 +638,79 @@ pub fn native_bitwise_right_shift(input: Value, pos: Value) -> InterpreterResult
     }
 }
 
+pub fn native_bitwise_left_rotate(input: Value, pos: Value) -> InterpreterResult<Value> {
+    if let Value::UInt(u128_val) = pos {
+        let shamt = u32::try_from(u128_val & 0x7f).map_err(|_| {
+            InterpreterError::Expect("FATAL: lower 32 bits did not convert to u32".into())
+        })?;
+
+        match input {
+            Value::Int(input) => {
+                let result = input.rotate_left(shamt);
+                Ok(Value::Int(result))
+            }
+            Value::UInt(input) => {
+                let result = input.rotate_left(shamt);
+                Ok(Value::UInt(result))
+            }
+            _ => Err(CheckErrors::UnionTypeError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::type_of(&input)?,
+            )
+            .into()),
+        }
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::UIntType, pos).into())
+    }
+}
+
+pub fn native_bitwise_right_rotate(input: Value, pos: Value) -> InterpreterResult<Value> {
+    if let Value::UInt(u128_val) = pos {
+        let shamt = u32::try_from(u128_val & 0x7f).map_err(|_| {
+            InterpreterError::Expect("FATAL: lower 32 bits did not convert to u32".into())
+        })?;
+
+        match input {
+            Value::Int(input) => {
+                let result = input.rotate_right(shamt);
+                Ok(Value::Int(result))
+            }
+            Value::UInt(input) => {
+                let result = input.rotate_right(shamt);
+                Ok(Value::UInt(result))
+            }
+            _ => Err(CheckErrors::UnionTypeError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::type_of(&input)?,
+            )
+            .into()),
+        }
+    } else {
+        Err(CheckErrors::TypeValueError(TypeSignature::UIntType, pos).into())
+    }
+}
+
+/// Unlike `log2`, this returns `none` instead of failing for inputs that are not positive,
+/// and always returns a `uint`.
+pub fn native_log2_uint(input: Value) -> InterpreterResult<Value> {
+    let log = match input {
+        Value::Int(input) if input > 0 => Some(127 - input.leading_zeros()),
+        Value::UInt(input) if input > 0 => Some(127 - input.leading_zeros()),
+        Value::Int(_) | Value::UInt(_) => None,
+        _ => {
+            return Err(CheckErrors::UnionTypeError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::type_of(&input)?,
+            )
+            .into())
+        }
+    };
+    match log {
+        Some(log) => Value::some(Value::UInt(u128::from(log))),
+        None => Ok(Value::none()),
+    }
+}
+
 pub fn native_to_uint(input: Value) -> InterpreterResult<Value> {
     if let Value::Int(int_val) = input {
         let uint_val =
@@ -18,6 +18,7 @@ use std::convert::{TryFrom, TryInto};
 
 use stacks_common::types::StacksEpochId;
 
+use crate::boot_util::boot_code_id;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::costs::{cost_functions, runtime_cost, CostTracker};
 use crate::vm::database::{ClarityDatabase, ClaritySerializable, STXBalance};
@@ -261,6 +262,139 @@ pub fn special_stx_account(
     .map(Value::Tuple)
 }
 
+/// Like `stx-account`, but also reports which PoX contract's lock (if any) applies to the
+/// account, as a `pox-version` of `u0` (no lock), `u1`, `u2`, or `u3`.
+pub fn special_stx_account_info(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    runtime_cost(ClarityCostFunction::StxGetAccount, env, 0)?;
+
+    let owner = eval(&args[0], env, context)?;
+    let principal = if let Value::Principal(p) = owner {
+        p
+    } else {
+        return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into());
+    };
+
+    let stx_balance = env
+        .global_context
+        .database
+        .get_stx_balance_snapshot(&principal)?
+        .canonical_balance_repr()?;
+    let v1_unlock_ht = env.global_context.database.get_v1_unlock_height();
+    let v2_unlock_ht = env.global_context.database.get_v2_unlock_height()?;
+
+    let pox_version: u128 = if stx_balance.amount_locked() == 0 {
+        0
+    } else {
+        match stx_balance {
+            STXBalance::Unlocked { .. } => 0,
+            STXBalance::LockedPoxOne { .. } => 1,
+            STXBalance::LockedPoxTwo { .. } => 2,
+            STXBalance::LockedPoxThree { .. } => 3,
+        }
+    };
+
+    TupleData::from_data(vec![
+        (
+            "unlocked"
+                .try_into()
+                .map_err(|_| InterpreterError::Expect("Bad special tuple name".into()))?,
+            Value::UInt(stx_balance.amount_unlocked()),
+        ),
+        (
+            "locked"
+                .try_into()
+                .map_err(|_| InterpreterError::Expect("Bad special tuple name".into()))?,
+            Value::UInt(stx_balance.amount_locked()),
+        ),
+        (
+            "unlock-height"
+                .try_into()
+                .map_err(|_| InterpreterError::Expect("Bad special tuple name".into()))?,
+            Value::UInt(u128::from(
+                stx_balance.effective_unlock_height(v1_unlock_ht, v2_unlock_ht),
+            )),
+        ),
+        (
+            "pox-version"
+                .try_into()
+                .map_err(|_| InterpreterError::Expect("Bad special tuple name".into()))?,
+            Value::UInt(pox_version),
+        ),
+    ])
+    .map(Value::Tuple)
+}
+
+/// Look up the principal's entry in the `delegation-state` map of the newest PoX contract of the
+/// current epoch: `pox` before Stacks 2.1, `pox-2` from 2.1 (where it is instantiated, before
+/// `pox` unlocks), and `pox-3` from 2.4. Returns `none` if the principal has no delegation
+/// entry, or if that PoX contract has not been instantiated yet. The entry is returned as-is, so
+/// callers must check `until-burn-ht` themselves to see whether it has expired.
+///
+/// This is priced as the `map-get?` it performs: it charges `FetchEntry`, for the size of the
+/// entry.
+pub fn special_stx_delegation_info(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+
+    let owner = eval(&args[0], env, context)?;
+    let principal = if let Value::Principal(p) = owner {
+        p
+    } else {
+        return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into());
+    };
+
+    let pox_name = match env.epoch() {
+        StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => "pox",
+        StacksEpochId::Epoch21 | StacksEpochId::Epoch22 | StacksEpochId::Epoch23 => "pox-2",
+        StacksEpochId::Epoch24 => "pox-3",
+    };
+    let pox_contract = boot_code_id(pox_name, env.global_context.mainnet);
+
+    if !env.global_context.database.has_contract(&pox_contract) {
+        runtime_cost(ClarityCostFunction::FetchEntry, env, 0)?;
+        return Ok(Value::none());
+    }
+
+    let map_name = "delegation-state";
+    let data_types = env
+        .global_context
+        .database
+        .load_map(&pox_contract, map_name)?;
+    let key = Value::from(TupleData::from_data(vec![(
+        "stacker"
+            .try_into()
+            .map_err(|_| InterpreterError::Expect("Bad special tuple name".into()))?,
+        Value::Principal(principal),
+    )])?);
+
+    let epoch = *env.epoch();
+    let result = env.global_context.database.fetch_entry_with_size(
+        &pox_contract,
+        map_name,
+        &key,
+        &data_types,
+        &epoch,
+    );
+
+    let result_size = match &result {
+        Ok(data) => data.serialized_byte_len,
+        Err(_e) => (data_types.value_type.size()? + data_types.key_type.size()?) as u64,
+    };
+
+    runtime_cost(ClarityCostFunction::FetchEntry, env, result_size)?;
+
+    result.map(|data| data.value)
+}
+
 pub fn special_stx_burn(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -225,6 +225,14 @@ pub fn native_int_to_utf8(value: Value) -> Result<Value> {
     native_int_to_string_generic(value, Value::string_utf8_from_bytes)
 }
 
+pub fn native_uint_to_ascii(value: Value) -> Result<Value> {
+    // Unlike `int-to-ascii`, only a uint is accepted, so the result fits in (string-ascii 39).
+    match value {
+        Value::UInt(_) => native_int_to_string_generic(value, Value::string_ascii_from_bytes),
+        _ => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, value).into()),
+    }
+}
+
 /// Returns `value` consensus serialized into a `(optional buff)` object.
 /// If the value cannot fit as serialized into the maximum buffer size,
 /// this returns `none`, otherwise, it will be `(some consensus-serialized-buffer)`
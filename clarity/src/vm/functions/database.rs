@@ -35,7 +35,7 @@ use crate::vm::types::{
     BlockInfoProperty, BuffData, BurnBlockInfoProperty, OptionalData, PrincipalData, SequenceData,
     TupleData, TypeSignature, Value, BUFF_32,
 };
-use crate::vm::{eval, Environment, LocalContext};
+use crate::vm::{eval, run_eval_hooks, Environment, LocalContext};
 
 switch_on_global_epoch!(special_fetch_variable(
     special_fetch_variable_v200,
@@ -62,6 +62,43 @@ switch_on_global_epoch!(special_delete_entry(
     special_delete_entry_v205
 ));
 
+/// Report a successful data var or map read to the environment's eval hooks
+fn trace_data_read(env: &mut Environment, name: &str, key: Option<&Value>, result: &Result<Value>) {
+    let contract_context = env.contract_context;
+    if let Ok(value) = result {
+        run_eval_hooks(env, |hook, env| {
+            hook.did_read_data(env, &contract_context.contract_identifier, name, key, value)
+        });
+    }
+}
+
+/// A copy of a value that is about to be written, kept only if there are eval hooks to report
+///  the write to
+fn traced_copy(env: &Environment, value: &Value) -> Option<Value> {
+    env.global_context
+        .eval_hooks
+        .as_ref()
+        .map(|_| value.clone())
+}
+
+/// Report a data var or map write to the environment's eval hooks. A write only happened if
+///  the operation returned `true`: `map-insert` and `map-delete` return `false` if they left
+///  the map unchanged.
+fn trace_data_write(
+    env: &mut Environment,
+    name: &str,
+    key: Option<&Value>,
+    value: Option<&Value>,
+    result: &Result<Value>,
+) {
+    let contract_context = env.contract_context;
+    if let Ok(Value::Bool(true)) = result {
+        run_eval_hooks(env, |hook, env| {
+            hook.did_write_data(env, &contract_context.contract_identifier, name, key, value)
+        });
+    }
+}
+
 pub fn special_contract_call(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -246,9 +283,12 @@ pub fn special_fetch_variable_v200(
     )?;
 
     let epoch = *env.epoch();
-    env.global_context
+    let result = env
+        .global_context
         .database
-        .lookup_variable(contract, var_name, data_types, &epoch)
+        .lookup_variable(contract, var_name, data_types, &epoch);
+    trace_data_read(env, var_name, None, &result);
+    result
 }
 
 /// The Stacks v205 version of fetch_variable uses the actual stored size of the
@@ -283,7 +323,9 @@ pub fn special_fetch_variable_v205(
 
     runtime_cost(ClarityCostFunction::FetchVar, env, result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_read(env, var_name, None, &result);
+    result
 }
 
 pub fn special_set_variable_v200(
@@ -317,11 +359,15 @@ pub fn special_set_variable_v200(
 
     env.add_memory(value.get_memory_use()?)?;
 
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
-    env.global_context
+    let result = env
+        .global_context
         .database
         .set_variable(contract, var_name, value, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    trace_data_write(env, var_name, None, traced_value.as_ref(), &result);
+    result
 }
 
 /// The Stacks v205 version of set_variable uses the actual stored size of the
@@ -349,6 +395,7 @@ pub fn special_set_variable_v205(
         .get(var_name)
         .ok_or(CheckErrors::NoSuchDataVariable(var_name.to_string()))?;
 
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
     let result = env
         .global_context
@@ -364,7 +411,9 @@ pub fn special_set_variable_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_write(env, var_name, None, traced_value.as_ref(), &result);
+    result
 }
 
 pub fn special_fetch_entry_v200(
@@ -393,9 +442,12 @@ pub fn special_fetch_entry_v200(
     )?;
 
     let epoch = *env.epoch();
-    env.global_context
+    let result = env
+        .global_context
         .database
-        .fetch_entry(contract, map_name, &key, data_types, &epoch)
+        .fetch_entry(contract, map_name, &key, data_types, &epoch);
+    trace_data_read(env, map_name, Some(&key), &result);
+    result
 }
 
 /// The Stacks v205 version of fetch_entry uses the actual stored size of the
@@ -432,7 +484,9 @@ pub fn special_fetch_entry_v205(
 
     runtime_cost(ClarityCostFunction::FetchEntry, env, result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_read(env, map_name, Some(&key), &result);
+    result
 }
 
 pub fn special_at_block(
@@ -496,11 +550,22 @@ pub fn special_set_entry_v200(
     env.add_memory(key.get_memory_use()?)?;
     env.add_memory(value.get_memory_use()?)?;
 
+    let traced_key = traced_copy(env, &key);
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
-    env.global_context
+    let result = env
+        .global_context
         .database
         .set_entry(contract, map_name, key, value, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    trace_data_write(
+        env,
+        map_name,
+        traced_key.as_ref(),
+        traced_value.as_ref(),
+        &result,
+    );
+    result
 }
 
 /// The Stacks v205 version of set_entry uses the actual stored size of the
@@ -530,6 +595,8 @@ pub fn special_set_entry_v205(
         .get(map_name)
         .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
 
+    let traced_key = traced_copy(env, &key);
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
     let result = env
         .global_context
@@ -545,7 +612,15 @@ pub fn special_set_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_write(
+        env,
+        map_name,
+        traced_key.as_ref(),
+        traced_value.as_ref(),
+        &result,
+    );
+    result
 }
 
 pub fn special_insert_entry_v200(
@@ -582,12 +657,23 @@ pub fn special_insert_entry_v200(
     env.add_memory(key.get_memory_use()?)?;
     env.add_memory(value.get_memory_use()?)?;
 
+    let traced_key = traced_copy(env, &key);
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
 
-    env.global_context
+    let result = env
+        .global_context
         .database
         .insert_entry(contract, map_name, key, value, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    trace_data_write(
+        env,
+        map_name,
+        traced_key.as_ref(),
+        traced_value.as_ref(),
+        &result,
+    );
+    result
 }
 
 /// The Stacks v205 version of insert_entry uses the actual stored size of the
@@ -617,6 +703,8 @@ pub fn special_insert_entry_v205(
         .get(map_name)
         .ok_or(CheckErrors::NoSuchMap(map_name.to_string()))?;
 
+    let traced_key = traced_copy(env, &key);
+    let traced_value = traced_copy(env, &value);
     let epoch = *env.epoch();
     let result = env
         .global_context
@@ -632,7 +720,15 @@ pub fn special_insert_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_write(
+        env,
+        map_name,
+        traced_key.as_ref(),
+        traced_value.as_ref(),
+        &result,
+    );
+    result
 }
 
 pub fn special_delete_entry_v200(
@@ -667,10 +763,13 @@ pub fn special_delete_entry_v200(
     env.add_memory(key.get_memory_use()?)?;
 
     let epoch = *env.epoch();
-    env.global_context
+    let result = env
+        .global_context
         .database
         .delete_entry(contract, map_name, &key, data_types, &epoch)
-        .map(|data| data.value)
+        .map(|data| data.value);
+    trace_data_write(env, map_name, Some(&key), None, &result);
+    result
 }
 
 /// The Stacks v205 version of delete_entry uses the actual stored size of the
@@ -713,7 +812,9 @@ pub fn special_delete_entry_v205(
 
     env.add_memory(result_size)?;
 
-    result.map(|data| data.value)
+    let result = result.map(|data| data.value);
+    trace_data_write(env, map_name, Some(&key), None, &result);
+    result
 }
 
 pub fn special_get_block_info(
@@ -29,6 +29,7 @@ use crate::vm::errors::{
     InterpreterResult as Result, RuntimeErrorType, ShortReturnType,
 };
 pub use crate::vm::functions::assets::stx_transfer_consolidated;
+pub use crate::vm::functions::sequences::MAX_CONCAT_MANY_ARGS;
 use crate::vm::representations::SymbolicExpressionType::{Atom, List};
 use crate::vm::representations::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 use crate::vm::types::{
@@ -189,6 +190,14 @@ define_versioned_named_enum!(NativeFunctions(ClarityVersion) {
     ToConsensusBuff("to-consensus-buff?", ClarityVersion::Clarity2),
     FromConsensusBuff("from-consensus-buff?", ClarityVersion::Clarity2),
     ReplaceAt("replace-at?", ClarityVersion::Clarity2),
+    StxGetAccountInfo("stx-account-info", ClarityVersion::Clarity3),
+    StxGetDelegationInfo("stx-delegation-info", ClarityVersion::Clarity3),
+    ContractPrincipalConstruct("contract-principal-construct?", ClarityVersion::Clarity3),
+    BitwiseLRotate("bit-rotate-left", ClarityVersion::Clarity3),
+    BitwiseRRotate("bit-rotate-right", ClarityVersion::Clarity3),
+    Log2UInt("log2-uint", ClarityVersion::Clarity3),
+    UIntToAscii("uint-to-ascii", ClarityVersion::Clarity3),
+    ConcatMany("concat-many", ClarityVersion::Clarity3),
 });
 
 impl NativeFunctions {
@@ -333,6 +342,11 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
                 NativeHandle::SingleArg(&conversions::native_int_to_utf8),
                 ClarityCostFunction::IntToUtf8,
             ),
+            UIntToAscii => NativeFunction(
+                "native_uint_to_ascii",
+                NativeHandle::SingleArg(&conversions::native_uint_to_ascii),
+                ClarityCostFunction::IntToAscii,
+            ),
             IsStandard => SpecialFunction("special_is_standard", &principals::special_is_standard),
             PrincipalDestruct => SpecialFunction(
                 "special_principal_destruct",
@@ -342,8 +356,13 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
                 "special_principal_construct",
                 &principals::special_principal_construct,
             ),
+            ContractPrincipalConstruct => SpecialFunction(
+                "special_contract_principal_construct",
+                &principals::special_contract_principal_construct,
+            ),
             Fold => SpecialFunction("special_fold", &sequences::special_fold),
             Concat => SpecialFunction("special_concat", &sequences::special_concat),
+            ConcatMany => SpecialFunction("special_concat_many", &sequences::special_concat_many),
             AsMaxLen => SpecialFunction("special_as_max_len", &sequences::special_as_max_len),
             Append => SpecialFunction("special_append", &sequences::special_append),
             Len => NativeFunction(
@@ -524,6 +543,13 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
             ),
             StxBurn => SpecialFunction("special_stx_burn", &assets::special_stx_burn),
             StxGetAccount => SpecialFunction("stx_get_account", &assets::special_stx_account),
+            StxGetAccountInfo => {
+                SpecialFunction("stx_get_account_info", &assets::special_stx_account_info)
+            }
+            StxGetDelegationInfo => SpecialFunction(
+                "stx_get_delegation_info",
+                &assets::special_stx_delegation_info,
+            ),
             ToConsensusBuff => NativeFunction205(
                 "to_consensus_buff",
                 NativeHandle::SingleArg(&conversions::to_consensus_buff),
@@ -559,6 +585,21 @@ pub fn lookup_reserved_functions(name: &str, version: &ClarityVersion) -> Option
                 NativeHandle::DoubleArg(&arithmetic::native_bitwise_right_shift),
                 ClarityCostFunction::BitwiseRShift,
             ),
+            BitwiseLRotate => NativeFunction(
+                "native_bitwise_left_rotate",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_left_rotate),
+                ClarityCostFunction::BitwiseLShift,
+            ),
+            BitwiseRRotate => NativeFunction(
+                "native_bitwise_right_rotate",
+                NativeHandle::DoubleArg(&arithmetic::native_bitwise_right_rotate),
+                ClarityCostFunction::BitwiseRShift,
+            ),
+            Log2UInt => NativeFunction(
+                "native_log2_uint",
+                NativeHandle::SingleArg(&arithmetic::native_log2_uint),
+                ClarityCostFunction::Log2,
+            ),
             BitwiseXor2 => NativeFunction(
                 "native_bitwise_xor",
                 NativeHandle::MoreArg(&arithmetic::native_bitwise_xor),
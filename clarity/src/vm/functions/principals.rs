@@ -28,6 +28,7 @@ pub enum PrincipalConstructErrorCode {
     VERSION_BYTE = 0,
     BUFFER_LENGTH = 1,
     CONTRACT_NAME = 2,
+    CONTRACT_ISSUER = 3,
 }
 
 /// Returns true if `version` indicates a mainnet address.
@@ -187,6 +188,45 @@ pub fn special_principal_destruct(
     }))
 }
 
+/// Checks that `name` is a valid contract name.
+/// The type-checker will have verified that it's a (string-ascii 40), but it can't check its
+/// length lower bound or its characters.  Returns `Ok(None)` if `name` is not a valid contract
+/// name, which warrants an (err ..) response.
+fn parse_contract_name(name: Value) -> Result<Option<ContractName>> {
+    let name_bytes = match name {
+        Value::Sequence(SequenceData::String(CharType::ASCII(ascii_data))) => ascii_data,
+        _ => {
+            return Err(CheckErrors::TypeValueError(
+                TypeSignature::contract_name_string_ascii_type()?,
+                name,
+            )
+            .into())
+        }
+    };
+
+    // If it's not long enough, then it's a runtime error that warrants an (err ..) response.
+    if name_bytes.data.len() < CONTRACT_MIN_NAME_LENGTH {
+        return Ok(None);
+    }
+
+    // if it's too long, then this should have been caught by the type-checker
+    if name_bytes.data.len() > CONTRACT_MAX_NAME_LENGTH {
+        return Err(CheckErrors::TypeValueError(
+            TypeSignature::contract_name_string_ascii_type()?,
+            Value::from(name_bytes),
+        )
+        .into());
+    }
+
+    let name_string = String::from_utf8(name_bytes.data).map_err(|_| {
+        InterpreterError::Expect(
+            "FAIL: could not convert bytes of type (string-ascii 40) back to a UTF-8 string".into(),
+        )
+    })?;
+
+    Ok(ContractName::try_from(name_string).ok())
+}
+
 pub fn special_principal_construct(
     args: &[SymbolicExpression],
     env: &mut Environment,
@@ -262,48 +302,9 @@ pub fn special_principal_construct(
 
     let principal = if let Some(name) = name_opt {
         // requested a contract principal.  Verify that the `name` is a valid ContractName.
-        // The type-checker will have verified that it's (string-ascii 40), but not long enough.
-        let name_bytes = match name {
-            Value::Sequence(SequenceData::String(CharType::ASCII(ascii_data))) => ascii_data,
-            _ => {
-                return Err(CheckErrors::TypeValueError(
-                    TypeSignature::contract_name_string_ascii_type()?,
-                    name,
-                )
-                .into())
-            }
-        };
-
-        // If it's not long enough, then it's a runtime error that warrants an (err ..) response.
-        if name_bytes.data.len() < CONTRACT_MIN_NAME_LENGTH {
-            return create_principal_true_error_response(
-                PrincipalConstructErrorCode::CONTRACT_NAME,
-            );
-        }
-
-        // if it's too long, then this should have been caught by the type-checker
-        if name_bytes.data.len() > CONTRACT_MAX_NAME_LENGTH {
-            return Err(CheckErrors::TypeValueError(
-                TypeSignature::contract_name_string_ascii_type()?,
-                Value::from(name_bytes),
-            )
-            .into());
-        }
-
-        // The type-checker can't verify that the name is a valid ContractName, so we'll need to do
-        // it here at runtime.  If it's not valid, then it warrants this function evaluating to
-        // (err ..).
-        let name_string = String::from_utf8(name_bytes.data).map_err(|_| {
-            InterpreterError::Expect(
-                "FAIL: could not convert bytes of type (string-ascii 40) back to a UTF-8 string"
-                    .into(),
-            )
-        })?;
-
-        let contract_name = match ContractName::try_from(name_string) {
-            Ok(cn) => cn,
-            Err(_) => {
-                // not a valid contract name
+        let contract_name = match parse_contract_name(name)? {
+            Some(cn) => cn,
+            None => {
                 return create_principal_true_error_response(
                     PrincipalConstructErrorCode::CONTRACT_NAME,
                 );
@@ -327,3 +328,51 @@ pub fn special_principal_construct(
         create_principal_value_error_response(PrincipalConstructErrorCode::VERSION_BYTE, principal)
     }
 }
+
+pub fn special_contract_principal_construct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+    // This reuses the cost of `principal-construct?`, which does the same work: checking the
+    // issuer's version byte and the contract name, and building the principal.
+    runtime_cost(ClarityCostFunction::PrincipalConstruct, env, 0)?;
+
+    let issuer = eval(&args[0], env, context)?;
+    let name = eval(&args[1], env, context)?;
+
+    let issuer = match issuer {
+        Value::Principal(PrincipalData::Standard(issuer)) => issuer,
+        Value::Principal(PrincipalData::Contract(_)) => {
+            // contracts cannot deploy contracts, so this can never name a contract
+            return create_principal_true_error_response(
+                PrincipalConstructErrorCode::CONTRACT_ISSUER,
+            );
+        }
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, issuer).into()),
+    };
+
+    let contract_name = match parse_contract_name(name)? {
+        Some(cn) => cn,
+        None => {
+            return create_principal_true_error_response(
+                PrincipalConstructErrorCode::CONTRACT_NAME,
+            );
+        }
+    };
+
+    let version_byte_is_valid = version_matches_current_network(issuer.0, env.global_context);
+    let principal = Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier::new(
+        issuer,
+        contract_name,
+    )));
+
+    if version_byte_is_valid {
+        Ok(Value::okay(principal).map_err(|_| {
+            InterpreterError::Expect("FAIL: failed to build an (ok ..) response".into())
+        })?)
+    } else {
+        create_principal_value_error_response(PrincipalConstructErrorCode::VERSION_BYTE, principal)
+    }
+}
@@ -22,8 +22,8 @@ use stacks_common::types::StacksEpochId;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::costs::{cost_functions, runtime_cost, CostOverflowingMath};
 use crate::vm::errors::{
-    check_argument_count, check_arguments_at_least, CheckErrors, InterpreterResult as Result,
-    RuntimeErrorType,
+    check_argument_count, check_arguments_at_least, check_arguments_at_most, CheckErrors,
+    InterpreterResult as Result, RuntimeErrorType,
 };
 use crate::vm::representations::{SymbolicExpression, SymbolicExpressionType};
 use crate::vm::types::signatures::ListTypeData;
@@ -274,6 +274,54 @@ pub fn special_concat_v205(
     Ok(wrapped_seq)
 }
 
+/// The most sequences `concat-many` can join in one call
+pub const MAX_CONCAT_MANY_ARGS: usize = 16;
+
+pub fn special_concat_many(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_arguments_at_least(1, args)?;
+    check_arguments_at_most(MAX_CONCAT_MANY_ARGS, args)?;
+
+    let eval_tried: Result<Vec<Value>> = args.iter().map(|x| eval(x, env, context)).collect();
+    let mut values = eval_tried?.into_iter();
+    let mut wrapped_seq = values
+        .next()
+        .ok_or(CheckErrors::RequiresAtLeastArguments(1, 0))?;
+
+    match wrapped_seq {
+        Value::Sequence(ref mut seq) => {
+            let mut total_len = seq.len() as u64;
+            let mut other_seqs = vec![];
+            for other_wrapped_seq in values {
+                match other_wrapped_seq {
+                    Value::Sequence(other_seq) => {
+                        total_len = total_len.cost_overflow_add(other_seq.len() as u64)?;
+                        other_seqs.push(other_seq);
+                    }
+                    _ => {
+                        runtime_cost(ClarityCostFunction::Concat, env, total_len)?;
+                        return Err(RuntimeErrorType::BadTypeConstruction.into());
+                    }
+                }
+            }
+            runtime_cost(ClarityCostFunction::Concat, env, total_len)?;
+
+            for other_seq in other_seqs.into_iter() {
+                seq.concat(env.epoch(), other_seq)?;
+            }
+        }
+        _ => {
+            runtime_cost(ClarityCostFunction::Concat, env, 1)?;
+            return Err(RuntimeErrorType::BadTypeConstruction.into());
+        }
+    };
+
+    Ok(wrapped_seq)
+}
+
 pub fn special_as_max_len(
     args: &[SymbolicExpression],
     env: &mut Environment,
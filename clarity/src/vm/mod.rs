@@ -41,6 +41,8 @@ pub mod version;
 
 pub mod coverage;
 
+pub mod trace;
+
 pub mod events;
 
 #[cfg(any(test, feature = "testing"))]
@@ -61,7 +63,7 @@ use self::analysis::ContractAnalysis;
 use self::ast::{ASTRules, ContractAST};
 use self::costs::ExecutionCost;
 use self::diagnostic::Diagnostic;
-use crate::vm::callables::CallableType;
+use crate::vm::callables::{CallableType, FunctionIdentifier};
 use crate::vm::contexts::GlobalContext;
 pub use crate::vm::contexts::{
     CallStack, ContractContext, Environment, LocalContext, MAX_CONTEXT_DEPTH,
@@ -76,6 +78,7 @@ pub use crate::vm::database::clarity_db::StacksEpoch;
 use crate::vm::errors::{
     CheckErrors, Error, InterpreterError, InterpreterResult as Result, RuntimeErrorType,
 };
+use crate::vm::events::StacksTransactionEvent;
 use crate::vm::functions::define::DefineResult;
 pub use crate::vm::functions::stx_transfer_consolidated;
 pub use crate::vm::representations::{
@@ -143,6 +146,9 @@ impl CostSynthesis {
 }
 
 /// EvalHook defines an interface for hooks to execute during evaluation.
+/// Instrumentation for the interpreter, e.g. for debuggers, coverage tools and tracers.
+/// Register hooks with `OwnedEnvironment::add_eval_hook()`. Every method has a no-op default,
+///  so a hook only implements the events it is interested in.
 pub trait EvalHook {
     // Called before the expression is evaluated
     fn will_begin_eval(
@@ -150,7 +156,8 @@ pub trait EvalHook {
         _env: &mut Environment,
         _context: &LocalContext,
         _expr: &SymbolicExpression,
-    );
+    ) {
+    }
 
     // Called after the expression is evaluated
     fn did_finish_eval(
@@ -159,10 +166,91 @@ pub trait EvalHook {
         _context: &LocalContext,
         _expr: &SymbolicExpression,
         _res: &core::result::Result<Value, crate::vm::errors::Error>,
-    );
+    ) {
+    }
 
     // Called upon completion of the execution
-    fn did_complete(&mut self, _result: core::result::Result<&mut ExecutionResult, String>);
+    fn did_complete(&mut self, _result: core::result::Result<&mut ExecutionResult, String>) {}
+
+    // Called before a user-defined function is applied
+    fn will_call_function(
+        &mut self,
+        _env: &mut Environment,
+        _function: &FunctionIdentifier,
+        _args: &[Value],
+    ) {
+    }
+
+    // Called after a user-defined function returns
+    fn did_return_from_function(
+        &mut self,
+        _env: &mut Environment,
+        _function: &FunctionIdentifier,
+        _res: &core::result::Result<Value, crate::vm::errors::Error>,
+    ) {
+    }
+
+    // Called before a public or read-only function of a contract is invoked, either by
+    //  a `contract-call?` or by a transaction
+    fn will_call_contract(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _function: &str,
+        _args: &[Value],
+    ) {
+    }
+
+    // Called after a contract invocation returns
+    fn did_finish_contract_call(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _function: &str,
+        _res: &core::result::Result<Value, crate::vm::errors::Error>,
+    ) {
+    }
+
+    // Called after a data var or map entry is read. `key` is `None` for data vars.
+    fn did_read_data(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _name: &str,
+        _key: Option<&Value>,
+        _value: &Value,
+    ) {
+    }
+
+    // Called after a data var or map entry is written. `key` is `None` for data vars,
+    //  and `value` is `None` if the map entry was deleted.
+    fn did_write_data(
+        &mut self,
+        _env: &mut Environment,
+        _contract: &QualifiedContractIdentifier,
+        _name: &str,
+        _key: Option<&Value>,
+        _value: Option<&Value>,
+    ) {
+    }
+
+    // Called when an event is recorded: an STX, fungible token or non-fungible token
+    //  transfer, mint or burn, an STX lock, or a `print`. Events of a transaction or
+    //  contract call that is rolled back are reported too.
+    fn did_emit_event(&mut self, _event: &StacksTransactionEvent) {}
+}
+
+/// Invoke `f` on each of the environment's eval hooks, if there are any
+pub(crate) fn run_eval_hooks<F>(env: &mut Environment, mut f: F)
+where
+    F: FnMut(&mut dyn EvalHook, &mut Environment),
+{
+    if let Some(mut eval_hooks) = env.global_context.eval_hooks.take() {
+        for hook in eval_hooks.iter_mut() {
+            f(*hook, env);
+        }
+        env.global_context.eval_hooks = Some(eval_hooks);
+    }
 }
 
 fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) -> Result<Value> {
@@ -0,0 +1,473 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Canonical formatting of Clarity source.
+//!
+//! Expressions that fit on a line are kept on one line.  Lists that do not fit keep their head
+//! (and first argument) on the opening line and put every other item on a line of its own,
+//! indented by `INDENT` spaces, with closing parentheses stacked at the end of the last item.
+//! Lists that do not start with an atom, like `let` bindings, are aligned just inside their
+//! opening parenthesis instead.  Top-level expressions are separated by a blank line.
+
+use super::{PreSymbolicExpression, PreSymbolicExpressionType};
+use crate::vm::ast::errors::ParseResult;
+use crate::vm::ast::parser::v2::lexer::token::Token;
+use crate::vm::ast::parser::v2::lexer::Lexer;
+use crate::vm::ast::parser::v2::parse;
+use crate::vm::types::{
+    ASCIIData, CharType, PrincipalData, SequenceData, TraitIdentifier, UTF8Data, Value,
+};
+
+/// Lines are broken up once they would grow longer than this
+pub const MAX_LINE_WIDTH: usize = 80;
+const INDENT: usize = 2;
+
+/// Forms whose arguments all go on lines of their own when the form does not fit on one line
+const BODY_FORMS: &[&str] = &["begin"];
+
+/// Format Clarity source.  Comments are kept: comments on lines of their own stay on lines of
+/// their own before the expression that follows them, and comments that follow code stay at the
+/// end of that code's line.
+pub fn format_source(source: &str) -> ParseResult<String> {
+    let exprs = parse(source)?;
+    let mut formatter = Formatter {
+        end_of_line_comments: find_end_of_line_comments(source),
+        next_comment: 0,
+    };
+    Ok(formatter.format_top_level(&exprs))
+}
+
+/// Format expressions parsed by the v2 parser.  The parsed expressions do not record where their
+/// comments were, so every comment goes on a line of its own.
+pub fn format_pre_expressions(exprs: &[PreSymbolicExpression]) -> String {
+    let mut formatter = Formatter {
+        end_of_line_comments: vec![],
+        next_comment: 0,
+    };
+    formatter.format_top_level(exprs)
+}
+
+/// For each comment in `source`, in order, whether it follows code on the same line
+fn find_end_of_line_comments(source: &str) -> Vec<bool> {
+    let mut end_of_line_comments = vec![];
+    let mut lexer = match Lexer::new(source, false) {
+        Ok(lexer) => lexer,
+        Err(_) => return end_of_line_comments,
+    };
+    let mut last_code_line = 0;
+    while let Ok(placed_token) = lexer.read_token() {
+        match placed_token.token {
+            Token::Eof => break,
+            Token::Whitespace => {}
+            Token::Comment(_) => {
+                end_of_line_comments.push(placed_token.span.start_line == last_code_line)
+            }
+            _ => last_code_line = placed_token.span.end_line,
+        }
+    }
+    end_of_line_comments
+}
+
+struct Formatter {
+    end_of_line_comments: Vec<bool>,
+    /// index of the next comment to be formatted, in source order
+    next_comment: usize,
+}
+
+impl Formatter {
+    /// Does the next comment follow code on the same line?  Must be called once per comment, in
+    /// source order.
+    fn next_comment_ends_line(&mut self) -> bool {
+        let ends_line = self
+            .end_of_line_comments
+            .get(self.next_comment)
+            .cloned()
+            .unwrap_or(false);
+        self.next_comment += 1;
+        ends_line
+    }
+
+    fn format_top_level(&mut self, exprs: &[PreSymbolicExpression]) -> String {
+        let mut out = String::new();
+        // was the last thing written a comment on a line of its own?
+        let mut in_comment_block = false;
+        for expr in exprs.iter() {
+            if let PreSymbolicExpressionType::Comment(comment) = &expr.pre_expr {
+                if self.next_comment_ends_line() && !out.is_empty() && !in_comment_block {
+                    out.push(' ');
+                    out.push_str(&format_comment(comment));
+                    continue;
+                }
+                if in_comment_block {
+                    out.push('\n');
+                } else if !out.is_empty() {
+                    out.push_str("\n\n");
+                }
+                out.push_str(&format_comment(comment));
+                in_comment_block = true;
+            } else {
+                if in_comment_block {
+                    out.push('\n');
+                } else if !out.is_empty() {
+                    out.push_str("\n\n");
+                }
+                out.push_str(&self.format_expr(expr, 0));
+                in_comment_block = false;
+            }
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Format an expression whose first line starts at column `indent`
+    fn format_expr(&mut self, expr: &PreSymbolicExpression, indent: usize) -> String {
+        if !has_comments(expr) {
+            let flat = format_flat(expr);
+            if indent + flat.len() <= MAX_LINE_WIDTH {
+                return flat;
+            }
+        }
+        match &expr.pre_expr {
+            PreSymbolicExpressionType::List(items) => self.format_list(items, indent),
+            PreSymbolicExpressionType::Tuple(items) => self.format_tuple(items, indent),
+            _ => format_flat(expr),
+        }
+    }
+
+    fn format_list(&mut self, items: &[PreSymbolicExpression], indent: usize) -> String {
+        let (num_header_items, body_indent) = match items.first().map(|item| &item.pre_expr) {
+            Some(PreSymbolicExpressionType::Atom(name)) => {
+                if BODY_FORMS.contains(&name.as_str()) {
+                    (1, indent + INDENT)
+                } else {
+                    (2, indent + INDENT)
+                }
+            }
+            _ => (1, indent + 1),
+        };
+
+        let mut out = "(".to_string();
+        let mut seen_comment = false;
+        let mut ends_with_comment = false;
+        for (i, item) in items.iter().enumerate() {
+            if let PreSymbolicExpressionType::Comment(comment) = &item.pre_expr {
+                if self.next_comment_ends_line() && !ends_with_comment {
+                    out.push(' ');
+                } else {
+                    push_newline(&mut out, body_indent);
+                }
+                out.push_str(&format_comment(comment));
+                seen_comment = true;
+                ends_with_comment = true;
+                continue;
+            }
+
+            if i == 0 {
+                out.push_str(&self.format_expr(item, indent + 1));
+            } else if i < num_header_items && !seen_comment {
+                out.push(' ');
+                let column = current_column(&out, indent);
+                out.push_str(&self.format_expr(item, column));
+            } else {
+                push_newline(&mut out, body_indent);
+                out.push_str(&self.format_expr(item, body_indent));
+            }
+            ends_with_comment = false;
+        }
+
+        if ends_with_comment {
+            push_newline(&mut out, indent);
+        }
+        out.push(')');
+        out
+    }
+
+    /// Tuples that do not fit on one line get one key-value pair per line
+    fn format_tuple(&mut self, items: &[PreSymbolicExpression], indent: usize) -> String {
+        let last_value = items
+            .iter()
+            .rposition(|item| !matches!(item.pre_expr, PreSymbolicExpressionType::Comment(_)));
+
+        let mut out = "{".to_string();
+        let mut num_items = 0;
+        let mut ends_with_comment = false;
+        for (i, item) in items.iter().enumerate() {
+            if let PreSymbolicExpressionType::Comment(comment) = &item.pre_expr {
+                if self.next_comment_ends_line() && !ends_with_comment {
+                    out.push(' ');
+                } else {
+                    push_newline(&mut out, indent + INDENT);
+                }
+                out.push_str(&format_comment(comment));
+                ends_with_comment = true;
+                continue;
+            }
+
+            if num_items % 2 == 0 {
+                // a key
+                if num_items == 0 && !ends_with_comment {
+                    out.push(' ');
+                } else {
+                    push_newline(&mut out, indent + INDENT);
+                }
+                out.push_str(&format_flat(item));
+                out.push(':');
+            } else {
+                // a value
+                if ends_with_comment {
+                    push_newline(&mut out, indent + 2 * INDENT);
+                } else {
+                    out.push(' ');
+                }
+                let column = current_column(&out, indent);
+                out.push_str(&self.format_expr(item, column));
+                if Some(i) != last_value {
+                    out.push(',');
+                }
+            }
+            num_items += 1;
+            ends_with_comment = false;
+        }
+
+        if ends_with_comment {
+            push_newline(&mut out, indent);
+        } else if num_items > 0 {
+            out.push(' ');
+        }
+        out.push('}');
+        out
+    }
+}
+
+fn push_newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    out.push_str(&" ".repeat(indent));
+}
+
+/// The column that the next character written to `out` will be at, when `out` starts at column
+/// `indent`
+fn current_column(out: &str, indent: usize) -> usize {
+    match out.rfind('\n') {
+        Some(newline) => out.len() - newline - 1,
+        None => indent + out.len(),
+    }
+}
+
+fn has_comments(expr: &PreSymbolicExpression) -> bool {
+    match &expr.pre_expr {
+        PreSymbolicExpressionType::Comment(_) => true,
+        PreSymbolicExpressionType::List(items) | PreSymbolicExpressionType::Tuple(items) => {
+            items.iter().any(has_comments)
+        }
+        _ => false,
+    }
+}
+
+fn format_comment(comment: &str) -> String {
+    if comment.is_empty() {
+        ";;".to_string()
+    } else {
+        format!(";; {}", comment)
+    }
+}
+
+/// Format an expression on one line.  `expr` must not contain comments.
+fn format_flat(expr: &PreSymbolicExpression) -> String {
+    match &expr.pre_expr {
+        PreSymbolicExpressionType::AtomValue(value) => format_value(value),
+        PreSymbolicExpressionType::Atom(name) => name.to_string(),
+        PreSymbolicExpressionType::List(items) => {
+            let items: Vec<_> = items.iter().map(format_flat).collect();
+            format!("({})", items.join(" "))
+        }
+        PreSymbolicExpressionType::Tuple(items) => {
+            if items.is_empty() {
+                return "{}".to_string();
+            }
+            let pairs: Vec<_> = items
+                .chunks(2)
+                .map(|pair| {
+                    let pair: Vec<_> = pair.iter().map(format_flat).collect();
+                    pair.join(": ")
+                })
+                .collect();
+            format!("{{ {} }}", pairs.join(", "))
+        }
+        PreSymbolicExpressionType::SugaredContractIdentifier(contract_name) => {
+            format!(".{}", contract_name)
+        }
+        PreSymbolicExpressionType::SugaredFieldIdentifier(contract_name, name) => {
+            format!(".{}.{}", contract_name, name)
+        }
+        PreSymbolicExpressionType::FieldIdentifier(TraitIdentifier {
+            name,
+            contract_identifier,
+        }) => format!("'{}.{}", contract_identifier, name),
+        PreSymbolicExpressionType::TraitReference(name) => format!("<{}>", name),
+        PreSymbolicExpressionType::Comment(comment) => format_comment(comment),
+        PreSymbolicExpressionType::Placeholder(placeholder) => placeholder.clone(),
+    }
+}
+
+/// Format a literal value the way the lexer reads it
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Principal(PrincipalData::Standard(principal)) => format!("'{}", principal),
+        Value::Principal(PrincipalData::Contract(contract_identifier)) => {
+            format!("'{}", contract_identifier)
+        }
+        Value::Sequence(SequenceData::String(CharType::ASCII(ASCIIData { data }))) => {
+            let mut out = "\"".to_string();
+            for c in data.iter() {
+                push_escaped_char(&mut out, *c as char);
+            }
+            out.push('"');
+            out
+        }
+        Value::Sequence(SequenceData::String(CharType::UTF8(UTF8Data { data }))) => {
+            let mut out = "u\"".to_string();
+            for c in data.iter() {
+                match std::str::from_utf8(c).ok().and_then(|c| c.chars().next()) {
+                    Some(c) if c.is_ascii() => push_escaped_char(&mut out, c),
+                    Some(c) => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                    None => {}
+                }
+            }
+            out.push('"');
+            out
+        }
+        _ => value.to_string(),
+    }
+}
+
+fn push_escaped_char(out: &mut String, c: char) {
+    match c {
+        '\\' => out.push_str("\\\\"),
+        '"' => out.push_str("\\\""),
+        '\n' => out.push_str("\\n"),
+        '\t' => out.push_str("\\t"),
+        '\r' => out.push_str("\\r"),
+        '\0' => out.push_str("\\0"),
+        c => out.push(c),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_formats_to(source: &str, expected: &str) {
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        // formatting is idempotent, and doesn't change what the source means
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+        assert_eq!(parse(&formatted).unwrap(), parse(source).unwrap());
+    }
+
+    #[test]
+    fn test_format_short_expressions() {
+        assert_formats_to(
+            "(define-constant   ERR_UNAUTHORIZED (err u401))\n\n\n(define-data-var counter uint u0)",
+            "(define-constant ERR_UNAUTHORIZED (err u401))\n\n(define-data-var counter uint u0)\n",
+        );
+        assert_formats_to(
+            "(define-map balances {owner:principal} {amount:uint,locked: bool})",
+            "(define-map balances { owner: principal } { amount: uint, locked: bool })\n",
+        );
+        assert_formats_to(
+            "(define-trait t ((f (uint) (response uint uint)))) (impl-trait .traits.t) (use-trait t2 'SP000000000000000000002Q6VF78.traits.t)",
+            "(define-trait t ((f (uint) (response uint uint))))\n\n(impl-trait .traits.t)\n\n(use-trait t2 'SP000000000000000000002Q6VF78.traits.t)\n",
+        );
+        assert_formats_to(
+            "(print \"say \\\"hi\\\"\\n\") (print u\"caf\\u{e9}\") (print 0x0102) (print 'SP000000000000000000002Q6VF78.pox)",
+            "(print \"say \\\"hi\\\"\\n\")\n\n(print u\"caf\\u{e9}\")\n\n(print 0x0102)\n\n(print 'SP000000000000000000002Q6VF78.pox)\n",
+        );
+    }
+
+    #[test]
+    fn test_format_long_expressions() {
+        let source = "(define-public (transfer (amount uint) (sender principal) (recipient principal))
+  (let ((sender-balance (get-balance sender)) (new-balance (- sender-balance amount)))
+    (asserts! (is-eq tx-sender sender) ERR_UNAUTHORIZED)
+    (begin (map-set balances { owner: sender } { amount: new-balance, locked: false }) (ok true))))";
+        let expected =
+            "(define-public (transfer (amount uint) (sender principal) (recipient principal))
+  (let ((sender-balance (get-balance sender))
+        (new-balance (- sender-balance amount)))
+    (asserts! (is-eq tx-sender sender) ERR_UNAUTHORIZED)
+    (begin
+      (map-set balances
+        { owner: sender }
+        { amount: new-balance, locked: false })
+      (ok true))))
+";
+        assert_formats_to(source, expected);
+    }
+
+    #[test]
+    fn test_format_comments() {
+        let source = ";; a counter
+;; that counts
+
+(define-data-var counter uint u0) ;; starts at zero
+(define-public (increment)
+  ;; add one
+  (begin (var-set counter (+ (var-get counter) u1)) ;; bump it
+    (ok (var-get counter)) ;; done
+  ))
+;; the end";
+        let expected = ";; a counter
+;; that counts
+(define-data-var counter uint u0) ;; starts at zero
+
+(define-public (increment)
+  ;; add one
+  (begin
+    (var-set counter (+ (var-get counter) u1)) ;; bump it
+    (ok (var-get counter)) ;; done
+  ))
+
+;; the end
+";
+        assert_formats_to(source, expected);
+    }
+
+    #[test]
+    fn test_format_tuple_comments() {
+        let source = "(define-constant T {
+  a: u1, ;; first
+  ;; and then
+  b: u2
+})";
+        let expected = "(define-constant T
+  { a: u1, ;; first
+    ;; and then
+    b: u2 })
+";
+        assert_formats_to(source, expected);
+    }
+
+    #[test]
+    fn test_format_pre_expressions() {
+        let exprs = parse("(define-data-var counter uint u0) ;; starts at zero").unwrap();
+        assert_eq!(
+            format_pre_expressions(&exprs),
+            "(define-data-var counter uint u0)\n\n;; starts at zero\n"
+        );
+    }
+}
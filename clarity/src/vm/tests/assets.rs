@@ -27,7 +27,7 @@ use crate::vm::tests::{
 };
 use crate::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier, Value};
 use crate::vm::version::ClarityVersion;
-use crate::vm::ContractContext;
+use crate::vm::{ContractContext, EvalHook};
 
 const FIRST_CLASS_TOKENS: &str = "(define-fungible-token stackaroos)
          (define-read-only (my-ft-get-balance (account principal))
@@ -928,6 +928,70 @@ fn test_total_supply(epoch: StacksEpochId, mut env_factory: TopLevelMemoryEnviro
     });
 }
 
+struct EventRecorder {
+    events: Vec<StacksTransactionEvent>,
+}
+
+impl EvalHook for EventRecorder {
+    fn did_emit_event(&mut self, event: &StacksTransactionEvent) {
+        self.events.push(event.clone());
+    }
+}
+
+#[apply(test_epochs)]
+fn test_eval_hook_sees_events(
+    epoch: StacksEpochId,
+    mut env_factory: TopLevelMemoryEnvironmentGenerator,
+) {
+    let contract = "(define-fungible-token stackaroos)
+         (define-non-fungible-token names int)
+         (define-public (faucet)
+            (begin (try! (ft-mint? stackaroos u2 tx-sender))
+                   (nft-mint? names 1 tx-sender)))
+         (define-public (send (to principal))
+            (begin (print to)
+                   (ft-transfer? stackaroos u1 tx-sender to)))";
+
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p2 = execute("'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G");
+    let p1_principal = match p1 {
+        Value::Principal(ref data) => data.clone(),
+        _ => panic!(),
+    };
+    let contract_id = QualifiedContractIdentifier::local("tokens").unwrap();
+
+    let mut recorder = EventRecorder { events: vec![] };
+    let mut owned_env = env_factory.get_env(epoch);
+    owned_env
+        .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+        .unwrap();
+    owned_env.add_eval_hook(&mut recorder);
+
+    let (result, _asset_map, faucet_events) = execute_transaction(
+        &mut owned_env,
+        p1_principal.clone(),
+        &contract_id,
+        "faucet",
+        &[],
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+    let (result, _asset_map, send_events) = execute_transaction(
+        &mut owned_env,
+        p1_principal.clone(),
+        &contract_id,
+        "send",
+        &symbols_from_values(vec![p2]),
+    )
+    .unwrap();
+    assert!(is_committed(&result));
+    drop(owned_env);
+
+    assert_eq!(faucet_events.len(), 2);
+    assert_eq!(send_events.len(), 2);
+    assert_eq!(recorder.events, [faucet_events, send_events].concat());
+}
+
 #[apply(test_epochs)]
 fn test_overlapping_nfts(
     epoch: StacksEpochId,
@@ -1387,3 +1451,32 @@ fn test_simple_naming_system(
         );
     }
 }
+
+#[test]
+fn test_stx_account_info_unmaterialized() {
+    let account_info = crate::vm::execute_with_parameters(
+        "(stx-account-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        ClarityVersion::Clarity3,
+        StacksEpochId::latest(),
+        ASTRules::PrecheckSize,
+        false,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        account_info.to_string(),
+        "(tuple (locked u0) (pox-version u0) (unlock-height u0) (unlocked u0))"
+    );
+
+    // no PoX contract is instantiated in this environment
+    let delegation_info = crate::vm::execute_with_parameters(
+        "(stx-delegation-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        ClarityVersion::Clarity3,
+        StacksEpochId::latest(),
+        ASTRules::PrecheckSize,
+        false,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(delegation_info, Value::none());
+}
@@ -14,8 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use stacks_common::types::StacksEpochId;
+
 pub use crate::vm::analysis::errors::{CheckError, CheckErrors};
-use crate::vm::execute_v2;
+use crate::vm::ast::ASTRules;
 use crate::vm::types::SequenceSubtype::{BufferType, StringType};
 use crate::vm::types::StringSubtype::ASCII;
 use crate::vm::types::TypeSignature::SequenceType;
@@ -23,6 +25,7 @@ use crate::vm::types::{
     ASCIIData, BuffData, BufferLength, CharType, SequenceData, TypeSignature, UTF8Data, Value,
 };
 use crate::vm::ClarityVersion;
+use crate::vm::{execute_v2, execute_with_parameters};
 use std::convert::TryFrom;
 
 #[test]
@@ -396,6 +399,38 @@ fn test_simple_int_to_ascii() {
     );
 }
 
+#[test]
+fn test_uint_to_ascii() {
+    let run = |input: &str| {
+        execute_with_parameters(
+            input,
+            ClarityVersion::Clarity3,
+            StacksEpochId::latest(),
+            ASTRules::PrecheckSize,
+            false,
+        )
+    };
+
+    assert_eq!(
+        run("(uint-to-ascii u1)").unwrap().unwrap(),
+        Value::string_ascii_from_bytes("1".into()).unwrap()
+    );
+    assert_eq!(
+        run("(uint-to-ascii u340282366920938463463374607431768211455)")
+            .unwrap()
+            .unwrap(),
+        Value::string_ascii_from_bytes("340282366920938463463374607431768211455".into()).unwrap()
+    );
+    assert_eq!(
+        run("(uint-to-ascii)").unwrap_err(),
+        CheckErrors::IncorrectArgumentCount(1, 0).into()
+    );
+    assert_eq!(
+        run("(uint-to-ascii 1)").unwrap_err(),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(1)).into()
+    );
+}
+
 #[test]
 fn test_simple_int_to_utf8() {
     let good1_test = r#"(int-to-utf8 1)"#;
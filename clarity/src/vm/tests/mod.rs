@@ -78,6 +78,8 @@ macro_rules! clarity_template {
                 // don't test these pairs, because they aren't supported:
                 (StacksEpochId::Epoch20, ClarityVersion::Clarity2) => (),
                 (StacksEpochId::Epoch2_05, ClarityVersion::Clarity2) => (),
+                // Clarity 3 is not yet activated in any epoch
+                (_, ClarityVersion::Clarity3) => (),
                 // this will lead to a compile time failure if a pair is left out
                 //  of the clarity_template! macro list
                 $((StacksEpochId::$epoch, ClarityVersion::$clarity))|* => (),
@@ -1162,3 +1162,77 @@ fn test_principal_construct_response_errors() {
         .unwrap()
     );
 }
+
+#[test]
+fn test_contract_principal_construct() {
+    let run = |input: &str| {
+        execute_with_parameters(
+            input,
+            ClarityVersion::Clarity3,
+            StacksEpochId::latest(),
+            ASTRules::PrecheckSize,
+            false,
+        )
+        .unwrap()
+        .unwrap()
+    };
+    let error_response = |error_code: PrincipalConstructErrorCode, value: Value| {
+        Value::Response(ResponseData {
+            committed: false,
+            data: Box::new(Value::Tuple(
+                TupleData::from_data(vec![
+                    ("error_code".into(), Value::UInt(error_code as u128)),
+                    ("value".into(), value),
+                ])
+                .expect("FAIL: Failed to initialize tuple."),
+            )),
+        })
+    };
+
+    // testnet issuer on testnet
+    assert_eq!(
+        run(r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo")"#),
+        Value::okay(Value::Principal(
+            PrincipalData::parse("ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo").unwrap()
+        ))
+        .unwrap()
+    );
+
+    // mainnet issuer on testnet
+    assert_eq!(
+        run(r#"(contract-principal-construct? 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY "foo")"#),
+        error_response(
+            PrincipalConstructErrorCode::VERSION_BYTE,
+            Value::some(Value::Principal(
+                PrincipalData::parse("SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo").unwrap()
+            ))
+            .unwrap()
+        )
+    );
+
+    // invalid contract names
+    assert_eq!(
+        run(r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "")"#),
+        error_response(PrincipalConstructErrorCode::CONTRACT_NAME, Value::none())
+    );
+    assert_eq!(
+        run(r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo[")"#),
+        error_response(PrincipalConstructErrorCode::CONTRACT_NAME, Value::none())
+    );
+
+    // contracts do not deploy contracts
+    assert_eq!(
+        run(
+            r#"(contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo "bar")"#
+        ),
+        error_response(PrincipalConstructErrorCode::CONTRACT_ISSUER, Value::none())
+    );
+
+    // round-trips through principal-destruct?
+    assert_eq!(
+        run(
+            r#"(principal-destruct? (unwrap-panic (contract-principal-construct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK "foo")))"#
+        ),
+        run(r#"(principal-destruct? 'ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)"#)
+    );
+}
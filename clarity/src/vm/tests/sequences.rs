@@ -20,6 +20,7 @@ use rstest::rstest;
 use rstest_reuse::{self, *};
 use stacks_common::types::StacksEpochId;
 
+use crate::vm::ast::ASTRules;
 use crate::vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use crate::vm::tests::test_clarity_versions;
 use crate::vm::types::signatures::SequenceSubtype;
@@ -27,7 +28,7 @@ use crate::vm::types::signatures::SequenceSubtype::{BufferType, StringType};
 use crate::vm::types::signatures::StringSubtype::ASCII;
 use crate::vm::types::TypeSignature::{BoolType, IntType, SequenceType, UIntType};
 use crate::vm::types::{BufferLength, StringSubtype, StringUTF8Length, TypeSignature, Value};
-use crate::vm::{execute, execute_v2, ClarityVersion};
+use crate::vm::{execute, execute_v2, execute_with_parameters, ClarityVersion};
 
 #[test]
 fn test_simple_list_admission() {
@@ -600,6 +601,69 @@ fn test_simple_list_concat() {
     );
 }
 
+#[test]
+fn test_concat_many() {
+    let run = |input: &str| {
+        execute_with_parameters(
+            input,
+            ClarityVersion::Clarity3,
+            StacksEpochId::latest(),
+            ASTRules::PrecheckSize,
+            false,
+        )
+    };
+    let tests = [
+        "(concat-many (list 1 2) (list 3) (list 4 5))",
+        "(concat-many (list 1))",
+        "(concat-many (list) (list 1) (list))",
+        "(concat-many 0x01 0x0203 0x04)",
+        r#"(concat-many "balance: " (uint-to-ascii u100) " STX")"#,
+        r#"(concat-many u"a" u"\u{1F600}" u"b")"#,
+    ];
+
+    let expected = [
+        Value::list_from(vec![
+            Value::Int(1),
+            Value::Int(2),
+            Value::Int(3),
+            Value::Int(4),
+            Value::Int(5),
+        ])
+        .unwrap(),
+        Value::list_from(vec![Value::Int(1)]).unwrap(),
+        Value::list_from(vec![Value::Int(1)]).unwrap(),
+        Value::buff_from(vec![1, 2, 3, 4]).unwrap(),
+        Value::string_ascii_from_bytes("balance: 100 STX".into()).unwrap(),
+        Value::string_utf8_from_bytes("a\u{1F600}b".into()).unwrap(),
+    ];
+
+    for (test, expected) in tests.iter().zip(expected.iter()) {
+        assert_eq!(expected.clone(), run(test).unwrap().unwrap());
+    }
+
+    assert_eq!(
+        run("(concat-many (list 1) (list u4 u8))").unwrap_err(),
+        CheckErrors::TypeError(IntType, UIntType).into()
+    );
+    assert_eq!(
+        run("(concat-many (list 1) (list 2) 3)").unwrap_err(),
+        RuntimeErrorType::BadTypeConstruction.into()
+    );
+    assert_eq!(
+        run("(concat-many 0x01 \"1\")").unwrap_err(),
+        RuntimeErrorType::BadTypeConstruction.into()
+    );
+    assert_eq!(
+        run("(concat-many)").unwrap_err(),
+        CheckErrors::RequiresAtLeastArguments(1, 0).into()
+    );
+    assert_eq!(
+        run("(concat-many 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01 0x01)")
+            .unwrap_err(),
+        CheckErrors::RequiresAtMostArguments(16, 17).into()
+    );
+}
+
 #[test]
 fn test_simple_buff_concat() {
     let tests = [
@@ -1347,6 +1347,74 @@ fn test_bitwise() {
     }
 }
 
+#[test]
+fn test_bitwise_rotate_and_log2_uint() {
+    let tests = [
+        "(bit-rotate-left u1 u2)",                                       // u4
+        "(bit-rotate-left u2 u127)",                                     // u1
+        "(bit-rotate-left u1 u128)",                                     // u1
+        "(bit-rotate-left u340282366920938463463374607431768211455 u9)", // u340282366920938463463374607431768211455
+        "(bit-rotate-left -1 u5)",                                       // -1
+        "(bit-rotate-left -170141183460469231731687303715884105728 u1)", // 1
+        "(bit-rotate-right u4 u2)",                                      // u1
+        "(bit-rotate-right u1 u1)", // u170141183460469231731687303715884105728
+        "(bit-rotate-right u1 u129)", // u170141183460469231731687303715884105728
+        "(bit-rotate-right 1 u1)",  // -170141183460469231731687303715884105728
+        "(bit-rotate-right -2 u1)", // 170141183460469231731687303715884105727
+        "(log2-uint u8)",           // (some u3)
+        "(log2-uint 1000)",         // (some u9)
+        "(log2-uint u1)",           // (some u0)
+        "(log2-uint u340282366920938463463374607431768211455)", // (some u127)
+        "(log2-uint u0)",           // none
+        "(log2-uint -8)",           // none
+    ];
+
+    let expectations = [
+        Value::UInt(4),
+        Value::UInt(1),
+        Value::UInt(1),
+        Value::UInt(u128::MAX),
+        Value::Int(-1),
+        Value::Int(1),
+        Value::UInt(1),
+        Value::UInt(1 << 127),
+        Value::UInt(1 << 127),
+        Value::Int(i128::MIN),
+        Value::Int(i128::MAX),
+        Value::some(Value::UInt(3)).unwrap(),
+        Value::some(Value::UInt(9)).unwrap(),
+        Value::some(Value::UInt(0)).unwrap(),
+        Value::some(Value::UInt(127)).unwrap(),
+        Value::none(),
+        Value::none(),
+    ];
+
+    for (program, expectation) in tests.iter().zip(expectations.iter()) {
+        assert_eq!(
+            expectation.clone(),
+            execute_with_parameters(
+                program,
+                ClarityVersion::Clarity3,
+                StacksEpochId::latest(),
+                ASTRules::PrecheckSize,
+                false
+            )
+            .unwrap()
+            .unwrap()
+        );
+
+        // not available before Clarity 3
+        assert!(execute_with_parameters(
+            program,
+            ClarityVersion::Clarity2,
+            StacksEpochId::latest(),
+            ASTRules::PrecheckSize,
+            false
+        )
+        .is_err());
+    }
+}
+
 #[test]
 fn test_some() {
     let tests = [
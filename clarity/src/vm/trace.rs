@@ -0,0 +1,355 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::EvalHook;
+use crate::vm::callables::FunctionIdentifier;
+use crate::vm::contexts::Environment;
+use crate::vm::errors::Error;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::Value;
+
+/// One step of a traced execution. Values are rendered in Clarity syntax.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// A user-defined function was applied
+    FunctionCall {
+        depth: usize,
+        function: String,
+        args: Vec<String>,
+    },
+    /// A user-defined function returned. Exactly one of `result` and `error` is set.
+    FunctionReturn {
+        depth: usize,
+        function: String,
+        result: Option<String>,
+        error: Option<String>,
+    },
+    /// A public or read-only function was invoked by `contract-call?` or by a transaction
+    ContractCall {
+        depth: usize,
+        contract: String,
+        function: String,
+        args: Vec<String>,
+    },
+    /// A contract invocation returned. Exactly one of `result` and `error` is set.
+    ContractReturn {
+        depth: usize,
+        contract: String,
+        function: String,
+        result: Option<String>,
+        error: Option<String>,
+    },
+    /// A data var (`key` is `None`) or map entry was read
+    DataRead {
+        depth: usize,
+        contract: String,
+        name: String,
+        key: Option<String>,
+        value: String,
+    },
+    /// A data var (`key` is `None`) or map entry was written. `value` is `None` if the map
+    /// entry was deleted.
+    DataWrite {
+        depth: usize,
+        contract: String,
+        name: String,
+        key: Option<String>,
+        value: Option<String>,
+    },
+}
+
+/// An eval hook that records function calls, contract calls and data accesses, in the order
+/// they happen, as a structured execution trace.
+pub struct ExecutionTracer {
+    events: Vec<TraceEvent>,
+    depth: usize,
+}
+
+fn render_values(values: &[Value]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn render_result(res: &Result<Value, Error>) -> (Option<String>, Option<String>) {
+    match res {
+        Ok(value) => (Some(value.to_string()), None),
+        Err(e) => (None, Some(e.to_string())),
+    }
+}
+
+impl ExecutionTracer {
+    pub fn new() -> ExecutionTracer {
+        ExecutionTracer {
+            events: vec![],
+            depth: 0,
+        }
+    }
+
+    /// The events recorded so far
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    pub fn into_events(self) -> Vec<TraceEvent> {
+        self.events
+    }
+}
+
+impl EvalHook for ExecutionTracer {
+    fn will_call_function(
+        &mut self,
+        _env: &mut Environment,
+        function: &FunctionIdentifier,
+        args: &[Value],
+    ) {
+        self.events.push(TraceEvent::FunctionCall {
+            depth: self.depth,
+            function: function.to_string(),
+            args: render_values(args),
+        });
+        self.depth += 1;
+    }
+
+    fn did_return_from_function(
+        &mut self,
+        _env: &mut Environment,
+        function: &FunctionIdentifier,
+        res: &Result<Value, Error>,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        let (result, error) = render_result(res);
+        self.events.push(TraceEvent::FunctionReturn {
+            depth: self.depth,
+            function: function.to_string(),
+            result,
+            error,
+        });
+    }
+
+    fn will_call_contract(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        args: &[Value],
+    ) {
+        self.events.push(TraceEvent::ContractCall {
+            depth: self.depth,
+            contract: contract.to_string(),
+            function: function.to_string(),
+            args: render_values(args),
+        });
+        self.depth += 1;
+    }
+
+    fn did_finish_contract_call(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        function: &str,
+        res: &Result<Value, Error>,
+    ) {
+        self.depth = self.depth.saturating_sub(1);
+        let (result, error) = render_result(res);
+        self.events.push(TraceEvent::ContractReturn {
+            depth: self.depth,
+            contract: contract.to_string(),
+            function: function.to_string(),
+            result,
+            error,
+        });
+    }
+
+    fn did_read_data(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        name: &str,
+        key: Option<&Value>,
+        value: &Value,
+    ) {
+        self.events.push(TraceEvent::DataRead {
+            depth: self.depth,
+            contract: contract.to_string(),
+            name: name.to_string(),
+            key: key.map(|key| key.to_string()),
+            value: value.to_string(),
+        });
+    }
+
+    fn did_write_data(
+        &mut self,
+        _env: &mut Environment,
+        contract: &QualifiedContractIdentifier,
+        name: &str,
+        key: Option<&Value>,
+        value: Option<&Value>,
+    ) {
+        self.events.push(TraceEvent::DataWrite {
+            depth: self.depth,
+            contract: contract.to_string(),
+            name: name.to_string(),
+            key: key.map(|key| key.to_string()),
+            value: value.map(|value| value.to_string()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stacks_common::types::StacksEpochId;
+
+    use super::*;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::PrincipalData;
+    use crate::vm::{ClarityVersion, SymbolicExpression};
+
+    #[test]
+    fn trace_contract_call() {
+        let contract = "(define-data-var counter uint u0)
+            (define-map owners uint principal)
+            (define-private (bump (by uint))
+                (begin (var-set counter (+ (var-get counter) by)) (var-get counter)))
+            (define-public (claim (id uint))
+                (begin
+                    (asserts! (map-insert owners id tx-sender) (err u1))
+                    (ok (bump u2))))";
+        let contract_id = QualifiedContractIdentifier::local("tracer").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+
+        let mut marf = MemoryBackingStore::new();
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::latest());
+        owned_env
+            .initialize_versioned_contract(
+                contract_id.clone(),
+                ClarityVersion::Clarity2,
+                contract,
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+
+        let mut tracer = ExecutionTracer::new();
+        owned_env.add_eval_hook(&mut tracer);
+        owned_env
+            .execute_transaction(
+                sender.clone(),
+                None,
+                contract_id.clone(),
+                "claim",
+                &[SymbolicExpression::atom_value(Value::UInt(1))],
+            )
+            .unwrap();
+        // A second claim of the same id fails without writing to the map
+        owned_env
+            .execute_transaction(
+                sender,
+                None,
+                contract_id.clone(),
+                "claim",
+                &[SymbolicExpression::atom_value(Value::UInt(1))],
+            )
+            .unwrap();
+        drop(owned_env);
+
+        let contract = contract_id.to_string();
+        let claim_call = TraceEvent::ContractCall {
+            depth: 0,
+            contract: contract.clone(),
+            function: "claim".into(),
+            args: vec!["u1".into()],
+        };
+        let claim_apply = TraceEvent::FunctionCall {
+            depth: 1,
+            function: format!("{}:claim", contract),
+            args: vec!["u1".into()],
+        };
+        let expected = vec![
+            claim_call.clone(),
+            claim_apply.clone(),
+            TraceEvent::DataWrite {
+                depth: 2,
+                contract: contract.clone(),
+                name: "owners".into(),
+                key: Some("u1".into()),
+                value: Some("S1G2081040G2081040G2081040G208105NK8PE5".into()),
+            },
+            TraceEvent::FunctionCall {
+                depth: 2,
+                function: format!("{}:bump", contract),
+                args: vec!["u2".into()],
+            },
+            TraceEvent::DataRead {
+                depth: 3,
+                contract: contract.clone(),
+                name: "counter".into(),
+                key: None,
+                value: "u0".into(),
+            },
+            TraceEvent::DataWrite {
+                depth: 3,
+                contract: contract.clone(),
+                name: "counter".into(),
+                key: None,
+                value: Some("u2".into()),
+            },
+            TraceEvent::DataRead {
+                depth: 3,
+                contract: contract.clone(),
+                name: "counter".into(),
+                key: None,
+                value: "u2".into(),
+            },
+            TraceEvent::FunctionReturn {
+                depth: 2,
+                function: format!("{}:bump", contract),
+                result: Some("u2".into()),
+                error: None,
+            },
+            TraceEvent::FunctionReturn {
+                depth: 1,
+                function: format!("{}:claim", contract),
+                result: Some("(ok u2)".into()),
+                error: None,
+            },
+            TraceEvent::ContractReturn {
+                depth: 0,
+                contract: contract.clone(),
+                function: "claim".into(),
+                result: Some("(ok u2)".into()),
+                error: None,
+            },
+            claim_call,
+            claim_apply,
+            TraceEvent::FunctionReturn {
+                depth: 1,
+                function: format!("{}:claim", contract),
+                result: Some("(err u1)".into()),
+                error: None,
+            },
+            TraceEvent::ContractReturn {
+                depth: 0,
+                contract: contract.clone(),
+                function: "claim".into(),
+                result: Some("(err u1)".into()),
+                error: None,
+            },
+        ];
+        assert_eq!(tracer.into_events(), expected);
+    }
+}
@@ -23,7 +23,7 @@ use std::{cmp, error, fmt, str};
 use serde_json::Value as JSONValue;
 use stacks_common::codec::{Error as codec_error, StacksMessageCodec};
 use stacks_common::types::StacksEpochId;
-use stacks_common::util::hash::{hex_bytes, to_hex};
+use stacks_common::util::hash::{hex_bytes, to_hex, Sha512Trunc256Sum};
 use stacks_common::util::retry::BoundReader;
 
 use super::{ListTypeData, TupleTypeSignature};
@@ -1223,6 +1223,14 @@ impl Value {
         Ok(to_hex(byte_serialization.as_slice()))
     }
 
+    /// The SHA512/256 hash of this value's consensus serialization.
+    /// This is what `(sha512/256 (unwrap-panic (to-consensus-buff? value)))` evaluates to
+    /// on-chain, so off-chain code can use it to compute the same hashes.
+    pub fn consensus_hash(&self) -> Result<Sha512Trunc256Sum, InterpreterError> {
+        let byte_serialization = self.serialize_to_vec()?;
+        Ok(Sha512Trunc256Sum::from_data(&byte_serialization))
+    }
+
     /// Sanitize `value` against pre-2.4 serialization
     ///
     /// Returns Some if the sanitization is successful, or was not necessary.
@@ -1379,6 +1387,7 @@ pub mod tests {
     use rstest::rstest;
     use rstest_reuse::{self, *};
     use stacks_common::types::StacksEpochId;
+    use stacks_common::util::hash::Sha512Trunc256Sum;
 
     use super::super::*;
     use super::SerializationError;
@@ -2223,4 +2232,61 @@ pub mod tests {
         test_bad_expectation(contract_p2, TypeSignature::BoolType);
         test_bad_expectation(standard_p, TypeSignature::BoolType);
     }
+
+    #[test]
+    fn test_consensus_hash() {
+        let issuer =
+            PrincipalData::parse_standard_principal("SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G")
+                .unwrap();
+        let values = [
+            ("u1", Value::UInt(1)),
+            ("-1", Value::Int(-1)),
+            ("none", Value::none()),
+            (
+                "'SM2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQVX8X0G",
+                Value::from(issuer),
+            ),
+            (
+                "{ a: u1, b: \"hello\" }",
+                Value::from(
+                    TupleData::from_data(vec![
+                        ("a".into(), Value::UInt(1)),
+                        (
+                            "b".into(),
+                            Value::string_ascii_from_bytes(b"hello".to_vec()).unwrap(),
+                        ),
+                    ])
+                    .unwrap(),
+                ),
+            ),
+            (
+                "(list 0x01 0x0203)",
+                Value::cons_list_unsanitized(vec![
+                    Value::buff_from(vec![0x01]).unwrap(),
+                    Value::buff_from(vec![0x02, 0x03]).unwrap(),
+                ])
+                .unwrap(),
+            ),
+        ];
+
+        for (literal, value) in values.into_iter() {
+            // matches the hash computed by Clarity code
+            let program = format!(
+                "(sha512/256 (unwrap-panic (to-consensus-buff? {})))",
+                literal
+            );
+            let expected = crate::vm::execute_v2(&program).unwrap().unwrap();
+            let hash = value.consensus_hash().unwrap();
+            assert_eq!(
+                expected,
+                Value::buff_from(hash.as_bytes().to_vec()).unwrap()
+            );
+
+            // and only depends on the serialization
+            assert_eq!(
+                hash,
+                Sha512Trunc256Sum::from_data(&value.serialize_to_vec().unwrap())
+            );
+        }
+    }
 }
@@ -9,6 +9,8 @@ use crate::vm::errors::{Error, RuntimeErrorType};
 pub enum ClarityVersion {
     Clarity1,
     Clarity2,
+    /// Not yet the default for any epoch, and not yet accepted in transactions
+    Clarity3,
 }
 
 impl fmt::Display for ClarityVersion {
@@ -16,6 +18,7 @@ impl fmt::Display for ClarityVersion {
         match self {
             ClarityVersion::Clarity1 => write!(f, "Clarity 1"),
             ClarityVersion::Clarity2 => write!(f, "Clarity 2"),
+            ClarityVersion::Clarity3 => write!(f, "Clarity 3"),
         }
     }
 }
@@ -49,9 +52,12 @@ impl FromStr for ClarityVersion {
             Ok(ClarityVersion::Clarity1)
         } else if s == "clarity2" {
             Ok(ClarityVersion::Clarity2)
+        } else if s == "clarity3" {
+            Ok(ClarityVersion::Clarity3)
         } else {
             Err(RuntimeErrorType::ParseError(
-                "Invalid clarity version. Valid versions are: Clarity1, Clarity2.".to_string(),
+                "Invalid clarity version. Valid versions are: Clarity1, Clarity2, Clarity3."
+                    .to_string(),
             )
             .into())
         }
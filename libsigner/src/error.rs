@@ -16,6 +16,8 @@
 
 use std::io;
 
+use clarity::vm::types::QualifiedContractIdentifier;
+
 /// Errors originating from doing an RPC request to the Stacks node
 #[derive(thiserror::Error, Debug)]
 pub enum RPCError {
@@ -66,4 +68,7 @@ pub enum EventError {
     /// Unrecognized event error
     #[error("Unrecognized event: {0}")]
     UnrecognizedEvent(String),
+    /// Chunks were written to a StackerDB instance the receiver does not listen to
+    #[error("Unrecognized StackerDB contract: {0}")]
+    UnrecognizedStackerDB(QualifiedContractIdentifier),
 }
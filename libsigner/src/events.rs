@@ -14,8 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::io::{Read, Write};
-use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
@@ -23,11 +21,9 @@ use std::sync::Arc;
 use clarity::vm::types::QualifiedContractIdentifier;
 use libstackerdb::StackerDBChunkData;
 use serde::{Deserialize, Serialize};
-use tiny_http::{
-    Method as HttpMethod, Request as HttpRequest, Response as HttpResponse, Server as HttpServer,
-};
+use stacks_common::types::chainstate::BurnchainHeaderHash;
 
-use crate::http::{decode_http_body, decode_http_request};
+use crate::transport::{EventEndpoint, EventTransport};
 use crate::EventError;
 
 /// Event structure for newly-arrived StackerDB data
@@ -39,6 +35,50 @@ pub struct StackerDBChunksEvent {
     pub modified_slots: Vec<StackerDBChunkData>,
 }
 
+/// An event pushed by the Stacks node to a signer, decoded by the event receiver
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignerEvent {
+    /// New chunks were written to one of the StackerDB instances the receiver listens to
+    StackerDBChunks(StackerDBChunksEvent),
+    /// The node processed a new burnchain block
+    BurnBlockAdvance {
+        /// The height of the new burnchain block
+        burn_block_height: u64,
+        /// The hash of the new burnchain block
+        burn_block_hash: BurnchainHeaderHash,
+    },
+}
+
+/// The parts of the node's `/new_burn_block` payload that signers use
+#[derive(Deserialize)]
+struct BurnBlockPayload {
+    burn_block_hash: String,
+    burn_block_height: u64,
+}
+
+impl SignerEvent {
+    /// Decode the body of a `/new_burn_block` request
+    fn from_burn_block_payload(body: &[u8]) -> Result<SignerEvent, EventError> {
+        let payload: BurnBlockPayload = serde_json::from_slice(body).map_err(|e| {
+            EventError::Deserialize(format!("Could not decode body to JSON: {:?}", &e))
+        })?;
+        let hash_hex = payload
+            .burn_block_hash
+            .strip_prefix("0x")
+            .unwrap_or(&payload.burn_block_hash);
+        let burn_block_hash = BurnchainHeaderHash::from_hex(hash_hex).map_err(|_| {
+            EventError::Deserialize(format!(
+                "Invalid burn block hash '{}'",
+                &payload.burn_block_hash
+            ))
+        })?;
+        Ok(SignerEvent::BurnBlockAdvance {
+            burn_block_height: payload.burn_block_height,
+            burn_block_hash,
+        })
+    }
+}
+
 /// Trait to implement a stop-signaler for the event receiver thread.
 /// The caller calls `send()` and the event receiver loop (which lives in a separate thread) will
 /// terminate.
@@ -53,14 +93,14 @@ pub trait EventReceiver {
     /// the call to `is_stopped()` below to return true.
     type ST: EventStopSignaler + Send + Sync;
 
-    /// Open a server socket to the given socket address.
-    fn bind(&mut self, listener: SocketAddr) -> Result<SocketAddr, EventError>;
+    /// Start listening on the given endpoint.
+    fn bind(&mut self, endpoint: EventEndpoint) -> Result<EventEndpoint, EventError>;
     /// Return the next event
-    fn next_event(&mut self) -> Result<StackerDBChunksEvent, EventError>;
+    fn next_event(&mut self) -> Result<SignerEvent, EventError>;
     /// Add a downstream event consumer
-    fn add_consumer(&mut self, event_out: Sender<StackerDBChunksEvent>);
+    fn add_consumer(&mut self, event_out: Sender<SignerEvent>);
     /// Forward the event to downstream consumers
-    fn forward_event(&mut self, ev: StackerDBChunksEvent) -> bool;
+    fn forward_event(&mut self, ev: SignerEvent) -> bool;
     /// Determine if the receiver should hang up
     fn is_stopped(&self) -> bool;
     /// Get a stop signal instance that, when sent, will cause this receiver to stop accepting new
@@ -77,7 +117,8 @@ pub trait EventReceiver {
             }
             let next_event = match self.next_event() {
                 Ok(event) => event,
-                Err(EventError::UnrecognizedEvent(..)) => {
+                Err(EventError::UnrecognizedEvent(..))
+                | Err(EventError::UnrecognizedStackerDB(..)) => {
                     // got an event that we don't care about (not a problem)
                     continue;
                 }
@@ -100,16 +141,14 @@ pub trait EventReceiver {
     }
 }
 
-/// Event receiver for StackerDB events
+/// Event receiver for StackerDB and burnchain events
 pub struct StackerDBEventReceiver {
-    /// contracts we're listening for
+    /// contracts we're listening for. Chunks written to any other contract are ignored.
     pub stackerdb_contract_ids: Vec<QualifiedContractIdentifier>,
-    /// Address we bind to
-    local_addr: Option<SocketAddr>,
-    /// server socket that listens for HTTP POSTs from the node
-    http_server: Option<HttpServer>,
+    /// transport that receives HTTP POSTs from the node
+    transport: Option<Box<dyn EventTransport>>,
     /// channel into which to write newly-discovered data
-    out_channels: Vec<Sender<StackerDBChunksEvent>>,
+    out_channels: Vec<Sender<SignerEvent>>,
     /// inter-thread stop variable -- if set to true, then the `main_loop` will exit
     stop_signal: Arc<AtomicBool>,
 }
@@ -120,27 +159,26 @@ impl StackerDBEventReceiver {
     pub fn new(contract_ids: Vec<QualifiedContractIdentifier>) -> StackerDBEventReceiver {
         StackerDBEventReceiver {
             stackerdb_contract_ids: contract_ids,
-            http_server: None,
-            local_addr: None,
+            transport: None,
             out_channels: vec![],
             stop_signal: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// Do something with the socket
-    pub fn with_server<F, R>(&mut self, todo: F) -> Result<R, EventError>
+    /// Do something with the transport
+    pub fn with_transport<F, R>(&mut self, todo: F) -> Result<R, EventError>
     where
-        F: FnOnce(&mut StackerDBEventReceiver, &mut HttpServer) -> R,
+        F: FnOnce(&mut StackerDBEventReceiver, &mut dyn EventTransport) -> R,
     {
-        let mut server = if let Some(s) = self.http_server.take() {
-            s
+        let mut transport = if let Some(t) = self.transport.take() {
+            t
         } else {
             return Err(EventError::NotBound);
         };
 
-        let res = todo(self, &mut server);
+        let res = todo(self, transport.as_mut());
 
-        self.http_server = Some(server);
+        self.transport = Some(transport);
         Ok(res)
     }
 }
@@ -148,15 +186,15 @@ impl StackerDBEventReceiver {
 /// Stop signaler implementation
 pub struct StackerDBStopSignaler {
     stop_signal: Arc<AtomicBool>,
-    local_addr: SocketAddr,
+    endpoint: EventEndpoint,
 }
 
 impl StackerDBStopSignaler {
     /// Make a new stop signaler
-    pub fn new(sig: Arc<AtomicBool>, local_addr: SocketAddr) -> StackerDBStopSignaler {
+    pub fn new(sig: Arc<AtomicBool>, endpoint: EventEndpoint) -> StackerDBStopSignaler {
         StackerDBStopSignaler {
             stop_signal: sig,
-            local_addr,
+            endpoint,
         }
     }
 }
@@ -166,79 +204,65 @@ impl EventStopSignaler for StackerDBStopSignaler {
         self.stop_signal.store(true, Ordering::SeqCst);
         // wake up the thread so the atomicbool can be checked
         // This makes me sad...but for now...it works.
-        if let Ok(mut stream) = TcpStream::connect(self.local_addr) {
-            // We need to send actual data to trigger the event receiver
-            let body = "Yo. Shut this shit down!".to_string();
-            let req = format!(
-                "POST /shutdown HTTP/1.0\r\nContent-Length: {}\r\n\r\n{}",
-                &body.len(),
-                body
-            );
-            stream.write_all(req.as_bytes()).unwrap();
-        }
+        self.endpoint.wake();
     }
 }
 
 impl EventReceiver for StackerDBEventReceiver {
     type ST = StackerDBStopSignaler;
 
-    /// Start listening on the given socket address.
-    /// Returns the address that was bound.
+    /// Start listening on the given endpoint.
+    /// Returns the endpoint that was bound.
     /// Errors out if bind(2) fails
-    fn bind(&mut self, listener: SocketAddr) -> Result<SocketAddr, EventError> {
-        self.http_server = Some(HttpServer::http(listener).expect("failed to start HttpServer"));
-        self.local_addr = Some(listener);
-        Ok(listener)
+    fn bind(&mut self, endpoint: EventEndpoint) -> Result<EventEndpoint, EventError> {
+        self.transport = Some(endpoint.bind()?);
+        Ok(endpoint)
     }
 
     /// Wait for the node to post something, and then return it.
     /// Errors are recoverable -- the caller should call this method again even if it returns an
     /// error.
-    fn next_event(&mut self) -> Result<StackerDBChunksEvent, EventError> {
-        self.with_server(|event_receiver, http_server| {
-            let mut request = http_server.recv()?;
+    fn next_event(&mut self) -> Result<SignerEvent, EventError> {
+        self.with_transport(|event_receiver, transport| {
+            let request = transport.recv()?;
 
             // were we asked to terminate?
             if event_receiver.is_stopped() {
                 return Err(EventError::Terminated);
             }
 
-            if request.method() != &HttpMethod::Post {
+            if request.method != "POST" {
                 return Err(EventError::MalformedRequest(format!(
                     "Unrecognized method '{}'",
-                    &request.method(),
+                    &request.method,
                 )));
             }
-            if request.url() != "/stackerdb_chunks" {
-                let url = request.url().to_string();
-
-                info!(
-                    "[{:?}] next_event got request with unexpected url {}, return OK so other side doesn't keep sending this",
-                    event_receiver.local_addr,
-                    request.url()
-                );
-
-                request
-                    .respond(HttpResponse::empty(200u16))
-                    .expect("response failed");
-                Err(EventError::UnrecognizedEvent(url))
-            } else {
-                let mut body = String::new();
-                request
-                    .as_reader()
-                    .read_to_string(&mut body)
-                    .expect("failed to read body");
-
-                let event: StackerDBChunksEvent =
-                    serde_json::from_slice(body.as_bytes()).map_err(|e| {
-                        EventError::Deserialize(format!("Could not decode body to JSON: {:?}", &e))
-                    })?;
-
-                request
-                    .respond(HttpResponse::empty(200u16))
-                    .expect("response failed");
-
-                Ok(event)
+            match request.url.as_str() {
+                "/stackerdb_chunks" => {
+                    let event: StackerDBChunksEvent = serde_json::from_slice(&request.body)
+                        .map_err(|e| {
+                            EventError::Deserialize(format!(
+                                "Could not decode body to JSON: {:?}",
+                                &e
+                            ))
+                        })?;
+                    if !event_receiver
+                        .stackerdb_contract_ids
+                        .contains(&event.contract_id)
+                    {
+                        return Err(EventError::UnrecognizedStackerDB(event.contract_id));
+                    }
+                    Ok(SignerEvent::StackerDBChunks(event))
+                }
+                "/new_burn_block" => SignerEvent::from_burn_block_payload(&request.body),
+                _ => {
+                    info!(
+                        "[{}] next_event got request with unexpected url {}, return OK so other side doesn't keep sending this",
+                        transport.endpoint(),
+                        &request.url
+                    );
+                    Err(EventError::UnrecognizedEvent(request.url))
+                }
             }
         })?
     }
@@ -251,7 +275,7 @@ impl EventReceiver for StackerDBEventReceiver {
     /// Forward an event
     /// Return true on success; false on error.
     /// Returning false terminates the event receiver.
-    fn forward_event(&mut self, ev: StackerDBChunksEvent) -> bool {
+    fn forward_event(&mut self, ev: SignerEvent) -> bool {
         if self.out_channels.is_empty() {
             // nothing to do
             error!("No channels connected to event receiver");
@@ -275,17 +299,17 @@ impl EventReceiver for StackerDBEventReceiver {
     }
 
     /// Add an event consumer.  A received event will be forwarded to this Sender.
-    fn add_consumer(&mut self, out_channel: Sender<StackerDBChunksEvent>) {
+    fn add_consumer(&mut self, out_channel: Sender<SignerEvent>) {
         self.out_channels.push(out_channel);
     }
 
     /// Get a stopped signaler.  The caller can then use it to terminate the event receiver loop,
     /// even if it's in a different thread.
     fn get_stop_signaler(&mut self) -> Result<StackerDBStopSignaler, EventError> {
-        if let Some(local_addr) = self.local_addr {
+        if let Some(transport) = self.transport.as_ref() {
             Ok(StackerDBStopSignaler::new(
                 self.stop_signal.clone(),
-                local_addr,
+                transport.endpoint().clone(),
             ))
         } else {
             Err(EventError::NotBound)
@@ -41,11 +41,17 @@ mod events;
 mod http;
 mod runloop;
 mod session;
+mod transport;
 
 pub use crate::error::{EventError, RPCError};
 pub use crate::events::{
-    EventReceiver, EventStopSignaler, StackerDBChunksEvent, StackerDBEventReceiver,
+    EventReceiver, EventStopSignaler, SignerEvent, StackerDBChunksEvent, StackerDBEventReceiver,
     StackerDBStopSignaler,
 };
 pub use crate::runloop::{RunningSigner, Signer, SignerRunLoop};
 pub use crate::session::{SignerSession, StackerDBSession};
+#[cfg(unix)]
+pub use crate::transport::UnixEventTransport;
+pub use crate::transport::{
+    EventEndpoint, EventRequest, EventTransport, HttpEventTransport, UNIX_ENDPOINT_PREFIX,
+};
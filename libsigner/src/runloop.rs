@@ -28,7 +28,8 @@ use stacks_common::deps_common::ctrlc as termination;
 use stacks_common::deps_common::ctrlc::SignalId;
 
 use crate::error::EventError;
-use crate::events::{EventReceiver, EventStopSignaler, StackerDBChunksEvent};
+use crate::events::{EventReceiver, EventStopSignaler, SignerEvent};
+use crate::transport::EventEndpoint;
 
 /// Some libcs, like musl, have a very small stack size.
 /// Make sure it's big enough.
@@ -45,12 +46,12 @@ pub trait SignerRunLoop<R: Send, CMD: Send> {
     fn set_event_timeout(&mut self, timeout: Duration);
     /// Getter for the event poll timeout
     fn get_event_timeout(&self) -> Duration;
-    /// Run one pass of the event loop, given new StackerDB events discovered since the last pass.
+    /// Run one pass of the event loop, given the next event from the Stacks node, if one arrived.
     /// Returns Some(R) if this is the final pass -- the runloop evaluated to R
     /// Returns None to keep running.
     fn run_one_pass(
         &mut self,
-        event: Option<StackerDBChunksEvent>,
+        event: Option<SignerEvent>,
         cmd: Option<CMD>,
         res: Sender<R>,
     ) -> Option<R>;
@@ -64,7 +65,7 @@ pub trait SignerRunLoop<R: Send, CMD: Send> {
     /// This would run in a separate thread from the event receiver.
     fn main_loop<EVST: EventStopSignaler>(
         &mut self,
-        event_recv: Receiver<StackerDBChunksEvent>,
+        event_recv: Receiver<SignerEvent>,
         command_recv: Receiver<CMD>,
         result_send: Sender<R>,
         mut event_stop_signaler: EVST,
@@ -230,12 +231,15 @@ impl<
     /// and runloop directly.  However, this method is present to help signer developers to get
     /// their implementations off the ground.
     ///
-    /// The given `bind_addr` is the server address this event receiver needs to listen on, so the
-    /// stacks node can POST events to it.
+    /// The given `endpoint` is where this event receiver needs to listen, so the stacks node can
+    /// POST events to it. It is either a TCP socket address or the path of a unix domain socket.
     ///
     /// On success, this method consumes the Signer and returns a RunningSigner with the relevant
     /// inter-thread communication primitives for the caller to shut down the system.
-    pub fn spawn(&mut self, bind_addr: SocketAddr) -> Result<RunningSigner<EV, R>, EventError> {
+    pub fn spawn<E: Into<EventEndpoint>>(
+        &mut self,
+        endpoint: E,
+    ) -> Result<RunningSigner<EV, R>, EventError> {
         let mut event_receiver = self
             .event_receiver
             .take()
@@ -253,7 +257,7 @@ impl<
         let (event_send, event_recv) = channel();
         event_receiver.add_consumer(event_send);
 
-        event_receiver.bind(bind_addr)?;
+        event_receiver.bind(endpoint.into())?;
         let stop_signaler = event_receiver.get_stop_signaler()?;
         let mut ret_stop_signaler = event_receiver.get_stop_signaler()?;
 
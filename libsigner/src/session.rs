@@ -14,7 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::net::{SocketAddr, TcpStream};
+use std::io::{self, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
 use std::str;
 
 use clarity::vm::types::QualifiedContractIdentifier;
@@ -22,6 +23,7 @@ use libstackerdb::{
     stackerdb_get_chunk_path, stackerdb_get_metadata_path, stackerdb_post_chunk_path, SlotMetadata,
     StackerDBChunkAckData, StackerDBChunkData,
 };
+use stacks_common::types::chainstate::StacksPrivateKey;
 
 use crate::error::RPCError;
 use crate::http::run_http_request;
@@ -61,6 +63,22 @@ pub trait SignerSession {
     fn get_latest_chunk(&mut self, slot_id: u32) -> Result<Option<Vec<u8>>, RPCError> {
         Ok(self.get_latest_chunks(&[(slot_id)])?[0].clone())
     }
+
+    /// Sign `data` as the signer of `slot_id` with `privkey`, and upload it as the slot's
+    /// `slot_version`th write.  `list_chunks()` reports each slot's current version.
+    fn put_chunk_signed(
+        &mut self,
+        privkey: &StacksPrivateKey,
+        slot_id: u32,
+        slot_version: u32,
+        data: Vec<u8>,
+    ) -> Result<StackerDBChunkAckData, RPCError> {
+        let mut chunk = StackerDBChunkData::new(slot_id, slot_version, data);
+        chunk
+            .sign(privkey)
+            .map_err(|e| RPCError::MalformedRequest(format!("Failed to sign chunk: {}", &e)))?;
+        self.put_chunk(chunk)
+    }
 }
 
 /// signer session for a stackerdb instance
@@ -93,6 +111,21 @@ impl StackerDBSession {
         Ok(())
     }
 
+    /// Flush and close the connection to the node, if there is one
+    pub fn disconnect(&mut self) -> Result<(), RPCError> {
+        if let Some(mut sock) = self.sock.take() {
+            debug!("disconnect from {}", &self.host);
+            sock.flush()?;
+            if let Err(e) = sock.shutdown(Shutdown::Both) {
+                // the node may have already closed its end of the connection
+                if e.kind() != io::ErrorKind::NotConnected {
+                    return Err(e.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Do something with the connected socket
     fn with_socket<F, R>(&mut self, todo: F) -> Result<R, RPCError>
     where
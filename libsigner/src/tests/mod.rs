@@ -16,18 +16,23 @@
 
 mod http;
 
-use std::io::Write;
+use std::io::{Read, Write};
 use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::Duration;
 use std::{mem, thread};
 
 use clarity::vm::types::QualifiedContractIdentifier;
 use libstackerdb::StackerDBChunkData;
+use stacks_common::types::chainstate::BurnchainHeaderHash;
 use stacks_common::util::secp256k1::Secp256k1PrivateKey;
 use stacks_common::util::sleep_ms;
 
-use crate::{Signer, SignerRunLoop, StackerDBChunksEvent, StackerDBEventReceiver};
+use crate::{
+    EventReceiver, Signer, SignerEvent, SignerRunLoop, StackerDBChunksEvent, StackerDBEventReceiver,
+};
 
 /// Simple runloop implementation.  It receives `max_events` events and returns `events` from the
 /// last call to `run_one_pass` as its final state.
@@ -62,12 +67,12 @@ impl SignerRunLoop<Vec<StackerDBChunksEvent>, Command> for SimpleRunLoop {
 
     fn run_one_pass(
         &mut self,
-        event: Option<StackerDBChunksEvent>,
+        event: Option<SignerEvent>,
         _cmd: Option<Command>,
         _res: Sender<Vec<StackerDBChunksEvent>>,
     ) -> Option<Vec<StackerDBChunksEvent>> {
         debug!("Got event: {:?}", &event);
-        if let Some(event) = event {
+        if let Some(SignerEvent::StackerDBChunks(event)) = event {
             self.events.push(event);
         }
 
@@ -156,3 +161,128 @@ fn test_simple_signer() {
     assert_eq!(accepted_events, chunks);
     mock_stacks_node.join().unwrap();
 }
+
+/// Verify that a mocked node can feed events to a signer listening on a unix domain socket, and
+/// that the event stop signaler can wake it up over the same socket.
+#[cfg(unix)]
+#[test]
+fn test_simple_signer_unix_socket() {
+    let contract_id =
+        QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world")
+            .unwrap();
+    let ev = StackerDBEventReceiver::new(vec![contract_id.clone()]);
+    let (_cmd_send, cmd_recv) = channel();
+    let (res_send, _res_recv) = channel();
+    let mut signer = Signer::new(SimpleRunLoop::new(3), ev, cmd_recv, res_send);
+    let socket_path =
+        std::env::temp_dir().join(format!("libsigner-test-{}.sock", std::process::id()));
+
+    let mut chunks = vec![];
+    for i in 0..3 {
+        let privk = Secp256k1PrivateKey::new();
+        let mut chunk = StackerDBChunkData::new(i as u32, 1, "hello world".as_bytes().to_vec());
+        chunk.sign(&privk).unwrap();
+        chunks.push(StackerDBChunksEvent {
+            contract_id: contract_id.clone(),
+            modified_slots: vec![chunk],
+        });
+    }
+
+    let thread_chunks = chunks.clone();
+    let thread_socket_path = socket_path.clone();
+    let mock_stacks_node = thread::spawn(move || {
+        let mut num_sent = 0;
+        while num_sent < thread_chunks.len() {
+            let mut sock = match UnixStream::connect(&thread_socket_path) {
+                Ok(sock) => sock,
+                Err(..) => {
+                    sleep_ms(100);
+                    continue;
+                }
+            };
+
+            let body = serde_json::to_string(&thread_chunks[num_sent]).unwrap();
+            let req = format!("POST /stackerdb_chunks HTTP/1.0\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}", &body.len(), body);
+            sock.write_all(req.as_bytes()).unwrap();
+            sock.flush().unwrap();
+
+            // wait for the acknowledgement, so events arrive in order
+            let mut ack = vec![];
+            sock.read_to_end(&mut ack).unwrap();
+            assert!(ack.starts_with(b"HTTP/1.1 200 OK"));
+
+            num_sent += 1;
+        }
+    });
+
+    let running_signer = signer.spawn(socket_path.clone()).unwrap();
+    mock_stacks_node.join().unwrap();
+    sleep_ms(1000);
+    let accepted_events = running_signer.stop().unwrap();
+
+    assert_eq!(accepted_events, chunks);
+    // the socket file is cleaned up once the receiver stops
+    assert!(!socket_path.exists());
+}
+
+/// Verify that the event receiver decodes burn block pushes, and drops chunks written to
+/// StackerDB instances it does not listen to.
+#[test]
+fn test_event_receiver_decodes_events() {
+    let contract_id =
+        QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world")
+            .unwrap();
+    let other_contract_id =
+        QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.other")
+            .unwrap();
+    let mut ev = StackerDBEventReceiver::new(vec![contract_id.clone()]);
+    let endpoint: SocketAddr = "127.0.0.1:30001".parse().unwrap();
+    ev.bind(endpoint.into()).unwrap();
+
+    let post = |path: &str, body: String| {
+        let req = format!(
+            "POST {} HTTP/1.0\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}",
+            path,
+            &body.len(),
+            body
+        );
+        let mut sock = TcpStream::connect(endpoint).unwrap();
+        sock.write_all(req.as_bytes()).unwrap();
+        sock.flush().unwrap();
+    };
+
+    let other_chunks = StackerDBChunksEvent {
+        contract_id: other_contract_id,
+        modified_slots: vec![StackerDBChunkData::new(0, 1, vec![1])],
+    };
+    post(
+        "/stackerdb_chunks",
+        serde_json::to_string(&other_chunks).unwrap(),
+    );
+    assert!(matches!(
+        ev.next_event(),
+        Err(crate::EventError::UnrecognizedStackerDB(..))
+    ));
+
+    let chunks = StackerDBChunksEvent {
+        contract_id,
+        modified_slots: vec![StackerDBChunkData::new(0, 1, vec![1])],
+    };
+    post("/stackerdb_chunks", serde_json::to_string(&chunks).unwrap());
+    assert_eq!(
+        ev.next_event().unwrap(),
+        SignerEvent::StackerDBChunks(chunks)
+    );
+
+    post(
+        "/new_burn_block",
+        "{\"burn_block_hash\":\"0x0101010101010101010101010101010101010101010101010101010101010101\",\"burn_block_height\":100,\"reward_recipients\":[],\"reward_slot_holders\":[],\"burn_amount\":0}".to_string(),
+    );
+    assert_eq!(
+        ev.next_event().unwrap(),
+        SignerEvent::BurnBlockAdvance {
+            burn_block_height: 100,
+            burn_block_hash: BurnchainHeaderHash([1; 32]),
+        }
+    );
+}
@@ -0,0 +1,278 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use stacks_common::codec::MAX_MESSAGE_LEN;
+use tiny_http::{Response as HttpResponse, Server as HttpServer};
+
+use crate::http::{decode_http_body, decode_http_request};
+use crate::EventError;
+
+/// The prefix that marks an event endpoint as a unix domain socket path
+pub const UNIX_ENDPOINT_PREFIX: &str = "unix:";
+
+/// Where an event receiver listens for events pushed by the Stacks node
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventEndpoint {
+    /// An HTTP endpoint on a TCP socket
+    Tcp(SocketAddr),
+    /// An HTTP endpoint on a unix domain socket, for signers co-located with the node
+    Unix(PathBuf),
+}
+
+impl From<SocketAddr> for EventEndpoint {
+    fn from(addr: SocketAddr) -> Self {
+        EventEndpoint::Tcp(addr)
+    }
+}
+
+impl From<PathBuf> for EventEndpoint {
+    fn from(path: PathBuf) -> Self {
+        EventEndpoint::Unix(path)
+    }
+}
+
+impl fmt::Display for EventEndpoint {
+    /// Formats the endpoint the way the node's event observer config expects it
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventEndpoint::Tcp(addr) => write!(f, "{}", addr),
+            EventEndpoint::Unix(path) => write!(f, "{}{}", UNIX_ENDPOINT_PREFIX, path.display()),
+        }
+    }
+}
+
+impl EventEndpoint {
+    /// Start listening on this endpoint
+    pub fn bind(&self) -> Result<Box<dyn EventTransport>, EventError> {
+        match self {
+            EventEndpoint::Tcp(addr) => Ok(Box::new(HttpEventTransport::bind(*addr)?)),
+            #[cfg(unix)]
+            EventEndpoint::Unix(path) => Ok(Box::new(UnixEventTransport::bind(path.clone())?)),
+            #[cfg(not(unix))]
+            EventEndpoint::Unix(_) => Err(EventError::MalformedRequest(
+                "Unix domain sockets are not supported on this platform".to_string(),
+            )),
+        }
+    }
+
+    /// Send a request to this endpoint that wakes up a receiver blocked on it.
+    /// The request is ignored by the receiver, which only needs to wake up to see that it has
+    /// been stopped.
+    pub fn wake(&self) {
+        let body = "Yo. Shut this shit down!".to_string();
+        let req = format!(
+            "POST /shutdown HTTP/1.0\r\nContent-Length: {}\r\n\r\n{}",
+            &body.len(),
+            body
+        );
+        match self {
+            EventEndpoint::Tcp(addr) => {
+                if let Ok(mut stream) = TcpStream::connect(addr) {
+                    stream.write_all(req.as_bytes()).unwrap();
+                }
+            }
+            #[cfg(unix)]
+            EventEndpoint::Unix(path) => {
+                if let Ok(mut stream) = UnixStream::connect(path) {
+                    stream.write_all(req.as_bytes()).unwrap();
+                }
+            }
+            #[cfg(not(unix))]
+            EventEndpoint::Unix(_) => {}
+        }
+    }
+}
+
+/// A request pushed by the Stacks node to an event receiver
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRequest {
+    /// The HTTP method
+    pub method: String,
+    /// The HTTP path
+    pub url: String,
+    /// The request body
+    pub body: Vec<u8>,
+}
+
+/// Trait to implement a transport over which the Stacks node pushes events to an event receiver
+pub trait EventTransport: Send {
+    /// Wait for the next request from the node, and acknowledge it.
+    /// Errors are recoverable -- the caller should call this method again even if it returns an
+    /// error.
+    fn recv(&mut self) -> Result<EventRequest, EventError>;
+    /// The endpoint this transport listens on
+    fn endpoint(&self) -> &EventEndpoint;
+}
+
+/// Event transport over HTTP on a TCP socket
+pub struct HttpEventTransport {
+    /// server socket that listens for HTTP POSTs from the node
+    http_server: HttpServer,
+    /// Address we bind to
+    endpoint: EventEndpoint,
+}
+
+impl HttpEventTransport {
+    /// Start listening on the given socket address.
+    /// Errors out if bind(2) fails
+    pub fn bind(addr: SocketAddr) -> Result<HttpEventTransport, EventError> {
+        let http_server = HttpServer::http(addr).map_err(|e| {
+            EventError::IO(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Failed to start HttpServer on {}: {:?}", addr, &e),
+            ))
+        })?;
+        Ok(HttpEventTransport {
+            http_server,
+            endpoint: EventEndpoint::Tcp(addr),
+        })
+    }
+}
+
+impl EventTransport for HttpEventTransport {
+    fn recv(&mut self) -> Result<EventRequest, EventError> {
+        let mut request = self.http_server.recv()?;
+        let mut body = vec![];
+        request.as_reader().read_to_end(&mut body)?;
+        let event_request = EventRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body,
+        };
+        request.respond(HttpResponse::empty(200u16))?;
+        Ok(event_request)
+    }
+
+    fn endpoint(&self) -> &EventEndpoint {
+        &self.endpoint
+    }
+}
+
+/// Event transport over HTTP on a unix domain socket.
+/// Each connection carries a single request.
+#[cfg(unix)]
+pub struct UnixEventTransport {
+    /// server socket that listens for HTTP POSTs from the node
+    listener: UnixListener,
+    /// Path we bind to
+    endpoint: EventEndpoint,
+}
+
+#[cfg(unix)]
+impl UnixEventTransport {
+    /// Start listening on the socket at the given path, replacing any stale socket file left
+    /// behind by an earlier run
+    pub fn bind(path: PathBuf) -> Result<UnixEventTransport, EventError> {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        Ok(UnixEventTransport {
+            listener,
+            endpoint: EventEndpoint::Unix(path),
+        })
+    }
+}
+
+#[cfg(unix)]
+impl EventTransport for UnixEventTransport {
+    fn recv(&mut self) -> Result<EventRequest, EventError> {
+        let (mut stream, _) = self.listener.accept()?;
+        let event_request = read_http_request(&mut stream)?;
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+        stream.flush()?;
+        Ok(event_request)
+    }
+
+    fn endpoint(&self) -> &EventEndpoint {
+        &self.endpoint
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixEventTransport {
+    fn drop(&mut self) {
+        if let EventEndpoint::Unix(path) = &self.endpoint {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Read a single HTTP request from the given stream
+fn read_http_request<S: Read>(stream: &mut S) -> Result<EventRequest, EventError> {
+    let mut buf = vec![];
+    let mut read_buf = [0u8; 4096];
+    // read until the headers are complete
+    let request = loop {
+        let num_read = stream.read(&mut read_buf)?;
+        if num_read == 0 {
+            return Err(EventError::MalformedRequest(
+                "Connection closed before the HTTP headers were complete".to_string(),
+            ));
+        }
+        buf.extend_from_slice(&read_buf[..num_read]);
+        if buf.len() > MAX_MESSAGE_LEN as usize {
+            return Err(EventError::MalformedRequest(
+                "HTTP request is too big".to_string(),
+            ));
+        }
+        match decode_http_request(&buf) {
+            Ok(request) => break request,
+            Err(EventError::Deserialize(..)) => continue,
+            Err(e) => return Err(e),
+        }
+    };
+    let (method, url, headers, body_offset) = request.destruct();
+    let content_length = headers
+        .get("content-length")
+        .map(|len| {
+            len.parse::<usize>().map_err(|_| {
+                EventError::MalformedRequest(format!("Invalid Content-Length '{}'", len))
+            })
+        })
+        .transpose()?;
+    match content_length {
+        Some(len) => {
+            if len > MAX_MESSAGE_LEN as usize {
+                return Err(EventError::MalformedRequest(
+                    "HTTP request is too big".to_string(),
+                ));
+            }
+            while buf.len() < body_offset + len {
+                let num_read = stream.read(&mut read_buf)?;
+                if num_read == 0 {
+                    return Err(EventError::MalformedRequest(
+                        "Connection closed before the HTTP body was complete".to_string(),
+                    ));
+                }
+                buf.extend_from_slice(&read_buf[..num_read]);
+            }
+            buf.truncate(body_offset + len);
+        }
+        None => {
+            stream.take(MAX_MESSAGE_LEN.into()).read_to_end(&mut buf)?;
+        }
+    }
+    let body = decode_http_body(&headers, &buf[body_offset..])?;
+    Ok(EventRequest { method, url, body })
+}
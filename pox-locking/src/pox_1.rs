@@ -169,16 +169,15 @@ pub fn handle_contract_call(
         unlock_height,
     ) {
         Ok(_) => {
-            if let Some(batch) = global_context.event_batches.last_mut() {
-                batch.events.push(StacksTransactionEvent::STXEvent(
-                    STXEventType::STXLockEvent(STXLockEventData {
-                        locked_amount,
-                        unlock_height,
-                        locked_address: stacker,
-                        contract_identifier: boot_code_id("pox", global_context.mainnet),
-                    }),
-                ));
-            }
+            let contract_identifier = boot_code_id("pox", global_context.mainnet);
+            global_context.push_to_event_batch(StacksTransactionEvent::STXEvent(
+                STXEventType::STXLockEvent(STXLockEventData {
+                    locked_amount,
+                    unlock_height,
+                    locked_address: stacker,
+                    contract_identifier,
+                }),
+            ));
             return Ok(());
         }
         Err(LockingError::DefunctPoxContract) => {
@@ -402,13 +402,11 @@ pub fn handle_contract_call(
     };
 
     // append the lockup event, so it looks as if the print event happened before the lock-up
-    if let Some(batch) = global_context.event_batches.last_mut() {
-        if let Some(print_event) = print_event_opt {
-            batch.events.push(print_event);
-        }
-        if let Some(lock_event) = lock_event_opt {
-            batch.events.push(lock_event);
-        }
+    if let Some(print_event) = print_event_opt {
+        global_context.push_to_event_batch(print_event);
+    }
+    if let Some(lock_event) = lock_event_opt {
+        global_context.push_to_event_batch(lock_event);
     }
 
     Ok(())
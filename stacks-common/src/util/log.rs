@@ -15,17 +15,130 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::io::Write;
-use std::sync::Mutex;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
 use std::time::{Duration, SystemTime};
-use std::{env, io, thread};
+use std::{env, fmt, io, thread};
 
 use chrono::prelude::*;
-use slog::{BorrowedKV, Drain, FnValue, Level, Logger, OwnedKVList, Record, KV};
+use slog::{
+    BorrowedKV, Drain, FnValue, Level, Logger, OwnedKVList, Record, SendSyncRefUnwindSafeDrain, KV,
+};
 use slog_term::{CountingWriter, Decorator, RecordDecorator, Serializer};
 
 lazy_static! {
     pub static ref LOGGER: Logger = make_logger();
     pub static ref STACKS_LOG_FORMAT_TIME: Option<String> = env::var("STACKS_LOG_FORMAT_TIME").ok();
+    static ref LOG_JSON: AtomicBool =
+        AtomicBool::new(env::var("STACKS_LOG_JSON") == Ok("1".into()));
+    static ref LOG_CONTEXT: RwLock<LogContext> = RwLock::new(LogContext::default());
+}
+
+/// Output format of the global logger
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines (the default)
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<LogFormat, String> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!(
+                "Invalid log format '{}'. Valid formats are: text, json.",
+                s
+            )),
+        }
+    }
+}
+
+/// Process-wide fields attached to every JSON log line, so that log aggregators can filter on
+/// them without parsing messages.  Unset fields are emitted as `null`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogContext {
+    /// ID of the signer running in this process
+    pub signer_id: Option<u32>,
+    /// Most recently observed reward cycle
+    pub reward_cycle: Option<u64>,
+    /// Most recently observed burnchain block height
+    pub burn_height: Option<u64>,
+}
+
+/// Switch the global logger between text and JSON output.  This can be called at any time,
+/// including after the first log line has been written.
+pub fn set_log_format(format: LogFormat) -> Result<(), String> {
+    if format == LogFormat::Json && !cfg!(feature = "slog_json") {
+        return Err(
+            "Tried to enable JSON logging, but stacks-blockchain built without slog_json feature enabled."
+                .into(),
+        );
+    }
+    LOG_JSON.store(format == LogFormat::Json, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Get the current output format of the global logger
+pub fn get_log_format() -> LogFormat {
+    if LOG_JSON.load(Ordering::SeqCst) {
+        LogFormat::Json
+    } else {
+        LogFormat::Text
+    }
+}
+
+/// Update the fields attached to every JSON log line
+pub fn update_log_context<F: FnOnce(&mut LogContext)>(update: F) {
+    match LOG_CONTEXT.write() {
+        Ok(mut ctx) => update(&mut ctx),
+        Err(poisoned) => update(&mut poisoned.into_inner()),
+    }
+}
+
+/// Get a copy of the fields attached to every JSON log line
+pub fn get_log_context() -> LogContext {
+    match LOG_CONTEXT.read() {
+        Ok(ctx) => ctx.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+type BoxedDrain =
+    Box<dyn SendSyncRefUnwindSafeDrain<Ok = (), Err = slog::Never> + UnwindSafe + RefUnwindSafe>;
+
+/// Sends each record to either the text or the JSON drain, depending on the current log format
+struct LogFormatDrain {
+    text: BoxedDrain,
+    json: Option<BoxedDrain>,
+}
+
+impl Drain for LogFormatDrain {
+    type Ok = ();
+    type Err = slog::Never;
+
+    fn log(&self, record: &Record, values: &OwnedKVList) -> Result<Self::Ok, Self::Err> {
+        match (&self.json, get_log_format()) {
+            (Some(json), LogFormat::Json) => json.log(record, values),
+            _ => self.text.log(record, values),
+        }
+    }
 }
 struct TermFormat<D: Decorator> {
     decorator: D,
@@ -189,7 +302,7 @@ impl<D: Decorator> TermFormat<D> {
 }
 
 #[cfg(feature = "slog_json")]
-fn make_json_logger() -> Logger {
+fn make_json_drain() -> Option<BoxedDrain> {
     let def_keys = o!("file" => FnValue(move |info| {
                           info.file()
                       }),
@@ -202,45 +315,61 @@ fn make_json_logger() -> Logger {
                               Some(name) => name.to_string(),
                           }
                       }),
+                      "component" => FnValue(move |info| {
+                          info.module()
+                      }),
+                      "signer_id" => FnValue(move |_| {
+                          get_log_context().signer_id
+                      }),
+                      "reward_cycle" => FnValue(move |_| {
+                          get_log_context().reward_cycle
+                      }),
+                      "burn_height" => FnValue(move |_| {
+                          get_log_context().burn_height
+                      }),
     );
 
-    let drain = Mutex::new(slog_json::Json::default(std::io::stderr()));
-    let filtered_drain = slog::LevelFilter::new(drain, get_loglevel()).ignore_res();
-    slog::Logger::root(filtered_drain, def_keys)
+    let drain = slog_json::Json::new(std::io::stderr())
+        .add_default_keys()
+        .add_key_value(def_keys)
+        .build();
+    let filtered_drain = slog::LevelFilter::new(Mutex::new(drain), get_loglevel()).ignore_res();
+    Some(Box::new(filtered_drain))
 }
 
 #[cfg(not(feature = "slog_json"))]
-fn make_json_logger() -> Logger {
-    panic!("Tried to construct JSON logger, but stacks-blockchain built without slog_json feature enabled.")
+fn make_json_drain() -> Option<BoxedDrain> {
+    if get_log_format() == LogFormat::Json {
+        panic!("Tried to construct JSON logger, but stacks-blockchain built without slog_json feature enabled.")
+    }
+    None
+}
+
+fn make_format_logger(text: BoxedDrain) -> Logger {
+    let drain = LogFormatDrain {
+        text,
+        json: make_json_drain(),
+    };
+    Logger::root(drain, o!())
 }
 
 #[cfg(not(any(test, feature = "testing")))]
 fn make_logger() -> Logger {
-    if env::var("STACKS_LOG_JSON") == Ok("1".into()) {
-        make_json_logger()
-    } else {
-        let debug = env::var("STACKS_LOG_DEBUG") == Ok("1".into());
-        let pretty_print = env::var("STACKS_LOG_PP") == Ok("1".into());
-        let decorator = slog_term::PlainSyncDecorator::new(std::io::stderr());
-        let atty = isatty(Stream::Stderr);
-        let drain = TermFormat::new(decorator, pretty_print, debug, atty);
-        let logger = Logger::root(drain.ignore_res(), o!());
-        logger
-    }
+    let debug = env::var("STACKS_LOG_DEBUG") == Ok("1".into());
+    let pretty_print = env::var("STACKS_LOG_PP") == Ok("1".into());
+    let decorator = slog_term::PlainSyncDecorator::new(std::io::stderr());
+    let atty = isatty(Stream::Stderr);
+    let drain = TermFormat::new(decorator, pretty_print, debug, atty);
+    make_format_logger(Box::new(drain.ignore_res()))
 }
 
 #[cfg(any(test, feature = "testing"))]
 fn make_logger() -> Logger {
-    if env::var("STACKS_LOG_JSON") == Ok("1".into()) {
-        make_json_logger()
-    } else {
-        let debug = env::var("STACKS_LOG_DEBUG") == Ok("1".into());
-        let plain = slog_term::PlainSyncDecorator::new(slog_term::TestStdoutWriter);
-        let isatty = isatty(Stream::Stdout);
-        let drain = TermFormat::new(plain, false, debug, isatty);
-        let logger = Logger::root(drain.ignore_res(), o!());
-        logger
-    }
+    let debug = env::var("STACKS_LOG_DEBUG") == Ok("1".into());
+    let plain = slog_term::PlainSyncDecorator::new(slog_term::TestStdoutWriter);
+    let isatty = isatty(Stream::Stdout);
+    let drain = TermFormat::new(plain, false, debug, isatty);
+    make_format_logger(Box::new(drain.ignore_res()))
 }
 
 fn inner_get_loglevel() -> slog::Level {
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use clarity::vm::types::QualifiedContractIdentifier;
+use p256k1::scalar::Scalar;
 use stacks_common::address::b58;
 use stacks_common::types::chainstate::StacksPrivateKey;
 
@@ -39,8 +40,19 @@ pub enum Command {
     Dkg(RunDkgArgs),
     /// Run the signer, waiting for events from the stacker-db instance
     Run(RunDkgArgs),
+    /// Run the signer without writing to the stacker-db instance, reporting what it would have
+    /// signed or rejected
+    Simulate(RunDkgArgs),
     /// Generate necessary files for running a collection of signers
     GenerateFiles(GenerateFilesArgs),
+    /// Sign a fleet policy, printing it in the format expected by put-chunk
+    SignFleetPolicy(SignFleetPolicyArgs),
+    /// Generate a new set of signer keys, printing them in the format expected by the config file
+    GenerateKeys(GenerateKeysArgs),
+    /// Check the config file against the stacks node it points to
+    CheckConfig(RunDkgArgs),
+    /// Print the signer packets held in the stacker-db instance
+    StackerdbDump(StackerDBDumpArgs),
 }
 
 /// Basic arguments for all cyrptographic and stacker-db functionality
@@ -141,6 +153,10 @@ pub struct GenerateFilesArgs {
     #[arg(long)]
     /// The total number of key ids to distribute among the signers
     pub num_keys: u32,
+    /// A comma-separated list of each signer's stacked weight. Key ids are distributed in
+    /// proportion to it, with at least one per signer. Defaults to an even distribution.
+    #[arg(long, value_delimiter = ',')]
+    pub weights: Option<Vec<u64>>,
     #[arg(long, value_parser = parse_network)]
     /// The network to use. One of "mainnet", "testnet", or "mocknet".
     pub network: Network,
@@ -152,6 +168,43 @@ pub struct GenerateFilesArgs {
     pub timeout: Option<u64>,
 }
 
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the sign-fleet-policy command
+pub struct SignFleetPolicyArgs {
+    /// Path to a TOML file containing the fleet policy
+    #[arg(long, value_name = "FILE")]
+    pub policy: PathBuf,
+    /// The governance private key to sign the policy with, in the same format as a signer's
+    /// message_private_key
+    #[arg(long, value_parser = parse_scalar)]
+    pub private_key: Scalar,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the generate-keys command
+pub struct GenerateKeysArgs {
+    #[arg(long, value_parser = parse_network, default_value = "testnet")]
+    /// The network to use. One of "mainnet", "testnet", or "mocknet".
+    pub network: Network,
+}
+
+#[derive(Parser, Debug, Clone)]
+/// Arguments for the stackerdb-dump command
+pub struct StackerDBDumpArgs {
+    /// The base arguments
+    #[clap(flatten)]
+    pub db_args: StackerDBArgs,
+    /// Only print the packets written by this signer
+    #[arg(long)]
+    pub signer_id: Option<u32>,
+    /// Only print the packets in this message slot of each signer. Each signer writes each type
+    /// of message to its own slot: 0 for DkgBegin, 1 for DkgPrivateBegin, 2 for DkgEnd, 4 for
+    /// DkgPublicShares, 5 for DkgPrivateShares, 6 for NonceRequest, 7 for NonceResponse, 8 for
+    /// SignatureShareRequest, and 9 for SignatureShareResponse.
+    #[arg(long)]
+    pub msg_id: Option<u32>,
+}
+
 /// Parse the contract ID
 fn parse_contract(contract: &str) -> Result<QualifiedContractIdentifier, String> {
     QualifiedContractIdentifier::parse(contract).map_err(|e| format!("Invalid contract: {}", e))
@@ -162,6 +215,11 @@ fn parse_private_key(private_key: &str) -> Result<StacksPrivateKey, String> {
     StacksPrivateKey::from_hex(private_key).map_err(|e| format!("Invalid private key: {}", e))
 }
 
+/// Parse the ECDSA private key
+fn parse_scalar(private_key: &str) -> Result<Scalar, String> {
+    Scalar::try_from(private_key).map_err(|e| format!("Invalid private key: {:?}", e))
+}
+
 /// Parse the input data
 fn parse_data(data: &str) -> Result<Vec<u8>, String> {
     let encoded_data = if data == "-" {
@@ -23,6 +23,7 @@ use std::time::Duration;
 use blockstack_lib::chainstate::stacks::TransactionVersion;
 use clarity::vm::types::QualifiedContractIdentifier;
 use hashbrown::HashMap;
+use libsigner::{EventEndpoint, UNIX_ENDPOINT_PREFIX};
 use p256k1::ecdsa;
 use p256k1::scalar::Scalar;
 use serde::Deserialize;
@@ -31,12 +32,21 @@ use stacks_common::address::{
 };
 use stacks_common::consts::{CHAIN_ID_MAINNET, CHAIN_ID_TESTNET};
 use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::util::log::LogFormat;
 use wsts::state_machine::PublicKeys;
 
 /// List of key_ids for each signer_id
 pub type SignerKeyIds = HashMap<u32, Vec<u32>>;
 
 const EVENT_TIMEOUT_MS: u64 = 5000;
+const SHUTDOWN_TIMEOUT_MS: u64 = 30000;
+const RATE_LIMIT_PACKETS: u32 = 64;
+const RATE_LIMIT_WINDOW_MS: u64 = 10000;
+const COORDINATOR_TIMEOUT_MS: u64 = 30000;
+const ROUND_RESUME_TIMEOUT_MS: u64 = 60000;
+const DKG_MAX_RETRIES: u32 = 3;
+const VOTE_CONFIRM_TIMEOUT_MS: u64 = 600000;
+const VOTE_MAX_RETRIES: u32 = 3;
 
 #[derive(thiserror::Error, Debug)]
 /// An error occurred parsing the provided configuration
@@ -98,7 +108,7 @@ pub struct Config {
     /// endpoint to the stacks node
     pub node_host: SocketAddr,
     /// endpoint to the stackerdb receiver
-    pub endpoint: SocketAddr,
+    pub endpoint: EventEndpoint,
     /// smart contract that controls the target stackerdb
     pub stackerdb_contract_id: QualifiedContractIdentifier,
     /// smart contract that controls the target stackerdb
@@ -119,6 +129,61 @@ pub struct Config {
     pub signer_id: u32,
     /// The time to wait for a response from the stacker-db instance
     pub event_timeout: Duration,
+    /// The time to wait for an in-flight operation to complete on shutdown
+    pub shutdown_timeout: Duration,
+    /// The maximum number of packets processed from any one signer within `rate_limit_window`
+    pub rate_limit_packets: u32,
+    /// The window over which `rate_limit_packets` applies
+    pub rate_limit_window: Duration,
+    /// How long to wait for an expected coordinator packet before electing the next coordinator
+    pub coordinator_timeout: Duration,
+    /// How old an in-flight operation may be and still be resumed after the stacks node restarts
+    pub round_resume_timeout: Duration,
+    /// How many burn blocks before each reward cycle's prepare phase to start DKG for that
+    /// cycle, if DKG is scheduled automatically
+    pub dkg_lead_time: Option<u64>,
+    /// How many times a scheduled DKG round that does not produce a key is retried
+    pub dkg_max_retries: u32,
+    /// Whether the coordinator votes for the aggregate public key produced by DKG
+    pub vote_for_aggregate_key: bool,
+    /// How long to wait for a vote transaction to be mined before rebroadcasting it
+    pub vote_confirm_timeout: Duration,
+    /// How many times an unconfirmed vote transaction is rebroadcast
+    pub vote_max_retries: u32,
+    /// The stacker-db slot that carries signed fleet policy updates, if the fleet uses them
+    pub fleet_policy_slot_id: Option<u32>,
+    /// The key that fleet policy updates must be signed with
+    pub governance_public_key: Option<ecdsa::PublicKey>,
+    /// The output format of the signer's logs
+    pub log_format: LogFormat,
+}
+
+/// The settings that can be changed by reloading the config file, without restarting the signer
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReloadableConfig {
+    /// endpoint to the stacks node
+    pub node_host: SocketAddr,
+    /// The time to wait for a response from the stacker-db instance
+    pub event_timeout: Duration,
+    /// The time to wait for an in-flight operation to complete on shutdown
+    pub shutdown_timeout: Duration,
+    /// The maximum number of packets processed from any one signer within `rate_limit_window`
+    pub rate_limit_packets: u32,
+    /// The window over which `rate_limit_packets` applies
+    pub rate_limit_window: Duration,
+    /// How long to wait for an expected coordinator packet before electing the next coordinator
+    pub coordinator_timeout: Duration,
+    /// How old an in-flight operation may be and still be resumed after the stacks node restarts
+    pub round_resume_timeout: Duration,
+    /// How many burn blocks before each reward cycle's prepare phase to start DKG for that
+    /// cycle, if DKG is scheduled automatically
+    pub dkg_lead_time: Option<u64>,
+    /// How many times a scheduled DKG round that does not produce a key is retried
+    pub dkg_max_retries: u32,
+    /// How long to wait for a vote transaction to be mined before rebroadcasting it
+    pub vote_confirm_timeout: Duration,
+    /// How many times an unconfirmed vote transaction is rebroadcast
+    pub vote_max_retries: u32,
 }
 
 /// Internal struct for loading up the config file signer data
@@ -128,12 +193,20 @@ struct RawSigners {
     pub key_ids: Vec<u32>,
 }
 
+/// Internal struct for loading up the config file logging options
+#[derive(Clone, Deserialize, Default, Debug)]
+struct RawLoggingConfig {
+    /// The log output format. One of "text" or "json".
+    pub format: Option<String>,
+}
+
 /// Internal struct for loading up the config file
 #[derive(Deserialize, Debug)]
 struct RawConfigFile {
     /// endpoint to stacks node
     pub node_host: String,
-    /// endpoint to stackerdb receiver
+    /// endpoint to stackerdb receiver: either `host:port`, or `unix:` followed by the path of a
+    /// unix domain socket
     pub endpoint: String,
     // FIXME: these contract's should go away in non testing scenarios. Make them both optionals.
     /// Stacker db contract identifier
@@ -155,6 +228,36 @@ struct RawConfigFile {
     pub signer_id: u32,
     /// The time to wait (in millisecs) for a response from the stacker-db instance
     pub event_timeout: Option<u64>,
+    /// The time to wait (in millisecs) for an in-flight operation to complete on shutdown
+    pub shutdown_timeout: Option<u64>,
+    /// The maximum number of packets processed from any one signer within the rate limit window
+    pub rate_limit_packets: Option<u32>,
+    /// The rate limit window (in millisecs)
+    pub rate_limit_window: Option<u64>,
+    /// The time to wait (in millisecs) for an expected coordinator packet before electing the
+    /// next coordinator
+    pub coordinator_timeout: Option<u64>,
+    /// How old (in millisecs) an in-flight operation may be and still be resumed after the
+    /// stacks node restarts
+    pub round_resume_timeout: Option<u64>,
+    /// How many burn blocks before each reward cycle's prepare phase to start DKG for that
+    /// cycle. If not set, DKG is only run on demand.
+    pub dkg_lead_time: Option<u64>,
+    /// How many times a scheduled DKG round that does not produce a key is retried
+    pub dkg_max_retries: Option<u32>,
+    /// Whether the coordinator votes for the aggregate public key produced by DKG
+    pub vote_for_aggregate_key: Option<bool>,
+    /// The time to wait (in millisecs) for a vote transaction to be mined before rebroadcasting it
+    pub vote_confirm_timeout: Option<u64>,
+    /// How many times an unconfirmed vote transaction is rebroadcast
+    pub vote_max_retries: Option<u32>,
+    /// The stacker-db slot that carries signed fleet policy updates
+    pub fleet_policy_slot_id: Option<u32>,
+    /// The public key that fleet policy updates must be signed with, in the same format as the
+    /// signers' public keys
+    pub governance_public_key: Option<String>,
+    /// Logging options
+    pub logging: Option<RawLoggingConfig>,
 }
 
 impl RawConfigFile {
@@ -169,6 +272,63 @@ impl RawConfigFile {
     pub fn load_from_file(path: &str) -> Result<Self, ConfigError> {
         Self::try_from(&PathBuf::from(path))
     }
+
+    /// The settings of this config that can be changed without restarting the signer
+    pub fn reloadable(&self) -> ReloadableConfig {
+        ReloadableConfig {
+            node_host: self.node_host,
+            event_timeout: self.event_timeout,
+            shutdown_timeout: self.shutdown_timeout,
+            rate_limit_packets: self.rate_limit_packets,
+            rate_limit_window: self.rate_limit_window,
+            coordinator_timeout: self.coordinator_timeout,
+            round_resume_timeout: self.round_resume_timeout,
+            dkg_lead_time: self.dkg_lead_time,
+            dkg_max_retries: self.dkg_max_retries,
+            vote_confirm_timeout: self.vote_confirm_timeout,
+            vote_max_retries: self.vote_max_retries,
+        }
+    }
+
+    /// Re-read the config file at `path`, and return the settings that can be applied to a
+    /// signer running with this config.
+    /// Fails if the file no longer parses, or if it changes the signer's identity (its keys, ID,
+    /// signer set, stacker-db contract, network, or event endpoint), which requires a restart.
+    pub fn reload(&self, path: &PathBuf) -> Result<ReloadableConfig, ConfigError> {
+        let config = Self::try_from(path)?;
+        let identity_changes = [
+            (
+                "message_private_key",
+                config.message_private_key != self.message_private_key,
+            ),
+            (
+                "stacks_private_key",
+                config.stacks_private_key != self.stacks_private_key,
+            ),
+            ("signer_id", config.signer_id != self.signer_id),
+            ("signers", config.signer_key_ids != self.signer_key_ids),
+            (
+                "stackerdb_contract_id",
+                config.stackerdb_contract_id != self.stackerdb_contract_id,
+            ),
+            (
+                "pox_contract_id",
+                config.pox_contract_id != self.pox_contract_id,
+            ),
+            (
+                "network",
+                config.network.to_chain_id() != self.network.to_chain_id(),
+            ),
+            ("endpoint", config.endpoint != self.endpoint),
+        ];
+        if let Some((field, _)) = identity_changes.iter().find(|(_, changed)| *changed) {
+            return Err(ConfigError::BadField(
+                field.to_string(),
+                "cannot be changed without restarting the signer".to_string(),
+            ));
+        }
+        Ok(config.reloadable())
+    }
 }
 
 impl TryFrom<&PathBuf> for RawConfigFile {
@@ -200,16 +360,23 @@ impl TryFrom<RawConfigFile> for Config {
                 raw_data.node_host.clone(),
             ))?;
 
-        let endpoint = raw_data
-            .endpoint
-            .clone()
-            .to_socket_addrs()
-            .map_err(|_| ConfigError::BadField("endpoint".to_string(), raw_data.endpoint.clone()))?
-            .next()
-            .ok_or(ConfigError::BadField(
-                "endpoint".to_string(),
-                raw_data.endpoint.clone(),
-            ))?;
+        let endpoint = if let Some(path) = raw_data.endpoint.strip_prefix(UNIX_ENDPOINT_PREFIX) {
+            EventEndpoint::Unix(PathBuf::from(path))
+        } else {
+            raw_data
+                .endpoint
+                .clone()
+                .to_socket_addrs()
+                .map_err(|_| {
+                    ConfigError::BadField("endpoint".to_string(), raw_data.endpoint.clone())
+                })?
+                .next()
+                .ok_or(ConfigError::BadField(
+                    "endpoint".to_string(),
+                    raw_data.endpoint.clone(),
+                ))?
+                .into()
+        };
 
         let stackerdb_contract_id =
             QualifiedContractIdentifier::parse(&raw_data.stackerdb_contract_id).map_err(|_| {
@@ -260,6 +427,14 @@ impl TryFrom<RawConfigFile> for Config {
                 ecdsa::PublicKey::try_from(s.public_key.as_str()).map_err(|_| {
                     ConfigError::BadField("signers.public_key".to_string(), s.public_key.clone())
                 })?;
+            // Every signer needs a key id to take part in DKG and signing, however little
+            // weight it has
+            if s.key_ids.is_empty() {
+                return Err(ConfigError::BadField(
+                    "signers.key_ids".to_string(),
+                    "[]".to_string(),
+                ));
+            }
             for key_id in &s.key_ids {
                 //We do not allow a key id of 0.
                 if *key_id == 0 {
@@ -268,15 +443,79 @@ impl TryFrom<RawConfigFile> for Config {
                         key_id.to_string(),
                     ));
                 }
-                public_keys.key_ids.insert(*key_id, signer_public_key);
+                // Nor do we allow a key id to belong to more than one signer
+                if public_keys
+                    .key_ids
+                    .insert(*key_id, signer_public_key)
+                    .is_some()
+                {
+                    return Err(ConfigError::BadField(
+                        "signers.key_ids".to_string(),
+                        key_id.to_string(),
+                    ));
+                }
             }
             //We start our signer and key IDs from 1 hence the + 1;
             let signer_key = u32::try_from(i).unwrap();
             public_keys.signers.insert(signer_key, signer_public_key);
             signer_key_ids.insert(signer_key, s.key_ids.clone());
         }
+        // The key ids may be spread across the signers in any proportion, but together they
+        // must be exactly 1 through the number of keys
+        let num_keys = public_keys.key_ids.len();
+        if public_keys.key_ids.keys().max().copied().unwrap_or(0) as usize != num_keys {
+            return Err(ConfigError::InvalidConfig(format!(
+                "signers.key_ids must cover key ids 1 through {num_keys} without gaps"
+            )));
+        }
+        if !signer_key_ids.contains_key(&raw_data.signer_id) {
+            return Err(ConfigError::BadField(
+                "signer_id".to_string(),
+                raw_data.signer_id.to_string(),
+            ));
+        }
         let event_timeout =
             Duration::from_millis(raw_data.event_timeout.unwrap_or(EVENT_TIMEOUT_MS));
+        let shutdown_timeout =
+            Duration::from_millis(raw_data.shutdown_timeout.unwrap_or(SHUTDOWN_TIMEOUT_MS));
+        let rate_limit_packets = raw_data.rate_limit_packets.unwrap_or(RATE_LIMIT_PACKETS);
+        let rate_limit_window =
+            Duration::from_millis(raw_data.rate_limit_window.unwrap_or(RATE_LIMIT_WINDOW_MS));
+        let coordinator_timeout = Duration::from_millis(
+            raw_data
+                .coordinator_timeout
+                .unwrap_or(COORDINATOR_TIMEOUT_MS),
+        );
+        let round_resume_timeout = Duration::from_millis(
+            raw_data
+                .round_resume_timeout
+                .unwrap_or(ROUND_RESUME_TIMEOUT_MS),
+        );
+        let dkg_max_retries = raw_data.dkg_max_retries.unwrap_or(DKG_MAX_RETRIES);
+        let vote_confirm_timeout = Duration::from_millis(
+            raw_data
+                .vote_confirm_timeout
+                .unwrap_or(VOTE_CONFIRM_TIMEOUT_MS),
+        );
+        let vote_max_retries = raw_data.vote_max_retries.unwrap_or(VOTE_MAX_RETRIES);
+        let governance_public_key = if let Some(key) = raw_data.governance_public_key.as_ref() {
+            Some(ecdsa::PublicKey::try_from(key.as_str()).map_err(|_| {
+                ConfigError::BadField("governance_public_key".to_string(), key.clone())
+            })?)
+        } else {
+            None
+        };
+        if governance_public_key.is_some() != raw_data.fleet_policy_slot_id.is_some() {
+            return Err(ConfigError::InvalidConfig(
+                "fleet_policy_slot_id and governance_public_key must be given together".to_string(),
+            ));
+        }
+        let log_format = match raw_data.logging.and_then(|logging| logging.format) {
+            Some(format) => format
+                .parse()
+                .map_err(|_| ConfigError::BadField("logging.format".to_string(), format))?,
+            None => LogFormat::default(),
+        };
         Ok(Self {
             node_host,
             endpoint,
@@ -290,6 +529,19 @@ impl TryFrom<RawConfigFile> for Config {
             signer_id: raw_data.signer_id,
             signer_key_ids,
             event_timeout,
+            shutdown_timeout,
+            rate_limit_packets,
+            rate_limit_window,
+            coordinator_timeout,
+            round_resume_timeout,
+            dkg_lead_time: raw_data.dkg_lead_time,
+            dkg_max_retries,
+            vote_for_aggregate_key: raw_data.vote_for_aggregate_key.unwrap_or(false),
+            vote_confirm_timeout,
+            vote_max_retries,
+            fleet_policy_slot_id: raw_data.fleet_policy_slot_id,
+            governance_public_key,
+            log_format,
         })
     }
 }
@@ -0,0 +1,258 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use p256k1::ecdsa;
+use p256k1::scalar::Scalar;
+use serde::{Deserialize, Serialize};
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+/// A fleet-wide policy update, written by the fleet's governance key to the configured policy
+/// slot of the stacker-db instance. Settings that are not given keep their current values.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FleetPolicy {
+    /// Policies are applied in order of sequence number. A policy whose sequence number is not
+    /// greater than that of the last accepted policy is ignored, so old policies cannot be replayed.
+    pub sequence: u64,
+    /// The reward cycle at whose start the policy is applied
+    pub activation_cycle: u64,
+    /// The time to wait (in millisecs) for a response from the stacker-db instance
+    pub event_timeout: Option<u64>,
+    /// The time to wait (in millisecs) for an expected coordinator packet before electing the
+    /// next coordinator
+    pub coordinator_timeout: Option<u64>,
+    /// The maximum number of packets processed from any one signer within the rate limit window
+    pub rate_limit_packets: Option<u32>,
+    /// The rate limit window (in millisecs)
+    pub rate_limit_window: Option<u64>,
+    /// The signer IDs whose packets are dropped. Replaces the current deny list.
+    pub denied_signers: Option<Vec<u32>>,
+}
+
+impl FleetPolicy {
+    /// The hash of the policy that the governance key signs
+    fn digest(&self) -> Sha512Trunc256Sum {
+        let bytes = bincode::serialize(self).expect("FATAL: failed to serialize fleet policy");
+        Sha512Trunc256Sum::from_data(&bytes)
+    }
+
+    /// Sign the policy with the governance private key
+    pub fn sign(self, private_key: &Scalar) -> Result<SignedFleetPolicy, ecdsa::Error> {
+        let signature = ecdsa::Signature::new(self.digest().as_bytes(), private_key)?;
+        Ok(SignedFleetPolicy {
+            policy: self,
+            signature: signature.to_bytes().to_vec(),
+        })
+    }
+}
+
+/// A fleet policy and the governance key's signature over it.
+/// This is what is written, bincode-encoded, to the policy slot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SignedFleetPolicy {
+    /// The policy
+    pub policy: FleetPolicy,
+    /// The governance key's signature over the policy
+    pub signature: Vec<u8>,
+}
+
+impl SignedFleetPolicy {
+    /// Whether the policy was signed by the given governance public key
+    pub fn verify(&self, public_key: &ecdsa::PublicKey) -> bool {
+        let Ok(signature) = ecdsa::Signature::try_from(self.signature.as_slice()) else {
+            return false;
+        };
+        signature.verify(self.policy.digest().as_bytes(), public_key)
+    }
+}
+
+/// Why a chunk written to the policy slot was ignored
+#[derive(PartialEq, Debug)]
+pub enum FleetPolicyRejection {
+    /// The chunk is not a signed fleet policy
+    Malformed,
+    /// The policy was not signed by the governance key
+    BadSignature,
+    /// The policy's sequence number is not newer than the last accepted policy's
+    Stale,
+}
+
+/// Validates the policies written to the policy slot, and holds the newest one until its
+/// activation cycle
+pub struct FleetPolicyTracker {
+    /// The stacker-db slot that carries fleet policies
+    pub slot_id: u32,
+    /// The key that fleet policies must be signed with
+    pub governance_public_key: ecdsa::PublicKey,
+    /// The sequence number of the last accepted policy
+    last_sequence: Option<u64>,
+    /// The last accepted policy, if it has not been applied yet
+    pending: Option<FleetPolicy>,
+}
+
+impl FleetPolicyTracker {
+    /// Track the policies written to `slot_id` by `governance_public_key`
+    pub fn new(slot_id: u32, governance_public_key: ecdsa::PublicKey) -> Self {
+        FleetPolicyTracker {
+            slot_id,
+            governance_public_key,
+            last_sequence: None,
+            pending: None,
+        }
+    }
+
+    /// Validate a chunk written to the policy slot. A valid policy replaces any pending one.
+    pub fn receive(&mut self, data: &[u8]) -> Result<(), FleetPolicyRejection> {
+        let signed_policy = bincode::deserialize::<SignedFleetPolicy>(data)
+            .map_err(|_| FleetPolicyRejection::Malformed)?;
+        if !signed_policy.verify(&self.governance_public_key) {
+            return Err(FleetPolicyRejection::BadSignature);
+        }
+        let sequence = signed_policy.policy.sequence;
+        if self
+            .last_sequence
+            .is_some_and(|last_sequence| sequence <= last_sequence)
+        {
+            return Err(FleetPolicyRejection::Stale);
+        }
+        self.last_sequence = Some(sequence);
+        self.pending = Some(signed_policy.policy);
+        Ok(())
+    }
+
+    /// The activation cycle of the pending policy, if there is one
+    pub fn pending_activation_cycle(&self) -> Option<u64> {
+        self.pending.as_ref().map(|policy| policy.activation_cycle)
+    }
+
+    /// Take the pending policy if it is due in `reward_cycle`
+    pub fn take_due(&mut self, reward_cycle: u64) -> Option<FleetPolicy> {
+        if self.pending_activation_cycle()? > reward_cycle {
+            return None;
+        }
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+
+    fn signed_policy_bytes(policy: FleetPolicy, private_key: &Scalar) -> Vec<u8> {
+        bincode::serialize(&policy.sign(private_key).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn tracker_accepts_newer_governance_policies() {
+        let governance_private_key = Scalar::random(&mut OsRng);
+        let governance_public_key = ecdsa::PublicKey::new(&governance_private_key).unwrap();
+        let mut tracker = FleetPolicyTracker::new(100, governance_public_key);
+
+        let policy = FleetPolicy {
+            sequence: 2,
+            activation_cycle: 10,
+            coordinator_timeout: Some(1000),
+            denied_signers: Some(vec![3]),
+            ..FleetPolicy::default()
+        };
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(
+                policy.clone(),
+                &governance_private_key
+            )),
+            Ok(())
+        );
+        assert_eq!(tracker.pending_activation_cycle(), Some(10));
+
+        // Not due until its activation cycle
+        assert_eq!(tracker.take_due(9), None);
+        assert_eq!(tracker.take_due(10), Some(policy.clone()));
+        assert_eq!(tracker.pending_activation_cycle(), None);
+
+        // Replays and older policies are ignored
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(
+                policy.clone(),
+                &governance_private_key
+            )),
+            Err(FleetPolicyRejection::Stale)
+        );
+        let older = FleetPolicy {
+            sequence: 1,
+            ..policy.clone()
+        };
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(older, &governance_private_key)),
+            Err(FleetPolicyRejection::Stale)
+        );
+
+        // A newer policy replaces a pending one
+        let newer = FleetPolicy {
+            sequence: 3,
+            activation_cycle: 12,
+            ..FleetPolicy::default()
+        };
+        let newest = FleetPolicy {
+            sequence: 4,
+            activation_cycle: 11,
+            ..FleetPolicy::default()
+        };
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(newer, &governance_private_key)),
+            Ok(())
+        );
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(
+                newest.clone(),
+                &governance_private_key
+            )),
+            Ok(())
+        );
+        assert_eq!(tracker.take_due(11), Some(newest));
+    }
+
+    #[test]
+    fn tracker_rejects_unsigned_policies() {
+        let governance_private_key = Scalar::random(&mut OsRng);
+        let governance_public_key = ecdsa::PublicKey::new(&governance_private_key).unwrap();
+        let mut tracker = FleetPolicyTracker::new(100, governance_public_key);
+
+        let policy = FleetPolicy {
+            sequence: 1,
+            ..FleetPolicy::default()
+        };
+        let other_private_key = Scalar::random(&mut OsRng);
+        assert_eq!(
+            tracker.receive(&signed_policy_bytes(policy.clone(), &other_private_key)),
+            Err(FleetPolicyRejection::BadSignature)
+        );
+
+        // Tampering with a signed policy invalidates it
+        let mut signed_policy = policy.sign(&governance_private_key).unwrap();
+        signed_policy.policy.denied_signers = Some(vec![0]);
+        assert_eq!(
+            tracker.receive(&bincode::serialize(&signed_policy).unwrap()),
+            Err(FleetPolicyRejection::BadSignature)
+        );
+
+        assert_eq!(
+            tracker.receive(&[1, 2, 3]),
+            Err(FleetPolicyRejection::Malformed)
+        );
+        assert_eq!(tracker.pending_activation_cycle(), None);
+    }
+}
@@ -7,9 +7,16 @@ Usage documentation can be found in the [README](https://github.com/Trust-Machin
 pub mod cli;
 /// The configuration module for the signer
 pub mod config;
+/// Fleet-wide policy updates distributed through the stacker-db instance
+pub mod fleet_policy;
+/// A scripted stacks node and stacker-db instance for unit tests
+#[cfg(test)]
+pub mod mock_client;
 /// The primary runloop for the signer
 pub mod runloop;
 /// The signer client for communicating with stackerdb/stacks nodes
 pub mod stacks_client;
 /// Util functions
 pub mod utils;
+/// Voting for the aggregate public key produced by DKG
+pub mod vote;
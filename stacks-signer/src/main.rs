@@ -36,25 +36,33 @@ use std::time::Duration;
 use clap::Parser;
 use clarity::vm::types::QualifiedContractIdentifier;
 use libsigner::{RunningSigner, Signer, SignerSession, StackerDBEventReceiver, StackerDBSession};
-use libstackerdb::StackerDBChunkData;
-use slog::slog_debug;
+use p256k1::ecdsa;
+use rand_core::OsRng;
+use slog::{slog_debug, slog_info, slog_warn};
 use stacks_common::address::{
-    AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+    b58, AddressHashMode, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
-use stacks_common::debug;
+use stacks_common::deps_common::ctrlc as termination;
+use stacks_common::deps_common::ctrlc::SignalId;
 use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::util::log::{set_log_format, update_log_context, LogFormat};
+use stacks_common::{debug, info, warn};
 use stacks_signer::cli::{
-    Cli, Command, GenerateFilesArgs, GetChunkArgs, GetLatestChunkArgs, PutChunkArgs, RunDkgArgs,
-    SignArgs, StackerDBArgs,
+    Cli, Command, GenerateFilesArgs, GenerateKeysArgs, GetChunkArgs, GetLatestChunkArgs,
+    PutChunkArgs, RunDkgArgs, SignArgs, SignFleetPolicyArgs, StackerDBArgs, StackerDBDumpArgs,
 };
 use stacks_signer::config::{Config, Network};
+use stacks_signer::fleet_policy::FleetPolicy;
 use stacks_signer::runloop::{RunLoop, RunLoopCommand};
-use stacks_signer::utils::{build_signer_config_tomls, build_stackerdb_contract};
+use stacks_signer::stacks_client::{StacksClient, SLOTS_PER_USER};
+use stacks_signer::utils::{build_stackerdb_contract, build_weighted_signer_config_tomls};
 use tracing_subscriber::prelude::*;
 use tracing_subscriber::{fmt, EnvFilter};
+use wsts::net::Packet;
 use wsts::state_machine::coordinator::frost::Coordinator as FrostCoordinator;
 use wsts::state_machine::OperationResult;
-use wsts::v2;
+use wsts::{v2, Scalar};
 
 struct SpawnedSigner {
     running_signer: RunningSigner<StackerDBEventReceiver, Vec<OperationResult>>,
@@ -83,20 +91,23 @@ fn write_chunk_to_stdout(chunk_opt: Option<Vec<u8>>) {
 }
 
 // Spawn a running signer and return its handle, command sender, and result receiver
-fn spawn_running_signer(path: &PathBuf) -> SpawnedSigner {
+fn spawn_running_signer(path: &PathBuf, simulate: bool) -> SpawnedSigner {
     let config = Config::try_from(path).unwrap();
+    update_log_context(|ctx| ctx.signer_id = Some(config.signer_id));
     let (cmd_send, cmd_recv) = channel();
     let (res_send, res_recv) = channel();
     let ev = StackerDBEventReceiver::new(vec![config.stackerdb_contract_id.clone()]);
-    let runloop: RunLoop<FrostCoordinator<v2::Aggregator>> = RunLoop::from(&config);
+    let mut runloop: RunLoop<FrostCoordinator<v2::Aggregator>> = RunLoop::from(&config);
+    runloop.simulate = simulate;
     let mut signer: Signer<
         RunLoopCommand,
         Vec<OperationResult>,
         RunLoop<FrostCoordinator<v2::Aggregator>>,
         StackerDBEventReceiver,
     > = Signer::new(runloop, ev, cmd_recv, res_send);
-    let endpoint = config.endpoint;
+    let endpoint = config.endpoint.clone();
     let running_signer = signer.spawn(endpoint).unwrap();
+    set_signal_handler(cmd_send.clone(), config, path.clone());
     SpawnedSigner {
         running_signer,
         cmd_send,
@@ -104,8 +115,47 @@ fn spawn_running_signer(path: &PathBuf) -> SpawnedSigner {
     }
 }
 
+/// Install a handler for UNIX signals.
+/// Terminating signals (SIGINT, SIGTERM, ...) ask the running signer to finish its in-flight
+/// operation and exit, rather than killing it mid-round. SIGHUP re-reads the config file at
+/// `path` and applies the settings that can change without a restart; if the file does not parse
+/// or changes the signer's identity, the current settings are kept.
+fn set_signal_handler(cmd_send: Sender<RunLoopCommand>, config: Config, path: PathBuf) {
+    termination::set_handler(move |sig_id| match sig_id {
+        SignalId::Bus => {
+            eprintln!("Caught SIGBUS; crashing immediately and dumping core");
+            std::process::abort();
+        }
+        SignalId::Hangup => {
+            info!("Reloading config from {}", path.display());
+            match config.reload(&path) {
+                Ok(reloadable) => {
+                    if cmd_send
+                        .send(RunLoopCommand::ReloadConfig(reloadable))
+                        .is_err()
+                    {
+                        warn!("Signer runloop is no longer running");
+                    }
+                }
+                Err(e) => warn!("Failed to reload config, keeping the current settings: {e}"),
+            }
+        }
+        _ => {
+            info!("Graceful termination request received (signal `{sig_id}`), will finish the current operation and shut down");
+            if cmd_send.send(RunLoopCommand::Shutdown).is_err() {
+                warn!("Signer runloop is no longer running");
+            }
+        }
+    })
+    .expect("FATAL: failed to set signal handler");
+}
+
 // Process a DKG result
 fn process_dkg_result(dkg_res: &[OperationResult]) {
+    if dkg_res.is_empty() {
+        println!("DKG was aborted because the signer shut down");
+        return;
+    }
     assert!(dkg_res.len() == 1, "Received unexpected number of results");
     let dkg = dkg_res.first().unwrap();
     match dkg {
@@ -129,6 +179,10 @@ fn process_dkg_result(dkg_res: &[OperationResult]) {
 
 // Process a Sign result
 fn process_sign_result(sign_res: &[OperationResult]) {
+    if sign_res.is_empty() {
+        println!("Signing was aborted because the signer shut down");
+        return;
+    }
     assert!(sign_res.len() == 1, "Received unexpected number of results");
     let sign = sign_res.first().unwrap();
     match sign {
@@ -174,15 +228,20 @@ fn handle_list_chunks(args: StackerDBArgs) {
 fn handle_put_chunk(args: PutChunkArgs) {
     debug!("Putting chunk...");
     let mut session = stackerdb_session(args.db_args.host, args.db_args.contract);
-    let mut chunk = StackerDBChunkData::new(args.slot_id, args.slot_version, args.data);
-    chunk.sign(&args.private_key).unwrap();
-    let chunk_ack = session.put_chunk(chunk).unwrap();
+    let chunk_ack = session
+        .put_chunk_signed(
+            &args.private_key,
+            args.slot_id,
+            args.slot_version,
+            args.data,
+        )
+        .unwrap();
     println!("{}", serde_json::to_string(&chunk_ack).unwrap());
 }
 
 fn handle_dkg(args: RunDkgArgs) {
     debug!("Running DKG...");
-    let spawned_signer = spawn_running_signer(&args.config);
+    let spawned_signer = spawn_running_signer(&args.config, false);
     spawned_signer.cmd_send.send(RunLoopCommand::Dkg).unwrap();
     let dkg_res = spawned_signer.res_recv.recv().unwrap();
     process_dkg_result(&dkg_res);
@@ -191,7 +250,7 @@ fn handle_dkg(args: RunDkgArgs) {
 
 fn handle_sign(args: SignArgs) {
     debug!("Signing message...");
-    let spawned_signer = spawn_running_signer(&args.config);
+    let spawned_signer = spawn_running_signer(&args.config, false);
     spawned_signer
         .cmd_send
         .send(RunLoopCommand::Sign {
@@ -207,7 +266,7 @@ fn handle_sign(args: SignArgs) {
 
 fn handle_dkg_sign(args: SignArgs) {
     debug!("Running DKG and signing message...");
-    let spawned_signer = spawn_running_signer(&args.config);
+    let spawned_signer = spawn_running_signer(&args.config, false);
     // First execute DKG, then sign
     spawned_signer.cmd_send.send(RunLoopCommand::Dkg).unwrap();
     spawned_signer
@@ -227,9 +286,17 @@ fn handle_dkg_sign(args: SignArgs) {
 
 fn handle_run(args: RunDkgArgs) {
     debug!("Running signer...");
-    let spawned_signer = spawn_running_signer(&args.config);
+    let spawned_signer = spawn_running_signer(&args.config, false);
     println!("Signer spawned successfully. Waiting for messages to process...");
-    // Wait for the spawned signer to stop (will only occur if an error occurs)
+    // Wait for the spawned signer to stop (will only occur if an error occurs or a shutdown is requested)
+    let _ = spawned_signer.running_signer.join();
+}
+
+fn handle_simulate(args: RunDkgArgs) {
+    debug!("Running signer simulation...");
+    let spawned_signer = spawn_running_signer(&args.config, true);
+    println!("Signer simulation spawned successfully. Nothing will be written to the stacker-db instance. Waiting for messages to process...");
+    // Wait for the spawned signer to stop (will only occur if an error occurs or a shutdown is requested)
     let _ = spawned_signer.running_signer.join();
 }
 
@@ -264,8 +331,18 @@ fn handle_generate_files(args: GenerateFilesArgs) {
         .collect::<Vec<StacksAddress>>();
     // Build the stackerdb contract
     let stackerdb_contract = build_stackerdb_contract(&signer_stacks_addresses);
-    let signer_config_tomls = build_signer_config_tomls(
+    let signer_weights = args
+        .weights
+        .unwrap_or_else(|| vec![1; signer_stacks_private_keys.len()]);
+    if signer_weights.len() != signer_stacks_private_keys.len() {
+        panic!("--weights must have one weight per signer.");
+    }
+    if (args.num_keys as usize) < signer_stacks_private_keys.len() {
+        panic!("--num-keys must be at least the number of signers.");
+    }
+    let signer_config_tomls = build_weighted_signer_config_tomls(
         &signer_stacks_private_keys,
+        &signer_weights,
         args.num_keys,
         &args.db_args.host.to_string(),
         &args.db_args.contract.to_string(),
@@ -294,11 +371,169 @@ fn handle_generate_files(args: GenerateFilesArgs) {
     );
 }
 
+fn handle_sign_fleet_policy(args: SignFleetPolicyArgs) {
+    debug!("Signing fleet policy...");
+    let policy_toml = std::fs::read_to_string(&args.policy).unwrap();
+    let policy: FleetPolicy = toml::from_str(&policy_toml).unwrap();
+    let signed_policy = policy.sign(&args.private_key).unwrap();
+    println!(
+        "{}",
+        b58::encode_slice(&bincode::serialize(&signed_policy).unwrap())
+    );
+}
+
+fn handle_generate_keys(args: GenerateKeysArgs) {
+    debug!("Generating keys...");
+    let stacks_private_key = StacksPrivateKey::new();
+    let message_private_key = Scalar::random(&mut OsRng);
+    let message_public_key = ecdsa::PublicKey::new(&message_private_key).unwrap();
+    println!("stacks_private_key = \"{}\"", stacks_private_key.to_hex());
+    println!("message_private_key = \"{}\"", message_private_key);
+    println!(
+        "# stacks address: {}",
+        to_addr(&stacks_private_key, &args.network)
+    );
+    println!("# message public key: {}", message_public_key);
+}
+
+fn handle_check_config(args: RunDkgArgs) {
+    debug!("Checking config...");
+    let config = match Config::try_from(&args.config) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("FAIL: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("OK: {} is a valid config file", args.config.display());
+    let stacks_client = StacksClient::from(&config);
+    if let Err(e) = stacks_client.ping() {
+        println!(
+            "FAIL: stacks node {} is unreachable: {}",
+            config.node_host, e
+        );
+        std::process::exit(1);
+    }
+    println!("OK: stacks node {} is reachable", config.node_host);
+
+    let mut failed = false;
+    match stacks_client.get_slot_signers() {
+        Ok(slot_signers) => {
+            let first_slot_id = config.signer_id * SLOTS_PER_USER;
+            let unregistered: Vec<u32> = (first_slot_id..first_slot_id + SLOTS_PER_USER)
+                .filter(|slot_id| {
+                    !slot_signers.iter().any(|(id, signer)| {
+                        id == slot_id && signer == stacks_client.stacks_address()
+                    })
+                })
+                .collect();
+            if unregistered.is_empty() {
+                println!(
+                    "OK: {} may write to the slots of signer {} in {}",
+                    stacks_client.stacks_address(),
+                    config.signer_id,
+                    config.stackerdb_contract_id
+                );
+            } else {
+                println!(
+                    "FAIL: {} may not write to slots {:?} of {}",
+                    stacks_client.stacks_address(),
+                    unregistered,
+                    config.stackerdb_contract_id
+                );
+                failed = true;
+            }
+        }
+        Err(e) => {
+            println!(
+                "FAIL: could not read the slots of {}: {}",
+                config.stackerdb_contract_id, e
+            );
+            failed = true;
+        }
+    }
+    match stacks_client.get_account_balance() {
+        Ok(0) if config.vote_for_aggregate_key => {
+            println!(
+                "FAIL: {} has no STX to pay for aggregate public key votes",
+                stacks_client.stacks_address()
+            );
+            failed = true;
+        }
+        Ok(balance) => println!(
+            "OK: {} has a balance of {} uSTX",
+            stacks_client.stacks_address(),
+            balance
+        ),
+        Err(e) => {
+            println!(
+                "FAIL: could not read the balance of {}: {}",
+                stacks_client.stacks_address(),
+                e
+            );
+            failed = true;
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn handle_stackerdb_dump(args: StackerDBDumpArgs) {
+    debug!("Dumping stacker-db packets...");
+    let mut session = stackerdb_session(args.db_args.host, args.db_args.contract);
+    let slots = session.list_chunks().unwrap();
+    for slot in slots.iter().filter(|slot| {
+        args.signer_id
+            .map_or(true, |signer_id| slot.slot_id / SLOTS_PER_USER == signer_id)
+            && args
+                .msg_id
+                .map_or(true, |msg_id| slot.slot_id % SLOTS_PER_USER == msg_id)
+    }) {
+        let Some(chunk) = session.get_latest_chunk(slot.slot_id).unwrap() else {
+            continue;
+        };
+        if chunk.is_empty() {
+            continue;
+        }
+        let mut entry = serde_json::json!({
+            "slot_id": slot.slot_id,
+            "slot_version": slot.slot_version,
+            "signer_id": slot.slot_id / SLOTS_PER_USER,
+            "msg_id": slot.slot_id % SLOTS_PER_USER,
+        });
+        match bincode::deserialize::<Packet>(&chunk) {
+            Ok(packet) => entry["packet"] = serde_json::to_value(&packet).unwrap(),
+            Err(e) => entry["error"] = format!("Failed to decode packet: {}", e).into(),
+        }
+        println!("{}", entry);
+    }
+}
+
+/// Get the log format from the config file, for commands that run a signer
+fn get_log_format(command: &Command) -> LogFormat {
+    let config_path = match command {
+        Command::Dkg(args) | Command::Run(args) | Command::Simulate(args) => &args.config,
+        Command::Sign(args) | Command::DkgSign(args) => &args.config,
+        _ => return LogFormat::default(),
+    };
+    Config::try_from(config_path)
+        .map(|config| config.log_format)
+        .unwrap_or_default()
+}
+
 fn main() {
     let cli = Cli::parse();
 
+    let log_format = get_log_format(&cli.command);
+    if let Err(e) = set_log_format(log_format) {
+        warn!("Failed to set log format: {}", e);
+    }
+    let json_layer = (log_format == LogFormat::Json).then(|| fmt::layer().json());
+    let text_layer = (log_format == LogFormat::Text).then(fmt::layer);
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(json_layer)
+        .with(text_layer)
         .with(EnvFilter::from_default_env())
         .init();
 
@@ -327,9 +562,24 @@ fn main() {
         Command::Run(args) => {
             handle_run(args);
         }
+        Command::Simulate(args) => {
+            handle_simulate(args);
+        }
         Command::GenerateFiles(args) => {
             handle_generate_files(args);
         }
+        Command::SignFleetPolicy(args) => {
+            handle_sign_fleet_policy(args);
+        }
+        Command::GenerateKeys(args) => {
+            handle_generate_keys(args);
+        }
+        Command::CheckConfig(args) => {
+            handle_check_config(args);
+        }
+        Command::StackerdbDump(args) => {
+            handle_stackerdb_dump(args);
+        }
     }
 }
 
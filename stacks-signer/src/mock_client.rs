@@ -0,0 +1,325 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::stacks::{
+    StacksTransaction, TransactionPayload, TransactionVersion,
+};
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
+use libsigner::StackerDBChunksEvent;
+use libstackerdb::{SlotMetadata, StackerDBChunkAckData, StackerDBChunkData};
+use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey};
+use wsts::net::Packet;
+use wsts::Point;
+
+use crate::config::Config;
+use crate::stacks_client::{
+    contract_call_payload, sign_transaction, slot_id, ClientError, NextRewardCycleInfo, StackerDB,
+    StacksNode, SLOTS_PER_USER,
+};
+
+/// The pox contract the mock reports if the config does not name one
+const MOCK_POX_CONTRACT: &str = "ST000000000000000000002AMW42H.pox-3";
+
+/// A failure the mock client injects in place of the next request's response
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockFailure {
+    /// The request is retried until the backoff gives up, as if the stacks node were down
+    Timeout,
+    /// The request fails to reach the stacks node and is retried successfully, as happens when
+    /// the node restarts
+    ConnectionLost,
+    /// The stacks node rejects the request for the given reason
+    Rejected(String),
+}
+
+/// A deterministic stand-in for the stacks node and its stacker-db instance.
+/// Node queries answer from the scripted fields, transactions are recorded instead of
+/// broadcast, and chunks are written to an in-memory replica of the stacker-db instance.
+pub struct MockStacksClient {
+    /// The private key that signs chunks and transactions
+    pub stacks_private_key: StacksPrivateKey,
+    /// The contract that controls the stacker-db instance
+    pub stackerdb_contract_id: QualifiedContractIdentifier,
+    /// The version of the transactions the client signs
+    pub tx_version: TransactionVersion,
+    /// The chain the client signs transactions for
+    pub chain_id: u32,
+    /// The pox contract address and name
+    pub pox_contract: (StacksAddress, ContractName),
+    /// The aggregate public key set in the pox contract
+    pub aggregate_public_key: Option<Point>,
+    /// The current reward cycle
+    pub reward_cycle: u64,
+    /// The next reward cycle and when its prepare phase starts
+    pub next_reward_cycle_info: NextRewardCycleInfo,
+    /// The signer's next possible nonce
+    pub nonce: u64,
+    /// The estimated fee of any transaction
+    pub tx_fee: u64,
+    /// The results of mined transactions
+    pub transaction_results: HashMap<Txid, ClarityValue>,
+    /// The stacks node the client talks to
+    pub node_host: Option<SocketAddr>,
+    /// The latest chunk in each slot of the stacker-db instance
+    pub slots: BTreeMap<u32, StackerDBChunkData>,
+    /// The chunks this client wrote, oldest first, that have not been taken yet
+    pub written: Vec<StackerDBChunkData>,
+    /// How many writes were superseded by a newer version already in the slot
+    pub stale_writes: u32,
+    /// The last version written to each slot, as far as this client knows
+    slot_versions: HashMap<u32, u32>,
+    /// The transactions submitted to the stacks node, oldest first
+    submitted_txs: Mutex<Vec<StacksTransaction>>,
+    /// Failures to inject, one per request, in order
+    failures: Mutex<VecDeque<MockFailure>>,
+    /// Whether a request failed to reach the stacks node since this was last checked
+    connection_lost: AtomicBool,
+}
+
+impl From<&Config> for MockStacksClient {
+    fn from(config: &Config) -> Self {
+        let pox_contract = config.pox_contract_id.clone().unwrap_or_else(|| {
+            QualifiedContractIdentifier::parse(MOCK_POX_CONTRACT)
+                .expect("FATAL: invalid mock pox contract id")
+        });
+        MockStacksClient {
+            stacks_private_key: config.stacks_private_key,
+            stackerdb_contract_id: config.stackerdb_contract_id.clone(),
+            tx_version: config.network.to_transaction_version(),
+            chain_id: config.network.to_chain_id(),
+            pox_contract: (pox_contract.issuer.into(), pox_contract.name),
+            aggregate_public_key: None,
+            reward_cycle: 1,
+            next_reward_cycle_info: NextRewardCycleInfo {
+                burn_block_height: 100,
+                reward_cycle: 2,
+                prepare_phase_start_block_height: 140,
+            },
+            nonce: 0,
+            tx_fee: 0,
+            transaction_results: HashMap::new(),
+            node_host: Some(config.node_host),
+            slots: BTreeMap::new(),
+            written: vec![],
+            stale_writes: 0,
+            slot_versions: HashMap::new(),
+            submitted_txs: Mutex::new(vec![]),
+            failures: Mutex::new(VecDeque::new()),
+            connection_lost: AtomicBool::new(false),
+        }
+    }
+}
+
+impl MockStacksClient {
+    /// Inject a failure in place of the response to the next request that has not been given one
+    pub fn fail_next(&self, failure: MockFailure) {
+        self.failures.lock().unwrap().push_back(failure);
+    }
+
+    /// Write a chunk to the stacker-db instance as some other writer, e.g. another signer or a
+    /// replica that is ahead of this client
+    pub fn overwrite_slot(&mut self, chunk: StackerDBChunkData) {
+        self.slots.insert(chunk.slot_id, chunk);
+    }
+
+    /// The transactions submitted to the stacks node, oldest first
+    pub fn submitted_txs(&self) -> Vec<StacksTransaction> {
+        self.submitted_txs.lock().unwrap().clone()
+    }
+
+    /// Take the chunks this client wrote since the last call, as the stacks node would deliver
+    /// them to the signers. Returns `None` if nothing was written.
+    pub fn take_written_event(&mut self) -> Option<StackerDBChunksEvent> {
+        if self.written.is_empty() {
+            return None;
+        }
+        Some(StackerDBChunksEvent {
+            contract_id: self.stackerdb_contract_id.clone(),
+            modified_slots: std::mem::take(&mut self.written),
+        })
+    }
+
+    /// Answer the next request with the next injected failure, if there is one
+    fn take_failure(&self) -> Result<(), ClientError> {
+        let Some(failure) = self.failures.lock().unwrap().pop_front() else {
+            return Ok(());
+        };
+        match failure {
+            MockFailure::Timeout => Err(ClientError::RetryTimeout),
+            MockFailure::ConnectionLost => {
+                self.connection_lost.store(true, Ordering::SeqCst);
+                Ok(())
+            }
+            MockFailure::Rejected(reason) => Err(ClientError::PutChunkRejected(reason)),
+        }
+    }
+}
+
+impl StackerDB for MockStacksClient {
+    /// Write the message to the signer's slot for its type. A write that does not supersede the
+    /// version in the slot is retried with the next version, like the real client does.
+    fn send_message_with_retry(
+        &mut self,
+        id: u32,
+        message: Packet,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        self.take_failure()?;
+        let message_bytes = bincode::serialize(&message)?;
+        let slot_id = slot_id(id, &message.msg);
+        loop {
+            let slot_version = *self.slot_versions.entry(slot_id).or_insert(0) + 1;
+            self.slot_versions.insert(slot_id, slot_version);
+            if self
+                .slots
+                .get(&slot_id)
+                .is_some_and(|chunk| chunk.slot_version >= slot_version)
+            {
+                self.stale_writes += 1;
+                continue;
+            }
+            let mut chunk = StackerDBChunkData::new(slot_id, slot_version, message_bytes.clone());
+            chunk.sign(&self.stacks_private_key)?;
+            let metadata = chunk.get_slot_metadata();
+            self.slots.insert(slot_id, chunk.clone());
+            self.written.push(chunk);
+            return Ok(StackerDBChunkAckData {
+                accepted: true,
+                reason: None,
+                metadata: Some(metadata),
+            });
+        }
+    }
+
+    fn stackerdb_contract_id(&self) -> &QualifiedContractIdentifier {
+        &self.stackerdb_contract_id
+    }
+
+    fn list_chunks(&mut self) -> Result<Vec<SlotMetadata>, ClientError> {
+        self.take_failure()?;
+        Ok(self
+            .slots
+            .values()
+            .map(|chunk| chunk.get_slot_metadata())
+            .collect())
+    }
+
+    fn get_chunk(
+        &mut self,
+        slot_id: u32,
+        slot_version: u32,
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        self.take_failure()?;
+        Ok(self
+            .slots
+            .get(&slot_id)
+            .filter(|chunk| chunk.slot_version == slot_version)
+            .map(|chunk| chunk.data.clone()))
+    }
+
+    fn sync_slot_versions(&mut self, id: u32, slots: &[SlotMetadata]) {
+        for slot in slots
+            .iter()
+            .filter(|slot| slot.slot_id / SLOTS_PER_USER == id)
+        {
+            self.slot_versions.insert(slot.slot_id, slot.slot_version);
+        }
+    }
+
+    fn flush(&mut self) {}
+}
+
+impl StacksNode for MockStacksClient {
+    fn get_aggregate_public_key(&self) -> Result<Option<Point>, ClientError> {
+        self.take_failure()?;
+        Ok(self.aggregate_public_key)
+    }
+
+    fn get_current_reward_cycle(&self) -> Result<u64, ClientError> {
+        self.take_failure()?;
+        Ok(self.reward_cycle)
+    }
+
+    fn get_next_reward_cycle_info(&self) -> Result<NextRewardCycleInfo, ClientError> {
+        self.take_failure()?;
+        Ok(self.next_reward_cycle_info.clone())
+    }
+
+    fn get_next_possible_nonce(&self) -> Result<u64, ClientError> {
+        self.take_failure()?;
+        Ok(self.nonce)
+    }
+
+    fn estimate_tx_fee(
+        &self,
+        _payload: &TransactionPayload,
+        _estimated_len: u64,
+    ) -> Result<u64, ClientError> {
+        self.take_failure()?;
+        Ok(self.tx_fee)
+    }
+
+    fn get_transaction_result(&self, txid: &Txid) -> Result<Option<ClarityValue>, ClientError> {
+        self.take_failure()?;
+        Ok(self.transaction_results.get(txid).cloned())
+    }
+
+    fn get_pox_contract(&self) -> Result<(StacksAddress, ContractName), ClientError> {
+        self.take_failure()?;
+        Ok(self.pox_contract.clone())
+    }
+
+    fn build_signed_transaction(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: ContractName,
+        function_name: ClarityName,
+        function_args: &[ClarityValue],
+        nonce: u64,
+        tx_fee: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        let tx_payload =
+            contract_call_payload(contract_addr, contract_name, function_name, function_args);
+        sign_transaction(
+            &self.stacks_private_key,
+            self.tx_version,
+            self.chain_id,
+            tx_payload,
+            nonce,
+            tx_fee,
+        )
+    }
+
+    fn submit_tx(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
+        self.take_failure()?;
+        self.submitted_txs.lock().unwrap().push(tx.clone());
+        Ok(tx.txid())
+    }
+
+    fn take_connection_lost(&self) -> bool {
+        self.connection_lost.swap(false, Ordering::SeqCst)
+    }
+
+    fn set_node_host(&mut self, node_host: SocketAddr) {
+        self.node_host = Some(node_host);
+    }
+}
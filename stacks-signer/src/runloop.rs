@@ -1,10 +1,12 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::mpsc::Sender;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use libsigner::{SignerRunLoop, StackerDBChunksEvent};
+use libsigner::{SignerEvent, SignerRunLoop, StackerDBChunksEvent};
+use libstackerdb::StackerDBChunkData;
 use p256k1::ecdsa;
 use slog::{slog_debug, slog_error, slog_info, slog_warn};
+use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::{debug, error, info, warn};
 use wsts::common::MerkleRoot;
 use wsts::net::{Message, Packet, Signable};
@@ -12,10 +14,15 @@ use wsts::state_machine::coordinator::frost::Coordinator as FrostCoordinator;
 use wsts::state_machine::coordinator::Coordinatable;
 use wsts::state_machine::signer::SigningRound;
 use wsts::state_machine::{OperationResult, PublicKeys};
-use wsts::v2;
+use wsts::{v2, Point};
 
-use crate::config::Config;
-use crate::stacks_client::{retry_with_exponential_backoff, ClientError, StacksClient};
+use crate::config::{Config, ReloadableConfig};
+use crate::fleet_policy::{FleetPolicy, FleetPolicyTracker};
+use crate::stacks_client::{
+    retry_with_exponential_backoff, ClientError, StackerDB, StacksClient, StacksNode,
+    SLOTS_PER_USER,
+};
+use crate::vote::{AggregateKeyVoter, VoteStatus};
 
 /// Which operation to perform
 #[derive(PartialEq, Clone)]
@@ -31,6 +38,10 @@ pub enum RunLoopCommand {
         /// Taproot merkle root
         merkle_root: Option<MerkleRoot>,
     },
+    /// Finish (or time out) the in-flight operation and exit the runloop
+    Shutdown,
+    /// Apply settings re-read from the config file
+    ReloadConfig(ReloadableConfig),
 }
 
 /// The RunLoop state
@@ -47,8 +58,285 @@ pub enum State {
     Sign,
 }
 
-/// The runloop for the stacks signer
-pub struct RunLoop<C> {
+/// Drops duplicate and excess stacker-db chunks before they reach the signing state machines.
+/// The sending signer of a chunk is the owner of the slot it was written to.
+pub struct PacketFilter {
+    /// The maximum number of packets accepted from any one signer within `rate_limit_window`
+    pub rate_limit_packets: u32,
+    /// The window over which `rate_limit_packets` applies
+    pub rate_limit_window: Duration,
+    /// The version of the last chunk accepted from each slot
+    last_slot_versions: HashMap<u32, u32>,
+    /// The arrival times of each signer's accepted chunks within the window, oldest first
+    recent_arrivals: HashMap<u32, VecDeque<Instant>>,
+}
+
+/// Why a chunk was dropped by the packet filter
+#[derive(PartialEq, Debug)]
+pub enum PacketRejection {
+    /// The same write to the slot was already accepted
+    DuplicateSlot,
+    /// The signer sent more packets than are allowed within the rate limit window
+    RateLimited,
+}
+
+impl PacketFilter {
+    /// Create a packet filter that allows up to `rate_limit_packets` per signer every `rate_limit_window`
+    pub fn new(rate_limit_packets: u32, rate_limit_window: Duration) -> Self {
+        PacketFilter {
+            rate_limit_packets,
+            rate_limit_window,
+            last_slot_versions: HashMap::new(),
+            recent_arrivals: HashMap::new(),
+        }
+    }
+
+    /// Decide whether the given chunk, received at `now`, should be processed.
+    /// Accepted chunks count against the sending signer's rate limit.
+    pub fn check(
+        &mut self,
+        chunk: &StackerDBChunkData,
+        now: Instant,
+    ) -> Result<(), PacketRejection> {
+        let signer_id = chunk.slot_id / SLOTS_PER_USER;
+
+        // A signer may legitimately write the same packet again, so only a redelivery of the
+        // same write is a duplicate
+        if self.last_slot_versions.get(&chunk.slot_id) == Some(&chunk.slot_version) {
+            return Err(PacketRejection::DuplicateSlot);
+        }
+
+        let window = self.rate_limit_window;
+        let arrivals = self.recent_arrivals.entry(signer_id).or_default();
+        while arrivals
+            .front()
+            .is_some_and(|arrival| now.saturating_duration_since(*arrival) >= window)
+        {
+            arrivals.pop_front();
+        }
+        if arrivals.len() >= self.rate_limit_packets as usize {
+            return Err(PacketRejection::RateLimited);
+        }
+
+        arrivals.push_back(now);
+        // Only an accepted chunk is recorded, so that a chunk that was rate limited is not
+        // dropped as a duplicate when it is re-fetched
+        self.last_slot_versions
+            .insert(chunk.slot_id, chunk.slot_version);
+        Ok(())
+    }
+}
+
+/// Tracks the latest version seen in each stacker-db slot, to detect writes that the stacks node
+/// did not deliver as events (for example, because it restarted)
+#[derive(Default)]
+pub struct EventGapDetector {
+    /// The latest version seen in each slot
+    slot_versions: HashMap<u32, u32>,
+}
+
+impl EventGapDetector {
+    /// Record a chunk delivered in an event.
+    /// Returns true if its slot skipped a version, or went back to an older version, since it was
+    /// last seen. Either means writes were missed, or the node's replica was reset.
+    pub fn observe(&mut self, slot_id: u32, slot_version: u32) -> bool {
+        match self.slot_versions.insert(slot_id, slot_version) {
+            Some(last_version) => {
+                slot_version > last_version.saturating_add(1) || slot_version < last_version
+            }
+            None => false,
+        }
+    }
+
+    /// The latest version seen in the given slot, if any
+    pub fn last_version(&self, slot_id: u32) -> Option<u32> {
+        self.slot_versions.get(&slot_id).copied()
+    }
+
+    /// Forget the given slot, so that the next resync fetches it again
+    pub fn forget(&mut self, slot_id: u32) {
+        self.slot_versions.remove(&slot_id);
+    }
+}
+
+/// Tracks which signer is the current coordinator.
+/// Coordinators are elected from a rotation of every signer, ordered by the hash of the current
+/// reward cycle and the signer ID, so the whole fleet derives the same order no matter when each
+/// signer started. The rotation is only reseeded when a new reward cycle begins.
+/// If an expected coordinator packet does not arrive within `timeout`, the next signer in the
+/// rotation takes over.
+pub struct CoordinatorSelector {
+    /// How long to wait for an expected coordinator packet before electing the next coordinator
+    pub timeout: Duration,
+    /// The reward cycle the rotation was seeded with, if it was seeded yet
+    reward_cycle: Option<u64>,
+    /// Signer IDs in election order
+    rotation: Vec<u32>,
+    /// Index into `rotation` of the current coordinator
+    index: usize,
+    /// When the current coordinator is considered unresponsive, if we are waiting on it
+    deadline: Option<Instant>,
+}
+
+impl CoordinatorSelector {
+    /// Create a selector over the given signers. Until it is seeded with a reward cycle, the
+    /// rotation is ordered by signer ID hash alone.
+    pub fn new(public_keys: &PublicKeys, timeout: Duration) -> Self {
+        CoordinatorSelector {
+            timeout,
+            reward_cycle: None,
+            rotation: Self::order(public_keys, &[]),
+            index: 0,
+            deadline: None,
+        }
+    }
+
+    /// Order the signers by the hash of `seed` and their signer ID
+    fn order(public_keys: &PublicKeys, seed: &[u8]) -> Vec<u32> {
+        let mut rotation: Vec<_> = public_keys
+            .signers
+            .keys()
+            .map(|signer_id| {
+                let mut preimage = seed.to_vec();
+                preimage.extend_from_slice(&signer_id.to_be_bytes());
+                (Sha512Trunc256Sum::from_data(&preimage), *signer_id)
+            })
+            .collect();
+        // ties are broken by signer ID
+        rotation.sort();
+        rotation
+            .into_iter()
+            .map(|(_, signer_id)| signer_id)
+            .collect()
+    }
+
+    /// Recompute the rotation for `reward_cycle`, and start over from its first coordinator.
+    /// Does nothing if the rotation was already seeded with `reward_cycle`, so that coordinators
+    /// elected during the cycle are kept. Returns whether the rotation was reseeded.
+    pub fn reseed(&mut self, public_keys: &PublicKeys, reward_cycle: u64) -> bool {
+        if self.reward_cycle == Some(reward_cycle) {
+            return false;
+        }
+        self.reward_cycle = Some(reward_cycle);
+        self.rotation = Self::order(public_keys, &reward_cycle.to_be_bytes());
+        self.index = 0;
+        self.deadline = None;
+        true
+    }
+
+    /// The reward cycle the rotation was seeded with, if any
+    pub fn reward_cycle(&self) -> Option<u64> {
+        self.reward_cycle
+    }
+
+    /// The ID of the current coordinator
+    pub fn coordinator_id(&self) -> u32 {
+        self.rotation[self.index]
+    }
+
+    /// The ID of the signer that takes over if the current coordinator is unresponsive
+    pub fn next_coordinator_id(&self) -> u32 {
+        self.rotation[(self.index + 1) % self.rotation.len()]
+    }
+
+    /// Start waiting on the coordinator, if we are not already
+    pub fn expect_packet(&mut self, now: Instant) {
+        if self.deadline.is_none() {
+            self.deadline = Some(now + self.timeout);
+        }
+    }
+
+    /// The coordinator sent us a packet, so it is alive
+    pub fn heard_from_coordinator(&mut self) {
+        self.deadline = None;
+    }
+
+    /// Elect the next coordinator in the rotation
+    pub fn elect_next(&mut self) {
+        self.index = (self.index + 1) % self.rotation.len();
+        self.deadline = None;
+    }
+
+    /// Whether the current coordinator missed its deadline at `now`
+    pub fn is_unresponsive(&self, now: Instant) -> bool {
+        self.deadline.map_or(false, |deadline| now >= deadline)
+    }
+}
+
+/// Decides when to start DKG for the next reward cycle.
+/// DKG for a cycle starts `lead_time` burn blocks before that cycle's prepare phase, so that the
+/// fleet has an aggregate public key before the prepare phase begins. A round that does not
+/// produce a key is retried up to `max_retries` times while the window is still open.
+pub struct DkgScheduler {
+    /// How many burn blocks before the prepare phase to start DKG
+    pub lead_time: u64,
+    /// How many times a failed DKG round is retried
+    pub max_retries: u32,
+    /// The reward cycle that DKG is being scheduled for, if any
+    reward_cycle: Option<u64>,
+    /// Whether DKG produced a key for `reward_cycle`
+    completed: bool,
+    /// How many DKG rounds were started for `reward_cycle`
+    attempts: u32,
+}
+
+impl DkgScheduler {
+    /// Create a scheduler with the given lead time and retry count
+    pub fn new(lead_time: u64, max_retries: u32) -> Self {
+        DkgScheduler {
+            lead_time,
+            max_retries,
+            reward_cycle: None,
+            completed: false,
+            attempts: 0,
+        }
+    }
+
+    /// Whether to start a DKG round for `reward_cycle`, whose prepare phase starts at
+    /// `prepare_phase_start`, given the current burn block height. Counts the round as an
+    /// attempt if so.
+    pub fn should_start(
+        &mut self,
+        burn_block_height: u64,
+        reward_cycle: u64,
+        prepare_phase_start: u64,
+    ) -> bool {
+        if self.reward_cycle != Some(reward_cycle) {
+            self.reward_cycle = Some(reward_cycle);
+            self.completed = false;
+            self.attempts = 0;
+        }
+        let window_start = prepare_phase_start.saturating_sub(self.lead_time);
+        if self.completed
+            || burn_block_height < window_start
+            || burn_block_height >= prepare_phase_start
+            || self.attempts > self.max_retries
+        {
+            return false;
+        }
+        self.attempts += 1;
+        true
+    }
+
+    /// Record that DKG produced a key, so no further rounds are started for this reward cycle
+    pub fn dkg_completed(&mut self) {
+        self.completed = true;
+    }
+
+    /// The reward cycle that a DKG round was started for, if any
+    pub fn scheduled_reward_cycle(&self) -> Option<u64> {
+        if self.attempts > 0 {
+            self.reward_cycle
+        } else {
+            None
+        }
+    }
+}
+
+/// The runloop for the stacks signer.
+/// `S` is the client for the stacks node and its stacker-db instance, which unit tests replace
+/// with a scripted mock.
+pub struct RunLoop<C, S = StacksClient> {
     /// The timeout for events
     pub event_timeout: Duration,
     /// The coordinator for inbound messages
@@ -58,14 +346,43 @@ pub struct RunLoop<C> {
     // See: https://github.com/stacks-network/stacks-blockchain/issues/3913
     pub signing_round: SigningRound<v2::Signer>,
     /// The stacks client
-    pub stacks_client: StacksClient,
+    pub stacks_client: S,
     /// Received Commands that need to be processed
     pub commands: VecDeque<RunLoopCommand>,
     /// The current state
     pub state: State,
+    /// The time to wait for an in-flight operation to finish once a shutdown is requested
+    pub shutdown_timeout: Duration,
+    /// The time at which an in-flight operation is abandoned, if a shutdown was requested
+    pub shutdown_deadline: Option<Instant>,
+    /// Whether to only report what the signer would broadcast, rather than writing it to the
+    /// stacker-db instance
+    pub simulate: bool,
+    /// Drops duplicate and excess inbound packets
+    pub packet_filter: PacketFilter,
+    /// Elects the coordinator, and replaces it if it stops responding
+    pub coordinator_selector: CoordinatorSelector,
+    /// Validates fleet policy updates, if the fleet uses them
+    pub fleet_policy: Option<FleetPolicyTracker>,
+    /// The signers whose packets are dropped, per the last applied fleet policy
+    pub denied_signers: HashSet<u32>,
+    /// When the in-flight operation started, if there is one
+    pub round_started: Option<Instant>,
+    /// How old an in-flight operation may be and still be resumed after the stacks node restarts
+    pub round_resume_timeout: Duration,
+    /// Detects stacker-db writes that were not delivered as events
+    pub event_gap_detector: EventGapDetector,
+    /// Whether the signer must resynchronize with the stacks node, because events were missed
+    pub resync_pending: bool,
+    /// Whether the burnchain advanced, so a new reward cycle may have begun
+    pub reseed_pending: bool,
+    /// Starts DKG ahead of each reward cycle, if DKG is scheduled automatically
+    pub dkg_scheduler: Option<DkgScheduler>,
+    /// Votes for the aggregate public key produced by DKG, if the signer votes
+    pub aggregate_key_voter: Option<AggregateKeyVoter>,
 }
 
-impl<C: Coordinatable> RunLoop<C> {
+impl<C: Coordinatable, S: StacksNode + StackerDB> RunLoop<C, S> {
     /// Initialize the signer, reading the stacker-db state and setting the aggregate public key
     fn initialize(&mut self) -> Result<(), ClientError> {
         // TODO: update to read stacker db to get state.
@@ -73,19 +390,306 @@ impl<C: Coordinatable> RunLoop<C> {
         if let Some(key) = self.stacks_client.get_aggregate_public_key()? {
             debug!("Aggregate public key is set: {:?}", key);
             self.coordinator.set_aggregate_public_key(Some(key));
-        } else {
-            // Update the state to IDLE so we don't needlessy requeue the DKG command.
-            let (coordinator_id, _) = calculate_coordinator(&self.signing_round.public_keys);
-            if coordinator_id == self.signing_round.signer_id
-                && self.commands.front() != Some(&RunLoopCommand::Dkg)
-            {
-                self.commands.push_front(RunLoopCommand::Dkg);
-            }
         }
+        let reward_cycle = self.stacks_client.get_current_reward_cycle()?;
+        self.coordinator_selector
+            .reseed(&self.signing_round.public_keys, reward_cycle);
+        if self.coordinator_selector.coordinator_id() == self.signing_round.signer_id {
+            self.queue_dkg_if_needed();
+        } else if self.coordinator.get_aggregate_public_key().is_none() {
+            // The coordinator should start DKG
+            self.coordinator_selector.expect_packet(Instant::now());
+        }
+        // Update the state to IDLE so we don't needlessy requeue the DKG command.
         self.state = State::Idle;
         Ok(())
     }
 
+    /// Queue a DKG round if there is no aggregate public key yet. Only the coordinator should call this.
+    fn queue_dkg_if_needed(&mut self) {
+        if self.coordinator.get_aggregate_public_key().is_none()
+            && !self.simulate
+            && self.commands.front() != Some(&RunLoopCommand::Dkg)
+        {
+            self.commands.push_front(RunLoopCommand::Dkg);
+        }
+    }
+
+    /// Replace the current coordinator with the next one in the rotation, taking over if that is us
+    fn elect_next_coordinator(&mut self) {
+        let was_coordinator =
+            self.coordinator_selector.coordinator_id() == self.signing_round.signer_id;
+        self.coordinator_selector.elect_next();
+        let coordinator_id = self.coordinator_selector.coordinator_id();
+        info!("Signer ID# {} is now the coordinator", coordinator_id);
+        if was_coordinator {
+            // The fleet gave up on us. Abandon whatever we were coordinating.
+            warn!("Stepping down as coordinator. Resetting coordinator's internal state.");
+            self.coordinator.reset();
+            self.state = State::Idle;
+            self.round_started = None;
+        }
+        if coordinator_id == self.signing_round.signer_id {
+            self.coordinator.reset();
+            self.queue_dkg_if_needed();
+        }
+    }
+
+    /// Elect the next coordinator if the current one has not sent an expected packet in time
+    fn check_coordinator_liveness(&mut self, now: Instant) {
+        if !self.coordinator_selector.is_unresponsive(now) {
+            return;
+        }
+        warn!(
+            "Coordinator ID# {} did not respond within {:?}",
+            self.coordinator_selector.coordinator_id(),
+            self.coordinator_selector.timeout
+        );
+        self.elect_next_coordinator();
+        // Keep waiting on the new coordinator to start DKG, so it is replaced in turn if it is
+        // also unresponsive
+        if self.coordinator_selector.coordinator_id() != self.signing_round.signer_id
+            && self.coordinator.get_aggregate_public_key().is_none()
+        {
+            self.coordinator_selector.expect_packet(now);
+        }
+    }
+
+    /// Decide whether to process a coordinator message written by `sender_id`.
+    /// Messages from the current coordinator always are. A correctly signed message from the next
+    /// coordinator in the rotation means it has already taken over from an unresponsive
+    /// coordinator, so we follow it.
+    fn accept_coordinator_message(&mut self, sender_id: u32, packet: &Packet) -> bool {
+        if sender_id == self.coordinator_selector.coordinator_id() {
+            return true;
+        }
+        if sender_id != self.coordinator_selector.next_coordinator_id() {
+            debug!(
+                "Ignoring {} message from signer ID# {}, which is not the coordinator",
+                message_type(&packet.msg),
+                sender_id
+            );
+            return false;
+        }
+        let Some(sender_public_key) = self.signing_round.public_keys.signers.get(&sender_id) else {
+            return false;
+        };
+        if !verify_msg(packet, &self.signing_round.public_keys, sender_public_key) {
+            return false;
+        }
+        self.elect_next_coordinator();
+        true
+    }
+
+    /// Apply the pending fleet policy once its activation cycle has begun.
+    /// Policies are only applied between operations, so that an in-flight round is not disrupted.
+    fn apply_due_fleet_policy(&mut self) {
+        if self.state != State::Idle {
+            return;
+        }
+        let Some(fleet_policy) = self.fleet_policy.as_mut() else {
+            return;
+        };
+        if fleet_policy.pending_activation_cycle().is_none() {
+            return;
+        }
+        let reward_cycle = match self.stacks_client.get_current_reward_cycle() {
+            Ok(reward_cycle) => reward_cycle,
+            Err(e) => {
+                warn!("Failed to get the current reward cycle: {:?}", e);
+                return;
+            }
+        };
+        if let Some(policy) = fleet_policy.take_due(reward_cycle) {
+            self.apply_fleet_policy(policy);
+        }
+    }
+
+    /// Reseed the coordinator rotation once a new reward cycle begins.
+    /// Rotations are only reseeded between operations, so that an in-flight round is not disrupted.
+    fn reseed_coordinator_rotation(&mut self) {
+        if self.state != State::Idle || !self.reseed_pending {
+            return;
+        }
+        let reward_cycle = match self.stacks_client.get_current_reward_cycle() {
+            Ok(reward_cycle) => reward_cycle,
+            Err(e) => {
+                warn!("Failed to get the current reward cycle: {:?}", e);
+                return;
+            }
+        };
+        self.reseed_pending = false;
+        if !self
+            .coordinator_selector
+            .reseed(&self.signing_round.public_keys, reward_cycle)
+        {
+            return;
+        }
+        let coordinator_id = self.coordinator_selector.coordinator_id();
+        info!(
+            "Reward cycle {} began. Signer ID# {} is now the coordinator",
+            reward_cycle, coordinator_id
+        );
+        if coordinator_id == self.signing_round.signer_id {
+            self.queue_dkg_if_needed();
+        } else if self.coordinator.get_aggregate_public_key().is_none() {
+            self.coordinator_selector.expect_packet(Instant::now());
+        }
+    }
+
+    /// Queue a DKG round if the next reward cycle's prepare phase is approaching.
+    /// Only the coordinator schedules DKG, and only between operations.
+    fn schedule_dkg(&mut self) {
+        if self.state != State::Idle
+            || self.simulate
+            || self.shutdown_deadline.is_some()
+            || self.coordinator_selector.coordinator_id() != self.signing_round.signer_id
+            || self.commands.contains(&RunLoopCommand::Dkg)
+        {
+            return;
+        }
+        let Some(dkg_scheduler) = self.dkg_scheduler.as_mut() else {
+            return;
+        };
+        let info = match self.stacks_client.get_next_reward_cycle_info() {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("Failed to get the next reward cycle: {:?}", e);
+                return;
+            }
+        };
+        if dkg_scheduler.should_start(
+            info.burn_block_height,
+            info.reward_cycle,
+            info.prepare_phase_start_block_height,
+        ) {
+            info!(
+                "Scheduling DKG for reward cycle {}, whose prepare phase starts at burn block {}",
+                info.reward_cycle, info.prepare_phase_start_block_height
+            );
+            self.commands.push_back(RunLoopCommand::Dkg);
+        }
+    }
+
+    /// Broadcast a vote for the aggregate public key produced by DKG.
+    /// A scheduled DKG round is voted into the reward cycle it was scheduled for, and any other
+    /// round into the current reward cycle.
+    fn vote_for_aggregate_key(&mut self, key: Point) {
+        if self.simulate {
+            return;
+        }
+        let Some(voter) = self.aggregate_key_voter.as_mut() else {
+            return;
+        };
+        let scheduled_reward_cycle = self
+            .dkg_scheduler
+            .as_ref()
+            .and_then(|dkg_scheduler| dkg_scheduler.scheduled_reward_cycle());
+        let reward_cycle = match scheduled_reward_cycle {
+            Some(reward_cycle) => reward_cycle,
+            None => match self.stacks_client.get_current_reward_cycle() {
+                Ok(reward_cycle) => reward_cycle,
+                Err(e) => {
+                    warn!("Failed to get the reward cycle to vote for: {:?}", e);
+                    return;
+                }
+            },
+        };
+        if let Err(e) = voter.vote(&self.stacks_client, reward_cycle, key) {
+            warn!(
+                "Failed to vote for aggregate public key {} for reward cycle {}: {:?}",
+                key, reward_cycle, e
+            );
+        }
+    }
+
+    /// Check whether the vote for the aggregate public key was mined, rebroadcasting it if not
+    fn check_aggregate_key_vote(&mut self, now: Instant) {
+        let Some(voter) = self.aggregate_key_voter.as_mut() else {
+            return;
+        };
+        if !voter.is_pending() {
+            return;
+        }
+        match voter.check(&self.stacks_client, now) {
+            Ok(Some(VoteStatus::Confirmed(txid))) => {
+                info!("Aggregate public key vote {} was mined", txid);
+            }
+            Ok(Some(VoteStatus::Rejected(txid, result))) => {
+                warn!(
+                    "Aggregate public key vote {} was rejected: {}",
+                    txid, result
+                );
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to check the aggregate public key vote: {:?}", e),
+        }
+    }
+
+    /// Queue the given command for execution.
+    /// A shutdown request drops all queued commands, reporting each one as aborted on `res`, and
+    /// any command received afterwards is ignored.
+    /// Reloaded settings are applied immediately rather than queued.
+    fn queue_command(&mut self, command: RunLoopCommand, res: &Sender<Vec<OperationResult>>) {
+        if let RunLoopCommand::ReloadConfig(config) = command {
+            self.apply_config_reload(config);
+        } else if command == RunLoopCommand::Shutdown {
+            if self.shutdown_deadline.is_none() {
+                info!(
+                    "Shutdown requested. Waiting up to {:?} for the current operation to finish.",
+                    self.shutdown_timeout
+                );
+                self.shutdown_deadline = Some(Instant::now() + self.shutdown_timeout);
+                if !self.commands.is_empty() {
+                    warn!(
+                        "Dropping {} queued command(s) due to shutdown.",
+                        self.commands.len()
+                    );
+                    for _ in self.commands.drain(..) {
+                        report_aborted_operation(res);
+                    }
+                }
+            }
+        } else if self.shutdown_deadline.is_some() {
+            warn!("Signer is shutting down. Ignoring command.");
+        } else if self.simulate {
+            // Coordinating a round requires broadcasting, which a simulation never does
+            warn!("Signer is running a simulation. Ignoring command.");
+        } else {
+            self.commands.push_back(command);
+        }
+    }
+
+    /// Determine whether a requested shutdown can complete.
+    /// The in-flight operation is abandoned if it is still running past the shutdown deadline,
+    /// and reported as aborted on `res`.
+    fn ready_to_shutdown(&mut self, res: &Sender<Vec<OperationResult>>) -> bool {
+        let Some(deadline) = self.shutdown_deadline else {
+            return false;
+        };
+        match self.state {
+            State::Uninitialized | State::Idle => true,
+            State::Dkg | State::Sign => {
+                if Instant::now() < deadline {
+                    debug!(
+                        "Waiting for {:?} operation to finish before shutting down",
+                        self.state
+                    );
+                    return false;
+                }
+                warn!(
+                    "Timed out waiting for {:?} operation to finish. Abandoning it.",
+                    self.state
+                );
+                self.coordinator.reset();
+                self.state = State::Idle;
+                self.round_started = None;
+                report_aborted_operation(res);
+                true
+            }
+        }
+    }
+
     /// Execute the given command and update state accordingly
     /// Returns true when it is successfully executed, else false
     fn execute_command(&mut self, command: &RunLoopCommand) -> bool {
@@ -99,6 +703,7 @@ impl<C: Coordinatable> RunLoop<C> {
                             .send_message_with_retry(self.signing_round.signer_id, msg);
                         debug!("ACK: {:?}", ack);
                         self.state = State::Dkg;
+                        self.round_started = Some(Instant::now());
                         true
                     }
                     Err(e) => {
@@ -125,6 +730,7 @@ impl<C: Coordinatable> RunLoop<C> {
                             .send_message_with_retry(self.signing_round.signer_id, msg);
                         debug!("ACK: {:?}", ack);
                         self.state = State::Sign;
+                        self.round_started = Some(Instant::now());
                         true
                     }
                     Err(e) => {
@@ -135,6 +741,10 @@ impl<C: Coordinatable> RunLoop<C> {
                     }
                 }
             }
+            RunLoopCommand::Shutdown | RunLoopCommand::ReloadConfig(_) => {
+                // Shutdown and reload requests are never queued. See queue_command.
+                true
+            }
         }
     }
 
@@ -163,36 +773,256 @@ impl<C: Coordinatable> RunLoop<C> {
         }
     }
 
+    /// Process the event, send the messages it provokes, and report any operation results.
+    fn handle_event(
+        &mut self,
+        event: &StackerDBChunksEvent,
+        res: &Sender<Vec<OperationResult>>,
+    ) -> Vec<OperationResult> {
+        let (outbound_messages, operation_results) = self.process_event(event);
+        if self.simulate {
+            for msg in outbound_messages.iter() {
+                report_simulated_message(msg);
+            }
+        } else {
+            self.send_outbound_messages(outbound_messages);
+        }
+
+        let nmb_results = operation_results.len();
+        if nmb_results > 0 {
+            // We finished our command. Update the state
+            self.state = State::Idle;
+            self.round_started = None;
+            for result in operation_results.iter() {
+                if let OperationResult::Dkg(key) = result {
+                    self.vote_for_aggregate_key(*key);
+                    if let Some(dkg_scheduler) = self.dkg_scheduler.as_mut() {
+                        dkg_scheduler.dkg_completed();
+                    }
+                }
+            }
+            match res.send(operation_results.clone()) {
+                Ok(_) => debug!("Successfully sent {} operation result(s)", nmb_results),
+                Err(e) => {
+                    warn!("Failed to send operation results: {:?}", e);
+                }
+            }
+        }
+        operation_results
+    }
+
+    /// Decide whether the in-flight operation survives a stacks node restart.
+    /// Operations older than `round_resume_timeout` are abandoned, since the rest of the fleet has
+    /// likely given up on them.
+    fn resolve_in_flight_round(&mut self, now: Instant) {
+        if !matches!(self.state, State::Dkg | State::Sign) {
+            return;
+        }
+        let expired = self.round_started.map_or(true, |started| {
+            now.saturating_duration_since(started) >= self.round_resume_timeout
+        });
+        if !expired {
+            info!("Resuming {:?} operation", self.state);
+            return;
+        }
+        warn!(
+            "{:?} operation is too old to resume. Abandoning it.",
+            self.state
+        );
+        self.coordinator.reset();
+        self.state = State::Idle;
+        self.round_started = None;
+        if self.coordinator_selector.coordinator_id() == self.signing_round.signer_id {
+            self.queue_dkg_if_needed();
+        }
+    }
+
+    /// Fetch the chunks written to the stacker-db instance that were not delivered as events, and
+    /// adopt the instance's versions of our own slots
+    fn fetch_missed_chunks(&mut self) -> Result<Vec<StackerDBChunkData>, ClientError> {
+        let slots = self.stacks_client.list_chunks()?;
+        self.stacks_client
+            .sync_slot_versions(self.signing_round.signer_id, &slots);
+        let mut chunks = vec![];
+        for slot in slots {
+            // Skip slots that were never written, or that we are caught up on
+            if slot.slot_version == 0
+                || self.event_gap_detector.last_version(slot.slot_id) == Some(slot.slot_version)
+            {
+                continue;
+            }
+            // The slot may have been overwritten since it was listed
+            let Some(data) = self
+                .stacks_client
+                .get_chunk(slot.slot_id, slot.slot_version)?
+            else {
+                continue;
+            };
+            chunks.push(StackerDBChunkData {
+                slot_id: slot.slot_id,
+                slot_version: slot.slot_version,
+                sig: slot.signature,
+                data,
+            });
+        }
+        Ok(chunks)
+    }
+
+    /// Bring the signer back in line with the stacks node after it may have restarted: re-fetch
+    /// the chain context, decide whether to keep the in-flight operation, and replay the
+    /// stacker-db writes we missed.
+    fn recover_from_node_restart(
+        &mut self,
+        res: &Sender<Vec<OperationResult>>,
+    ) -> Vec<OperationResult> {
+        warn!("Stacks node may have restarted. Resynchronizing with it.");
+        self.resync_pending = false;
+        // A new reward cycle may have begun while burn blocks were missed
+        self.reseed_pending = true;
+        self.resolve_in_flight_round(Instant::now());
+        match self.stacks_client.get_aggregate_public_key() {
+            Ok(Some(key)) => self.coordinator.set_aggregate_public_key(Some(key)),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to re-fetch the aggregate public key: {:?}", e);
+                self.resync_pending = true;
+                return vec![];
+            }
+        }
+        let chunks = match self.fetch_missed_chunks() {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                warn!("Failed to fetch missed stacker-db chunks: {:?}", e);
+                self.resync_pending = true;
+                return vec![];
+            }
+        };
+        // Requests made while recovering do not indicate another restart
+        self.stacks_client.take_connection_lost();
+        if chunks.is_empty() {
+            return vec![];
+        }
+        info!("Replaying {} missed stacker-db chunk(s)", chunks.len());
+        let event = StackerDBChunksEvent {
+            contract_id: self.stacks_client.stackerdb_contract_id().clone(),
+            modified_slots: chunks,
+        };
+        let operation_results = self.handle_event(&event, res);
+        // Replayed chunks can skip versions, but nothing was missed. Chunks that were rate limited
+        // again are still forgotten, and must be fetched by a later resync.
+        self.resync_pending = event.modified_slots.iter().any(|chunk| {
+            self.event_gap_detector
+                .last_version(chunk.slot_id)
+                .is_none()
+        });
+        operation_results
+    }
+
     /// Process the event as both a signer and a coordinator
     fn process_event(
         &mut self,
         event: &StackerDBChunksEvent,
     ) -> (Vec<Packet>, Vec<OperationResult>) {
-        // Determine the current coordinator id and public key for verification
-        let (coordinator_id, coordinator_public_key) =
-            calculate_coordinator(&self.signing_round.public_keys);
-        // Filter out invalid messages
-        let inbound_messages: Vec<Packet> = event
-            .modified_slots
-            .iter()
-            .filter_map(|chunk| {
-                let message = bincode::deserialize::<Packet>(&chunk.data).ok()?;
-                if verify_msg(
-                    &message,
-                    &self.signing_round.public_keys,
-                    coordinator_public_key,
-                ) {
-                    Some(message)
-                } else {
-                    None
+        // Filter out duplicate, excess, and invalid messages
+        let now = Instant::now();
+        let mut inbound_messages: Vec<Packet> = vec![];
+        for chunk in event.modified_slots.iter() {
+            if self
+                .event_gap_detector
+                .observe(chunk.slot_id, chunk.slot_version)
+            {
+                debug!(
+                    "Missed writes to slot {} before version {}",
+                    chunk.slot_id, chunk.slot_version
+                );
+                self.resync_pending = true;
+            }
+            if let Some(fleet_policy) = self.fleet_policy.as_mut() {
+                if chunk.slot_id == fleet_policy.slot_id {
+                    match fleet_policy.receive(&chunk.data) {
+                        Ok(()) => info!(
+                            "Received fleet policy, to be applied in reward cycle {:?}",
+                            fleet_policy.pending_activation_cycle()
+                        ),
+                        Err(rejection) => warn!(
+                            "Ignoring chunk in fleet policy slot {} (version {}): {:?}",
+                            chunk.slot_id, chunk.slot_version, rejection
+                        ),
+                    }
+                    continue;
                 }
-            })
-            .collect();
+            }
+            let sender_id = chunk.slot_id / SLOTS_PER_USER;
+            if self.denied_signers.contains(&sender_id) {
+                debug!(
+                    "Dropping chunk in slot {} from denied signer ID# {}",
+                    chunk.slot_id, sender_id
+                );
+                continue;
+            }
+            if let Err(rejection) = self.packet_filter.check(chunk, now) {
+                debug!(
+                    "Dropping chunk in slot {} (version {}): {:?}",
+                    chunk.slot_id, chunk.slot_version, rejection
+                );
+                if rejection == PacketRejection::RateLimited {
+                    // Fetch the chunk again once the signer is within its rate limit
+                    self.event_gap_detector.forget(chunk.slot_id);
+                    self.resync_pending = true;
+                }
+                continue;
+            }
+            let Ok(message) = bincode::deserialize::<Packet>(&chunk.data) else {
+                if self.simulate {
+                    info!(
+                        "Simulation: would reject chunk in slot {}: not a valid packet",
+                        chunk.slot_id
+                    );
+                }
+                continue;
+            };
+            let from_coordinator = is_coordinator_message(&message.msg);
+            if from_coordinator && !self.accept_coordinator_message(sender_id, &message) {
+                continue;
+            }
+            // Determine the current coordinator public key for verification
+            let coordinator_id = self.coordinator_selector.coordinator_id();
+            let Some(coordinator_public_key) =
+                self.signing_round.public_keys.signers.get(&coordinator_id)
+            else {
+                continue;
+            };
+            if verify_msg(
+                &message,
+                &self.signing_round.public_keys,
+                coordinator_public_key,
+            ) {
+                if from_coordinator {
+                    self.coordinator_selector.heard_from_coordinator();
+                }
+                inbound_messages.push(message);
+            } else if self.simulate {
+                info!(
+                    "Simulation: would reject chunk in slot {}: invalid signature on {} message",
+                    chunk.slot_id,
+                    message_type(&message.msg)
+                );
+            }
+        }
         // First process all messages as a signer
         let mut outbound_messages = self
             .signing_round
             .process_inbound_messages(&inbound_messages)
             .unwrap_or_default();
+        let coordinator_id = self.coordinator_selector.coordinator_id();
+        // Some responses must be followed up by the coordinator
+        if self.signing_round.signer_id != coordinator_id
+            && outbound_messages
+                .iter()
+                .any(|packet| awaits_coordinator(&packet.msg))
+        {
+            self.coordinator_selector.expect_packet(now);
+        }
         // If the signer is the coordinator, then next process the message as the coordinator
         let (messages, results) = if self.signing_round.signer_id == coordinator_id {
             self.coordinator
@@ -206,9 +1036,92 @@ impl<C: Coordinatable> RunLoop<C> {
     }
 }
 
+impl<C, S: StacksNode + StackerDB> RunLoop<C, S> {
+    /// Apply the settings of a fleet policy
+    fn apply_fleet_policy(&mut self, policy: FleetPolicy) {
+        info!(
+            "Applying fleet policy #{} for reward cycle {}",
+            policy.sequence, policy.activation_cycle
+        );
+        if let Some(event_timeout) = policy.event_timeout {
+            self.event_timeout = Duration::from_millis(event_timeout);
+        }
+        if let Some(coordinator_timeout) = policy.coordinator_timeout {
+            self.coordinator_selector.timeout = Duration::from_millis(coordinator_timeout);
+        }
+        if let Some(rate_limit_packets) = policy.rate_limit_packets {
+            self.packet_filter.rate_limit_packets = rate_limit_packets;
+        }
+        if let Some(rate_limit_window) = policy.rate_limit_window {
+            self.packet_filter.rate_limit_window = Duration::from_millis(rate_limit_window);
+        }
+        if let Some(denied_signers) = policy.denied_signers {
+            if denied_signers.contains(&self.signing_round.signer_id) {
+                warn!(
+                    "Signer ID# {} is on the fleet's deny list",
+                    self.signing_round.signer_id
+                );
+            }
+            self.denied_signers = denied_signers.into_iter().collect();
+        }
+    }
+
+    /// Apply settings re-read from the config file.
+    /// An in-flight operation keeps running, and picks up the new timeouts from here on.
+    fn apply_config_reload(&mut self, config: ReloadableConfig) {
+        info!("Applying reloaded config: {:?}", config);
+        self.stacks_client.set_node_host(config.node_host);
+        self.event_timeout = config.event_timeout;
+        self.shutdown_timeout = config.shutdown_timeout;
+        self.packet_filter.rate_limit_packets = config.rate_limit_packets;
+        self.packet_filter.rate_limit_window = config.rate_limit_window;
+        self.coordinator_selector.timeout = config.coordinator_timeout;
+        self.round_resume_timeout = config.round_resume_timeout;
+        match (self.dkg_scheduler.as_mut(), config.dkg_lead_time) {
+            (Some(dkg_scheduler), Some(lead_time)) => {
+                dkg_scheduler.lead_time = lead_time;
+                dkg_scheduler.max_retries = config.dkg_max_retries;
+            }
+            (None, Some(lead_time)) => {
+                self.dkg_scheduler = Some(DkgScheduler::new(lead_time, config.dkg_max_retries));
+            }
+            (_, None) => self.dkg_scheduler = None,
+        }
+        if let Some(voter) = self.aggregate_key_voter.as_mut() {
+            voter.confirm_timeout = config.vote_confirm_timeout;
+            voter.max_retries = config.vote_max_retries;
+        }
+    }
+
+    /// Write the given messages to the stacker-db instance
+    fn send_outbound_messages(&mut self, outbound_messages: Vec<Packet>) {
+        debug!(
+            "Sending {} messages to other stacker-db instances.",
+            outbound_messages.len()
+        );
+        for msg in outbound_messages {
+            let ack = self
+                .stacks_client
+                .send_message_with_retry(self.signing_round.signer_id, msg);
+            if let Ok(ack) = ack {
+                debug!("ACK: {:?}", ack);
+            } else {
+                warn!("Failed to send message to stacker-db instance: {:?}", ack);
+            }
+        }
+    }
+}
+
 impl From<&Config> for RunLoop<FrostCoordinator<v2::Aggregator>> {
     /// Creates new runloop from a config
     fn from(config: &Config) -> Self {
+        RunLoop::new(config, StacksClient::from(config))
+    }
+}
+
+impl<S> RunLoop<FrostCoordinator<v2::Aggregator>, S> {
+    /// Creates a new runloop from a config, that talks to the stacks node through the given client
+    pub fn new(config: &Config, stacks_client: S) -> Self {
         // TODO: this should be a config option
         // See: https://github.com/stacks-network/stacks-blockchain/issues/3914
         let threshold = ((config.signer_ids_public_keys.key_ids.len() * 7) / 10)
@@ -248,7 +1161,6 @@ impl From<&Config> for RunLoop<FrostCoordinator<v2::Aggregator>> {
             config.message_private_key,
             config.signer_ids_public_keys.clone(),
         );
-        let stacks_client = StacksClient::from(config);
         RunLoop {
             event_timeout: config.event_timeout,
             coordinator,
@@ -256,11 +1168,40 @@ impl From<&Config> for RunLoop<FrostCoordinator<v2::Aggregator>> {
             stacks_client,
             commands: VecDeque::new(),
             state: State::Uninitialized,
+            shutdown_timeout: config.shutdown_timeout,
+            shutdown_deadline: None,
+            simulate: false,
+            packet_filter: PacketFilter::new(config.rate_limit_packets, config.rate_limit_window),
+            // Seeded with the current reward cycle once the signer initializes
+            coordinator_selector: CoordinatorSelector::new(
+                &config.signer_ids_public_keys,
+                config.coordinator_timeout,
+            ),
+            fleet_policy: config
+                .fleet_policy_slot_id
+                .zip(config.governance_public_key)
+                .map(|(slot_id, governance_public_key)| {
+                    FleetPolicyTracker::new(slot_id, governance_public_key)
+                }),
+            denied_signers: HashSet::new(),
+            round_started: None,
+            round_resume_timeout: config.round_resume_timeout,
+            event_gap_detector: EventGapDetector::default(),
+            resync_pending: false,
+            reseed_pending: false,
+            dkg_scheduler: config
+                .dkg_lead_time
+                .map(|lead_time| DkgScheduler::new(lead_time, config.dkg_max_retries)),
+            aggregate_key_voter: config.vote_for_aggregate_key.then(|| {
+                AggregateKeyVoter::new(config.vote_confirm_timeout, config.vote_max_retries)
+            }),
         }
     }
 }
 
-impl<C: Coordinatable> SignerRunLoop<Vec<OperationResult>, RunLoopCommand> for RunLoop<C> {
+impl<C: Coordinatable, S: StacksNode + StackerDB>
+    SignerRunLoop<Vec<OperationResult>, RunLoopCommand> for RunLoop<C, S>
+{
     fn set_event_timeout(&mut self, timeout: Duration) {
         self.event_timeout = timeout;
     }
@@ -271,7 +1212,7 @@ impl<C: Coordinatable> SignerRunLoop<Vec<OperationResult>, RunLoopCommand> for R
 
     fn run_one_pass(
         &mut self,
-        event: Option<StackerDBChunksEvent>,
+        event: Option<SignerEvent>,
         cmd: Option<RunLoopCommand>,
         res: Sender<Vec<OperationResult>>,
     ) -> Option<Vec<OperationResult>> {
@@ -280,42 +1221,49 @@ impl<C: Coordinatable> SignerRunLoop<Vec<OperationResult>, RunLoopCommand> for R
             self.signing_round.signer_id, self.state
         );
         if let Some(command) = cmd {
-            self.commands.push_back(command);
+            self.queue_command(command, &res);
         }
-        if self.state == State::Uninitialized {
+        if self.state == State::Uninitialized && self.shutdown_deadline.is_none() {
             let request_fn = || self.initialize().map_err(backoff::Error::transient);
             retry_with_exponential_backoff(request_fn)
                 .expect("Failed to connect to initialize due to timeout. Stacks node may be down.");
         }
+        let mut operation_results = vec![];
         // Process any arrived events
-        if let Some(event) = event {
-            let (outbound_messages, operation_results) = self.process_event(&event);
-            debug!(
-                "Sending {} messages to other stacker-db instances.",
-                outbound_messages.len()
-            );
-            for msg in outbound_messages {
-                let ack = self
-                    .stacks_client
-                    .send_message_with_retry(self.signing_round.signer_id, msg);
-                if let Ok(ack) = ack {
-                    debug!("ACK: {:?}", ack);
-                } else {
-                    warn!("Failed to send message to stacker-db instance: {:?}", ack);
-                }
+        match event {
+            Some(SignerEvent::StackerDBChunks(event)) => {
+                operation_results = self.handle_event(&event, &res);
             }
-
-            let nmb_results = operation_results.len();
-            if nmb_results > 0 {
-                // We finished our command. Update the state
-                self.state = State::Idle;
-                match res.send(operation_results) {
-                    Ok(_) => debug!("Successfully sent {} operation result(s)", nmb_results),
-                    Err(e) => {
-                        warn!("Failed to send operation results: {:?}", e);
-                    }
-                }
+            Some(SignerEvent::BurnBlockAdvance {
+                burn_block_height,
+                burn_block_hash,
+            }) => {
+                debug!(
+                    "Burnchain advanced to block {} at height {}",
+                    burn_block_hash, burn_block_height
+                );
+                self.reseed_pending = true;
             }
+            None => {}
+        }
+        // A failed request or missed events mean the stacks node may have restarted
+        if self.state != State::Uninitialized
+            && (self.stacks_client.take_connection_lost() || self.resync_pending)
+        {
+            operation_results.extend(self.recover_from_node_restart(&res));
+        }
+        if self.state != State::Uninitialized {
+            self.check_coordinator_liveness(Instant::now());
+        }
+        self.apply_due_fleet_policy();
+        self.reseed_coordinator_rotation();
+        self.schedule_dkg();
+        self.check_aggregate_key_vote(Instant::now());
+        if self.ready_to_shutdown(&res) {
+            info!("Signer ID# {} shutting down", self.signing_round.signer_id);
+            self.stacks_client.flush();
+            // The final results have already been delivered on the result channel
+            return Some(operation_results);
         }
         // The process the next command
         // Must be called AFTER processing the event as the state may update to IDLE due to said event.
@@ -324,12 +1272,66 @@ impl<C: Coordinatable> SignerRunLoop<Vec<OperationResult>, RunLoopCommand> for R
     }
 }
 
-/// Helper function for determining the coordinator public key given the the public keys
-fn calculate_coordinator(public_keys: &PublicKeys) -> (u32, &ecdsa::PublicKey) {
-    // TODO: do some sort of VRF here to calculate the public key
-    // See: https://github.com/stacks-network/stacks-blockchain/issues/3915
-    // Mockamato just uses the first signer_id as the coordinator for now
-    (0, public_keys.signers.get(&0).unwrap())
+/// Report a DKG or signing operation that will never finish because the signer is shutting down.
+/// `OperationResult` has no variant for an aborted operation, so it is reported as an empty list
+/// of results.
+fn report_aborted_operation(res: &Sender<Vec<OperationResult>>) {
+    match res.send(vec![]) {
+        Ok(_) => debug!("Reported an aborted operation"),
+        Err(e) => warn!("Failed to report an aborted operation: {:?}", e),
+    }
+}
+
+/// Whether the message can only be sent by the coordinator
+fn is_coordinator_message(msg: &Message) -> bool {
+    matches!(
+        msg,
+        Message::DkgBegin(_)
+            | Message::DkgPrivateBegin(_)
+            | Message::NonceRequest(_)
+            | Message::SignatureShareRequest(_)
+    )
+}
+
+/// Whether a signer that sent this message is waiting on the coordinator's next step
+fn awaits_coordinator(msg: &Message) -> bool {
+    matches!(msg, Message::DkgPublicShares(_) | Message::NonceResponse(_))
+}
+
+/// Helper function for naming the type of a message in logs
+fn message_type(msg: &Message) -> &'static str {
+    match msg {
+        Message::DkgBegin(_) => "DkgBegin",
+        Message::DkgPrivateBegin(_) => "DkgPrivateBegin",
+        Message::DkgEnd(_) => "DkgEnd",
+        Message::DkgPublicShares(_) => "DkgPublicShares",
+        Message::DkgPrivateShares(_) => "DkgPrivateShares",
+        Message::NonceRequest(_) => "NonceRequest",
+        Message::NonceResponse(_) => "NonceResponse",
+        Message::SignatureShareRequest(_) => "SignatureShareRequest",
+        Message::SignatureShareResponse(_) => "SignatureShareResponse",
+    }
+}
+
+/// Report a message that a simulated signer would have written to the stacker-db instance
+fn report_simulated_message(packet: &Packet) {
+    match &packet.msg {
+        Message::NonceResponse(msg) => info!(
+            "Simulation: would commit nonces for sign_id {} (iteration {}) with key ids {:?}",
+            msg.sign_id, msg.sign_iter_id, msg.key_ids
+        ),
+        Message::SignatureShareResponse(msg) => info!(
+            "Simulation: would sign for sign_id {} (iteration {}) with {} signature share(s)",
+            msg.sign_id,
+            msg.sign_iter_id,
+            msg.signature_shares.len()
+        ),
+        Message::DkgEnd(msg) => info!(
+            "Simulation: would end DKG round {} with status {:?}",
+            msg.dkg_id, msg.status
+        ),
+        msg => info!("Simulation: would send {} message", message_type(msg)),
+    }
 }
 
 /// TODO: this should not be here.
@@ -435,3 +1437,986 @@ fn verify_msg(
     }
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::{channel, Receiver};
+
+    use clarity::vm::types::QualifiedContractIdentifier;
+    use p256k1::scalar::Scalar;
+    use rand_core::OsRng;
+    use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksPrivateKey};
+
+    use super::*;
+    use crate::mock_client::{MockFailure, MockStacksClient};
+    use crate::stacks_client::slot_id;
+    use crate::utils::build_weighted_signer_config_tomls;
+
+    fn test_runloop() -> RunLoop<FrostCoordinator<v2::Aggregator>> {
+        let config = Config::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        RunLoop::from(&config)
+    }
+
+    #[test]
+    fn shutdown_while_idle_exits_immediately() {
+        let mut runloop = test_runloop();
+        runloop.commands.push_back(RunLoopCommand::Dkg);
+        let (res_send, res_recv) = channel();
+        let final_results = runloop.run_one_pass(None, Some(RunLoopCommand::Shutdown), res_send);
+        assert_eq!(final_results.map(|results| results.len()), Some(0));
+        assert!(runloop.commands.is_empty());
+        // The dropped command is reported as aborted
+        assert!(res_recv.try_recv().unwrap().is_empty());
+        assert!(res_recv.try_recv().is_err());
+    }
+
+    #[test]
+    fn shutdown_waits_for_in_flight_operation() {
+        let mut runloop = test_runloop();
+        runloop.state = State::Dkg;
+        runloop.shutdown_timeout = Duration::from_secs(3600);
+        let (res_send, res_recv) = channel();
+        assert!(runloop
+            .run_one_pass(None, Some(RunLoopCommand::Shutdown), res_send.clone())
+            .is_none());
+        assert!(runloop.shutdown_deadline.is_some());
+        assert!(res_recv.try_recv().is_err());
+
+        // Commands received after the shutdown request are ignored
+        assert!(runloop
+            .run_one_pass(None, Some(RunLoopCommand::Dkg), res_send.clone())
+            .is_none());
+        assert!(runloop.commands.is_empty());
+
+        // Once the deadline passes, the operation is abandoned and reported as aborted
+        runloop.shutdown_deadline = Some(Instant::now());
+        assert!(runloop.run_one_pass(None, None, res_send).is_some());
+        assert_eq!(runloop.state, State::Idle);
+        assert!(res_recv.try_recv().unwrap().is_empty());
+        assert!(res_recv.try_recv().is_err());
+    }
+
+    #[test]
+    fn simulation_does_not_coordinate() {
+        let mut runloop = test_runloop();
+        runloop.simulate = true;
+        runloop.state = State::Idle;
+        let (res_send, _res_recv) = channel();
+        assert!(runloop
+            .run_one_pass(None, Some(RunLoopCommand::Dkg), res_send)
+            .is_none());
+        assert!(runloop.commands.is_empty());
+        assert_eq!(runloop.state, State::Idle);
+    }
+
+    #[test]
+    fn reloaded_config_is_applied_immediately() {
+        let config = Config::load_from_file("./src/tests/conf/signer-0.toml").unwrap();
+        let mut runloop = RunLoop::from(&config);
+        runloop.state = State::Sign;
+        let mut reloadable = config.reloadable();
+        reloadable.event_timeout = Duration::from_millis(1234);
+        reloadable.rate_limit_packets = 7;
+        reloadable.coordinator_timeout = Duration::from_millis(4321);
+        reloadable.dkg_lead_time = Some(20);
+
+        let (res_send, _res_recv) = channel();
+        assert!(runloop
+            .run_one_pass(
+                None,
+                Some(RunLoopCommand::ReloadConfig(reloadable)),
+                res_send
+            )
+            .is_none());
+        // The in-flight operation is not disrupted, and nothing is queued
+        assert_eq!(runloop.state, State::Sign);
+        assert!(runloop.commands.is_empty());
+        assert_eq!(runloop.event_timeout, Duration::from_millis(1234));
+        assert_eq!(runloop.packet_filter.rate_limit_packets, 7);
+        assert_eq!(
+            runloop.coordinator_selector.timeout,
+            Duration::from_millis(4321)
+        );
+        assert_eq!(runloop.dkg_scheduler.map(|s| s.lead_time), Some(20));
+    }
+
+    #[test]
+    fn dkg_scheduler_starts_within_lead_time() {
+        let mut scheduler = DkgScheduler::new(10, 1);
+        // Too early, and too late
+        assert!(!scheduler.should_start(89, 5, 100));
+        assert!(!scheduler.should_start(100, 5, 100));
+        // First attempt and one retry
+        assert!(scheduler.should_start(90, 5, 100));
+        assert!(scheduler.should_start(95, 5, 100));
+        assert!(!scheduler.should_start(96, 5, 100));
+
+        // A new reward cycle resets the retries
+        assert_eq!(scheduler.scheduled_reward_cycle(), Some(5));
+        assert!(!scheduler.should_start(189, 6, 200));
+        assert_eq!(scheduler.scheduled_reward_cycle(), None);
+        assert!(scheduler.should_start(190, 6, 200));
+        scheduler.dkg_completed();
+        assert!(!scheduler.should_start(191, 6, 200));
+    }
+
+    #[test]
+    fn packet_filter_drops_duplicates() {
+        let mut filter = PacketFilter::new(100, Duration::from_secs(10));
+        let now = Instant::now();
+        let chunk = StackerDBChunkData::new(SLOTS_PER_USER + 4, 1, vec![1, 2, 3]);
+        assert_eq!(filter.check(&chunk, now), Ok(()));
+
+        // The same write, delivered again
+        assert_eq!(
+            filter.check(&chunk, now),
+            Err(PacketRejection::DuplicateSlot)
+        );
+
+        // The same packet, written again
+        let mut rewritten = chunk.clone();
+        rewritten.slot_version += 1;
+        assert_eq!(filter.check(&rewritten, now), Ok(()));
+
+        // The same packet, written to another slot
+        let moved = StackerDBChunkData::new(SLOTS_PER_USER + 5, 1, vec![1, 2, 3]);
+        assert_eq!(filter.check(&moved, now), Ok(()));
+
+        // Different sender, same slot version
+        let other = StackerDBChunkData::new(2 * SLOTS_PER_USER + 4, 2, vec![1, 2, 3]);
+        assert_eq!(filter.check(&other, now), Ok(()));
+    }
+
+    #[test]
+    fn packet_filter_rate_limits_each_signer() {
+        let window = Duration::from_secs(10);
+        let mut filter = PacketFilter::new(3, window);
+        let start = Instant::now();
+        for i in 0..3u8 {
+            let chunk = StackerDBChunkData::new(i as u32, 1, vec![i]);
+            assert_eq!(filter.check(&chunk, start), Ok(()));
+        }
+        let excess = StackerDBChunkData::new(3, 1, vec![3]);
+        assert_eq!(
+            filter.check(&excess, start),
+            Err(PacketRejection::RateLimited)
+        );
+
+        // Other signers are unaffected
+        let other = StackerDBChunkData::new(SLOTS_PER_USER, 1, vec![3]);
+        assert_eq!(filter.check(&other, start), Ok(()));
+
+        // Once the window passes, the signer may send again, including the chunk that was
+        // rate limited
+        assert_eq!(filter.check(&excess, start + window), Ok(()));
+        let later = StackerDBChunkData::new(4, 1, vec![4]);
+        assert_eq!(filter.check(&later, start + window), Ok(()));
+    }
+
+    #[test]
+    fn event_gap_detector_flags_missed_writes() {
+        let mut detector = EventGapDetector::default();
+        assert!(!detector.observe(4, 3));
+        assert!(!detector.observe(4, 4));
+        // Redelivery of the same version is not a gap
+        assert!(!detector.observe(4, 4));
+        assert!(detector.observe(4, 6));
+        // The node's replica was reset
+        assert!(detector.observe(4, 1));
+        assert_eq!(detector.last_version(4), Some(1));
+        assert_eq!(detector.last_version(5), None);
+    }
+
+    #[test]
+    fn missed_writes_require_resync() {
+        let mut runloop = test_runloop();
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-stackerdb",
+        )
+        .unwrap();
+        let event = StackerDBChunksEvent {
+            contract_id: contract_id.clone(),
+            modified_slots: vec![StackerDBChunkData::new(SLOTS_PER_USER + 4, 1, vec![1])],
+        };
+        runloop.process_event(&event);
+        assert!(!runloop.resync_pending);
+
+        let event = StackerDBChunksEvent {
+            contract_id,
+            modified_slots: vec![StackerDBChunkData::new(SLOTS_PER_USER + 4, 3, vec![3])],
+        };
+        runloop.process_event(&event);
+        assert!(runloop.resync_pending);
+    }
+
+    #[test]
+    fn rate_limited_chunks_require_resync() {
+        let mut runloop = test_runloop();
+        runloop.packet_filter.rate_limit_packets = 1;
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-stackerdb",
+        )
+        .unwrap();
+        let event = StackerDBChunksEvent {
+            contract_id,
+            modified_slots: vec![
+                StackerDBChunkData::new(SLOTS_PER_USER + 4, 1, vec![1]),
+                StackerDBChunkData::new(SLOTS_PER_USER + 5, 1, vec![2]),
+            ],
+        };
+        runloop.process_event(&event);
+        assert!(runloop.resync_pending);
+        // The rate limited slot is fetched again by the resync
+        assert_eq!(
+            runloop.event_gap_detector.last_version(SLOTS_PER_USER + 4),
+            Some(1)
+        );
+        assert_eq!(
+            runloop.event_gap_detector.last_version(SLOTS_PER_USER + 5),
+            None
+        );
+    }
+
+    #[test]
+    fn node_restart_resumes_only_recent_rounds() {
+        let mut runloop = test_runloop();
+        let started = Instant::now();
+        runloop.state = State::Dkg;
+        runloop.round_started = Some(started);
+        runloop.round_resume_timeout = Duration::from_secs(60);
+
+        runloop.resolve_in_flight_round(started + Duration::from_secs(30));
+        assert_eq!(runloop.state, State::Dkg);
+        assert_eq!(runloop.round_started, Some(started));
+
+        runloop.resolve_in_flight_round(started + Duration::from_secs(60));
+        assert_eq!(runloop.state, State::Idle);
+        assert!(runloop.round_started.is_none());
+
+        // An operation of unknown age is not resumed
+        runloop.state = State::Sign;
+        runloop.resolve_in_flight_round(started);
+        assert_eq!(runloop.state, State::Idle);
+    }
+
+    #[test]
+    fn fleet_policy_is_applied() {
+        let governance_private_key = Scalar::random(&mut OsRng);
+        let governance_public_key = ecdsa::PublicKey::new(&governance_private_key).unwrap();
+        let policy_slot_id = 100 * SLOTS_PER_USER;
+        let mut runloop = test_runloop();
+        runloop.fleet_policy = Some(FleetPolicyTracker::new(
+            policy_slot_id,
+            governance_public_key,
+        ));
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-stackerdb",
+        )
+        .unwrap();
+
+        let policy = FleetPolicy {
+            sequence: 1,
+            activation_cycle: 5,
+            event_timeout: Some(1000),
+            coordinator_timeout: Some(2000),
+            rate_limit_packets: Some(8),
+            rate_limit_window: Some(3000),
+            denied_signers: Some(vec![1]),
+        };
+        let signed_policy = policy.clone().sign(&governance_private_key).unwrap();
+        let event = StackerDBChunksEvent {
+            contract_id: contract_id.clone(),
+            modified_slots: vec![StackerDBChunkData::new(
+                policy_slot_id,
+                1,
+                bincode::serialize(&signed_policy).unwrap(),
+            )],
+        };
+        runloop.process_event(&event);
+        let fleet_policy = runloop.fleet_policy.as_mut().unwrap();
+        assert_eq!(fleet_policy.pending_activation_cycle(), Some(5));
+        // The policy slot is not subject to packet filtering
+        assert!(runloop.packet_filter.last_slot_versions.is_empty());
+
+        let due_policy = fleet_policy.take_due(5).unwrap();
+        runloop.apply_fleet_policy(due_policy);
+        assert_eq!(runloop.event_timeout, Duration::from_millis(1000));
+        assert_eq!(
+            runloop.coordinator_selector.timeout,
+            Duration::from_millis(2000)
+        );
+        assert_eq!(runloop.packet_filter.rate_limit_packets, 8);
+        assert_eq!(
+            runloop.packet_filter.rate_limit_window,
+            Duration::from_millis(3000)
+        );
+
+        // Chunks from denied signers are dropped
+        let event = StackerDBChunksEvent {
+            contract_id,
+            modified_slots: vec![
+                StackerDBChunkData::new(SLOTS_PER_USER + 4, 1, vec![1, 2, 3]),
+                StackerDBChunkData::new(2 * SLOTS_PER_USER + 4, 1, vec![1, 2, 3]),
+            ],
+        };
+        runloop.process_event(&event);
+        assert!(!runloop
+            .packet_filter
+            .last_slot_versions
+            .contains_key(&(SLOTS_PER_USER + 4)));
+        assert!(runloop
+            .packet_filter
+            .last_slot_versions
+            .contains_key(&(2 * SLOTS_PER_USER + 4)));
+    }
+
+    /// Upper bound on the number of bus deliveries a single round may take before it is
+    /// considered stalled
+    const MAX_ROUND_PASSES: usize = 16;
+
+    /// An in-process fleet of signers. Every stacker-db write is delivered to every signer over
+    /// an in-memory bus instead of through a stacks node.
+    struct SignerFleet {
+        runloops: Vec<RunLoop<FrostCoordinator<v2::Aggregator>>>,
+        contract_id: QualifiedContractIdentifier,
+    }
+
+    /// Message counts and timing for a single DKG or signing round
+    #[derive(Debug)]
+    struct RoundStats {
+        elapsed: Duration,
+        passes: usize,
+        messages: usize,
+        bytes: usize,
+    }
+
+    impl SignerFleet {
+        fn new(num_signers: u32, num_keys: u32) -> Self {
+            Self::new_weighted(&vec![1; num_signers as usize], num_keys)
+        }
+
+        /// A fleet whose key ids are shared out in proportion to the given signer weights
+        fn new_weighted(weights: &[u64], num_keys: u32) -> Self {
+            let stacks_private_keys: Vec<_> =
+                weights.iter().map(|_| StacksPrivateKey::new()).collect();
+            let contract_id = QualifiedContractIdentifier::parse(
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-stackerdb",
+            )
+            .unwrap();
+            let runloops = build_weighted_signer_config_tomls(
+                &stacks_private_keys,
+                weights,
+                num_keys,
+                "127.0.0.1:20443",
+                &contract_id.to_string(),
+                None,
+                None,
+            )
+            .iter()
+            .map(|toml| {
+                let config = Config::load_from_str(toml).unwrap();
+                let mut runloop = RunLoop::from(&config);
+                // No node to read the aggregate public key from
+                runloop.state = State::Idle;
+                runloop
+            })
+            .collect();
+            SignerFleet {
+                runloops,
+                contract_id,
+            }
+        }
+
+        /// The coordinator most of the fleet agrees on
+        fn coordinator_id(&self) -> u32 {
+            let mut votes = HashMap::new();
+            for runloop in self.runloops.iter() {
+                *votes
+                    .entry(runloop.coordinator_selector.coordinator_id())
+                    .or_insert(0) += 1;
+            }
+            votes
+                .into_iter()
+                .max_by_key(|(_, count)| *count)
+                .map(|(coordinator_id, _)| coordinator_id)
+                .unwrap()
+        }
+
+        fn coordinator(&mut self) -> &mut RunLoop<FrostCoordinator<v2::Aggregator>> {
+            let coordinator_id = self.coordinator_id();
+            &mut self.runloops[coordinator_id as usize]
+        }
+
+        /// Deliver the given packets to the whole fleet, returning the packets they provoke
+        fn deliver(
+            &mut self,
+            outbox: Vec<(u32, Packet)>,
+        ) -> (Vec<(u32, Packet)>, Vec<OperationResult>) {
+            let modified_slots = outbox
+                .into_iter()
+                .map(|(signer_id, packet)| {
+                    let data = bincode::serialize(&packet).unwrap();
+                    StackerDBChunkData::new(slot_id(signer_id, &packet.msg), 1, data)
+                })
+                .collect();
+            let event = StackerDBChunksEvent {
+                contract_id: self.contract_id.clone(),
+                modified_slots,
+            };
+            let mut packets = vec![];
+            let mut results = vec![];
+            for runloop in self.runloops.iter_mut() {
+                let (outbound, operation_results) = runloop.process_event(&event);
+                let signer_id = runloop.signing_round.signer_id;
+                packets.extend(outbound.into_iter().map(|packet| (signer_id, packet)));
+                results.extend(operation_results);
+            }
+            (packets, results)
+        }
+
+        /// Deliver `first_packet` and every packet it provokes to the whole fleet, until the
+        /// coordinator reports the outcome of the round
+        fn run_round(&mut self, first_packet: Packet) -> (Vec<OperationResult>, RoundStats) {
+            let start = Instant::now();
+            let mut outbox = vec![(self.coordinator_id(), first_packet)];
+            let mut results = vec![];
+            let mut passes = 0;
+            let mut messages = 0;
+            let mut bytes = 0;
+            while results.is_empty() {
+                assert!(
+                    !outbox.is_empty(),
+                    "Round stalled with no messages in flight"
+                );
+                assert!(passes < MAX_ROUND_PASSES, "Round did not finish");
+                passes += 1;
+                messages += outbox.len();
+                bytes += outbox
+                    .iter()
+                    .map(|(_, packet)| bincode::serialize(packet).unwrap().len())
+                    .sum::<usize>();
+                let (packets, operation_results) = self.deliver(std::mem::take(&mut outbox));
+                outbox = packets;
+                results.extend(operation_results);
+            }
+            let stats = RoundStats {
+                elapsed: start.elapsed(),
+                passes,
+                messages,
+                bytes,
+            };
+            (results, stats)
+        }
+
+        fn run_dkg(&mut self) -> RoundStats {
+            let packet = self
+                .coordinator()
+                .coordinator
+                .start_distributed_key_generation()
+                .unwrap();
+            let (results, stats) = self.run_round(packet);
+            assert!(matches!(results.as_slice(), [OperationResult::Dkg(_)]));
+            stats
+        }
+
+        fn run_sign(&mut self, message: &[u8]) -> RoundStats {
+            let packet = self
+                .coordinator()
+                .coordinator
+                .start_signing_message(message, false, None)
+                .unwrap();
+            let (results, stats) = self.run_round(packet);
+            assert!(matches!(results.as_slice(), [OperationResult::Sign(_)]));
+            stats
+        }
+    }
+
+    /// Run DKG and then a signing round over a fleet of the given size, checking that the
+    /// number of messages stays linear in the number of signers
+    fn check_fleet(num_signers: u32, num_keys: u32) -> (RoundStats, RoundStats) {
+        let mut fleet = SignerFleet::new(num_signers, num_keys);
+        let n = num_signers as usize;
+
+        let dkg_stats = fleet.run_dkg();
+        // DkgBegin, DkgPrivateBegin, and from each signer DkgPublicShares, DkgPrivateShares, DkgEnd
+        assert!(
+            dkg_stats.messages <= 3 * n + 2,
+            "DKG over {} signers took {} messages",
+            n,
+            dkg_stats.messages
+        );
+
+        let sign_stats = fleet.run_sign(&[1, 2, 3, 4, 5]);
+        // NonceRequest, SignatureShareRequest, and from each signer NonceResponse and
+        // SignatureShareResponse
+        assert!(
+            sign_stats.messages <= 2 * n + 2,
+            "Signing over {} signers took {} messages",
+            n,
+            sign_stats.messages
+        );
+        (dkg_stats, sign_stats)
+    }
+
+    #[test]
+    fn small_fleet_dkg_and_sign() {
+        check_fleet(5, 20);
+    }
+
+    #[test]
+    fn uneven_fleet_dkg_and_sign() {
+        // one signer with 60% of the weight, and 40 with 1% each
+        let mut weights = vec![60];
+        weights.extend(vec![1; 40]);
+        let mut fleet = SignerFleet::new_weighted(&weights, 141);
+
+        let num_signer_keys: Vec<_> = fleet
+            .runloops
+            .iter()
+            .map(|runloop| {
+                wsts::traits::Signer::get_key_ids(&runloop.signing_round.signer).len() as u32
+            })
+            .collect();
+        assert_eq!(num_signer_keys[0], 61);
+        assert!(num_signer_keys[1..].iter().all(|num_keys| *num_keys == 2));
+        // the dominant signer cannot sign alone
+        assert!(num_signer_keys[0] < fleet.runloops[0].signing_round.threshold);
+
+        fleet.run_dkg();
+        fleet.run_sign(&[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn coordinator_rotation_is_shared() {
+        let fleet = SignerFleet::new(5, 20);
+        let public_keys = &fleet.runloops[0].signing_round.public_keys;
+        let timeout = Duration::from_secs(30);
+        let mut selector = CoordinatorSelector::new(public_keys, timeout);
+        let mut other = CoordinatorSelector::new(public_keys, timeout);
+        assert!(selector.reseed(public_keys, 1));
+        assert!(other.reseed(public_keys, 1));
+        assert_eq!(selector.rotation, other.rotation);
+        assert_eq!(selector.rotation.len(), 5);
+
+        // Every signer takes a turn, in the same order
+        let mut coordinators = vec![];
+        for _ in 0..5 {
+            coordinators.push(other.coordinator_id());
+            let next = other.next_coordinator_id();
+            other.elect_next();
+            assert_eq!(other.coordinator_id(), next);
+        }
+        assert_eq!(coordinators, selector.rotation);
+        assert_eq!(other.coordinator_id(), selector.coordinator_id());
+
+        // Reseeding with the same reward cycle keeps the elected coordinator
+        other.elect_next();
+        assert!(!other.reseed(public_keys, 1));
+        assert_eq!(other.coordinator_id(), selector.next_coordinator_id());
+
+        // A new reward cycle gives a new rotation, that starts over
+        assert!(other.reseed(public_keys, 2));
+        assert_eq!(other.reward_cycle(), Some(2));
+        let mut rotation = other.rotation.clone();
+        rotation.sort();
+        assert_eq!(rotation, vec![0, 1, 2, 3, 4]);
+        assert_eq!(other.coordinator_id(), other.rotation[0]);
+    }
+
+    #[test]
+    fn coordinator_selector_times_out() {
+        let fleet = SignerFleet::new(3, 6);
+        let timeout = Duration::from_secs(30);
+        let mut selector =
+            CoordinatorSelector::new(&fleet.runloops[0].signing_round.public_keys, timeout);
+        let start = Instant::now();
+        // Not waiting on anything
+        assert!(!selector.is_unresponsive(start + timeout));
+
+        selector.expect_packet(start);
+        // Waiting again does not push back the deadline
+        selector.expect_packet(start + timeout / 2);
+        assert!(!selector.is_unresponsive(start + timeout / 2));
+        assert!(selector.is_unresponsive(start + timeout));
+
+        selector.heard_from_coordinator();
+        assert!(!selector.is_unresponsive(start + timeout));
+    }
+
+    #[test]
+    fn unresponsive_coordinator_is_replaced() {
+        let mut fleet = SignerFleet::new(5, 20);
+        let old_coordinator_id = fleet.coordinator_id();
+        let new_coordinator_id = fleet.runloops[0].coordinator_selector.next_coordinator_id();
+        let timeout = fleet.runloops[0].coordinator_selector.timeout;
+
+        // The coordinator starts DKG, but never follows up on the signers' public shares
+        let packet = fleet
+            .coordinator()
+            .coordinator
+            .start_distributed_key_generation()
+            .unwrap();
+        let (responses, _) = fleet.deliver(vec![(old_coordinator_id, packet)]);
+        assert!(!responses.is_empty());
+
+        // Every signer but the old coordinator gives up on it
+        let later = Instant::now() + timeout;
+        for runloop in fleet.runloops.iter_mut() {
+            if runloop.signing_round.signer_id != old_coordinator_id {
+                runloop.check_coordinator_liveness(later);
+            }
+        }
+        for runloop in fleet.runloops.iter() {
+            let expected = if runloop.signing_round.signer_id == old_coordinator_id {
+                old_coordinator_id
+            } else {
+                new_coordinator_id
+            };
+            assert_eq!(runloop.coordinator_selector.coordinator_id(), expected);
+        }
+        assert_eq!(fleet.coordinator_id(), new_coordinator_id);
+        assert_eq!(
+            fleet.runloops[new_coordinator_id as usize].commands.front(),
+            Some(&RunLoopCommand::Dkg)
+        );
+
+        // The old coordinator follows the new one once it starts DKG, and DKG completes
+        fleet.run_dkg();
+        for runloop in fleet.runloops.iter() {
+            assert_eq!(
+                runloop.coordinator_selector.coordinator_id(),
+                new_coordinator_id
+            );
+        }
+    }
+
+    /// Measure DKG and signing round times versus fleet size.
+    /// Run with `cargo test --release -p stacks-signer -- --ignored large_fleet_scalability`
+    #[test]
+    #[ignore]
+    fn large_fleet_scalability() {
+        for num_signers in [10, 25, 50, 100, 128] {
+            let (dkg_stats, sign_stats) = check_fleet(num_signers, num_signers * 2);
+            info!(
+                "Fleet of {} signers: DKG {:?}, sign {:?}",
+                num_signers, dkg_stats, sign_stats
+            );
+        }
+    }
+
+    /// A fleet of signers that each talk to their own mock stacks node. Every chunk a signer
+    /// writes is delivered to the whole fleet as a stacker-db event, through `run_one_pass`.
+    struct MockFleet {
+        runloops: Vec<RunLoop<FrostCoordinator<v2::Aggregator>, MockStacksClient>>,
+        res_send: Sender<Vec<OperationResult>>,
+        res_recv: Receiver<Vec<OperationResult>>,
+    }
+
+    impl MockFleet {
+        fn new(num_signers: u32, num_keys: u32) -> Self {
+            let stacks_private_keys: Vec<_> =
+                (0..num_signers).map(|_| StacksPrivateKey::new()).collect();
+            let runloops = build_weighted_signer_config_tomls(
+                &stacks_private_keys,
+                &vec![1; num_signers as usize],
+                num_keys,
+                "127.0.0.1:20443",
+                "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM.signers-stackerdb",
+                None,
+                None,
+            )
+            .iter()
+            .map(|toml| {
+                let config = Config::load_from_str(toml).unwrap();
+                RunLoop::new(&config, MockStacksClient::from(&config))
+            })
+            .collect();
+            let (res_send, res_recv) = channel();
+            MockFleet {
+                runloops,
+                res_send,
+                res_recv,
+            }
+        }
+
+        /// The coordinator the fleet elects for the mock node's reward cycle
+        fn coordinator_id(&self) -> u32 {
+            let runloop = &self.runloops[0];
+            let public_keys = &runloop.signing_round.public_keys;
+            let mut selector = CoordinatorSelector::new(public_keys, Duration::from_secs(30));
+            selector.reseed(public_keys, runloop.stacks_client.reward_cycle);
+            selector.coordinator_id()
+        }
+
+        /// Tell the signer that the burnchain advanced to `burn_block_height`
+        fn advance_burnchain(&mut self, signer_id: u32, burn_block_height: u64) {
+            let runloop = &mut self.runloops[signer_id as usize];
+            let event = SignerEvent::BurnBlockAdvance {
+                burn_block_height,
+                burn_block_hash: BurnchainHeaderHash([0; 32]),
+            };
+            let final_results = runloop.run_one_pass(Some(event), None, self.res_send.clone());
+            assert!(final_results.is_none());
+        }
+
+        /// Some signer other than the coordinator
+        fn follower_id(&self) -> u32 {
+            (self.coordinator_id() + 1) % self.runloops.len() as u32
+        }
+
+        fn run_one_pass(&mut self, signer_id: u32, event: Option<StackerDBChunksEvent>) {
+            let runloop = &mut self.runloops[signer_id as usize];
+            let final_results = runloop.run_one_pass(
+                event.map(SignerEvent::StackerDBChunks),
+                None,
+                self.res_send.clone(),
+            );
+            assert!(final_results.is_none());
+        }
+
+        /// Run one pass of every signer, delivering `event` to each, and collect the operation
+        /// results they report
+        fn run_pass(&mut self, event: Option<StackerDBChunksEvent>) -> Vec<OperationResult> {
+            for signer_id in 0..self.runloops.len() as u32 {
+                self.run_one_pass(signer_id, event.clone());
+            }
+            self.res_recv.try_iter().flatten().collect()
+        }
+
+        /// Take the chunks every signer wrote since the last delivery, as a single event
+        fn take_written_event(&mut self) -> Option<StackerDBChunksEvent> {
+            let mut events = self
+                .runloops
+                .iter_mut()
+                .filter_map(|runloop| runloop.stacks_client.take_written_event());
+            let mut event = events.next()?;
+            for other in events {
+                event.modified_slots.extend(other.modified_slots);
+            }
+            Some(event)
+        }
+
+        /// Deliver every write to the whole fleet until an operation finishes
+        fn run_until_result(&mut self) -> Vec<OperationResult> {
+            for _ in 0..MAX_ROUND_PASSES {
+                let event = self
+                    .take_written_event()
+                    .expect("Round stalled with no messages in flight");
+                let results = self.run_pass(Some(event));
+                if !results.is_empty() {
+                    return results;
+                }
+            }
+            panic!("Round did not finish");
+        }
+    }
+
+    #[test]
+    fn mock_fleet_initializes_and_runs_dkg_and_sign() {
+        let mut fleet = MockFleet::new(5, 20);
+        let coordinator_id = fleet.coordinator_id() as usize;
+        fleet.runloops[coordinator_id].aggregate_key_voter =
+            Some(AggregateKeyVoter::new(Duration::from_secs(60), 1));
+
+        // Initializing reads the aggregate public key and the seed from the node, and the
+        // coordinator starts DKG right away since there is no key yet
+        assert!(fleet.run_pass(None).is_empty());
+        for runloop in fleet.runloops.iter() {
+            assert_eq!(
+                runloop.coordinator_selector.coordinator_id(),
+                coordinator_id as u32
+            );
+            let expected_state = if runloop.signing_round.signer_id == coordinator_id as u32 {
+                State::Dkg
+            } else {
+                State::Idle
+            };
+            assert_eq!(runloop.state, expected_state);
+        }
+
+        let results = fleet.run_until_result();
+        let [OperationResult::Dkg(key)] = results.as_slice() else {
+            panic!("Expected a DKG result, got {:?}", results);
+        };
+        // The coordinator voted for the new key
+        let coordinator = &fleet.runloops[coordinator_id];
+        assert_eq!(coordinator.state, State::Idle);
+        assert_eq!(
+            coordinator.coordinator.get_aggregate_public_key(),
+            Some(*key)
+        );
+        assert_eq!(coordinator.stacks_client.submitted_txs().len(), 1);
+
+        fleet.runloops[coordinator_id]
+            .commands
+            .push_back(RunLoopCommand::Sign {
+                message: vec![1, 2, 3, 4, 5],
+                is_taproot: false,
+                merkle_root: None,
+            });
+        assert!(fleet.run_pass(None).is_empty());
+        assert_eq!(fleet.runloops[coordinator_id].state, State::Sign);
+        let results = fleet.run_until_result();
+        assert!(matches!(results.as_slice(), [OperationResult::Sign(_)]));
+    }
+
+    #[test]
+    fn signers_started_in_different_blocks_agree_on_coordinator() {
+        let mut fleet = MockFleet::new(5, 20);
+        let coordinator_id = fleet.coordinator_id();
+        let (early_id, late_id) = (fleet.follower_id(), (coordinator_id + 2) % 5);
+        // There is already a key, so neither signer starts DKG if it becomes the coordinator
+        for signer_id in [early_id, late_id] {
+            fleet.runloops[signer_id as usize]
+                .coordinator
+                .set_aggregate_public_key(Some(Point::new()));
+        }
+
+        // One signer starts during reward cycle 1, and sees the burnchain advance into cycle 2
+        fleet.run_one_pass(early_id, None);
+        assert_eq!(
+            fleet.runloops[early_id as usize]
+                .coordinator_selector
+                .reward_cycle(),
+            Some(1)
+        );
+        for runloop in fleet.runloops.iter_mut() {
+            runloop.stacks_client.reward_cycle = 2;
+        }
+        fleet.advance_burnchain(early_id, 150);
+
+        // Another starts later in reward cycle 2
+        fleet.advance_burnchain(late_id, 160);
+        let early = &fleet.runloops[early_id as usize].coordinator_selector;
+        let late = &fleet.runloops[late_id as usize].coordinator_selector;
+        assert_eq!(early.reward_cycle(), Some(2));
+        assert_eq!(late.reward_cycle(), Some(2));
+        assert_eq!(early.coordinator_id(), late.coordinator_id());
+        assert_eq!(early.coordinator_id(), fleet.coordinator_id());
+
+        // Later burn blocks in the same cycle keep the rotation
+        fleet.runloops[early_id as usize]
+            .coordinator_selector
+            .elect_next();
+        let elected_id = fleet.runloops[early_id as usize]
+            .coordinator_selector
+            .coordinator_id();
+        fleet.advance_burnchain(early_id, 161);
+        assert_eq!(
+            fleet.runloops[early_id as usize]
+                .coordinator_selector
+                .coordinator_id(),
+            elected_id
+        );
+    }
+
+    #[test]
+    fn initialization_retries_node_timeouts() {
+        let mut fleet = MockFleet::new(5, 20);
+        let follower_id = fleet.follower_id();
+        let stacks_client = &fleet.runloops[follower_id as usize].stacks_client;
+        stacks_client.fail_next(MockFailure::Timeout);
+        stacks_client.fail_next(MockFailure::Timeout);
+
+        fleet.run_one_pass(follower_id, None);
+        let runloop = &fleet.runloops[follower_id as usize];
+        assert_eq!(runloop.state, State::Idle);
+        assert!(!runloop.resync_pending);
+    }
+
+    #[test]
+    fn stale_slot_version_is_superseded() {
+        let mut fleet = MockFleet::new(5, 20);
+        let coordinator_id = fleet.coordinator_id();
+        // The stacker-db instance already holds a newer DKG begin message from an earlier run
+        let dkg_begin_slot_id = coordinator_id * SLOTS_PER_USER;
+        let stacks_client = &mut fleet.runloops[coordinator_id as usize].stacks_client;
+        stacks_client.overwrite_slot(StackerDBChunkData::new(dkg_begin_slot_id, 3, vec![3]));
+
+        fleet.run_pass(None);
+        let stacks_client = &fleet.runloops[coordinator_id as usize].stacks_client;
+        assert_eq!(stacks_client.stale_writes, 3);
+        assert_eq!(stacks_client.written.len(), 1);
+        assert_eq!(stacks_client.written[0].slot_id, dkg_begin_slot_id);
+        assert_eq!(stacks_client.written[0].slot_version, 4);
+
+        // The fleet still completes DKG
+        let results = fleet.run_until_result();
+        assert!(matches!(results.as_slice(), [OperationResult::Dkg(_)]));
+    }
+
+    #[test]
+    fn lost_connection_replays_missed_chunks() {
+        let mut fleet = MockFleet::new(5, 20);
+        let coordinator_id = fleet.coordinator_id();
+        let follower_id = fleet.follower_id();
+        fleet.run_one_pass(coordinator_id, None);
+        let dkg_begin = fleet.take_written_event().unwrap();
+
+        // The follower's node stored the DKG begin message, but restarted before delivering it
+        let stacks_client = &mut fleet.runloops[follower_id as usize].stacks_client;
+        for chunk in dkg_begin.modified_slots {
+            stacks_client.overwrite_slot(chunk);
+        }
+        stacks_client.fail_next(MockFailure::ConnectionLost);
+
+        fleet.run_one_pass(follower_id, None);
+        let runloop = &fleet.runloops[follower_id as usize];
+        assert_eq!(runloop.state, State::Idle);
+        assert_eq!(
+            runloop
+                .event_gap_detector
+                .last_version(coordinator_id * SLOTS_PER_USER),
+            Some(1)
+        );
+        // The follower answered the replayed message with its public shares
+        let slot_ids: Vec<_> = runloop
+            .stacks_client
+            .written
+            .iter()
+            .map(|chunk| chunk.slot_id)
+            .collect();
+        assert_eq!(slot_ids, vec![follower_id * SLOTS_PER_USER + 4]);
+    }
+
+    #[test]
+    fn invalid_packets_are_rejected() {
+        let mut fleet = MockFleet::new(5, 20);
+        let coordinator_id = fleet.coordinator_id();
+        let follower_id = fleet.follower_id();
+        fleet.run_pass(None);
+        let dkg_begin = fleet.take_written_event().unwrap();
+        let chunk = dkg_begin.modified_slots[0].clone();
+
+        // A packet that is not signed by the coordinator, and a chunk that is not a packet
+        let mut packet: Packet = bincode::deserialize(&chunk.data).unwrap();
+        packet.sig = vec![0; 64];
+        let forged = StackerDBChunkData::new(
+            chunk.slot_id,
+            chunk.slot_version,
+            bincode::serialize(&packet).unwrap(),
+        );
+        let garbage = StackerDBChunkData::new(coordinator_id * SLOTS_PER_USER + 1, 1, vec![1]);
+        let event = StackerDBChunksEvent {
+            contract_id: dkg_begin.contract_id.clone(),
+            modified_slots: vec![forged, garbage],
+        };
+        fleet.run_one_pass(follower_id, Some(event));
+        let runloop = &fleet.runloops[follower_id as usize];
+        assert!(runloop.stacks_client.written.is_empty());
+        assert_eq!(
+            runloop.coordinator_selector.coordinator_id(),
+            coordinator_id
+        );
+
+        // The genuine packet is still accepted
+        fleet.run_one_pass(follower_id, Some(dkg_begin));
+        assert_eq!(
+            fleet.runloops[follower_id as usize]
+                .stacks_client
+                .written
+                .len(),
+            1
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use bincode::Error as BincodeError;
@@ -12,11 +14,16 @@ use clarity::vm::types::{QualifiedContractIdentifier, SequenceData};
 use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
 use hashbrown::HashMap;
 use libsigner::{RPCError, SignerSession, StackerDBSession};
-use libstackerdb::{Error as StackerDBError, StackerDBChunkAckData, StackerDBChunkData};
+use libstackerdb::{
+    Error as StackerDBError, SlotMetadata, StackerDBChunkAckData, StackerDBChunkData,
+};
 use serde_json::json;
 use slog::{slog_debug, slog_warn};
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey, StacksPublicKey};
+use stacks_common::types::Address;
+use stacks_common::util::hash::to_hex;
+use stacks_common::util::log::update_log_context;
 use stacks_common::{debug, warn};
 use wsts::net::{Message, Packet};
 use wsts::{Point, Scalar};
@@ -33,6 +40,12 @@ const BACKOFF_MAX_INTERVAL: u64 = 16384;
 /// Is equal to the number of message types
 pub const SLOTS_PER_USER: u32 = 10;
 
+/// The fee paid by a signer transaction if the stacks node cannot estimate one
+// FIXME: Because signers are given priority, we can put down a tx fee of 0
+// https://github.com/stacks-network/stacks-blockchain/issues/4006
+// Note: if set to 0 now, will cause a failure (MemPoolRejection::FeeTooLow)
+pub const DEFAULT_TX_FEE: u64 = 10_000;
+
 #[derive(thiserror::Error, Debug)]
 /// Client error type
 pub enum ClientError {
@@ -77,6 +90,85 @@ pub enum ClientError {
     RetryTimeout,
 }
 
+/// Where the burnchain is relative to the next reward cycle, as reported by the stacks node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NextRewardCycleInfo {
+    /// The current burnchain block height
+    pub burn_block_height: u64,
+    /// The next reward cycle
+    pub reward_cycle: u64,
+    /// The burnchain block height at which the next reward cycle's prepare phase starts
+    pub prepare_phase_start_block_height: u64,
+}
+
+/// The stacker-db operations the signer runloop depends on.
+/// Implemented by [`StacksClient`], and by a scripted mock in unit tests.
+pub trait StackerDB {
+    /// Write a message to the signer's slot for its type, retrying until the stacker-db instance
+    /// accepts a version of it
+    fn send_message_with_retry(
+        &mut self,
+        id: u32,
+        message: Packet,
+    ) -> Result<StackerDBChunkAckData, ClientError>;
+    /// The contract that controls the stacker-db instance
+    fn stackerdb_contract_id(&self) -> &QualifiedContractIdentifier;
+    /// List the metadata of every slot in the stacker-db instance
+    fn list_chunks(&mut self) -> Result<Vec<SlotMetadata>, ClientError>;
+    /// Retrieve the given version of a stacker-db chunk, if the stacker-db instance still has it
+    fn get_chunk(
+        &mut self,
+        slot_id: u32,
+        slot_version: u32,
+    ) -> Result<Option<Vec<u8>>, ClientError>;
+    /// Adopt the stacker-db instance's versions of the given signer's slots, so the next chunk
+    /// written to each slot supersedes what the instance holds
+    fn sync_slot_versions(&mut self, id: u32, slots: &[SlotMetadata]);
+    /// Flush and close the connection to the stacker-db instance
+    fn flush(&mut self);
+}
+
+/// The stacks node queries and transactions the signer runloop depends on.
+/// Implemented by [`StacksClient`], and by a scripted mock in unit tests.
+pub trait StacksNode {
+    /// Retrieve the current DKG aggregate public key
+    fn get_aggregate_public_key(&self) -> Result<Option<Point>, ClientError>;
+    /// Retrieve the current reward cycle number
+    fn get_current_reward_cycle(&self) -> Result<u64, ClientError>;
+    /// Retrieve the next reward cycle and when its prepare phase starts
+    fn get_next_reward_cycle_info(&self) -> Result<NextRewardCycleInfo, ClientError>;
+    /// Retrieve the next possible nonce for the signer
+    fn get_next_possible_nonce(&self) -> Result<u64, ClientError>;
+    /// Estimate the fee for a transaction with the given payload
+    fn estimate_tx_fee(
+        &self,
+        payload: &TransactionPayload,
+        estimated_len: u64,
+    ) -> Result<u64, ClientError>;
+    /// Retrieve the result of a transaction that was mined in a block on the canonical fork.
+    /// Returns `None` if the stacks node has not processed the transaction.
+    fn get_transaction_result(&self, txid: &Txid) -> Result<Option<ClarityValue>, ClientError>;
+    /// Retrieve the pox contract address and name
+    fn get_pox_contract(&self) -> Result<(StacksAddress, ContractName), ClientError>;
+    /// Build and sign a contract call transaction from the signer
+    fn build_signed_transaction(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: ContractName,
+        function_name: ClarityName,
+        function_args: &[ClarityValue],
+        nonce: u64,
+        tx_fee: u64,
+    ) -> Result<StacksTransaction, ClientError>;
+    /// Submit a transaction to the stacks node
+    fn submit_tx(&self, tx: &StacksTransaction) -> Result<Txid, ClientError>;
+    /// Whether a request failed to reach the stacks node since the last call, as happens when
+    /// the node restarts
+    fn take_connection_lost(&self) -> bool;
+    /// Talk to the stacks node at the given address from now on
+    fn set_node_host(&mut self, node_host: SocketAddr);
+}
+
 /// The Stacks signer client used to communicate with the stacker-db instance
 pub struct StacksClient {
     /// The stacker-db session
@@ -97,6 +189,8 @@ pub struct StacksClient {
     stacks_node_client: reqwest::blocking::Client,
     /// The pox contract ID
     pox_contract_id: Option<QualifiedContractIdentifier>,
+    /// Whether a request failed to reach the stacks node since this was last checked
+    connection_lost: AtomicBool,
 }
 
 impl From<&Config> for StacksClient {
@@ -114,67 +208,16 @@ impl From<&Config> for StacksClient {
             chain_id: config.network.to_chain_id(),
             stacks_node_client: reqwest::blocking::Client::new(),
             pox_contract_id: config.pox_contract_id.clone(),
+            connection_lost: AtomicBool::new(false),
         }
     }
 }
 
 impl StacksClient {
-    /// Sends messages to the stacker-db with an exponential backoff retry
-    pub fn send_message_with_retry(
-        &mut self,
-        id: u32,
-        message: Packet,
-    ) -> Result<StackerDBChunkAckData, ClientError> {
-        let message_bytes = bincode::serialize(&message)?;
-        let slot_id = slot_id(id, &message.msg);
-
-        loop {
-            let slot_version = *self.slot_versions.entry(slot_id).or_insert(0) + 1;
-            let mut chunk = StackerDBChunkData::new(slot_id, slot_version, message_bytes.clone());
-            chunk.sign(&self.stacks_private_key)?;
-            debug!("Sending a chunk to stackerdb!\n{:?}", chunk.clone());
-            let send_request = || {
-                self.stackerdb_session
-                    .put_chunk(chunk.clone())
-                    .map_err(backoff::Error::transient)
-            };
-            let chunk_ack: StackerDBChunkAckData = retry_with_exponential_backoff(send_request)?;
-            self.slot_versions.insert(slot_id, slot_version);
-
-            if chunk_ack.accepted {
-                debug!("Chunk accepted by stackerdb: {:?}", chunk_ack);
-                return Ok(chunk_ack);
-            } else {
-                warn!("Chunk rejected by stackerdb: {:?}", chunk_ack);
-            }
-            if let Some(reason) = chunk_ack.reason {
-                // TODO: fix this jankiness. Update stackerdb to use an error code mapping instead of just a string
-                // See: https://github.com/stacks-network/stacks-blockchain/issues/3917
-                if reason == "Data for this slot and version already exist" {
-                    warn!("Failed to send message to stackerdb due to wrong version number {}. Incrementing and retrying...", slot_version);
-                } else {
-                    warn!("Failed to send message to stackerdb: {}", reason);
-                    return Err(ClientError::PutChunkRejected(reason));
-                }
-            }
-        }
-    }
-
-    /// Retrieve the current DKG aggregate public key
-    pub fn get_aggregate_public_key(&self) -> Result<Option<Point>, ClientError> {
-        let reward_cycle = self.get_current_reward_cycle()?;
-        let function_name_str = "get-aggregate-public-key"; // FIXME: this may need to be modified to match .pox-4
-        let function_name = ClarityName::try_from(function_name_str)
-            .map_err(|_| ClientError::InvalidClarityName(function_name_str.to_string()))?;
-        let (contract_addr, contract_name) = self.get_pox_contract()?;
-        let function_args = &[ClarityValue::UInt(reward_cycle as u128)];
-        let contract_response_hex = self.read_only_contract_call_with_retry(
-            &contract_addr,
-            &contract_name,
-            &function_name,
-            function_args,
-        )?;
-        self.parse_aggregate_public_key(&contract_response_hex)
+    /// Record that a request failed to reach the stacks node, and retry it
+    fn transport_error<E>(&self, e: E) -> backoff::Error<E> {
+        self.connection_lost.store(true, Ordering::SeqCst);
+        backoff::Error::transient(e)
     }
 
     /// Retrieve the total number of slots allocated to a stacker-db writer
@@ -185,60 +228,75 @@ impl StacksClient {
         SLOTS_PER_USER
     }
 
-    /// Helper function to retrieve the current reward cycle number from the stacks node
-    fn get_current_reward_cycle(&self) -> Result<u64, ClientError> {
+    /// Retrieve the signer's STX balance from the stacks node, in microstacks
+    pub fn get_account_balance(&self) -> Result<u128, ClientError> {
         let send_request = || {
             self.stacks_node_client
-                .get(self.pox_path())
+                .get(self.accounts_path())
                 .send()
-                .map_err(backoff::Error::transient)
+                .map_err(|e| self.transport_error(e))
         };
         let response = retry_with_exponential_backoff(send_request)?;
         if !response.status().is_success() {
             return Err(ClientError::RequestFailure(response.status()));
         }
         let json_response = response.json::<serde_json::Value>()?;
-        let entry = "current_cycle";
+        let entry = "balance";
         json_response
             .get(entry)
-            .and_then(|cycle: &serde_json::Value| cycle.get("id"))
-            .and_then(|id| id.as_u64())
-            .ok_or_else(|| ClientError::InvalidJsonEntry(format!("{}.id", entry)))
+            .and_then(|balance| balance.as_str())
+            .and_then(|balance| {
+                u128::from_str_radix(balance.strip_prefix("0x").unwrap_or(balance), 16).ok()
+            })
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))
     }
 
-    /// Helper function to retrieve the next possible nonce for the signer from the stacks node
-    #[allow(dead_code)]
-    fn get_next_possible_nonce(&self) -> Result<u64, ClientError> {
-        //FIXME: use updated RPC call to get mempool nonces. Depends on https://github.com/stacks-network/stacks-blockchain/issues/4000
-        todo!("Get the next possible nonce from the stacks node");
-    }
-
-    /// Helper function to retrieve the pox contract address and name from the stacks node
-    fn get_pox_contract(&self) -> Result<(StacksAddress, ContractName), ClientError> {
-        // Check if we have overwritten the pox contract ID in the config
-        if let Some(pox_contract) = self.pox_contract_id.clone() {
-            return Ok((pox_contract.issuer.into(), pox_contract.name));
-        }
-        // TODO: we may want to cache the pox contract inside the client itself (calling this function once on init)
-        // https://github.com/stacks-network/stacks-blockchain/issues/4005
+    /// Retrieve the address that may write to each slot of the stacker-db instance
+    pub fn get_slot_signers(&self) -> Result<Vec<(u32, StacksAddress)>, ClientError> {
         let send_request = || {
             self.stacks_node_client
-                .get(self.pox_path())
+                .get(self.stackerdb_metadata_path())
                 .send()
-                .map_err(backoff::Error::transient)
+                .map_err(|e| self.transport_error(e))
         };
         let response = retry_with_exponential_backoff(send_request)?;
         if !response.status().is_success() {
             return Err(ClientError::RequestFailure(response.status()));
         }
         let json_response = response.json::<serde_json::Value>()?;
-        let entry = "contract_id";
-        let contract_id_string = json_response
-            .get(entry)
-            .and_then(|id: &serde_json::Value| id.as_str())
-            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))?;
-        let id = QualifiedContractIdentifier::parse(contract_id_string).unwrap();
-        Ok((id.issuer.into(), id.name))
+        let slots = json_response
+            .as_array()
+            .ok_or_else(|| ClientError::InvalidJsonEntry("slots".to_string()))?;
+        slots
+            .iter()
+            .map(|slot| {
+                let slot_id = slot
+                    .get("slot_id")
+                    .and_then(|slot_id| slot_id.as_u64())
+                    .and_then(|slot_id| u32::try_from(slot_id).ok())
+                    .ok_or_else(|| ClientError::InvalidJsonEntry("slot_id".to_string()))?;
+                let signer = slot
+                    .get("signer")
+                    .and_then(|signer| signer.as_str())
+                    .and_then(StacksAddress::from_string)
+                    .ok_or_else(|| ClientError::InvalidJsonEntry("signer".to_string()))?;
+                Ok((slot_id, signer))
+            })
+            .collect()
+    }
+
+    /// Make a single request to the stacks node, without retrying, to see if it is reachable
+    pub fn ping(&self) -> Result<(), ClientError> {
+        let response = self.stacks_node_client.get(self.info_path()).send()?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        Ok(())
+    }
+
+    /// The stacks address of the signer
+    pub fn stacks_address(&self) -> &StacksAddress {
+        &self.stacks_address
     }
 
     /// Helper function that attempts to deserialize a clarity hex string as the aggregate public key
@@ -277,77 +335,12 @@ impl StacksClient {
             contract_name,
             function_name,
             function_args,
+            self.get_next_possible_nonce()?,
+            DEFAULT_TX_FEE,
         )?;
         self.submit_tx(&signed_tx)
     }
 
-    /// Helper function to create a stacks transaction for a modifying contract call
-    fn build_signed_transaction(
-        &self,
-        contract_addr: &StacksAddress,
-        contract_name: ContractName,
-        function_name: ClarityName,
-        function_args: &[ClarityValue],
-    ) -> Result<StacksTransaction, ClientError> {
-        let tx_payload = TransactionPayload::ContractCall(TransactionContractCall {
-            address: *contract_addr,
-            contract_name,
-            function_name,
-            function_args: function_args.to_vec(),
-        });
-        let public_key = StacksPublicKey::from_private(&self.stacks_private_key);
-        let tx_auth = TransactionAuth::Standard(
-            TransactionSpendingCondition::new_singlesig_p2pkh(public_key).ok_or(
-                ClientError::TransactionGenerationFailure(format!(
-                    "Failed to create spending condition from public key: {}",
-                    public_key.to_hex()
-                )),
-            )?,
-        );
-
-        let mut unsigned_tx = StacksTransaction::new(self.tx_version, tx_auth, tx_payload);
-
-        // FIXME: Because signers are given priority, we can put down a tx fee of 0
-        // https://github.com/stacks-network/stacks-blockchain/issues/4006
-        // Note: if set to 0 now, will cause a failure (MemPoolRejection::FeeTooLow)
-        unsigned_tx.set_tx_fee(10_000);
-        unsigned_tx.set_origin_nonce(self.get_next_possible_nonce()?);
-
-        unsigned_tx.anchor_mode = TransactionAnchorMode::Any;
-        unsigned_tx.post_condition_mode = TransactionPostConditionMode::Allow;
-        unsigned_tx.chain_id = self.chain_id;
-
-        let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
-        tx_signer
-            .sign_origin(&self.stacks_private_key)
-            .map_err(|e| ClientError::TransactionGenerationFailure(e.to_string()))?;
-
-        tx_signer
-            .get_tx()
-            .ok_or(ClientError::TransactionGenerationFailure(
-                "Failed to generate transaction from a transaction signer".to_string(),
-            ))
-    }
-
-    /// Helper function to submit a transaction to the Stacks node
-    fn submit_tx(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
-        let txid = tx.txid();
-        let tx = tx.serialize_to_vec();
-        let send_request = || {
-            self.stacks_node_client
-                .post(self.transaction_path())
-                .header("Content-Type", "application/octet-stream")
-                .body(tx.clone())
-                .send()
-                .map_err(backoff::Error::transient)
-        };
-        let response = retry_with_exponential_backoff(send_request)?;
-        if !response.status().is_success() {
-            return Err(ClientError::RequestFailure(response.status()));
-        }
-        Ok(txid)
-    }
-
     /// Makes a read only contract call to a stacks contract
     pub fn read_only_contract_call_with_retry(
         &self,
@@ -376,7 +369,7 @@ impl StacksClient {
                 .header("Content-Type", "application/json")
                 .body(body.clone())
                 .send()
-                .map_err(backoff::Error::transient)
+                .map_err(|e| self.transport_error(e))
         };
         let response = retry_with_exponential_backoff(send_request)?;
         if !response.status().is_success() {
@@ -409,6 +402,33 @@ impl StacksClient {
         format!("{}/v2/pox", self.http_origin)
     }
 
+    fn accounts_path(&self) -> String {
+        format!(
+            "{}/v2/accounts/{}?proof=0",
+            self.http_origin, self.stacks_address
+        )
+    }
+
+    fn stackerdb_metadata_path(&self) -> String {
+        let contract_id = self.stackerdb_contract_id();
+        format!(
+            "{}/v2/stackerdb/{}/{}/metadata",
+            self.http_origin, contract_id.issuer, contract_id.name
+        )
+    }
+
+    fn fees_path(&self) -> String {
+        format!("{}/v2/fees/transaction", self.http_origin)
+    }
+
+    fn transaction_result_path(&self, txid: &Txid) -> String {
+        format!("{}/v2/transactions/{}", self.http_origin, txid)
+    }
+
+    fn info_path(&self) -> String {
+        format!("{}/v2/info", self.http_origin)
+    }
+
     fn transaction_path(&self) -> String {
         format!("{}/v2/transactions", self.http_origin)
     }
@@ -426,6 +446,409 @@ impl StacksClient {
     }
 }
 
+impl StackerDB for StacksClient {
+    /// Sends messages to the stacker-db with an exponential backoff retry
+    fn send_message_with_retry(
+        &mut self,
+        id: u32,
+        message: Packet,
+    ) -> Result<StackerDBChunkAckData, ClientError> {
+        let message_bytes = bincode::serialize(&message)?;
+        let slot_id = slot_id(id, &message.msg);
+
+        loop {
+            let slot_version = *self.slot_versions.entry(slot_id).or_insert(0) + 1;
+            let mut chunk = StackerDBChunkData::new(slot_id, slot_version, message_bytes.clone());
+            chunk.sign(&self.stacks_private_key)?;
+            debug!("Sending a chunk to stackerdb!\n{:?}", chunk.clone());
+            let connection_lost = &self.connection_lost;
+            let send_request = || {
+                self.stackerdb_session
+                    .put_chunk(chunk.clone())
+                    .map_err(|e| stackerdb_error(connection_lost, e))
+            };
+            let chunk_ack: StackerDBChunkAckData = retry_with_exponential_backoff(send_request)?;
+            self.slot_versions.insert(slot_id, slot_version);
+
+            if chunk_ack.accepted {
+                debug!("Chunk accepted by stackerdb: {:?}", chunk_ack);
+                return Ok(chunk_ack);
+            } else {
+                warn!("Chunk rejected by stackerdb: {:?}", chunk_ack);
+            }
+            if let Some(reason) = chunk_ack.reason {
+                // TODO: fix this jankiness. Update stackerdb to use an error code mapping instead of just a string
+                // See: https://github.com/stacks-network/stacks-blockchain/issues/3917
+                if reason == "Data for this slot and version already exist" {
+                    warn!("Failed to send message to stackerdb due to wrong version number {}. Incrementing and retrying...", slot_version);
+                } else {
+                    warn!("Failed to send message to stackerdb: {}", reason);
+                    return Err(ClientError::PutChunkRejected(reason));
+                }
+            }
+        }
+    }
+
+    /// The contract that controls the stacker-db instance
+    fn stackerdb_contract_id(&self) -> &QualifiedContractIdentifier {
+        &self.stackerdb_session.stackerdb_contract_id
+    }
+
+    /// List the metadata of every slot in the stacker-db instance
+    fn list_chunks(&mut self) -> Result<Vec<SlotMetadata>, ClientError> {
+        let connection_lost = &self.connection_lost;
+        let send_request = || {
+            self.stackerdb_session
+                .list_chunks()
+                .map_err(|e| stackerdb_error(connection_lost, e))
+        };
+        retry_with_exponential_backoff(send_request)
+    }
+
+    /// Retrieve the given version of a stacker-db chunk, if the stacker-db instance still has it
+    fn get_chunk(
+        &mut self,
+        slot_id: u32,
+        slot_version: u32,
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        let connection_lost = &self.connection_lost;
+        let send_request = || {
+            self.stackerdb_session
+                .get_chunk(slot_id, slot_version)
+                .map_err(|e| stackerdb_error(connection_lost, e))
+        };
+        retry_with_exponential_backoff(send_request)
+    }
+
+    /// Adopt the stacker-db instance's versions of the given signer's slots, so the next chunk
+    /// written to each slot supersedes what the instance holds
+    fn sync_slot_versions(&mut self, id: u32, slots: &[SlotMetadata]) {
+        for slot in slots
+            .iter()
+            .filter(|slot| slot.slot_id / SLOTS_PER_USER == id)
+        {
+            self.slot_versions.insert(slot.slot_id, slot.slot_version);
+        }
+    }
+
+    /// Flush and close the connection to the stacker-db instance
+    fn flush(&mut self) {
+        if let Err(e) = self.stackerdb_session.disconnect() {
+            warn!("Failed to cleanly close stacker-db session: {:?}", e);
+        }
+    }
+}
+
+impl StacksNode for StacksClient {
+    /// Retrieve the current DKG aggregate public key
+    fn get_aggregate_public_key(&self) -> Result<Option<Point>, ClientError> {
+        let reward_cycle = self.get_current_reward_cycle()?;
+        let function_name_str = "get-aggregate-public-key"; // FIXME: this may need to be modified to match .pox-4
+        let function_name = ClarityName::try_from(function_name_str)
+            .map_err(|_| ClientError::InvalidClarityName(function_name_str.to_string()))?;
+        let (contract_addr, contract_name) = self.get_pox_contract()?;
+        let function_args = &[ClarityValue::UInt(reward_cycle as u128)];
+        let contract_response_hex = self.read_only_contract_call_with_retry(
+            &contract_addr,
+            &contract_name,
+            &function_name,
+            function_args,
+        )?;
+        self.parse_aggregate_public_key(&contract_response_hex)
+    }
+
+    /// Retrieve the current reward cycle number from the stacks node
+    fn get_current_reward_cycle(&self) -> Result<u64, ClientError> {
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.pox_path())
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "current_cycle";
+        let reward_cycle = json_response
+            .get(entry)
+            .and_then(|cycle: &serde_json::Value| cycle.get("id"))
+            .and_then(|id| id.as_u64())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(format!("{}.id", entry)))?;
+        let burn_height = json_response
+            .get("current_burnchain_block_height")
+            .and_then(|height| height.as_u64());
+        update_log_context(|ctx| {
+            ctx.reward_cycle = Some(reward_cycle);
+            if burn_height.is_some() {
+                ctx.burn_height = burn_height;
+            }
+        });
+        Ok(reward_cycle)
+    }
+
+    /// Retrieve the next reward cycle and when its prepare phase starts from the stacks node
+    fn get_next_reward_cycle_info(&self) -> Result<NextRewardCycleInfo, ClientError> {
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.pox_path())
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "current_burnchain_block_height";
+        let burn_block_height = json_response
+            .get(entry)
+            .and_then(|height| height.as_u64())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))?;
+        let entry = "next_cycle";
+        let next_cycle = json_response
+            .get(entry)
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))?;
+        let reward_cycle = next_cycle
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(format!("{}.id", entry)))?;
+        let prepare_phase_start_block_height = next_cycle
+            .get("prepare_phase_start_block_height")
+            .and_then(|height| height.as_u64())
+            .ok_or_else(|| {
+                ClientError::InvalidJsonEntry(format!("{}.prepare_phase_start_block_height", entry))
+            })?;
+        Ok(NextRewardCycleInfo {
+            burn_block_height,
+            reward_cycle,
+            prepare_phase_start_block_height,
+        })
+    }
+
+    /// Retrieve the next possible nonce for the signer from the stacks node.
+    /// This is the nonce of the signer's account at the chain tip, so it does not account for
+    /// the signer's transactions that are still in the mempool.
+    fn get_next_possible_nonce(&self) -> Result<u64, ClientError> {
+        //FIXME: use updated RPC call to get mempool nonces. Depends on https://github.com/stacks-network/stacks-blockchain/issues/4000
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.accounts_path())
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "nonce";
+        json_response
+            .get(entry)
+            .and_then(|nonce| nonce.as_u64())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))
+    }
+
+    /// Estimate the fee for a transaction with the given payload from the stacks node.
+    /// Returns the middle of the node's low, middle, and high estimates.
+    fn estimate_tx_fee(
+        &self,
+        payload: &TransactionPayload,
+        estimated_len: u64,
+    ) -> Result<u64, ClientError> {
+        let body = json!({
+            "transaction_payload": to_hex(&payload.serialize_to_vec()),
+            "estimated_len": estimated_len,
+        });
+        let send_request = || {
+            self.stacks_node_client
+                .post(self.fees_path())
+                .json(&body)
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "estimations";
+        json_response
+            .get(entry)
+            .and_then(|estimations| estimations.get(1))
+            .and_then(|estimation| estimation.get("fee"))
+            .and_then(|fee| fee.as_u64())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))
+    }
+
+    /// Retrieve the result of a transaction that was mined in a block on the canonical fork.
+    /// Returns `None` if the stacks node has not processed the transaction.
+    fn get_transaction_result(&self, txid: &Txid) -> Result<Option<ClarityValue>, ClientError> {
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.transaction_result_path(txid))
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "result";
+        let result_hex = json_response
+            .get(entry)
+            .and_then(|result| result.as_str())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))?;
+        Ok(Some(ClarityValue::try_deserialize_hex_untyped(result_hex)?))
+    }
+
+    /// Helper function to retrieve the pox contract address and name from the stacks node
+    fn get_pox_contract(&self) -> Result<(StacksAddress, ContractName), ClientError> {
+        // Check if we have overwritten the pox contract ID in the config
+        if let Some(pox_contract) = self.pox_contract_id.clone() {
+            return Ok((pox_contract.issuer.into(), pox_contract.name));
+        }
+        // TODO: we may want to cache the pox contract inside the client itself (calling this function once on init)
+        // https://github.com/stacks-network/stacks-blockchain/issues/4005
+        let send_request = || {
+            self.stacks_node_client
+                .get(self.pox_path())
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        let json_response = response.json::<serde_json::Value>()?;
+        let entry = "contract_id";
+        let contract_id_string = json_response
+            .get(entry)
+            .and_then(|id: &serde_json::Value| id.as_str())
+            .ok_or_else(|| ClientError::InvalidJsonEntry(entry.to_string()))?;
+        let id = QualifiedContractIdentifier::parse(contract_id_string).unwrap();
+        Ok((id.issuer.into(), id.name))
+    }
+
+    /// Helper function to create a stacks transaction for a modifying contract call
+    fn build_signed_transaction(
+        &self,
+        contract_addr: &StacksAddress,
+        contract_name: ContractName,
+        function_name: ClarityName,
+        function_args: &[ClarityValue],
+        nonce: u64,
+        tx_fee: u64,
+    ) -> Result<StacksTransaction, ClientError> {
+        let tx_payload =
+            contract_call_payload(contract_addr, contract_name, function_name, function_args);
+        sign_transaction(
+            &self.stacks_private_key,
+            self.tx_version,
+            self.chain_id,
+            tx_payload,
+            nonce,
+            tx_fee,
+        )
+    }
+
+    /// Helper function to submit a transaction to the Stacks node
+    fn submit_tx(&self, tx: &StacksTransaction) -> Result<Txid, ClientError> {
+        let txid = tx.txid();
+        let tx = tx.serialize_to_vec();
+        let send_request = || {
+            self.stacks_node_client
+                .post(self.transaction_path())
+                .header("Content-Type", "application/octet-stream")
+                .body(tx.clone())
+                .send()
+                .map_err(|e| self.transport_error(e))
+        };
+        let response = retry_with_exponential_backoff(send_request)?;
+        if !response.status().is_success() {
+            return Err(ClientError::RequestFailure(response.status()));
+        }
+        Ok(txid)
+    }
+
+    /// Whether a request failed to reach the stacks node since the last call, as happens when
+    /// the node restarts
+    fn take_connection_lost(&self) -> bool {
+        self.connection_lost.swap(false, Ordering::SeqCst)
+    }
+
+    /// Talk to the stacks node at the given address from now on.
+    /// Any open stacker-db connection to the previous node is closed.
+    fn set_node_host(&mut self, node_host: SocketAddr) {
+        if let Err(e) = self.stackerdb_session.disconnect() {
+            debug!("Failed to disconnect from {}: {:?}", self.http_origin, e);
+        }
+        let contract_id = self.stackerdb_session.stackerdb_contract_id.clone();
+        self.stackerdb_session = StackerDBSession::new(node_host, contract_id);
+        self.http_origin = format!("http://{}", node_host);
+    }
+}
+
+/// Build the payload of a contract call transaction
+pub fn contract_call_payload(
+    contract_addr: &StacksAddress,
+    contract_name: ContractName,
+    function_name: ClarityName,
+    function_args: &[ClarityValue],
+) -> TransactionPayload {
+    TransactionPayload::ContractCall(TransactionContractCall {
+        address: *contract_addr,
+        contract_name,
+        function_name,
+        function_args: function_args.to_vec(),
+    })
+}
+
+/// Build and sign a transaction with the given payload, nonce, and fee
+pub fn sign_transaction(
+    stacks_private_key: &StacksPrivateKey,
+    tx_version: TransactionVersion,
+    chain_id: u32,
+    tx_payload: TransactionPayload,
+    nonce: u64,
+    tx_fee: u64,
+) -> Result<StacksTransaction, ClientError> {
+    let public_key = StacksPublicKey::from_private(stacks_private_key);
+    let tx_auth = TransactionAuth::Standard(
+        TransactionSpendingCondition::new_singlesig_p2pkh(public_key).ok_or(
+            ClientError::TransactionGenerationFailure(format!(
+                "Failed to create spending condition from public key: {}",
+                public_key.to_hex()
+            )),
+        )?,
+    );
+
+    let mut unsigned_tx = StacksTransaction::new(tx_version, tx_auth, tx_payload);
+
+    unsigned_tx.set_tx_fee(tx_fee);
+    unsigned_tx.set_origin_nonce(nonce);
+
+    unsigned_tx.anchor_mode = TransactionAnchorMode::Any;
+    unsigned_tx.post_condition_mode = TransactionPostConditionMode::Allow;
+    unsigned_tx.chain_id = chain_id;
+
+    let mut tx_signer = StacksTransactionSigner::new(&unsigned_tx);
+    tx_signer
+        .sign_origin(stacks_private_key)
+        .map_err(|e| ClientError::TransactionGenerationFailure(e.to_string()))?;
+
+    tx_signer
+        .get_tx()
+        .ok_or(ClientError::TransactionGenerationFailure(
+            "Failed to generate transaction from a transaction signer".to_string(),
+        ))
+}
+
 /// Retry a function F with an exponential backoff and notification on transient failure
 pub fn retry_with_exponential_backoff<F, E, T>(request_fn: F) -> Result<T, ClientError>
 where
@@ -446,8 +869,17 @@ where
     backoff::retry_notify(backoff_timer, request_fn, notify).map_err(|_| ClientError::RetryTimeout)
 }
 
+/// Record a failed stacker-db request in `connection_lost` if it did not reach the stacks node,
+/// and retry it
+fn stackerdb_error(connection_lost: &AtomicBool, e: RPCError) -> backoff::Error<RPCError> {
+    if matches!(e, RPCError::IO(_) | RPCError::NotConnected) {
+        connection_lost.store(true, Ordering::SeqCst);
+    }
+    backoff::Error::transient(e)
+}
+
 /// Helper function to determine the slot ID for the provided stacker-db writer id and the message type
-fn slot_id(id: u32, message: &Message) -> u32 {
+pub(crate) fn slot_id(id: u32, message: &Message) -> u32 {
     let slot_id = match message {
         Message::DkgBegin(_) => 0,
         Message::DkgPrivateBegin(_) => 1,
@@ -656,6 +1088,111 @@ mod tests {
         assert!(matches!(res, Err(ClientError::InvalidJsonEntry(_))));
     }
 
+    #[test]
+    fn next_reward_cycle_info_should_succeed() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_next_reward_cycle_info());
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"current_burnchain_block_height\":2000,\"next_cycle\":{\"id\":507,\"prepare_phase_start_block_height\":2090,\"reward_phase_start_block_height\":2100}}",
+        );
+        let info = h.join().unwrap().unwrap();
+        assert_eq!(
+            info,
+            NextRewardCycleInfo {
+                burn_block_height: 2000,
+                reward_cycle: 507,
+                prepare_phase_start_block_height: 2090,
+            }
+        );
+    }
+
+    #[test]
+    fn missing_prepare_phase_start_should_fail() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_next_reward_cycle_info());
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"current_burnchain_block_height\":2000,\"next_cycle\":{\"id\":507}}",
+        );
+        let res = h.join().unwrap();
+        assert!(matches!(res, Err(ClientError::InvalidJsonEntry(_))));
+    }
+
+    #[test]
+    fn next_possible_nonce_should_succeed() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_next_possible_nonce());
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"balance\":\"0x00000000000000000000000000000000\",\"locked\":\"0x00000000000000000000000000000000\",\"unlock_height\":0,\"nonce\":12}",
+        );
+        assert_eq!(h.join().unwrap().unwrap(), 12);
+    }
+
+    #[test]
+    fn account_balance_should_succeed() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_account_balance());
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"balance\":\"0x000000000000000000000000000003e8\",\"locked\":\"0x00000000000000000000000000000000\",\"unlock_height\":0,\"nonce\":12}",
+        );
+        assert_eq!(h.join().unwrap().unwrap(), 1000);
+    }
+
+    #[test]
+    fn slot_signers_should_succeed() {
+        let config = TestConfig::new();
+        let address = config.client.stacks_address;
+        let h = spawn(move || config.client.get_slot_signers());
+        write_response(
+            config.mock_server,
+            format!(
+                "HTTP/1.1 200 Ok\n\n[{{\"slot_id\":0,\"slot_version\":1,\"signer\":\"{}\",\"write_time\":0}}]",
+                address
+            )
+            .as_bytes(),
+        );
+        assert_eq!(h.join().unwrap().unwrap(), vec![(0, address)]);
+    }
+
+    #[test]
+    fn estimate_tx_fee_should_use_middle_estimate() {
+        let config = TestConfig::new();
+        let payload = contract_call_payload(
+            &config.client.stacks_address,
+            ContractName::try_from("contract-name").unwrap(),
+            ClarityName::try_from("function-name").unwrap(),
+            &[],
+        );
+        let h = spawn(move || config.client.estimate_tx_fee(&payload, 200));
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"estimations\":[{\"fee_rate\":1.0,\"fee\":200},{\"fee_rate\":2.0,\"fee\":400},{\"fee_rate\":3.0,\"fee\":600}]}",
+        );
+        assert_eq!(h.join().unwrap().unwrap(), 400);
+    }
+
+    #[test]
+    fn unknown_transaction_result_should_be_none() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_transaction_result(&Txid([1; 32])));
+        write_response(config.mock_server, b"HTTP/1.1 404 Not Found\n\n");
+        assert_eq!(h.join().unwrap().unwrap(), None);
+    }
+
+    #[test]
+    fn transaction_result_should_succeed() {
+        let config = TestConfig::new();
+        let h = spawn(move || config.client.get_transaction_result(&Txid([1; 32])));
+        write_response(
+            config.mock_server,
+            b"HTTP/1.1 200 Ok\n\n{\"txid\":\"0101010101010101010101010101010101010101010101010101010101010101\",\"result\":\"0x0703\"}",
+        );
+        assert_eq!(h.join().unwrap().unwrap(), Some(ClarityValue::okay_true()));
+    }
+
     #[test]
     fn parse_valid_aggregate_public_key_should_succeed() {
         let config = TestConfig::new();
@@ -701,6 +1238,8 @@ mod tests {
                 ContractName::try_from("contract-name").unwrap(),
                 ClarityName::try_from("function-name").unwrap(),
                 &[],
+                0,
+                DEFAULT_TX_FEE,
             )
             .unwrap();
 
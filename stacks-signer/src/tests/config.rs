@@ -1 +1,67 @@
+use stacks_signer::config::{Config, ConfigError};
 
+/// A signer config for a fleet of three signers with the given key ids
+fn config_toml(signer_id: u32, key_ids: [&str; 3]) -> String {
+    format!(
+        r#"
+message_private_key = "2ZCxUV9BAKJrGnTPaamKHb4HVgj9ArQgEhowuTe7uRt3"
+stacks_private_key = "69be0e68947fa7128702761151dc8d9b39ee1401e547781bb2ec3e5b4eb1b36f01"
+node_host = "127.0.0.1:20443"
+endpoint = "localhost:30000"
+network = "testnet"
+stackerdb_contract_id = "ST11Z60137Y96MF89K1KKRTA3CR6B25WY1Y931668.signers-stackerdb"
+signer_id = {signer_id}
+signers = [
+    {{public_key = "swBaKxfzs4pQne7spxhrkF6AtB34WEcreAkJ8mPcqx3t", key_ids = [{}]}},
+    {{public_key = "yDJhntuJczbss1XGDmyWtG9Wpw5NDqoBBnedxmyhKiFN", key_ids = [{}]}},
+    {{public_key = "xNVCph6zd7HLLJcuwrWz1gNbFoPHjXxn7cyRvvTYhP3U", key_ids = [{}]}}
+]
+"#,
+        key_ids[0], key_ids[1], key_ids[2]
+    )
+}
+
+#[test]
+fn uneven_key_ids() {
+    let config =
+        Config::load_from_str(&config_toml(2, ["1, 2, 3, 4, 5, 6, 7, 8", "9", "10"])).unwrap();
+    assert_eq!(config.signer_ids_public_keys.key_ids.len(), 10);
+    assert_eq!(config.signer_ids_public_keys.signers.len(), 3);
+    assert_eq!(config.signer_key_ids.get(&0).unwrap().len(), 8);
+    assert_eq!(config.signer_key_ids.get(&2).unwrap(), &vec![10]);
+
+    // key ids need not be handed out in order
+    Config::load_from_str(&config_toml(0, ["3, 1", "2", "5, 4"])).unwrap();
+}
+
+#[test]
+fn signer_without_key_ids() {
+    assert!(matches!(
+        Config::load_from_str(&config_toml(0, ["1, 2, 3", "4", ""])),
+        Err(ConfigError::BadField(field, _)) if field == "signers.key_ids"
+    ));
+}
+
+#[test]
+fn duplicate_key_ids() {
+    assert!(matches!(
+        Config::load_from_str(&config_toml(0, ["1, 2, 3", "3", "4"])),
+        Err(ConfigError::BadField(field, value)) if field == "signers.key_ids" && value == "3"
+    ));
+}
+
+#[test]
+fn key_ids_with_gaps() {
+    assert!(matches!(
+        Config::load_from_str(&config_toml(0, ["1, 2, 3", "4", "6"])),
+        Err(ConfigError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn unknown_signer_id() {
+    assert!(matches!(
+        Config::load_from_str(&config_toml(3, ["1, 2, 3", "4", "5"])),
+        Err(ConfigError::BadField(field, _)) if field == "signer_id"
+    ));
+}
@@ -2,14 +2,59 @@ use std::time::Duration;
 
 use p256k1::ecdsa;
 use rand_core::OsRng;
-use slog::slog_debug;
-use stacks_common::debug;
 use stacks_common::types::chainstate::{StacksAddress, StacksPrivateKey};
 use wsts::Scalar;
 
 use crate::stacks_client::SLOTS_PER_USER;
 
-/// Helper function for building a signer config for each provided signer private key
+/// Distribute the key ids `1..=num_keys` among signers in proportion to their stacked weights,
+/// using the largest remainder method. Every signer gets at least one key id, so that it can take
+/// part in DKG and signing however little it has stacked, and the rest are shared out by weight.
+/// Signers are given contiguous runs of key ids, in order. If no signer has any weight, the key
+/// ids are shared out evenly.
+pub fn key_ids_by_weight(weights: &[u64], num_keys: u32) -> Vec<Vec<u32>> {
+    let num_signers = u32::try_from(weights.len()).expect("Too many signers");
+    assert!(
+        num_signers > 0 && num_keys >= num_signers,
+        "Cannot give each of {num_signers} signers at least one of {num_keys} keys"
+    );
+    let total_weight: u128 = weights.iter().map(|weight| u128::from(*weight)).sum();
+    if total_weight == 0 {
+        return key_ids_by_weight(&vec![1; weights.len()], num_keys);
+    }
+
+    let spare_keys = u128::from(num_keys - num_signers);
+    let mut num_signer_keys = vec![1u32; weights.len()];
+    let mut assigned = 0;
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, weight) in weights.iter().enumerate() {
+        let share = spare_keys * u128::from(*weight);
+        let whole = share / total_weight;
+        num_signer_keys[i] += u32::try_from(whole).expect("Too many keys");
+        assigned += whole;
+        remainders.push((share % total_weight, i));
+    }
+    // hand out the keys left over from rounding down to the largest remainders, breaking ties
+    // in favor of the earlier signer
+    remainders.sort_by(|(rem_a, i_a), (rem_b, i_b)| rem_b.cmp(rem_a).then(i_a.cmp(i_b)));
+    let leftover = usize::try_from(spare_keys - assigned).expect("Too many keys");
+    for (_, i) in remainders.into_iter().take(leftover) {
+        num_signer_keys[i] += 1;
+    }
+
+    let mut key_id = 1;
+    num_signer_keys
+        .into_iter()
+        .map(|count| {
+            let ids = (key_id..key_id + count).collect();
+            key_id += count;
+            ids
+        })
+        .collect()
+}
+
+/// Helper function for building a signer config for each provided signer private key, with the
+/// key ids shared out evenly
 pub fn build_signer_config_tomls(
     signer_stacks_private_keys: &[StacksPrivateKey],
     num_keys: u32,
@@ -18,28 +63,44 @@ pub fn build_signer_config_tomls(
     pox_contract_id: Option<&str>,
     timeout: Option<Duration>,
 ) -> Vec<String> {
+    build_weighted_signer_config_tomls(
+        signer_stacks_private_keys,
+        &vec![1; signer_stacks_private_keys.len()],
+        num_keys,
+        node_host,
+        stackerdb_contract_id,
+        pox_contract_id,
+        timeout,
+    )
+}
+
+/// Helper function for building a signer config for each provided signer private key, with the
+/// key ids shared out in proportion to the given signer weights
+pub fn build_weighted_signer_config_tomls(
+    signer_stacks_private_keys: &[StacksPrivateKey],
+    signer_weights: &[u64],
+    num_keys: u32,
+    node_host: &str,
+    stackerdb_contract_id: &str,
+    pox_contract_id: Option<&str>,
+    timeout: Option<Duration>,
+) -> Vec<String> {
+    assert_eq!(
+        signer_stacks_private_keys.len(),
+        signer_weights.len(),
+        "Expected a weight for each signer"
+    );
     let num_signers = signer_stacks_private_keys.len() as u32;
     let mut rng = OsRng;
-    let keys_per_signer = num_keys / num_signers;
-    let mut key_id: u32 = 1;
-    let mut key_ids = Vec::new();
-    for i in 0..num_signers {
-        let mut ids = Vec::new();
-        for _ in 0..keys_per_signer {
-            ids.push(format!("{key_id}"));
-            key_id += 1;
-        }
-        if i + 1 == num_signers {
-            for _ in 0..num_keys % num_signers {
-                // We have requested a number of keys that cannot fit evenly into the number of signers
-                // Append the remaining keys to the last signer
-                ids.push(format!("{key_id}"));
-                key_id += 1;
-                debug!("Appending extra key to last signer...");
-            }
-        }
-        key_ids.push(ids.join(", "));
-    }
+    let key_ids: Vec<String> = key_ids_by_weight(signer_weights, num_keys)
+        .iter()
+        .map(|ids| {
+            ids.iter()
+                .map(|key_id| key_id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect();
     let signer_ecdsa_private_keys = (0..num_signers)
         .map(|_| Scalar::random(&mut rng))
         .collect::<Vec<Scalar>>();
@@ -131,3 +192,116 @@ pub fn build_stackerdb_contract(signer_stacks_addresses: &[StacksAddress]) -> St
     stackerdb_contract += "    ";
     stackerdb_contract
 }
+
+#[cfg(test)]
+mod tests {
+    use rand_core::RngCore;
+
+    use super::*;
+
+    /// Check the invariants of a key id assignment
+    fn check_key_ids_by_weight(weights: &[u64], num_keys: u32) {
+        let key_ids = key_ids_by_weight(weights, num_keys);
+        assert_eq!(key_ids.len(), weights.len());
+
+        // every key id is handed out exactly once, in order
+        let all_key_ids: Vec<u32> = key_ids.iter().flatten().copied().collect();
+        assert_eq!(all_key_ids, (1..=num_keys).collect::<Vec<_>>());
+
+        let total_weight: u128 = weights.iter().map(|weight| u128::from(*weight)).sum();
+        let spare_keys = u128::from(num_keys) - weights.len() as u128;
+        for (i, ids) in key_ids.iter().enumerate() {
+            // every signer can take part
+            assert!(!ids.is_empty(), "Signer {i} has no keys for {weights:?}");
+            // and gets within one key of its share of the rest
+            if total_weight > 0 {
+                let share = spare_keys * u128::from(weights[i]);
+                let lower = 1 + share / total_weight;
+                let upper = lower + u128::from(share % total_weight != 0);
+                let num_ids = ids.len() as u128;
+                assert!(
+                    lower <= num_ids && num_ids <= upper,
+                    "Signer {i} has {num_ids} of {num_keys} keys for {weights:?}"
+                );
+            }
+            // signers with more weight never get fewer keys
+            for (j, other_ids) in key_ids.iter().enumerate() {
+                if weights[i] > weights[j] {
+                    assert!(
+                        ids.len() >= other_ids.len(),
+                        "Signer {i} has fewer keys than signer {j} for {weights:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn key_ids_by_weight_even() {
+        assert_eq!(
+            key_ids_by_weight(&[1, 1, 1], 7),
+            vec![vec![1, 2, 3], vec![4, 5], vec![6, 7]]
+        );
+        assert_eq!(key_ids_by_weight(&[0, 0], 4), vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn key_ids_by_weight_dominant_signer() {
+        // one signer with 60% of the weight, and 50 with 0.8% each
+        let mut weights = vec![600];
+        weights.extend(vec![8; 50]);
+        let key_ids = key_ids_by_weight(&weights, 4051);
+        check_key_ids_by_weight(&weights, 4051);
+        assert_eq!(key_ids[0].len(), 2401);
+        assert!(key_ids[1..].iter().all(|ids| ids.len() == 33));
+
+        // too few keys to go around by weight, but every signer still gets one
+        let key_ids = key_ids_by_weight(&weights, 60);
+        check_key_ids_by_weight(&weights, 60);
+        assert_eq!(key_ids[0].len(), 7);
+        assert_eq!(
+            key_ids.iter().filter(|ids| ids.len() == 1).count(),
+            weights.len() - 1 - 3
+        );
+    }
+
+    #[test]
+    fn key_ids_by_weight_random_distributions() {
+        let mut rng = OsRng;
+        for _ in 0..500 {
+            let num_signers = 1 + (rng.next_u32() % 100) as usize;
+            let num_keys = num_signers as u32 + rng.next_u32() % 4000;
+            let weights: Vec<u64> = match rng.next_u32() % 4 {
+                // uniform
+                0 => (0..num_signers)
+                    .map(|_| rng.next_u64() % 1_000_000)
+                    .collect(),
+                // heavy-tailed
+                1 => (0..num_signers)
+                    .map(|_| 1u64 << (rng.next_u32() % 48))
+                    .collect(),
+                // one whale among minnows
+                2 => (0..num_signers)
+                    .map(|i| {
+                        if i == 0 {
+                            1_000_000
+                        } else {
+                            1 + rng.next_u64() % 10
+                        }
+                    })
+                    .collect(),
+                // some signers with no weight at all
+                _ => (0..num_signers)
+                    .map(|_| (rng.next_u64() % 3) * (rng.next_u64() % 1000))
+                    .collect(),
+            };
+            check_key_ids_by_weight(&weights, num_keys);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn key_ids_by_weight_too_few_keys() {
+        key_ids_by_weight(&[1, 1, 1], 2);
+    }
+}
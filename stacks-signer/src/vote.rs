@@ -0,0 +1,285 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use blockstack_lib::burnchains::Txid;
+use blockstack_lib::chainstate::stacks::StacksTransaction;
+use clarity::vm::types::{BuffData, SequenceData};
+use clarity::vm::{ClarityName, ContractName, Value as ClarityValue};
+use slog::{slog_info, slog_warn};
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::{info, warn};
+use wsts::Point;
+
+use crate::stacks_client::{contract_call_payload, ClientError, StacksNode, DEFAULT_TX_FEE};
+
+/// The function of the pox contract that records a signer's vote for a reward cycle's aggregate
+/// public key
+// FIXME: this may need to be modified to match .pox-4
+pub const VOTE_FUNCTION_NAME: &str = "vote-for-aggregate-public-key";
+/// The estimated length of a signed vote transaction, used to estimate its fee
+const VOTE_TX_ESTIMATED_LEN: u64 = 200;
+/// How much each rebroadcast of an unconfirmed vote raises its fee, in percent
+const FEE_BUMP_PERCENT: u64 = 25;
+
+/// What became of the vote transaction being tracked
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoteStatus {
+    /// The vote has not been mined yet
+    Pending,
+    /// The vote was mined and the contract accepted it
+    Confirmed(Txid),
+    /// The vote was mined but the contract rejected it
+    Rejected(Txid, ClarityValue),
+    /// The vote was not mined after every retry
+    Abandoned,
+}
+
+/// A vote transaction that has been broadcast but not confirmed
+#[derive(Debug, Clone)]
+struct PendingVote {
+    /// The reward cycle being voted on
+    reward_cycle: u64,
+    /// The aggregate public key being voted for
+    key: Point,
+    /// The txid of the most recent broadcast
+    txid: Txid,
+    /// The nonce the vote was sent with. Rebroadcasts reuse it so that they replace the
+    /// earlier transaction.
+    nonce: u64,
+    /// The fee of the most recent broadcast
+    tx_fee: u64,
+    /// When the vote was last broadcast
+    broadcast_at: Instant,
+    /// How many times the vote was rebroadcast
+    retries: u32,
+}
+
+/// Votes for the aggregate public key produced by DKG, and tracks the vote until it is mined.
+/// A vote that is not mined within `confirm_timeout` is rebroadcast with the same nonce and a
+/// higher fee, up to `max_retries` times.
+pub struct AggregateKeyVoter {
+    /// How long to wait for a vote to be mined before rebroadcasting it
+    pub confirm_timeout: Duration,
+    /// How many times an unconfirmed vote is rebroadcast
+    pub max_retries: u32,
+    /// The vote being tracked, if any
+    pending: Option<PendingVote>,
+    /// The lowest nonce that the signer has not used yet, as far as this voter knows
+    next_nonce: Option<u64>,
+}
+
+impl AggregateKeyVoter {
+    /// Create a voter with the given confirmation timeout and retry count
+    pub fn new(confirm_timeout: Duration, max_retries: u32) -> Self {
+        AggregateKeyVoter {
+            confirm_timeout,
+            max_retries,
+            pending: None,
+            next_nonce: None,
+        }
+    }
+
+    /// Whether a vote is waiting to be mined
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Broadcast a vote for `key` as the aggregate public key of `reward_cycle`.
+    /// Any vote that was still being tracked is replaced.
+    pub fn vote<S: StacksNode>(
+        &mut self,
+        stacks_client: &S,
+        reward_cycle: u64,
+        key: Point,
+    ) -> Result<Txid, ClientError> {
+        // Never reuse a nonce we already sent a vote with, even if the node has not seen it yet
+        let node_nonce = stacks_client.get_next_possible_nonce()?;
+        let nonce = self
+            .next_nonce
+            .map_or(node_nonce, |nonce| nonce.max(node_nonce));
+        let tx_fee = self.estimate_fee(stacks_client, reward_cycle, &key);
+        let tx = build_vote_transaction(stacks_client, reward_cycle, &key, nonce, tx_fee)?;
+        let txid = stacks_client.submit_tx(&tx)?;
+        info!(
+            "Voted for aggregate public key {} for reward cycle {} in tx {}",
+            key, reward_cycle, txid
+        );
+        self.next_nonce = Some(nonce + 1);
+        self.pending = Some(PendingVote {
+            reward_cycle,
+            key,
+            txid,
+            nonce,
+            tx_fee,
+            broadcast_at: Instant::now(),
+            retries: 0,
+        });
+        Ok(txid)
+    }
+
+    /// Check whether the tracked vote was mined, rebroadcasting it if it is overdue.
+    /// Returns `None` if no vote is being tracked.
+    pub fn check<S: StacksNode>(
+        &mut self,
+        stacks_client: &S,
+        now: Instant,
+    ) -> Result<Option<VoteStatus>, ClientError> {
+        let Some(pending) = self.pending.as_mut() else {
+            return Ok(None);
+        };
+        if let Some(result) = stacks_client.get_transaction_result(&pending.txid)? {
+            let txid = pending.txid;
+            self.pending = None;
+            return Ok(Some(vote_status(txid, result)));
+        }
+        if now.saturating_duration_since(pending.broadcast_at) < self.confirm_timeout {
+            return Ok(Some(VoteStatus::Pending));
+        }
+        if pending.retries >= self.max_retries {
+            warn!(
+                "Vote tx {} for reward cycle {} was not mined after {} retries. Giving up.",
+                pending.txid, pending.reward_cycle, pending.retries
+            );
+            self.pending = None;
+            return Ok(Some(VoteStatus::Abandoned));
+        }
+        let tx_fee = bump_fee(pending.tx_fee);
+        let tx = build_vote_transaction(
+            stacks_client,
+            pending.reward_cycle,
+            &pending.key,
+            pending.nonce,
+            tx_fee,
+        )?;
+        let txid = stacks_client.submit_tx(&tx)?;
+        info!(
+            "Vote tx {} for reward cycle {} was not mined. Rebroadcast as {} with fee {}",
+            pending.txid, pending.reward_cycle, txid, tx_fee
+        );
+        pending.txid = txid;
+        pending.tx_fee = tx_fee;
+        pending.broadcast_at = now;
+        pending.retries += 1;
+        Ok(Some(VoteStatus::Pending))
+    }
+
+    /// Estimate the fee of a vote, falling back to the default fee if the node cannot estimate it
+    fn estimate_fee<S: StacksNode>(
+        &self,
+        stacks_client: &S,
+        reward_cycle: u64,
+        key: &Point,
+    ) -> u64 {
+        let estimate = vote_call(stacks_client, reward_cycle, key).and_then(
+            |(contract_addr, contract_name, function_name, function_args)| {
+                let payload = contract_call_payload(
+                    &contract_addr,
+                    contract_name,
+                    function_name,
+                    &function_args,
+                );
+                stacks_client.estimate_tx_fee(&payload, VOTE_TX_ESTIMATED_LEN)
+            },
+        );
+        match estimate {
+            Ok(fee) => fee.max(DEFAULT_TX_FEE),
+            Err(e) => {
+                warn!("Failed to estimate the vote fee: {:?}", e);
+                DEFAULT_TX_FEE
+            }
+        }
+    }
+}
+
+/// The contract call that votes for `key` as the aggregate public key of `reward_cycle`
+fn vote_call<S: StacksNode>(
+    stacks_client: &S,
+    reward_cycle: u64,
+    key: &Point,
+) -> Result<(StacksAddress, ContractName, ClarityName, Vec<ClarityValue>), ClientError> {
+    let function_name = ClarityName::try_from(VOTE_FUNCTION_NAME)
+        .map_err(|_| ClientError::InvalidClarityName(VOTE_FUNCTION_NAME.to_string()))?;
+    let (contract_addr, contract_name) = stacks_client.get_pox_contract()?;
+    let function_args = vec![
+        ClarityValue::Sequence(SequenceData::Buffer(BuffData {
+            data: key.compress().data.to_vec(),
+        })),
+        ClarityValue::UInt(reward_cycle as u128),
+    ];
+    Ok((contract_addr, contract_name, function_name, function_args))
+}
+
+/// Build and sign a vote transaction with the given nonce and fee
+fn build_vote_transaction<S: StacksNode>(
+    stacks_client: &S,
+    reward_cycle: u64,
+    key: &Point,
+    nonce: u64,
+    tx_fee: u64,
+) -> Result<StacksTransaction, ClientError> {
+    let (contract_addr, contract_name, function_name, function_args) =
+        vote_call(stacks_client, reward_cycle, key)?;
+    stacks_client.build_signed_transaction(
+        &contract_addr,
+        contract_name,
+        function_name,
+        &function_args,
+        nonce,
+        tx_fee,
+    )
+}
+
+/// Interpret the result of a mined vote transaction
+fn vote_status(txid: Txid, result: ClarityValue) -> VoteStatus {
+    match &result {
+        ClarityValue::Response(response) if response.committed => VoteStatus::Confirmed(txid),
+        _ => VoteStatus::Rejected(txid, result),
+    }
+}
+
+/// The fee of a rebroadcast vote. The mempool only replaces a transaction with one that pays a
+/// higher fee.
+fn bump_fee(tx_fee: u64) -> u64 {
+    tx_fee.saturating_add((tx_fee.saturating_mul(FEE_BUMP_PERCENT) / 100).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebroadcast_raises_fee() {
+        assert_eq!(bump_fee(10_000), 12_500);
+        assert_eq!(bump_fee(1), 2);
+        assert_eq!(bump_fee(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn vote_status_follows_contract_result() {
+        let txid = Txid([1; 32]);
+        assert_eq!(
+            vote_status(txid, ClarityValue::okay_true()),
+            VoteStatus::Confirmed(txid)
+        );
+        let rejection = ClarityValue::error(ClarityValue::UInt(1)).unwrap();
+        assert_eq!(
+            vote_status(txid, rejection.clone()),
+            VoteStatus::Rejected(txid, rejection)
+        );
+    }
+}
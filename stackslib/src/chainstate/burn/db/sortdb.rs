@@ -85,6 +85,7 @@ use crate::util_lib::db::{
     query_rows, sql_pragma, tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn, DBTx,
     Error as db_error, FromColumn, FromRow, IndexDBConn, IndexDBTx,
 };
+use crate::util_lib::migrations::Migration;
 
 const BLOCK_HEIGHT_MAX: u64 = ((1 as u64) << 63) - 1;
 
@@ -2653,12 +2654,11 @@ impl SortitionDB {
         for row_text in SORTITION_DB_INITIAL_SCHEMA {
             db_tx.execute_batch(row_text)?;
         }
-        SortitionDB::apply_schema_2(&db_tx, epochs_ref)?;
-        SortitionDB::apply_schema_3(&db_tx)?;
-        SortitionDB::apply_schema_4(&db_tx)?;
-        SortitionDB::apply_schema_5(&db_tx, epochs_ref)?;
-        SortitionDB::apply_schema_6(&db_tx, epochs_ref)?;
-        SortitionDB::apply_schema_7(&db_tx, epochs_ref)?;
+        let mut version = "1".to_string();
+        while let Some(migration) = SortitionDB::schema_migration(&version, epochs_ref) {
+            migration.apply(&db_tx)?;
+            version = migration.version().to_string();
+        }
 
         db_tx.instantiate_index()?;
 
@@ -2906,41 +2906,19 @@ impl SortitionDB {
         Ok(version)
     }
 
-    fn apply_schema_2(tx: &DBTx, epochs: &[StacksEpoch]) -> Result<(), db_error> {
-        for sql_exec in SORTITION_DB_SCHEMA_2 {
-            tx.execute_batch(sql_exec)?;
-        }
-
-        SortitionDB::validate_and_insert_epochs(&tx, epochs)?;
-
+    fn set_schema_version(tx: &DBTx, version: &str) -> Result<(), db_error> {
         tx.execute(
             "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["2"],
+            &[version],
         )?;
-
         Ok(())
     }
 
-    fn apply_schema_3(tx: &DBTx) -> Result<(), db_error> {
-        for sql_exec in SORTITION_DB_SCHEMA_3 {
-            tx.execute_batch(sql_exec)?;
-        }
-        tx.execute(
-            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["3"],
-        )?;
-        Ok(())
-    }
-
-    fn apply_schema_4(tx: &DBTx) -> Result<(), db_error> {
-        for sql_exec in SORTITION_DB_SCHEMA_4 {
-            tx.execute_batch(sql_exec)?;
-        }
-
-        let typical_rules: &[&dyn ToSql] = &[&(ASTRules::Typical as u8), &0i64];
+    fn insert_ast_rule_heights(tx: &DBTx) -> Result<(), db_error> {
+        let typical_rules: &[&dyn ToSql] = &[&ASTRules::Typical.to_u8(), &0i64];
 
         let precheck_size_rules: &[&dyn ToSql] = &[
-            &(ASTRules::PrecheckSize as u8),
+            &ASTRules::PrecheckSize.to_u8(),
             &u64_to_sql(AST_RULES_PRECHECK_SIZE)?,
         ];
 
@@ -2952,59 +2930,51 @@ impl SortitionDB {
             "INSERT INTO ast_rule_heights (ast_rule_id,block_height) VALUES (?1, ?2)",
             precheck_size_rules,
         )?;
-        tx.execute(
-            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["4"],
-        )?;
-        Ok(())
-    }
-
-    fn apply_schema_5(tx: &DBTx, epochs: &[StacksEpoch]) -> Result<(), db_error> {
-        // the schema 5 changes simply **replace** the contents of the epochs table
-        //  by dropping all the current rows and then revalidating and inserting
-        //  `epochs`
-        for sql_exec in SORTITION_DB_SCHEMA_5 {
-            tx.execute_batch(sql_exec)?;
-        }
-
-        SortitionDB::validate_and_insert_epochs(&tx, epochs)?;
-
-        tx.execute(
-            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["5"],
-        )?;
-
         Ok(())
     }
 
-    fn apply_schema_6(tx: &DBTx, epochs: &[StacksEpoch]) -> Result<(), db_error> {
-        for sql_exec in SORTITION_DB_SCHEMA_6 {
-            tx.execute_batch(sql_exec)?;
-        }
-
-        SortitionDB::validate_and_insert_epochs(&tx, epochs)?;
-
-        tx.execute(
-            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["6"],
-        )?;
-
-        Ok(())
-    }
-
-    fn apply_schema_7(tx: &DBTx, epochs: &[StacksEpoch]) -> Result<(), db_error> {
-        for sql_exec in SORTITION_DB_SCHEMA_7 {
-            tx.execute_batch(sql_exec)?;
-        }
-
-        SortitionDB::validate_and_insert_epochs(&tx, epochs)?;
-
-        tx.execute(
-            "INSERT OR REPLACE INTO db_config (version) VALUES (?1)",
-            &["7"],
-        )?;
-
-        Ok(())
+    /// Get the migration from the given sortition DB schema version to the next one, given the
+    /// set of system epochs.  Returns None if there is no later version.
+    fn schema_migration<'a>(version: &str, epochs: &'a [StacksEpoch]) -> Option<Migration<'a>> {
+        let migration = match version {
+            "1" => Migration::new("sortition", "2")
+                .sql_steps(SORTITION_DB_SCHEMA_2)
+                .step("insert epochs", move |tx| {
+                    SortitionDB::validate_and_insert_epochs(tx, epochs)
+                }),
+            // add the tables of schema 3, but do not populate them.
+            "2" => Migration::new("sortition", "3").sql_steps(SORTITION_DB_SCHEMA_3),
+            "3" => Migration::new("sortition", "4")
+                .sql_steps(SORTITION_DB_SCHEMA_4)
+                .step("insert AST rule heights", |tx| {
+                    SortitionDB::insert_ast_rule_heights(tx)
+                }),
+            // the schema 5 changes simply **replace** the contents of the epochs table
+            //  by dropping all the current rows and then revalidating and inserting
+            //  `epochs`
+            "4" => Migration::new("sortition", "5")
+                .sql_steps(SORTITION_DB_SCHEMA_5)
+                .step("insert epochs", move |tx| {
+                    SortitionDB::validate_and_insert_epochs(tx, epochs)
+                }),
+            "5" => Migration::new("sortition", "6")
+                .sql_steps(SORTITION_DB_SCHEMA_6)
+                .step("insert epochs", move |tx| {
+                    SortitionDB::validate_and_insert_epochs(tx, epochs)
+                }),
+            "6" => Migration::new("sortition", "7")
+                .sql_steps(SORTITION_DB_SCHEMA_7)
+                .step("insert epochs", move |tx| {
+                    SortitionDB::validate_and_insert_epochs(tx, epochs)
+                }),
+            _ => {
+                return None;
+            }
+        };
+        let next_version = migration.version().to_string();
+        Some(migration.step("set schema version", move |tx| {
+            SortitionDB::set_schema_version(tx, &next_version)
+        }))
     }
 
     fn check_schema_version_or_error(&mut self) -> Result<(), db_error> {
@@ -3032,35 +3002,15 @@ impl SortitionDB {
         loop {
             match SortitionDB::get_schema_version(self.conn()) {
                 Ok(Some(version)) => {
-                    if version == "1" {
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_2(&tx.deref(), epochs)?;
-                        tx.commit()?;
-                    } else if version == "2" {
-                        // add the tables of schema 3, but do not populate them.
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_3(&tx.deref())?;
-                        tx.commit()?;
-                    } else if version == "3" {
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_4(&tx.deref())?;
-                        tx.commit()?;
-                    } else if version == "4" {
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_5(&tx.deref(), epochs)?;
-                        tx.commit()?;
-                    } else if version == "5" {
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_6(&tx.deref(), epochs)?;
-                        tx.commit()?;
-                    } else if version == "6" {
-                        let tx = self.tx_begin()?;
-                        SortitionDB::apply_schema_7(&tx.deref(), epochs)?;
-                        tx.commit()?;
-                    } else if version == expected_version {
+                    if version == expected_version {
                         return Ok(());
-                    } else {
-                        panic!("The schema version of the sortition DB is invalid.")
+                    }
+                    if !self.readwrite {
+                        return Err(db_error::ReadOnly);
+                    }
+                    match SortitionDB::schema_migration(&version, epochs) {
+                        Some(migration) => migration.run(&mut self.marf)?,
+                        None => panic!("The schema version of the sortition DB is invalid."),
                     }
                 }
                 Ok(None) => panic!("The schema version of the sortition DB is not recorded."),
@@ -3118,7 +3068,7 @@ impl SortitionDB {
         ast_rules: ASTRules,
         height: u64,
     ) -> Result<(), db_error> {
-        let rules: &[&dyn ToSql] = &[&u64_to_sql(height)?, &(ast_rules as u8)];
+        let rules: &[&dyn ToSql] = &[&u64_to_sql(height)?, &ast_rules.to_u8()];
 
         tx.execute(
             "UPDATE ast_rule_heights SET block_height = ?1 WHERE ast_rule_id = ?2",
@@ -521,6 +521,12 @@ impl<'a, T: BlockEventDispatcher, U: RewardSetProvider, B: BurnchainHeaderReader
             burnchain_indexer,
         }
     }
+
+    /// Get the coordinator's chainstate, e.g. to change how it processes blocks in a test
+    #[cfg(test)]
+    pub fn chainstate_mut(&mut self) -> &mut StacksChainState {
+        &mut self.chain_state_db
+    }
 }
 
 pub fn get_next_recipients<U: RewardSetProvider>(
@@ -24,4 +24,5 @@ pub trait ChainstateDB {
 // needs to come _after_ the macro def above, since they both use this macro
 pub mod burn;
 pub mod coordinator;
+pub mod snapshot;
 pub mod stacks;
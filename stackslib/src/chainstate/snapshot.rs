@@ -0,0 +1,582 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Chainstate snapshots, which let a new node skip initial block download.
+//!
+//! A snapshot is a directory with a copy of a stopped node's `burnchain` and `chainstate`
+//! directories, plus a `snapshot.json` manifest signed by a party the operator trusts.  The
+//! manifest lists the hash of every file, the burnchain block the snapshot was taken at, and the
+//! MARF root hashes of the sortition DB and the Stacks chain tip.  Before a node installs a
+//! snapshot, it checks the signature and the file hashes, recomputes the MARF roots from the
+//! snapshot's own databases, and checks that the snapshot's burnchain block is on the burnchain
+//! the node sees.  The node then syncs the rest of the chain as usual.
+
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::{fs, io};
+
+use rusqlite::OpenFlags;
+use sha2::{Digest, Sha512_256};
+use stacks_common::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, StacksBlockId, StacksPrivateKey,
+    StacksPublicKey, TrieHash,
+};
+use stacks_common::types::{PrivateKey, PublicKey};
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use stacks_common::util::secp256k1::MessageSignature;
+
+use crate::burnchains::PoxConstants;
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::index::marf::{MARFOpenOpts, MARF};
+use crate::chainstate::stacks::index::storage::TrieFileStorage;
+use crate::chainstate::stacks::index::Error as MARFError;
+use crate::chainstate::stacks::Error as ChainstateError;
+use crate::util_lib::db::{sqlite_open, Error as DBError};
+
+/// Name of the signed manifest in a snapshot directory
+pub const SNAPSHOT_MANIFEST_FILENAME: &str = "snapshot.json";
+/// Directories of a node's working directory that a snapshot contains
+pub const SNAPSHOT_DIRS: &[&str] = &["burnchain", "chainstate"];
+
+#[derive(Debug)]
+pub enum Error {
+    IOError(io::Error),
+    DBError(DBError),
+    MARFError(MARFError),
+    ChainstateError(ChainstateError),
+    /// The manifest could not be read or parsed
+    BadManifest(String),
+    /// The manifest was not signed by the trusted signer
+    BadSignature,
+    /// The snapshot's files do not match the manifest
+    FileMismatch(String),
+    /// The snapshot's databases do not match the manifest
+    StateMismatch(String),
+    /// The snapshot's burnchain block is not on the burnchain this node sees
+    BurnchainMismatch(u64),
+    /// The snapshot would overwrite this existing file
+    DestinationExists(PathBuf),
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::IOError(e)
+    }
+}
+
+impl From<DBError> for Error {
+    fn from(e: DBError) -> Error {
+        Error::DBError(e)
+    }
+}
+
+impl From<MARFError> for Error {
+    fn from(e: MARFError) -> Error {
+        Error::MARFError(e)
+    }
+}
+
+impl From<ChainstateError> for Error {
+    fn from(e: ChainstateError) -> Error {
+        Error::ChainstateError(e)
+    }
+}
+
+/// Describes the state in a snapshot, and every file it is made of
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Height of the burnchain block the snapshot was taken at
+    pub burn_block_height: u64,
+    /// Hash of the burnchain block the snapshot was taken at
+    pub burn_header_hash: BurnchainHeaderHash,
+    /// Root hash of the sortition DB's MARF at the snapshot's burnchain tip
+    pub sortition_root: TrieHash,
+    /// Canonical Stacks chain tip as of the snapshot's burnchain tip
+    pub stacks_tip_consensus_hash: ConsensusHash,
+    pub stacks_tip_block_hash: BlockHeaderHash,
+    /// Root hash of the block header index MARF at the Stacks tip
+    pub stacks_tip_index_root: TrieHash,
+    /// Root hash of the Clarity state MARF at the Stacks tip
+    pub stacks_tip_state_root: TrieHash,
+    /// Hash of each file, by its `/`-separated path relative to the snapshot directory
+    pub files: BTreeMap<String, Sha512Trunc256Sum>,
+}
+
+/// A manifest, and its signature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedSnapshotManifest {
+    pub manifest: SnapshotManifest,
+    pub signature: MessageSignature,
+}
+
+impl SnapshotManifest {
+    /// Hash of the manifest, which is what gets signed
+    pub fn sighash(&self) -> Sha512Trunc256Sum {
+        let bytes = serde_json::to_vec(self).expect("FATAL: failed to serialize manifest");
+        Sha512Trunc256Sum::from_data(&bytes)
+    }
+
+    /// Sign the manifest
+    pub fn sign(self, privkey: &StacksPrivateKey) -> Result<SignedSnapshotManifest, Error> {
+        let signature = privkey
+            .sign(self.sighash().as_bytes())
+            .map_err(|e| Error::BadManifest(e.to_string()))?;
+        Ok(SignedSnapshotManifest {
+            manifest: self,
+            signature,
+        })
+    }
+
+    /// Check that the snapshot's burnchain block is the block with hash `burn_header_hash`,
+    /// as the node's own burnchain headers have it
+    pub fn check_burnchain_header(
+        &self,
+        burn_header_hash: &BurnchainHeaderHash,
+    ) -> Result<(), Error> {
+        if &self.burn_header_hash != burn_header_hash {
+            return Err(Error::BurnchainMismatch(self.burn_block_height));
+        }
+        Ok(())
+    }
+}
+
+impl SignedSnapshotManifest {
+    /// Load the signed manifest from a snapshot directory
+    pub fn load(snapshot_dir: &Path) -> Result<SignedSnapshotManifest, Error> {
+        let bytes = fs::read(snapshot_dir.join(SNAPSHOT_MANIFEST_FILENAME))?;
+        serde_json::from_slice(&bytes).map_err(|e| Error::BadManifest(e.to_string()))
+    }
+
+    /// Store the signed manifest in a snapshot directory
+    pub fn store(&self, snapshot_dir: &Path) -> Result<(), Error> {
+        let bytes = serde_json::to_vec_pretty(self).expect("FATAL: failed to serialize manifest");
+        fs::write(snapshot_dir.join(SNAPSHOT_MANIFEST_FILENAME), bytes)?;
+        Ok(())
+    }
+
+    /// Check that the manifest was signed by `signer`
+    pub fn verify(&self, signer: &StacksPublicKey) -> Result<(), Error> {
+        match signer.verify(self.manifest.sighash().as_bytes(), &self.signature) {
+            Ok(true) => Ok(()),
+            _ => Err(Error::BadSignature),
+        }
+    }
+}
+
+fn hash_file(path: &Path) -> Result<Sha512Trunc256Sum, Error> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha512_256::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(Sha512Trunc256Sum::from_hasher(hasher))
+}
+
+fn hash_dir(
+    root: &Path,
+    dir: &Path,
+    files: &mut BTreeMap<String, Sha512Trunc256Sum>,
+) -> Result<(), Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        let file_name = path.to_string_lossy().to_string();
+        if file_type.is_dir() {
+            hash_dir(root, &path, files)?;
+        } else if file_name.ends_with("-shm") {
+            // shared-memory index of a SQLite WAL, which is not part of the database
+            continue;
+        } else if file_name.ends_with("-wal") {
+            // a non-empty WAL holds writes that were not checkpointed into the database,
+            // which only happens if the node was not shut down cleanly
+            if fs::metadata(&path)?.len() > 0 {
+                return Err(Error::FileMismatch(format!(
+                    "{} has writes that are not checkpointed",
+                    path.display()
+                )));
+            }
+            continue;
+        } else if file_type.is_file() {
+            let relative_path = path
+                .strip_prefix(root)
+                .expect("FATAL: file is not under the snapshot directory")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(relative_path, hash_file(&path)?);
+        } else {
+            return Err(Error::FileMismatch(format!(
+                "{} is not a regular file",
+                path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Hash every file in the snapshot's `burnchain` and `chainstate` directories
+pub fn hash_snapshot_files(
+    snapshot_dir: &Path,
+) -> Result<BTreeMap<String, Sha512Trunc256Sum>, Error> {
+    let mut files = BTreeMap::new();
+    for dir in SNAPSHOT_DIRS.iter() {
+        let path = snapshot_dir.join(dir);
+        if path.is_dir() {
+            hash_dir(snapshot_dir, &path, &mut files)?;
+        }
+    }
+    Ok(files)
+}
+
+fn sortition_db_path(snapshot_dir: &Path) -> String {
+    snapshot_dir
+        .join("burnchain")
+        .join("sortition")
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Compute the root hash of the chainstate MARF at `path` as of `block_id`
+fn get_marf_root_hash(
+    path: &Path,
+    block_id: &StacksBlockId,
+    marf_opts: &MARFOpenOpts,
+) -> Result<TrieHash, Error> {
+    // the chainstate always keeps its tries in external blob files, whatever the options say
+    // (see `StacksChainState::open_index()` and `MarfedKV::setup_db()`)
+    let mut marf_opts = marf_opts.clone();
+    marf_opts.external_blobs = true;
+    let storage = TrieFileStorage::open_readonly(&path.to_string_lossy(), marf_opts)?;
+    let mut marf = MARF::from_storage(storage);
+    Ok(marf.get_root_hash_at(block_id)?)
+}
+
+/// Read the state of the databases in a snapshot directory, and recompute its MARF roots.
+/// The returned manifest has no files.
+fn read_snapshot_state(
+    snapshot_dir: &Path,
+    pox_constants: PoxConstants,
+    marf_opts: &MARFOpenOpts,
+) -> Result<SnapshotManifest, Error> {
+    let mut sortdb = SortitionDB::open(&sortition_db_path(snapshot_dir), false, pox_constants)?;
+    let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+    let sortition_root = sortdb.marf.get_root_hash_at(&burn_tip.sortition_id)?;
+    if sortition_root != burn_tip.index_root {
+        return Err(Error::StateMismatch(format!(
+            "sortition DB MARF root {} does not match snapshot index root {}",
+            &sortition_root, &burn_tip.index_root
+        )));
+    }
+
+    // the Stacks tip must have won its sortition
+    let stacks_tip_ch = burn_tip.canonical_stacks_tip_consensus_hash.clone();
+    let stacks_tip_bhh = burn_tip.canonical_stacks_tip_hash.clone();
+    let stacks_tip_sn = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &stacks_tip_ch)?
+        .ok_or_else(|| {
+        Error::StateMismatch(format!("no sortition for Stacks tip {}", &stacks_tip_ch))
+    })?;
+    if !stacks_tip_sn.sortition || stacks_tip_sn.winning_stacks_block_hash != stacks_tip_bhh {
+        return Err(Error::StateMismatch(format!(
+            "Stacks tip {}/{} did not win its sortition",
+            &stacks_tip_ch, &stacks_tip_bhh
+        )));
+    }
+
+    // the Stacks tip's header must hash to the committed block hash, and commit to the roots of
+    // the snapshot's MARFs
+    let chainstate_path = snapshot_dir.join("chainstate");
+    let header_index_path = StacksChainState::header_index_root_path(chainstate_path.clone());
+    let headers_conn = sqlite_open(&header_index_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)
+        .map_err(DBError::SqliteError)?;
+    let stacks_tip_header = StacksChainState::get_anchored_block_header_info(
+        &headers_conn,
+        &stacks_tip_ch,
+        &stacks_tip_bhh,
+    )?
+    .ok_or_else(|| {
+        Error::StateMismatch(format!(
+            "no header for Stacks tip {}/{}",
+            &stacks_tip_ch, &stacks_tip_bhh
+        ))
+    })?;
+    if stacks_tip_header.anchored_header.block_hash() != stacks_tip_bhh {
+        return Err(Error::StateMismatch(format!(
+            "header for Stacks tip {}/{} has the wrong hash",
+            &stacks_tip_ch, &stacks_tip_bhh
+        )));
+    }
+
+    let stacks_tip_id = StacksBlockId::new(&stacks_tip_ch, &stacks_tip_bhh);
+    let index_root = get_marf_root_hash(&header_index_path, &stacks_tip_id, marf_opts)?;
+    if index_root != stacks_tip_header.index_root {
+        return Err(Error::StateMismatch(format!(
+            "header index MARF root {} does not match Stacks tip index root {}",
+            &index_root, &stacks_tip_header.index_root
+        )));
+    }
+    let state_root = get_marf_root_hash(
+        &StacksChainState::vm_state_index_marf_path(chainstate_path),
+        &stacks_tip_id,
+        marf_opts,
+    )?;
+    if state_root != stacks_tip_header.anchored_header.state_index_root {
+        return Err(Error::StateMismatch(format!(
+            "Clarity state MARF root {} does not match Stacks tip state root {}",
+            &state_root, &stacks_tip_header.anchored_header.state_index_root
+        )));
+    }
+
+    Ok(SnapshotManifest {
+        burn_block_height: burn_tip.block_height,
+        burn_header_hash: burn_tip.burn_header_hash,
+        sortition_root,
+        stacks_tip_consensus_hash: stacks_tip_ch,
+        stacks_tip_block_hash: stacks_tip_bhh,
+        stacks_tip_index_root: index_root,
+        stacks_tip_state_root: state_root,
+        files: BTreeMap::new(),
+    })
+}
+
+/// Make the manifest for a snapshot directory.  The databases in it must not be in use.
+pub fn make_snapshot_manifest(
+    snapshot_dir: &Path,
+    pox_constants: PoxConstants,
+    marf_opts: &MARFOpenOpts,
+) -> Result<SnapshotManifest, Error> {
+    // hash the files before opening any databases, since opening them can create files
+    let files = hash_snapshot_files(snapshot_dir)?;
+    let mut manifest = read_snapshot_state(snapshot_dir, pox_constants, marf_opts)?;
+    manifest.files = files;
+    Ok(manifest)
+}
+
+/// Verify a snapshot directory: its manifest must be signed by `trusted_signer`, its files must
+/// match the manifest, and its databases must have the MARF roots the manifest lists.
+/// The caller must still check that the snapshot's burnchain block is on its burnchain, with
+/// `SnapshotManifest::check_burnchain_header()`.
+pub fn verify_snapshot(
+    snapshot_dir: &Path,
+    trusted_signer: &StacksPublicKey,
+    pox_constants: PoxConstants,
+    marf_opts: &MARFOpenOpts,
+) -> Result<SnapshotManifest, Error> {
+    let signed_manifest = SignedSnapshotManifest::load(snapshot_dir)?;
+    signed_manifest.verify(trusted_signer)?;
+    let manifest = signed_manifest.manifest;
+
+    let files = hash_snapshot_files(snapshot_dir)?;
+    for (path, hash) in manifest.files.iter() {
+        match files.get(path) {
+            Some(file_hash) if file_hash == hash => {}
+            Some(_) => {
+                return Err(Error::FileMismatch(format!("{} has the wrong hash", path)));
+            }
+            None => {
+                return Err(Error::FileMismatch(format!("{} is missing", path)));
+            }
+        }
+    }
+    if let Some(path) = files
+        .keys()
+        .find(|path| !manifest.files.contains_key(*path))
+    {
+        return Err(Error::FileMismatch(format!(
+            "{} is not in the manifest",
+            path
+        )));
+    }
+
+    let state = read_snapshot_state(snapshot_dir, pox_constants, marf_opts)?;
+    if state.burn_block_height != manifest.burn_block_height
+        || state.burn_header_hash != manifest.burn_header_hash
+        || state.sortition_root != manifest.sortition_root
+        || state.stacks_tip_consensus_hash != manifest.stacks_tip_consensus_hash
+        || state.stacks_tip_block_hash != manifest.stacks_tip_block_hash
+        || state.stacks_tip_index_root != manifest.stacks_tip_index_root
+        || state.stacks_tip_state_root != manifest.stacks_tip_state_root
+    {
+        return Err(Error::StateMismatch(format!(
+            "snapshot state {:?} does not match manifest",
+            &state
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Copy the files of a verified snapshot into `dest_dir`, which is laid out like the snapshot
+/// directory.  Fails without copying anything if any of the files already exist.
+pub fn install_snapshot(
+    snapshot_dir: &Path,
+    manifest: &SnapshotManifest,
+    dest_dir: &Path,
+) -> Result<(), Error> {
+    for path in manifest.files.keys() {
+        let relative_path = Path::new(path);
+        if !relative_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(Error::BadManifest(format!("invalid file path {}", path)));
+        }
+        let dest_path = dest_dir.join(relative_path);
+        if dest_path.exists() {
+            return Err(Error::DestinationExists(dest_path));
+        }
+    }
+    for path in manifest.files.keys() {
+        let dest_path = dest_dir.join(path);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(snapshot_dir.join(path), &dest_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::burnchains::Burnchain;
+    use crate::chainstate::stacks::boot::test::instantiate_pox_peer;
+    use crate::core::BITCOIN_REGTEST_FIRST_BLOCK_HASH;
+
+    fn copy_dir(src: &Path, dest: &Path) {
+        fs::create_dir_all(dest).unwrap();
+        for entry in fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            let dest_path = dest.join(path.file_name().unwrap());
+            if path.is_dir() {
+                copy_dir(&path, &dest_path);
+            } else {
+                fs::copy(&path, &dest_path).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_verify_and_install() {
+        let mut burnchain = Burnchain::default_unittest(
+            0,
+            &BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap(),
+        );
+        burnchain.pox_constants.reward_cycle_length = 5;
+        burnchain.pox_constants.prepare_length = 2;
+        burnchain.pox_constants.anchor_threshold = 1;
+
+        let (mut peer, _keys) = instantiate_pox_peer(&burnchain, function_name!(), 6030);
+        let mut coinbase_nonce = 0;
+        peer.tenure_with_txs(&[], &mut coinbase_nonce);
+        let tip_id = peer.tenure_with_txs(&[], &mut coinbase_nonce);
+
+        let sortition_path = PathBuf::from(peer.config.burnchain.get_db_path());
+        let chainstate_path = PathBuf::from(peer.chainstate_path.clone());
+        // next to the chainstate, not inside it, so copying it does not copy the snapshot
+        let test_dir = chainstate_path.parent().unwrap();
+        let snapshot_dir = test_dir.join("snapshot");
+        let install_dir = test_dir.join("install");
+        let pox_constants = burnchain.pox_constants.clone();
+        drop(peer);
+
+        for dir in [&snapshot_dir, &install_dir] {
+            if dir.exists() {
+                fs::remove_dir_all(dir).unwrap();
+            }
+        }
+        copy_dir(
+            &sortition_path,
+            &snapshot_dir.join("burnchain").join("sortition"),
+        );
+        copy_dir(&chainstate_path, &snapshot_dir.join("chainstate"));
+
+        let marf_opts = MARFOpenOpts::default();
+        let manifest =
+            make_snapshot_manifest(&snapshot_dir, pox_constants.clone(), &marf_opts).unwrap();
+        assert_eq!(
+            StacksBlockId::new(
+                &manifest.stacks_tip_consensus_hash,
+                &manifest.stacks_tip_block_hash
+            ),
+            tip_id
+        );
+        assert!(manifest.files.contains_key("chainstate/vm/index.sqlite"));
+
+        let signer = StacksPrivateKey::new();
+        manifest
+            .clone()
+            .sign(&signer)
+            .unwrap()
+            .store(&snapshot_dir)
+            .unwrap();
+
+        // only the trusted signer's manifest is accepted
+        match verify_snapshot(
+            &snapshot_dir,
+            &StacksPublicKey::from_private(&StacksPrivateKey::new()),
+            pox_constants.clone(),
+            &marf_opts,
+        ) {
+            Err(Error::BadSignature) => {}
+            x => panic!("expected BadSignature, got {:?}", x),
+        }
+
+        let verified_manifest = verify_snapshot(
+            &snapshot_dir,
+            &StacksPublicKey::from_private(&signer),
+            pox_constants.clone(),
+            &marf_opts,
+        )
+        .unwrap();
+        assert_eq!(verified_manifest, manifest);
+
+        assert!(manifest
+            .check_burnchain_header(&manifest.burn_header_hash)
+            .is_ok());
+        match manifest.check_burnchain_header(&BurnchainHeaderHash([0x01; 32])) {
+            Err(Error::BurnchainMismatch(height)) => {
+                assert_eq!(height, manifest.burn_block_height)
+            }
+            x => panic!("expected BurnchainMismatch, got {:?}", x),
+        }
+
+        install_snapshot(&snapshot_dir, &manifest, &install_dir).unwrap();
+        assert_eq!(hash_snapshot_files(&install_dir).unwrap(), manifest.files);
+
+        // a snapshot is never installed over existing state
+        match install_snapshot(&snapshot_dir, &manifest, &install_dir) {
+            Err(Error::DestinationExists(_)) => {}
+            x => panic!("expected DestinationExists, got {:?}", x),
+        }
+
+        // tampered files are detected before any database is opened
+        let clarity_marf_path = snapshot_dir.join("chainstate/vm/clarity/marf.sqlite");
+        let mut bytes = fs::read(&clarity_marf_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&clarity_marf_path, bytes).unwrap();
+        match verify_snapshot(
+            &snapshot_dir,
+            &StacksPublicKey::from_private(&signer),
+            pox_constants,
+            &marf_opts,
+        ) {
+            Err(Error::FileMismatch(path)) => {
+                assert!(path.starts_with("chainstate/vm/clarity/marf.sqlite"))
+            }
+            x => panic!("expected FileMismatch, got {:?}", x),
+        }
+    }
+}
@@ -65,7 +65,10 @@ use crate::clarity_vm::database::SortitionDBRef;
 use crate::core::mempool::{MemPoolDB, MAXIMUM_MEMPOOL_TX_CHAINING};
 use crate::core::*;
 use crate::cost_estimates::EstimatorError;
-use crate::monitoring::{set_last_block_transaction_count, set_last_execution_cost_observed};
+use crate::monitoring::{
+    observe_stx_block_processing_time, set_last_block_transaction_count,
+    set_last_execution_cost_observed, update_epoch_label,
+};
 use crate::net::relay::Relayer;
 use crate::net::{BlocksInvData, Error as net_error};
 use crate::util_lib::boot::boot_code_id;
@@ -145,6 +148,8 @@ pub enum MemPoolRejection {
     DBError(db_error),
     EstimatorError(EstimatorError),
     TemporarilyBlacklisted,
+    /// The transaction fails the static checks for known-problematic transactions
+    Problematic(String),
     Other(String),
 }
 
@@ -206,92 +211,109 @@ impl BlockEventDispatcher for DummyEventDispatcher {
 }
 
 impl MemPoolRejection {
+    /// The code identifying the reason for this rejection, as reported to RPC clients and in
+    /// metrics
+    pub fn reason_code(&self) -> &'static str {
+        use self::MemPoolRejection::*;
+        match self {
+            SerializationFailure(_) => "Serialization",
+            DeserializationFailure(_) => "Deserialization",
+            TooMuchChaining { .. } => "TooMuchChaining",
+            BadTransactionVersion => "BadTransactionVersion",
+            FailedToValidate(_) => "SignatureValidation",
+            FeeTooLow(..) => "FeeTooLow",
+            TransferRecipientIsSender(_) => "TransferRecipientCannotEqualSender",
+            TransferAmountMustBePositive => "TransferAmountMustBePositive",
+            BadNonces(_) => "BadNonce",
+            NotEnoughFunds(..) => "NotEnoughFunds",
+            EstimatorError(_) => "EstimatorError",
+            NoSuchContract => "NoSuchContract",
+            NoSuchPublicFunction => "NoSuchPublicFunction",
+            BadFunctionArgument(_) => "BadFunctionArgument",
+            ConflictingNonceInMempool => "ConflictingNonceInMempool",
+            ContractAlreadyExists(_) => "ContractAlreadyExists",
+            PoisonMicroblocksDoNotConflict => "PoisonMicroblocksDoNotConflict",
+            NoAnchorBlockWithPubkeyHash(_) => "PoisonMicroblockHasUnknownPubKeyHash",
+            InvalidMicroblocks => "PoisonMicroblockIsInvalid",
+            BadAddressVersionByte => "BadAddressVersionByte",
+            NoCoinbaseViaMempool => "NoCoinbaseViaMempool",
+            // this should never happen via the RPC interface
+            NoSuchChainTip(..) => "ServerFailureNoSuchChainTip",
+            DBError(_) => "ServerFailureDatabase",
+            TemporarilyBlacklisted => "TemporarilyBlacklisted",
+            Problematic(_) => "Problematic",
+            Other(_) => "ServerFailureOther",
+        }
+    }
+
     pub fn into_json(self, txid: &Txid) -> serde_json::Value {
         use self::MemPoolRejection::*;
-        let (reason_code, reason_data) = match self {
-            SerializationFailure(e) => ("Serialization", Some(json!({"message": e.to_string()}))),
-            DeserializationFailure(e) => {
-                ("Deserialization", Some(json!({"message": e.to_string()})))
-            }
+        let reason_code = self.reason_code();
+        let reason_data = match self {
+            SerializationFailure(e) => Some(json!({"message": e.to_string()})),
+            DeserializationFailure(e) => Some(json!({"message": e.to_string()})),
             TooMuchChaining {
                 max_nonce,
                 actual_nonce,
                 principal,
                 is_origin,
                 ..
-            } => (
-                "TooMuchChaining",
-                Some(
-                    json!({"message": "Nonce would exceed chaining limit in mempool",
-                                "expected": max_nonce,
-                                "actual": actual_nonce,
-                                "principal": principal.to_string(),
-                                "is_origin": is_origin
-                    }),
-                ),
-            ),
-            BadTransactionVersion => ("BadTransactionVersion", None),
-            FailedToValidate(e) => (
-                "SignatureValidation",
-                Some(json!({"message": e.to_string()})),
+            } => Some(
+                json!({"message": "Nonce would exceed chaining limit in mempool",
+                            "expected": max_nonce,
+                            "actual": actual_nonce,
+                            "principal": principal.to_string(),
+                            "is_origin": is_origin
+                }),
             ),
-            FeeTooLow(actual, expected) => (
-                "FeeTooLow",
-                Some(json!({
-                                                "expected": expected,
-                                                "actual": actual})),
-            ),
-            TransferRecipientIsSender(recipient) => (
-                "TransferRecipientCannotEqualSender",
-                Some(json!({"recipient": recipient.to_string()})),
-            ),
-            TransferAmountMustBePositive => ("TransferAmountMustBePositive", None),
+            FailedToValidate(e) => Some(json!({"message": e.to_string()})),
+            FeeTooLow(actual, expected) => Some(json!({
+                                            "expected": expected,
+                                            "actual": actual})),
+            TransferRecipientIsSender(recipient) => {
+                Some(json!({"recipient": recipient.to_string()}))
+            }
             BadNonces(TransactionNonceMismatch {
                 expected,
                 actual,
                 principal,
                 is_origin,
                 ..
-            }) => (
-                "BadNonce",
-                Some(json!({
-                     "expected": expected,
-                     "actual": actual,
-                     "principal": principal.to_string(),
-                     "is_origin": is_origin})),
-            ),
-            NotEnoughFunds(expected, actual) => (
-                "NotEnoughFunds",
-                Some(json!({
-                    "expected": format!("0x{}", to_hex(&expected.to_be_bytes())),
-                    "actual": format!("0x{}", to_hex(&actual.to_be_bytes()))
-                })),
-            ),
-            EstimatorError(e) => ("EstimatorError", Some(json!({"message": e.to_string()}))),
-            NoSuchContract => ("NoSuchContract", None),
-            NoSuchPublicFunction => ("NoSuchPublicFunction", None),
-            BadFunctionArgument(e) => (
-                "BadFunctionArgument",
-                Some(json!({"message": e.to_string()})),
-            ),
-            ConflictingNonceInMempool => ("ConflictingNonceInMempool", None),
-            ContractAlreadyExists(id) => (
-                "ContractAlreadyExists",
-                Some(json!({ "contract_identifier": id.to_string() })),
-            ),
-            PoisonMicroblocksDoNotConflict => ("PoisonMicroblocksDoNotConflict", None),
-            NoAnchorBlockWithPubkeyHash(_h) => ("PoisonMicroblockHasUnknownPubKeyHash", None),
-            InvalidMicroblocks => ("PoisonMicroblockIsInvalid", None),
-            BadAddressVersionByte => ("BadAddressVersionByte", None),
-            NoCoinbaseViaMempool => ("NoCoinbaseViaMempool", None),
-            // this should never happen via the RPC interface
-            NoSuchChainTip(..) => ("ServerFailureNoSuchChainTip", None),
-            DBError(e) => (
-                "ServerFailureDatabase",
-                Some(json!({"message": e.to_string()})),
-            ),
-            TemporarilyBlacklisted => ("TemporarilyBlacklisted", None),
-            Other(s) => ("ServerFailureOther", Some(json!({ "message": s }))),
+            }) => Some(json!({
+                 "expected": expected,
+                 "actual": actual,
+                 "principal": principal.to_string(),
+                 "is_origin": is_origin})),
+            NotEnoughFunds(expected, actual) => Some(json!({
+                "expected": format!("0x{}", to_hex(&expected.to_be_bytes())),
+                "actual": format!("0x{}", to_hex(&actual.to_be_bytes()))
+            })),
+            EstimatorError(e) => Some(json!({"message": e.to_string()})),
+            BadFunctionArgument(e) => Some(json!({
+                "message": e.to_string(),
+                "diagnostic": e.diagnostic
+            })),
+            ContractAlreadyExists(id) => Some(json!({ "contract_identifier": id.to_string() })),
+            DBError(e) => Some(json!({"message": e.to_string()})),
+            NoAnchorBlockWithPubkeyHash(pubkey_hash) => {
+                Some(json!({ "pubkey_hash": pubkey_hash.to_string() }))
+            }
+            NoSuchChainTip(consensus_hash, block_hash) => Some(json!({
+                "consensus_hash": consensus_hash.to_string(),
+                "block_hash": block_hash.to_string()
+            })),
+            Problematic(s) => Some(json!({ "message": s })),
+            Other(s) => Some(json!({ "message": s })),
+            BadTransactionVersion
+            | TransferAmountMustBePositive
+            | NoSuchContract
+            | NoSuchPublicFunction
+            | ConflictingNonceInMempool
+            | PoisonMicroblocksDoNotConflict
+            | InvalidMicroblocks
+            | BadAddressVersionByte
+            | NoCoinbaseViaMempool
+            | TemporarilyBlacklisted => None,
         };
         let mut result = json!({
             "txid": format!("{}", txid.to_hex()),
@@ -747,6 +769,74 @@ impl StacksChainState {
         StacksChainState::free_block(blocks_path, consensus_hash, &block_header.block_hash())
     }
 
+    /// Prune the bodies of processed, non-orphaned anchored blocks whose sortitions happened
+    /// before `burn_height`, along with the bodies of the microblocks they confirmed.
+    /// Block headers, staging metadata, and the MARF are kept, so the node can still validate
+    /// new blocks and serve headers, but it can no longer serve or replay the pruned blocks.
+    /// Block files are truncated (like invalid blocks are) so the downloader does not fetch them
+    /// again.
+    /// Returns the number of anchored blocks and microblocks pruned.
+    pub fn prune_blocks(&mut self, burn_height: u64) -> Result<(u64, u64), Error> {
+        let sql = "SELECT staging_blocks.* FROM staging_blocks \
+                   JOIN block_headers ON staging_blocks.index_block_hash = block_headers.index_block_hash \
+                   WHERE staging_blocks.processed = 1 AND staging_blocks.orphaned = 0 AND staging_blocks.pruned = 0 \
+                   AND block_headers.burn_header_height < ?1";
+        let args: &[&dyn ToSql] = &[&u64_to_sql(burn_height)?];
+        let candidates = query_rows::<StagingBlock, _>(self.db(), sql, args)?;
+        if candidates.len() == 0 {
+            return Ok((0, 0));
+        }
+
+        // truncate the block files first, so that if we crash before marking them as pruned,
+        // the next pass will simply find them again.
+        for candidate in candidates.iter() {
+            let block_path = StacksChainState::get_block_path(
+                &self.blocks_path,
+                &candidate.consensus_hash,
+                &candidate.anchored_block_hash,
+            )?;
+            StacksChainState::atomic_file_write(&block_path, &[])?;
+        }
+
+        let mut num_microblocks = 0;
+        let tx = self.db_tx_begin()?;
+        for candidate in candidates.iter() {
+            tx.execute(
+                "UPDATE staging_blocks SET pruned = 1 WHERE consensus_hash = ?1 AND anchored_block_hash = ?2",
+                &[&candidate.consensus_hash as &dyn ToSql, &candidate.anchored_block_hash],
+            )?;
+
+            if candidate.parent_microblock_hash == EMPTY_MICROBLOCK_PARENT_HASH {
+                continue;
+            }
+
+            let parent_index_block_hash = StacksBlockId::new(
+                &candidate.parent_consensus_hash,
+                &candidate.parent_anchored_block_hash,
+            );
+            let sql = "SELECT microblock_hash FROM staging_microblocks \
+                       WHERE index_block_hash = ?1 AND sequence <= ?2 AND processed = 1 AND pruned = 0";
+            let args: &[&dyn ToSql] = &[&parent_index_block_hash, &candidate.parent_microblock_seq];
+            let microblock_hashes: Vec<BlockHeaderHash> =
+                query_row_columns(&tx, sql, args, "microblock_hash")?;
+
+            for microblock_hash in microblock_hashes.iter() {
+                tx.execute(
+                    "DELETE FROM staging_microblocks_data WHERE block_hash = ?1",
+                    &[microblock_hash],
+                )?;
+                tx.execute(
+                    "UPDATE staging_microblocks SET pruned = 1 WHERE index_block_hash = ?1 AND microblock_hash = ?2",
+                    &[&parent_index_block_hash as &dyn ToSql, microblock_hash],
+                )?;
+                num_microblocks += 1;
+            }
+        }
+        tx.commit()?;
+
+        Ok((candidates.len() as u64, num_microblocks))
+    }
+
     /// Get a list of all anchored blocks' hashes, and their burnchain headers
     pub fn list_blocks(
         blocks_conn: &DBConn,
@@ -1863,6 +1953,26 @@ impl StacksChainState {
             })
     }
 
+    /// Has the given anchored block's data been pruned?
+    pub fn is_block_pruned(
+        blocks_conn: &DBConn,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<bool, Error> {
+        let sql = "SELECT pruned FROM staging_blocks WHERE index_block_hash = ?1";
+        let pruned: Option<i64> = query_row(blocks_conn, sql, &[index_block_hash])?;
+        Ok(pruned.unwrap_or(0) != 0)
+    }
+
+    /// Has the given microblock's data been pruned?
+    pub fn is_microblock_pruned(
+        blocks_conn: &DBConn,
+        index_microblock_hash: &StacksBlockId,
+    ) -> Result<bool, Error> {
+        let sql = "SELECT pruned FROM staging_microblocks WHERE index_microblock_hash = ?1";
+        let pruned: Option<i64> = query_row(blocks_conn, sql, &[index_microblock_hash])?;
+        Ok(pruned.unwrap_or(0) != 0)
+    }
+
     /// Given an anchor block's index hash, does it confirm any microblocks?
     /// Due to the way we process microblocks -- i.e. all microblocks between a parent/child anchor
     /// block are processed atomically -- it is sufficient to check that there exists a microblock
@@ -2116,7 +2226,7 @@ impl StacksChainState {
             reward_cycle,
         );
 
-        let sql = "SELECT staging_blocks.consensus_hash, staging_blocks.processed, staging_blocks.orphaned, staging_microblocks.processed, staging_microblocks.orphaned \
+        let sql = "SELECT staging_blocks.consensus_hash, staging_blocks.processed, staging_blocks.orphaned, staging_microblocks.processed, staging_microblocks.orphaned, staging_blocks.pruned \
                    FROM staging_blocks LEFT JOIN staging_microblocks \
                    ON staging_blocks.parent_microblock_hash = staging_microblocks.microblock_hash \
                    WHERE staging_blocks.height >= ?1 AND staging_blocks.height <= ?2";
@@ -2141,6 +2251,12 @@ impl StacksChainState {
             let block_orphaned: i64 = row.get_unwrap(2);
             let microblock_processed_opt: Option<i64> = row.get_unwrap(3);
             let microblock_orphaned_opt: Option<i64> = row.get_unwrap(4);
+            let block_pruned: i64 = row.get_unwrap(5);
+
+            // we can't serve pruned blocks or the microblocks they confirm
+            if block_pruned != 0 {
+                continue;
+            }
 
             if block_processed != 0 && block_orphaned == 0 {
                 block_bits[index] = true;
@@ -2956,8 +3072,19 @@ impl StacksChainState {
         blocks_path: &str,
         index_block_hash: &StacksBlockId,
     ) -> Result<ExtendedStacksHeader, Error> {
-        let header = StacksChainState::load_block_header_indexed(blocks_path, index_block_hash)?
-            .ok_or(Error::NoSuchBlockError)?;
+        // pruned blocks only have their headers in the headers DB
+        let header =
+            match StacksChainState::load_block_header_indexed(blocks_path, index_block_hash)? {
+                Some(header) => header,
+                None => {
+                    StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        db,
+                        index_block_hash,
+                    )?
+                    .ok_or(Error::NoSuchBlockError)?
+                    .anchored_header
+                }
+            };
 
         let header_info = StacksChainState::load_staging_block_info(db, index_block_hash)?
             .ok_or(Error::NoSuchBlockError)?;
@@ -5310,6 +5437,7 @@ impl StacksChainState {
             SortitionDB::get_ast_rules(burn_dbconn.tx(), chain_tip_burn_header_height.into())?;
 
         let mainnet = chainstate_tx.get_config().mainnet;
+        let append_start = get_epoch_time_ms();
         let next_block_height = block.header.total_work.work;
 
         // NEW in 2.05
@@ -5687,10 +5815,18 @@ impl StacksChainState {
         )
         .expect("FATAL: failed to advance chain tip");
 
-        chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
+        chainstate_tx.log_transactions_processed(
+            &new_tip.index_block_hash(),
+            new_tip.stacks_block_height,
+            &tx_receipts,
+        );
 
         set_last_block_transaction_count(block.txs.len() as u64);
         set_last_execution_cost_observed(&block_execution_cost, &block_limit);
+        update_epoch_label(evaluated_epoch);
+        observe_stx_block_processing_time(
+            get_epoch_time_ms().saturating_sub(append_start) as f64 / 1000.0,
+        );
 
         let epoch_receipt = StacksEpochReceipt {
             header: new_tip,
@@ -10280,6 +10416,73 @@ pub mod test {
             );
             assert_eq!(original_blocks_inv, blocks_inv);
         }
+
+        // prune the block data from before reward cycle 2
+        let prune_height = peer_config.burnchain.reward_cycle_to_block_height(2);
+        let (num_blocks, num_microblocks) = chainstate.prune_blocks(prune_height).unwrap();
+        assert!(num_blocks > 0);
+        assert!(num_microblocks > 0);
+
+        // nothing left to prune
+        assert_eq!(chainstate.prune_blocks(prune_height).unwrap(), (0, 0));
+
+        for block_height in 0..(header_hashes.len() as u64) {
+            let (consensus_hash, hdr_hash) = match &header_hashes[block_height as usize] {
+                (ch, Some(hdr_hash)) => (ch, hdr_hash),
+                _ => continue,
+            };
+            let index_block_hash = StacksBlockId::new(consensus_hash, hdr_hash);
+            let orphaned = block_height % 3 == 0;
+            let pruned = block_height < prune_height && !orphaned;
+            assert_eq!(
+                StacksChainState::is_block_pruned(chainstate.db(), &index_block_hash).unwrap(),
+                pruned
+            );
+            if pruned {
+                assert!(StacksChainState::load_block(
+                    &chainstate.blocks_path,
+                    consensus_hash,
+                    hdr_hash
+                )
+                .unwrap()
+                .is_none());
+
+                // headers are still available
+                let extended_header = StacksChainState::read_extended_header(
+                    chainstate.db(),
+                    &chainstate.blocks_path,
+                    &index_block_hash,
+                )
+                .unwrap();
+                assert_eq!(extended_header.header.block_hash(), *hdr_hash);
+            }
+        }
+
+        // pruned blocks and their microblocks are no longer in the inventory
+        for i in 0..total_reward_cycles {
+            let start_range = peer_config.burnchain.reward_cycle_to_block_height(i);
+            let end_range = cmp::min(
+                header_hashes.len() as u64,
+                peer_config.burnchain.reward_cycle_to_block_height(i + 1),
+            );
+            let blocks_inv = chainstate
+                .get_blocks_inventory_for_reward_cycle(
+                    &peer_config.burnchain,
+                    i,
+                    &header_hashes[(start_range as usize)..(end_range as usize)],
+                )
+                .unwrap();
+
+            let original_blocks_inv = chainstate
+                .get_blocks_inventory(&header_hashes[(start_range as usize)..(end_range as usize)])
+                .unwrap();
+
+            assert_eq!(original_blocks_inv, blocks_inv);
+            for block_height in start_range..cmp::min(end_range, prune_height) {
+                assert!(!blocks_inv.has_ith_block((block_height - start_range) as u16));
+                assert!(!blocks_inv.has_ith_microblock_stream((block_height - start_range) as u16));
+            }
+        }
     }
 
     #[test]
@@ -28,8 +28,10 @@ use clarity::vm::clarity::TransactionConnection;
 use clarity::vm::contexts::OwnedEnvironment;
 use clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
 use clarity::vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
+    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, StoreType,
+    NULL_BURN_STATE_DB,
 };
+use clarity::vm::errors::Error as clarity_interpreter_error;
 use clarity::vm::events::*;
 use clarity::vm::representations::{ClarityName, ContractName};
 use clarity::vm::types::TupleData;
@@ -46,7 +48,9 @@ use stacks_common::util::hash::{hex_bytes, to_hex};
 use crate::burnchains::bitcoin::address::{BitcoinAddress, LegacyBitcoinAddress};
 use crate::burnchains::{Address, Burnchain, BurnchainParameters, PoxConstants};
 use crate::chainstate::burn::db::sortdb::{BlockHeaderCache, SortitionDB, SortitionDBConn, *};
-use crate::chainstate::burn::operations::{DelegateStxOp, StackStxOp, TransferStxOp};
+use crate::chainstate::burn::operations::{
+    BlockstackOperationType, DelegateStxOp, StackStxOp, TransferStxOp,
+};
 use crate::chainstate::burn::{ConsensusHash, ConsensusHashExtensions};
 use crate::chainstate::stacks::address::StacksAddressExtensions;
 use crate::chainstate::stacks::boot::*;
@@ -69,7 +73,7 @@ use crate::clarity_vm::clarity::{
     ClarityBlockConnection, ClarityConnection, ClarityInstance, ClarityReadOnlyConnection,
     Error as clarity_error, PreCommitClarityBlock,
 };
-use crate::clarity_vm::database::marf::MarfedKV;
+use crate::clarity_vm::database::marf::{get_data_map_key_candidates, MarfedKV};
 use crate::clarity_vm::database::HeadersDBConn;
 use crate::core::*;
 use crate::monitoring;
@@ -77,9 +81,10 @@ use crate::net::atlas::BNS_CHARS_REGEX;
 use crate::net::Error as net_error;
 use crate::util_lib::boot::{boot_code_acc, boot_code_addr, boot_code_id, boot_code_tx_auth};
 use crate::util_lib::db::{
-    query_count, query_row, tx_begin_immediate, tx_busy_handler, DBConn, DBTx, Error as db_error,
-    FromColumn, FromRow, IndexDBConn, IndexDBTx,
+    query_count, query_row, query_rows, tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn,
+    DBTx, Error as db_error, FromColumn, FromRow, IndexDBConn, IndexDBTx,
 };
+use crate::util_lib::migrations::Migration;
 
 pub mod accounts;
 pub mod blocks;
@@ -117,9 +122,203 @@ pub struct StacksChainState {
     pub root_path: String,
     pub unconfirmed_state: Option<UnconfirmedState>,
     pub fault_injection: StacksChainStateFaults,
+    /// Whether to record the outcome of each processed transaction in the `transactions` table,
+    /// so it can be looked up by txid. Defaults to whether `STACKS_TRANSACTION_LOG=1` is set.
+    pub txindex: bool,
+    /// Whether to record which accounts each processed transaction involved in the
+    /// `account_transactions` table, so an account's history can be looked up
+    pub account_txindex: bool,
     marf_opts: Option<MARFOpenOpts>,
 }
 
+/// A processed transaction, as recorded in the `transactions` table when `txindex` is on
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionLogEntry {
+    pub txid: Txid,
+    /// The block that processed it, directly or in one of its parent microblocks
+    pub index_block_hash: StacksBlockId,
+    pub tx_hex: String,
+    /// The transaction's result, formatted as a Clarity value
+    pub result: String,
+    // The rest are `None` for transactions logged before chainstate schema version 4
+    /// The consensus-serialized result, hex-encoded
+    pub result_hex: Option<String>,
+    pub tx_index: Option<u32>,
+    pub microblock_hash: Option<BlockHeaderHash>,
+    pub post_condition_aborted: Option<bool>,
+    pub vm_error: Option<String>,
+    pub execution_cost: Option<ExecutionCost>,
+    /// The transaction's events, in the format sent to event observers
+    pub events: Option<serde_json::Value>,
+}
+
+impl FromRow<TransactionLogEntry> for TransactionLogEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<TransactionLogEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let tx_hex: String = row.get_unwrap("tx_hex");
+        let result: String = row.get_unwrap("result");
+        let result_hex: Option<String> = row.get_unwrap("result_hex");
+        let tx_index: Option<u32> = row.get_unwrap("tx_index");
+        let microblock_hash_hex: Option<String> = row.get_unwrap("microblock_hash");
+        let microblock_hash = match microblock_hash_hex {
+            Some(hex) => Some(BlockHeaderHash::from_hex(&hex).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let post_condition_aborted: Option<bool> = row.get_unwrap("post_condition_aborted");
+        let vm_error: Option<String> = row.get_unwrap("vm_error");
+        let execution_cost_json: Option<String> = row.get_unwrap("execution_cost");
+        let execution_cost = match execution_cost_json {
+            Some(json) => Some(serde_json::from_str(&json).map_err(db_error::SerializationError)?),
+            None => None,
+        };
+        let events_json: Option<String> = row.get_unwrap("events");
+        let events = match events_json {
+            Some(json) => Some(serde_json::from_str(&json).map_err(db_error::SerializationError)?),
+            None => None,
+        };
+        Ok(TransactionLogEntry {
+            txid,
+            index_block_hash,
+            tx_hex,
+            result,
+            result_hex,
+            tx_index,
+            microblock_hash,
+            post_condition_aborted,
+            vm_error,
+            execution_cost,
+            events,
+        })
+    }
+}
+
+/// How an account was involved in a transaction, as recorded in the `account_transactions`
+/// table when `account_txindex` is on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountTransactionRole {
+    /// The account sent the transaction
+    Origin,
+    /// The account paid the transaction's fee
+    Sponsor,
+    /// The account received STX or tokens from the transaction
+    Recipient,
+}
+
+impl AccountTransactionRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountTransactionRole::Origin => "origin",
+            AccountTransactionRole::Sponsor => "sponsor",
+            AccountTransactionRole::Recipient => "recipient",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<AccountTransactionRole> {
+        match s {
+            "origin" => Some(AccountTransactionRole::Origin),
+            "sponsor" => Some(AccountTransactionRole::Sponsor),
+            "recipient" => Some(AccountTransactionRole::Recipient),
+            _ => None,
+        }
+    }
+
+    /// Every account involved in a processed transaction, and how it was involved
+    pub fn of_receipt(
+        receipt: &StacksTransactionReceipt,
+    ) -> BTreeMap<String, Vec<AccountTransactionRole>> {
+        let mut accounts: BTreeMap<String, Vec<AccountTransactionRole>> = BTreeMap::new();
+        let mut add = |principal: String, role: AccountTransactionRole| {
+            let roles = accounts.entry(principal).or_default();
+            if !roles.contains(&role) {
+                roles.push(role);
+                roles.sort();
+            }
+        };
+        match &receipt.transaction {
+            TransactionOrigin::Stacks(tx) => {
+                add(
+                    tx.origin_address().to_string(),
+                    AccountTransactionRole::Origin,
+                );
+                if let Some(sponsor) = tx.sponsor_address() {
+                    add(sponsor.to_string(), AccountTransactionRole::Sponsor);
+                }
+                if let TransactionPayload::TokenTransfer(recipient, ..) = &tx.payload {
+                    add(recipient.to_string(), AccountTransactionRole::Recipient);
+                }
+            }
+            TransactionOrigin::Burn(BlockstackOperationType::TransferStx(op)) => {
+                add(op.sender.to_string(), AccountTransactionRole::Origin);
+                add(op.recipient.to_string(), AccountTransactionRole::Recipient);
+            }
+            TransactionOrigin::Burn(BlockstackOperationType::StackStx(op)) => {
+                add(op.sender.to_string(), AccountTransactionRole::Origin);
+            }
+            TransactionOrigin::Burn(BlockstackOperationType::DelegateStx(op)) => {
+                add(op.sender.to_string(), AccountTransactionRole::Origin);
+            }
+            TransactionOrigin::Burn(_) => {}
+        }
+        for event in receipt.events.iter() {
+            let recipient = match event {
+                StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                    &data.recipient
+                }
+                StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => {
+                    &data.recipient
+                }
+                StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => {
+                    &data.recipient
+                }
+                StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => &data.recipient,
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => {
+                    &data.recipient
+                }
+                StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => {
+                    &data.recipient
+                }
+                _ => continue,
+            };
+            add(recipient.to_string(), AccountTransactionRole::Recipient);
+        }
+        accounts
+    }
+}
+
+/// A transaction that involved an account, as recorded in the `account_transactions` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountTransactionEntry {
+    pub txid: Txid,
+    /// The block that processed it, directly or in one of its parent microblocks
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub roles: Vec<AccountTransactionRole>,
+}
+
+impl FromRow<AccountTransactionEntry> for AccountTransactionEntry {
+    fn from_row<'a>(row: &'a Row) -> Result<AccountTransactionEntry, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let roles_str: String = row.get_unwrap("roles");
+        let roles = roles_str
+            .split(',')
+            .map(|role| AccountTransactionRole::from_str(role).ok_or(db_error::ParseError))
+            .collect::<Result<_, _>>()?;
+        Ok(AccountTransactionEntry {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            roles,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct StacksAccount {
     pub principal: PrincipalData,
@@ -253,14 +452,40 @@ impl DBConfig {
                     || self.version == "2"
                     || self.version == "3"
                     || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
             }
             StacksEpochId::Epoch2_05 => {
-                self.version == "2" || self.version == "3" || self.version == "4"
+                self.version == "2"
+                    || self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
+            }
+            StacksEpochId::Epoch21 => {
+                self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
+            }
+            StacksEpochId::Epoch22 => {
+                self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
+            }
+            StacksEpochId::Epoch23 => {
+                self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
+            }
+            StacksEpochId::Epoch24 => {
+                self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5"
+                    || self.version == "6"
             }
-            StacksEpochId::Epoch21 => self.version == "3" || self.version == "4",
-            StacksEpochId::Epoch22 => self.version == "3" || self.version == "4",
-            StacksEpochId::Epoch23 => self.version == "3" || self.version == "4",
-            StacksEpochId::Epoch24 => self.version == "3" || self.version == "4",
         }
     }
 }
@@ -493,6 +718,8 @@ pub struct ChainstateTx<'a> {
     pub blocks_path: String,
     pub tx: StacksDBTx<'a>,
     pub root_path: String,
+    pub txindex: bool,
+    pub account_txindex: bool,
 }
 
 impl<'a> ChainstateTx<'a> {
@@ -501,12 +728,16 @@ impl<'a> ChainstateTx<'a> {
         blocks_path: String,
         root_path: String,
         config: DBConfig,
+        txindex: bool,
+        account_txindex: bool,
     ) -> ChainstateTx<'a> {
         ChainstateTx {
             config,
             blocks_path,
             tx,
             root_path,
+            txindex,
+            account_txindex,
         }
     }
 
@@ -525,21 +756,78 @@ impl<'a> ChainstateTx<'a> {
     pub fn log_transactions_processed(
         &self,
         block_id: &StacksBlockId,
+        block_height: u64,
         events: &[StacksTransactionReceipt],
     ) {
-        if *TRANSACTION_LOG {
-            let insert =
-                "INSERT INTO transactions (txid, index_block_hash, tx_hex, result) VALUES (?, ?, ?, ?)";
+        if self.txindex {
+            let insert = "INSERT INTO transactions
+                (txid, index_block_hash, tx_hex, result, result_hex, tx_index, microblock_hash,
+                 post_condition_aborted, vm_error, execution_cost, events)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
             for tx_event in events.iter() {
                 let txid = tx_event.transaction.txid();
                 let tx_hex = tx_event.transaction.serialize_to_dbstring();
                 let result = tx_event.result.to_string();
-                let params: &[&dyn ToSql] = &[&txid, block_id, &tx_hex, &result];
+                let result_hex = tx_event.result.serialize_to_hex().ok();
+                let microblock_hash = tx_event
+                    .microblock_header
+                    .as_ref()
+                    .map(|header| header.block_hash());
+                let execution_cost = serde_json::to_string(&tx_event.execution_cost)
+                    .expect("FATAL: failed to serialize execution cost");
+                let events: Vec<_> = tx_event
+                    .events
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(event_index, event)| {
+                        event
+                            .json_serialize(event_index, &txid, true)
+                            .map_err(|e| warn!("Failed to serialize TX event: {:?}", &e; "txid" => %txid))
+                            .ok()
+                    })
+                    .collect();
+                let events = serde_json::to_string(&events)
+                    .expect("FATAL: failed to serialize transaction events");
+                let params: &[&dyn ToSql] = &[
+                    &txid,
+                    block_id,
+                    &tx_hex,
+                    &result,
+                    &result_hex,
+                    &tx_event.tx_index,
+                    &microblock_hash,
+                    &tx_event.post_condition_aborted,
+                    &tx_event.vm_error,
+                    &execution_cost,
+                    &events,
+                ];
                 if let Err(e) = self.tx.tx().execute(insert, params) {
                     warn!("Failed to log TX: {}", e);
                 }
             }
         }
+        if self.account_txindex {
+            let insert = "INSERT OR REPLACE INTO account_transactions
+                (principal, txid, index_block_hash, block_height, tx_index, roles)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+            for tx_event in events.iter() {
+                let txid = tx_event.transaction.txid();
+                for (principal, roles) in AccountTransactionRole::of_receipt(tx_event) {
+                    let roles: Vec<_> = roles.iter().map(|role| role.as_str()).collect();
+                    let params: &[&dyn ToSql] = &[
+                        &principal,
+                        &txid,
+                        block_id,
+                        &u64_to_sql(block_height).expect("FATAL: block height exceeds i64"),
+                        &tx_event.tx_index,
+                        &roles.join(","),
+                    ];
+                    if let Err(e) = self.tx.tx().execute(insert, params) {
+                        warn!("Failed to index TX for account: {}", e; "txid" => %txid, "principal" => %principal);
+                    }
+                }
+            }
+        }
         for tx_event in events.iter() {
             let txid = tx_event.transaction.txid();
             if let Err(e) = monitoring::log_transaction_processed(&txid, &self.root_path) {
@@ -562,7 +850,7 @@ impl<'a> DerefMut for ChainstateTx<'a> {
     }
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "3";
+pub const CHAINSTATE_VERSION: &'static str = "6";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -767,6 +1055,81 @@ const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
     "#,
 ];
 
+const CHAINSTATE_SCHEMA_4: &'static [&'static str] = &[
+    // new in schema version 4
+    // record each transaction's full outcome in the transaction log, so that it can be served
+    // by txid
+    r#"
+    ALTER TABLE transactions ADD COLUMN result_hex TEXT;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN tx_index INTEGER;
+    "#,
+    r#"
+    -- NULL if the transaction was mined in an anchored block
+    ALTER TABLE transactions ADD COLUMN microblock_hash TEXT;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN post_condition_aborted INTEGER;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN vm_error TEXT;
+    "#,
+    r#"
+    -- JSON-encoded ExecutionCost
+    ALTER TABLE transactions ADD COLUMN execution_cost TEXT;
+    "#,
+    r#"
+    -- JSON-encoded list of events, in the format sent to event observers
+    ALTER TABLE transactions ADD COLUMN events TEXT;
+    "#,
+    r#"
+    UPDATE db_config SET version = "4";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_5: &'static [&'static str] = &[
+    // new in schema version 5
+    // index the accounts each processed transaction involved, so that an account's history can
+    // be served
+    r#"
+    CREATE TABLE account_transactions(
+        principal TEXT NOT NULL,
+        txid TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        tx_index INTEGER NOT NULL,
+        -- comma-separated list of how the account was involved (origin, sponsor, recipient)
+        roles TEXT NOT NULL,
+
+        PRIMARY KEY(principal, txid, index_block_hash)
+    );"#,
+    r#"
+    CREATE INDEX IF NOT EXISTS account_transactions_by_height ON account_transactions(principal,block_height DESC,tx_index DESC);
+    "#,
+    r#"
+    UPDATE db_config SET version = "5";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_6: &'static [&'static str] = &[
+    // new in schema version 6
+    // record which processed blocks and microblocks have had their data pruned, so that they are
+    // neither served nor advertised
+    r#"
+    ALTER TABLE staging_blocks ADD COLUMN pruned INTEGER NOT NULL DEFAULT 0;
+    "#,
+    r#"
+    ALTER TABLE staging_microblocks ADD COLUMN pruned INTEGER NOT NULL DEFAULT 0;
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS pruned_stacks_blocks ON staging_blocks(pruned,processed);
+    "#,
+    r#"
+    UPDATE db_config SET version = "6";
+    "#,
+];
+
 const CHAINSTATE_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS index_block_hash_to_primary_key ON block_headers(index_block_hash,consensus_hash,block_hash);",
     "CREATE INDEX IF NOT EXISTS block_headers_hash_index ON block_headers(block_hash,block_height);",
@@ -937,13 +1300,9 @@ impl StacksChainState {
         Ok(config.expect("BUG: no db_config installed"))
     }
 
-    fn apply_schema_migrations<'a>(
-        tx: &DBTx<'a>,
-        mainnet: bool,
-        chain_id: u32,
-    ) -> Result<(), Error> {
-        let mut db_config =
-            StacksChainState::load_db_config(tx).expect("CORRUPTION: no db_config found");
+    fn check_db_config(conn: &DBConn, mainnet: bool, chain_id: u32) -> Result<(), Error> {
+        let db_config =
+            StacksChainState::load_db_config(conn).expect("CORRUPTION: no db_config found");
 
         if db_config.mainnet != mainnet {
             error!(
@@ -960,38 +1319,69 @@ impl StacksChainState {
             );
             return Err(Error::InvalidChainstateDB);
         }
+        Ok(())
+    }
 
-        if db_config.version != CHAINSTATE_VERSION {
-            while db_config.version != CHAINSTATE_VERSION {
-                match db_config.version.as_str() {
-                    "1" => {
-                        // migrate to 2
-                        info!("Migrating chainstate schema from version 1 to 2");
-                        for cmd in CHAINSTATE_SCHEMA_2.iter() {
-                            tx.execute_batch(cmd)?;
-                        }
-                    }
-                    "2" => {
-                        // migrate to 3
-                        info!("Migrating chainstate schema from version 2 to 3");
-                        for cmd in CHAINSTATE_SCHEMA_3.iter() {
-                            tx.execute_batch(cmd)?;
-                        }
-                    }
-                    "3" => {
-                        // done
-                        break;
-                    }
-                    _ => {
-                        error!(
-                            "Invalid chain state database: expected version = {}, got {}",
-                            CHAINSTATE_VERSION, db_config.version
-                        );
-                        return Err(Error::InvalidChainstateDB);
-                    }
-                }
-                db_config =
-                    StacksChainState::load_db_config(tx).expect("CORRUPTION: no db_config found");
+    /// Get the migration from the given chainstate schema version to the next one, or None if
+    /// `version` is the latest version.
+    fn schema_migration(version: &str) -> Result<Option<Migration<'static>>, Error> {
+        let (next_version, schema): (&str, &'static [&'static str]) = match version {
+            "1" => ("2", CHAINSTATE_SCHEMA_2),
+            "2" => ("3", CHAINSTATE_SCHEMA_3),
+            "3" => ("4", CHAINSTATE_SCHEMA_4),
+            "4" => ("5", CHAINSTATE_SCHEMA_5),
+            "5" => ("6", CHAINSTATE_SCHEMA_6),
+            "6" => {
+                // done
+                return Ok(None);
+            }
+            _ => {
+                error!(
+                    "Invalid chain state database: expected version = {}, got {}",
+                    CHAINSTATE_VERSION, version
+                );
+                return Err(Error::InvalidChainstateDB);
+            }
+        };
+        Ok(Some(
+            Migration::new("chainstate", next_version).sql_steps(schema),
+        ))
+    }
+
+    /// Bring a chainstate DB that is being instantiated up to the latest schema version, within
+    /// the instantiating transaction.
+    fn apply_schema_migrations<'a>(
+        tx: &DBTx<'a>,
+        mainnet: bool,
+        chain_id: u32,
+    ) -> Result<(), Error> {
+        StacksChainState::check_db_config(tx, mainnet, chain_id)?;
+        loop {
+            let db_config =
+                StacksChainState::load_db_config(tx).expect("CORRUPTION: no db_config found");
+            match StacksChainState::schema_migration(&db_config.version)? {
+                Some(migration) => migration.apply(tx)?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    /// Migrate an existing chainstate DB to the latest schema version.  Each migration step is
+    /// committed separately, so if the node is interrupted, the migration resumes where it left
+    /// off the next time the DB is opened.
+    fn run_schema_migrations(
+        marf: &mut MARF<StacksBlockId>,
+        mainnet: bool,
+        chain_id: u32,
+    ) -> Result<(), Error> {
+        StacksChainState::check_db_config(marf.sqlite_conn(), mainnet, chain_id)?;
+        loop {
+            let db_config = StacksChainState::load_db_config(marf.sqlite_conn())
+                .expect("CORRUPTION: no db_config found");
+            match StacksChainState::schema_migration(&db_config.version)? {
+                Some(migration) => migration.run(marf)?,
+                None => break,
             }
         }
         Ok(())
@@ -1016,8 +1406,8 @@ impl StacksChainState {
             StacksChainState::instantiate_db(mainnet, chain_id, index_path, true)
         } else {
             let mut marf = StacksChainState::open_index(index_path)?;
+            StacksChainState::run_schema_migrations(&mut marf, mainnet, chain_id)?;
             let tx = marf.storage_tx()?;
-            StacksChainState::apply_schema_migrations(&tx, mainnet, chain_id)?;
             StacksChainState::add_indexes(&tx)?;
             tx.commit()?;
             Ok(marf)
@@ -1712,6 +2102,8 @@ impl StacksChainState {
             root_path: path_str.to_string(),
             unconfirmed_state: None,
             fault_injection: StacksChainStateFaults::new(),
+            txindex: *TRANSACTION_LOG,
+            account_txindex: false,
             marf_opts: marf_opts,
         };
 
@@ -1751,6 +2143,29 @@ impl StacksChainState {
         Ok(StacksDBConn::new(&self.state_index, ()))
     }
 
+    /// Get every logged processing of a transaction, across all forks, in the order they were
+    /// processed
+    pub fn get_logged_transactions(
+        conn: &Connection,
+        txid: &Txid,
+    ) -> Result<Vec<TransactionLogEntry>, Error> {
+        let sql = "SELECT * FROM transactions WHERE txid = ?1 ORDER BY id ASC";
+        query_rows(conn, sql, &[txid]).map_err(Error::DBError)
+    }
+
+    /// Get the indexed transactions that involved `principal` at or below `max_height`, across
+    /// all forks, newest first
+    pub fn get_account_transactions(
+        conn: &Connection,
+        principal: &PrincipalData,
+        max_height: u64,
+    ) -> Result<Vec<AccountTransactionEntry>, Error> {
+        let sql = "SELECT * FROM account_transactions WHERE principal = ?1 AND block_height <= ?2
+                   ORDER BY block_height DESC, tx_index DESC";
+        let args: &[&dyn ToSql] = &[&principal.to_string(), &u64_to_sql(max_height)?];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
     /// Begin a transaction against the underlying DB
     /// Does not create a Clarity instance, and does not affect the MARF.
     pub fn db_tx_begin<'a>(&'a mut self) -> Result<DBTx<'a>, Error> {
@@ -1767,8 +2182,14 @@ impl StacksChainState {
         let clarity_instance = &mut self.clarity_state;
         let inner_tx = StacksDBTx::new(&mut self.state_index, ());
 
-        let chainstate_tx =
-            ChainstateTx::new(inner_tx, blocks_path, self.root_path.clone(), config);
+        let chainstate_tx = ChainstateTx::new(
+            inner_tx,
+            blocks_path,
+            self.root_path.clone(),
+            config,
+            self.txindex,
+            self.account_txindex,
+        );
 
         Ok((chainstate_tx, clarity_instance))
     }
@@ -2019,6 +2440,92 @@ impl StacksChainState {
         }
     }
 
+    /// Turn indexing the keys of every data map written by processed blocks on or off, so they
+    /// can be enumerated with `get_data_map_entries()`
+    pub fn set_map_key_index(&mut self, enabled: bool) -> Result<(), Error> {
+        self.clarity_state.set_map_key_index(enabled)?;
+        Ok(())
+    }
+
+    pub fn is_map_key_index_enabled(&self) -> bool {
+        self.clarity_state.is_map_key_index_enabled()
+    }
+
+    /// Get up to `limit` entries of a data map as of the given chain tip, in key order, starting
+    /// after the hex-serialized key `after`.  Each entry is the hex serialization of its key and
+    /// of its value (a Clarity optional).  Only keys written while `map_key_index` was on are
+    /// found.
+    /// Returns Ok(None) if the tip does not exist.
+    pub fn get_data_map_entries(
+        &mut self,
+        burn_dbconn: &dyn BurnStateDB,
+        tip: &StacksBlockId,
+        contract_identifier: &QualifiedContractIdentifier,
+        map_name: &str,
+        after: Option<&str>,
+        limit: u32,
+    ) -> Result<Option<Vec<(String, String)>>, Error> {
+        // deleted entries are stored as `none`
+        let none_hex = Value::none()
+            .serialize_to_hex()
+            .map_err(clarity_interpreter_error::from)?;
+
+        // the index covers every fork, so only keep the keys that are set at the tip
+        let mut entries = vec![];
+        let mut cursor = after.map(|key_hex| key_hex.to_string());
+        while entries.len() < limit as usize {
+            let candidates = self.with_clarity_marf(|marf| {
+                get_data_map_key_candidates(
+                    marf.sqlite_conn(),
+                    contract_identifier,
+                    map_name,
+                    cursor.as_deref(),
+                    limit,
+                )
+            })?;
+            if candidates.is_empty() {
+                break;
+            }
+            cursor = candidates.last().cloned();
+
+            let values_opt = self.maybe_read_only_clarity_tx(burn_dbconn, tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    candidates
+                        .into_iter()
+                        .map(|key_hex| {
+                            let key = ClarityDatabase::make_key_for_quad(
+                                contract_identifier,
+                                StoreType::DataMap,
+                                map_name,
+                                &key_hex,
+                            );
+                            let value_hex: Option<String> = clarity_db.get(&key)?;
+                            Ok((key_hex, value_hex))
+                        })
+                        .collect::<Result<Vec<_>, clarity_interpreter_error>>()
+                })
+            })?;
+            let values = match values_opt {
+                Some(values) => values?,
+                None => {
+                    return Ok(None);
+                }
+            };
+
+            for (key_hex, value_hex_opt) in values.into_iter() {
+                if entries.len() >= limit as usize {
+                    break;
+                }
+                if let Some(value_hex) = value_hex_opt {
+                    if value_hex != none_hex {
+                        entries.push((key_hex, value_hex));
+                    }
+                }
+            }
+        }
+        Ok(Some(entries))
+    }
+
     fn get_parent_index_block(
         parent_consensus_hash: &ConsensusHash,
         parent_block: &BlockHeaderHash,
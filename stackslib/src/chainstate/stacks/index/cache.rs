@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::char::from_digit;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::hash::{Hash, Hasher};
 use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
@@ -44,6 +44,7 @@ use crate::chainstate::stacks::index::node::{
     TrieNode48, TrieNodeID, TrieNodeType, TriePath, TriePtr,
 };
 use crate::chainstate::stacks::index::{trie_sql, ClarityMarfTrieId, Error, MarfTrieId, TrieLeaf};
+use crate::monitoring;
 use crate::util_lib::db::{
     sql_pragma, sqlite_open, tx_begin_immediate, tx_busy_handler, Error as db_error,
     SQLITE_MMAP_SIZE,
@@ -156,6 +157,169 @@ impl<T: MarfTrieId> TrieCacheState<T> {
     }
 }
 
+/// Default byte budget of the "lru" cache strategy
+pub const DEFAULT_LRU_CACHE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// A cached node and/or node hash, and when it was last used
+struct TrieLruEntry {
+    node: Option<TrieNodeType>,
+    hash: Option<TrieHash>,
+    size: u64,
+    last_used: u64,
+}
+
+impl TrieLruEntry {
+    /// The number of bytes this entry counts against the cache's budget: the serialized size of
+    /// its node and hash.
+    fn byte_len(node: &Option<TrieNodeType>) -> u64 {
+        match node {
+            Some(node) => get_node_byte_len(node) as u64,
+            None => TRIEHASH_ENCODED_SIZE as u64,
+        }
+    }
+}
+
+/// Node cache that holds at most `budget` bytes of nodes and hashes, and evicts the
+/// least-recently-used ones to make room for new ones.
+pub struct TrieLruCache {
+    budget: u64,
+    size: u64,
+    /// logical clock, advanced on every access
+    clock: u64,
+    entries: HashMap<TrieNodeAddr, TrieLruEntry>,
+    /// the entries' addresses, by when they were last used
+    recency: BTreeMap<u64, TrieNodeAddr>,
+}
+
+impl TrieLruCache {
+    pub fn new(budget: u64) -> TrieLruCache {
+        TrieLruCache {
+            budget,
+            size: 0,
+            clock: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+        }
+    }
+
+    /// Number of bytes currently cached
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Look up an entry and mark it as the most-recently-used
+    fn touch(&mut self, addr: &TrieNodeAddr) -> Option<&TrieLruEntry> {
+        let entry = self.entries.get_mut(addr)?;
+        self.recency.remove(&entry.last_used);
+        self.clock += 1;
+        entry.last_used = self.clock;
+        self.recency.insert(self.clock, addr.clone());
+        Some(entry)
+    }
+
+    pub fn load_node(&mut self, block_id: u32, trieptr: &TriePtr) -> Option<TrieNodeType> {
+        self.touch(&TrieNodeAddr(block_id, trieptr.clone()))
+            .and_then(|entry| entry.node.clone())
+    }
+
+    pub fn load_node_hash(&mut self, block_id: u32, trieptr: &TriePtr) -> Option<TrieHash> {
+        self.touch(&TrieNodeAddr(block_id, trieptr.clone()))
+            .and_then(|entry| entry.hash.clone())
+    }
+
+    pub fn load_node_and_hash(
+        &mut self,
+        block_id: u32,
+        trieptr: &TriePtr,
+    ) -> Option<(TrieNodeType, TrieHash)> {
+        let entry = self.touch(&TrieNodeAddr(block_id, trieptr.clone()))?;
+        match (&entry.node, &entry.hash) {
+            (Some(node), Some(hash)) => Some((node.clone(), hash.clone())),
+            _ => None,
+        }
+    }
+
+    /// Cache a node and/or hash. Whatever is already cached for the address is kept if not given.
+    fn store(
+        &mut self,
+        block_id: u32,
+        trieptr: TriePtr,
+        node: Option<TrieNodeType>,
+        hash: Option<TrieHash>,
+    ) {
+        let addr = TrieNodeAddr(block_id, trieptr);
+        let (node, hash) = match self.entries.remove(&addr) {
+            Some(old) => {
+                self.recency.remove(&old.last_used);
+                self.size -= old.size;
+                (node.or(old.node), hash.or(old.hash))
+            }
+            None => (node, hash),
+        };
+
+        let size = TrieLruEntry::byte_len(&node);
+        if size > self.budget {
+            return;
+        }
+        while self.size + size > self.budget {
+            self.evict_one();
+        }
+
+        self.clock += 1;
+        self.recency.insert(self.clock, addr.clone());
+        self.entries.insert(
+            addr,
+            TrieLruEntry {
+                node,
+                hash,
+                size,
+                last_used: self.clock,
+            },
+        );
+        self.size += size;
+    }
+
+    fn evict_one(&mut self) {
+        let (last_used, addr) = match self.recency.iter().next() {
+            Some((last_used, addr)) => (*last_used, addr.clone()),
+            None => return,
+        };
+        self.recency.remove(&last_used);
+        if let Some(entry) = self.entries.remove(&addr) {
+            self.size -= entry.size;
+            monitoring::increment_marf_cache_evictions_counter();
+        }
+    }
+
+    pub fn store_node_and_hash(
+        &mut self,
+        block_id: u32,
+        trieptr: TriePtr,
+        node: TrieNodeType,
+        hash: TrieHash,
+    ) {
+        self.store(block_id, trieptr, Some(node), Some(hash))
+    }
+
+    pub fn store_node(&mut self, block_id: u32, trieptr: TriePtr, node: TrieNodeType) {
+        self.store(block_id, trieptr, Some(node), None)
+    }
+
+    pub fn store_node_hash(&mut self, block_id: u32, trieptr: TriePtr, hash: TrieHash) {
+        self.store(block_id, trieptr, None, Some(hash))
+    }
+}
+
+/// Count a cache lookup as a hit or a miss
+fn record_lookup<R>(result: Option<R>) -> Option<R> {
+    if result.is_some() {
+        monitoring::increment_marf_cache_hits_counter();
+    } else {
+        monitoring::increment_marf_cache_misses_counter();
+    }
+    result
+}
+
 /// Trie node cache strategies
 pub enum TrieCache<T: MarfTrieId> {
     /// Do nothing
@@ -164,6 +328,9 @@ pub enum TrieCache<T: MarfTrieId> {
     Everything(TrieCacheState<T>),
     /// Cache only TrieNode256's
     Node256(TrieCacheState<T>),
+    /// Cache the most-recently-used nodes, up to a byte budget.  The state only caches block
+    /// hashes.
+    Lru(TrieCacheState<T>, TrieLruCache),
 }
 
 impl<T: MarfTrieId> TrieCache<T> {
@@ -178,13 +345,20 @@ impl<T: MarfTrieId> TrieCache<T> {
     }
 
     /// Make a new cache strategy.
-    /// `strategy` must be one of "noop", "everything", or "node256".
-    /// Any other option causes a runtime panic.
+    /// `strategy` must be one of "noop", "everything", "node256", or "lru".
+    /// Any other option falls back to "noop".
+    /// The "lru" strategy gets the default byte budget.
     pub fn new(strategy: &str) -> TrieCache<T> {
+        TrieCache::new_sized(strategy, DEFAULT_LRU_CACHE_SIZE)
+    }
+
+    /// Make a new cache strategy, with a byte budget of `lru_cache_size` for the "lru" strategy.
+    pub fn new_sized(strategy: &str, lru_cache_size: u64) -> TrieCache<T> {
         match strategy {
             "noop" => TrieCache::Noop(TrieCacheState::new()),
             "everything" => TrieCache::Everything(TrieCacheState::new()),
             "node256" => TrieCache::Node256(TrieCacheState::new()),
+            "lru" => TrieCache::Lru(TrieCacheState::new(), TrieLruCache::new(lru_cache_size)),
             _ => {
                 error!(
                     "Unsupported trie node cache strategy '{}'; falling back to `Noop` strategy",
@@ -201,6 +375,7 @@ impl<T: MarfTrieId> TrieCache<T> {
             TrieCache::Noop(ref state) => state,
             TrieCache::Everything(ref state) => state,
             TrieCache::Node256(ref state) => state,
+            TrieCache::Lru(ref state, _) => state,
         }
     }
 
@@ -210,15 +385,16 @@ impl<T: MarfTrieId> TrieCache<T> {
             TrieCache::Noop(ref mut state) => state,
             TrieCache::Everything(ref mut state) => state,
             TrieCache::Node256(ref mut state) => state,
+            TrieCache::Lru(ref mut state, _) => state,
         }
     }
 
     /// Load a node from the cache, given its block ID and trie pointer within the block.
     pub fn load_node(&mut self, block_id: u32, trieptr: &TriePtr) -> Option<TrieNodeType> {
-        if let TrieCache::Noop(_) = self {
-            None
-        } else {
-            self.state_mut().load_node(block_id, trieptr)
+        match self {
+            TrieCache::Noop(_) => None,
+            TrieCache::Lru(_, ref mut lru) => record_lookup(lru.load_node(block_id, trieptr)),
+            _ => record_lookup(self.state_mut().load_node(block_id, trieptr)),
         }
     }
 
@@ -229,19 +405,21 @@ impl<T: MarfTrieId> TrieCache<T> {
         block_id: u32,
         trieptr: &TriePtr,
     ) -> Option<(TrieNodeType, TrieHash)> {
-        if let TrieCache::Noop(_) = self {
-            None
-        } else {
-            self.state_mut().load_node_and_hash(block_id, trieptr)
+        match self {
+            TrieCache::Noop(_) => None,
+            TrieCache::Lru(_, ref mut lru) => {
+                record_lookup(lru.load_node_and_hash(block_id, trieptr))
+            }
+            _ => record_lookup(self.state_mut().load_node_and_hash(block_id, trieptr)),
         }
     }
 
     /// Load a node's hash, given its node's block ID and trie pointer within the block.
     pub fn load_node_hash(&mut self, block_id: u32, trieptr: &TriePtr) -> Option<TrieHash> {
-        if let TrieCache::Noop(_) = self {
-            None
-        } else {
-            self.state_mut().load_node_hash(block_id, trieptr)
+        match self {
+            TrieCache::Noop(_) => None,
+            TrieCache::Lru(_, ref mut lru) => record_lookup(lru.load_node_hash(block_id, trieptr)),
+            _ => record_lookup(self.state_mut().load_node_hash(block_id, trieptr)),
         }
     }
 
@@ -265,6 +443,9 @@ impl<T: MarfTrieId> TrieCache<T> {
                 }
                 _ => {}
             },
+            TrieCache::Lru(_, ref mut lru) => {
+                lru.store_node_and_hash(block_id, trieptr, node, hash)
+            }
         }
     }
 
@@ -280,6 +461,7 @@ impl<T: MarfTrieId> TrieCache<T> {
                 }
                 _ => {}
             },
+            TrieCache::Lru(_, ref mut lru) => lru.store_node(block_id, trieptr, node),
         }
     }
 
@@ -304,6 +486,7 @@ impl<T: MarfTrieId> TrieCache<T> {
                 }
                 _ => {}
             },
+            TrieCache::Lru(_, ref mut lru) => lru.store_node_hash(block_id, trieptr, hash),
         }
     }
 
@@ -381,6 +564,24 @@ pub mod test {
         hash_strategy: TrieHashCalculationMode,
         data: &[Vec<(String, MARFValue)>],
         batch_size: Option<usize>,
+    ) -> TrieHash {
+        test_marf_with_cache_size(
+            test_name,
+            cache_strategy,
+            DEFAULT_LRU_CACHE_SIZE,
+            hash_strategy,
+            data,
+            batch_size,
+        )
+    }
+
+    pub fn test_marf_with_cache_size(
+        test_name: &str,
+        cache_strategy: &str,
+        cache_size: u64,
+        hash_strategy: TrieHashCalculationMode,
+        data: &[Vec<(String, MARFValue)>],
+        batch_size: Option<usize>,
     ) -> TrieHash {
         let test_file = if test_name == ":memory:" {
             test_name.to_string()
@@ -398,7 +599,8 @@ pub mod test {
             test_file
         };
 
-        let marf_opts = MARFOpenOpts::new(hash_strategy, cache_strategy, true);
+        let mut marf_opts = MARFOpenOpts::new(hash_strategy, cache_strategy, true);
+        marf_opts.cache_size = cache_size;
         let f = TrieFileStorage::open(&test_file, marf_opts).unwrap();
         let mut marf = MARF::from_storage(f);
         let mut last_block_header = BlockHeaderHash::sentinel();
@@ -856,4 +1058,76 @@ pub mod test {
         );
         assert_eq!(root_hash, root_hash_batched);
     }
+
+    #[test]
+    fn test_lru_cache_evicts_least_recently_used() {
+        let node = TrieNodeType::Leaf(TrieLeaf::new(&[], &[0u8; 40].to_vec()));
+        let node_size = get_node_byte_len(&node) as u64;
+        let ptr = |i: u32| TriePtr::new(TrieNodeID::Leaf as u8, 0, i);
+
+        let mut lru = TrieLruCache::new(3 * node_size);
+        for i in 0..3 {
+            lru.store_node_and_hash(0, ptr(i), node.clone(), TrieHash([i as u8; 32]));
+        }
+        assert_eq!(lru.size(), 3 * node_size);
+
+        // 0 becomes the most-recently-used, so 1 is evicted to make room for 3
+        assert!(lru.load_node(0, &ptr(0)).is_some());
+        lru.store_node(0, ptr(3), node.clone());
+        assert_eq!(lru.size(), 3 * node_size);
+        assert!(lru.load_node(0, &ptr(1)).is_none());
+        assert_eq!(
+            lru.load_node_and_hash(0, &ptr(0)),
+            Some((node.clone(), TrieHash([0u8; 32])))
+        );
+        assert!(lru.load_node(0, &ptr(2)).is_some());
+
+        // 3 was stored without its hash; adding the hash keeps the node
+        assert!(lru.load_node_hash(0, &ptr(3)).is_none());
+        lru.store_node_hash(0, ptr(3), TrieHash([3u8; 32]));
+        assert_eq!(
+            lru.load_node_and_hash(0, &ptr(3)),
+            Some((node.clone(), TrieHash([3u8; 32])))
+        );
+        assert_eq!(lru.size(), 3 * node_size);
+
+        // nodes bigger than the whole budget are not cached
+        let mut tiny = TrieLruCache::new(node_size - 1);
+        tiny.store_node(0, ptr(0), node.clone());
+        assert!(tiny.load_node(0, &ptr(0)).is_none());
+        assert_eq!(tiny.size(), 0);
+    }
+
+    #[test]
+    fn test_marf_node_cache_lru() {
+        let test_data = make_test_insert_data(128, 128);
+        let root_hash = test_marf_with_cache(
+            "test_marf_node_cache_lru",
+            "noop",
+            TrieHashCalculationMode::Immediate,
+            &test_data,
+            None,
+        );
+        eprintln!("Final root hash is {}", root_hash);
+
+        // big enough to hold everything, and small enough to evict constantly
+        for cache_size in [DEFAULT_LRU_CACHE_SIZE, 16 * 1024] {
+            for hash_strategy in [
+                TrieHashCalculationMode::Immediate,
+                TrieHashCalculationMode::Deferred,
+            ] {
+                for batch_size in [None, Some(64), Some(13)] {
+                    let root_hash_lru = test_marf_with_cache_size(
+                        "test_marf_node_cache_lru",
+                        "lru",
+                        cache_size,
+                        hash_strategy,
+                        &test_data,
+                        batch_size,
+                    );
+                    assert_eq!(root_hash, root_hash_lru);
+                }
+            }
+        }
+    }
 }
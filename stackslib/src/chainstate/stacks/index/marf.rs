@@ -26,6 +26,7 @@ use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::log;
 
 use crate::chainstate::stacks::index::bits::{get_leaf_hash, get_node_hash, read_root_hash};
+use crate::chainstate::stacks::index::cache::DEFAULT_LRU_CACHE_SIZE;
 use crate::chainstate::stacks::index::node::{
     clear_backptr, is_backptr, set_backptr, CursorError, TrieCursor, TrieNode, TrieNode16,
     TrieNode256, TrieNode4, TrieNode48, TrieNodeID, TrieNodeType, TriePath, TriePtr, TRIEPTR_SIZE,
@@ -67,6 +68,8 @@ pub struct MARFOpenOpts {
     pub hash_calculation_mode: TrieHashCalculationMode,
     /// Cache strategy to use
     pub cache_strategy: String,
+    /// Byte budget of the "lru" cache strategy
+    pub cache_size: u64,
     /// store trie blobs externally from the DB, in a flat file
     pub external_blobs: bool,
     /// unconditionally do a DB migration (used for testing)
@@ -78,6 +81,7 @@ impl MARFOpenOpts {
         MARFOpenOpts {
             hash_calculation_mode: TrieHashCalculationMode::Deferred,
             cache_strategy: "noop".to_string(),
+            cache_size: DEFAULT_LRU_CACHE_SIZE,
             external_blobs: false,
             force_db_migrate: false,
         }
@@ -91,6 +95,7 @@ impl MARFOpenOpts {
         MARFOpenOpts {
             hash_calculation_mode,
             cache_strategy: cache_strategy.to_string(),
+            cache_size: DEFAULT_LRU_CACHE_SIZE,
             external_blobs,
             force_db_migrate: false,
         }
@@ -1445,7 +1445,7 @@ impl<T: MarfTrieId> TrieFileStorage<T> {
             blobs.is_some()
         );
 
-        let cache = TrieCache::new(&marf_opts.cache_strategy);
+        let cache = TrieCache::new_sized(&marf_opts.cache_strategy, marf_opts.cache_size);
 
         let ret = TrieFileStorage {
             db_path,
@@ -94,6 +94,8 @@ pub enum Error {
     StacksTransactionSkipped(String),
     PostConditionFailed(String),
     NoSuchBlockError,
+    /// The block or microblock was processed, but its data has since been pruned
+    BlockPruned,
     InvalidChainstateDB,
     BlockTooBigError,
     TransactionTooBigError,
@@ -155,6 +157,7 @@ impl fmt::Display for Error {
             Error::InvalidStacksTransaction(ref s, _) => fmt::Display::fmt(s, f),
             Error::PostConditionFailed(ref s) => fmt::Display::fmt(s, f),
             Error::NoSuchBlockError => write!(f, "No such Stacks block"),
+            Error::BlockPruned => write!(f, "Stacks block data was pruned"),
             Error::InvalidChainstateDB => write!(f, "Invalid chainstate database"),
             Error::BlockTooBigError => write!(f, "Too much data in block"),
             Error::TransactionTooBigError => write!(f, "Too much data in transaction"),
@@ -218,6 +221,7 @@ impl error::Error for Error {
             Error::InvalidStacksTransaction(ref _s, _q) => None,
             Error::PostConditionFailed(ref _s) => None,
             Error::NoSuchBlockError => None,
+            Error::BlockPruned => None,
             Error::InvalidChainstateDB => None,
             Error::BlockTooBigError => None,
             Error::TransactionTooBigError => None,
@@ -258,6 +262,7 @@ impl Error {
             Error::InvalidStacksTransaction(ref _s, _q) => "InvalidStacksTransaction",
             Error::PostConditionFailed(ref _s) => "PostConditionFailed",
             Error::NoSuchBlockError => "NoSuchBlockError",
+            Error::BlockPruned => "BlockPruned",
             Error::InvalidChainstateDB => "InvalidChainstateDB",
             Error::BlockTooBigError => "BlockTooBigError",
             Error::TransactionTooBigError => "TransactionTooBigError",
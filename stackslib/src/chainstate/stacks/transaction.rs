@@ -117,6 +117,12 @@ fn ClarityVersion_consensus_serialize<W: Write>(
     match *version {
         ClarityVersion::Clarity1 => write_next(fd, &1u8)?,
         ClarityVersion::Clarity2 => write_next(fd, &2u8)?,
+        // Clarity 3 is not yet activated in any epoch, so it cannot be used in transactions
+        ClarityVersion::Clarity3 => {
+            return Err(codec_error::SerializeError(
+                "Clarity 3 is not supported in transactions".into(),
+            ))
+        }
     }
     Ok(())
 }
@@ -128,6 +134,7 @@ fn ClarityVersion_consensus_deserialize<R: Read>(
     match version_byte {
         1u8 => Ok(ClarityVersion::Clarity1),
         2u8 => Ok(ClarityVersion::Clarity2),
+        // Clarity 3 is not yet activated in any epoch, so it cannot be used in transactions
         _ => Err(codec_error::DeserializeError(format!(
             "Unrecognized ClarityVersion byte {}",
             &version_byte
@@ -2745,6 +2752,20 @@ mod test {
         );
     }
 
+    #[test]
+    fn tx_stacks_transaction_clarity3_rejected() {
+        // Clarity 3 can be neither written nor read in a transaction
+        let mut bytes = vec![];
+        assert!(ClarityVersion_consensus_serialize(&ClarityVersion::Clarity3, &mut bytes).is_err());
+        assert!(bytes.is_empty());
+
+        assert!(ClarityVersion_consensus_deserialize(&mut &[3u8][..])
+            .unwrap_err()
+            .to_string()
+            .find("Unrecognized ClarityVersion byte 3")
+            .is_some());
+    }
+
     #[test]
     fn tx_stacks_transaction_payload_invalid_contract_name() {
         // test invalid contract name
@@ -23,6 +23,7 @@ use std::str::FromStr;
 use std::{env, fs, io, process};
 
 use clarity::vm::coverage::CoverageReporter;
+use clarity::vm::trace::ExecutionTracer;
 use rand::Rng;
 use rusqlite::types::ToSql;
 use rusqlite::{Connection, OpenFlags, Row, Transaction, NO_PARAMS};
@@ -47,16 +48,23 @@ use crate::chainstate::stacks::boot::{
 use crate::chainstate::stacks::index::storage::TrieFileStorage;
 use crate::chainstate::stacks::index::{ClarityMarfTrieId, MarfTrieId};
 use crate::clarity::vm::analysis::contract_interface_builder::build_contract_interface;
+use crate::clarity::vm::analysis::deployment::DeployCostEstimate;
 use crate::clarity::vm::analysis::errors::{CheckError, CheckResult};
-use crate::clarity::vm::analysis::{AnalysisDatabase, ContractAnalysis};
+use crate::clarity::vm::analysis::sarif::SarifLog;
+use crate::clarity::vm::analysis::{AnalysisDatabase, AnalysisPassRegistry, ContractAnalysis};
 use crate::clarity::vm::ast::{build_ast_with_rules, ASTRules};
 use crate::clarity::vm::contexts::{AssetMap, GlobalContext, OwnedEnvironment};
 use crate::clarity::vm::costs::{ExecutionCost, LimitedCostTracker};
 use crate::clarity::vm::database::{
-    BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
+    BurnStateDB, ClarityBackingStore, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection,
+    NULL_BURN_STATE_DB,
 };
+use crate::clarity::vm::diagnostic::Diagnostic;
 use crate::clarity::vm::errors::{Error, InterpreterResult, RuntimeErrorType};
-use crate::clarity::vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier};
+use crate::clarity::vm::representations::format::format_source;
+use crate::clarity::vm::types::{
+    OptionalData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
 use crate::clarity::vm::{
     analysis, ast, eval_all, ClarityVersion, ContractContext, ContractName, SymbolicExpression,
     SymbolicExpressionType, Value,
@@ -114,14 +122,20 @@ where command is one of:
 
   initialize         to initialize a local VM state database.
   check              to typecheck a potential contract definition.
+  check_deployment   to analyze a set of contracts that are deployed together.
+  estimate_deploy_cost
+                     to estimate the cost of deploying a contract, and whether it fits in a block.
+  format             to rewrite a program in the canonical Clarity style.
   launch             to launch a initialize a new contract in the local state database.
   eval               to evaluate (in read-only mode) a program in a given contract context.
   eval_at_chaintip   like `eval`, but does not advance to a new block.
   eval_at_block      like `eval_at_chaintip`, but accepts a index-block-hash to evaluate at,
                      must be passed eval string via stdin.
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
-  repl               to typecheck and evaluate expressions in a stdin/stdout loop.
-  execute            to execute a public function of a defined contract.
+  repl               to typecheck and evaluate expressions in a stdin/stdout loop, keeping
+                     deployed contracts and their state between expressions.
+  execute            to execute a public function of a defined contract. With `--trace`,
+                     also outputs the function calls and data accesses it made.
   generate_address   to generate a random Stacks public address for testing purposes.
 ",
         invoked_by
@@ -214,7 +228,7 @@ fn run_analysis_free<C: ClarityStorage>(
     save_contract: bool,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
     let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
-    analysis::run_analysis(
+    analysis::run_analysis_with_passes(
         contract_identifier,
         expressions,
         &mut marf_kv.get_analysis_db(),
@@ -222,6 +236,8 @@ fn run_analysis_free<C: ClarityStorage>(
         LimitedCostTracker::new_free(),
         DEFAULT_CLI_EPOCH,
         clarity_version,
+        &AnalysisPassRegistry::tooling(),
+        false,
     )
 }
 
@@ -246,7 +262,7 @@ fn run_analysis<C: ClarityStorage>(
         DEFAULT_CLI_EPOCH,
     )
     .unwrap();
-    analysis::run_analysis(
+    analysis::run_analysis_with_passes(
         contract_identifier,
         expressions,
         &mut marf_kv.get_analysis_db(),
@@ -254,6 +270,35 @@ fn run_analysis<C: ClarityStorage>(
         cost_track,
         DEFAULT_CLI_EPOCH,
         clarity_version,
+        &AnalysisPassRegistry::tooling(),
+        false,
+    )
+}
+
+/// Estimate the cost of deploying `content` on top of the state in `marf_kv`. The estimate is
+/// charged without a limit, so that it can be compared to the block limit.
+fn estimate_deploy_cost<C: ClarityStorage + ClarityBackingStore>(
+    contract_identifier: &QualifiedContractIdentifier,
+    content: &str,
+    header_db: &CLIHeadersDB,
+    marf_kv: &mut C,
+) -> Result<DeployCostEstimate, Diagnostic> {
+    let mainnet = header_db.is_mainnet();
+    let cost_track = LimitedCostTracker::new(
+        mainnet,
+        default_chain_id(mainnet),
+        ExecutionCost::max_value(),
+        &mut marf_kv.get_clarity_db(header_db, &NULL_BURN_STATE_DB),
+        DEFAULT_CLI_EPOCH,
+    )
+    .unwrap();
+    analysis::deployment::estimate_deploy_cost(
+        contract_identifier,
+        content,
+        marf_kv,
+        cost_track,
+        DEFAULT_CLI_EPOCH,
+        ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH),
     )
 }
 
@@ -417,6 +462,7 @@ fn with_env_costs<F, R>(
     header_db: &CLIHeadersDB,
     marf: &mut WritableMarfStore,
     coverage: Option<&mut CoverageReporter>,
+    tracer: Option<&mut ExecutionTracer>,
     f: F,
 ) -> (R, ExecutionCost)
 where
@@ -445,6 +491,9 @@ where
     if let Some(coverage) = coverage {
         vm_env.add_eval_hook(coverage);
     }
+    if let Some(tracer) = tracer {
+        vm_env.add_eval_hook(tracer);
+    }
     let result = f(&mut vm_env);
     let cost = vm_env.get_cost_total();
     (result, cost)
@@ -929,12 +978,244 @@ fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C)
         .unwrap();
 }
 
+/// The default tx-sender of a `repl` session
+const DEFAULT_REPL_SENDER: &str = "S1G2081040G2081040G2081040G208105NK8PE5";
+
+/// The result of handling one line of `repl` input
+enum ReplOutput {
+    /// Print this, then wait for the next line
+    Continue(String),
+    /// End the session
+    Exit,
+}
+
+/// An interactive `repl` session. Like `launch` and `execute`, each deployment and evaluation is
+/// mined in a block of its own, so deployed contracts and their data persist between inputs.
+struct ReplSession {
+    header_db: CLIHeadersDB,
+    marf_kv: MarfedKV,
+    /// The database directory, if the session made its own and should remove it when it ends
+    temporary_db_path: Option<PathBuf>,
+    /// The tx-sender of evaluations, and the issuer of deployed contracts
+    sender: StandardPrincipalData,
+    /// The execution cost of the last deployment or evaluation
+    last_cost: Option<ExecutionCost>,
+}
+
+impl ReplSession {
+    /// Continue from the chain tip of an initialized `vm-state.db`
+    fn resume(db_path: &str, sender: StandardPrincipalData) -> ReplSession {
+        let header_db = friendly_expect(CLIHeadersDB::resume(db_path), "Failed to open CLI DB");
+        let marf_kv = friendly_expect(
+            MarfedKV::open(db_path, None, None),
+            "Failed to open VM database.",
+        );
+        ReplSession {
+            header_db,
+            marf_kv,
+            temporary_db_path: None,
+            sender,
+            last_cost: None,
+        }
+    }
+
+    /// Start from a new database with the boot code installed, in the temporary directory
+    fn new_temporary(mainnet: bool, sender: StandardPrincipalData) -> ReplSession {
+        let mut db_path = env::temp_dir();
+        db_path.push(format!("clarity-repl-{}", rand::thread_rng().gen::<u64>()));
+        let db_name = friendly_expect_opt(db_path.to_str(), "Non-UTF-8 temporary directory");
+        let header_db = CLIHeadersDB::new(db_name, mainnet);
+        let marf_kv = friendly_expect(
+            MarfedKV::open(db_name, None, None),
+            "Failed to open VM database.",
+        );
+        let mut session = ReplSession {
+            header_db,
+            marf_kv,
+            temporary_db_path: Some(db_path),
+            sender,
+            last_cost: None,
+        };
+        session.mine_block(|header_db, marf| install_boot_code(header_db, marf));
+        session
+    }
+
+    fn is_mainnet(&self) -> bool {
+        self.header_db.is_mainnet()
+    }
+
+    fn block_height(&self) -> u64 {
+        let chain_tip = get_cli_chain_tip(self.header_db.conn());
+        get_cli_block_height(self.header_db.conn(), &chain_tip).unwrap_or(0)
+    }
+
+    /// Run `f` in a new block on top of the chain tip, and commit the block
+    fn mine_block<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&CLIHeadersDB, &mut WritableMarfStore) -> R,
+    {
+        let (from, to) = self.header_db.advance_cli_chain_tip();
+        let mut marf = self.marf_kv.begin(&from, &to);
+        let result = f(&self.header_db, &mut marf);
+        marf.commit_to(&to).expect("FATAL: failed to commit block");
+        result
+    }
+
+    /// Handle one line of input: either a `::` command, or a Clarity expression to evaluate
+    fn handle_input(&mut self, input: &str) -> ReplOutput {
+        let input = input.trim();
+        let Some(command) = input.strip_prefix("::") else {
+            return ReplOutput::Continue(self.eval(input));
+        };
+        let mut words = command.split_whitespace();
+        let output = match (words.next(), words.next(), words.next()) {
+            (Some("deploy"), Some(contract_name), Some(program_file)) => {
+                match fs::read_to_string(program_file) {
+                    Ok(content) => self.deploy(contract_name, &content),
+                    Err(e) => format!("Error reading file {}:\n{}", program_file, e),
+                }
+            }
+            (Some("advance_chain_tip"), count, None) => {
+                match count.map(|count| count.parse::<u64>()).unwrap_or(Ok(1)) {
+                    Ok(count) => {
+                        for _ in 0..count {
+                            self.mine_block(|_, _| ());
+                        }
+                        format!("Chain tip advanced to block {}", self.block_height())
+                    }
+                    Err(_) => format!("Not a block count: {}", count.unwrap_or_default()),
+                }
+            }
+            (Some("get_costs"), None, None) => match self.last_cost.as_ref() {
+                Some(cost) => serde_json::to_string(cost).unwrap(),
+                None => "Nothing has been evaluated yet".to_string(),
+            },
+            (Some("set_tx_sender"), Some(sender), None) => {
+                match PrincipalData::parse_standard_principal(sender) {
+                    Ok(sender) => {
+                        self.sender = sender;
+                        format!("tx-sender set to {}", self.sender)
+                    }
+                    Err(e) => format!("Not a standard principal: {}", e),
+                }
+            }
+            (Some("get_tx_sender"), None, None) => self.sender.to_string(),
+            (Some("exit"), None, None) => return ReplOutput::Exit,
+            _ => "Commands:
+  ::deploy <contract-name> <program-file.clar>  deploy a contract issued by the tx-sender
+  ::advance_chain_tip [count]                   mine empty blocks (one by default)
+  ::get_costs                                   show the cost of the last evaluation or deployment
+  ::set_tx_sender <principal>                   set the tx-sender of evaluations and deployments
+  ::get_tx_sender                               show the tx-sender
+  ::exit                                        end the session"
+                .to_string(),
+        };
+        ReplOutput::Continue(output)
+    }
+
+    /// Deploy `content` as the contract `contract_name`, issued by the tx-sender
+    fn deploy(&mut self, contract_name: &str, content: &str) -> String {
+        let contract_name = match ContractName::try_from(contract_name.to_string()) {
+            Ok(contract_name) => contract_name,
+            Err(e) => return format!("Not a contract name: {}", e),
+        };
+        let contract_identifier =
+            QualifiedContractIdentifier::new(self.sender.clone(), contract_name);
+        let mut ast = match parse(&contract_identifier, content, ClarityVersion::Clarity2) {
+            Ok(ast) => ast,
+            Err(e) => return format!("Parse error:\n{}", e),
+        };
+        let mainnet = self.is_mainnet();
+        let (result, cost) = self.mine_block(|header_db, marf| {
+            if let Err((e, _)) = run_analysis(&contract_identifier, &mut ast, header_db, marf, true)
+            {
+                return (format!("Type check error:\n{}", e), None);
+            }
+            let (result, cost) = with_env_costs(mainnet, header_db, marf, None, None, |vm_env| {
+                vm_env.initialize_versioned_contract(
+                    contract_identifier.clone(),
+                    ClarityVersion::Clarity2,
+                    content,
+                    None,
+                    ASTRules::PrecheckSize,
+                )
+            });
+            match result {
+                Ok(_) => (format!("Deployed {}", contract_identifier), Some(cost)),
+                Err(e) => (format!("Execution error:\n{}", e), Some(cost)),
+            }
+        });
+        if cost.is_some() {
+            self.last_cost = cost;
+        }
+        result
+    }
+
+    /// Evaluate the first expression in `content` as the tx-sender, and show its value and type
+    fn eval(&mut self, content: &str) -> String {
+        let contract_id = QualifiedContractIdentifier::transient();
+        let mut ast = match parse(&contract_id, content, ClarityVersion::Clarity2) {
+            Ok(ast) => ast,
+            Err(e) => return format!("Parse error:\n{}", e),
+        };
+        if ast.is_empty() {
+            return String::new();
+        }
+        let mainnet = self.is_mainnet();
+        let sender = PrincipalData::Standard(self.sender.clone());
+        let (result, cost) = self.mine_block(|header_db, marf| {
+            let analysis = match run_analysis(&contract_id, &mut ast, header_db, marf, false) {
+                Ok(analysis) => analysis,
+                Err((e, _)) => return (format!("Type check error:\n{}", e), None),
+            };
+            let value_type = analysis
+                .type_map
+                .as_ref()
+                .and_then(|type_map| type_map.get_type(&ast[0]))
+                .cloned();
+            let (result, cost) = with_env_costs(mainnet, header_db, marf, None, None, |vm_env| {
+                let eval_context =
+                    ContractContext::new(contract_id.clone(), ClarityVersion::Clarity2);
+                vm_env.execute_in_env(sender, None, Some(eval_context), |env| {
+                    env.eval_raw_with_rules(content, ASTRules::PrecheckSize)
+                })
+            });
+            let output = match (result, value_type) {
+                (Ok((value, _, _)), Some(value_type)) => format!("{} :: {}", value, value_type),
+                (Ok((value, _, _)), None) => value.to_string(),
+                (Err(e), _) => format!("Execution error:\n{}", e),
+            };
+            (output, Some(cost))
+        });
+        if cost.is_some() {
+            self.last_cost = cost;
+        }
+        result
+    }
+}
+
+impl Drop for ReplSession {
+    fn drop(&mut self) {
+        if let Some(db_path) = self.temporary_db_path.as_ref() {
+            if let Err(e) = fs::remove_dir_all(db_path) {
+                eprintln!("Failed to remove {:?}: {:?}", db_path, e);
+            }
+        }
+    }
+}
+
 pub fn add_costs(result: &mut serde_json::Value, costs: bool, runtime: ExecutionCost) {
     if costs {
         result["costs"] = serde_json::to_value(runtime).unwrap();
     }
 }
 
+pub fn add_trace(result: &mut serde_json::Value, tracer: Option<ExecutionTracer>) {
+    if let Some(tracer) = tracer {
+        result["trace"] = serde_json::to_value(tracer.into_events()).unwrap();
+    }
+}
+
 pub fn add_assets(result: &mut serde_json::Value, assets: bool, asset_map: AssetMap) {
     if assets {
         result["assets"] = asset_map.to_json();
@@ -1077,7 +1358,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
         "check" => {
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] [--format json|sarif] (vm-state.db)",
                     invoked_by, args[0]
                 );
                 panic_test!();
@@ -1112,6 +1393,16 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 false
             };
 
+            let sarif = match consume_arg(&mut argv, &["--format"], true) {
+                Ok(None) => false,
+                Ok(Some(format)) if format == "json" => false,
+                Ok(Some(format)) if format == "sarif" => true,
+                _ => {
+                    eprintln!("Expected --format to be one of 'json' or 'sarif'");
+                    panic_test!();
+                }
+            };
+
             // NOTE: ignored if we're using a DB
             let mut testnet_given = false;
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
@@ -1177,6 +1468,31 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             };
 
+            if sarif {
+                let uri = if &argv[1] == "-" {
+                    format!("{}.clar", &contract_id.name)
+                } else {
+                    argv[1].clone()
+                };
+                let (exit_code, diagnostics) = match &contract_analysis_res {
+                    Ok(contract_analysis) => (
+                        0,
+                        contract_analysis
+                            .warnings
+                            .iter()
+                            .map(|warning| warning.diagnostic.clone())
+                            .collect(),
+                    ),
+                    Err((e, _)) => (1, vec![e.diagnostic.clone()]),
+                };
+                let log = SarifLog::new(
+                    "clarity-cli",
+                    env!("CARGO_PKG_VERSION"),
+                    &[(uri, diagnostics)],
+                );
+                return (exit_code, Some(serde_json::to_value(&log).unwrap()));
+            }
+
             let mut contract_analysis = match contract_analysis_res {
                 Ok(contract_analysis) => contract_analysis,
                 Err((e, cost_tracker)) => {
@@ -1201,6 +1517,15 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 contract_analysis.take_contract_cost_tracker().get_total(),
             );
 
+            if !contract_analysis.warnings.is_empty() {
+                let warnings: Vec<_> = contract_analysis
+                    .warnings
+                    .iter()
+                    .map(|warning| serde_json::to_value(&warning.diagnostic).unwrap())
+                    .collect();
+                result["warnings"] = json!(warnings);
+            }
+
             if output_analysis {
                 result["analysis"] =
                     serde_json::to_value(&build_contract_interface(&contract_analysis).unwrap())
@@ -1208,28 +1533,321 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             }
             (0, Some(result))
         }
-        "repl" => {
+        "check_deployment" => {
             let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let deployer = if let Ok(optarg) = consume_arg(&mut argv, &["--deployer"], true) {
+                optarg
+                    .map(|optarg_str| {
+                        friendly_expect(
+                            PrincipalData::parse_standard_principal(&optarg_str),
+                            &format!("Error parsing deployer address '{}'", &optarg_str),
+                        )
+                    })
+                    .unwrap_or(StandardPrincipalData::transient())
+            } else {
+                eprintln!("Expected argument for --deployer");
+                panic_test!();
+            };
+
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
                 false
             } else {
                 true
             };
+
+            if argv.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [--deployer ADDRESS] [--testnet] [program-file.clar]...",
+                    invoked_by, argv[0]
+                );
+                panic_test!();
+            }
+
+            let mut sources = vec![];
+            for path in argv[1..].iter() {
+                let contract_name = friendly_expect_opt(
+                    PathBuf::from(path)
+                        .file_stem()
+                        .and_then(OsStr::to_str)
+                        .map(|stem| stem.to_string()),
+                    &format!("Error reading contract name from file name: {}", path),
+                );
+                let contract_identifier = QualifiedContractIdentifier::new(
+                    deployer.clone(),
+                    friendly_expect(
+                        ContractName::try_from(contract_name),
+                        &format!("Invalid contract name from file name: {}", path),
+                    ),
+                );
+                let content = friendly_expect(
+                    fs::read_to_string(path),
+                    &format!("Error reading file: {}", path),
+                );
+                sources.push((contract_identifier, content));
+            }
+            let contracts: Vec<_> = sources
+                .iter()
+                .map(|(contract_identifier, content)| {
+                    (contract_identifier.clone(), content.as_str())
+                })
+                .collect();
+
+            let header_db = CLIHeadersDB::new_memory(mainnet);
             let mut marf = MemoryBackingStore::new();
-            let mut vm_env = OwnedEnvironment::new_free(
+            install_boot_code(&header_db, &mut marf);
+            let cost_track = LimitedCostTracker::new(
                 mainnet,
                 default_chain_id(mainnet),
-                marf.as_clarity_db(),
+                if mainnet {
+                    BLOCK_LIMIT_MAINNET_205.clone()
+                } else {
+                    HELIUM_BLOCK_LIMIT_20.clone()
+                },
+                &mut marf.get_clarity_db(&header_db, &NULL_BURN_STATE_DB),
                 DEFAULT_CLI_EPOCH,
+            )
+            .unwrap();
+            let report = analysis::deployment::analyze_deployment(
+                &contracts,
+                &mut marf,
+                cost_track,
+                DEFAULT_CLI_EPOCH,
+                ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH),
             );
-            let mut placeholder_context = ContractContext::new(
-                QualifiedContractIdentifier::transient(),
-                ClarityVersion::Clarity2,
+
+            let failures: serde_json::Map<_, _> = report
+                .failures
+                .iter()
+                .map(|(contract_identifier, diagnostic)| {
+                    (
+                        contract_identifier.to_string(),
+                        serde_json::to_value(diagnostic).unwrap(),
+                    )
+                })
+                .collect();
+            let trait_conformance: Vec<_> = report
+                .trait_conformance
+                .iter()
+                .map(|conformance| {
+                    json!({
+                        "contract": conformance.contract_identifier.to_string(),
+                        "trait": conformance.trait_identifier.to_string(),
+                        "declared": conformance.declared,
+                    })
+                })
+                .collect();
+            let mut costs = serde_json::Map::new();
+            for ((contract_identifier, function_name), cost) in report.callable_function_costs() {
+                costs.insert(
+                    format!("{}.{}", contract_identifier, function_name),
+                    serde_json::to_value(cost).unwrap(),
+                );
+            }
+
+            let result = json!({
+                "message": if report.is_deployable() { "Checks passed." } else { "Checks failed." },
+                "deployment_order": report
+                    .deployment_order
+                    .iter()
+                    .map(|contract_identifier| contract_identifier.to_string())
+                    .collect::<Vec<_>>(),
+                "dependency_cycles": report
+                    .dependency_cycles
+                    .iter()
+                    .map(|cycle| cycle.iter().map(|contract_identifier| contract_identifier.to_string()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+                "failures": failures,
+                "trait_conformance": trait_conformance,
+                "costs": costs,
+            });
+            if report.is_deployable() {
+                (0, Some(result))
+            } else {
+                (1, Some(result))
+            }
+        }
+        "estimate_deploy_cost" => {
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--testnet] (vm-state.db)",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let contract_id = if let Ok(optarg) = consume_arg(&mut argv, &["--contract_id"], true) {
+                optarg
+                    .map(|optarg_str| {
+                        friendly_expect(
+                            QualifiedContractIdentifier::parse(&optarg_str),
+                            &format!("Error parsing contract identifier '{}", &optarg_str),
+                        )
+                    })
+                    .unwrap_or(QualifiedContractIdentifier::transient())
+            } else {
+                eprintln!("Expected argument for --contract-id");
+                panic_test!();
+            };
+
+            // NOTE: ignored if we're using a DB
+            let mut testnet_given = false;
+            let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
+                testnet_given = true;
+                false
+            } else {
+                true
+            };
+
+            let content: String = friendly_expect(
+                fs::read_to_string(&argv[1]),
+                &format!("Error reading file: {}", argv[1]),
             );
-            let mut exec_env = vm_env.get_exec_environment(None, None, &mut placeholder_context);
-            let mut analysis_marf = MemoryBackingStore::new();
 
-            let contract_id = QualifiedContractIdentifier::transient();
+            let (mainnet, estimate_res) = if argv.len() >= 3 {
+                // use a persisted marf
+                if testnet_given {
+                    eprintln!("WARN: ignoring --testnet in favor of DB state in {:?}. Re-instantiate the DB to change.", &argv[2]);
+                }
+
+                let vm_filename = &argv[2];
+                let header_db =
+                    friendly_expect(CLIHeadersDB::resume(vm_filename), "Failed to open CLI DB");
+                let marf_kv = friendly_expect(
+                    MarfedKV::open(vm_filename, None, None),
+                    "Failed to open VM database.",
+                );
+                let result = at_chaintip(&argv[2], marf_kv, |mut marf| {
+                    let result =
+                        estimate_deploy_cost(&contract_id, &content, &header_db, &mut marf);
+                    (marf, result)
+                });
+                (header_db.is_mainnet(), result)
+            } else {
+                let header_db = CLIHeadersDB::new_memory(mainnet);
+                let mut marf = MemoryBackingStore::new();
+                install_boot_code(&header_db, &mut marf);
+                let result = estimate_deploy_cost(&contract_id, &content, &header_db, &mut marf);
+                (mainnet, result)
+            };
+
+            let estimate = match estimate_res {
+                Ok(estimate) => estimate,
+                Err(diagnostic) => {
+                    let result = json!({
+                        "message": "Checks failed.",
+                        "error": {
+                            "analysis": serde_json::to_value(&diagnostic).unwrap(),
+                        }
+                    });
+                    return (1, Some(result));
+                }
+            };
+
+            let block_limit = if mainnet {
+                BLOCK_LIMIT_MAINNET_205.clone()
+            } else {
+                HELIUM_BLOCK_LIMIT_20.clone()
+            };
+            let fits = estimate.fits_within(&block_limit);
+            let result = json!({
+                "message": if fits { "Contract fits in a block." } else { "Contract does not fit in a block." },
+                "estimate": serde_json::to_value(&estimate).unwrap(),
+                "block_limit": serde_json::to_value(&block_limit).unwrap(),
+                "percent_of_block": block_limit.proportion_largest_dimension(&estimate.total),
+            });
+            if fits {
+                (0, Some(result))
+            } else {
+                (1, Some(result))
+            }
+        }
+        "format" => {
+            if args.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [program-file.clar|-] [--check]",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let check_only = if let Ok(Some(_)) = consume_arg(&mut argv, &["--check"], false) {
+                true
+            } else {
+                false
+            };
+
+            let content: String = if &argv[1] == "-" {
+                let mut buffer = String::new();
+                friendly_expect(
+                    io::stdin().read_to_string(&mut buffer),
+                    "Error reading from stdin.",
+                );
+                buffer
+            } else {
+                friendly_expect(
+                    fs::read_to_string(&argv[1]),
+                    &format!("Error reading file: {}", argv[1]),
+                )
+            };
+
+            let formatted = match format_source(&content) {
+                Ok(formatted) => formatted,
+                Err(e) => {
+                    let result = json!({
+                        "message": "Failed to parse program.",
+                        "error": {
+                            "parse": format!("{}", e),
+                        }
+                    });
+                    return (1, Some(result));
+                }
+            };
+
+            let already_formatted = formatted == content;
+            if check_only {
+                let result = json!({
+                    "message": if already_formatted { "Program is formatted." } else { "Program is not formatted." },
+                    "formatted": already_formatted,
+                });
+                if already_formatted {
+                    (0, Some(result))
+                } else {
+                    (1, Some(result))
+                }
+            } else {
+                let result = json!({
+                    "message": "Program formatted.",
+                    "changed": !already_formatted,
+                    "output": formatted,
+                });
+                (0, Some(result))
+            }
+        }
+        "repl" => {
+            let mut argv: Vec<String> = args.into_iter().map(|x| x.clone()).collect();
+            let sender = if let Ok(optarg) = consume_arg(&mut argv, &["--tx-sender"], true) {
+                let sender_in = optarg.unwrap_or(DEFAULT_REPL_SENDER.to_string());
+                friendly_expect(
+                    PrincipalData::parse_standard_principal(&sender_in),
+                    &format!("Error parsing tx-sender '{}'", &sender_in),
+                )
+            } else {
+                eprintln!("Expected argument for --tx-sender");
+                panic_test!();
+            };
+            let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
+                false
+            } else {
+                true
+            };
+            let mut session = if argv.len() >= 2 {
+                // use a persisted marf
+                ReplSession::resume(&argv[1], sender)
+            } else {
+                ReplSession::new_temporary(mainnet, sender)
+            };
 
             let mut stdout = io::stdout();
 
@@ -1243,6 +1861,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         panic!("Failed to flush stdout prompt string:\n{}", e);
                     });
                     match io::stdin().read_line(&mut buffer) {
+                        Ok(0) => break,
                         Ok(_) => buffer,
                         Err(error) => {
                             eprintln!("Error reading from stdin:\n{}", error);
@@ -1251,33 +1870,12 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 };
 
-                let mut ast = match parse(&contract_id, &content, ClarityVersion::Clarity2) {
-                    Ok(val) => val,
-                    Err(error) => {
-                        println!("Parse error:\n{}", error);
-                        continue;
-                    }
-                };
-
-                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
-                    Ok(_) => (),
-                    Err((error, _)) => {
-                        println!("Type check error:\n{}", error);
-                        continue;
-                    }
+                match session.handle_input(&content) {
+                    ReplOutput::Continue(output) => println!("{}", output),
+                    ReplOutput::Exit => break,
                 }
-
-                let eval_result =
-                    match exec_env.eval_raw_with_rules(&content, ASTRules::PrecheckSize) {
-                        Ok(val) => val,
-                        Err(error) => {
-                            println!("Execution error:\n{}", error);
-                            continue;
-                        }
-                    };
-
-                println!("{}", eval_result);
             }
+            (0, None)
         }
         "eval_raw" => {
             let content: String = {
@@ -1365,7 +1963,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, None, None, |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1442,6 +2040,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    None,
                     |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
@@ -1529,7 +2128,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             );
             let result_and_cost = at_block(chain_tip, marf_kv, |mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, None, None, |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1660,6 +2259,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                                 &header_db,
                                 &mut marf,
                                 coverage.as_mut(),
+                                None,
                                 |vm_env| {
                                     vm_env.initialize_versioned_contract(
                                         contract_identifier,
@@ -1740,9 +2340,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 false
             };
+            let mut tracer = if let Ok(Some(_)) = consume_arg(&mut argv, &["--trace"], false) {
+                Some(ExecutionTracer::new())
+            } else {
+                None
+            };
 
             if argv.len() < 5 {
-                eprintln!("Usage: {} {} [--costs] [--assets] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
+                eprintln!("Usage: {} {} [--costs] [--assets] [--trace] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
                 panic_test!();
             }
 
@@ -1798,6 +2403,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     &header_db,
                     &mut marf,
                     coverage.as_mut(),
+                    tracer.as_mut(),
                     |vm_env| {
                         vm_env.execute_transaction(
                             sender,
@@ -1826,6 +2432,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                             add_serialized_output(&mut result, *data.data);
                             add_costs(&mut result, costs, cost);
                             add_assets(&mut result, assets, asset_map);
+                            add_trace(&mut result, tracer);
 
                             let events_json: Vec<_> = events
                                 .into_iter()
@@ -1846,28 +2453,31 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                             add_costs(&mut result, costs, cost);
                             add_serialized_output(&mut result, *data.data);
                             add_assets(&mut result, assets, asset_map);
+                            add_trace(&mut result, tracer);
 
                             (0, Some(result))
                         }
                     } else {
-                        let result = json!({
+                        let mut result = json!({
                             "error": {
                                 "runtime": "Expected a ResponseType result from transaction.",
                                 "output": serde_json::to_value(&x).unwrap()
                             },
                             "success": false,
                         });
+                        add_trace(&mut result, tracer);
                         (1, Some(result))
                     }
                 }
                 (Err(error), ..) => {
-                    let result = json!({
+                    let mut result = json!({
                         "error": {
                             "runtime": "Transaction execution error.",
                             "error": serde_json::to_value(&format!("{}", error)).unwrap()
                         },
                         "success": false,
                     });
+                    add_trace(&mut result, tracer);
                     (1, Some(result))
                 }
             }
@@ -2157,6 +2767,36 @@ mod test {
         assert!(result["message"].as_str().unwrap().len() > 0);
         assert!(result["events"].as_array().unwrap().len() == 0);
         assert_eq!(result["output"], json!({"UInt": 1000}));
+        assert!(result["trace"] == json!(null));
+
+        eprintln!("execute tokens with trace");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "execute".to_string(),
+                "--trace".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u1000".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        let trace = result["trace"].as_array().unwrap();
+        assert_eq!(trace[0]["event"], "contract_call");
+        assert_eq!(trace[0]["function"], "mint!");
+        assert_eq!(trace[0]["args"], json!(["u1000"]));
+        assert!(trace
+            .iter()
+            .any(|event| event["event"] == "data_write" && event["name"] == "tokens"));
+        let last = trace.last().unwrap();
+        assert_eq!(last["event"], "contract_return");
+        assert_eq!(last["result"], "(ok u1000)");
 
         eprintln!("eval tokens");
         let invoked = invoke_command(
@@ -2271,6 +2911,296 @@ mod test {
         assert!(result["costs"] != json!(null));
     }
 
+    #[test]
+    fn test_check_deployment() {
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check_deployment".to_string(),
+                "../sample-contracts/names.clar".to_string(),
+                "../sample-contracts/tokens.clar".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert_eq!(
+            result["deployment_order"],
+            json!([
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens",
+                "S1G2081040G2081040G2081040G208105NK8PE5.names"
+            ])
+        );
+        assert!(
+            result["costs"]["S1G2081040G2081040G2081040G208105NK8PE5.names.register"]["runtime"]
+                .is_u64()
+        );
+
+        eprintln!("check deployment without a dependency");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check_deployment".to_string(),
+                "../sample-contracts/names.clar".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert!(result["failures"]["S1G2081040G2081040G2081040G208105NK8PE5.names"].is_object());
+    }
+
+    #[test]
+    fn test_check_sarif() {
+        let contract_path = format!("/tmp/sarif_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_path,
+            "(define-private (helper) u1)\n(define-read-only (get-one) u1)",
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                contract_path.clone(),
+                "--format".to_string(),
+                "sarif".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert_eq!(result["version"], "2.1.0");
+        let results = result["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "UnusedPrivateFunction");
+        assert_eq!(results[0]["level"], "warning");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            json!(contract_path)
+        );
+        // expressions only carry their source spans in developer mode
+        #[cfg(feature = "developer-mode")]
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            1
+        );
+
+        eprintln!("check a contract that fails analysis");
+        fs::write(&contract_path, "(define-read-only (get-one) (+ u1 1))").unwrap();
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--format".to_string(),
+                "sarif".to_string(),
+                contract_path.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        let results = result["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "TypeError");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            result["runs"][0]["tool"]["driver"]["rules"],
+            json!([{"id": "TypeError"}])
+        );
+
+        fs::remove_file(&contract_path).unwrap();
+    }
+
+    #[test]
+    fn test_estimate_deploy_cost() {
+        let invoked = invoke_command(
+            "test",
+            &[
+                "estimate_deploy_cost".to_string(),
+                "../sample-contracts/tokens.clar".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        let estimate = &result["estimate"];
+        assert!(estimate["parse"]["runtime"].as_u64().unwrap() > 0);
+        assert!(estimate["analysis"]["runtime"].as_u64().unwrap() > 0);
+        // storing the contract writes its source
+        let source_len = fs::read_to_string("../sample-contracts/tokens.clar")
+            .unwrap()
+            .len() as u64;
+        assert!(estimate["storage"]["write_length"].as_u64().unwrap() >= source_len);
+        assert_eq!(
+            estimate["total"]["runtime"].as_u64().unwrap(),
+            estimate["parse"]["runtime"].as_u64().unwrap()
+                + estimate["analysis"]["runtime"].as_u64().unwrap()
+                + estimate["storage"]["runtime"].as_u64().unwrap()
+        );
+
+        eprintln!("estimate an ill-typed contract");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "estimate_deploy_cost".to_string(),
+                "../sample-contracts/names.clar".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert!(result["error"]["analysis"].is_object());
+    }
+
+    #[test]
+    fn test_format() {
+        let invoked = invoke_command(
+            "test",
+            &[
+                "format".to_string(),
+                "../sample-contracts/tokens.clar".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        let formatted = result["output"].as_str().unwrap().to_string();
+
+        // formatting is idempotent
+        let formatted_file = format!("/tmp/format_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(&formatted_file, &formatted).unwrap();
+        let invoked = invoke_command(
+            "test",
+            &[
+                "format".to_string(),
+                formatted_file.clone(),
+                "--check".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert_eq!(result["formatted"], json!(true));
+
+        eprintln!("check a program that is not formatted");
+        fs::write(&formatted_file, "(define-constant    a\n  1)").unwrap();
+        let invoked = invoke_command(
+            "test",
+            &[
+                "format".to_string(),
+                formatted_file.clone(),
+                "--check".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["formatted"], json!(false));
+
+        eprintln!("format a program that does not parse");
+        fs::write(&formatted_file, "(define-constant a 1").unwrap();
+        let invoked = invoke_command("test", &["format".to_string(), formatted_file.clone()]);
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 1);
+        assert!(result["error"]["parse"].is_string());
+    }
+
+    fn repl_output(session: &mut ReplSession, input: &str) -> String {
+        match session.handle_input(input) {
+            ReplOutput::Continue(output) => output,
+            ReplOutput::Exit => panic!("Session ended on {}", input),
+        }
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let sender = PrincipalData::parse_standard_principal(DEFAULT_REPL_SENDER).unwrap();
+        let mut session = ReplSession::new_temporary(false, sender);
+        let db_path = session.temporary_db_path.clone().unwrap();
+
+        let contract_file = format!("/tmp/repl_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_file,
+            "(define-data-var counter uint u0)
+             (define-public (increment) (begin (var-set counter (+ (var-get counter) u1)) (ok (var-get counter))))
+             (define-read-only (get-counter) (var-get counter))",
+        )
+        .unwrap();
+        assert_eq!(
+            repl_output(&mut session, &format!("::deploy counter {}", contract_file)),
+            format!("Deployed {}.counter", DEFAULT_REPL_SENDER)
+        );
+        assert!(session.last_cost.as_ref().unwrap().runtime > 0);
+
+        // Data var mutations persist between evaluations
+        let increment = format!(
+            "(contract-call? '{}.counter increment)",
+            DEFAULT_REPL_SENDER
+        );
+        assert_eq!(
+            repl_output(&mut session, &increment),
+            "(ok u1) :: (response uint UnknownType)"
+        );
+        assert_eq!(
+            repl_output(&mut session, &increment),
+            "(ok u2) :: (response uint UnknownType)"
+        );
+        assert_eq!(repl_output(&mut session, "(+ 1 2)"), "3 :: int");
+        let costs: ExecutionCost =
+            serde_json::from_str(&repl_output(&mut session, "::get_costs")).unwrap();
+        assert!(costs.runtime > 0);
+
+        // Each evaluation is mined in a block of its own
+        let height = session.block_height();
+        assert_eq!(
+            repl_output(&mut session, "::advance_chain_tip 3"),
+            format!("Chain tip advanced to block {}", height + 3)
+        );
+
+        assert_eq!(
+            repl_output(
+                &mut session,
+                "::set_tx_sender SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY"
+            ),
+            "tx-sender set to SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY"
+        );
+        assert_eq!(
+            repl_output(&mut session, "tx-sender"),
+            "SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY :: principal"
+        );
+
+        assert!(repl_output(&mut session, "(+ 1 u2)").starts_with("Type check error"));
+        assert!(repl_output(&mut session, "(+ 1").starts_with("Parse error"));
+        assert!(matches!(session.handle_input("::exit"), ReplOutput::Exit));
+
+        // The session's database is removed when it ends
+        drop(session);
+        assert!(fs::metadata(&db_path).is_err());
+    }
+
     #[test]
     fn test_assets() {
         let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
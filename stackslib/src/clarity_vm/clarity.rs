@@ -245,6 +245,15 @@ impl ClarityInstance {
         self.mainnet
     }
 
+    pub fn set_map_key_index(&mut self, enabled: bool) -> Result<(), Error> {
+        self.datastore.set_map_key_index(enabled)?;
+        Ok(())
+    }
+
+    pub fn is_map_key_index_enabled(&self) -> bool {
+        self.datastore.is_map_key_index_enabled()
+    }
+
     /// Returns the Stacks epoch of the burn block that elected `stacks_block`
     fn get_epoch_of(
         stacks_block: &StacksBlockId,
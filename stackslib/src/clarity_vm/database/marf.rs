@@ -4,12 +4,13 @@ use std::str::FromStr;
 use clarity::vm::analysis::AnalysisDatabase;
 use clarity::vm::database::{
     BurnStateDB, ClarityBackingStore, ClarityDatabase, HeadersDB, SpecialCaseHandler,
-    SqliteConnection,
+    SqliteConnection, StoreType,
 };
 use clarity::vm::errors::{
     IncomparableError, InterpreterError, InterpreterResult, RuntimeErrorType,
 };
 use clarity::vm::types::QualifiedContractIdentifier;
+use rusqlite::types::ToSql;
 use rusqlite::Connection;
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::{BlockHeaderHash, StacksBlockId, TrieHash};
@@ -20,7 +21,56 @@ use crate::chainstate::stacks::index::{
 };
 use crate::clarity_vm::special::handle_contract_call_special_cases;
 use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
-use crate::util_lib::db::{Error as DatabaseError, IndexDBConn};
+use crate::util_lib::db::{query_rows, Error as DatabaseError, IndexDBConn};
+
+/// Every data map key written while `map_key_index` is on, across all forks.  A key being listed
+/// here does not mean it is set at any particular tip.
+const DATA_MAP_KEYS_TABLE: &'static str = r#"
+    CREATE TABLE IF NOT EXISTS data_map_keys(
+        contract_identifier TEXT NOT NULL,
+        map_name TEXT NOT NULL,
+        -- hex serialization of the key's Clarity value
+        key_hex TEXT NOT NULL,
+        PRIMARY KEY(contract_identifier, map_name, key_hex)
+    );"#;
+
+/// Split a MARF key into the contract, map name, and key hex of the data map entry it stores, if
+/// it stores one.  Data map entries are stored under
+/// `vm::{contract}::{StoreType::DataMap}::{map}::{key}`.
+fn parse_data_map_key(key: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = key.splitn(5, "::");
+    if parts.next()? != "vm" {
+        return None;
+    }
+    let contract_identifier = parts.next()?;
+    if parts.next()? != format!("{}", StoreType::DataMap as u8) {
+        return None;
+    }
+    let map_name = parts.next()?;
+    let key_hex = parts.next()?;
+    Some((contract_identifier, map_name, key_hex))
+}
+
+/// Get up to `limit` keys of a data map that were ever written while `map_key_index` was on,
+/// in order, starting after `after`.  The caller must check which of them are set at its tip.
+pub fn get_data_map_key_candidates(
+    conn: &Connection,
+    contract_identifier: &QualifiedContractIdentifier,
+    map_name: &str,
+    after: Option<&str>,
+    limit: u32,
+) -> Result<Vec<String>, DatabaseError> {
+    let sql = "SELECT key_hex FROM data_map_keys
+        WHERE contract_identifier = ?1 AND map_name = ?2 AND key_hex > ?3
+        ORDER BY key_hex ASC LIMIT ?4";
+    let args: &[&dyn ToSql] = &[
+        &contract_identifier.to_string(),
+        &map_name,
+        &after.unwrap_or(""),
+        &limit,
+    ];
+    query_rows(conn, sql, args)
+}
 
 /// The MarfedKV struct is used to wrap a MARF data structure and side-storage
 ///   for use as a K/V store for ClarityDB or the AnalysisDB.
@@ -32,6 +82,8 @@ use crate::util_lib::db::{Error as DatabaseError, IndexDBConn};
 pub struct MarfedKV {
     chain_tip: StacksBlockId,
     marf: MARF<StacksBlockId>,
+    /// whether to record the data map keys written by each block in `data_map_keys`
+    map_key_index: bool,
 }
 
 impl MarfedKV {
@@ -89,7 +141,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            map_key_index: false,
+        })
     }
 
     pub fn open_unconfirmed(
@@ -103,7 +159,11 @@ impl MarfedKV {
             None => StacksBlockId::sentinel(),
         };
 
-        Ok(MarfedKV { marf, chain_tip })
+        Ok(MarfedKV {
+            marf,
+            chain_tip,
+            map_key_index: false,
+        })
     }
 
     // used by benchmarks
@@ -134,7 +194,11 @@ impl MarfedKV {
 
         let chain_tip = StacksBlockId::sentinel();
 
-        MarfedKV { marf, chain_tip }
+        MarfedKV {
+            marf,
+            chain_tip,
+            map_key_index: false,
+        }
     }
 
     pub fn begin_read_only<'a>(
@@ -212,6 +276,7 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            map_key_index: self.map_key_index,
         }
     }
 
@@ -233,6 +298,7 @@ impl MarfedKV {
         WritableMarfStore {
             chain_tip,
             marf: tx,
+            map_key_index: self.map_key_index,
         }
     }
 
@@ -240,6 +306,27 @@ impl MarfedKV {
         &self.chain_tip
     }
 
+    /// Turn recording the data map keys written by each block on or off.  Only keys written
+    /// while it is on can be enumerated.
+    pub fn set_map_key_index(&mut self, enabled: bool) -> InterpreterResult<()> {
+        if enabled {
+            let tx = self
+                .marf
+                .storage_tx()
+                .map_err(|err| InterpreterError::DBError(err.to_string()))?;
+            tx.execute_batch(DATA_MAP_KEYS_TABLE)
+                .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+            tx.commit()
+                .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+        }
+        self.map_key_index = enabled;
+        Ok(())
+    }
+
+    pub fn is_map_key_index_enabled(&self) -> bool {
+        self.map_key_index
+    }
+
     pub fn get_marf(&mut self) -> &mut MARF<StacksBlockId> {
         &mut self.marf
     }
@@ -260,6 +347,7 @@ impl MarfedKV {
 pub struct WritableMarfStore<'a> {
     chain_tip: StacksBlockId,
     marf: MarfTransaction<'a, StacksBlockId>,
+    map_key_index: bool,
 }
 
 pub struct ReadOnlyMarfStore<'a> {
@@ -673,6 +761,16 @@ impl<'a> ClarityBackingStore for WritableMarfStore<'a> {
             trace!("MarfedKV put '{}' = '{}'", &key, &value);
             let marf_value = MARFValue::from_value(&value);
             SqliteConnection::put(self.get_side_store(), &marf_value.to_hex(), &value)?;
+            if self.map_key_index {
+                if let Some((contract_identifier, map_name, key_hex)) = parse_data_map_key(&key) {
+                    self.get_side_store()
+                        .execute(
+                            "INSERT OR IGNORE INTO data_map_keys (contract_identifier, map_name, key_hex) VALUES (?1, ?2, ?3)",
+                            &[contract_identifier, map_name, key_hex],
+                        )
+                        .map_err(|err| InterpreterError::SqliteError(IncomparableError { err }))?;
+                }
+            }
             keys.push(key);
             values.push(marf_value);
         }
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use clarity::vm::analysis::{run_analysis_with_passes, AnalysisPassRegistry};
 use clarity::vm::ast::ASTRules;
 use clarity::vm::clarity::TransactionConnection;
 use clarity::vm::contexts::{
@@ -318,3 +319,93 @@ fn epoch_205_test_all_mainnet() {
 fn epoch_205_test_all_testnet() {
     epoch_205_test_all(false);
 }
+
+#[test]
+fn test_cost_bounds() {
+    let mut clarity_instance =
+        setup_tracked_cost_test(false, StacksEpochId::Epoch21, ClarityVersion::Clarity2);
+    let contract = "(define-trait callee ((foo () (response bool uint))))
+        (define-data-var count uint u0)
+        (define-private (inc (x uint)) (+ x u1))
+        (define-public (noop) (ok true))
+        (define-public (bump) (ok (var-set count (inc (var-get count)))))
+        (define-read-only (sum-10 (xs (list 10 uint))) (fold + xs u0))
+        (define-read-only (sum-20 (xs (list 20 uint))) (fold + xs u0))
+        (define-public (dynamic (c <callee>)) (contract-call? c foo))";
+    let p1 = PrincipalData::parse_standard_principal("SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR")
+        .unwrap();
+    let contract_id = QualifiedContractIdentifier::new(p1.clone(), "cost-bounds".into());
+    let sender = PrincipalData::Standard(p1);
+
+    let burn_state_db = UnitTestBurnStateDB {
+        epoch_id: StacksEpochId::Epoch21,
+        ast_rules: ASTRules::PrecheckSize,
+    };
+    let mut conn = clarity_instance.begin_block(
+        &StacksBlockId([3 as u8; 32]),
+        &StacksBlockId([200 as u8; 32]),
+        &TEST_HEADER_DB,
+        &burn_state_db,
+    );
+
+    let cost_bounds = conn.as_transaction(|conn| {
+        let (ast, analysis) = conn
+            .analyze_smart_contract(
+                &contract_id,
+                ClarityVersion::Clarity2,
+                contract,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        conn.initialize_smart_contract(
+            &contract_id,
+            ClarityVersion::Clarity2,
+            &ast,
+            contract,
+            None,
+            |_, _| false,
+        )
+        .unwrap();
+        conn.save_analysis(&contract_id, &analysis).unwrap();
+        // only the tooling passes compute cost bounds
+        assert!(analysis.cost_bounds.is_empty());
+        conn.with_analysis_db(|db, cost_track| {
+            let mut expressions = ast.expressions.clone();
+            let mut analysis = run_analysis_with_passes(
+                &contract_id,
+                &mut expressions,
+                db,
+                false,
+                cost_track,
+                StacksEpochId::Epoch21,
+                ClarityVersion::Clarity2,
+                &AnalysisPassRegistry::tooling(),
+                false,
+            )
+            .map_err(|(e, _)| e)
+            .unwrap();
+            (analysis.take_contract_cost_tracker(), analysis.cost_bounds)
+        })
+    });
+
+    let bump = &cost_bounds["bump"];
+    assert!(bump.read_count >= 1);
+    assert!(bump.write_count >= 1);
+    assert!(cost_bounds["sum-20"].runtime > cost_bounds["sum-10"].runtime);
+    assert!(cost_bounds.get("dynamic").is_none());
+
+    // calling `bump` costs no more than its bound over calling `noop`
+    let mut call_cost = |function: &str| {
+        conn.as_transaction(|conn| {
+            let before = conn.cost_so_far();
+            conn.run_contract_call(&sender, None, &contract_id, function, &[], |_, _| false)
+                .unwrap();
+            let mut cost = conn.cost_so_far();
+            cost.sub(&before).unwrap();
+            cost
+        })
+    };
+    let mut bump_cost = call_cost("bump");
+    bump_cost.sub(&call_cost("noop")).unwrap();
+    assert!(!bump_cost.exceeds(bump));
+}
@@ -167,6 +167,16 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         ToConsensusBuff => "(to-consensus-buff? u1)",
         FromConsensusBuff => "(from-consensus-buff? bool 0x03)",
         ReplaceAt => "(replace-at? list-bar u0 5)",
+        StxGetAccountInfo => "(stx-account-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        StxGetDelegationInfo => "(stx-delegation-info 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        ContractPrincipalConstruct => {
+            "(contract-principal-construct? 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR \"foo\")"
+        }
+        BitwiseLRotate => "(bit-rotate-left 2 u1)",
+        BitwiseRRotate => "(bit-rotate-right 2 u1)",
+        Log2UInt => "(log2-uint u8)",
+        UIntToAscii => "(uint-to-ascii u1)",
+        ConcatMany => "(concat-many list-bar list-bar list-bar)",
     }
 }
 
@@ -180,7 +190,7 @@ fn execute_transaction(
     env.execute_transaction(issuer, None, contract_identifier.clone(), tx, args)
 }
 
-fn with_owned_env<F, R>(epoch: StacksEpochId, use_mainnet: bool, to_do: F) -> R
+pub fn with_owned_env<F, R>(epoch: StacksEpochId, use_mainnet: bool, to_do: F) -> R
 where
     F: Fn(OwnedEnvironment) -> R,
 {
@@ -824,6 +834,56 @@ fn epoch205_nfts_testnet() {
     epoch205_nfts(false)
 }
 
+#[test]
+fn cost_profile_per_function() {
+    let contract = "(define-private (add-one (x int)) (+ x 1))
+        (define-public (execute) (ok (add-one (add-one 1))))";
+    let p1 = execute("'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR");
+    let p1_principal = match p1 {
+        Value::Principal(PrincipalData::Standard(ref data)) => data.clone(),
+        _ => panic!(),
+    };
+    let contract_id = QualifiedContractIdentifier::new(p1_principal.clone(), "self".into());
+
+    with_owned_env(StacksEpochId::Epoch21, false, |mut owned_env| {
+        owned_env
+            .initialize_contract(contract_id.clone(), contract, None, ASTRules::PrecheckSize)
+            .unwrap();
+
+        let cost_before = owned_env.get_cost_total();
+        owned_env.enable_cost_profiling();
+        execute_transaction(
+            &mut owned_env,
+            p1_principal.clone().into(),
+            &contract_id,
+            "execute",
+            &[],
+        )
+        .unwrap();
+
+        let mut cost = owned_env.get_cost_total();
+        cost.sub(&cost_before).unwrap();
+
+        let profile = owned_env.get_cost_profile().unwrap();
+        assert_eq!(profile.cost, cost);
+
+        let execute_frame = &profile.children[&format!("{}:execute", &contract_id)];
+        assert_eq!(execute_frame.count, 1);
+
+        let add_one = &execute_frame.children[&format!("{}:add-one", &contract_id)];
+        assert_eq!(add_one.count, 2);
+        assert_eq!(add_one.children["cost_add"].count, 2);
+        assert!(add_one.cost.runtime > 0);
+        assert!(add_one.cost.runtime < execute_frame.cost.runtime);
+
+        let folded = profile.folded_stacks("tx");
+        assert!(folded.contains(&format!(
+            "tx;{}:execute;{}:add-one;cost_add {}",
+            &contract_id, &contract_id, add_one.children["cost_add"].cost.runtime
+        )));
+    })
+}
+
 fn setup_cost_tracked_test(
     use_mainnet: bool,
     version: ClarityVersion,
@@ -990,6 +1050,9 @@ fn epoch_21_test_all(use_mainnet: bool) {
 
         for (ix, f) in NativeFunctions::ALL.iter().enumerate() {
             // Note: Include Clarity2 functions for Epoch21.
+            if f.get_version() > ClarityVersion::Clarity2 {
+                continue;
+            }
             let test = get_simple_test(f);
             let cost = test_program_cost(test, ClarityVersion::Clarity2, &mut owned_env, ix + 1);
             assert!(cost.exceeds(&baseline));
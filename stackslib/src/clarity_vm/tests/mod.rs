@@ -22,4 +22,6 @@ pub mod epoch_switch;
 pub mod events;
 pub mod forking;
 pub mod large_contract;
+#[cfg(feature = "clarity-property-tests")]
+pub mod properties;
 pub mod simple_tests;
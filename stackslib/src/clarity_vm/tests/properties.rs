@@ -0,0 +1,445 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Property tests of the Clarity native functions, run on randomly generated well-typed
+//! expressions. These are slow, so they only build with the `clarity-property-tests` feature:
+//!
+//!   cargo test -p stackslib --features clarity-property-tests clarity_vm::tests::properties
+//!
+//! Each test prints the seed it used. Set `CLARITY_PROPERTY_TEST_SEED` to replay a failure, and
+//! `CLARITY_PROPERTY_TEST_CASES` to change how many expressions are generated.
+
+use std::env;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use clarity::vm::analysis::mem_type_check;
+use clarity::vm::ast::ASTRules;
+use clarity::vm::contexts::{ContractContext, OwnedEnvironment};
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::errors::Error;
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::ClarityVersion;
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+use stacks_common::types::StacksEpochId;
+use stacks_common::util::hash::to_hex;
+
+use crate::clarity_vm::tests::costs::with_owned_env;
+
+const DEFAULT_CASES: usize = 200;
+const MAX_DEPTH: u32 = 4;
+
+/// The types of the expressions the generator produces
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GenType {
+    Int,
+    UInt,
+    Bool,
+    Buff,
+    IntList,
+    OptionalInt,
+}
+
+const ALL_TYPES: [GenType; 6] = [
+    GenType::Int,
+    GenType::UInt,
+    GenType::Bool,
+    GenType::Buff,
+    GenType::IntList,
+    GenType::OptionalInt,
+];
+
+/// Generates random well-typed Clarity expressions. Only natives from Clarity 1 are used unless
+/// `clarity2` is set, so that the expressions mean the same thing to every type checker.
+struct ExprGenerator {
+    rng: StdRng,
+    clarity2: bool,
+}
+
+impl ExprGenerator {
+    fn new(seed: u64, clarity2: bool) -> ExprGenerator {
+        ExprGenerator {
+            rng: StdRng::seed_from_u64(seed),
+            clarity2,
+        }
+    }
+
+    fn expression(&mut self) -> String {
+        let ty = ALL_TYPES[self.rng.gen_range(0, ALL_TYPES.len())];
+        self.gen(ty, 0)
+    }
+
+    fn gen(&mut self, ty: GenType, depth: u32) -> String {
+        if depth >= MAX_DEPTH || self.rng.gen_range(0, 4) == 0 {
+            return self.literal(ty);
+        }
+        let depth = depth + 1;
+        match ty {
+            GenType::Int => self.gen_int(depth),
+            GenType::UInt => self.gen_uint(depth),
+            GenType::Bool => self.gen_bool(depth),
+            GenType::Buff => self.gen_buff(depth),
+            GenType::IntList => self.gen_int_list(depth),
+            GenType::OptionalInt => self.gen_optional_int(depth),
+        }
+    }
+
+    fn literal(&mut self, ty: GenType) -> String {
+        match ty {
+            GenType::Int => match self.rng.gen_range(0, 5) {
+                0 => i128::MAX.to_string(),
+                1 => (-i128::MAX).to_string(),
+                _ => self.rng.gen_range(-100i128, 100i128).to_string(),
+            },
+            GenType::UInt => match self.rng.gen_range(0, 5) {
+                0 => format!("u{}", u128::MAX),
+                _ => format!("u{}", self.rng.gen_range(0u128, 200u128)),
+            },
+            GenType::Bool => self.rng.gen::<bool>().to_string(),
+            GenType::Buff => {
+                let len = self.rng.gen_range(1, 9);
+                let bytes: Vec<u8> = (0..len).map(|_| self.rng.gen()).collect();
+                format!("0x{}", to_hex(&bytes))
+            }
+            GenType::IntList => {
+                let len = self.rng.gen_range(1, 5);
+                let items: Vec<_> = (0..len).map(|_| self.literal(GenType::Int)).collect();
+                format!("(list {})", items.join(" "))
+            }
+            GenType::OptionalInt => format!("(some {})", self.literal(GenType::Int)),
+        }
+    }
+
+    fn gen_int(&mut self, depth: u32) -> String {
+        let forms = if self.clarity2 { 13 } else { 10 };
+        match self.rng.gen_range(0, forms) {
+            0 => self.binary(
+                &["+", "-", "*", "/", "mod", "pow", "xor"],
+                GenType::Int,
+                depth,
+            ),
+            1 => self.variadic(&["+", "-", "*"], GenType::Int, depth),
+            2 => self.unary(&["sqrti", "log2"], GenType::Int, depth),
+            3 => format!("(to-int {})", self.gen(GenType::UInt, depth)),
+            4 => self.gen_if(GenType::Int, depth),
+            5 => format!(
+                "(default-to {} {})",
+                self.gen(GenType::Int, depth),
+                self.gen(GenType::OptionalInt, depth)
+            ),
+            6 => format!("(unwrap-panic {})", self.gen(GenType::OptionalInt, depth)),
+            7 => format!(
+                "(fold + {} {})",
+                self.gen(GenType::IntList, depth),
+                self.gen(GenType::Int, depth)
+            ),
+            8 => format!(
+                "(let ((a {})) (+ a {}))",
+                self.gen(GenType::Int, depth),
+                self.gen(GenType::Int, depth)
+            ),
+            9 => format!(
+                "(get a {{ a: {}, b: {} }})",
+                self.gen(GenType::Int, depth),
+                self.gen(GenType::Bool, depth)
+            ),
+            10 => self.binary(&["bit-and", "bit-or", "bit-xor"], GenType::Int, depth),
+            11 => format!(
+                "(bit-shift-left {} {})",
+                self.gen(GenType::Int, depth),
+                self.gen(GenType::UInt, depth)
+            ),
+            _ => format!(
+                "(buff-to-int-le (unwrap-panic (as-max-len? {} u16)))",
+                self.gen(GenType::Buff, depth)
+            ),
+        }
+    }
+
+    fn gen_uint(&mut self, depth: u32) -> String {
+        match self.rng.gen_range(0, 6) {
+            0 => self.binary(&["+", "-", "*", "/", "mod", "pow"], GenType::UInt, depth),
+            1 => format!("(to-uint {})", self.gen(GenType::Int, depth)),
+            2 => format!("(len {})", self.gen(GenType::IntList, depth)),
+            3 => format!("(len {})", self.gen(GenType::Buff, depth)),
+            4 => self.unary(&["sqrti", "log2"], GenType::UInt, depth),
+            _ => self.gen_if(GenType::UInt, depth),
+        }
+    }
+
+    fn gen_bool(&mut self, depth: u32) -> String {
+        match self.rng.gen_range(0, 7) {
+            0 => self.binary(&["<", ">", "<=", ">="], GenType::Int, depth),
+            1 => self.binary(&["<", ">", "<=", ">="], GenType::UInt, depth),
+            2 => {
+                let ty = ALL_TYPES[self.rng.gen_range(0, ALL_TYPES.len())];
+                self.binary(&["is-eq"], ty, depth)
+            }
+            3 => self.variadic(&["and", "or"], GenType::Bool, depth),
+            4 => self.unary(&["not"], GenType::Bool, depth),
+            5 => self.unary(&["is-some", "is-none"], GenType::OptionalInt, depth),
+            _ => self.gen_if(GenType::Bool, depth),
+        }
+    }
+
+    fn gen_buff(&mut self, depth: u32) -> String {
+        match self.rng.gen_range(0, 4) {
+            0 => self.unary(
+                &["sha256", "hash160", "keccak256", "sha512", "sha512/256"],
+                GenType::Buff,
+                depth,
+            ),
+            1 => self.binary(&["concat"], GenType::Buff, depth),
+            2 => format!(
+                "(unwrap-panic (as-max-len? {} u32))",
+                self.gen(GenType::Buff, depth)
+            ),
+            _ => self.gen_if(GenType::Buff, depth),
+        }
+    }
+
+    fn gen_int_list(&mut self, depth: u32) -> String {
+        match self.rng.gen_range(0, 5) {
+            0 => {
+                let len = self.rng.gen_range(1, 5);
+                let items: Vec<_> = (0..len).map(|_| self.gen(GenType::Int, depth)).collect();
+                format!("(list {})", items.join(" "))
+            }
+            1 => self.binary(&["concat"], GenType::IntList, depth),
+            2 => format!(
+                "(append {} {})",
+                self.gen(GenType::IntList, depth),
+                self.gen(GenType::Int, depth)
+            ),
+            3 => format!(
+                "(unwrap-panic (as-max-len? {} u10))",
+                self.gen(GenType::IntList, depth)
+            ),
+            _ => self.gen_if(GenType::IntList, depth),
+        }
+    }
+
+    fn gen_optional_int(&mut self, depth: u32) -> String {
+        let element_at = if self.clarity2 && self.rng.gen() {
+            "element-at?"
+        } else {
+            "element-at"
+        };
+        match self.rng.gen_range(0, 3) {
+            0 => format!("(some {})", self.gen(GenType::Int, depth)),
+            1 => format!(
+                "({} {} {})",
+                element_at,
+                self.gen(GenType::IntList, depth),
+                self.gen(GenType::UInt, depth)
+            ),
+            _ => self.gen_if(GenType::OptionalInt, depth),
+        }
+    }
+
+    fn choose<'a>(&mut self, names: &[&'a str]) -> &'a str {
+        names[self.rng.gen_range(0, names.len())]
+    }
+
+    fn unary(&mut self, names: &[&str], arg_type: GenType, depth: u32) -> String {
+        let name = self.choose(names);
+        format!("({} {})", name, self.gen(arg_type, depth))
+    }
+
+    fn binary(&mut self, names: &[&str], arg_type: GenType, depth: u32) -> String {
+        let name = self.choose(names);
+        format!(
+            "({} {} {})",
+            name,
+            self.gen(arg_type, depth),
+            self.gen(arg_type, depth)
+        )
+    }
+
+    fn variadic(&mut self, names: &[&str], arg_type: GenType, depth: u32) -> String {
+        let name = self.choose(names);
+        let count = self.rng.gen_range(1, 5);
+        let args: Vec<_> = (0..count).map(|_| self.gen(arg_type, depth)).collect();
+        format!("({} {})", name, args.join(" "))
+    }
+
+    fn gen_if(&mut self, ty: GenType, depth: u32) -> String {
+        format!(
+            "(if {} {} {})",
+            self.gen(GenType::Bool, depth),
+            self.gen(ty, depth),
+            self.gen(ty, depth)
+        )
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// The expressions to test, and the seed they were generated from
+fn generate(clarity2: bool) -> (u64, Vec<String>) {
+    let seed = env_or("CLARITY_PROPERTY_TEST_SEED", thread_rng().gen());
+    let cases = env_or("CLARITY_PROPERTY_TEST_CASES", DEFAULT_CASES);
+    eprintln!(
+        "Generating {} Clarity expressions with seed {}",
+        cases, seed
+    );
+    let mut generator = ExprGenerator::new(seed, clarity2);
+    (seed, (0..cases).map(|_| generator.expression()).collect())
+}
+
+/// Evaluate `program` as a transaction, returning the result and what it cost. Panics are caught
+/// and returned as `Err`.
+fn eval_with_cost(
+    owned_env: &mut OwnedEnvironment,
+    version: ClarityVersion,
+    program: &str,
+) -> Result<(Result<(), Error>, ExecutionCost), String> {
+    let cost_before = owned_env.get_cost_total();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        owned_env
+            .execute_in_env(
+                QualifiedContractIdentifier::transient().issuer.into(),
+                None,
+                Some(ContractContext::new(
+                    QualifiedContractIdentifier::transient(),
+                    version,
+                )),
+                |exec_env| exec_env.eval_raw_with_rules(program, ASTRules::PrecheckSize),
+            )
+            .map(|_| ())
+    }))
+    .map_err(|panic| {
+        panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "unknown panic".into())
+    })?;
+
+    let cost_after = owned_env.get_cost_total();
+    assert!(
+        !cost_before.exceeds(&cost_after),
+        "Cost total decreased from {:?} to {:?} evaluating {}",
+        cost_before,
+        cost_after,
+        program
+    );
+    let mut cost = cost_after;
+    cost.sub(&cost_before)
+        .expect("Cost total should not decrease");
+    Ok((result, cost))
+}
+
+/// Analysis accepting an expression implies that evaluating it does not panic, and does not fail
+/// with an error that analysis should have caught. Evaluating an expression twice never costs
+/// less than evaluating it once.
+fn check_accepted_expressions_evaluate(version: ClarityVersion, epoch: StacksEpochId) {
+    let (seed, programs) = generate(version >= ClarityVersion::Clarity2);
+    let accepted: Vec<_> = programs
+        .into_iter()
+        .filter(|program| mem_type_check(program, version, epoch).is_ok())
+        .collect();
+    eprintln!(
+        "Evaluating the {} expressions accepted by analysis in {} with {}",
+        accepted.len(),
+        epoch,
+        version
+    );
+
+    with_owned_env(epoch, false, |mut owned_env| {
+        for program in accepted.iter() {
+            let (result, cost) =
+                eval_with_cost(&mut owned_env, version, program).unwrap_or_else(|panic| {
+                    panic!("Evaluating {} panicked (seed {}): {}", program, seed, panic)
+                });
+            match result {
+                Err(Error::Unchecked(e)) => panic!(
+                    "Evaluating {} failed with an error analysis should catch (seed {}): {:?}",
+                    program, seed, e
+                ),
+                Err(Error::Interpreter(e)) => panic!(
+                    "Evaluating {} failed with an interpreter error (seed {}): {:?}",
+                    program, seed, e
+                ),
+                Ok(_) | Err(Error::Runtime(..)) | Err(Error::ShortReturn(_)) => {}
+            }
+
+            let repeated = format!("(begin {} {})", program, program);
+            let (repeated_result, repeated_cost) =
+                eval_with_cost(&mut owned_env, version, &repeated).unwrap_or_else(|panic| {
+                    panic!(
+                        "Evaluating {} panicked (seed {}): {}",
+                        repeated, seed, panic
+                    )
+                });
+            if repeated_result.is_ok() {
+                assert!(
+                    !cost.exceeds(&repeated_cost),
+                    "Evaluating {} cost {:?}, more than the {:?} of evaluating it twice (seed {})",
+                    program,
+                    cost,
+                    repeated_cost,
+                    seed
+                );
+            }
+        }
+    })
+}
+
+#[test]
+fn accepted_expressions_evaluate_2_05() {
+    check_accepted_expressions_evaluate(ClarityVersion::Clarity1, StacksEpochId::Epoch2_05);
+}
+
+#[test]
+fn accepted_expressions_evaluate_clarity1_2_1() {
+    check_accepted_expressions_evaluate(ClarityVersion::Clarity1, StacksEpochId::Epoch21);
+}
+
+#[test]
+fn accepted_expressions_evaluate_clarity2_2_1() {
+    check_accepted_expressions_evaluate(ClarityVersion::Clarity2, StacksEpochId::Epoch21);
+}
+
+/// The 2.05 and 2.1 type checkers accept the same Clarity 1 expressions, and give them the same
+/// type.
+#[test]
+fn type_checkers_agree_2_05_2_1() {
+    let (seed, programs) = generate(false);
+    for program in programs.iter() {
+        let type_2_05 = mem_type_check(program, ClarityVersion::Clarity1, StacksEpochId::Epoch2_05)
+            .map(|(program_type, _)| program_type);
+        let type_2_1 = mem_type_check(program, ClarityVersion::Clarity1, StacksEpochId::Epoch21)
+            .map(|(program_type, _)| program_type);
+        match (&type_2_05, &type_2_1) {
+            (Ok(type_2_05), Ok(type_2_1)) => assert_eq!(
+                type_2_05, type_2_1,
+                "Type checkers disagree on the type of {} (seed {})",
+                program, seed
+            ),
+            (Err(_), Err(_)) => {}
+            _ => panic!(
+                "Type checkers disagree on {} (seed {}): 2.05 gave {:?}, 2.1 gave {:?}",
+                program, seed, type_2_05, type_2_1
+            ),
+        }
+    }
+}
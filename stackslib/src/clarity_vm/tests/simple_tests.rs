@@ -1,7 +1,9 @@
+use clarity::vm::ast::ASTRules;
 use clarity::vm::contexts::OwnedEnvironment;
-use clarity::vm::errors::{Error, RuntimeErrorType};
+use clarity::vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use clarity::vm::test_util::{TEST_BURN_STATE_DB, TEST_HEADER_DB};
-use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use clarity::vm::Value;
 use stacks_common::consts::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 use stacks_common::types::chainstate::{BlockHeaderHash, StacksBlockId};
 use stacks_common::types::StacksEpochId;
@@ -74,3 +76,76 @@ fn test_at_unknown_block() {
 
     with_marfed_environment(test, true);
 }
+
+#[test]
+fn test_speculative_execution() {
+    let counter_id = QualifiedContractIdentifier::local("counter").unwrap();
+    let speculative_id = QualifiedContractIdentifier::local("speculative").unwrap();
+    let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+    let first_block = StacksBlockId::new(&FIRST_BURNCHAIN_CONSENSUS_HASH, &FIRST_STACKS_BLOCK_HASH);
+
+    let mut marf_kv = MarfedKV::temporary();
+    {
+        let mut store = marf_kv.begin(&StacksBlockId::sentinel(), &first_block);
+        let mut owned_env = OwnedEnvironment::new(
+            store.as_clarity_db(&TEST_HEADER_DB, &TEST_BURN_STATE_DB),
+            StacksEpochId::latest(),
+        );
+        owned_env
+            .initialize_contract(
+                counter_id.clone(),
+                "(define-data-var count int 0)
+                 (define-public (bump) (begin (var-set count (+ 1 (var-get count))) (ok (var-get count))))
+                 (define-read-only (get-count) (var-get count))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        drop(owned_env);
+        store.test_commit();
+    }
+
+    let mut store = marf_kv.begin(&first_block, &StacksBlockId([1 as u8; 32]));
+    let mut db = store.as_clarity_db(&TEST_HEADER_DB, &TEST_BURN_STATE_DB);
+
+    // an overlay can't be opened over pending edits, which it wouldn't see
+    db.begin();
+    assert!(db.begin_speculative().is_err());
+    db.roll_back().unwrap();
+
+    {
+        let mut speculative = db.begin_speculative().unwrap();
+        let mut owned_env =
+            OwnedEnvironment::new(speculative.as_clarity_db(), StacksEpochId::latest());
+        let (result, ..) = owned_env
+            .execute_transaction(sender.clone(), None, counter_id.clone(), "bump", &[])
+            .unwrap();
+        assert_eq!(result, Value::okay(Value::Int(1)).unwrap());
+        owned_env
+            .initialize_contract(
+                speculative_id.clone(),
+                "(define-read-only (get-count) (contract-call? .counter get-count))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        let (result, ..) = owned_env
+            .eval_read_only(&speculative_id, "(get-count)")
+            .unwrap();
+        assert_eq!(result, Value::Int(1));
+    }
+
+    // nothing written speculatively reached the real store
+    let mut owned_env = OwnedEnvironment::new(db, StacksEpochId::latest());
+    let (result, ..) = owned_env
+        .eval_read_only(&counter_id, "(get-count)")
+        .unwrap();
+    assert_eq!(result, Value::Int(0));
+    match owned_env
+        .eval_read_only(&speculative_id, "(get-count)")
+        .unwrap_err()
+    {
+        Error::Unchecked(CheckErrors::NoSuchContract(_)) => {}
+        e => panic!("Unexpected error: {}", e),
+    }
+}
@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::{self, Ordering};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::hash::Hasher;
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
@@ -58,7 +58,10 @@ use crate::core::{
 };
 use crate::cost_estimates::metrics::{CostMetric, UnitMetric};
 use crate::cost_estimates::{CostEstimator, EstimatorError, UnitEstimator};
-use crate::monitoring::increment_stx_mempool_gc;
+use crate::monitoring::{
+    increment_mempool_evictions_counter, increment_mempool_txs_admitted_counter,
+    increment_mempool_txs_rejected_counter, increment_stx_mempool_gc,
+};
 use crate::net::Error as net_error;
 use crate::util_lib::bloom::{BloomCounter, BloomFilter, BloomNodeHasher};
 use crate::util_lib::db::{
@@ -335,6 +338,7 @@ pub enum MemPoolDropReason {
     STALE_COLLECT,
     TOO_EXPENSIVE,
     PROBLEMATIC,
+    SIZE_LIMIT,
 }
 
 pub struct ConsiderTransaction {
@@ -360,6 +364,7 @@ impl std::fmt::Display for MemPoolDropReason {
             MemPoolDropReason::REPLACE_ACROSS_FORK => write!(f, "ReplaceAcrossFork"),
             MemPoolDropReason::REPLACE_BY_FEE => write!(f, "ReplaceByFee"),
             MemPoolDropReason::PROBLEMATIC => write!(f, "Problematic"),
+            MemPoolDropReason::SIZE_LIMIT => write!(f, "SizeLimitEvicted"),
         }
     }
 }
@@ -475,6 +480,28 @@ impl MemPoolWalkTxTypes {
     }
 }
 
+/// How the miner walks the mempool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemPoolWalkStrategy {
+    /// Consider transactions in descending fee-rate order across the whole mempool, caching
+    /// transactions whose nonces are too high to retry after each mined transaction
+    GlobalFeeRate,
+    /// Consider only the next-nonce transaction of each origin, in descending order of the
+    /// effective fee-rate of the origin's nonce chain
+    NextNonceWithHighestFeeRate,
+}
+
+impl FromStr for MemPoolWalkStrategy {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GlobalFeeRate" => Ok(Self::GlobalFeeRate),
+            "NextNonceWithHighestFeeRate" => Ok(Self::NextNonceWithHighestFeeRate),
+            _ => Err("Unknown mempool walk strategy"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MemPoolWalkSettings {
     /// Maximum amount of time a miner will spend walking through mempool transactions, in
@@ -487,12 +514,15 @@ pub struct MemPoolWalkSettings {
     /// Size of the nonce cache. This avoids MARF look-ups.
     pub nonce_cache_size: u64,
     /// Size of the candidate cache. These are the candidates that will be retried after each
-    /// transaction is mined.
+    /// transaction is mined. When walking by nonce chain, this is the most candidates that are
+    /// loaded from the mempool.
     pub candidate_retry_cache_size: u64,
     /// Types of transactions we'll consider
     pub txs_to_consider: HashSet<MemPoolWalkTxTypes>,
     /// Origins for transactions that we'll consider
     pub filter_origins: HashSet<StacksAddress>,
+    /// How to order the transactions we consider
+    pub strategy: MemPoolWalkStrategy,
 }
 
 impl MemPoolWalkSettings {
@@ -510,6 +540,7 @@ impl MemPoolWalkSettings {
             .into_iter()
             .collect(),
             filter_origins: HashSet::new(),
+            strategy: MemPoolWalkStrategy::GlobalFeeRate,
         }
     }
     pub fn zero() -> MemPoolWalkSettings {
@@ -526,6 +557,31 @@ impl MemPoolWalkSettings {
             .into_iter()
             .collect(),
             filter_origins: HashSet::new(),
+            strategy: MemPoolWalkStrategy::GlobalFeeRate,
+        }
+    }
+}
+
+/// Limits on the transactions that the mempool retains
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolGCPolicy {
+    /// Transactions accepted more than this many Stacks blocks below the Stacks chain tip are
+    /// dropped
+    pub max_tx_age: u64,
+    /// The most bytes of transactions the mempool holds, if limited.  Once it holds more, the
+    /// transactions with the lowest fee per byte are evicted.
+    pub max_size_bytes: Option<u64>,
+    /// Once the mempool holds this percentage of `max_size_bytes`, a new transaction must pay a
+    /// higher fee per byte than the cheapest transaction in the mempool to be admitted.
+    pub fee_floor_threshold_pct: u8,
+}
+
+impl Default for MemPoolGCPolicy {
+    fn default() -> MemPoolGCPolicy {
+        MemPoolGCPolicy {
+            max_tx_age: MEMPOOL_MAX_TRANSACTION_AGE,
+            max_size_bytes: None,
+            fee_floor_threshold_pct: 90,
         }
     }
 }
@@ -755,6 +811,37 @@ const MEMPOOL_SCHEMA_6_NONCES: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_7_TOTAL_SIZE: &'static [&'static str] = &[
+    r#"
+    -- Total length of the transactions in the mempool
+    CREATE TABLE IF NOT EXISTS mempool_size(
+        size INTEGER NOT NULL
+    );
+    "#,
+    r#"
+    -- Maintain the total length of the mempool's transactions. `INSERT OR REPLACE` does not
+    -- fire the delete trigger, so transactions must be deleted before they are replaced.
+    CREATE TRIGGER IF NOT EXISTS mempool_size_inc
+    AFTER INSERT ON mempool
+    BEGIN
+        UPDATE mempool_size SET size = size + NEW.length;
+    END
+    "#,
+    r#"
+    CREATE TRIGGER IF NOT EXISTS mempool_size_dec
+    AFTER DELETE ON mempool
+    BEGIN
+        UPDATE mempool_size SET size = size - OLD.length;
+    END
+    "#,
+    r#"
+    INSERT INTO mempool_size (size) SELECT IFNULL(SUM(length), 0) FROM mempool
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (7)
+    "#,
+];
+
 const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS by_txid ON mempool(txid);",
     "CREATE INDEX IF NOT EXISTS by_height ON mempool(height);",
@@ -779,12 +866,14 @@ pub struct MemPoolDB {
     metric: Box<dyn CostMetric>,
     pub blacklist_timeout: u64,
     pub blacklist_max_size: u64,
+    pub gc_policy: MemPoolGCPolicy,
 }
 
 pub struct MemPoolTx<'a> {
     tx: DBTx<'a>,
     admitter: &'a mut MemPoolAdmitter,
     bloom_counter: Option<&'a mut BloomCounter<BloomNodeHasher>>,
+    gc_policy: &'a MemPoolGCPolicy,
 }
 
 impl<'a> Deref for MemPoolTx<'a> {
@@ -805,11 +894,13 @@ impl<'a> MemPoolTx<'a> {
         tx: DBTx<'a>,
         admitter: &'a mut MemPoolAdmitter,
         bloom_counter: &'a mut BloomCounter<BloomNodeHasher>,
+        gc_policy: &'a MemPoolGCPolicy,
     ) -> MemPoolTx<'a> {
         MemPoolTx {
             tx,
             admitter,
             bloom_counter: Some(bloom_counter),
+            gc_policy,
         }
     }
 
@@ -1187,6 +1278,203 @@ impl CandidateCache {
     }
 }
 
+/// The next-nonce transaction of an origin's nonce chain, queued for consideration
+#[derive(Debug, Clone)]
+struct ChainHead {
+    /// The effective fee-rate of the chain this transaction heads
+    fee_rate: f64,
+    /// Queue order, to break fee-rate ties in favor of the earlier head
+    seq: u64,
+    origin_address: StacksAddress,
+    origin_nonce: u64,
+}
+
+impl PartialEq for ChainHead {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ChainHead {}
+
+impl PartialOrd for ChainHead {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ChainHead {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.fee_rate
+            .total_cmp(&other.fee_rate)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// Indexes the mempool's transactions by origin address and nonce, so that the mempool can be
+/// walked one nonce chain at a time.
+/// Only the transaction at the head of each origin's chain can be mined next, so only heads are
+/// queued for consideration. A head is ranked by the effective fee-rate of its chain: the best
+/// average fee-rate of any run of consecutive nonces starting at the head, so that a low-fee
+/// transaction that unblocks high-fee descendants is not starved. Transactions behind a nonce
+/// gap are never queued.
+struct NonceChainIndex {
+    /// Each origin's transactions, by origin nonce
+    chains: HashMap<StacksAddress, BTreeMap<u64, MemPoolTxInfoPartial>>,
+    /// Heads with a fee-rate estimate, highest effective fee-rate first
+    ready: BinaryHeap<ChainHead>,
+    /// Heads without a fee-rate estimate, in the order they were queued
+    ready_no_estimate: VecDeque<ChainHead>,
+    /// Origins whose head is waiting for its sponsor's nonce to advance, by sponsor address
+    awaiting_sponsor: HashMap<StacksAddress, Vec<StacksAddress>>,
+    /// Number of heads queued so far
+    seq: u64,
+}
+
+impl NonceChainIndex {
+    fn new(candidates: Vec<MemPoolTxInfoPartial>) -> Self {
+        let mut chains: HashMap<StacksAddress, BTreeMap<u64, MemPoolTxInfoPartial>> =
+            HashMap::new();
+        for candidate in candidates.into_iter() {
+            let chain = chains.entry(candidate.origin_address).or_default();
+            // Keep the better-paying transaction if two share a nonce
+            let replace = match chain.get(&candidate.origin_nonce) {
+                Some(existing) => {
+                    candidate.fee_rate.unwrap_or_default() > existing.fee_rate.unwrap_or_default()
+                }
+                None => true,
+            };
+            if replace {
+                chain.insert(candidate.origin_nonce, candidate);
+            }
+        }
+        let origins: Vec<_> = chains.keys().cloned().collect();
+        let mut index = NonceChainIndex {
+            chains,
+            ready: BinaryHeap::new(),
+            ready_no_estimate: VecDeque::new(),
+            awaiting_sponsor: HashMap::new(),
+            seq: 0,
+        };
+        // The origins' nonces are not known yet, so each chain is queued from its lowest nonce
+        // and checked against the origin's account nonce when it is dequeued.
+        for origin in origins.into_iter() {
+            index.queue_head(&origin);
+        }
+        index
+    }
+
+    /// The effective fee-rate of the given chain, or None if its head has no estimate
+    fn chain_fee_rate(chain: &BTreeMap<u64, MemPoolTxInfoPartial>) -> Option<f64> {
+        let (head_nonce, head) = chain.iter().next()?;
+        head.fee_rate?;
+        let mut total = 0.0;
+        let mut best = f64::MIN;
+        for (i, (nonce, tx)) in chain
+            .iter()
+            .take(MAXIMUM_MEMPOOL_TX_CHAINING as usize)
+            .enumerate()
+        {
+            if *nonce != head_nonce + i as u64 {
+                break;
+            }
+            total += tx.fee_rate.unwrap_or_default();
+            best = best.max(total / (i + 1) as f64);
+        }
+        Some(best)
+    }
+
+    /// Queue the head of the origin's chain for consideration
+    fn queue_head(&mut self, origin: &StacksAddress) {
+        let Some(chain) = self.chains.get(origin) else {
+            return;
+        };
+        let Some(origin_nonce) = chain.keys().next().cloned() else {
+            self.chains.remove(origin);
+            return;
+        };
+        let fee_rate = Self::chain_fee_rate(chain);
+        let head = ChainHead {
+            fee_rate: fee_rate.unwrap_or_default(),
+            seq: self.seq,
+            origin_address: origin.clone(),
+            origin_nonce,
+        };
+        self.seq += 1;
+        if fee_rate.is_some() {
+            self.ready.push(head);
+        } else {
+            self.ready_no_estimate.push_back(head);
+        }
+    }
+
+    /// Whether the queued head still heads its chain
+    fn is_current(&self, head: &ChainHead) -> bool {
+        self.chains
+            .get(&head.origin_address)
+            .and_then(|chain| chain.keys().next())
+            == Some(&head.origin_nonce)
+    }
+
+    /// Dequeue the next head to consider, preferring heads without a fee-rate estimate if
+    /// `no_estimate` is set
+    fn next(&mut self, no_estimate: bool) -> Option<MemPoolTxInfoPartial> {
+        loop {
+            let head = if no_estimate {
+                self.ready_no_estimate
+                    .pop_front()
+                    .or_else(|| self.ready.pop())
+            } else {
+                self.ready
+                    .pop()
+                    .or_else(|| self.ready_no_estimate.pop_front())
+            }?;
+            if !self.is_current(&head) {
+                continue;
+            }
+            return self
+                .chains
+                .get(&head.origin_address)
+                .and_then(|chain| chain.get(&head.origin_nonce))
+                .cloned();
+        }
+    }
+
+    /// Drop the origin's transactions with nonces below `origin_nonce`, which can no longer be
+    /// mined, and queue the chain again from its new head
+    fn advance(&mut self, origin: &StacksAddress, origin_nonce: u64) {
+        if let Some(chain) = self.chains.get_mut(origin) {
+            *chain = chain.split_off(&origin_nonce);
+        }
+        self.queue_head(origin);
+    }
+
+    /// Stop considering the origin's transactions in this walk
+    fn drop_chain(&mut self, origin: &StacksAddress) {
+        self.chains.remove(origin);
+    }
+
+    /// Hold the origin's chain until the sponsor's nonce advances
+    fn await_sponsor(&mut self, origin: &StacksAddress, sponsor: &StacksAddress) {
+        self.awaiting_sponsor
+            .entry(sponsor.clone())
+            .or_default()
+            .push(origin.clone());
+    }
+
+    /// Queue the heads of the chains that were waiting for the given account's nonce to advance
+    fn sponsor_advanced(&mut self, sponsor: &StacksAddress) {
+        for origin in self
+            .awaiting_sponsor
+            .remove(sponsor)
+            .unwrap_or_default()
+            .into_iter()
+        {
+            self.queue_head(&origin);
+        }
+    }
+}
+
 /// Evaluates the pair of nonces, to determine an order
 ///
 /// Returns:
@@ -1216,6 +1504,35 @@ fn order_nonces(
     Ordering::Equal
 }
 
+/// Whether the walk settings allow transactions with the given payload to be considered.
+/// Returns the payload's type name along with the decision.
+fn consider_tx_type(
+    settings: &MemPoolWalkSettings,
+    payload: &TransactionPayload,
+) -> (String, bool) {
+    match payload {
+        TransactionPayload::TokenTransfer(..) => (
+            "TokenTransfer".to_string(),
+            settings
+                .txs_to_consider
+                .contains(&MemPoolWalkTxTypes::TokenTransfer),
+        ),
+        TransactionPayload::SmartContract(..) => (
+            "SmartContract".to_string(),
+            settings
+                .txs_to_consider
+                .contains(&MemPoolWalkTxTypes::SmartContract),
+        ),
+        TransactionPayload::ContractCall(..) => (
+            "ContractCall".to_string(),
+            settings
+                .txs_to_consider
+                .contains(&MemPoolWalkTxTypes::ContractCall),
+        ),
+        _ => ("".to_string(), true),
+    }
+}
+
 impl MemPoolDB {
     fn instantiate_mempool_db(conn: &mut DBConn) -> Result<(), db_error> {
         let mut tx = tx_begin_immediate(conn)?;
@@ -1275,6 +1592,9 @@ impl MemPoolDB {
                     MemPoolDB::instantiate_nonces(tx)?;
                 }
                 6 => {
+                    MemPoolDB::instantiate_total_size(tx)?;
+                }
+                7 => {
                     break;
                 }
                 _ => {
@@ -1346,6 +1666,15 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Keep a running total of the mempool's size
+    fn instantiate_total_size(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_7_TOTAL_SIZE {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
     pub fn db_path(chainstate_root_path: &str) -> Result<String, db_error> {
         let mut path = PathBuf::from(chainstate_root_path);
 
@@ -1407,6 +1736,7 @@ impl MemPoolDB {
             metric,
             blacklist_timeout: DEFAULT_BLACKLIST_TIMEOUT,
             blacklist_max_size: DEFAULT_BLACKLIST_MAX_SIZE,
+            gc_policy: MemPoolGCPolicy::default(),
         })
     }
 
@@ -1550,6 +1880,47 @@ impl MemPoolDB {
         }
     }
 
+    /// Bump the cached origin and sponsor nonces past those of a transaction that was mined
+    fn bump_nonces(
+        nonce_cache: &mut NonceCache,
+        retry_store: &mut HashMap<StacksAddress, u64>,
+        settings: &MemPoolWalkSettings,
+        conn: &DBConn,
+        consider: &ConsiderTransaction,
+        expected_origin_nonce: u64,
+        expected_sponsor_nonce: u64,
+    ) {
+        let stored = nonce_cache.update(
+            consider.tx.metadata.origin_address,
+            expected_origin_nonce + 1,
+            conn,
+        );
+        if !stored {
+            Self::save_nonce_for_retry(
+                retry_store,
+                settings.nonce_cache_size,
+                consider.tx.metadata.origin_address,
+                expected_origin_nonce + 1,
+            );
+        }
+
+        if consider.tx.tx.auth.is_sponsored() {
+            let stored = nonce_cache.update(
+                consider.tx.metadata.sponsor_address,
+                expected_sponsor_nonce + 1,
+                conn,
+            );
+            if !stored {
+                Self::save_nonce_for_retry(
+                    retry_store,
+                    settings.nonce_cache_size,
+                    consider.tx.metadata.sponsor_address,
+                    expected_sponsor_nonce + 1,
+                );
+            }
+        }
+    }
+
     /// Iterate over candidates in the mempool
     /// `todo` will be called once for each transaction that is a valid
     /// candidate for inclusion in the next block, meaning its origin and
@@ -1568,6 +1939,10 @@ impl MemPoolDB {
     /// `settings` struct, the caller may choose how long to spend iterating
     /// before this method stops.
     ///
+    /// If `settings.strategy` is `NextNonceWithHighestFeeRate`, the mempool is
+    /// walked one nonce chain at a time instead. See
+    /// `iterate_candidates_by_nonce_chain`.
+    ///
     /// `todo` returns an option to a `TransactionEvent` representing the
     /// outcome, or None to indicate that iteration through the mempool should
     /// be halted.
@@ -1592,6 +1967,15 @@ impl MemPoolDB {
         ) -> Result<Option<TransactionEvent>, E>,
         E: From<db_error> + From<ChainstateError>,
     {
+        if settings.strategy == MemPoolWalkStrategy::NextNonceWithHighestFeeRate {
+            return self.iterate_candidates_by_nonce_chain(
+                clarity_tx,
+                output_events,
+                settings,
+                todo,
+            );
+        }
+
         let start_time = Instant::now();
         let mut total_considered = 0;
 
@@ -1753,27 +2137,7 @@ impl MemPoolDB {
                 }
             };
 
-            let (tx_type, do_consider) = match &tx_info.tx.payload {
-                TransactionPayload::TokenTransfer(..) => (
-                    "TokenTransfer".to_string(),
-                    settings
-                        .txs_to_consider
-                        .contains(&MemPoolWalkTxTypes::TokenTransfer),
-                ),
-                TransactionPayload::SmartContract(..) => (
-                    "SmartContract".to_string(),
-                    settings
-                        .txs_to_consider
-                        .contains(&MemPoolWalkTxTypes::SmartContract),
-                ),
-                TransactionPayload::ContractCall(..) => (
-                    "ContractCall".to_string(),
-                    settings
-                        .txs_to_consider
-                        .contains(&MemPoolWalkTxTypes::ContractCall),
-                ),
-                _ => ("".to_string(), true),
-            };
+            let (tx_type, do_consider) = consider_tx_type(&settings, &tx_info.tx.payload);
             if !do_consider {
                 debug!("Will skip mempool tx, since it does not have an acceptable type";
                        "txid" => %tx_info.tx.txid(),
@@ -1818,35 +2182,15 @@ impl MemPoolDB {
                     match tx_event {
                         TransactionEvent::Success(_) => {
                             // Bump nonces in the cache for the executed transaction
-                            let stored = nonce_cache.update(
-                                consider.tx.metadata.origin_address,
-                                expected_origin_nonce + 1,
+                            Self::bump_nonces(
+                                &mut nonce_cache,
+                                &mut retry_store,
+                                &settings,
                                 self.conn(),
+                                &consider,
+                                expected_origin_nonce,
+                                expected_sponsor_nonce,
                             );
-                            if !stored {
-                                Self::save_nonce_for_retry(
-                                    &mut retry_store,
-                                    settings.nonce_cache_size,
-                                    consider.tx.metadata.origin_address,
-                                    expected_origin_nonce + 1,
-                                );
-                            }
-
-                            if consider.tx.tx.auth.is_sponsored() {
-                                let stored = nonce_cache.update(
-                                    consider.tx.metadata.sponsor_address,
-                                    expected_sponsor_nonce + 1,
-                                    self.conn(),
-                                );
-                                if !stored {
-                                    Self::save_nonce_for_retry(
-                                        &mut retry_store,
-                                        settings.nonce_cache_size,
-                                        consider.tx.metadata.sponsor_address,
-                                        expected_sponsor_nonce + 1,
-                                    );
-                                }
-                            }
                             output_events.push(tx_event);
                         }
                         TransactionEvent::Skipped(_) => {
@@ -1895,6 +2239,250 @@ impl MemPoolDB {
         Ok(total_considered)
     }
 
+    /// Iterate over candidates in the mempool one nonce chain at a time, per
+    /// `MemPoolWalkStrategy::NextNonceWithHighestFeeRate`.
+    /// Each origin's next-nonce transaction is considered in descending order of the effective
+    /// fee-rate of the origin's nonce chain (see `NonceChainIndex`). Once a transaction is mined,
+    /// the next transaction in its origin's chain is queued. If it is not mined, the rest of the
+    /// chain cannot be mined either, so it is not considered again in this walk. Transactions
+    /// behind a nonce gap are never considered.
+    ///
+    /// `todo`, `output_events` and the returned count are as in `iterate_candidates`.
+    fn iterate_candidates_by_nonce_chain<F, E, C>(
+        &mut self,
+        clarity_tx: &mut C,
+        output_events: &mut Vec<TransactionEvent>,
+        settings: MemPoolWalkSettings,
+        mut todo: F,
+    ) -> Result<u64, E>
+    where
+        C: ClarityConnection,
+        F: FnMut(
+            &mut C,
+            &ConsiderTransaction,
+            &mut dyn CostEstimator,
+        ) -> Result<Option<TransactionEvent>, E>,
+        E: From<db_error> + From<ChainstateError>,
+    {
+        let start_time = Instant::now();
+        let mut total_considered = 0;
+
+        debug!(
+            "Mempool walk by nonce chain for {}ms",
+            settings.max_walk_time_ms
+        );
+
+        let tx_consideration_sampler = Uniform::new(0, 100);
+        let mut rng = rand::thread_rng();
+        let mut nonce_cache = NonceCache::new(settings.nonce_cache_size);
+        let mut retry_store = HashMap::new();
+
+        // Skip transactions whose origin nonce is below the one in the `nonces` table, which
+        // are already mined, and load at most `candidate_retry_cache_size` of the rest. Each
+        // origin's lowest nonces are loaded first, so that no chain is cut off ahead of the
+        // transactions behind it. Among transactions at the same place in their chains, those
+        // without a fee-rate estimate come first with probability `consider_no_estimate_tx_prob`,
+        // and the rest go best fee-rate first.
+        let no_estimate_first =
+            tx_consideration_sampler.sample(&mut rng) < settings.consider_no_estimate_tx_prob;
+        let limit = u64_to_sql(settings.candidate_retry_cache_size)?;
+        let filter_origins: Vec<String> = settings
+            .filter_origins
+            .iter()
+            .map(|origin| origin.to_string())
+            .collect();
+        let mut args: Vec<&dyn ToSql> = vec![&no_estimate_first, &limit];
+        let origin_filter = if !filter_origins.is_empty() {
+            let mut params = vec![];
+            for origin in filter_origins.iter() {
+                args.push(origin);
+                params.push(format!("?{}", args.len()));
+            }
+            format!("AND m.origin_address IN ({})", params.join(", "))
+        } else {
+            "".to_string()
+        };
+        let sql = format!(
+            "
+            SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate
+            FROM (
+                SELECT m.txid, m.origin_nonce, m.origin_address, m.sponsor_nonce,
+                    m.sponsor_address, m.fee_rate,
+                    DENSE_RANK() OVER (
+                        PARTITION BY m.origin_address ORDER BY m.origin_nonce ASC
+                    ) AS chain_position
+                FROM mempool AS m
+                LEFT JOIN nonces AS n ON m.origin_address = n.address
+                WHERE (n.nonce IS NULL OR m.origin_nonce >= n.nonce) {origin_filter}
+            )
+            ORDER BY chain_position ASC, (fee_rate IS NULL) = ?1 DESC, fee_rate DESC
+            LIMIT ?2
+            "
+        );
+        let candidates: Vec<MemPoolTxInfoPartial> = query_rows(self.conn(), &sql, args)?;
+        let mut index = NonceChainIndex::new(candidates);
+
+        loop {
+            if start_time.elapsed().as_millis() > settings.max_walk_time_ms as u128 {
+                debug!("Mempool iteration deadline exceeded";
+                       "deadline_ms" => settings.max_walk_time_ms);
+                break;
+            }
+
+            let start_with_no_estimate =
+                tx_consideration_sampler.sample(&mut rng) < settings.consider_no_estimate_tx_prob;
+            let Some(candidate) = index.next(start_with_no_estimate) else {
+                debug!("No more transactions to consider in mempool");
+                break;
+            };
+            let update_estimate = candidate.fee_rate.is_none();
+
+            // Check the nonces.
+            let (expected_origin_nonce, retry_store_origin_nonce) =
+                nonce_cache.get(&candidate.origin_address, clarity_tx, self.conn());
+            if retry_store_origin_nonce {
+                Self::save_nonce_for_retry(
+                    &mut retry_store,
+                    settings.nonce_cache_size,
+                    candidate.origin_address.clone(),
+                    expected_origin_nonce,
+                );
+            }
+            match candidate.origin_nonce.cmp(&expected_origin_nonce) {
+                Ordering::Less => {
+                    // Already mined. Pick the chain back up at the origin's next nonce.
+                    index.advance(&candidate.origin_address, expected_origin_nonce);
+                    continue;
+                }
+                Ordering::Greater => {
+                    debug!(
+                        "Mempool: nonce gap: drop chain {}:{} (expected {})",
+                        candidate.origin_address, candidate.origin_nonce, expected_origin_nonce
+                    );
+                    index.drop_chain(&candidate.origin_address);
+                    continue;
+                }
+                Ordering::Equal => {}
+            }
+
+            let (expected_sponsor_nonce, retry_store_sponsor_nonce) =
+                nonce_cache.get(&candidate.sponsor_address, clarity_tx, self.conn());
+            if retry_store_sponsor_nonce {
+                Self::save_nonce_for_retry(
+                    &mut retry_store,
+                    settings.nonce_cache_size,
+                    candidate.sponsor_address.clone(),
+                    expected_sponsor_nonce,
+                );
+            }
+            match candidate.sponsor_nonce.cmp(&expected_sponsor_nonce) {
+                Ordering::Less => {
+                    debug!(
+                        "Mempool: unexecutable: drop chain {}:{} (sponsor nonce {} < {})",
+                        candidate.origin_address,
+                        candidate.origin_nonce,
+                        candidate.sponsor_nonce,
+                        expected_sponsor_nonce
+                    );
+                    index.drop_chain(&candidate.origin_address);
+                    continue;
+                }
+                Ordering::Greater => {
+                    // The sponsor's other transactions may be mined in this walk
+                    index.await_sponsor(&candidate.origin_address, &candidate.sponsor_address);
+                    continue;
+                }
+                Ordering::Equal => {}
+            }
+
+            // Read in and deserialize the transaction.
+            let tx_info = match MemPoolDB::get_tx(&self.conn(), &candidate.txid)? {
+                Some(tx) => tx,
+                None => {
+                    // Note: Don't panic here because maybe the state has changed from garbage collection.
+                    warn!("Miner: could not find a tx for id {:?}", &candidate.txid);
+                    index.drop_chain(&candidate.origin_address);
+                    continue;
+                }
+            };
+
+            let (tx_type, do_consider) = consider_tx_type(&settings, &tx_info.tx.payload);
+            if !do_consider {
+                debug!("Will skip mempool tx chain, since it does not have an acceptable type";
+                       "txid" => %tx_info.tx.txid(),
+                       "type" => %tx_type);
+                index.drop_chain(&candidate.origin_address);
+                continue;
+            }
+
+            let consider = ConsiderTransaction {
+                tx: tx_info,
+                update_estimate,
+            };
+            debug!("Consider mempool transaction";
+                           "txid" => %consider.tx.tx.txid(),
+                           "origin_addr" => %consider.tx.metadata.origin_address,
+                           "origin_nonce" => candidate.origin_nonce,
+                           "sponsor_addr" => %consider.tx.metadata.sponsor_address,
+                           "sponsor_nonce" => candidate.sponsor_nonce,
+                           "accept_time" => consider.tx.metadata.accept_time,
+                           "tx_fee" => consider.tx.metadata.tx_fee,
+                           "fee_rate" => candidate.fee_rate,
+                           "size" => consider.tx.metadata.len);
+            total_considered += 1;
+
+            // Run `todo` on the transaction.
+            match todo(clarity_tx, &consider, self.cost_estimator.as_mut())? {
+                Some(tx_event) => match tx_event {
+                    TransactionEvent::Success(_) => {
+                        Self::bump_nonces(
+                            &mut nonce_cache,
+                            &mut retry_store,
+                            &settings,
+                            self.conn(),
+                            &consider,
+                            expected_origin_nonce,
+                            expected_sponsor_nonce,
+                        );
+                        index.advance(&candidate.origin_address, expected_origin_nonce + 1);
+                        index.sponsor_advanced(&candidate.origin_address);
+                        if candidate.sponsor_address != candidate.origin_address {
+                            index.sponsor_advanced(&candidate.sponsor_address);
+                        }
+                        output_events.push(tx_event);
+                    }
+                    TransactionEvent::Skipped(_) => {
+                        // don't push `Skipped` events to the observer
+                        index.drop_chain(&candidate.origin_address);
+                    }
+                    _ => {
+                        index.drop_chain(&candidate.origin_address);
+                        output_events.push(tx_event);
+                    }
+                },
+                None => {
+                    debug!("Mempool iteration early exit from iterator");
+                    break;
+                }
+            }
+        }
+
+        if retry_store.len() > 0 {
+            let tx = self.tx_begin()?;
+            for (address, nonce) in retry_store.into_iter() {
+                nonce_cache.update(address, nonce, &tx);
+            }
+            tx.commit()?;
+        }
+
+        debug!(
+            "Mempool iteration finished";
+            "considered_txs" => total_considered,
+            "elapsed_ms" => start_time.elapsed().as_millis()
+        );
+        Ok(total_considered)
+    }
+
     pub fn conn(&self) -> &DBConn {
         &self.db
     }
@@ -1905,6 +2493,7 @@ impl MemPoolDB {
             tx,
             &mut self.admitter,
             &mut self.bloom_counter,
+            &self.gc_policy,
         ))
     }
 
@@ -1926,7 +2515,6 @@ impl MemPoolDB {
     }
 
     /// Get all transactions across all tips
-    #[cfg(test)]
     pub fn get_all_txs(conn: &DBConn) -> Result<Vec<MemPoolTxInfo>, db_error> {
         let sql = "SELECT * FROM mempool";
         let rows = query_rows::<MemPoolTxInfo, _>(conn, &sql, NO_PARAMS)?;
@@ -2133,6 +2721,22 @@ impl MemPoolDB {
 
         tx.update_bloom_counter(height, &txid, prior_tx.as_ref().map(|tx| tx.txid.clone()))?;
 
+        // Delete the transactions this one replaces first, so that the mempool's total size is
+        // kept up to date (see `MEMPOOL_SCHEMA_7_TOTAL_SIZE`)
+        let sql = "DELETE FROM mempool
+            WHERE txid = ?1
+            OR (origin_address = ?2 AND origin_nonce = ?3)
+            OR (sponsor_address = ?4 AND sponsor_nonce = ?5)";
+        let args: &[&dyn ToSql] = &[
+            &txid,
+            &origin_address.to_string(),
+            &u64_to_sql(origin_nonce)?,
+            &sponsor_address.to_string(),
+            &u64_to_sql(sponsor_nonce)?,
+        ];
+        tx.execute(sql, args)
+            .map_err(|e| MemPoolRejection::DBError(db_error::SqliteError(e)))?;
+
         let sql = "INSERT OR REPLACE INTO mempool (
             txid,
             origin_address,
@@ -2193,11 +2797,95 @@ impl MemPoolDB {
 
         let sql = "DELETE FROM mempool WHERE height < ?1";
 
-        tx.execute(sql, args)?;
+        let num_evicted = tx.execute(sql, args)?;
         increment_stx_mempool_gc();
+        increment_mempool_evictions_counter("age", num_evicted as u64);
         Ok(())
     }
 
+    /// The total size, in bytes, of the transactions in the mempool
+    pub fn get_total_size(conn: &DBConn) -> Result<u64, db_error> {
+        let sql = "SELECT size FROM mempool_size";
+        let size = query_int(conn, sql, NO_PARAMS)?;
+        u64::try_from(size).map_err(|_| db_error::ParseError)
+    }
+
+    /// The number of transactions in the mempool
+    pub fn get_num_txs(conn: &DBConn) -> Result<u64, db_error> {
+        let sql = "SELECT COUNT(*) FROM mempool";
+        let count = query_int(conn, sql, NO_PARAMS)?;
+        u64::try_from(count).map_err(|_| db_error::ParseError)
+    }
+
+    /// The lowest fee that a new transaction of `len` bytes must pay to be admitted, if the
+    /// mempool is near its size limit.  The transaction must pay a higher fee per byte than the
+    /// cheapest transaction in the mempool, so that admitting it can only evict a cheaper one.
+    pub fn get_fee_floor(
+        conn: &DBConn,
+        policy: &MemPoolGCPolicy,
+        len: u64,
+    ) -> Result<Option<u64>, db_error> {
+        let Some(max_size) = policy.max_size_bytes else {
+            return Ok(None);
+        };
+        let size = MemPoolDB::get_total_size(conn)?;
+        if (size as u128) * 100 < (max_size as u128) * (policy.fee_floor_threshold_pct as u128) {
+            return Ok(None);
+        }
+        let sql = "SELECT tx_fee, length FROM mempool WHERE length > 0 ORDER BY (tx_fee * 1.0 / length) ASC LIMIT 1";
+        let Some((min_fee, min_len)) = query_row::<(u64, u64), _>(conn, sql, NO_PARAMS)? else {
+            return Ok(None);
+        };
+        let floor = (min_fee as u128) * (len as u128) / (min_len as u128) + 1;
+        Ok(Some(u64::try_from(floor).unwrap_or(u64::MAX)))
+    }
+
+    /// Evict the transactions with the lowest fee per byte until the mempool fits within the
+    /// size limit of its GC policy, if it has one.
+    /// Returns the number of transactions evicted.
+    pub fn enforce_size_limit(
+        tx: &mut MemPoolTx,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+    ) -> Result<u64, db_error> {
+        let Some(max_size) = tx.gc_policy.max_size_bytes else {
+            return Ok(0);
+        };
+        let mut size = MemPoolDB::get_total_size(tx)?;
+        if size <= max_size {
+            return Ok(0);
+        }
+
+        let mut evicted = vec![];
+        {
+            let sql =
+                "SELECT txid, length FROM mempool ORDER BY (tx_fee * 1.0 / MAX(length, 1)) ASC";
+            let mut stmt = tx.prepare(sql)?;
+            let mut rows = stmt.query(NO_PARAMS)?;
+            while size > max_size {
+                let Some(row) = rows.next()? else {
+                    break;
+                };
+                let txid = Txid::from_column(row, "txid")?;
+                let len = u64::from_column(row, "length")?;
+                size = size.saturating_sub(len);
+                evicted.push(txid);
+            }
+        }
+
+        debug!(
+            "Evict {} transaction(s) to fit the mempool within {} bytes",
+            evicted.len(),
+            max_size
+        );
+        MemPoolDB::inner_drop_txs(tx, &evicted)?;
+        let num_evicted = evicted.len() as u64;
+        if let Some(event_observer) = event_observer {
+            event_observer.mempool_txs_dropped(evicted, MemPoolDropReason::SIZE_LIMIT);
+        }
+        increment_mempool_evictions_counter("size", num_evicted);
+        Ok(num_evicted)
+    }
+
     #[cfg(test)]
     pub fn clear_before_height(&mut self, min_height: u64) -> Result<(), db_error> {
         let mut tx = self.tx_begin()?;
@@ -2296,6 +2984,13 @@ impl MemPoolDB {
             mempool_tx
                 .admitter
                 .will_admit_tx(chainstate, sortdb, tx, len)?;
+
+            if let Some(min_fee) = MemPoolDB::get_fee_floor(mempool_tx, mempool_tx.gc_policy, len)?
+            {
+                if tx_fee < min_fee {
+                    return Err(MemPoolRejection::FeeTooLow(tx_fee, min_fee));
+                }
+            }
         }
 
         MemPoolDB::try_add_tx(
@@ -2339,6 +3034,72 @@ impl MemPoolDB {
         event_observer: Option<&dyn MemPoolEventDispatcher>,
         block_limit: &ExecutionCost,
         stacks_epoch_id: &StacksEpochId,
+    ) -> Result<(), MemPoolRejection> {
+        let res = self.inner_submit(
+            chainstate,
+            sortdb,
+            consensus_hash,
+            block_hash,
+            tx,
+            event_observer,
+            block_limit,
+            stacks_epoch_id,
+        );
+        match res {
+            Ok(()) => increment_mempool_txs_admitted_counter(),
+            Err(ref e) => increment_mempool_txs_rejected_counter(e.reason_code()),
+        }
+        res
+    }
+
+    /// Run the admission checks that `submit` would run on this transaction, but do not store
+    /// it.  Conflicts with transactions already in the mempool are not checked.
+    pub fn check(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+        tx: &StacksTransaction,
+        block_limit: &ExecutionCost,
+        stacks_epoch_id: &StacksEpochId,
+    ) -> Result<(), MemPoolRejection> {
+        if self.is_tx_blacklisted(&tx.txid())? {
+            return Err(MemPoolRejection::TemporarilyBlacklisted);
+        }
+
+        match cost_estimates::estimate_fee_rate(
+            tx,
+            self.cost_estimator.as_ref(),
+            self.metric.as_ref(),
+            block_limit,
+            stacks_epoch_id,
+        ) {
+            Ok(_) | Err(EstimatorError::NoEstimateAvailable) => {}
+            Err(e) => {
+                return Err(MemPoolRejection::EstimatorError(e));
+            }
+        }
+
+        let mut tx_data = vec![];
+        tx.consensus_serialize(&mut tx_data)
+            .map_err(MemPoolRejection::SerializationFailure)?;
+
+        self.admitter.set_block(block_hash, consensus_hash.clone());
+        self.admitter
+            .will_admit_tx(chainstate, sortdb, tx, tx_data.len() as u64)
+    }
+
+    fn inner_submit(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+        tx: &StacksTransaction,
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+        block_limit: &ExecutionCost,
+        stacks_epoch_id: &StacksEpochId,
     ) -> Result<(), MemPoolRejection> {
         if self.is_tx_blacklisted(&tx.txid())? {
             // don't re-store this transaction
@@ -2603,6 +3364,118 @@ impl MemPoolDB {
         eprintln!("{:#?}", txs);
     }
 
+    /// Write every transaction in the mempool to a checkpoint file at `path`, so they can be
+    /// re-submitted with `restore_checkpoint()` after a restart.
+    /// The checkpoint is written to `{path}.tmp` first and then renamed into place, so an
+    /// interrupted write never clobbers the last good checkpoint.
+    /// Returns the number of transactions written.
+    pub fn write_checkpoint(&self, path: &str) -> Result<usize, db_error> {
+        let txs = MemPoolDB::get_all_txs(self.conn())?;
+        let tmp_path = format!("{}.tmp", path);
+        let mut fd = fs::File::create(&tmp_path).map_err(db_error::IOError)?;
+        for tx_info in txs.iter() {
+            tx_info
+                .tx
+                .consensus_serialize(&mut fd)
+                .map_err(|e| db_error::Other(format!("Failed to serialize tx: {:?}", &e)))?;
+        }
+        fd.sync_all().map_err(db_error::IOError)?;
+        fs::rename(&tmp_path, path).map_err(db_error::IOError)?;
+        Ok(txs.len())
+    }
+
+    /// Read back the transactions in a checkpoint file written by `write_checkpoint()`.
+    /// If the file ends with a partial or corrupt transaction, the transactions before it are
+    /// still returned.
+    /// Returns NotFoundError if there is no checkpoint at `path`.
+    pub fn read_checkpoint(path: &str) -> Result<Vec<StacksTransaction>, db_error> {
+        let bytes = fs::read(path).map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                db_error::NotFoundError
+            } else {
+                db_error::IOError(e)
+            }
+        })?;
+
+        let mut txs = vec![];
+        let mut cursor = &bytes[..];
+        while !cursor.is_empty() {
+            match StacksTransaction::consensus_deserialize(&mut cursor) {
+                Ok(tx) => txs.push(tx),
+                Err(e) => {
+                    warn!("Mempool checkpoint is corrupt; ignoring the rest of it";
+                          "path" => path,
+                          "num_read" => txs.len(),
+                          "error" => ?e);
+                    break;
+                }
+            }
+        }
+        Ok(txs)
+    }
+
+    /// Re-submit the transactions in the checkpoint file at `path`.
+    /// Each transaction goes through the usual admission checks against the canonical Stacks
+    /// chain tip, so transactions that were mined or invalidated while the node was down are
+    /// dropped.  Transactions already in the mempool are skipped.
+    /// Returns the number of transactions (restored, rejected).  A missing checkpoint restores
+    /// nothing.
+    pub fn restore_checkpoint(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        sortdb: &SortitionDB,
+        path: &str,
+    ) -> Result<(usize, usize), db_error> {
+        let txs = match MemPoolDB::read_checkpoint(path) {
+            Ok(txs) => txs,
+            Err(db_error::NotFoundError) => {
+                return Ok((0, 0));
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        let (consensus_hash, block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(sortdb.conn())?;
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let stacks_epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), burn_tip.block_height)?
+            .ok_or_else(|| {
+                db_error::Other(format!(
+                    "No Stacks epoch for canonical burn height {}",
+                    burn_tip.block_height
+                ))
+            })?;
+
+        let mut num_restored = 0;
+        let mut num_rejected = 0;
+        for tx in txs.iter() {
+            let txid = tx.txid();
+            if self.has_tx(&txid) {
+                continue;
+            }
+            match self.submit(
+                chainstate,
+                sortdb,
+                &consensus_hash,
+                &block_hash,
+                tx,
+                None,
+                &stacks_epoch.block_limit,
+                &stacks_epoch.epoch_id,
+            ) {
+                Ok(()) => {
+                    num_restored += 1;
+                }
+                Err(e) => {
+                    debug!("Dropping checkpointed transaction"; "txid" => %txid, "reason" => ?e);
+                    num_rejected += 1;
+                }
+            }
+        }
+        Ok((num_restored, num_rejected))
+    }
+
     /// Do we have a transaction?
     pub fn has_tx(&self, txid: &Txid) -> bool {
         match MemPoolDB::db_has_tx(self.conn(), txid) {
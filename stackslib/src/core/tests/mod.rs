@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
-use std::{cmp, io};
+use std::{cmp, fs, io};
 
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::database::HeadersDB;
@@ -58,14 +58,15 @@ use crate::chainstate::stacks::{
     C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use crate::core::mempool::{
-    db_get_all_nonces, MemPoolSyncData, MemPoolWalkSettings, MemPoolWalkTxTypes, TxTag,
-    BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS,
+    db_get_all_nonces, MemPoolGCPolicy, MemPoolSyncData, MemPoolWalkSettings, MemPoolWalkStrategy,
+    MemPoolWalkTxTypes, TxTag, BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE,
+    MAX_BLOOM_COUNTER_TXS,
 };
 use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 use crate::net::Error as NetError;
 use crate::util_lib::bloom::test::setup_bloom_counter;
 use crate::util_lib::bloom::*;
-use crate::util_lib::db::{tx_begin_immediate, DBConn, FromRow};
+use crate::util_lib::db::{tx_begin_immediate, DBConn, Error as db_error, FromRow};
 use crate::util_lib::strings::StacksString;
 
 const FOO_CONTRACT: &'static str = "(define-public (foo) (ok 1))
@@ -768,6 +769,445 @@ fn test_iterate_candidates_consider_no_estimate_tx_prob() {
     );
 }
 
+#[test]
+/// This test verifies that walking the mempool by nonce chain considers each origin's
+/// next-nonce transaction in order of its chain's effective fee-rate, never considers
+/// transactions behind a nonce gap, and abandons a chain once one of its transactions is not
+/// mined.
+fn test_iterate_candidates_by_nonce_chain() {
+    let mut chainstate =
+        instantiate_chainstate_with_balances(false, 0x80000000, function_name!(), vec![]);
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+    let b_2 = make_block(&mut chainstate, ConsensusHash([0x2; 20]), &b_1, 2, 2);
+
+    let mut mempool_settings = MemPoolWalkSettings::default();
+    mempool_settings.strategy = MemPoolWalkStrategy::NextNonceWithHighestFeeRate;
+    mempool_settings.consider_no_estimate_tx_prob = 0;
+    let mut tx_events = Vec::new();
+
+    let mut txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+    );
+
+    let addr = |byte: u8| StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([byte; 20]),
+    };
+    // (origin, nonce, fee rate)
+    let candidates = [
+        // A low-fee transaction that unblocks a high-fee one
+        (addr(1), 0, 1.0),
+        (addr(1), 1, 1.0),
+        (addr(1), 2, 100.0),
+        (addr(2), 0, 10.0),
+        // Behind a nonce gap
+        (addr(3), 1, 1000.0),
+        (addr(3), 2, 1000.0),
+        // The first of these is skipped
+        (addr(4), 0, 50.0),
+        (addr(4), 1, 60.0),
+    ];
+    for (i, (origin_address, nonce, fee_rate)) in candidates.iter().enumerate() {
+        let mut tx = txs.pop().unwrap();
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        tx.set_tx_fee(100 + i as u64);
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let tx_fee = tx.get_tx_fee();
+        let height = 100;
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_1.0,
+            &b_1.1,
+            txid,
+            tx_bytes,
+            tx_fee,
+            height,
+            origin_address,
+            *nonce,
+            origin_address,
+            *nonce,
+            None,
+        )
+        .unwrap();
+
+        mempool_tx
+            .execute(
+                "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
+                rusqlite::params![Some(*fee_rate), &txid],
+            )
+            .unwrap();
+
+        mempool_tx.commit().unwrap();
+    }
+
+    chainstate.with_read_only_clarity_tx(
+        &TEST_BURN_STATE_DB,
+        &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+        |clarity_conn| {
+            let mut considered = vec![];
+            mempool
+                .iterate_candidates::<_, ChainstateError, _>(
+                    clarity_conn,
+                    &mut tx_events,
+                    2,
+                    mempool_settings.clone(),
+                    |_, available_tx, _| {
+                        let origin = available_tx.tx.metadata.origin_address;
+                        considered.push((origin, available_tx.tx.metadata.origin_nonce));
+                        let result = if origin == addr(4) {
+                            TransactionResult::skipped(
+                                &available_tx.tx.tx,
+                                "event not relevant to test".to_string(),
+                            )
+                            .convert_to_event()
+                        } else {
+                            // Generate any success result
+                            TransactionResult::success(
+                                &available_tx.tx.tx,
+                                available_tx.tx.metadata.tx_fee,
+                                StacksTransactionReceipt::from_stx_transfer(
+                                    available_tx.tx.tx.clone(),
+                                    vec![],
+                                    Value::okay(Value::Bool(true)).unwrap(),
+                                    ExecutionCost::zero(),
+                                ),
+                            )
+                            .convert_to_event()
+                        };
+                        Ok(Some(result))
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                considered,
+                vec![
+                    (addr(4), 0),
+                    (addr(1), 0),
+                    (addr(1), 1),
+                    (addr(1), 2),
+                    (addr(2), 0),
+                ]
+            );
+        },
+    );
+}
+
+#[test]
+/// This test verifies that walking the mempool by nonce chain does not load transactions that
+/// the `nonces` table shows are already mined, or from origins that are filtered out, and loads
+/// at most `candidate_retry_cache_size` of the rest, each origin's lowest nonces first.
+fn test_iterate_candidates_by_nonce_chain_bounded() {
+    let mut chainstate =
+        instantiate_chainstate_with_balances(false, 0x80000000, function_name!(), vec![]);
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+    let b_2 = make_block(&mut chainstate, ConsensusHash([0x2; 20]), &b_1, 2, 2);
+
+    let mut mempool_settings = MemPoolWalkSettings::default();
+    mempool_settings.strategy = MemPoolWalkStrategy::NextNonceWithHighestFeeRate;
+    mempool_settings.consider_no_estimate_tx_prob = 0;
+    mempool_settings.candidate_retry_cache_size = 4;
+    let mut tx_events = Vec::new();
+
+    let mut txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+    );
+
+    let addr = |byte: u8| StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([byte; 20]),
+    };
+    mempool_settings.filter_origins = [addr(1), addr(2), addr(3)].into_iter().collect();
+    // (origin, nonce, fee rate)
+    let candidates = [
+        // The first two are already mined. The low-fee head is loaded along with the
+        // transaction that pays for it.
+        (addr(1), 0, Some(10.0)),
+        (addr(1), 1, Some(10.0)),
+        (addr(1), 2, Some(1.0)),
+        (addr(1), 3, Some(100.0)),
+        (addr(2), 0, Some(5.0)),
+        // Past the limit
+        (addr(2), 1, Some(5.0)),
+        // Loaded even though it has no estimate
+        (addr(3), 0, None),
+        // Filtered out
+        (addr(4), 0, Some(50.0)),
+    ];
+    for (i, (origin_address, nonce, fee_rate)) in candidates.iter().enumerate() {
+        let mut tx = txs.pop().unwrap();
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        tx.set_tx_fee(100 + i as u64);
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let tx_fee = tx.get_tx_fee();
+        let height = 100;
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_1.0,
+            &b_1.1,
+            txid,
+            tx_bytes,
+            tx_fee,
+            height,
+            origin_address,
+            *nonce,
+            origin_address,
+            *nonce,
+            None,
+        )
+        .unwrap();
+
+        mempool_tx
+            .execute(
+                "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
+                rusqlite::params![fee_rate, &txid],
+            )
+            .unwrap();
+
+        mempool_tx.commit().unwrap();
+    }
+
+    let mempool_tx = mempool.tx_begin().unwrap();
+    mempool_tx
+        .execute(
+            "INSERT INTO nonces (address, nonce) VALUES (?1, ?2)",
+            rusqlite::params![addr(1).to_string(), 2],
+        )
+        .unwrap();
+    mempool_tx.commit().unwrap();
+
+    chainstate.with_read_only_clarity_tx(
+        &TEST_BURN_STATE_DB,
+        &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+        |clarity_conn| {
+            let mut considered = vec![];
+            mempool
+                .iterate_candidates::<_, ChainstateError, _>(
+                    clarity_conn,
+                    &mut tx_events,
+                    2,
+                    mempool_settings.clone(),
+                    |_, available_tx, _| {
+                        considered.push((
+                            available_tx.tx.metadata.origin_address,
+                            available_tx.tx.metadata.origin_nonce,
+                        ));
+                        Ok(Some(
+                            // Generate any success result
+                            TransactionResult::success(
+                                &available_tx.tx.tx,
+                                available_tx.tx.metadata.tx_fee,
+                                StacksTransactionReceipt::from_stx_transfer(
+                                    available_tx.tx.tx.clone(),
+                                    vec![],
+                                    Value::okay(Value::Bool(true)).unwrap(),
+                                    ExecutionCost::zero(),
+                                ),
+                            )
+                            .convert_to_event(),
+                        ))
+                    },
+                )
+                .unwrap();
+            assert_eq!(
+                considered,
+                vec![(addr(1), 2), (addr(1), 3), (addr(2), 0), (addr(3), 0)]
+            );
+        },
+    );
+}
+
+#[test]
+/// This test compares the two mempool walk strategies on a mempool where the best-paying
+/// transactions are behind a low-fee transaction or a nonce gap, for a block that only has room
+/// for 20 transactions. It reports the fees each block collects and how long each walk took, and
+/// checks that walking by nonce chain fills the block with more fees.
+fn test_iterate_candidates_walk_strategy_comparison() {
+    let mut chainstate =
+        instantiate_chainstate_with_balances(false, 0x80000000, function_name!(), vec![]);
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+    let b_2 = make_block(&mut chainstate, ConsensusHash([0x2; 20]), &b_1, 2, 2);
+
+    let template_tx = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+    )
+    .pop()
+    .unwrap();
+
+    let addr = |byte: u8| StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([byte; 20]),
+    };
+    // (origin, nonce, fee rate)
+    let mut candidates = vec![];
+    for i in 0..10 {
+        // A low-fee transaction that unblocks a high-fee one
+        candidates.push((addr(i), 0, 1));
+        candidates.push((addr(i), 1, 100));
+        // A single transaction
+        candidates.push((addr(10 + i), 0, 10));
+    }
+    for i in 0..50 {
+        // A chain behind a nonce gap, which can never be mined
+        for nonce in 1..=20 {
+            candidates.push((addr(20 + i), nonce, 1000));
+        }
+    }
+    for (i, (origin_address, nonce, fee_rate)) in candidates.iter().enumerate() {
+        let mut tx = template_tx.clone();
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        // The fee is proportional to the fee rate, and unique so that each txid is
+        tx.set_tx_fee(fee_rate * 10_000 + i as u64);
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let tx_fee = tx.get_tx_fee();
+        let height = 100;
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_1.0,
+            &b_1.1,
+            txid,
+            tx_bytes,
+            tx_fee,
+            height,
+            origin_address,
+            *nonce,
+            origin_address,
+            *nonce,
+            None,
+        )
+        .unwrap();
+
+        mempool_tx
+            .execute(
+                "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
+                rusqlite::params![Some(*fee_rate as f64), &txid],
+            )
+            .unwrap();
+
+        mempool_tx.commit().unwrap();
+    }
+
+    let mut block_fees = vec![];
+    for strategy in [
+        MemPoolWalkStrategy::GlobalFeeRate,
+        MemPoolWalkStrategy::NextNonceWithHighestFeeRate,
+    ] {
+        let mut mempool_settings = MemPoolWalkSettings::default();
+        mempool_settings.strategy = strategy;
+        mempool_settings.consider_no_estimate_tx_prob = 0;
+        mempool.reset_nonce_cache().unwrap();
+
+        chainstate.with_read_only_clarity_tx(
+            &TEST_BURN_STATE_DB,
+            &StacksBlockHeader::make_index_block_hash(&b_2.0, &b_2.1),
+            |clarity_conn| {
+                let mut tx_events = Vec::new();
+                let mut mined = 0;
+                let mut fees = 0;
+                let start_time = get_epoch_time_ms();
+                let considered = mempool
+                    .iterate_candidates::<_, ChainstateError, _>(
+                        clarity_conn,
+                        &mut tx_events,
+                        2,
+                        mempool_settings.clone(),
+                        |_, available_tx, _| {
+                            if mined == 20 {
+                                // The block is full
+                                return Ok(None);
+                            }
+                            mined += 1;
+                            fees += available_tx.tx.metadata.tx_fee / 10_000;
+                            Ok(Some(
+                                // Generate any success result
+                                TransactionResult::success(
+                                    &available_tx.tx.tx,
+                                    available_tx.tx.metadata.tx_fee,
+                                    StacksTransactionReceipt::from_stx_transfer(
+                                        available_tx.tx.tx.clone(),
+                                        vec![],
+                                        Value::okay(Value::Bool(true)).unwrap(),
+                                        ExecutionCost::zero(),
+                                    ),
+                                )
+                                .convert_to_event(),
+                            ))
+                        },
+                    )
+                    .unwrap();
+                eprintln!(
+                    "{:?}: mined {} txs with fee rates adding up to {}, considered {}, in {}ms",
+                    strategy,
+                    mined,
+                    fees,
+                    considered,
+                    get_epoch_time_ms() - start_time
+                );
+                assert_eq!(mined, 20);
+                block_fees.push(fees);
+            },
+        );
+    }
+
+    // Walking by nonce chain mines the ten low-fee transactions and the high-fee ones behind
+    // them, while the global walk spends part of the block on the single transactions first.
+    assert_eq!(block_fees[1], 10 + 10 * 100);
+    assert!(block_fees[0] < block_fees[1]);
+}
+
 #[test]
 /// This test verifies that when a transaction is skipped, other transactions
 /// from the same address with higher nonces are not considered for inclusion in a block.
@@ -1620,6 +2060,105 @@ fn mempool_db_load_store_replace_tx() {
     assert_eq!(txs.len(), 0);
 }
 
+#[test]
+fn mempool_size_limit_evicts_cheapest_txs() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let mut txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+    );
+
+    // Load 3 transactions with increasing fees per byte
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    let mut added = vec![];
+    for i in 0..3u8 {
+        let mut tx = txs.pop().unwrap();
+        let origin_address = StacksAddress {
+            version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+            bytes: Hash160([i; 20]),
+        };
+        let len = tx.serialize_to_vec().len() as u64;
+        tx.set_tx_fee(len * (i as u64 + 1) * 10);
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let tx_fee = tx.get_tx_fee();
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            txid,
+            tx_bytes,
+            tx_fee,
+            100,
+            &origin_address,
+            0,
+            &origin_address,
+            0,
+            None,
+        )
+        .unwrap();
+        added.push((txid, len, tx_fee));
+    }
+    mempool_tx.commit().unwrap();
+
+    let total_size: u64 = added.iter().map(|(_, len, _)| len).sum();
+    assert_eq!(
+        MemPoolDB::get_total_size(mempool.conn()).unwrap(),
+        total_size
+    );
+
+    // No limit, no floor, and nothing to evict
+    let policy = MemPoolGCPolicy::default();
+    assert_eq!(
+        MemPoolDB::get_fee_floor(mempool.conn(), &policy, 100).unwrap(),
+        None
+    );
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    assert_eq!(
+        MemPoolDB::enforce_size_limit(&mut mempool_tx, None).unwrap(),
+        0
+    );
+    mempool_tx.commit().unwrap();
+
+    // Once the mempool is near capacity, a new transaction must outbid the cheapest one
+    let policy = MemPoolGCPolicy {
+        max_size_bytes: Some(total_size),
+        ..MemPoolGCPolicy::default()
+    };
+    let (_, cheapest_len, cheapest_fee) = added[0];
+    assert_eq!(
+        MemPoolDB::get_fee_floor(mempool.conn(), &policy, cheapest_len).unwrap(),
+        Some(cheapest_fee + 1)
+    );
+
+    // Shrinking the limit by one byte evicts only the cheapest transaction
+    mempool.gc_policy = MemPoolGCPolicy {
+        max_size_bytes: Some(total_size - 1),
+        ..MemPoolGCPolicy::default()
+    };
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    assert_eq!(
+        MemPoolDB::enforce_size_limit(&mut mempool_tx, None).unwrap(),
+        1
+    );
+    mempool_tx.commit().unwrap();
+
+    assert!(!MemPoolDB::db_has_tx(mempool.conn(), &added[0].0).unwrap());
+    assert!(MemPoolDB::db_has_tx(mempool.conn(), &added[1].0).unwrap());
+    assert!(MemPoolDB::db_has_tx(mempool.conn(), &added[2].0).unwrap());
+    assert_eq!(
+        MemPoolDB::get_total_size(mempool.conn()).unwrap(),
+        total_size - cheapest_len
+    );
+}
+
 #[test]
 fn mempool_db_test_rbf() {
     let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
@@ -1752,6 +2291,7 @@ fn mempool_db_test_rbf() {
     // check that the transaction was replaced
     assert!(!MemPoolDB::db_has_tx(&mempool_tx, &old_txid).unwrap());
     assert!(MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
+    assert_eq!(MemPoolDB::get_total_size(&mempool_tx).unwrap(), second_len);
 
     let tx_info_after =
         MemPoolDB::get_tx_metadata_by_address(&mempool_tx, true, &origin_address, origin_nonce)
@@ -2643,6 +3183,97 @@ fn test_drop_and_blacklist_txs_by_size() {
     assert_eq!(num_blacklisted, 5);
 }
 
+#[test]
+fn test_mempool_checkpoint() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let checkpoint_path = format!("{}/mempool_checkpoint.bin", &chainstate_path);
+
+    // no checkpoint yet
+    assert!(matches!(
+        MemPoolDB::read_checkpoint(&checkpoint_path),
+        Err(db_error::NotFoundError)
+    ));
+
+    let addr = StacksAddress {
+        version: 1,
+        bytes: Hash160([0xff; 20]),
+    };
+    let mut txs = vec![];
+    let block_height = 10;
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    for i in 0..10 {
+        let pk = StacksPrivateKey::new();
+        let mut tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::from_p2pkh(&pk).unwrap(),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: vec![],
+            payload: TransactionPayload::TokenTransfer(
+                addr.to_account_principal(),
+                123,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+        tx.set_tx_fee(1000);
+        tx.set_origin_nonce(0);
+
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let origin_addr = tx.origin_address();
+        let origin_nonce = tx.get_origin_nonce();
+        let sponsor_addr = tx.sponsor_address().unwrap_or(origin_addr.clone());
+        let sponsor_nonce = tx.get_sponsor_nonce().unwrap_or(origin_nonce);
+        let tx_fee = tx.get_tx_fee();
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &ConsensusHash([0x1 + (block_height as u8); 20]),
+            &BlockHeaderHash([0x2 + (block_height as u8); 32]),
+            txid.clone(),
+            tx_bytes,
+            tx_fee,
+            block_height as u64,
+            &origin_addr,
+            origin_nonce,
+            &sponsor_addr,
+            sponsor_nonce,
+            None,
+        )
+        .unwrap();
+
+        eprintln!("Added {} {}", i, &txid);
+        txs.push(tx);
+    }
+    mempool_tx.commit().unwrap();
+
+    assert_eq!(mempool.write_checkpoint(&checkpoint_path).unwrap(), 10);
+    assert!(fs::metadata(format!("{}.tmp", &checkpoint_path)).is_err());
+
+    let mut expected_txids: Vec<_> = txs.iter().map(|tx| tx.txid()).collect();
+    expected_txids.sort();
+    let mut txids: Vec<_> = MemPoolDB::read_checkpoint(&checkpoint_path)
+        .unwrap()
+        .iter()
+        .map(|tx| tx.txid())
+        .collect();
+    txids.sort();
+    assert_eq!(txids, expected_txids);
+
+    // a truncated checkpoint still yields every complete transaction
+    let bytes = fs::read(&checkpoint_path).unwrap();
+    fs::write(&checkpoint_path, &bytes[0..bytes.len() - 1]).unwrap();
+    assert_eq!(
+        MemPoolDB::read_checkpoint(&checkpoint_path).unwrap().len(),
+        9
+    );
+}
+
 #[test]
 fn test_filter_txs_by_type() {
     let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
@@ -304,7 +304,7 @@ fn maybe_add_minimum_fee_rate(working_rates: &mut Vec<FeeRateAndWeight>, full_bl
 /// Returns None if:
 ///   1) There is no fee rate for the tx.
 ///   2) Cacluated fee rate is infinite.
-fn fee_rate_and_weight_from_receipt(
+pub(crate) fn fee_rate_and_weight_from_receipt(
     metric: &dyn CostMetric,
     tx_receipt: &StacksTransactionReceipt,
     block_limit: &ExecutionCost,
@@ -0,0 +1,224 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+use clarity::vm::costs::ExecutionCost;
+use rusqlite::{Connection, Error as SqliteError, Transaction as SqlTransaction};
+
+use super::fee_medians::fee_rate_and_weight_from_receipt;
+use super::metrics::CostMetric;
+use super::{EstimatorError, FeeEstimator, FeeRateEstimate, FeeRatePercentiles};
+use crate::chainstate::stacks::db::StacksEpochReceipt;
+use crate::chainstate::stacks::events::TransactionOrigin;
+use crate::chainstate::stacks::TransactionPayload;
+use crate::util_lib::db::{sqlite_open, table_exists, tx_begin_immediate_sqlite};
+
+const CREATE_TABLE: &'static str = "
+CREATE TABLE percentile_fee_estimator (
+    measure_key INTEGER PRIMARY KEY AUTOINCREMENT,
+    block_key INTEGER NOT NULL,
+    tx_type TEXT NOT NULL,
+    fee_rate NUMBER NOT NULL
+);
+CREATE INDEX percentile_fee_estimator_by_type ON percentile_fee_estimator(tx_type, fee_rate);
+CREATE INDEX percentile_fee_estimator_by_block ON percentile_fee_estimator(block_key);
+";
+
+/// FeeRateEstimator which records the fee rate paid by each transaction included in the last
+/// `window_size` blocks, bucketed by transaction payload type (see `TransactionPayload::name()`).
+///
+/// Unlike `WeightedMedianFeeRateEstimator`, each transaction counts once regardless of its size,
+/// and empty block space is not considered: the percentiles describe what recently-included
+/// transactions of a given type actually paid.  Blocks without any fee-paying transactions do
+/// not count towards the window.
+///
+/// Samples are stored in a SQLite DB, so estimates survive restarts.
+pub struct PercentileFeeRateEstimator<M: CostMetric> {
+    db: Connection,
+    /// We only keep the fee rates of the last `window_size` blocks.
+    window_size: u32,
+    /// Use this cost metric in fee rate calculations.
+    metric: M,
+}
+
+impl<M: CostMetric> PercentileFeeRateEstimator<M> {
+    /// Open a fee rate estimator at the given db path. Creates if not existent.
+    pub fn open(p: &Path, metric: M, window_size: u32) -> Result<Self, SqliteError> {
+        let mut db = sqlite_open(
+            p,
+            rusqlite::OpenFlags::SQLITE_OPEN_CREATE | rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE,
+            false,
+        )?;
+
+        // check if the db needs to be instantiated regardless of whether or not
+        //  it was newly created: the db itself may be shared with other fee estimators,
+        //  which would not have created the necessary table for this estimator.
+        let tx = tx_begin_immediate_sqlite(&mut db)?;
+        Self::instantiate_db(&tx)?;
+        tx.commit()?;
+
+        Ok(Self {
+            db,
+            window_size,
+            metric,
+        })
+    }
+
+    /// Check if the SQL database was already created. Necessary to avoid races if
+    ///  different threads open an estimator at the same time.
+    fn db_already_instantiated(tx: &SqlTransaction) -> Result<bool, SqliteError> {
+        table_exists(tx, "percentile_fee_estimator")
+    }
+
+    fn instantiate_db(tx: &SqlTransaction) -> Result<(), SqliteError> {
+        if !Self::db_already_instantiated(tx)? {
+            tx.execute_batch(CREATE_TABLE)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the sorted fee rates for `tx_type`, or for all payload types if `None`.
+    fn get_sorted_fee_rates(
+        conn: &Connection,
+        tx_type: Option<&str>,
+    ) -> Result<Vec<f64>, EstimatorError> {
+        let mut fee_rates = match tx_type {
+            Some(tx_type) => {
+                let sql = "SELECT fee_rate FROM percentile_fee_estimator WHERE tx_type = ?";
+                let mut stmt = conn.prepare(sql)?;
+                let rows = stmt.query_map(rusqlite::params![tx_type], |row| row.get(0))?;
+                rows.collect::<Result<Vec<f64>, SqliteError>>()?
+            }
+            None => {
+                let sql = "SELECT fee_rate FROM percentile_fee_estimator";
+                let mut stmt = conn.prepare(sql)?;
+                let rows = stmt.query_map(rusqlite::NO_PARAMS, |row| row.get(0))?;
+                rows.collect::<Result<Vec<f64>, SqliteError>>()?
+            }
+        };
+
+        // Sort our float arrays. For float values that do not compare easily,
+        // treat them as equals.
+        fee_rates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        Ok(fee_rates)
+    }
+
+    fn get_percentiles_from_sql(
+        conn: &Connection,
+        tx_type: Option<&str>,
+    ) -> Result<FeeRatePercentiles, EstimatorError> {
+        let fee_rates = Self::get_sorted_fee_rates(conn, tx_type)?;
+        if fee_rates.is_empty() {
+            return Err(EstimatorError::NoEstimateAvailable);
+        }
+        Ok(fee_rate_percentiles_from_sorted_fees(&fee_rates))
+    }
+
+    fn update_estimate(&mut self, fee_rates: Vec<(&'static str, f64)>) {
+        let tx = tx_begin_immediate_sqlite(&mut self.db).expect("SQLite failure");
+        let block_key: i64 = tx
+            .query_row(
+                "SELECT IFNULL(MAX(block_key), 0) + 1 FROM percentile_fee_estimator",
+                rusqlite::NO_PARAMS,
+                |row| row.get(0),
+            )
+            .expect("SQLite failure");
+
+        let insert_sql = "INSERT INTO percentile_fee_estimator
+                          (block_key, tx_type, fee_rate) VALUES (?, ?, ?)";
+        for (tx_type, fee_rate) in fee_rates.iter() {
+            tx.execute(insert_sql, rusqlite::params![block_key, tx_type, fee_rate])
+                .expect("SQLite failure");
+        }
+
+        let deletion_sql = "DELETE FROM percentile_fee_estimator WHERE block_key <= ?";
+        tx.execute(
+            deletion_sql,
+            rusqlite::params![block_key - i64::from(self.window_size)],
+        )
+        .expect("SQLite failure");
+
+        let estimate = Self::get_percentiles_from_sql(&tx, None);
+        tx.commit().expect("SQLite failure");
+        if let Ok(next_estimate) = estimate {
+            debug!("Updating percentile fee rate estimate for new block";
+                   "num_fee_rates" => fee_rates.len(),
+                   "new_estimate_p25" => next_estimate.p25,
+                   "new_estimate_p50" => next_estimate.p50,
+                   "new_estimate_p75" => next_estimate.p75,
+                   "new_estimate_p95" => next_estimate.p95);
+        }
+    }
+}
+
+impl<M: CostMetric> FeeEstimator for PercentileFeeRateEstimator<M> {
+    fn notify_block(
+        &mut self,
+        receipt: &StacksEpochReceipt,
+        block_limit: &ExecutionCost,
+    ) -> Result<(), EstimatorError> {
+        let fee_rates: Vec<_> = receipt
+            .tx_receipts
+            .iter()
+            .filter_map(|tx_receipt| {
+                let tx_type = match tx_receipt.transaction {
+                    TransactionOrigin::Stacks(ref tx) => tx.payload.name(),
+                    TransactionOrigin::Burn(_) => return None,
+                };
+                fee_rate_and_weight_from_receipt(&self.metric, tx_receipt, block_limit)
+                    .map(|rate_and_weight| (tx_type, rate_and_weight.fee_rate))
+            })
+            .collect();
+
+        if fee_rates.len() > 0 {
+            self.update_estimate(fee_rates);
+        }
+
+        Ok(())
+    }
+
+    /// Estimates across all payload types: `low`, `middle`, and `high` are the 25th, 50th and
+    /// 95th percentiles.
+    fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
+        let percentiles = Self::get_percentiles_from_sql(&self.db, None)?;
+        Ok(FeeRateEstimate {
+            high: percentiles.p95,
+            middle: percentiles.p50,
+            low: percentiles.p25,
+        })
+    }
+
+    fn get_percentile_estimates(
+        &self,
+        payload: &TransactionPayload,
+    ) -> Result<FeeRatePercentiles, EstimatorError> {
+        Self::get_percentiles_from_sql(&self.db, Some(payload.name()))
+    }
+}
+
+/// Computes the value at `percentile` (in [0, 1]) of `sorted_fee_rates`, linearly interpolating
+/// between the closest ranks.
+///
+/// `sorted_fee_rates` must be non-empty.
+fn percentile_of_sorted_fees(sorted_fee_rates: &[f64], percentile: f64) -> f64 {
+    assert!(!sorted_fee_rates.is_empty());
+
+    let rank = percentile * (sorted_fee_rates.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let vk = sorted_fee_rates[lower];
+    let vk1 = sorted_fee_rates[upper];
+    vk + (rank - lower as f64) * (vk1 - vk)
+}
+
+/// Computes the `FeeRatePercentiles` of `sorted_fee_rates`.
+///
+/// `sorted_fee_rates` must be non-empty.
+pub fn fee_rate_percentiles_from_sorted_fees(sorted_fee_rates: &[f64]) -> FeeRatePercentiles {
+    FeeRatePercentiles {
+        p25: percentile_of_sorted_fees(sorted_fee_rates, 0.25),
+        p50: percentile_of_sorted_fees(sorted_fee_rates, 0.5),
+        p75: percentile_of_sorted_fees(sorted_fee_rates, 0.75),
+        p95: percentile_of_sorted_fees(sorted_fee_rates, 0.95),
+    }
+}
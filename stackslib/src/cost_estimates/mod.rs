@@ -15,6 +15,7 @@ use crate::chainstate::stacks::events::{StacksTransactionReceipt, TransactionOri
 use crate::chainstate::stacks::{StacksBlock, TransactionPayload};
 
 pub mod fee_medians;
+pub mod fee_percentiles;
 pub mod fee_rate_fuzzer;
 pub mod fee_scalar;
 pub mod metrics;
@@ -49,6 +50,15 @@ pub trait FeeEstimator {
     ) -> Result<(), EstimatorError>;
     /// Get the current estimates for fee rate
     fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError>;
+    /// Get the current fee rate percentiles paid by transactions with the same payload type as
+    ///  `payload`. Estimators which do not track fee rates by payload type return
+    ///  `EstimatorError::NoEstimateAvailable`.
+    fn get_percentile_estimates(
+        &self,
+        _payload: &TransactionPayload,
+    ) -> Result<FeeRatePercentiles, EstimatorError> {
+        Err(EstimatorError::NoEstimateAvailable)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
@@ -60,6 +70,16 @@ pub struct FeeRateEstimate {
     pub low: f64,
 }
 
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+/// This struct is returned from fee rate estimators which track the fee rates paid by recently
+/// included transactions, as the percentiles of those fee rates.
+pub struct FeeRatePercentiles {
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p95: f64,
+}
+
 fn saturating_f64_math(res: f64) -> f64 {
     if res.is_finite() {
         res
@@ -0,0 +1,267 @@
+use std::env;
+
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::Value;
+use rand::Rng;
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::util::hash::{to_hex, Hash160};
+
+use crate::chainstate::stacks::events::StacksTransactionReceipt;
+use crate::chainstate::stacks::{
+    CoinbasePayload, StacksTransaction, TokenTransferMemo, TransactionAuth,
+    TransactionContractCall, TransactionPayload, TransactionSpendingCondition, TransactionVersion,
+};
+use crate::cost_estimates::fee_percentiles::{
+    fee_rate_percentiles_from_sorted_fees, PercentileFeeRateEstimator,
+};
+use crate::cost_estimates::metrics::{CostMetric, UnitMetric};
+use crate::cost_estimates::tests::common::*;
+use crate::cost_estimates::{EstimatorError, FeeEstimator, FeeRatePercentiles};
+
+/// Returns true iff `b` is within `0.1%` of `a`.
+fn is_close_f64(a: f64, b: f64) -> bool {
+    let error = (a - b).abs() / a.abs();
+    error < 0.001
+}
+
+/// Returns `true` iff each value in `left` "close" to its counterpart in `right`.
+fn is_close(left: FeeRatePercentiles, right: FeeRatePercentiles) -> bool {
+    let is_ok = is_close_f64(left.p25, right.p25)
+        && is_close_f64(left.p50, right.p50)
+        && is_close_f64(left.p75, right.p75)
+        && is_close_f64(left.p95, right.p95);
+    if !is_ok {
+        warn!(
+            "FeeRatePercentiles are not close. {:?} vs {:?}",
+            left, right
+        );
+    }
+    is_ok
+}
+
+fn instantiate_test_db<CM: CostMetric>(m: CM) -> PercentileFeeRateEstimator<CM> {
+    let mut path = env::temp_dir();
+    let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
+    path.push(&format!("fee_db_{}.sqlite", &to_hex(&random_bytes)[0..8]));
+
+    let window_size = 2;
+    PercentileFeeRateEstimator::open(&path, m, window_size)
+        .expect("Test failure: could not open fee rate DB")
+}
+
+fn cc_payload() -> TransactionPayload {
+    TransactionPayload::ContractCall(TransactionContractCall {
+        address: StacksAddress::new(0, Hash160([0; 20])),
+        contract_name: "cc-dummy".into(),
+        function_name: "func-name".into(),
+        function_args: vec![],
+    })
+}
+
+fn transfer_payload() -> TransactionPayload {
+    TransactionPayload::TokenTransfer(
+        StacksAddress::new(0, Hash160([0; 20])).into(),
+        1,
+        TokenTransferMemo([0; 34]),
+    )
+}
+
+fn make_dummy_coinbase_tx() -> StacksTransactionReceipt {
+    StacksTransactionReceipt::from_coinbase(StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::Coinbase(CoinbasePayload([0; 32]), None),
+    ))
+}
+
+/// With the `UnitMetric`, every transaction's fee rate is its fee.
+fn make_dummy_tx(payload: TransactionPayload, fee: u64) -> StacksTransactionReceipt {
+    let mut tx = StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        payload,
+    );
+    tx.set_tx_fee(fee);
+    StacksTransactionReceipt::from_contract_call(
+        tx,
+        vec![],
+        Value::okay(Value::Bool(true)).unwrap(),
+        0,
+        ExecutionCost::zero(),
+    )
+}
+
+const block_limit: ExecutionCost = ExecutionCost {
+    write_length: 100,
+    write_count: 100,
+    read_length: 100,
+    read_count: 100,
+    runtime: 100,
+};
+
+#[test]
+fn test_percentiles_math() {
+    assert!(is_close(
+        fee_rate_percentiles_from_sorted_fees(&[7f64]),
+        FeeRatePercentiles {
+            p25: 7f64,
+            p50: 7f64,
+            p75: 7f64,
+            p95: 7f64,
+        }
+    ));
+
+    let fee_rates: Vec<f64> = (0..=100).map(|x| x as f64).collect();
+    assert!(is_close(
+        fee_rate_percentiles_from_sorted_fees(&fee_rates),
+        FeeRatePercentiles {
+            p25: 25f64,
+            p50: 50f64,
+            p75: 75f64,
+            p95: 95f64,
+        }
+    ));
+
+    // interpolates between ranks
+    assert!(is_close(
+        fee_rate_percentiles_from_sorted_fees(&[10f64, 20f64]),
+        FeeRatePercentiles {
+            p25: 12.5f64,
+            p50: 15f64,
+            p75: 17.5f64,
+            p95: 19.5f64,
+        }
+    ));
+}
+
+#[test]
+fn test_empty_fee_estimator() {
+    let mut estimator = instantiate_test_db(UnitMetric);
+    assert_eq!(
+        estimator
+            .get_rate_estimates()
+            .expect_err("Empty rate estimator should error."),
+        EstimatorError::NoEstimateAvailable
+    );
+
+    // coinbases don't pay fees, so they never produce an estimate
+    estimator
+        .notify_block(
+            &make_block_receipt(vec![make_dummy_coinbase_tx()]),
+            &block_limit,
+        )
+        .expect("Should be able to process block");
+    assert_eq!(
+        estimator
+            .get_percentile_estimates(&cc_payload())
+            .expect_err("Empty rate estimator should error."),
+        EstimatorError::NoEstimateAvailable
+    );
+}
+
+#[test]
+fn test_percentiles_by_payload_type() {
+    let mut estimator = instantiate_test_db(UnitMetric);
+
+    let mut receipts = vec![make_dummy_coinbase_tx()];
+    for fee in 1..=5 {
+        receipts.push(make_dummy_tx(cc_payload(), fee * 100));
+    }
+    receipts.push(make_dummy_tx(transfer_payload(), 10));
+    estimator
+        .notify_block(&make_block_receipt(receipts), &block_limit)
+        .expect("Should be able to process block");
+
+    assert!(is_close(
+        estimator.get_percentile_estimates(&cc_payload()).unwrap(),
+        FeeRatePercentiles {
+            p25: 200f64,
+            p50: 300f64,
+            p75: 400f64,
+            p95: 480f64,
+        }
+    ));
+    assert!(is_close(
+        estimator
+            .get_percentile_estimates(&transfer_payload())
+            .unwrap(),
+        FeeRatePercentiles {
+            p25: 10f64,
+            p50: 10f64,
+            p75: 10f64,
+            p95: 10f64,
+        }
+    ));
+
+    // all payload types are pooled for the legacy estimate
+    let estimate = estimator.get_rate_estimates().unwrap();
+    assert!(is_close_f64(estimate.low, 125f64));
+    assert!(is_close_f64(estimate.middle, 250f64));
+    assert!(is_close_f64(estimate.high, 475f64));
+}
+
+#[test]
+fn test_window_size_forgets_old_blocks() {
+    let mut estimator = instantiate_test_db(UnitMetric);
+
+    for fee in [100, 200, 300].iter() {
+        estimator
+            .notify_block(
+                &make_block_receipt(vec![make_dummy_tx(cc_payload(), *fee)]),
+                &block_limit,
+            )
+            .expect("Should be able to process block");
+    }
+
+    // only the last 2 blocks are kept
+    assert!(is_close(
+        estimator.get_percentile_estimates(&cc_payload()).unwrap(),
+        FeeRatePercentiles {
+            p25: 225f64,
+            p50: 250f64,
+            p75: 275f64,
+            p95: 295f64,
+        }
+    ));
+
+    // blocks with no fee-paying transactions don't count towards the window
+    estimator
+        .notify_block(
+            &make_block_receipt(vec![make_dummy_coinbase_tx()]),
+            &block_limit,
+        )
+        .expect("Should be able to process block");
+    assert_eq!(
+        estimator
+            .get_percentile_estimates(&cc_payload())
+            .unwrap()
+            .p50,
+        250f64
+    );
+}
+
+#[test]
+fn test_estimates_survive_reopen() {
+    let mut path = env::temp_dir();
+    let random_bytes = rand::thread_rng().gen::<[u8; 32]>();
+    path.push(&format!("fee_db_{}.sqlite", &to_hex(&random_bytes)[0..8]));
+
+    {
+        let mut estimator = PercentileFeeRateEstimator::open(&path, UnitMetric, 5).unwrap();
+        estimator
+            .notify_block(
+                &make_block_receipt(vec![make_dummy_tx(cc_payload(), 100)]),
+                &block_limit,
+            )
+            .expect("Should be able to process block");
+    }
+
+    let estimator = PercentileFeeRateEstimator::open(&path, UnitMetric, 5).unwrap();
+    assert_eq!(
+        estimator
+            .get_percentile_estimates(&cc_payload())
+            .unwrap()
+            .p50,
+        100f64
+    );
+}
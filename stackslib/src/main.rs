@@ -65,7 +65,7 @@ use blockstack_lib::clarity_cli::vm_execute;
 use blockstack_lib::core::{MemPoolDB, *};
 use blockstack_lib::cost_estimates::metrics::UnitMetric;
 use blockstack_lib::cost_estimates::UnitEstimator;
-use blockstack_lib::net::db::LocalPeer;
+use blockstack_lib::net::db::{LocalPeer, PeerDB, PeerQualityRecord};
 use blockstack_lib::net::p2p::PeerNetwork;
 use blockstack_lib::net::relay::Relayer;
 use blockstack_lib::net::StacksMessage;
@@ -1117,6 +1117,50 @@ simulating a miner.
         process::exit(0);
     }
 
+    if argv[1] == "export-peer-quality" {
+        if argv.len() < 3 {
+            eprintln!("Usage: {} export-peer-quality PEER_DB_PATH", &argv[0]);
+            process::exit(1);
+        }
+        let peerdb = PeerDB::open(&argv[2], false).unwrap();
+        let local_peer = PeerDB::get_local_peer(peerdb.conn()).unwrap();
+        let records = PeerDB::export_peer_quality(peerdb.conn(), local_peer.network_id).unwrap();
+
+        println!("{}", &serde_json::to_string_pretty(&records).unwrap());
+        process::exit(0);
+    }
+
+    if argv[1] == "import-peer-quality" {
+        if argv.len() < 4 {
+            eprintln!(
+                "Usage: {} import-peer-quality PEER_DB_PATH PEER_QUALITY_JSON_PATH",
+                &argv[0]
+            );
+            process::exit(1);
+        }
+        let records_json = fs::read_to_string(&argv[3]).unwrap();
+        let records: Vec<PeerQualityRecord> = serde_json::from_str(&records_json).unwrap();
+
+        let mut peerdb = PeerDB::open(&argv[2], true).unwrap();
+        let local_peer = PeerDB::get_local_peer(peerdb.conn()).unwrap();
+        let records: Vec<_> = records
+            .into_iter()
+            .filter(|record| record.network_id == local_peer.network_id)
+            .collect();
+
+        let tx = peerdb.tx_begin().unwrap();
+        let num_imported = PeerDB::import_peer_quality(&tx, &records).unwrap();
+        tx.commit().unwrap();
+
+        println!(
+            "Imported {} of {} peers into {}",
+            num_imported,
+            records.len(),
+            &argv[2]
+        );
+        process::exit(0);
+    }
+
     if argv[1] == "replay-chainstate" {
         if argv.len() < 7 {
             eprintln!("Usage: {} OLD_CHAINSTATE_PATH OLD_SORTITION_DB_PATH OLD_BURNCHAIN_DB_PATH NEW_CHAINSTATE_PATH NEW_BURNCHAIN_DB_PATH", &argv[0]);
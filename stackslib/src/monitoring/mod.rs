@@ -23,6 +23,7 @@ use std::{fmt, fs};
 
 use clarity::vm::costs::ExecutionCost;
 use rusqlite::{OpenFlags, OptionalExtension};
+use stacks_common::types::StacksEpochId;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::uint::{Uint256, Uint512};
 
@@ -35,6 +36,11 @@ use crate::util_lib::db::{sqlite_open, tx_busy_handler, DBConn, Error as Databas
 #[cfg(feature = "monitoring_prom")]
 mod prometheus;
 
+/// Gather all of the node's metrics for export, labeled with the node's network and the
+/// current epoch
+#[cfg(feature = "monitoring_prom")]
+pub use self::prometheus::gather as gather_metrics;
+
 #[cfg(feature = "monitoring_prom")]
 lazy_static! {
     static ref GLOBAL_BURNCHAIN_SIGNER: Mutex<Option<BurnchainSigner>> = Mutex::new(None);
@@ -101,6 +107,45 @@ pub fn increment_btc_blocks_received_counter() {
     prometheus::BTC_BLOCKS_RECEIVED_COUNTER.inc();
 }
 
+pub fn increment_marf_cache_hits_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MARF_CACHE_HITS_COUNTER.inc();
+}
+
+pub fn increment_marf_cache_misses_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MARF_CACHE_MISSES_COUNTER.inc();
+}
+
+pub fn increment_marf_cache_evictions_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MARF_CACHE_EVICTIONS_COUNTER.inc();
+}
+
+/// Set the `network` label attached to every metric
+#[allow(unused_variables)]
+pub fn set_network_label(network: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    {
+        prometheus::NODE_LABELS.lock().unwrap().network = Some(network.to_string());
+    }
+}
+
+/// Set the `epoch` label attached to every metric, to the epoch the last block was evaluated in
+#[allow(unused_variables)]
+pub fn update_epoch_label(epoch_id: StacksEpochId) {
+    #[cfg(feature = "monitoring_prom")]
+    {
+        prometheus::NODE_LABELS.lock().unwrap().epoch = Some(epoch_id.to_string());
+    }
+}
+
+#[allow(unused_variables)]
+pub fn observe_stx_block_processing_time(seconds: f64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STX_BLOCK_PROCESSING_TIME_HISTOGRAM.observe(seconds);
+}
+
 /// Log `execution_cost` as a ratio of `block_limit`.
 #[allow(unused_variables)]
 pub fn set_last_execution_cost_observed(
@@ -166,6 +211,58 @@ pub fn increment_btc_ops_sent_counter() {
     prometheus::BTC_OPS_SENT_COUNTER.inc();
 }
 
+/// Time a call to the bitcoind RPC method `method`
+#[allow(unused_variables)]
+pub fn instrument_btc_rpc_call<F, R>(method: &str, call: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    #[cfg(feature = "monitoring_prom")]
+    let timer = prometheus::new_btc_rpc_call_timer(method);
+
+    let res = call();
+
+    #[cfg(feature = "monitoring_prom")]
+    timer.stop_and_record();
+
+    res
+}
+
+/// Time an attempt to deliver a payload to the event observer at `endpoint`, counting it as a
+/// failure if `deliver` returns false
+#[allow(unused_variables)]
+pub fn instrument_event_observer_delivery<F>(endpoint: &str, path: &str, deliver: F) -> bool
+where
+    F: FnOnce() -> bool,
+{
+    #[cfg(feature = "monitoring_prom")]
+    let timer = prometheus::new_event_observer_delivery_timer(endpoint, path);
+
+    let delivered = deliver();
+
+    #[cfg(feature = "monitoring_prom")]
+    {
+        timer.stop_and_record();
+        if !delivered {
+            prometheus::EVENT_OBSERVER_DELIVERY_FAILURES_COUNTER
+                .with_label_values(&[endpoint])
+                .inc();
+        }
+    }
+
+    delivered
+}
+
+pub fn increment_btc_utxo_cache_hits_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BTC_UTXO_CACHE_HITS_COUNTER.inc();
+}
+
+pub fn increment_btc_utxo_cache_misses_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::BTC_UTXO_CACHE_MISSES_COUNTER.inc();
+}
+
 pub fn increment_stx_blocks_processed_counter() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::STX_BLOCKS_PROCESSED_COUNTER.inc();
@@ -340,16 +437,69 @@ pub fn increment_msg_counter(name: String) {
         .inc();
 }
 
+#[allow(unused_variables)]
+pub fn update_reference_node_divergence(reference_node: &str, value: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::REFERENCE_NODE_DIVERGENCE_GAUGE
+        .with_label_values(&[reference_node])
+        .set(value);
+}
+
+pub fn increment_fork_divergence_alerts_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::FORK_DIVERGENCE_ALERTS_COUNTER.inc();
+}
+
+pub fn increment_mempool_txs_admitted_counter() {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MEMPOOL_TXS_ADMITTED_COUNTER.inc();
+}
+
+#[allow(unused_variables)]
+pub fn increment_mempool_txs_rejected_counter(reason: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MEMPOOL_TXS_REJECTED_COUNTER
+        .with_label_values(&[reason])
+        .inc();
+}
+
 pub fn increment_stx_mempool_gc() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::STX_MEMPOOL_GC.inc();
 }
 
+/// Count transactions evicted from the mempool by its GC policy, by reason ("age" or "size")
+#[allow(unused_variables)]
+pub fn increment_mempool_evictions_counter(reason: &str, count: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::MEMPOOL_EVICTIONS_COUNTER
+        .with_label_values(&[reason])
+        .inc_by(count);
+}
+
 pub fn increment_contract_calls_processed() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::CONTRACT_CALLS_PROCESSED_COUNT.inc();
 }
 
+/// Count an attempt to store a chunk in the StackerDB for `contract`, by result ("accepted",
+/// "stale" or "rejected")
+#[allow(unused_variables)]
+pub fn increment_stackerdb_chunk_writes_counter(contract: &str, result: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_CHUNK_WRITES_COUNTER
+        .with_label_values(&[contract, result])
+        .inc();
+}
+
+#[allow(unused_variables)]
+pub fn increment_stackerdb_chunk_reads_counter(contract: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::STACKERDB_CHUNK_READS_COUNTER
+        .with_label_values(&[contract])
+        .inc();
+}
+
 /// Given a value (type uint256), return value/uint256::max() as an f64 value.
 /// The precision of the percentage is determined by the input `precision_points`, which is capped
 /// at a max of 15.
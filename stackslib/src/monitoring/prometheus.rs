@@ -14,246 +14,501 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Mutex;
+
+use prometheus::proto::{LabelPair, MetricFamily};
 use prometheus::{
     Gauge, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, DEFAULT_BUCKETS,
 };
 
 lazy_static! {
     pub static ref RPC_CALL_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_rpc_requests_total",
         "Total number of RPC requests made.",
-        labels! {"handler" => "all",}
+        labels! {"component" => "rpc", "handler" => "all",}
     )).unwrap();
 
     pub static ref RPC_CALL_LATENCIES_HISTOGRAM: HistogramVec = register_histogram_vec!(histogram_opts!(
         "stacks_node_rpc_call_latencies_histogram",
-        "Time (seconds) measuring RPC calls latency"
-        // Will use DEFAULT_BUCKETS = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0] by default
+        "Time (seconds) measuring RPC calls latency",
+        // DEFAULT_BUCKETS = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+        DEFAULT_BUCKETS.to_vec(),
+        labels! {"component".to_string() => "rpc".to_string(),}
     ), &["path"]).unwrap();
 
     pub static ref STX_BLOCKS_RECEIVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_blocks_received_total",
-        "Total number of Stacks blocks received"
+        "Total number of Stacks blocks received",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref STX_MICRO_BLOCKS_RECEIVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_micro_blocks_received_total",
-        "Total number of Stacks micro blocks received"
+        "Total number of Stacks micro blocks received",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref STX_BLOCKS_SERVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_blocks_served_total",
-        "Total number of Stacks blocks served"
+        "Total number of Stacks blocks served",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref STX_MICRO_BLOCKS_SERVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_micro_blocks_served_total",
-        "Total number of Stacks micro blocks served"
+        "Total number of Stacks micro blocks served",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref STX_CONFIRMED_MICRO_BLOCKS_SERVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_confirmed_micro_blocks_served_total",
-        "Total number of Stacks blocks served"
+        "Total number of Stacks blocks served",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref TXS_RECEIVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_transactions_received_total",
-        "Total number of transactions received and relayed"
+        "Total number of transactions received and relayed",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref BTC_BLOCKS_RECEIVED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_btc_blocks_received_total",
-        "Total number of blocks processed from the burnchain"
+        "Total number of blocks processed from the burnchain",
+        labels! {"component" => "burnchain",}
     )).unwrap();
 
     pub static ref BTC_OPS_SENT_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_btc_ops_sent_total",
-        "Total number of ops (key registrations, block commits, user burn supports) submitted to the burnchain"
+        "Total number of ops (key registrations, block commits, user burn supports) submitted to the burnchain",
+        labels! {"component" => "burnchain",}
+    )).unwrap();
+
+    pub static ref BTC_RPC_CALL_LATENCIES_HISTOGRAM: HistogramVec = register_histogram_vec!(histogram_opts!(
+        "stacks_node_btc_rpc_call_latencies_histogram",
+        "Time (seconds) measuring bitcoind RPC call latency, by method",
+        DEFAULT_BUCKETS.to_vec(),
+        labels! {"component".to_string() => "burnchain".to_string(),}
+    ), &["method"]).unwrap();
+
+    pub static ref BTC_UTXO_CACHE_HITS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_btc_utxo_cache_hits_total",
+        "Total number of miner UTXO lookups served from the UTXO cache",
+        labels! {"component" => "burnchain",}
+    )).unwrap();
+
+    pub static ref BTC_UTXO_CACHE_MISSES_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_btc_utxo_cache_misses_total",
+        "Total number of miner UTXO lookups that had to query the burnchain node",
+        labels! {"component" => "burnchain",}
     )).unwrap();
 
     pub static ref STX_BLOCKS_PROCESSED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_blocks_processed_total",
-        "Total number of stacks blocks processed"
+        "Total number of stacks blocks processed",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref STX_BLOCKS_MINED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_stx_blocks_mined_total",
-        "Total number of stacks blocks mined by node"
+        "Total number of stacks blocks mined by node",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref WARNING_EMITTED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_warning_emitted_total",
-        "Total number of warning logs emitted by node"
+        "Total number of warning logs emitted by node",
+        labels! {"component" => "node",}
     )).unwrap();
 
     pub static ref ERRORS_EMITTED_COUNTER: IntCounter = register_int_counter!(opts!(
         "stacks_node_errors_emitted_total",
-        "Total number of error logs emitted by node"
+        "Total number of error logs emitted by node",
+        labels! {"component" => "node",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_READ_COUNT: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_read_count",
-        "`execution_cost_read_count` for the last block observed."
+        "`execution_cost_read_count` for the last block observed.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_WRITE_COUNT: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_write_count",
-        "`execution_cost_write_count` for the last block observed."
+        "`execution_cost_write_count` for the last block observed.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_READ_LENGTH: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_read_length",
-        "`execution_cost_read_length` for the last block observed."
+        "`execution_cost_read_length` for the last block observed.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_WRITE_LENGTH: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_write_length",
-        "`execution_cost_write_length` for the last block observed."
+        "`execution_cost_write_length` for the last block observed.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_RUNTIME: Gauge = register_gauge!(opts!(
         "stacks_node_last_block_runtime",
-        "`execution_cost_runtime` for the last block observed."
+        "`execution_cost_runtime` for the last block observed.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_BLOCK_TRANSACTION_COUNT: IntGauge = register_int_gauge!(opts!(
         "stacks_node_last_block_transaction_count",
-        "Number of transactions in the last block."
+        "Number of transactions in the last block.",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_READ_COUNT: Gauge = register_gauge!(opts!(
         "stacks_node_last_mined_block_read_count",
-        "`execution_cost_read_count` for the last mined block produced."
+        "`execution_cost_read_count` for the last mined block produced.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_WRITE_COUNT: Gauge = register_gauge!(opts!(
         "stacks_node_last_mined_block_write_count",
-        "`execution_cost_write_count` for the last mined block produced."
+        "`execution_cost_write_count` for the last mined block produced.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_READ_LENGTH: Gauge = register_gauge!(opts!(
         "stacks_node_last_mined_block_read_length",
-        "`execution_cost_read_length` for the last mined block produced."
+        "`execution_cost_read_length` for the last mined block produced.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_WRITE_LENGTH: Gauge = register_gauge!(opts!(
         "stacks_node_last_mined_block_write_length",
-        "`execution_cost_write_length` for the last mined block produced."
+        "`execution_cost_write_length` for the last mined block produced.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_RUNTIME: Gauge = register_gauge!(opts!(
         "stacks_node_last_mined_block_runtime",
-        "`execution_cost_runtime` for the last mined block produced."
+        "`execution_cost_runtime` for the last mined block produced.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref LAST_MINED_BLOCK_TRANSACTION_COUNT: IntGauge = register_int_gauge!(opts!(
         "stacks_node_last_mined_block_transaction_count",
-        "Number of transactions in the last mined block."
+        "Number of transactions in the last mined block.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
 
     pub static ref ACTIVE_MINERS_COUNT_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_active_miners_total",
-        "Total number of active miners"
+        "Total number of active miners",
+        labels! {"component" => "miner",}
+    )).unwrap();
+
+    pub static ref STX_BLOCK_PROCESSING_TIME_HISTOGRAM: Histogram = register_histogram!(histogram_opts!(
+        "stacks_node_stx_block_processing_time_seconds",
+        "Time (seconds) taken to validate and append a Stacks block and its parent microblocks to the chainstate",
+        vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0],
+        labels! {"component".to_string() => "chainstate".to_string(),}
     )).unwrap();
 
     pub static ref STACKS_TIP_HEIGHT_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_stacks_tip_height",
-        "Stacks chain tip height"
+        "Stacks chain tip height",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref BURNCHAIN_HEIGHT_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_burn_block_height",
-        "Burnchain tip height"
+        "Burnchain tip height",
+        labels! {"component" => "burnchain",}
     )).unwrap();
 
     pub static ref INBOUND_NEIGHBORS_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_neighbors_inbound",
-        "Total count of current known inbound neighbors"
+        "Total count of current known inbound neighbors",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref OUTBOUND_NEIGHBORS_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_neighbors_outbound",
-        "Total count of current known outbound neighbors"
+        "Total count of current known outbound neighbors",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref INBOUND_BANDWIDTH_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_bandwidth_inbound",
-        "Total inbound bandwidth total in bytes"
+        "Total inbound bandwidth total in bytes",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref OUTBOUND_BANDWIDTH_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_bandwidth_outbound",
-        "Total outbound bandwidth total in bytes"
+        "Total outbound bandwidth total in bytes",
+        labels! {"component" => "p2p",}
     )).unwrap();
 
     pub static ref INBOUND_RPC_BANDWIDTH_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_rpc_bandwidth_inbound",
-        "Total RPC inbound bandwidth in bytes"
+        "Total RPC inbound bandwidth in bytes",
+        labels! {"component" => "rpc",}
     )).unwrap();
 
     pub static ref OUTBOUND_RPC_BANDWIDTH_GAUGE: IntGauge = register_int_gauge!(opts!(
         "stacks_node_rpc_bandwidth_outbound",
-        "Total RPC outbound bandwidth in bytes"
+        "Total RPC outbound bandwidth in bytes",
+        labels! {"component" => "rpc",}
     )).unwrap();
 
-    pub static ref MSG_COUNTER_VEC: IntCounterVec = register_int_counter_vec!(
+    pub static ref MSG_COUNTER_VEC: IntCounterVec = register_int_counter_vec!(opts!(
         "stacks_node_message_count",
         "Stacks message count by type of message",
-        &["name"]
-    ).unwrap();
+        labels! {"component" => "p2p",}
+    ), &["name"]).unwrap();
 
 
     pub static ref STX_MEMPOOL_GC: IntCounter = register_int_counter!(opts!(
         "stacks_node_mempool_gc_count",
-        "Total count of all mempool garbage collections"
+        "Total count of all mempool garbage collections",
+        labels! {"component" => "mempool",}
     )).unwrap();
 
     pub static ref CONTRACT_CALLS_PROCESSED_COUNT: IntCounter = register_int_counter!(opts!(
         "stacks_contract_calls_processed",
-        "Total count of processed contract calls"
+        "Total count of processed contract calls",
+        labels! {"component" => "chainstate",}
     )).unwrap();
 
     pub static ref MEMPOOL_OUTSTANDING_TXS: IntGauge = register_int_gauge!(opts!(
         "stacks_node_mempool_outstanding_txs",
         "Number of still-unprocessed transactions received by this node since it started",
-        labels! {"handler" => "all",}
+        labels! {"component" => "mempool", "handler" => "all",}
     )).unwrap();
 
+    pub static ref MEMPOOL_TXS_ADMITTED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_mempool_txs_admitted_total",
+        "Total number of transactions admitted to the mempool",
+        labels! {"component" => "mempool",}
+    )).unwrap();
+
+    pub static ref MEMPOOL_TXS_REJECTED_COUNTER: IntCounterVec = register_int_counter_vec!(opts!(
+        "stacks_node_mempool_txs_rejected_total",
+        "Total number of transactions rejected by the mempool, by rejection reason",
+        labels! {"component" => "mempool",}
+    ), &["reason"]).unwrap();
+
+    pub static ref MEMPOOL_EVICTIONS_COUNTER: IntCounterVec = register_int_counter_vec!(opts!(
+        "stacks_node_mempool_evictions_total",
+        "Total number of transactions evicted from the mempool by its GC policy, by reason",
+        labels! {"component" => "mempool",}
+    ), &["reason"]).unwrap();
+
     pub static ref MEMPOOL_TX_CONFIRM_TIME: Histogram = register_histogram!(histogram_opts!(
         "stacks_node_mempool_tx_confirm_times",
         "Time (seconds) between when a tx was received by this node's mempool and when a tx was first processed in a block",
         vec![300.0, 600.0, 900.0, 1200.0, 1500.0, 1800.0, 2100.0, 2400.0, 2700.0, 3000.0, 3600.0, 4200.0, 4800.0, 6000.0],
-        labels! {"handler".to_string() => "all".to_string(),}
+        labels! {"component".to_string() => "mempool".to_string(), "handler".to_string() => "all".to_string(),}
     )).unwrap();
 
     pub static ref COMPUTED_RELATIVE_MINER_SCORE: Gauge = register_gauge!(opts!(
         "stacks_node_computed_relative_miner_score",
-        "Percentage of the u256 range that this miner is assigned in a particular round of sortition"
+        "Percentage of the u256 range that this miner is assigned in a particular round of sortition",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref COMPUTED_MINER_COMMITMENT_HIGH: IntGauge = register_int_gauge!(opts!(
         "stacks_node_computed_miner_commitment_high",
-        "High 64 bits of a miner's effective commitment (min of the miner's previous commitment and their median commitment)"
+        "High 64 bits of a miner's effective commitment (min of the miner's previous commitment and their median commitment)",
+        labels! {"component" => "miner",}
     )).unwrap();
 
      pub static ref COMPUTED_MINER_COMMITMENT_LOW: IntGauge = register_int_gauge!(opts!(
         "stacks_node_computed_miner_commitment_low",
-        "Low 64 bits of a miner's effective commitment (min of the miner's previous commitment and their median commitment)"
+        "Low 64 bits of a miner's effective commitment (min of the miner's previous commitment and their median commitment)",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref MINER_CURRENT_MEDIAN_COMMITMENT_HIGH: IntGauge = register_int_gauge!(opts!(
         "stacks_node_miner_current_median_commitment_high",
-        "High 64 bits of a miner's median commitment over the mining commitment window."
+        "High 64 bits of a miner's median commitment over the mining commitment window.",
+        labels! {"component" => "miner",}
     )).unwrap();
 
     pub static ref MINER_CURRENT_MEDIAN_COMMITMENT_LOW: IntGauge = register_int_gauge!(opts!(
         "stacks_node_miner_current_median_commitment_low",
-        "Low 64 bits of a miner's median commitment over the mining commitment window."
+        "Low 64 bits of a miner's median commitment over the mining commitment window.",
+        labels! {"component" => "miner",}
+    )).unwrap();
+
+    pub static ref REFERENCE_NODE_DIVERGENCE_GAUGE: IntGaugeVec = register_int_gauge_vec!(opts!(
+        "stacks_node_reference_node_divergence",
+        "Number of blocks by which our canonical Stacks tip diverges from a reference node's tip",
+        labels! {"component" => "chainstate",}
+    ), &["reference_node"]).unwrap();
+
+    pub static ref FORK_DIVERGENCE_ALERTS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_fork_divergence_alerts_total",
+        "Total number of times a reference node's tip diverged from ours by more than the threshold",
+        labels! {"component" => "chainstate",}
+    )).unwrap();
+
+    pub static ref MARF_CACHE_HITS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_marf_cache_hits_total",
+        "Total number of MARF trie node lookups served from the node cache",
+        labels! {"component" => "marf",}
+    )).unwrap();
+
+    pub static ref MARF_CACHE_MISSES_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_marf_cache_misses_total",
+        "Total number of MARF trie node lookups that missed the node cache",
+        labels! {"component" => "marf",}
+    )).unwrap();
+
+    pub static ref MARF_CACHE_EVICTIONS_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_marf_cache_evictions_total",
+        "Total number of MARF trie nodes evicted from the LRU node cache to stay within its byte budget",
+        labels! {"component" => "marf",}
     )).unwrap();
+
+    pub static ref STACKERDB_CHUNK_WRITES_COUNTER: IntCounterVec = register_int_counter_vec!(opts!(
+        "stacks_node_stackerdb_chunk_writes_total",
+        "Total number of attempts to store a StackerDB chunk, by contract and result",
+        labels! {"component" => "stackerdb",}
+    ), &["contract", "result"]).unwrap();
+
+    pub static ref STACKERDB_CHUNK_READS_COUNTER: IntCounterVec = register_int_counter_vec!(opts!(
+        "stacks_node_stackerdb_chunk_reads_total",
+        "Total number of StackerDB chunks read, by contract",
+        labels! {"component" => "stackerdb",}
+    ), &["contract"]).unwrap();
+
+    pub static ref EVENT_OBSERVER_DELIVERY_LATENCIES_HISTOGRAM: HistogramVec = register_histogram_vec!(histogram_opts!(
+        "stacks_node_event_observer_delivery_latencies_histogram",
+        "Time (seconds) measuring each attempt to deliver a payload to an event observer, by endpoint",
+        DEFAULT_BUCKETS.to_vec(),
+        labels! {"component".to_string() => "event_observer".to_string(),}
+    ), &["endpoint", "path"]).unwrap();
+
+    pub static ref EVENT_OBSERVER_DELIVERY_FAILURES_COUNTER: IntCounterVec = register_int_counter_vec!(opts!(
+        "stacks_node_event_observer_delivery_failures_total",
+        "Total number of failed attempts to deliver a payload to an event observer, by endpoint",
+        labels! {"component" => "event_observer",}
+    ), &["endpoint"]).unwrap();
+}
+
+/// Labels attached to every metric when they are gathered, since they are not known until the
+/// node is configured or has processed a block
+#[derive(Debug, Clone, Default)]
+pub struct NodeLabels {
+    pub network: Option<String>,
+    pub epoch: Option<String>,
+}
+
+lazy_static! {
+    pub static ref NODE_LABELS: Mutex<NodeLabels> = Mutex::new(NodeLabels::default());
+}
+
+/// Add the node-wide labels to each gathered metric, keeping each metric's labels sorted
+fn add_node_labels(metric_families: &mut [MetricFamily], node_labels: &NodeLabels) {
+    let labels: Vec<(&str, &String)> = [
+        ("network", &node_labels.network),
+        ("epoch", &node_labels.epoch),
+    ]
+    .iter()
+    .filter_map(|(name, value_opt)| value_opt.as_ref().map(|value| (*name, value)))
+    .collect();
+
+    for metric_family in metric_families.iter_mut() {
+        for metric in metric_family.mut_metric().iter_mut() {
+            let metric_labels = metric.mut_label();
+            for (name, value) in labels.iter() {
+                let mut label = LabelPair::new();
+                label.set_name(name.to_string());
+                label.set_value(value.to_string());
+                metric_labels.push(label);
+            }
+            metric_labels.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+        }
+    }
+}
+
+/// Gather all of the node's metrics, labeled with the network and epoch
+pub fn gather() -> Vec<MetricFamily> {
+    let mut metric_families = prometheus::gather();
+    let node_labels = NODE_LABELS.lock().unwrap().clone();
+    add_node_labels(&mut metric_families, &node_labels);
+    metric_families
 }
 
 pub fn new_rpc_call_timer(path: &str) -> HistogramTimer {
     let histogram = RPC_CALL_LATENCIES_HISTOGRAM.with_label_values(&[path]);
     histogram.start_timer()
 }
+
+pub fn new_btc_rpc_call_timer(method: &str) -> HistogramTimer {
+    let histogram = BTC_RPC_CALL_LATENCIES_HISTOGRAM.with_label_values(&[method]);
+    histogram.start_timer()
+}
+
+pub fn new_event_observer_delivery_timer(endpoint: &str, path: &str) -> HistogramTimer {
+    let histogram =
+        EVENT_OBSERVER_DELIVERY_LATENCIES_HISTOGRAM.with_label_values(&[endpoint, path]);
+    histogram.start_timer()
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::{Encoder, Registry, TextEncoder};
+
+    use super::*;
+
+    #[test]
+    fn node_labels_are_added_to_each_metric() {
+        let registry = Registry::new();
+        let counter = IntCounterVec::new(
+            opts!(
+                "test_counter",
+                "A test counter",
+                labels! {"component" => "p2p",}
+            ),
+            &["name"],
+        )
+        .unwrap();
+        registry.register(Box::new(counter.clone())).unwrap();
+        counter.with_label_values(&["ping"]).inc();
+        counter.with_label_values(&["pong"]).inc_by(2);
+
+        let mut metric_families = registry.gather();
+        add_node_labels(
+            &mut metric_families,
+            &NodeLabels {
+                network: Some("mainnet".into()),
+                epoch: Some("2.4".into()),
+            },
+        );
+
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains(
+            r#"test_counter{component="p2p",epoch="2.4",name="ping",network="mainnet"} 1"#
+        ));
+        assert!(text.contains(
+            r#"test_counter{component="p2p",epoch="2.4",name="pong",network="mainnet"} 2"#
+        ));
+
+        // labels that aren't known yet are left out
+        let mut metric_families = registry.gather();
+        add_node_labels(&mut metric_families, &NodeLabels::default());
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        assert!(text.contains(r#"test_counter{component="p2p",name="ping"} 1"#));
+    }
+}
@@ -79,6 +79,8 @@ pub struct CallReadOnlyResponse {
 pub struct RPCCallReadOnlyRequestHandler {
     maximum_call_argument_size: u32,
     read_only_call_limit: ExecutionCost,
+    /// how many blocks behind the canonical tip a call may be evaluated at (0 means no limit)
+    read_only_call_max_tip_depth: u64,
 
     /// Runtime fields
     pub contract_identifier: Option<QualifiedContractIdentifier>,
@@ -86,18 +88,26 @@ pub struct RPCCallReadOnlyRequestHandler {
     pub sender: Option<PrincipalData>,
     pub sponsor: Option<PrincipalData>,
     pub arguments: Option<Vec<Value>>,
+    /// height of the canonical ancestor block to evaluate the call at, if not the tip
+    pub block_height: Option<u64>,
 }
 
 impl RPCCallReadOnlyRequestHandler {
-    pub fn new(maximum_call_argument_size: u32, read_only_call_limit: ExecutionCost) -> Self {
+    pub fn new(
+        maximum_call_argument_size: u32,
+        read_only_call_limit: ExecutionCost,
+        read_only_call_max_tip_depth: u64,
+    ) -> Self {
         Self {
             maximum_call_argument_size,
             read_only_call_limit,
+            read_only_call_max_tip_depth,
             contract_identifier: None,
             function: None,
             sender: None,
             sponsor: None,
             arguments: None,
+            block_height: None,
         }
     }
 }
@@ -163,13 +173,33 @@ impl HttpRequest for RPCCallReadOnlyRequestHandler {
             .collect::<Option<Vec<Value>>>()
             .ok_or_else(|| Error::DecodeError("Failed to deserialize argument value".into()))?;
 
+        let contents = HttpRequestContents::new().query_string(query);
+        let block_height = contents
+            .get_query_arg("block_height")
+            .map(|value| {
+                value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|height| *height < u64::from(u32::MAX))
+                    .ok_or_else(|| {
+                        Error::DecodeError("Failed to parse `block_height` argument".into())
+                    })
+            })
+            .transpose()?;
+        if block_height.is_some() && contents.get_query_arg("tip").is_some() {
+            return Err(Error::DecodeError(
+                "Invalid Http request: `tip` and `block_height` are mutually exclusive".into(),
+            ));
+        }
+
         self.contract_identifier = Some(contract_identifier);
         self.function = Some(function);
         self.sender = Some(sender);
         self.sponsor = sponsor;
         self.arguments = Some(arguments);
+        self.block_height = block_height;
 
-        Ok(HttpRequestContents::new().query_string(query))
+        Ok(contents)
     }
 }
 
@@ -182,6 +212,7 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
         self.sender = None;
         self.sponsor = None;
         self.arguments = None;
+        self.block_height = None;
     }
 
     /// Make the response
@@ -198,6 +229,76 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
             }
         };
 
+        // evaluate at the canonical ancestor at `block_height`, if given
+        let tip = if let Some(block_height) = self.block_height.take() {
+            let ancestor_res =
+                node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                    let ancestor_opt = chainstate
+                        .index_conn()?
+                        .get_ancestor_block_hash(block_height, &tip)?;
+                    Ok::<_, ChainError>(ancestor_opt)
+                });
+            match ancestor_res {
+                Ok(Some(ancestor)) => ancestor,
+                Ok(None) => {
+                    return StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpNotFound::new(format!("No block at height {}", block_height)),
+                    )
+                    .try_into_contents()
+                    .map_err(NetError::from);
+                }
+                Err(e) => {
+                    return StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpServerError::new(format!("Failed to load ancestor block: {:?}", &e)),
+                    )
+                    .try_into_contents()
+                    .map_err(NetError::from);
+                }
+            }
+        } else {
+            tip
+        };
+
+        // refuse to evaluate too far behind the canonical tip.  Tips without a header (i.e. the
+        // unconfirmed tip) are never behind it.
+        if self.read_only_call_max_tip_depth > 0 {
+            let header_res =
+                node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                    StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        chainstate.db(),
+                        &tip,
+                    )
+                });
+            let canonical_height = u64::from(node.canonical_stacks_tip_height());
+            match header_res {
+                Ok(Some(header)) => {
+                    let depth = canonical_height.saturating_sub(header.stacks_block_height);
+                    if depth > self.read_only_call_max_tip_depth {
+                        return StacksHttpResponse::new_error(
+                            &preamble,
+                            &HttpBadRequest::new(format!(
+                                "Block {} is {} blocks behind the canonical tip; this node only evaluates read-only calls up to {} blocks behind it",
+                                &tip, depth, self.read_only_call_max_tip_depth
+                            )),
+                        )
+                        .try_into_contents()
+                        .map_err(NetError::from);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    return StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpServerError::new(format!("Failed to load block header: {:?}", &e)),
+                    )
+                    .try_into_contents()
+                    .map_err(NetError::from);
+                }
+            }
+        }
+
         let contract_identifier = self
             .contract_identifier
             .take()
@@ -319,6 +420,7 @@ impl RPCRequestHandler for RPCCallReadOnlyRequestHandler {
 
         let mut preamble = HttpResponsePreamble::ok_json(&preamble);
         preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        preamble.set_stacks_tip(&tip);
         let body = HttpResponseContents::try_from_json(&data_resp)?;
         Ok((preamble, body))
     }
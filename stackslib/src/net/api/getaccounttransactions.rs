@@ -0,0 +1,315 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{Read, Write};
+
+use clarity::vm::representations::PRINCIPAL_DATA_REGEX_STRING;
+use clarity::vm::types::PrincipalData;
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::net::PeerHost;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::{AccountTransactionRole, StacksChainState};
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState, TipRequest};
+
+/// How many transactions are returned if the request does not set `limit`
+pub const DEFAULT_ACCOUNT_TRANSACTIONS_LIMIT: u32 = 20;
+/// The most transactions a single request can return
+pub const MAX_ACCOUNT_TRANSACTIONS_LIMIT: u32 = 50;
+
+/// A transaction on the canonical fork that involved the account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountTransactionResponse {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    /// How the account was involved in the transaction
+    pub roles: Vec<AccountTransactionRole>,
+}
+
+/// A page of an account's transaction history, newest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountTransactionsResponse {
+    pub limit: u32,
+    pub offset: u32,
+    pub results: Vec<AccountTransactionResponse>,
+}
+
+#[derive(Clone)]
+pub struct RPCGetAccountTransactionsRequestHandler {
+    pub account: Option<PrincipalData>,
+    pub limit: u32,
+    pub offset: u32,
+}
+impl RPCGetAccountTransactionsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            account: None,
+            limit: DEFAULT_ACCOUNT_TRANSACTIONS_LIMIT,
+            offset: 0,
+        }
+    }
+}
+
+/// Parse an optional numeric query argument
+fn get_u32_query_arg(
+    contents: &HttpRequestContents,
+    key: &str,
+    default: u32,
+) -> Result<u32, Error> {
+    contents
+        .get_query_arg(key)
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .map_err(|_e| Error::DecodeError(format!("Failed to parse `{}` argument", key)))
+        })
+        .unwrap_or(Ok(default))
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetAccountTransactionsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(&format!(
+            "^/v2/addresses/(?P<principal>{})/transactions$",
+            *PRINCIPAL_DATA_REGEX_STRING
+        ))
+        .unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let account = if let Some(value) = captures.name("principal") {
+            PrincipalData::parse(value.into())
+                .map_err(|_e| Error::DecodeError("Failed to parse `principal` field".to_string()))?
+        } else {
+            return Err(Error::DecodeError(
+                "Missing in request path: `principal`".into(),
+            ));
+        };
+
+        let contents = HttpRequestContents::new().query_string(query);
+        let limit = get_u32_query_arg(&contents, "limit", DEFAULT_ACCOUNT_TRANSACTIONS_LIMIT)?;
+        if limit == 0 || limit > MAX_ACCOUNT_TRANSACTIONS_LIMIT {
+            return Err(Error::DecodeError(format!(
+                "`limit` must be between 1 and {}",
+                MAX_ACCOUNT_TRANSACTIONS_LIMIT
+            )));
+        }
+        let offset = get_u32_query_arg(&contents, "offset", 0)?;
+
+        self.account = Some(account);
+        self.limit = limit;
+        self.offset = offset;
+
+        Ok(contents)
+    }
+}
+
+impl RPCRequestHandler for RPCGetAccountTransactionsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.account = None;
+        self.limit = DEFAULT_ACCOUNT_TRANSACTIONS_LIMIT;
+        self.offset = 0;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let tip = match node.load_stacks_chain_tip(&preamble, &contents) {
+            Ok(tip) => tip,
+            Err(error_resp) => {
+                return error_resp.try_into_contents().map_err(NetError::from);
+            }
+        };
+        let account = self
+            .account
+            .take()
+            .ok_or(NetError::SendError("Missing `account`".into()))?;
+        let limit = self.limit;
+        let offset = self.offset;
+
+        let account_txindex =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                chainstate.account_txindex
+            });
+        if !account_txindex {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpNotFound::new(
+                    "This node does not index account transactions (set `node.account_txindex = true` to enable it)".to_string(),
+                ),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        }
+
+        let results_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                let tip_height =
+                    StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        chainstate.db(),
+                        &tip,
+                    )?
+                    .ok_or(NetError::NotFoundError)?
+                    .stacks_block_height;
+                let entries = StacksChainState::get_account_transactions(
+                    chainstate.db(),
+                    &account,
+                    tip_height,
+                )?;
+
+                // the index covers every fork, so only keep the transactions processed by the
+                // tip's ancestors
+                let index_conn = chainstate.index_conn()?;
+                let mut results = vec![];
+                let mut skipped = 0;
+                for entry in entries.into_iter() {
+                    if results.len() >= limit as usize {
+                        break;
+                    }
+                    if index_conn
+                        .get_ancestor_block_height(&entry.index_block_hash, &tip)?
+                        .is_none()
+                    {
+                        continue;
+                    }
+                    if skipped < offset {
+                        skipped += 1;
+                        continue;
+                    }
+                    results.push(AccountTransactionResponse {
+                        txid: entry.txid,
+                        index_block_hash: entry.index_block_hash,
+                        block_height: entry.block_height,
+                        tx_index: entry.tx_index,
+                        roles: entry.roles,
+                    });
+                }
+                Ok(results)
+            });
+
+        let results = match results_res {
+            Ok(results) => results,
+            Err(NetError::NotFoundError) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("Chain tip '{}' not found", &tip)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Failed to query transactions of {}: {:?}",
+                        &account, &e
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let response = AccountTransactionsResponse {
+            limit,
+            offset,
+            results,
+        };
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        preamble.set_stacks_tip(&tip);
+        let body = HttpResponseContents::try_from_json(&response)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetAccountTransactionsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let history: AccountTransactionsResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(history)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for a page of an account's transaction history
+    pub fn new_getaccounttransactions(
+        host: PeerHost,
+        principal: PrincipalData,
+        tip_req: TipRequest,
+        limit: u32,
+        offset: u32,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v2/addresses/{}/transactions", &principal),
+            HttpRequestContents::new()
+                .for_tip(tip_req)
+                .query_arg("limit".into(), limit.to_string())
+                .query_arg("offset".into(), offset.to_string()),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_getaccounttransactions(self) -> Result<AccountTransactionsResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let history: AccountTransactionsResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(history)
+    }
+}
@@ -29,9 +29,9 @@ use {serde, serde_json};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{Error as ChainError, StacksBlock};
 use crate::net::http::{
-    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
-    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
-    HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpError,
+    HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
 };
 use crate::net::httpcore::{
     HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
@@ -68,6 +68,9 @@ impl StacksBlockStream {
     pub fn new(chainstate: &StacksChainState, block: &StacksBlockId) -> Result<Self, ChainError> {
         let _ = StacksChainState::load_staging_block_info(chainstate.db(), block)?
             .ok_or(ChainError::NoSuchBlockError)?;
+        if StacksChainState::is_block_pruned(chainstate.db(), block)? {
+            return Err(ChainError::BlockPruned);
+        }
 
         let blocks_path = chainstate.blocks_path.clone();
 
@@ -154,6 +157,14 @@ impl RPCRequestHandler for RPCBlocksRequestHandler {
                 .try_into_contents()
                 .map_err(NetError::from)
             }
+            Err(ChainError::BlockPruned) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpError::new(410, format!("Block {:?} was pruned\n", &block_id)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
             Err(e) => {
                 // nope -- error trying to check
                 let msg = format!("Failed to load block: {:?}\n", &e);
@@ -0,0 +1,224 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use serde;
+use serde_json;
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::operations::{DelegateStxOp, StackStxOp, TransferStxOp};
+use crate::net::http::{
+    parse_json, Error, HttpBadRequest, HttpNotFound, HttpRequest, HttpRequestContents,
+    HttpRequestPreamble, HttpResponse, HttpResponseContents, HttpResponsePayload,
+    HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+use crate::util_lib::db::Error as DBError;
+
+/// The processed burnchain operations of one kind in a burnchain block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnchainOps {
+    StackStx(Vec<StackStxOp>),
+    DelegateStx(Vec<DelegateStxOp>),
+    TransferStx(Vec<TransferStxOp>),
+}
+
+#[derive(Clone)]
+pub struct RPCGetBurnOpsRequestHandler {
+    pub height: Option<u64>,
+    pub op_type: Option<String>,
+}
+impl RPCGetBurnOpsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            height: None,
+            op_type: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetBurnOpsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/burn_ops/(?P<height>[0-9]{1,20})/(?P<op_type>[a-z_]{1,32})$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let height = captures
+            .name("height")
+            .ok_or(Error::DecodeError("Failed to match path to height".into()))?
+            .as_str()
+            .parse::<u64>()
+            .map_err(|_e| Error::DecodeError("Failed to decode `height`".into()))?;
+        let op_type = captures
+            .name("op_type")
+            .ok_or(Error::DecodeError("Failed to match path to op_type".into()))?
+            .as_str()
+            .to_string();
+
+        self.height = Some(height);
+        self.op_type = Some(op_type);
+
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetBurnOpsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.height = None;
+        self.op_type = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let height = self
+            .height
+            .take()
+            .ok_or(NetError::SendError("`height` not set".into()))?;
+        let op_type = self
+            .op_type
+            .take()
+            .ok_or(NetError::SendError("`op_type` not set".into()))?;
+
+        if !["stack_stx", "delegate_stx", "transfer_stx"].contains(&op_type.as_str()) {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpBadRequest::new(format!("Unknown burnchain operation type `{}`\n", &op_type)),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        }
+
+        let ops_res = node.with_node_state(|_network, sortdb, _chainstate, _mempool, _rpc_args| {
+            let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+            let snapshot = match SortitionDB::get_ancestor_snapshot(
+                &sortdb.index_conn(),
+                height,
+                &tip.sortition_id,
+            )? {
+                Some(snapshot) => snapshot,
+                None => {
+                    return Ok(None);
+                }
+            };
+            let burn_header_hash = &snapshot.burn_header_hash;
+            let ops = match op_type.as_str() {
+                "stack_stx" => BurnchainOps::StackStx(SortitionDB::get_stack_stx_ops(
+                    sortdb.conn(),
+                    burn_header_hash,
+                )?),
+                "delegate_stx" => BurnchainOps::DelegateStx(SortitionDB::get_delegate_stx_ops(
+                    sortdb.conn(),
+                    burn_header_hash,
+                )?),
+                _ => BurnchainOps::TransferStx(SortitionDB::get_transfer_stx_ops(
+                    sortdb.conn(),
+                    burn_header_hash,
+                )?),
+            };
+            Ok::<_, DBError>(Some(ops))
+        });
+
+        let ops = match ops_res {
+            Ok(Some(ops)) => ops,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No burnchain block at height {}\n", height)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                let msg = format!("Failed to load burnchain operations: {:?}\n", &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&ops)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetBurnOpsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let ops: BurnchainOps = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(ops)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the processed burnchain operations of the given type
+    /// (`stack_stx`, `delegate_stx`, or `transfer_stx`) at a burnchain block height
+    pub fn new_get_burn_ops(host: PeerHost, height: u64, op_type: &str) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v2/burn_ops/{}/{}", height, op_type),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_burn_ops(self) -> Result<BurnchainOps, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: BurnchainOps = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
@@ -190,6 +190,7 @@ impl RPCRequestHandler for RPCGetDataVarRequestHandler {
 
         let mut preamble = HttpResponsePreamble::ok_json(&preamble);
         preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        preamble.set_stacks_tip(&tip);
         let body = HttpResponseContents::try_from_json(&data_resp)?;
         Ok((preamble, body))
     }
@@ -0,0 +1,212 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::get_epoch_time_secs;
+
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::core::mempool::MemPoolDB;
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCEndpointGroup, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Machine-readable summary of the node's sync progress, connectivity and activity
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCHealthData {
+    /// The height of the burnchain tip this node has processed
+    pub burn_block_height: u64,
+    /// The height of the burnchain tip, as far as this node has downloaded headers.
+    /// `None` if the node has not learned it yet.
+    pub bitcoin_tip_height: Option<u64>,
+    /// How many burnchain blocks this node has yet to process
+    pub burn_block_lag: Option<u64>,
+    /// The height of the canonical Stacks tip
+    pub stacks_tip_height: u64,
+    /// Seconds since the burnchain block that selected the canonical Stacks tip.
+    /// `None` if the node has not processed a Stacks block yet.
+    pub stacks_tip_age: Option<u64>,
+    /// Number of authenticated inbound peer connections
+    pub inbound_peers: u64,
+    /// Number of authenticated outbound peer connections
+    pub outbound_peers: u64,
+    /// Number of transactions in the mempool
+    pub mempool_size: u64,
+    /// Whether this node runs a miner thread
+    pub miner_enabled: bool,
+    /// Whether the miner thread is running, i.e. it is enabled and not blocked
+    pub miner_active: bool,
+}
+
+#[derive(Clone)]
+pub struct RPCGetHealthRequestHandler {}
+impl RPCGetHealthRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetHealthRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/health$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetHealthRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::Admin
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let health_res = node.with_node_state(|network, _sortdb, chainstate, mempool, rpc_args| {
+            let burn_tip = &network.burnchain_tip;
+            let stacks_tip_age = StacksChainState::get_anchored_block_header_info(
+                chainstate.db(),
+                &burn_tip.canonical_stacks_tip_consensus_hash,
+                &burn_tip.canonical_stacks_tip_hash,
+            )?
+            .map(|header| get_epoch_time_secs().saturating_sub(header.burn_header_timestamp));
+
+            let mut inbound_peers = 0;
+            let mut outbound_peers = 0;
+            for convo in network.peers.values() {
+                if !convo.is_authenticated() {
+                    continue;
+                }
+                if convo.is_outbound() {
+                    outbound_peers += 1;
+                } else {
+                    inbound_peers += 1;
+                }
+            }
+
+            let (miner_enabled, miner_active) = match rpc_args.miner_status.as_ref() {
+                Some(miner_status) => {
+                    let blocked = miner_status
+                        .lock()
+                        .expect("FATAL: mutex poisoned")
+                        .is_blocked();
+                    (true, !blocked)
+                }
+                None => (false, false),
+            };
+
+            Ok::<_, ChainError>(RPCHealthData {
+                burn_block_height: burn_tip.block_height,
+                bitcoin_tip_height: rpc_args.bitcoin_tip_height,
+                burn_block_lag: rpc_args
+                    .bitcoin_tip_height
+                    .map(|height| height.saturating_sub(burn_tip.block_height)),
+                stacks_tip_height: burn_tip.canonical_stacks_tip_height,
+                stacks_tip_age,
+                inbound_peers,
+                outbound_peers,
+                mempool_size: MemPoolDB::get_num_txs(mempool.conn())?,
+                miner_enabled,
+                miner_active,
+            })
+        });
+
+        let health = match health_res {
+            Ok(health) => health,
+            Err(e) => {
+                let msg = format!("Failed to load node health: {:?}\n", &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&health)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetHealthRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let health: RPCHealthData = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(health)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the node's health
+    pub fn new_get_health(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v2/health".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_health(self) -> Result<RPCHealthData, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: RPCHealthData = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
@@ -0,0 +1,307 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
+use clarity::vm::representations::{CONTRACT_NAME_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING};
+use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::{ClarityName, ContractName};
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::StacksAddress;
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::hex_bytes;
+
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler,
+    StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState, TipRequest};
+
+/// How many entries are returned if the request does not set `limit`
+pub const DEFAULT_MAP_ENTRIES_LIMIT: u32 = 20;
+/// The most entries a single request can return
+pub const MAX_MAP_ENTRIES_LIMIT: u32 = 50;
+
+/// An entry of a data map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapEntryKeyValueResponse {
+    /// hex serialization of the key
+    pub key: String,
+    /// hex serialization of the value, as the `(some ...)` that `map-get?` returns
+    pub value: String,
+}
+
+/// A page of a data map's entries, in key order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapEntriesResponse {
+    pub limit: u32,
+    pub results: Vec<MapEntryKeyValueResponse>,
+    /// Pass this as `after` to get the next page.  Not set if this is the last page.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct RPCGetMapEntriesRequestHandler {
+    pub contract_identifier: Option<QualifiedContractIdentifier>,
+    pub map_name: Option<ClarityName>,
+    pub limit: u32,
+    pub after: Option<String>,
+}
+impl RPCGetMapEntriesRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            contract_identifier: None,
+            map_name: None,
+            limit: DEFAULT_MAP_ENTRIES_LIMIT,
+            after: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetMapEntriesRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(&format!(
+            "^/v2/map_entries/(?P<address>{})/(?P<contract>{})/(?P<map>{})$",
+            *STANDARD_PRINCIPAL_REGEX_STRING, *CONTRACT_NAME_REGEX_STRING, *CLARITY_NAME_REGEX
+        ))
+        .unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let contract_identifier = request::get_contract_address(captures, "address", "contract")?;
+        let map_name = request::get_clarity_name(captures, "map")?;
+
+        let contents = HttpRequestContents::new().query_string(query);
+        let limit = match contents.get_query_arg("limit") {
+            Some(value) => value
+                .parse::<u32>()
+                .map_err(|_e| Error::DecodeError("Failed to parse `limit` argument".into()))?,
+            None => DEFAULT_MAP_ENTRIES_LIMIT,
+        };
+        if limit == 0 || limit > MAX_MAP_ENTRIES_LIMIT {
+            return Err(Error::DecodeError(format!(
+                "`limit` must be between 1 and {}",
+                MAX_MAP_ENTRIES_LIMIT
+            )));
+        }
+
+        // keys are stored as lowercase hex, without the 0x prefix
+        let after = match contents.get_query_arg("after") {
+            Some(value) => {
+                let key_hex = value.strip_prefix("0x").unwrap_or(value).to_lowercase();
+                hex_bytes(&key_hex)
+                    .map_err(|_e| Error::DecodeError("Failed to parse `after` argument".into()))?;
+                Some(key_hex)
+            }
+            None => None,
+        };
+
+        self.contract_identifier = Some(contract_identifier);
+        self.map_name = Some(map_name);
+        self.limit = limit;
+        self.after = after;
+
+        Ok(contents)
+    }
+}
+
+/// Handle the HTTP request
+impl RPCRequestHandler for RPCGetMapEntriesRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.contract_identifier = None;
+        self.map_name = None;
+        self.limit = DEFAULT_MAP_ENTRIES_LIMIT;
+        self.after = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let contract_identifier = self
+            .contract_identifier
+            .take()
+            .ok_or(NetError::SendError("`contract_identifier` not set".into()))?;
+        let map_name = self
+            .map_name
+            .take()
+            .ok_or(NetError::SendError("`map_name` not set".into()))?;
+        let limit = self.limit;
+        let after = self.after.take();
+
+        let tip = match node.load_stacks_chain_tip(&preamble, &contents) {
+            Ok(tip) => tip,
+            Err(error_resp) => {
+                return error_resp.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let map_key_index =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                chainstate.is_map_key_index_enabled()
+            });
+        if !map_key_index {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpNotFound::new(
+                    "This node does not index data map keys (set `node.map_key_index = true` to enable it)".to_string(),
+                ),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        }
+
+        let entries_res =
+            node.with_node_state(|_network, sortdb, chainstate, _mempool, _rpc_args| {
+                chainstate.get_data_map_entries(
+                    &sortdb.index_conn(),
+                    &tip,
+                    &contract_identifier,
+                    &map_name,
+                    after.as_deref(),
+                    limit,
+                )
+            });
+
+        let entries = match entries_res {
+            Ok(Some(entries)) => entries,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("Chain tip '{}' not found", &tip)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Failed to query entries of {}.{}: {:?}",
+                        &contract_identifier, &map_name, &e
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        // a full page may be followed by more entries
+        let next = if entries.len() >= limit as usize {
+            entries.last().map(|(key_hex, _)| format!("0x{}", key_hex))
+        } else {
+            None
+        };
+        let results = entries
+            .into_iter()
+            .map(|(key_hex, value_hex)| MapEntryKeyValueResponse {
+                key: format!("0x{}", key_hex),
+                value: format!("0x{}", value_hex),
+            })
+            .collect();
+
+        let response = MapEntriesResponse {
+            limit,
+            results,
+            next,
+        };
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        preamble.set_stacks_tip(&tip);
+        let body = HttpResponseContents::try_from_json(&response)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetMapEntriesRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let entries: MapEntriesResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(entries)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for a page of a data map's entries
+    pub fn new_getmapentries(
+        host: PeerHost,
+        contract_addr: StacksAddress,
+        contract_name: ContractName,
+        map_name: ClarityName,
+        tip_req: TipRequest,
+        limit: u32,
+        after: Option<String>,
+    ) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new()
+            .for_tip(tip_req)
+            .query_arg("limit".into(), limit.to_string());
+        if let Some(after) = after {
+            contents = contents.query_arg("after".into(), after);
+        }
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!(
+                "/v2/map_entries/{}/{}/{}",
+                &contract_addr, &contract_name, &map_name
+            ),
+            contents,
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_map_entries_response(self) -> Result<MapEntriesResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let entries: MapEntriesResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(entries)
+    }
+}
@@ -30,9 +30,9 @@ use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{Error as ChainError, StacksBlockHeader, StacksMicroblock};
 use crate::net::api::getmicroblocks_indexed::StacksIndexedMicroblockStream;
 use crate::net::http::{
-    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
-    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
-    HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpError,
+    HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
 };
 use crate::net::httpcore::{
     request, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
@@ -137,6 +137,17 @@ impl RPCRequestHandler for RPCMicroblocksConfirmedRequestHandler {
                 .try_into_contents()
                 .map_err(NetError::from);
             }
+            Err(ChainError::BlockPruned) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpError::new(
+                        410,
+                        format!("Microblocks confirmed by {:?} were pruned\n", &block_id),
+                    ),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
             Err(e) => {
                 // nope -- error trying to check
                 let msg = format!("Failed to load block: {:?}\n", &e);
@@ -29,9 +29,9 @@ use {serde, serde_json};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{Error as ChainError, StacksBlockHeader, StacksMicroblock};
 use crate::net::http::{
-    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
-    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
-    HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpError,
+    HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
 };
 use crate::net::httpcore::{
     request, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
@@ -77,6 +77,9 @@ impl StacksIndexedMicroblockStream {
             tail_index_microblock_hash,
         )?
         .ok_or(ChainError::NoSuchBlockError)?;
+        if StacksChainState::is_microblock_pruned(&chainstate.db(), tail_index_microblock_hash)? {
+            return Err(ChainError::BlockPruned);
+        }
 
         let parent_index_block_hash = StacksBlockHeader::make_index_block_hash(
             &mblock_info.consensus_hash,
@@ -168,6 +171,17 @@ impl RPCRequestHandler for RPCMicroblocksIndexedRequestHandler {
                 .try_into_contents()
                 .map_err(NetError::from)
             }
+            Err(ChainError::BlockPruned) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpError::new(
+                        410,
+                        format!("Microblock {:?} was pruned\n", &tail_microblock_id),
+                    ),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
             Err(e) => {
                 // nope -- error trying to check
                 let msg = format!("Failed to load microblock: {:?}\n", &e);
@@ -27,7 +27,8 @@ use crate::net::http::{
     HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpVersion,
 };
 use crate::net::httpcore::{
-    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest, StacksHttpResponse,
+    HttpPreambleExtensions, RPCEndpointGroup, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+    StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::{Error as NetError, NeighborKey, StacksNodeState, MAX_NEIGHBORS_DATA_LEN};
@@ -201,6 +202,10 @@ impl RPCRequestHandler for RPCNeighborsRequestHandler {
     /// Reset internal state
     fn restart(&mut self) {}
 
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::Admin
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
@@ -0,0 +1,185 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::{PeerAddress, PeerHost};
+use stacks_common::util::get_epoch_time_secs;
+
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCEndpointGroup, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::p2p::PeerNetwork;
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Reputation of a peer that has misbehaved recently
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPeerReputation {
+    pub network_id: u32,
+    pub peer_version: u32,
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    /// Current score, out of a perfect `PEER_SCORE_MAX`
+    pub score: i64,
+    /// Whether the peer's score is low enough that we avoid relaying through it
+    pub deprioritized: bool,
+    /// When the peer's ban for a low score expires, if it is banned
+    pub banned_until: Option<u64>,
+    pub invalid_blocks: u64,
+    pub malformed_messages: u64,
+    pub stale_data: u64,
+    pub unsolicited_floods: u64,
+}
+
+/// Struct given back from a call to `/v2/neighbors/reputation`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPeerReputationInfo {
+    pub peers: Vec<RPCPeerReputation>,
+}
+
+impl RPCPeerReputationInfo {
+    /// Load the reputations of all recently-misbehaving peers from the peer network, lowest
+    /// score first
+    pub fn from_p2p(network: &PeerNetwork) -> RPCPeerReputationInfo {
+        let now = get_epoch_time_secs();
+        let mut peers: Vec<_> = network
+            .reputation
+            .iter()
+            .map(|(nk, peer_score)| RPCPeerReputation {
+                network_id: nk.network_id,
+                peer_version: nk.peer_version,
+                addrbytes: nk.addrbytes.clone(),
+                port: nk.port,
+                score: peer_score.get_score(now),
+                deprioritized: network.is_peer_deprioritized(nk),
+                banned_until: peer_score.banned_until.filter(|until| *until > now),
+                invalid_blocks: peer_score.invalid_blocks,
+                malformed_messages: peer_score.malformed_messages,
+                stale_data: peer_score.stale_data,
+                unsolicited_floods: peer_score.unsolicited_floods,
+            })
+            .collect();
+        peers.sort_by(|a, b| {
+            a.score
+                .cmp(&b.score)
+                .then_with(|| (&a.addrbytes, a.port).cmp(&(&b.addrbytes, b.port)))
+        });
+        RPCPeerReputationInfo { peers }
+    }
+}
+
+#[derive(Clone)]
+pub struct RPCGetPeerReputationRequestHandler {}
+impl RPCGetPeerReputationRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetPeerReputationRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/neighbors/reputation$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetPeerReputationRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::Admin
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let reputation_info =
+            node.with_node_state(|network, _sortdb, _chainstate, _mempool, _rpc_args| {
+                RPCPeerReputationInfo::from_p2p(network)
+            });
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&reputation_info)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetPeerReputationRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let reputation_info: RPCPeerReputationInfo = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(reputation_info)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the reputations of misbehaving peers
+    pub fn new_get_peer_reputation(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v2/neighbors/reputation".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_peer_reputation(self) -> Result<RPCPeerReputationInfo, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: RPCPeerReputationInfo = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
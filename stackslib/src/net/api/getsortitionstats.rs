@@ -0,0 +1,320 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::operations::LeaderBlockCommitOp;
+use crate::chainstate::burn::BlockSnapshot;
+use crate::net::http::{
+    parse_json, Error, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
+    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+use crate::util_lib::db::Error as DBError;
+
+/// How many burnchain blocks the statistics cover if the request does not set `blocks`
+pub const DEFAULT_SORTITION_STATS_BLOCKS: u64 = 144;
+/// The most burnchain blocks a single request can cover
+pub const MAX_SORTITION_STATS_BLOCKS: u64 = 2100;
+
+/// A miner's block-commits within the requested window of burnchain blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MinerSortitionStats {
+    /// The miner's burnchain address, i.e. the apparent sender of its block-commits
+    pub address: String,
+    /// How many of the miner's block-commits were accepted
+    pub commits: u64,
+    /// How many sortitions the miner won
+    pub wins: u64,
+    /// How much the miner committed, in satoshis
+    pub total_burn: u64,
+    /// The sum of the miner's share of the total commitment in each block.
+    /// Sortition weighs commits by their median over recent blocks, so this is an estimate.
+    pub expected_wins: f64,
+    /// `expected_wins` per burnchain block in the window
+    pub expected_win_probability: f64,
+}
+
+impl MinerSortitionStats {
+    fn new(address: String) -> Self {
+        Self {
+            address,
+            commits: 0,
+            wins: 0,
+            total_burn: 0,
+            expected_wins: 0.0,
+            expected_win_probability: 0.0,
+        }
+    }
+
+    /// Count a block-commit in a block where `block_burn` satoshis were committed in total
+    fn record(&mut self, commit: &LeaderBlockCommitOp, block_burn: u64, won: bool) {
+        self.commits += 1;
+        self.total_burn = self.total_burn.saturating_add(commit.burn_fee);
+        if won {
+            self.wins += 1;
+        }
+        if block_burn > 0 {
+            self.expected_wins += commit.burn_fee as f64 / block_burn as f64;
+        }
+    }
+
+    fn finish(&mut self, num_blocks: u64) {
+        if num_blocks > 0 {
+            self.expected_win_probability = self.expected_wins / num_blocks as f64;
+        }
+    }
+}
+
+/// Sortition statistics over the most recent burnchain blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SortitionStatsResponse {
+    /// The height of the canonical burnchain tip
+    pub tip_height: u64,
+    /// How many burnchain blocks the statistics cover, ending at the tip
+    pub num_blocks: u64,
+    /// How many of those blocks selected a winning block-commit
+    pub num_sortitions: u64,
+    /// The total committed in those blocks, in satoshis
+    pub total_burn: u64,
+    /// Every miner that committed in those blocks, highest total commitment first
+    pub miners: Vec<MinerSortitionStats>,
+    /// This node's miner, if it is one. Its statistics cover all of its burnchain addresses.
+    pub local_miner: Option<MinerSortitionStats>,
+}
+
+impl SortitionStatsResponse {
+    /// Compute the statistics of the given sortitions and their accepted block-commits.
+    /// `local_miner_addrs` are the burnchain addresses this node's miner commits from.
+    pub fn from_sortitions(
+        tip_height: u64,
+        sortitions: &[(BlockSnapshot, Vec<LeaderBlockCommitOp>)],
+        local_miner_addrs: &[String],
+    ) -> Self {
+        let num_blocks = sortitions.len() as u64;
+        let mut num_sortitions = 0;
+        let mut total_burn = 0u64;
+        let mut miners: HashMap<String, MinerSortitionStats> = HashMap::new();
+        let mut local_miner = local_miner_addrs
+            .first()
+            .map(|addr| MinerSortitionStats::new(addr.clone()));
+
+        for (snapshot, commits) in sortitions.iter() {
+            if snapshot.sortition {
+                num_sortitions += 1;
+            }
+            let block_burn = commits
+                .iter()
+                .fold(0u64, |sum, commit| sum.saturating_add(commit.burn_fee));
+            total_burn = total_burn.saturating_add(block_burn);
+
+            for commit in commits.iter() {
+                let address = commit.apparent_sender.to_string();
+                let won = snapshot.sortition && commit.txid == snapshot.winning_block_txid;
+                if let Some(local_miner) = local_miner.as_mut() {
+                    if local_miner_addrs.contains(&address) {
+                        local_miner.record(commit, block_burn, won);
+                    }
+                }
+                miners
+                    .entry(address.clone())
+                    .or_insert_with(|| MinerSortitionStats::new(address))
+                    .record(commit, block_burn, won);
+            }
+        }
+
+        let mut miners: Vec<_> = miners.into_values().collect();
+        for miner in miners.iter_mut() {
+            miner.finish(num_blocks);
+        }
+        miners.sort_by(|a, b| {
+            b.total_burn
+                .cmp(&a.total_burn)
+                .then_with(|| a.address.cmp(&b.address))
+        });
+        if let Some(local_miner) = local_miner.as_mut() {
+            local_miner.finish(num_blocks);
+        }
+
+        Self {
+            tip_height,
+            num_blocks,
+            num_sortitions,
+            total_burn,
+            miners,
+            local_miner,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RPCGetSortitionStatsRequestHandler {
+    pub num_blocks: u64,
+}
+impl RPCGetSortitionStatsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            num_blocks: DEFAULT_SORTITION_STATS_BLOCKS,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetSortitionStatsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/sortitions/stats$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let contents = HttpRequestContents::new().query_string(query);
+        let num_blocks = match contents.get_query_arg("blocks") {
+            Some(value) => value
+                .parse::<u64>()
+                .map_err(|_e| Error::DecodeError("Failed to parse `blocks` argument".into()))?,
+            None => DEFAULT_SORTITION_STATS_BLOCKS,
+        };
+        if num_blocks == 0 || num_blocks > MAX_SORTITION_STATS_BLOCKS {
+            return Err(Error::DecodeError(format!(
+                "`blocks` must be between 1 and {}",
+                MAX_SORTITION_STATS_BLOCKS
+            )));
+        }
+
+        self.num_blocks = num_blocks;
+        Ok(contents)
+    }
+}
+
+impl RPCRequestHandler for RPCGetSortitionStatsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.num_blocks = DEFAULT_SORTITION_STATS_BLOCKS;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let num_blocks = self.num_blocks;
+        let stats_res =
+            node.with_node_state(|_network, sortdb, _chainstate, _mempool, rpc_args| {
+                let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+                let mut sortitions = vec![];
+                let mut snapshot = tip.clone();
+                while (sortitions.len() as u64) < num_blocks
+                    && snapshot.block_height > sortdb.first_block_height
+                {
+                    let commits = SortitionDB::get_block_commits_by_block(
+                        sortdb.conn(),
+                        &snapshot.sortition_id,
+                    )?;
+                    let parent_sortition_id = snapshot.parent_sortition_id.clone();
+                    sortitions.push((snapshot, commits));
+                    snapshot =
+                        match SortitionDB::get_block_snapshot(sortdb.conn(), &parent_sortition_id)?
+                        {
+                            Some(parent) => parent,
+                            None => break,
+                        };
+                }
+                Ok::<_, DBError>(SortitionStatsResponse::from_sortitions(
+                    tip.block_height,
+                    &sortitions,
+                    &rpc_args.miner_addresses,
+                ))
+            });
+
+        let stats = match stats_res {
+            Ok(stats) => stats,
+            Err(e) => {
+                let msg = format!("Failed to load sortition statistics: {:?}\n", &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&stats)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetSortitionStatsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let stats: SortitionStatsResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(stats)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for sortition statistics over the last `num_blocks` burnchain blocks,
+    /// or the default number of blocks if `None`
+    pub fn new_get_sortition_stats(host: PeerHost, num_blocks: Option<u64>) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new();
+        if let Some(num_blocks) = num_blocks {
+            contents = contents.query_arg("blocks".into(), num_blocks.to_string());
+        }
+        StacksHttpRequest::new_for_peer(host, "GET".into(), "/v2/sortitions/stats".into(), contents)
+            .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_sortition_stats(self) -> Result<SortitionStatsResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: SortitionStatsResponse = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
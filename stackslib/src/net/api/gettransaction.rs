@@ -0,0 +1,253 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::{Read, Write};
+
+use clarity::vm::costs::ExecutionCost;
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, StacksBlockId,
+};
+use stacks_common::types::net::PeerHost;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler,
+    StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState, TipRequest};
+
+/// A transaction processed in a block on the canonical fork, with its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionResponse {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub block_height: u64,
+    pub consensus_hash: ConsensusHash,
+    pub burn_block_hash: BurnchainHeaderHash,
+    pub burn_block_height: u32,
+    /// The consensus-serialized transaction, hex-encoded
+    pub tx: String,
+    /// The fields below are `null` for transactions logged by an older node
+    pub tx_index: Option<u32>,
+    /// Set if the transaction was mined in a microblock confirmed by this block
+    pub microblock_hash: Option<BlockHeaderHash>,
+    /// The consensus-serialized result, hex-encoded with a `0x` prefix
+    pub result: Option<String>,
+    /// The result, formatted as a Clarity value
+    pub result_repr: String,
+    pub post_condition_aborted: Option<bool>,
+    pub vm_error: Option<String>,
+    pub execution_cost: Option<ExecutionCost>,
+    /// The transaction's events, in the format sent to event observers
+    pub events: Option<serde_json::Value>,
+}
+
+#[derive(Clone)]
+pub struct RPCGetTransactionRequestHandler {
+    pub txid: Option<Txid>,
+}
+impl RPCGetTransactionRequestHandler {
+    pub fn new() -> Self {
+        Self { txid: None }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetTransactionRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/transactions/(?P<txid>[0-9a-f]{64})$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body for GetTransaction".to_string(),
+            ));
+        }
+
+        let txid = request::get_txid(captures, "txid")?;
+        self.txid = Some(txid);
+
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetTransactionRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.txid = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let tip = match node.load_stacks_chain_tip(&preamble, &contents) {
+            Ok(tip) => tip,
+            Err(error_resp) => {
+                return error_resp.try_into_contents().map_err(NetError::from);
+            }
+        };
+        let txid = self
+            .txid
+            .take()
+            .ok_or(NetError::SendError("`txid` no set".into()))?;
+
+        let txindex = node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+            chainstate.txindex
+        });
+        let txinfo_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                let entries = StacksChainState::get_logged_transactions(chainstate.db(), &txid)?;
+
+                // the transaction may have been processed in several forks
+                let index_conn = chainstate.index_conn()?;
+                let mut entry_opt = None;
+                for entry in entries.into_iter() {
+                    if index_conn
+                        .get_ancestor_block_height(&entry.index_block_hash, &tip)?
+                        .is_some()
+                    {
+                        entry_opt = Some(entry);
+                        break;
+                    }
+                }
+                let entry = entry_opt.ok_or(NetError::NotFoundError)?;
+
+                let header_info =
+                    StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                        chainstate.db(),
+                        &entry.index_block_hash,
+                    )?
+                    .ok_or(NetError::NotFoundError)?;
+
+                Ok(TransactionResponse {
+                    txid: entry.txid,
+                    index_block_hash: entry.index_block_hash,
+                    block_hash: header_info.anchored_header.block_hash(),
+                    block_height: header_info.stacks_block_height,
+                    consensus_hash: header_info.consensus_hash,
+                    burn_block_hash: header_info.burn_header_hash,
+                    burn_block_height: header_info.burn_header_height,
+                    tx: entry.tx_hex,
+                    tx_index: entry.tx_index,
+                    microblock_hash: entry.microblock_hash,
+                    result: entry
+                        .result_hex
+                        .map(|result_hex| format!("0x{}", &result_hex)),
+                    result_repr: entry.result,
+                    post_condition_aborted: entry.post_condition_aborted,
+                    vm_error: entry.vm_error,
+                    execution_cost: entry.execution_cost,
+                    events: entry.events,
+                })
+            });
+
+        let txinfo = match txinfo_res {
+            Ok(txinfo) => txinfo,
+            Err(NetError::NotFoundError) => {
+                let msg = if txindex {
+                    format!("Transaction {} not found in the canonical fork", &txid)
+                } else {
+                    format!(
+                        "Transaction {} not found: this node does not index transactions (set `node.txindex = true` to enable it)",
+                        &txid
+                    )
+                };
+                return StacksHttpResponse::new_error(&preamble, &HttpNotFound::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+            Err(e) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Failed to query transaction {}: {:?}",
+                        &txid, &e
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&txinfo)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetTransactionRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let txinfo: TransactionResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(txinfo)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new get-tx request
+    pub fn new_gettransaction(
+        host: PeerHost,
+        txid: Txid,
+        tip_req: TipRequest,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v2/transactions/{}", &txid),
+            HttpRequestContents::new().for_tip(tip_req),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_gettransaction(self) -> Result<TransactionResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let txinfo: TransactionResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(txinfo)
+    }
+}
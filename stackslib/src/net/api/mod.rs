@@ -35,33 +35,43 @@ use crate::stacks_common::codec::StacksMessageCodec;
 
 pub mod callreadonly;
 pub mod getaccount;
+pub mod getaccounttransactions;
 pub mod getattachment;
 pub mod getattachmentsinv;
 pub mod getblock;
+pub mod getburnops;
 pub mod getconstantval;
 pub mod getcontractabi;
 pub mod getcontractsrc;
 pub mod getdatavar;
 pub mod getheaders;
+pub mod gethealth;
 pub mod getinfo;
 pub mod getistraitimplemented;
+pub mod getmapentries;
 pub mod getmapentry;
 pub mod getmicroblocks_confirmed;
 pub mod getmicroblocks_indexed;
 pub mod getmicroblocks_unconfirmed;
 pub mod getneighbors;
+pub mod getpeerreputation;
 pub mod getpoxinfo;
+pub mod getsortitionstats;
 pub mod getstackerdbchunk;
 pub mod getstackerdbmetadata;
+pub mod getstackerdbslots;
 pub mod getstxtransfercost;
+pub mod gettransaction;
 pub mod gettransaction_unconfirmed;
 pub mod liststackerdbreplicas;
 pub mod postblock;
 pub mod postfeerate;
+pub mod postfeerate_v2;
 pub mod postmempoolquery;
 pub mod postmicroblock;
 pub mod poststackerdbchunk;
 pub mod posttransaction;
+pub mod postvaluehash;
 
 #[cfg(test)]
 mod tests;
@@ -73,20 +83,27 @@ impl StacksHttp {
         self.register_rpc_endpoint(callreadonly::RPCCallReadOnlyRequestHandler::new(
             self.maximum_call_argument_size,
             self.read_only_call_limit.clone(),
+            self.read_only_call_max_tip_depth,
         ));
         self.register_rpc_endpoint(getaccount::RPCGetAccountRequestHandler::new());
+        self.register_rpc_endpoint(
+            getaccounttransactions::RPCGetAccountTransactionsRequestHandler::new(),
+        );
         self.register_rpc_endpoint(getattachment::RPCGetAttachmentRequestHandler::new());
         self.register_rpc_endpoint(getattachmentsinv::RPCGetAttachmentsInvRequestHandler::new());
         self.register_rpc_endpoint(getblock::RPCBlocksRequestHandler::new());
+        self.register_rpc_endpoint(getburnops::RPCGetBurnOpsRequestHandler::new());
         self.register_rpc_endpoint(getconstantval::RPCGetConstantValRequestHandler::new());
         self.register_rpc_endpoint(getcontractabi::RPCGetContractAbiRequestHandler::new());
         self.register_rpc_endpoint(getcontractsrc::RPCGetContractSrcRequestHandler::new());
         self.register_rpc_endpoint(getdatavar::RPCGetDataVarRequestHandler::new());
         self.register_rpc_endpoint(getheaders::RPCHeadersRequestHandler::new());
+        self.register_rpc_endpoint(gethealth::RPCGetHealthRequestHandler::new());
         self.register_rpc_endpoint(getinfo::RPCPeerInfoRequestHandler::new());
         self.register_rpc_endpoint(
             getistraitimplemented::RPCGetIsTraitImplementedRequestHandler::new(),
         );
+        self.register_rpc_endpoint(getmapentries::RPCGetMapEntriesRequestHandler::new());
         self.register_rpc_endpoint(getmapentry::RPCGetMapEntryRequestHandler::new());
         self.register_rpc_endpoint(
             getmicroblocks_confirmed::RPCMicroblocksConfirmedRequestHandler::new(),
@@ -98,12 +115,16 @@ impl StacksHttp {
             getmicroblocks_unconfirmed::RPCMicroblocksUnconfirmedRequestHandler::new(),
         );
         self.register_rpc_endpoint(getneighbors::RPCNeighborsRequestHandler::new());
+        self.register_rpc_endpoint(getpeerreputation::RPCGetPeerReputationRequestHandler::new());
         self.register_rpc_endpoint(getstxtransfercost::RPCGetStxTransferCostRequestHandler::new());
         self.register_rpc_endpoint(getstackerdbchunk::RPCGetStackerDBChunkRequestHandler::new());
         self.register_rpc_endpoint(getpoxinfo::RPCPoxInfoRequestHandler::new());
+        self.register_rpc_endpoint(getsortitionstats::RPCGetSortitionStatsRequestHandler::new());
         self.register_rpc_endpoint(
             getstackerdbmetadata::RPCGetStackerDBMetadataRequestHandler::new(),
         );
+        self.register_rpc_endpoint(getstackerdbslots::RPCGetStackerDBSlotsRequestHandler::new());
+        self.register_rpc_endpoint(gettransaction::RPCGetTransactionRequestHandler::new());
         self.register_rpc_endpoint(
             gettransaction_unconfirmed::RPCGetTransactionUnconfirmedRequestHandler::new(),
         );
@@ -112,10 +133,14 @@ impl StacksHttp {
         );
         self.register_rpc_endpoint(postblock::RPCPostBlockRequestHandler::new());
         self.register_rpc_endpoint(postfeerate::RPCPostFeeRateRequestHandler::new());
+        self.register_rpc_endpoint(postfeerate_v2::RPCPostFeeRateV2RequestHandler::new());
         self.register_rpc_endpoint(postmempoolquery::RPCMempoolQueryRequestHandler::new());
         self.register_rpc_endpoint(postmicroblock::RPCPostMicroblockRequestHandler::new());
         self.register_rpc_endpoint(poststackerdbchunk::RPCPostStackerDBChunkRequestHandler::new());
         self.register_rpc_endpoint(posttransaction::RPCPostTransactionRequestHandler::new());
+        self.register_rpc_endpoint(postvaluehash::RPCPostValueHashRequestHandler::new(
+            self.maximum_call_argument_size,
+        ));
     }
 }
 
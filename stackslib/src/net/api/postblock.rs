@@ -43,8 +43,8 @@ use crate::net::http::{
     HttpResponsePayload, HttpResponsePreamble, HttpServerError,
 };
 use crate::net::httpcore::{
-    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler,
-    StacksHttpRequest, StacksHttpResponse,
+    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCEndpointGroup,
+    RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::Relayer;
@@ -139,6 +139,10 @@ impl RPCRequestHandler for RPCPostBlockRequestHandler {
         self.block = None;
     }
 
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::BlockProposal
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
@@ -87,6 +87,44 @@ pub struct RPCFeeEstimateResponse {
     pub cost_scalar_change_by_byte: f64,
 }
 
+/// Decode and validate the JSON body of a fee rate estimate request.
+/// Returns the transaction payload and the estimated transaction length.
+pub fn parse_fee_rate_request_body(
+    preamble: &HttpRequestPreamble,
+    body: &[u8],
+) -> Result<(TransactionPayload, u64), Error> {
+    let content_len = preamble.get_content_length();
+    if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+        return Err(Error::DecodeError(format!(
+            "Invalid Http request: invalid body length for FeeRateEstimate ({})",
+            content_len
+        )));
+    }
+
+    if preamble.content_type != Some(HttpContentType::JSON) {
+        return Err(Error::DecodeError(
+            "Invalid content-type: expected application/json".to_string(),
+        ));
+    }
+
+    let body: FeeRateEstimateRequestBody = serde_json::from_slice(body)
+        .map_err(|e| Error::DecodeError(format!("Failed to parse JSON body: {}", e)))?;
+
+    let payload_hex = if body.transaction_payload.starts_with("0x") {
+        &body.transaction_payload[2..]
+    } else {
+        &body.transaction_payload
+    };
+
+    let payload_data = hex_bytes(payload_hex).map_err(|_e| {
+        Error::DecodeError("Bad hex string supplied for transaction payload".into())
+    })?;
+
+    let tx = TransactionPayload::consensus_deserialize(&mut payload_data.as_slice())?;
+    let estimated_len = std::cmp::max(body.estimated_len.unwrap_or(0), payload_data.len() as u64);
+    Ok((tx, estimated_len))
+}
+
 #[derive(Clone)]
 pub struct RPCPostFeeRateRequestHandler {
     pub estimated_len: Option<u64>,
@@ -120,36 +158,7 @@ impl HttpRequest for RPCPostFeeRateRequestHandler {
         query: Option<&str>,
         body: &[u8],
     ) -> Result<HttpRequestContents, Error> {
-        let content_len = preamble.get_content_length();
-        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
-            return Err(Error::DecodeError(format!(
-                "Invalid Http request: invalid body length for FeeRateEstimate ({})",
-                content_len
-            )));
-        }
-
-        if preamble.content_type != Some(HttpContentType::JSON) {
-            return Err(Error::DecodeError(
-                "Invalid content-type: expected application/json".to_string(),
-            ));
-        }
-
-        let body: FeeRateEstimateRequestBody = serde_json::from_slice(body)
-            .map_err(|e| Error::DecodeError(format!("Failed to parse JSON body: {}", e)))?;
-
-        let payload_hex = if body.transaction_payload.starts_with("0x") {
-            &body.transaction_payload[2..]
-        } else {
-            &body.transaction_payload
-        };
-
-        let payload_data = hex_bytes(payload_hex).map_err(|_e| {
-            Error::DecodeError("Bad hex string supplied for transaction payload".into())
-        })?;
-
-        let tx = TransactionPayload::consensus_deserialize(&mut payload_data.as_slice())?;
-        let estimated_len =
-            std::cmp::max(body.estimated_len.unwrap_or(0), payload_data.len() as u64);
+        let (tx, estimated_len) = parse_fee_rate_request_body(preamble, body)?;
 
         self.transaction_payload = Some(tx);
         self.estimated_len = Some(estimated_len);
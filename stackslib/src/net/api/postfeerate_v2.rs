@@ -0,0 +1,246 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::costs::ExecutionCost;
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::stacks::db::blocks::MINIMUM_TX_FEE_RATE_PER_BYTE;
+use crate::chainstate::stacks::TransactionPayload;
+use crate::cost_estimates::FeeRatePercentiles;
+use crate::net::api::postfeerate::{
+    parse_fee_rate_request_body, FeeRateEstimateRequestBody, RPCFeeEstimate,
+};
+use crate::net::http::{
+    parse_json, Error, HttpBadRequest, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Fee estimates at each fee rate percentile paid by recently-included transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeePercentileEstimates {
+    pub p25: RPCFeeEstimate,
+    pub p50: RPCFeeEstimate,
+    pub p75: RPCFeeEstimate,
+    pub p95: RPCFeeEstimate,
+}
+
+impl RPCFeePercentileEstimates {
+    pub fn estimate_fees(scalar: u64, fee_rates: FeeRatePercentiles) -> RPCFeePercentileEstimates {
+        let estimate = |fee_rate: f64| RPCFeeEstimate {
+            fee: (fee_rate * (scalar as f64)) as u64,
+            fee_rate,
+        };
+        RPCFeePercentileEstimates {
+            p25: estimate(fee_rates.p25),
+            p50: estimate(fee_rates.p50),
+            p75: estimate(fee_rates.p75),
+            p95: estimate(fee_rates.p95),
+        }
+    }
+
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut RPCFeeEstimate> {
+        vec![&mut self.p25, &mut self.p50, &mut self.p75, &mut self.p95].into_iter()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeEstimateV2Response {
+    /// The payload type whose fee rates were used, as given by `TransactionPayload::name()`
+    pub payload_type: String,
+    pub estimated_cost: ExecutionCost,
+    pub estimated_cost_scalar: u64,
+    pub estimations: RPCFeePercentileEstimates,
+    pub cost_scalar_change_by_byte: f64,
+}
+
+#[derive(Clone)]
+pub struct RPCPostFeeRateV2RequestHandler {
+    pub estimated_len: Option<u64>,
+    pub transaction_payload: Option<TransactionPayload>,
+}
+impl RPCPostFeeRateV2RequestHandler {
+    pub fn new() -> Self {
+        Self {
+            estimated_len: None,
+            transaction_payload: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCPostFeeRateV2RequestHandler {
+    fn verb(&self) -> &'static str {
+        "POST"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/fees/transaction-v2$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        let (tx, estimated_len) = parse_fee_rate_request_body(preamble, body)?;
+        self.transaction_payload = Some(tx);
+        self.estimated_len = Some(estimated_len);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCPostFeeRateV2RequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.estimated_len = None;
+        self.transaction_payload = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let estimated_len = self
+            .estimated_len
+            .take()
+            .ok_or(NetError::SendError("`estimated_len` not set".into()))?;
+        let tx = self
+            .transaction_payload
+            .take()
+            .ok_or(NetError::SendError("`transaction_payload` not set".into()))?;
+
+        let data_resp =
+            node.with_node_state(|_network, sortdb, _chainstate, _mempool, rpc_args| {
+                let tip = self.get_canonical_burn_chain_tip(&preamble, sortdb)?;
+                let stacks_epoch = self.get_stacks_epoch(&preamble, sortdb, tip.block_height)?;
+
+                if let Some((cost_estimator, fee_estimator, metric)) = rpc_args.get_estimators_ref()
+                {
+                    let estimated_cost = cost_estimator
+                        .estimate_cost(&tx, &stacks_epoch.epoch_id)
+                        .map_err(|e| {
+                            StacksHttpResponse::new_error(
+                                &preamble,
+                                &HttpBadRequest::new_json(e.into_json()),
+                            )
+                        })?;
+
+                    let scalar_cost = metric.from_cost_and_len(
+                        &estimated_cost,
+                        &stacks_epoch.block_limit,
+                        estimated_len,
+                    );
+                    let fee_rates = fee_estimator.get_percentile_estimates(&tx).map_err(|e| {
+                        StacksHttpResponse::new_error(
+                            &preamble,
+                            &HttpBadRequest::new_json(e.into_json()),
+                        )
+                    })?;
+
+                    let mut estimations =
+                        RPCFeePercentileEstimates::estimate_fees(scalar_cost, fee_rates);
+
+                    let minimum_fee = estimated_len * MINIMUM_TX_FEE_RATE_PER_BYTE;
+
+                    for estimate in estimations.iter_mut() {
+                        if estimate.fee < minimum_fee {
+                            estimate.fee = minimum_fee;
+                        }
+                    }
+
+                    Ok(RPCFeeEstimateV2Response {
+                        payload_type: tx.name().to_string(),
+                        estimated_cost,
+                        estimations,
+                        estimated_cost_scalar: scalar_cost,
+                        cost_scalar_change_by_byte: metric.change_per_byte(),
+                    })
+                } else {
+                    debug!("Fee and cost estimation not configured on this stacks node");
+                    Err(StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpBadRequest::new(
+                            "Fee estimation not supported on this node".to_string(),
+                        ),
+                    ))
+                }
+            });
+
+        let data_resp = match data_resp {
+            Ok(data) => data,
+            Err(response) => {
+                return response.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&data_resp)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCPostFeeRateV2RequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let fee: RPCFeeEstimateV2Response = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(fee)?)
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_fee_estimate_v2(self) -> Result<RPCFeeEstimateV2Response, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let fee: RPCFeeEstimateV2Response = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(fee)
+    }
+}
+
+impl StacksHttpRequest {
+    pub fn new_post_fee_rate_v2(
+        host: PeerHost,
+        fee_request: FeeRateEstimateRequestBody,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "POST".into(),
+            "/v2/fees/transaction-v2".into(),
+            HttpRequestContents::new().payload_json(
+                serde_json::to_value(fee_request)
+                    .expect("FATAL: failed to encode fee rate request to JSON"),
+            ),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
@@ -43,8 +43,8 @@ use crate::net::http::{
     HttpResponsePayload, HttpResponsePreamble, HttpServerError,
 };
 use crate::net::httpcore::{
-    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest,
-    StacksHttpResponse,
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCEndpointGroup, RPCRequestHandler,
+    StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::Relayer;
@@ -127,6 +127,10 @@ impl RPCRequestHandler for RPCPostMicroblockRequestHandler {
         self.microblock = None;
     }
 
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::BlockProposal
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
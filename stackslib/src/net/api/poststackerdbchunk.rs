@@ -45,8 +45,8 @@ use crate::net::http::{
     HttpResponsePayload, HttpResponsePreamble, HttpServerError,
 };
 use crate::net::httpcore::{
-    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
-    StacksHttpRequest, StacksHttpResponse,
+    request, HttpPreambleExtensions, HttpRequestContentsExtensions, RPCEndpointGroup,
+    RPCRequestHandler, StacksHttp, StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::{
     Error as NetError, StackerDBPushChunkData, StacksMessageType, StacksNodeState, TipRequest,
@@ -152,6 +152,10 @@ impl RPCRequestHandler for RPCPostStackerDBChunkRequestHandler {
         self.chunk = None;
     }
 
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::BlockProposal
+    }
+
     /// Make the response.
     fn try_handle_request(
         &mut self,
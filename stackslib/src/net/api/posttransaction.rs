@@ -30,7 +30,7 @@ use stacks_common::util::retry::BoundReader;
 use crate::burnchains::affirmation::AffirmationMap;
 use crate::burnchains::Txid;
 use crate::chainstate::burn::db::sortdb::SortitionDB;
-use crate::chainstate::stacks::db::blocks::MINIMUM_TX_FEE_RATE_PER_BYTE;
+use crate::chainstate::stacks::db::blocks::{MemPoolRejection, MINIMUM_TX_FEE_RATE_PER_BYTE};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{StacksTransaction, TransactionPayload};
 use crate::core::mempool::MemPoolDB;
@@ -41,7 +41,8 @@ use crate::net::http::{
     HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
 };
 use crate::net::httpcore::{
-    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+    HttpPreambleExtensions, RPCEndpointGroup, RPCRequestHandler, StacksHttpRequest,
+    StacksHttpResponse,
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::Relayer;
@@ -167,7 +168,18 @@ impl HttpRequest for RPCPostTransactionRequestHandler {
             }
         }
 
-        Ok(HttpRequestContents::new().query_string(query))
+        let contents = HttpRequestContents::new().query_string(query);
+        match contents.get_query_arg("mode").map(|mode| mode.as_str()) {
+            None | Some("check") => {}
+            Some(mode) => {
+                return Err(Error::DecodeError(format!(
+                    "Invalid Http request: unsupported PostTransaction mode `{}`",
+                    mode
+                )));
+            }
+        }
+
+        Ok(contents)
     }
 }
 
@@ -178,13 +190,19 @@ impl RPCRequestHandler for RPCPostTransactionRequestHandler {
         self.attachment = None;
     }
 
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::TxSubmission
+    }
+
     /// Make the response
     fn try_handle_request(
         &mut self,
         preamble: HttpRequestPreamble,
-        _contents: HttpRequestContents,
+        contents: HttpRequestContents,
         node: &mut StacksNodeState,
     ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        // in check mode, the transaction is validated but neither stored nor relayed
+        let check_only = contents.get_query_arg("mode").map(|mode| mode.as_str()) == Some("check");
         let tx = self
             .tx
             .take()
@@ -205,26 +223,50 @@ impl RPCRequestHandler for RPCPostTransactionRequestHandler {
             let stacks_epoch = self.get_stacks_epoch(&preamble, sortdb, burn_tip.block_height)?;
 
             // check for defects which can be determined statically
-            if Relayer::do_static_problematic_checks()
-                && !Relayer::static_check_problematic_relayed_tx(
+            if Relayer::do_static_problematic_checks() {
+                if let Err(e) = Relayer::static_check_problematic_relayed_tx(
                     chainstate.mainnet,
                     stacks_epoch.epoch_id,
                     &tx,
                     network.ast_rules,
-                )
-                .is_ok()
-            {
-                // we statically check the tx for known problems, and it had some.  Reject.
-                debug!(
-                    "Transaction {} is problematic in rules {:?}; will not store or relay",
-                    &tx.txid(),
-                    network.ast_rules
-                );
-                return Ok(false);
+                ) {
+                    // we statically check the tx for known problems, and it had some.  Reject.
+                    debug!(
+                        "Transaction {} is problematic in rules {:?}; will not store or relay",
+                        &tx.txid(),
+                        network.ast_rules
+                    );
+                    return Err(StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpBadRequest::new_json(
+                            MemPoolRejection::Problematic(e.to_string()).into_json(&txid),
+                        ),
+                    ));
+                }
             }
 
             let stacks_tip = self.get_stacks_chain_tip(&preamble, sortdb, chainstate)?;
 
+            if check_only {
+                mempool
+                    .check(
+                        chainstate,
+                        sortdb,
+                        &stacks_tip.consensus_hash,
+                        &stacks_tip.anchored_block_hash,
+                        &tx,
+                        &stacks_epoch.block_limit,
+                        &stacks_epoch.epoch_id,
+                    )
+                    .map_err(|e| {
+                        StacksHttpResponse::new_error(
+                            &preamble,
+                            &HttpBadRequest::new_json(e.into_json(&txid)),
+                        )
+                    })?;
+                return Ok(false);
+            }
+
             // accept to mempool
             if let Err(e) = mempool.submit(
                 chainstate,
@@ -311,6 +353,19 @@ impl StacksHttpRequest {
         .expect("FATAL: failed to construct request from infallible data")
     }
 
+    /// Make a new post-transaction request which only validates the transaction
+    pub fn new_check_transaction(host: PeerHost, tx: StacksTransaction) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "POST".into(),
+            "/v2/transactions".to_string(),
+            HttpRequestContents::new()
+                .query_arg("mode".into(), "check".into())
+                .payload_stacks(&tx),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+
     /// Make a new post-transaction request with an attachment
     pub fn new_post_transaction_with_attachment(
         host: PeerHost,
@@ -0,0 +1,168 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::Value;
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+use crate::net::http::{
+    parse_json, Error, HttpContentType, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
+};
+use crate::net::httpcore::{RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ValueHashRequestBody {
+    /// Hex-encoded consensus serialization of a Clarity value
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValueHashResponse {
+    /// SHA512/256 hash of the value's consensus serialization
+    pub hash: Sha512Trunc256Sum,
+}
+
+#[derive(Clone)]
+pub struct RPCPostValueHashRequestHandler {
+    maximum_call_argument_size: u32,
+    pub value: Option<Value>,
+}
+
+impl RPCPostValueHashRequestHandler {
+    pub fn new(maximum_call_argument_size: u32) -> Self {
+        Self {
+            maximum_call_argument_size,
+            value: None,
+        }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCPostValueHashRequestHandler {
+    fn verb(&self) -> &'static str {
+        "POST"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/clarity/value/hash$"#).unwrap()
+    }
+
+    /// Try to decode this request.
+    /// The body must carry a valid Clarity value.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < self.maximum_call_argument_size) {
+            return Err(Error::DecodeError(format!(
+                "Invalid Http request: invalid body length for ValueHash ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(Error::DecodeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let body: ValueHashRequestBody = serde_json::from_slice(body)
+            .map_err(|_e| Error::DecodeError("Failed to parse JSON body".into()))?;
+
+        let value = Value::try_deserialize_hex_untyped(&body.value)
+            .map_err(|_e| Error::DecodeError("Failed to deserialize Clarity value".into()))?;
+
+        self.value = Some(value);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCPostValueHashRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.value = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        _node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let value = self
+            .value
+            .take()
+            .ok_or(NetError::SendError("`value` not set".into()))?;
+
+        let hash = value
+            .consensus_hash()
+            .map_err(|e| NetError::SerializeError(format!("{:?}", &e)))?;
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&ValueHashResponse { hash })?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCPostValueHashRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let resp: ValueHashResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(resp)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request to hash a Clarity value
+    pub fn new_post_value_hash(host: PeerHost, value: &Value) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "POST".into(),
+            "/v2/clarity/value/hash".into(),
+            HttpRequestContents::new().payload_json(
+                serde_json::to_value(ValueHashRequestBody {
+                    value: value
+                        .serialize_to_hex()
+                        .expect("FATAL: failed to serialize Clarity value"),
+                })
+                .expect("FATAL: failed to encode infallible data"),
+            ),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_value_hash(self) -> Result<ValueHashResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let contents_json: serde_json::Value = contents.try_into()?;
+        let resp: ValueHashResponse = serde_json::from_value(contents_json)
+            .map_err(|_e| NetError::DeserializeError("Failed to load from JSON".to_string()))?;
+        Ok(resp)
+    }
+}
@@ -27,12 +27,43 @@ use super::test_rpc;
 use crate::core::BLOCK_LIMIT_MAINNET_21;
 use crate::net::api::*;
 use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpRequestContents;
 use crate::net::httpcore::{
     HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
     StacksHttpRequest,
 };
 use crate::net::{ProtocolFamily, TipRequest};
 
+/// Make a read-only call request with the given query arguments
+fn make_callreadonly_request_with_query(
+    addr: SocketAddr,
+    contract_name: &str,
+    function_name: &str,
+    query_args: Vec<(&str, String)>,
+) -> StacksHttpRequest {
+    let mut contents = HttpRequestContents::new();
+    for (key, value) in query_args.into_iter() {
+        contents = contents.query_arg(key.to_string(), value);
+    }
+    StacksHttpRequest::new_for_peer(
+        addr.into(),
+        "POST".into(),
+        format!(
+            "/v2/contracts/call-read/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/{}/{}",
+            contract_name, function_name
+        ),
+        contents.payload_json(
+            serde_json::to_value(callreadonly::CallReadOnlyRequestBody {
+                sender: "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R".to_string(),
+                sponsor: None,
+                arguments: vec![],
+            })
+            .unwrap(),
+        ),
+    )
+    .unwrap()
+}
+
 #[test]
 fn test_try_parse_request() {
     let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
@@ -61,7 +92,7 @@ fn test_try_parse_request() {
 
     let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
     let mut handler =
-        callreadonly::RPCCallReadOnlyRequestHandler::new(4096, BLOCK_LIMIT_MAINNET_21);
+        callreadonly::RPCCallReadOnlyRequestHandler::new(4096, BLOCK_LIMIT_MAINNET_21, 0);
     let mut parsed_request = http
         .handle_try_parse_request(
             &mut handler,
@@ -101,6 +132,57 @@ fn test_try_parse_request() {
     assert!(handler.sender.is_none());
     assert!(handler.sponsor.is_none());
     assert!(handler.arguments.is_none());
+    assert!(handler.block_height.is_none());
+
+    // evaluate at a block height
+    let request = make_callreadonly_request_with_query(
+        addr.clone(),
+        "hello-world",
+        "ro-confirmed",
+        vec![("block_height", "1".to_string())],
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler =
+        callreadonly::RPCCallReadOnlyRequestHandler::new(4096, BLOCK_LIMIT_MAINNET_21, 0);
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+    assert_eq!(handler.block_height, Some(1));
+
+    handler.restart();
+    assert!(handler.block_height.is_none());
+
+    // `tip` and `block_height` can't both be given, and `block_height` must be a height
+    for query_args in [
+        vec![
+            ("tip", format!("{}", &StacksBlockId([0x22; 32]))),
+            ("block_height", "1".to_string()),
+        ],
+        vec![("block_height", "one".to_string())],
+        vec![("block_height", format!("{}", u32::MAX))],
+    ] {
+        let request = make_callreadonly_request_with_query(
+            addr.clone(),
+            "hello-world",
+            "ro-confirmed",
+            query_args,
+        );
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler =
+            callreadonly::RPCCallReadOnlyRequestHandler::new(4096, BLOCK_LIMIT_MAINNET_21, 0);
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
 }
 
 #[test]
@@ -184,6 +266,24 @@ fn test_try_make_response() {
     );
     requests.push(request);
 
+    // query at the height of the confirmed tip
+    let request = make_callreadonly_request_with_query(
+        addr.clone(),
+        "hello-world",
+        "ro-confirmed",
+        vec![("block_height", "1".to_string())],
+    );
+    requests.push(request);
+
+    // query at a height the chain has not reached
+    let request = make_callreadonly_request_with_query(
+        addr.clone(),
+        "hello-world",
+        "ro-confirmed",
+        vec![("block_height", "100".to_string())],
+    );
+    requests.push(request);
+
     let mut responses = test_rpc(function_name!(), requests);
 
     // confirmed tip
@@ -270,4 +370,28 @@ fn test_try_make_response() {
 
     let (preamble, payload) = response.destruct();
     assert_eq!(preamble.status_code, 404);
+
+    // block height of the confirmed tip
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let resp = response.decode_call_readonly_response().unwrap();
+
+    assert!(resp.okay);
+
+    // u1
+    assert_eq!(resp.result.unwrap(), "0x0100000000000000000000000000000001");
+
+    // non-existent block height
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let (preamble, payload) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
 }
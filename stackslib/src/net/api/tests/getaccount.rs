@@ -138,6 +138,7 @@ fn test_try_make_response() {
         response.preamble().get_canonical_stacks_tip_height(),
         Some(1)
     );
+    let anchored_tip = response.preamble().get_stacks_tip().unwrap();
 
     let resp = response.decode_account_entry_response().unwrap();
 
@@ -195,6 +196,9 @@ fn test_try_make_response() {
         response.preamble().get_canonical_stacks_tip_height(),
         Some(1)
     );
+    // the answer reflects the unconfirmed state, not the anchored tip
+    let unconfirmed_tip = response.preamble().get_stacks_tip().unwrap();
+    assert_ne!(unconfirmed_tip, anchored_tip);
 
     let resp = response.decode_account_entry_response().unwrap();
 
@@ -0,0 +1,185 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::types::PrincipalData;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::chainstate::stacks::db::AccountTransactionRole;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::{ProtocolFamily, TipRequest};
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let principal = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        principal.clone(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32])),
+        10,
+        30,
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getaccounttransactions::RPCGetAccountTransactionsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.account, Some(principal.clone()));
+    assert_eq!(handler.limit, 10);
+    assert_eq!(handler.offset, 30);
+    assert_eq!(
+        parsed_request.contents().tip_request(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32]))
+    );
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.account.is_none());
+    assert_eq!(
+        handler.limit,
+        getaccounttransactions::DEFAULT_ACCOUNT_TRANSACTIONS_LIMIT
+    );
+    assert_eq!(handler.offset, 0);
+
+    // the limit is capped
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        principal,
+        TipRequest::UseLatestAnchoredTip,
+        getaccounttransactions::MAX_ACCOUNT_TRANSACTIONS_LIMIT + 1,
+        0,
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+    let mut requests = vec![];
+
+    // the address of privk1, which mined the tip
+    let miner = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+
+    // the miner's coinbase and contract, newest first
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        miner.clone(),
+        TipRequest::UseLatestAnchoredTip,
+        20,
+        0,
+    );
+    requests.push(request);
+
+    // second page
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        miner.clone(),
+        TipRequest::SpecificTip(rpc_test.canonical_tip.clone()),
+        1,
+        1,
+    );
+    requests.push(request);
+
+    // an account with no history
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        PrincipalData::parse("ST000000000000000000002AMW42H").unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+        20,
+        0,
+    );
+    requests.push(request);
+
+    // unknown tip
+    let request = StacksHttpRequest::new_getaccounttransactions(
+        addr.into(),
+        miner,
+        TipRequest::SpecificTip(StacksBlockId([0x11; 32])),
+        20,
+        0,
+    );
+    requests.push(request);
+
+    let canonical_tip = rpc_test.canonical_tip.clone();
+    let coinbase_txid = rpc_test.block_txids[0].clone();
+    let contract_txid = rpc_test.block_txids[1].clone();
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let resp = response.decode_getaccounttransactions().unwrap();
+    assert_eq!(resp.limit, 20);
+    assert_eq!(resp.offset, 0);
+    assert_eq!(resp.results.len(), 2);
+    assert_eq!(resp.results[0].txid, contract_txid);
+    assert_eq!(resp.results[0].tx_index, 1);
+    assert_eq!(resp.results[1].txid, coinbase_txid);
+    assert_eq!(resp.results[1].tx_index, 0);
+    for result in resp.results.iter() {
+        assert_eq!(result.index_block_hash, canonical_tip);
+        assert_eq!(result.block_height, 1);
+        assert_eq!(result.roles, vec![AccountTransactionRole::Origin]);
+    }
+
+    let response = responses.remove(0);
+    let resp = response.decode_getaccounttransactions().unwrap();
+    assert_eq!(resp.results.len(), 1);
+    assert_eq!(resp.results[0].txid, coinbase_txid);
+
+    let response = responses.remove(0);
+    let resp = response.decode_getaccounttransactions().unwrap();
+    assert!(resp.results.is_empty());
+
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
@@ -0,0 +1,115 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use super::test_rpc;
+use crate::net::api::getburnops::BurnchainOps;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_burn_ops(addr.into(), 123, "stack_stx");
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getburnops::RPCGetBurnOpsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.height, Some(123));
+    assert_eq!(handler.op_type, Some("stack_stx".to_string()));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.height.is_none());
+    assert!(handler.op_type.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut requests = vec![];
+
+    for op_type in ["stack_stx", "delegate_stx", "transfer_stx"] {
+        requests.push(StacksHttpRequest::new_get_burn_ops(addr.into(), 1, op_type));
+    }
+
+    // no such burnchain block
+    let request = StacksHttpRequest::new_get_burn_ops(addr.into(), 1_000_000, "stack_stx");
+    requests.push(request);
+
+    // no such op type
+    let request = StacksHttpRequest::new_get_burn_ops(addr.into(), 1, "leader_key_register");
+    requests.push(request);
+
+    let mut responses = test_rpc(function_name!(), requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+    assert_eq!(
+        response.decode_burn_ops().unwrap(),
+        BurnchainOps::StackStx(vec![])
+    );
+
+    let response = responses.remove(0);
+    assert_eq!(
+        response.decode_burn_ops().unwrap(),
+        BurnchainOps::DelegateStx(vec![])
+    );
+
+    let response = responses.remove(0);
+    assert_eq!(
+        response.decode_burn_ops().unwrap(),
+        BurnchainOps::TransferStx(vec![])
+    );
+
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 400);
+}
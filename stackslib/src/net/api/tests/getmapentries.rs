@@ -0,0 +1,249 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::types::{QualifiedContractIdentifier, StacksAddressExtensions, TupleData};
+use clarity::vm::Value;
+use stacks_common::types::chainstate::{StacksAddress, StacksBlockId};
+use stacks_common::types::Address;
+
+use super::test_rpc;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::{ProtocolFamily, TipRequest};
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+        "hello-world".try_into().unwrap(),
+        "test-map".try_into().unwrap(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32])),
+        10,
+        Some("0x0100000000000000000000000000000001".to_string()),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getmapentries::RPCGetMapEntriesRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(
+        handler.contract_identifier,
+        Some(
+            QualifiedContractIdentifier::parse(
+                "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world"
+            )
+            .unwrap()
+        )
+    );
+    assert_eq!(handler.map_name, Some("test-map".into()));
+    assert_eq!(handler.limit, 10);
+    assert_eq!(
+        handler.after,
+        Some("0100000000000000000000000000000001".to_string())
+    );
+    assert_eq!(
+        parsed_request.contents().tip_request(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32]))
+    );
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.contract_identifier.is_none());
+    assert!(handler.map_name.is_none());
+    assert_eq!(handler.limit, getmapentries::DEFAULT_MAP_ENTRIES_LIMIT);
+    assert!(handler.after.is_none());
+
+    // the limit is capped, and `after` must be a hex-serialized key
+    for (limit, after) in [
+        (getmapentries::MAX_MAP_ENTRIES_LIMIT + 1, None),
+        (0, None),
+        (10, Some("not-hex".to_string())),
+    ] {
+        let request = StacksHttpRequest::new_getmapentries(
+            addr.into(),
+            StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+            "hello-world".try_into().unwrap(),
+            "test-map".try_into().unwrap(),
+            TipRequest::UseLatestAnchoredTip,
+            limit,
+            after,
+        );
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let contract_addr =
+        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+
+    let mut requests = vec![];
+
+    // all of unit-map
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        contract_addr.clone(),
+        "hello-world".try_into().unwrap(),
+        "unit-map".try_into().unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+        20,
+        None,
+    );
+    requests.push(request);
+
+    // first page of test-map
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        contract_addr.clone(),
+        "hello-world".try_into().unwrap(),
+        "test-map".try_into().unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+        1,
+        None,
+    );
+    requests.push(request);
+
+    // second page of test-map
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        contract_addr.clone(),
+        "hello-world".try_into().unwrap(),
+        "test-map".try_into().unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+        1,
+        Some("0x0100000000000000000000000000000001".to_string()),
+    );
+    requests.push(request);
+
+    // non-existent map
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        contract_addr.clone(),
+        "hello-world".try_into().unwrap(),
+        "does-not-exist".try_into().unwrap(),
+        TipRequest::UseLatestAnchoredTip,
+        20,
+        None,
+    );
+    requests.push(request);
+
+    // non-existent tip
+    let request = StacksHttpRequest::new_getmapentries(
+        addr.into(),
+        contract_addr,
+        "hello-world".try_into().unwrap(),
+        "test-map".try_into().unwrap(),
+        TipRequest::SpecificTip(StacksBlockId([0x11; 32])),
+        20,
+        None,
+    );
+    requests.push(request);
+
+    let mut responses = test_rpc(function_name!(), requests);
+
+    // unit-map
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let resp = response.decode_map_entries_response().unwrap();
+    assert_eq!(resp.limit, 20);
+    assert_eq!(resp.results.len(), 1);
+    assert!(resp.next.is_none());
+
+    let key = Value::try_deserialize_hex_untyped(&resp.results[0].key).unwrap();
+    assert_eq!(
+        key,
+        Value::Tuple(
+            TupleData::from_data(vec![(
+                "account".into(),
+                Value::Principal(contract_addr.to_account_principal())
+            )])
+            .unwrap()
+        )
+    );
+    let value = Value::try_deserialize_hex_untyped(&resp.results[0].value).unwrap();
+    assert_eq!(
+        value,
+        Value::some(Value::Tuple(
+            TupleData::from_data(vec![("units".into(), Value::Int(123))]).unwrap()
+        ))
+        .unwrap()
+    );
+
+    // first page of test-map
+    let response = responses.remove(0);
+    let resp = response.decode_map_entries_response().unwrap();
+    assert_eq!(resp.results.len(), 1);
+    assert_eq!(resp.results[0].key, "0x0100000000000000000000000000000001");
+    assert_eq!(
+        resp.results[0].value,
+        "0x0a0100000000000000000000000000000002"
+    );
+    assert_eq!(
+        resp.next,
+        Some("0x0100000000000000000000000000000001".to_string())
+    );
+
+    // second page of test-map
+    let response = responses.remove(0);
+    let resp = response.decode_map_entries_response().unwrap();
+    assert!(resp.results.is_empty());
+    assert!(resp.next.is_none());
+
+    // non-existent map
+    let response = responses.remove(0);
+    let resp = response.decode_map_entries_response().unwrap();
+    assert!(resp.results.is_empty());
+
+    // non-existent tip
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
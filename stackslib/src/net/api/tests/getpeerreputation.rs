@@ -0,0 +1,116 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::net::PeerAddress;
+
+use super::TestRPC;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::reputation::{PeerMisbehavior, PEER_SCORE_MAX};
+use crate::net::{NeighborKey, ProtocolFamily};
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_peer_reputation(addr.into());
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getpeerreputation::RPCGetPeerReputationRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut rpc_test = TestRPC::setup(function_name!());
+
+    // two peers misbehave on peer 2
+    let flooder = NeighborKey {
+        peer_version: 0x18000000,
+        network_id: 0x80000000,
+        addrbytes: PeerAddress::from_ipv4(127, 0, 0, 2),
+        port: 20444,
+    };
+    let bad_block_sender = NeighborKey {
+        addrbytes: PeerAddress::from_ipv4(127, 0, 0, 3),
+        ..flooder.clone()
+    };
+    for _ in 0..6 {
+        rpc_test
+            .peer_2
+            .network
+            .record_peer_misbehavior(&flooder, PeerMisbehavior::UnsolicitedFlood);
+    }
+    rpc_test
+        .peer_2
+        .network
+        .record_peer_misbehavior(&bad_block_sender, PeerMisbehavior::InvalidBlock);
+
+    let mut requests = vec![];
+    requests.push(StacksHttpRequest::new_get_peer_reputation(addr.into()));
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    let reputation = response.decode_peer_reputation().unwrap();
+    assert_eq!(reputation.peers.len(), 2);
+
+    // the flooder is deprioritized, but not banned
+    let flooder_reputation = &reputation.peers[0];
+    assert_eq!(flooder_reputation.addrbytes, flooder.addrbytes);
+    assert_eq!(flooder_reputation.score, PEER_SCORE_MAX - 60);
+    assert!(flooder_reputation.deprioritized);
+    assert_eq!(flooder_reputation.banned_until, None);
+    assert_eq!(flooder_reputation.unsolicited_floods, 6);
+
+    // the peer that sent an invalid block is banned, and starts over once the ban expires
+    let bad_block_sender_reputation = &reputation.peers[1];
+    assert_eq!(
+        bad_block_sender_reputation.addrbytes,
+        bad_block_sender.addrbytes
+    );
+    assert_eq!(bad_block_sender_reputation.score, PEER_SCORE_MAX);
+    assert!(!bad_block_sender_reputation.deprioritized);
+    assert!(bad_block_sender_reputation.banned_until.is_some());
+    assert_eq!(bad_block_sender_reputation.invalid_blocks, 1);
+}
@@ -0,0 +1,207 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, VRFSeed};
+
+use super::test_rpc;
+use crate::burnchains::{BurnchainSigner, Txid};
+use crate::chainstate::burn::operations::LeaderBlockCommitOp;
+use crate::chainstate::burn::BlockSnapshot;
+use crate::net::api::getsortitionstats::{
+    SortitionStatsResponse, DEFAULT_SORTITION_STATS_BLOCKS, MAX_SORTITION_STATS_BLOCKS,
+};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_sortition_stats(addr.into(), Some(12));
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getsortitionstats::RPCGetSortitionStatsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.num_blocks, 12);
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert_eq!(handler.num_blocks, DEFAULT_SORTITION_STATS_BLOCKS);
+
+    // out-of-range windows are rejected
+    for num_blocks in [0, MAX_SORTITION_STATS_BLOCKS + 1] {
+        let request = StacksHttpRequest::new_get_sortition_stats(addr.into(), Some(num_blocks));
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = getsortitionstats::RPCGetSortitionStatsRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let mut requests = vec![];
+    requests.push(StacksHttpRequest::new_get_sortition_stats(
+        addr.into(),
+        None,
+    ));
+    requests.push(StacksHttpRequest::new_get_sortition_stats(
+        addr.into(),
+        Some(1),
+    ));
+
+    let mut responses = test_rpc(function_name!(), requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+    let stats = response.decode_sortition_stats().unwrap();
+    assert!(stats.num_blocks > 0);
+    assert!(stats.num_blocks <= DEFAULT_SORTITION_STATS_BLOCKS);
+    assert!(stats.num_sortitions <= stats.num_blocks);
+    assert_eq!(
+        stats.total_burn,
+        stats
+            .miners
+            .iter()
+            .map(|miner| miner.total_burn)
+            .sum::<u64>()
+    );
+    assert!(stats.local_miner.is_none());
+
+    let response = responses.remove(0);
+    let stats = response.decode_sortition_stats().unwrap();
+    assert_eq!(stats.num_blocks, 1);
+}
+
+fn make_commit(txid_byte: u8, sender: &str, burn_fee: u64) -> LeaderBlockCommitOp {
+    LeaderBlockCommitOp {
+        sunset_burn: 0,
+        block_header_hash: BlockHeaderHash([txid_byte; 32]),
+        burn_fee,
+        input: (Txid([0; 32]), 0),
+        apparent_sender: BurnchainSigner(sender.to_string()),
+        key_block_ptr: 1,
+        key_vtxindex: 1,
+        memo: vec![],
+        new_seed: VRFSeed([0x11; 32]),
+        parent_block_ptr: 0,
+        parent_vtxindex: 0,
+        vtxindex: txid_byte as u32,
+        txid: Txid([txid_byte; 32]),
+        block_height: 0,
+        burn_header_hash: BurnchainHeaderHash::zero(),
+        burn_parent_modulus: 0,
+        commit_outs: vec![],
+    }
+}
+
+fn make_snapshot(height: u64, winner: Option<u8>) -> BlockSnapshot {
+    let mut snapshot = BlockSnapshot::initial(height, &BurnchainHeaderHash([height as u8; 32]), 0);
+    snapshot.sortition = winner.is_some();
+    snapshot.winning_block_txid = Txid([winner.unwrap_or(0); 32]);
+    snapshot
+}
+
+#[test]
+fn test_sortition_stats_from_sortitions() {
+    let sortitions = vec![
+        (
+            make_snapshot(12, Some(1)),
+            vec![make_commit(1, "alice", 3000), make_commit(2, "bob", 1000)],
+        ),
+        (
+            make_snapshot(11, Some(4)),
+            vec![
+                make_commit(3, "alice-segwit", 1000),
+                make_commit(4, "bob", 1000),
+            ],
+        ),
+        (make_snapshot(10, None), vec![]),
+    ];
+    let local_miner_addrs = vec!["alice".to_string(), "alice-segwit".to_string()];
+
+    let stats = SortitionStatsResponse::from_sortitions(12, &sortitions, &local_miner_addrs);
+    assert_eq!(stats.tip_height, 12);
+    assert_eq!(stats.num_blocks, 3);
+    assert_eq!(stats.num_sortitions, 2);
+    assert_eq!(stats.total_burn, 6000);
+
+    // highest total commitment first
+    let addresses: Vec<_> = stats.miners.iter().map(|m| m.address.as_str()).collect();
+    assert_eq!(addresses, vec!["alice", "bob", "alice-segwit"]);
+
+    let alice = &stats.miners[0];
+    assert_eq!(alice.commits, 1);
+    assert_eq!(alice.wins, 1);
+    assert_eq!(alice.total_burn, 3000);
+    assert_eq!(alice.expected_wins, 0.75);
+    assert_eq!(alice.expected_win_probability, 0.25);
+
+    let bob = &stats.miners[1];
+    assert_eq!(bob.commits, 2);
+    assert_eq!(bob.wins, 1);
+    assert_eq!(bob.total_burn, 2000);
+    assert_eq!(bob.expected_wins, 0.75);
+
+    // the local miner's statistics cover all of its addresses
+    let local_miner = stats.local_miner.unwrap();
+    assert_eq!(local_miner.address, "alice");
+    assert_eq!(local_miner.commits, 2);
+    assert_eq!(local_miner.wins, 1);
+    assert_eq!(local_miner.total_burn, 4000);
+    assert_eq!(local_miner.expected_wins, 1.25);
+    assert_eq!(local_miner.expected_win_probability, 1.25 / 3.0);
+
+    let stats = SortitionStatsResponse::from_sortitions(12, &sortitions, &[]);
+    assert!(stats.local_miner.is_none());
+}
@@ -0,0 +1,144 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::burnchains::Txid;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::{ProtocolFamily, TipRequest};
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_gettransaction(
+        addr.into(),
+        Txid([0x11; 32]),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32])),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettransaction::RPCGetTransactionRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.txid, Some(Txid([0x11; 32])));
+    assert_eq!(
+        parsed_request.contents().tip_request(),
+        TipRequest::SpecificTip(StacksBlockId([0x22; 32]))
+    );
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.txid.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+    let mut requests = vec![];
+
+    // get the contract transaction in the tip's anchored block
+    let request = StacksHttpRequest::new_gettransaction(
+        addr.into(),
+        rpc_test.block_txids[1].clone(),
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // get it as of the tip
+    let request = StacksHttpRequest::new_gettransaction(
+        addr.into(),
+        rpc_test.block_txids[1].clone(),
+        TipRequest::SpecificTip(rpc_test.canonical_tip.clone()),
+    );
+    requests.push(request);
+
+    // not yet confirmed
+    let request = StacksHttpRequest::new_gettransaction(
+        addr.into(),
+        rpc_test.microblock_txids[0].clone(),
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    // never processed
+    let request = StacksHttpRequest::new_gettransaction(
+        addr.into(),
+        Txid([0x21; 32]),
+        TipRequest::UseLatestAnchoredTip,
+    );
+    requests.push(request);
+
+    let canonical_tip = rpc_test.canonical_tip.clone();
+    let contract_txid = rpc_test.block_txids[1].clone();
+    let mut responses = rpc_test.run(requests);
+
+    for _ in 0..2 {
+        let response = responses.remove(0);
+        debug!(
+            "Response:\n{}\n",
+            std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+        );
+
+        let resp = response.decode_gettransaction().unwrap();
+        assert_eq!(resp.txid, contract_txid);
+        assert_eq!(resp.index_block_hash, canonical_tip);
+        assert_eq!(resp.block_height, 1);
+        assert_eq!(resp.tx_index, Some(1));
+        assert_eq!(resp.microblock_hash, None);
+        assert_eq!(resp.post_condition_aborted, Some(false));
+        assert_eq!(resp.vm_error, None);
+        assert_eq!(resp.result_repr, "(ok true)");
+        assert_eq!(resp.result, Some("0x0703".to_string()));
+        assert!(resp.execution_cost.unwrap().runtime > 0);
+        assert!(resp.events.unwrap().is_array());
+    }
+
+    for _ in 0..2 {
+        let response = responses.remove(0);
+        debug!(
+            "Response:\n{}\n",
+            std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+        );
+        let (preamble, body) = response.destruct();
+
+        assert_eq!(preamble.status_code, 404);
+    }
+}
@@ -50,33 +50,43 @@ use crate::net::{
 
 mod callreadonly;
 mod getaccount;
+mod getaccounttransactions;
 mod getattachment;
 mod getattachmentsinv;
 mod getblock;
+mod getburnops;
 mod getconstantval;
 mod getcontractabi;
 mod getcontractsrc;
 mod getdatavar;
 mod getheaders;
+mod gethealth;
 mod getinfo;
 mod getistraitimplemented;
+mod getmapentries;
 mod getmapentry;
 mod getmicroblocks_confirmed;
 mod getmicroblocks_indexed;
 mod getmicroblocks_unconfirmed;
 mod getneighbors;
+mod getpeerreputation;
 mod getpoxinfo;
+mod getsortitionstats;
 mod getstackerdbchunk;
 mod getstackerdbmetadata;
+mod getstackerdbslots;
 mod getstxtransfercost;
+mod gettransaction;
 mod gettransaction_unconfirmed;
 mod liststackerdbreplicas;
 mod postblock;
 mod postfeerate;
+mod postfeerate_v2;
 mod postmempoolquery;
 mod postmicroblock;
 mod poststackerdbchunk;
 mod posttransaction;
+mod postvaluehash;
 
 const TEST_CONTRACT: &'static str = "
     (define-trait test-trait
@@ -191,6 +201,8 @@ pub struct TestRPC<'a> {
     pub microblock_tip_hash: BlockHeaderHash,
     /// list of mempool transactions
     pub mempool_txids: Vec<Txid>,
+    /// list of transactions in the chain tip's anchored block
+    pub block_txids: Vec<Txid>,
     /// list of microblock transactions
     pub microblock_txids: Vec<Txid>,
     /// next block to post, and its consensus hash
@@ -290,6 +302,17 @@ impl<'a> TestRPC<'a> {
         let mut peer_1 = TestPeer::new(peer_1_config);
         let mut peer_2 = TestPeer::new(peer_2_config);
 
+        // index processed transactions, for /v2/transactions/{txid} and
+        // /v2/addresses/{principal}/transactions, and data map keys, for /v2/map_entries
+        for peer in [&mut peer_1, &mut peer_2] {
+            peer.chainstate().txindex = true;
+            peer.coord.chainstate_mut().txindex = true;
+            peer.chainstate().account_txindex = true;
+            peer.coord.chainstate_mut().account_txindex = true;
+            peer.chainstate().set_map_key_index(true).unwrap();
+            peer.coord.chainstate_mut().set_map_key_index(true).unwrap();
+        }
+
         // mine one block with a contract in it
         // first the coinbase
         // make a coinbase for this miner
@@ -491,6 +514,7 @@ impl<'a> TestRPC<'a> {
             mblock
         };
 
+        let block_txids = stacks_block.txs.iter().map(|tx| tx.txid()).collect();
         let microblock_txids = microblock.txs.iter().map(|tx| tx.txid()).collect();
         let canonical_tip =
             StacksBlockHeader::make_index_block_hash(&consensus_hash, &stacks_block.block_hash());
@@ -802,6 +826,7 @@ impl<'a> TestRPC<'a> {
             consensus_hash,
             microblock_tip_hash: microblock.block_hash(),
             mempool_txids,
+            block_txids,
             microblock_txids,
             next_block: (next_consensus_hash, next_stacks_block),
             next_microblock: microblock,
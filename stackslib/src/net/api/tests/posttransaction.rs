@@ -159,6 +159,57 @@ fn test_try_parse_request() {
     handler.restart();
     assert!(handler.tx.is_none());
     assert!(handler.attachment.is_none());
+
+    // Test in check mode
+    let request = StacksHttpRequest::new_check_transaction(addr.into(), tx_cc_signed.clone());
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = posttransaction::RPCPostTransactionRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    assert_eq!(handler.tx, Some(tx_cc_signed.clone()));
+    assert_eq!(
+        parsed_request.contents().get_query_arg("mode"),
+        Some(&"check".to_string())
+    );
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.tx.is_none());
+
+    // Test with an unsupported mode
+    let request = StacksHttpRequest::new_for_peer(
+        addr.into(),
+        "POST".into(),
+        "/v2/transactions".to_string(),
+        HttpRequestContents::new()
+            .query_arg("mode".into(), "dry-run".into())
+            .payload_stacks(&tx_cc_signed),
+    )
+    .unwrap();
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = posttransaction::RPCPostTransactionRequestHandler::new();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
 }
 
 #[test]
@@ -201,6 +252,18 @@ fn test_try_make_response() {
         StacksHttpRequest::new_post_transaction_with_attachment(addr.into(), bad_tx.clone(), None);
     requests.push(request);
 
+    // check a tx (should succeed, but not be stored)
+    let request = StacksHttpRequest::new_check_transaction(addr.into(), sendable_txs[2].clone());
+    requests.push(request);
+
+    let request =
+        StacksHttpRequest::new_gettransaction_unconfirmed(addr.into(), sendable_txs[2].txid());
+    requests.push(request);
+
+    // check a bad tx (should fail)
+    let request = StacksHttpRequest::new_check_transaction(addr.into(), bad_tx.clone());
+    requests.push(request);
+
     let mut responses = rpc_test.run(requests);
 
     let response = responses.remove(0);
@@ -238,4 +301,32 @@ fn test_try_make_response() {
 
     let (preamble, body) = response.destruct();
     assert_eq!(preamble.status_code, 400);
+    let rejection: serde_json::Value = body.try_into().unwrap();
+    assert_eq!(rejection["reason"], "BadTransactionVersion");
+    assert_eq!(rejection["txid"], bad_tx.txid().to_hex());
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let txid = response.decode_txid().unwrap();
+    assert_eq!(txid, sendable_txs[2].txid());
+
+    // checked tx was not admitted
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 400);
+    let rejection: serde_json::Value = body.try_into().unwrap();
+    assert_eq!(rejection["reason"], "BadTransactionVersion");
 }
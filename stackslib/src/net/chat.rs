@@ -44,6 +44,7 @@ use crate::net::db::{PeerDB, *};
 use crate::net::neighbors::MAX_NEIGHBOR_BLOCK_DELAY;
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::*;
+use crate::net::reputation::PeerMisbehavior;
 use crate::net::stackerdb::StackerDBs;
 use crate::net::{
     Error as net_error, GetBlocksInv, GetPoxInv, Neighbor, NeighborKey, StacksMessage, StacksP2P,
@@ -129,6 +130,8 @@ pub struct NeighborStats {
     /// (timestamp, num bytes)
     pub stackerdb_push_rx_counts: VecDeque<(u64, u64)>,
     pub relayed_messages: HashMap<NeighborAddress, RelayStats>,
+    /// protocol misbehavior seen in this conversation, which the peer network has yet to score
+    pub misbehavior: Vec<PeerMisbehavior>,
 }
 
 impl NeighborStats {
@@ -153,6 +156,7 @@ impl NeighborStats {
             transaction_push_rx_counts: VecDeque::new(),
             stackerdb_push_rx_counts: VecDeque::new(),
             relayed_messages: HashMap::new(),
+            misbehavior: vec![],
         }
     }
 
@@ -1953,6 +1957,9 @@ impl ConversationP2P {
         if !self.process_relayers(local_peer, preamble, &relayers) {
             warn!("Drop pushed blocks -- invalid relayers {:?}", &relayers);
             self.stats.msgs_err += 1;
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::MalformedMessage);
             return Err(net_error::InvalidMessage);
         }
 
@@ -1968,6 +1975,9 @@ impl ConversationP2P {
                 self.connection.options.max_block_push_bandwidth,
                 self.stats.get_block_push_bandwidth()
             );
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::UnsolicitedFlood);
             return self
                 .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
                 .and_then(|handle| Ok(Some(handle)));
@@ -1995,6 +2005,9 @@ impl ConversationP2P {
                 &relayers
             );
             self.stats.msgs_err += 1;
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::MalformedMessage);
             return Err(net_error::InvalidMessage);
         }
 
@@ -2006,6 +2019,9 @@ impl ConversationP2P {
                 > (self.connection.options.max_microblocks_push_bandwidth as f64)
         {
             debug!("Neighbor {:?} exceeded max microblocks-push bandwidth of {} bytes/sec (currently at {})", &self.to_neighbor_key(), self.connection.options.max_microblocks_push_bandwidth, self.stats.get_microblocks_push_bandwidth());
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::UnsolicitedFlood);
             return self
                 .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
                 .and_then(|handle| Ok(Some(handle)));
@@ -2032,6 +2048,9 @@ impl ConversationP2P {
                 &relayers
             );
             self.stats.msgs_err += 1;
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::MalformedMessage);
             return Err(net_error::InvalidMessage);
         }
 
@@ -2043,6 +2062,9 @@ impl ConversationP2P {
                 > (self.connection.options.max_transaction_push_bandwidth as f64)
         {
             debug!("Neighbor {:?} exceeded max transaction-push bandwidth of {} bytes/sec (currently at {})", &self.to_neighbor_key(), self.connection.options.max_transaction_push_bandwidth, self.stats.get_transaction_push_bandwidth());
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::UnsolicitedFlood);
             return self
                 .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
                 .and_then(|handle| Ok(Some(handle)));
@@ -2070,6 +2092,9 @@ impl ConversationP2P {
                 &relayers
             );
             self.stats.msgs_err += 1;
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::MalformedMessage);
             return Err(net_error::InvalidMessage);
         }
 
@@ -2081,6 +2106,9 @@ impl ConversationP2P {
                 > (self.connection.options.max_stackerdb_push_bandwidth as f64)
         {
             debug!("Neighbor {:?} exceeded max stackerdb-push bandwidth of {} bytes/sec (currently at {})", &self.to_neighbor_key(), self.connection.options.max_stackerdb_push_bandwidth, self.stats.get_stackerdb_push_bandwidth());
+            self.stats
+                .misbehavior
+                .push(PeerMisbehavior::UnsolicitedFlood);
             return self
                 .reply_nack(local_peer, chain_view, preamble, NackErrorCodes::Throttled)
                 .and_then(|handle| Ok(Some(handle)));
@@ -2300,6 +2328,9 @@ impl ConversationP2P {
                     );
                     self.stats.msgs_err += 1;
                     self.stats.add_healthpoint(false);
+                    self.stats
+                        .misbehavior
+                        .push(PeerMisbehavior::MalformedMessage);
                     return Err(e);
                 }
                 _ => {
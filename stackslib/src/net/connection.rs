@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::io::{Read, Write};
 use std::ops::{Deref, DerefMut};
@@ -25,7 +25,7 @@ use std::time::Duration;
 use std::{io, net};
 
 use clarity::vm::costs::ExecutionCost;
-use clarity::vm::types::BOUND_VALUE_SERIALIZATION_HEX;
+use clarity::vm::types::{QualifiedContractIdentifier, BOUND_VALUE_SERIALIZATION_HEX};
 use mio;
 use mio::net as mio_net;
 use stacks_common::codec::{StacksMessageCodec, MAX_MESSAGE_LEN};
@@ -39,15 +39,18 @@ use crate::chainstate::burn::ConsensusHash;
 use crate::core::mempool::MAX_BLOOM_COUNTER_TXS;
 use crate::monitoring::{update_inbound_bandwidth, update_outbound_bandwidth};
 use crate::net::codec::*;
-use crate::net::download::BLOCK_DOWNLOAD_INTERVAL;
+use crate::net::download::{BlockDownloadPeerSelection, BLOCK_DOWNLOAD_INTERVAL};
+use crate::net::httpcore::RPCEndpointGroup;
 use crate::net::inv::{INV_REWARD_CYCLES, INV_SYNC_INTERVAL};
 use crate::net::neighbors::{
     MAX_NEIGHBOR_AGE, NEIGHBOR_REQUEST_TIMEOUT, NEIGHBOR_WALK_INTERVAL, NUM_INITIAL_WALKS,
     WALK_MAX_DURATION, WALK_MIN_DURATION, WALK_RESET_INTERVAL, WALK_RESET_PROB, WALK_RETRY_COUNT,
     WALK_STATE_TIMEOUT,
 };
+use crate::net::stackerdb::StackerDBReplicationSettings;
 use crate::net::{
-    Error as net_error, MessageSequence, Preamble, ProtocolFamily, RelayData, StacksHttp, StacksP2P,
+    Error as net_error, MessageSequence, Preamble, ProtocolFamily, RelayData, StacksHttp,
+    StacksP2P, DENY_BAN_DURATION,
 };
 
 /// Receiver notification handle.
@@ -354,20 +357,41 @@ pub struct ConnectionOptions {
     pub walk_reset_prob: f64,
     pub walk_reset_interval: u64,
     pub walk_state_timeout: u64,
+    /// how many of the peers that served us best before to walk to alongside the bootstrap
+    /// peers during initial block download (0 to only walk to bootstrap peers)
+    pub max_known_bootstrap_peers: u64,
     pub inv_sync_interval: u64,
     pub inv_reward_cycles: u64,
     pub download_interval: u64,
     pub pingback_timeout: u64,
     pub dns_timeout: u128,
     pub max_inflight_blocks: u64,
+    /// how many of the parallel block downloads may go to the same peer (0 for no limit)
+    pub max_inflight_blocks_per_peer: u64,
+    /// whether or not to raise or lower the number of parallel block downloads depending on how
+    /// well peers serve them, up to `ADAPTIVE_MAX_INFLIGHT_FACTOR` times `max_inflight_blocks`
+    pub adaptive_max_inflight_blocks: bool,
+    /// how to choose which peer to download each block from
+    pub block_download_peer_selection: BlockDownloadPeerSelection,
     pub max_inflight_attachments: u64,
     pub max_attachment_retry_count: u64,
     pub read_only_call_limit: ExecutionCost,
     pub maximum_call_argument_size: u32,
+    /// how many blocks behind the canonical tip a read-only call may be evaluated at (0 means
+    /// no limit)
+    pub read_only_call_max_tip_depth: u64,
     pub max_block_push_bandwidth: u64,
     pub max_microblocks_push_bandwidth: u64,
     pub max_transaction_push_bandwidth: u64,
     pub max_stackerdb_push_bandwidth: u64,
+    /// minimum time between StackerDB anti-entropy syncs for a DB, in seconds. The DB's own
+    /// `write_freq` is used instead if it is longer.
+    pub stackerdb_sync_interval: u64,
+    /// StackerDB slots whose new chunks are pushed to connected replicas as soon as they
+    /// arrive, instead of waiting for the next anti-entropy sync
+    pub stackerdb_hot_slots: HashMap<QualifiedContractIdentifier, HashSet<u32>>,
+    /// per-DB replication priority and sync settings, for DBs the operator cares most about
+    pub stackerdb_replication: HashMap<QualifiedContractIdentifier, StackerDBReplicationSettings>,
     pub max_sockets: usize,
     pub public_ip_address: Option<(PeerAddress, u16)>,
     pub public_ip_request_timeout: u64,
@@ -393,6 +417,20 @@ pub struct ConnectionOptions {
     pub socket_send_buffer_size: u32,
     /// whether or not to announce or accept neighbors that are behind private networks
     pub private_neighbors: bool,
+    /// RPC endpoint path prefixes whose responses may be compressed, if the client asks for it
+    /// via `Accept-Encoding`
+    pub http_compression_endpoints: Vec<String>,
+    /// in-RAM RPC responses smaller than this many bytes are never compressed
+    pub http_compression_min_size: u32,
+    /// RPC endpoint groups that require an `Authorization:` header whose value is the given token
+    pub rpc_auth_tokens: HashMap<RPCEndpointGroup, String>,
+    /// peers whose reputation score is at or below this are less likely to be picked as relay
+    /// recipients
+    pub peer_deprioritize_score: i64,
+    /// peers whose reputation score is at or below this are banned
+    pub peer_ban_score: i64,
+    /// how long a peer is banned for once its reputation score is too low, in seconds
+    pub peer_score_ban_duration: u64,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -446,13 +484,17 @@ impl std::default::Default for ConnectionOptions {
             walk_reset_prob: WALK_RESET_PROB,
             walk_reset_interval: WALK_RESET_INTERVAL,
             walk_state_timeout: WALK_STATE_TIMEOUT,
+            max_known_bootstrap_peers: 0,
             inv_sync_interval: INV_SYNC_INTERVAL, // how often to synchronize block inventories
             inv_reward_cycles: INV_REWARD_CYCLES, // how many reward cycles of blocks to sync in a non-full inventory sync
             download_interval: BLOCK_DOWNLOAD_INTERVAL, // how often to scan for blocks to download
             pingback_timeout: 60,
-            dns_timeout: 15_000,            // DNS timeout, in millis
-            max_inflight_blocks: 6,         // number of parallel block downloads
-            max_inflight_attachments: 6,    // number of parallel attachments downloads
+            dns_timeout: 15_000,             // DNS timeout, in millis
+            max_inflight_blocks: 6,          // number of parallel block downloads
+            max_inflight_blocks_per_peer: 0, // no limit on parallel block downloads per peer
+            adaptive_max_inflight_blocks: false,
+            block_download_peer_selection: BlockDownloadPeerSelection::Random,
+            max_inflight_attachments: 6, // number of parallel attachments downloads
             max_attachment_retry_count: 32, // how many attempt to get an attachment before giving up
             read_only_call_limit: ExecutionCost {
                 write_length: 0,
@@ -462,10 +504,14 @@ impl std::default::Default for ConnectionOptions {
                 runtime: 1_000_000_000,
             },
             maximum_call_argument_size: 20 * BOUND_VALUE_SERIALIZATION_HEX,
+            read_only_call_max_tip_depth: 0,
             max_block_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_microblocks_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_transaction_push_bandwidth: 0, // infinite upload bandwidth allowed
             max_stackerdb_push_bandwidth: 0, // infinite upload bandwidth allowed
+            stackerdb_sync_interval: 0,  // sync as often as each DB's write_freq allows
+            stackerdb_hot_slots: HashMap::new(), // no slots are pushed outside of syncs
+            stackerdb_replication: HashMap::new(), // all DBs are replicated alike
             max_sockets: 800,            // maximum number of client sockets we'll ever register
             public_ip_address: None,     // resolve it at runtime by default
             public_ip_request_timeout: 60, // how often we can attempt to look up our public IP address
@@ -485,6 +531,17 @@ impl std::default::Default for ConnectionOptions {
             socket_recv_buffer_size: 131072, // Linux default
             socket_send_buffer_size: 16384, // Linux default
             private_neighbors: true,
+            http_compression_endpoints: vec![
+                "/v2/blocks/".to_string(),
+                "/v2/microblocks/".to_string(),
+                "/v2/headers/".to_string(),
+                "/v2/contracts/source/".to_string(),
+            ],
+            http_compression_min_size: 1024,
+            rpc_auth_tokens: HashMap::new(), // every endpoint is open
+            peer_deprioritize_score: 50,
+            peer_ban_score: 0,
+            peer_score_ban_duration: DENY_BAN_DURATION,
 
             // no faults on by default
             disable_neighbor_walk: false,
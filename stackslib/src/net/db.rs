@@ -16,7 +16,8 @@
 
 use std::collections::HashSet;
 use std::convert::{From, TryFrom};
-use std::{fmt, fs};
+use std::net::SocketAddr;
+use std::{cmp, fmt, fs};
 
 use clarity::vm::types::{
     QualifiedContractIdentifier, StacksAddressExtensions, StandardPrincipalData,
@@ -45,7 +46,7 @@ use crate::util_lib::db::{
 };
 use crate::util_lib::strings::UrlString;
 
-pub const PEERDB_VERSION: &'static str = "2";
+pub const PEERDB_VERSION: &'static str = "3";
 
 const NUM_SLOTS: usize = 8;
 
@@ -307,6 +308,89 @@ impl FromRow<Neighbor> for Neighbor {
     }
 }
 
+/// What this node has learned about how well a peer serves it.  This outlives the peer's slot in
+/// the frontier, so a restarted node can reconnect to the peers that served it well.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeerQuality {
+    /// Number of outbound sessions with this peer
+    pub num_sessions: u64,
+    /// Number of those sessions that got as far as a handshake
+    pub num_handshakes: u64,
+    /// Total time, in seconds, that those sessions lasted
+    pub connected_secs: u64,
+    /// Number of blocks and microblock streams this peer served
+    pub blocks_served: u64,
+    /// Number of block and microblock stream requests this peer failed to serve
+    pub block_failures: u64,
+    /// Smoothed time, in milliseconds, this peer took to serve a block or microblock stream
+    pub latency_ms: Option<u64>,
+    /// When the last session with this peer ended
+    pub last_session_time: u64,
+}
+
+impl PeerQuality {
+    /// Fraction of sessions that got as far as a handshake (0.5 if we've had none)
+    pub fn uptime(&self) -> f64 {
+        if self.num_sessions == 0 {
+            return 0.5;
+        }
+        (self.num_handshakes as f64) / (self.num_sessions as f64)
+    }
+
+    /// Fraction of block requests this peer served (0.5 if we've asked it for none)
+    pub fn block_success_rate(&self) -> f64 {
+        let num_requests = self.blocks_served.saturating_add(self.block_failures);
+        if num_requests == 0 {
+            return 0.5;
+        }
+        (self.blocks_served as f64) / (num_requests as f64)
+    }
+
+    /// Score in [0, 1] to rank peers by.  A peer we know nothing about scores 0.5, so it ranks
+    /// below peers that served us well and above peers that did not.
+    pub fn score(&self) -> f64 {
+        let latency_score = match self.latency_ms {
+            Some(latency_ms) => 1000.0 / (1000.0 + (latency_ms as f64)),
+            None => 0.5,
+        };
+        0.5 * self.uptime() + 0.3 * self.block_success_rate() + 0.2 * latency_score
+    }
+}
+
+impl FromRow<PeerQuality> for PeerQuality {
+    fn from_row<'a>(row: &'a Row) -> Result<PeerQuality, db_error> {
+        let num_sessions = u64::from_column(row, "num_sessions")?;
+        let num_handshakes = u64::from_column(row, "num_handshakes")?;
+        let connected_secs = u64::from_column(row, "connected_secs")?;
+        let blocks_served = u64::from_column(row, "blocks_served")?;
+        let block_failures = u64::from_column(row, "block_failures")?;
+        let latency_ms: Option<i64> = row.get_unwrap("latency_ms");
+        let last_session_time = u64::from_column(row, "last_session_time")?;
+
+        Ok(PeerQuality {
+            num_sessions,
+            num_handshakes,
+            connected_secs,
+            blocks_served,
+            block_failures,
+            latency_ms: latency_ms.map(|latency_ms| latency_ms as u64),
+            last_session_time,
+        })
+    }
+}
+
+/// A peer and its quality, as exported by one node and imported by another to bootstrap from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerQualityRecord {
+    pub peer_version: u32,
+    pub network_id: u32,
+    pub addr: SocketAddr,
+    /// hex-encoded compressed public key
+    pub public_key: String,
+    pub expire_block: u64,
+    pub quality: PeerQuality,
+}
+
 // In what is likely an abuse of Sqlite, the peer database is structured such that the `frontier`
 // table stores peers keyed by a deterministically-chosen random "slot," instead of their IP/port.
 // (i.e. the slot is determined by a cryptographic the hash of the IP/port).  The reason for this
@@ -401,6 +485,27 @@ const PEERDB_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+const PEERDB_SCHEMA_3: &'static [&'static str] = &[
+    r#"
+    CREATE TABLE peer_quality(
+        network_id INTEGER NOT NULL,
+        addrbytes TEXT NOT NULL,
+        port INTEGER NOT NULL,
+        num_sessions INTEGER NOT NULL,
+        num_handshakes INTEGER NOT NULL,
+        connected_secs INTEGER NOT NULL,
+        blocks_served INTEGER NOT NULL,
+        block_failures INTEGER NOT NULL,
+        latency_ms INTEGER,
+        last_session_time INTEGER NOT NULL,
+        PRIMARY KEY(network_id,addrbytes,port)
+    );
+    "#,
+    r#"
+    UPDATE db_config SET version = 3;
+    "#,
+];
+
 #[derive(Debug)]
 pub struct PeerDB {
     pub conn: Connection,
@@ -526,6 +631,14 @@ impl PeerDB {
         Ok(())
     }
 
+    fn apply_schema_3(tx: &Transaction) -> Result<(), db_error> {
+        test_debug!("Apply schema 3 to peer DB");
+        for row_text in PEERDB_SCHEMA_3 {
+            tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+        }
+        Ok(())
+    }
+
     fn apply_schema_migrations(tx: &Transaction) -> Result<String, db_error> {
         test_debug!("Apply any schema migrations");
         let expected_version = PEERDB_VERSION.to_string();
@@ -538,6 +651,8 @@ impl PeerDB {
                     }
                     if version == "1" {
                         PeerDB::apply_schema_2(tx)?;
+                    } else if version == "2" {
+                        PeerDB::apply_schema_3(tx)?;
                     } else if version == expected_version {
                         return Ok(ret.expect("unreachable"));
                     } else {
@@ -1784,6 +1899,184 @@ impl PeerDB {
         Ok(rows)
     }
 
+    /// Get what we know about a peer's quality, if anything
+    pub fn get_peer_quality(
+        conn: &DBConn,
+        network_id: u32,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+    ) -> Result<Option<PeerQuality>, db_error> {
+        let qry =
+            "SELECT * FROM peer_quality WHERE network_id = ?1 AND addrbytes = ?2 AND port = ?3";
+        let args: &[&dyn ToSql] = &[&network_id, &peer_addr.to_bin(), &peer_port];
+        query_row::<PeerQuality, _>(conn, qry, args)
+    }
+
+    /// Store a peer's quality, replacing what we knew about it
+    pub fn set_peer_quality(
+        tx: &Transaction,
+        network_id: u32,
+        peer_addr: &PeerAddress,
+        peer_port: u16,
+        quality: &PeerQuality,
+    ) -> Result<(), db_error> {
+        let latency_ms = match quality.latency_ms {
+            Some(latency_ms) => Some(u64_to_sql(latency_ms)?),
+            None => None,
+        };
+        let args: &[&dyn ToSql] = &[
+            &network_id,
+            &peer_addr.to_bin(),
+            &peer_port,
+            &u64_to_sql(quality.num_sessions)?,
+            &u64_to_sql(quality.num_handshakes)?,
+            &u64_to_sql(quality.connected_secs)?,
+            &u64_to_sql(quality.blocks_served)?,
+            &u64_to_sql(quality.block_failures)?,
+            &latency_ms,
+            &u64_to_sql(quality.last_session_time)?,
+        ];
+        tx.execute("INSERT OR REPLACE INTO peer_quality (network_id, addrbytes, port, num_sessions, num_handshakes, connected_secs, blocks_served, block_failures, latency_ms, last_session_time) \
+                   VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)", args)
+            .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Record that an outbound session with a peer ended, and whether or not it got as far as a
+    /// handshake
+    pub fn record_peer_session(
+        tx: &Transaction,
+        nk: &NeighborKey,
+        handshake: bool,
+        connected_secs: u64,
+    ) -> Result<(), db_error> {
+        let mut quality = PeerDB::get_peer_quality(tx, nk.network_id, &nk.addrbytes, nk.port)?
+            .unwrap_or_default();
+        quality.num_sessions = quality.num_sessions.saturating_add(1);
+        if handshake {
+            quality.num_handshakes = quality.num_handshakes.saturating_add(1);
+        }
+        quality.connected_secs = quality.connected_secs.saturating_add(connected_secs);
+        quality.last_session_time = get_epoch_time_secs();
+        PeerDB::set_peer_quality(tx, nk.network_id, &nk.addrbytes, nk.port, &quality)
+    }
+
+    /// Record how many block and microblock stream requests a peer served and failed, and its
+    /// latest smoothed latency
+    pub fn record_peer_block_results(
+        tx: &Transaction,
+        nk: &NeighborKey,
+        blocks_served: u64,
+        block_failures: u64,
+        latency_ms: Option<u64>,
+    ) -> Result<(), db_error> {
+        let mut quality = PeerDB::get_peer_quality(tx, nk.network_id, &nk.addrbytes, nk.port)?
+            .unwrap_or_default();
+        quality.blocks_served = quality.blocks_served.saturating_add(blocks_served);
+        quality.block_failures = quality.block_failures.saturating_add(block_failures);
+        if latency_ms.is_some() {
+            quality.latency_ms = latency_ms;
+        }
+        PeerDB::set_peer_quality(tx, nk.network_id, &nk.addrbytes, nk.port, &quality)
+    }
+
+    /// Get up to `count` peers in the frontier that we've handshaked with before and that are not
+    /// denied, along with their quality, best first.
+    pub fn get_peers_by_quality(
+        conn: &DBConn,
+        network_id: u32,
+        count: usize,
+    ) -> Result<Vec<(Neighbor, PeerQuality)>, db_error> {
+        let qry = "SELECT frontier.* FROM frontier JOIN peer_quality ON frontier.network_id = peer_quality.network_id AND frontier.addrbytes = peer_quality.addrbytes AND frontier.port = peer_quality.port \
+                   WHERE frontier.network_id = ?1 AND frontier.denied < ?2 AND peer_quality.num_handshakes > 0";
+        let args: &[&dyn ToSql] = &[&network_id, &u64_to_sql(get_epoch_time_secs())?];
+        let mut peers = vec![];
+        for neighbor in query_rows::<Neighbor, _>(conn, qry, args)?.into_iter() {
+            let quality = PeerDB::get_peer_quality(
+                conn,
+                network_id,
+                &neighbor.addr.addrbytes,
+                neighbor.addr.port,
+            )?
+            .unwrap_or_default();
+            peers.push((neighbor, quality));
+        }
+        peers.sort_by(|(_, quality_1), (_, quality_2)| {
+            quality_2
+                .score()
+                .partial_cmp(&quality_1.score())
+                .unwrap_or(cmp::Ordering::Equal)
+        });
+        peers.truncate(count);
+        Ok(peers)
+    }
+
+    /// Export every peer we know the quality of, so another node can bootstrap from them
+    pub fn export_peer_quality(
+        conn: &DBConn,
+        network_id: u32,
+    ) -> Result<Vec<PeerQualityRecord>, db_error> {
+        let records = PeerDB::get_peers_by_quality(conn, network_id, usize::MAX)?
+            .into_iter()
+            .map(|(neighbor, quality)| PeerQualityRecord {
+                peer_version: neighbor.addr.peer_version,
+                network_id: neighbor.addr.network_id,
+                addr: neighbor.addr.addrbytes.to_socketaddr(neighbor.addr.port),
+                public_key: to_hex(&neighbor.public_key.to_bytes_compressed()),
+                expire_block: neighbor.expire_block,
+                quality,
+            })
+            .collect();
+        Ok(records)
+    }
+
+    /// Import peers exported by another node.  Peers we already know of are left alone, and so
+    /// is the quality of any peer we've already observed ourselves.
+    /// Returns the number of peers added to the frontier.
+    pub fn import_peer_quality(
+        tx: &Transaction,
+        records: &[PeerQualityRecord],
+    ) -> Result<u64, db_error> {
+        let mut num_imported = 0;
+        for record in records.iter() {
+            let addrbytes = PeerAddress::from_socketaddr(&record.addr);
+            let port = record.addr.port();
+            if PeerDB::get_peer_quality(tx, record.network_id, &addrbytes, port)?.is_none() {
+                PeerDB::set_peer_quality(tx, record.network_id, &addrbytes, port, &record.quality)?;
+            }
+            if PeerDB::has_peer(tx, record.network_id, &addrbytes, port)? {
+                continue;
+            }
+
+            let mut public_key = Secp256k1PublicKey::from_hex(&record.public_key)
+                .map_err(|_e| db_error::ParseError)?;
+            public_key.set_compressed(true);
+            let neighbor = Neighbor {
+                addr: NeighborKey {
+                    peer_version: record.peer_version,
+                    network_id: record.network_id,
+                    addrbytes: addrbytes.clone(),
+                    port,
+                },
+                public_key,
+                expire_block: record.expire_block,
+                last_contact_time: record.quality.last_session_time,
+                asn: PeerDB::asn_lookup(tx, &addrbytes)?.unwrap_or(0),
+                org: 0,
+                allowed: 0,
+                denied: 0,
+                in_degree: 1,
+                out_degree: 1,
+            };
+            if PeerDB::try_insert_peer(tx, &neighbor, &[])? {
+                num_imported += 1;
+            } else {
+                debug!("No room in the frontier for imported peer {:?}", &neighbor);
+            }
+        }
+        Ok(num_imported)
+    }
+
     /// Find out which peers replicate a particular stacker DB.
     /// Return a randomized list of up to the given size, where all
     /// peers returned have a last-contact time greater than the given minimum age.
@@ -3530,6 +3823,166 @@ mod test {
         assert_eq!(local_peer.private_key, key2);
     }
 
+    /// Test recording peer quality, ranking peers by it, and exporting and importing it
+    #[test]
+    fn test_peer_quality() {
+        let make_neighbor = |port: u16, pubkey_hex: &str| Neighbor {
+            addr: NeighborKey {
+                peer_version: 0x12345678,
+                network_id: 0x9abcdef0,
+                addrbytes: PeerAddress::from_ipv4(1, 2, 3, 4),
+                port,
+            },
+            public_key: Secp256k1PublicKey::from_hex(pubkey_hex).unwrap(),
+            expire_block: 23456,
+            last_contact_time: 1552509642,
+            allowed: 0,
+            denied: 0,
+            asn: 0,
+            org: 0,
+            in_degree: 1,
+            out_degree: 1,
+        };
+        let good_neighbor = make_neighbor(
+            12345,
+            "02fa66b66f8971a8cd4d20ffded09674e030f0f33883f337f34b95ad4935bac0e3",
+        );
+        let bad_neighbor = make_neighbor(
+            12346,
+            "02845047365c8b4d62ee1f9aee8c4fd4a039cb3ea8ac1034a16beec68e1bda0b53",
+        );
+
+        let mut db =
+            PeerDB::connect_memory(0x9abcdef0, 12345, 0, "http://foo.com".into(), &[], &[])
+                .unwrap();
+        {
+            let tx = db.tx_begin().unwrap();
+            assert!(PeerDB::try_insert_peer(&tx, &good_neighbor, &[]).unwrap());
+            assert!(PeerDB::try_insert_peer(&tx, &bad_neighbor, &[]).unwrap());
+
+            PeerDB::record_peer_session(&tx, &good_neighbor.addr, true, 100).unwrap();
+            PeerDB::record_peer_session(&tx, &good_neighbor.addr, true, 50).unwrap();
+            PeerDB::record_peer_block_results(&tx, &good_neighbor.addr, 3, 1, Some(200)).unwrap();
+            PeerDB::record_peer_block_results(&tx, &good_neighbor.addr, 1, 0, None).unwrap();
+            PeerDB::record_peer_session(&tx, &bad_neighbor.addr, false, 0).unwrap();
+            tx.commit().unwrap();
+        }
+
+        let good_quality = PeerDB::get_peer_quality(
+            db.conn(),
+            0x9abcdef0,
+            &good_neighbor.addr.addrbytes,
+            good_neighbor.addr.port,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(good_quality.num_sessions, 2);
+        assert_eq!(good_quality.num_handshakes, 2);
+        assert_eq!(good_quality.connected_secs, 150);
+        assert_eq!(good_quality.blocks_served, 4);
+        assert_eq!(good_quality.block_failures, 1);
+        assert_eq!(good_quality.latency_ms, Some(200));
+
+        let bad_quality = PeerDB::get_peer_quality(
+            db.conn(),
+            0x9abcdef0,
+            &bad_neighbor.addr.addrbytes,
+            bad_neighbor.addr.port,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(bad_quality.uptime(), 0.0);
+
+        // peers we know nothing about rank between good and bad peers
+        assert!(good_quality.score() > PeerQuality::default().score());
+        assert!(PeerQuality::default().score() > bad_quality.score());
+
+        // only peers we've handshaked with are ranked
+        let ranked = PeerDB::get_peers_by_quality(db.conn(), 0x9abcdef0, 10).unwrap();
+        assert_eq!(ranked, vec![(good_neighbor.clone(), good_quality.clone())]);
+        assert_eq!(
+            PeerDB::get_peers_by_quality(db.conn(), 0x9abcdef0, 0).unwrap(),
+            vec![]
+        );
+
+        let records = PeerDB::export_peer_quality(db.conn(), 0x9abcdef0).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].addr.to_string(), "1.2.3.4:12345");
+
+        let records_json = serde_json::to_string(&records).unwrap();
+        let records: Vec<PeerQualityRecord> = serde_json::from_str(&records_json).unwrap();
+
+        let mut imported_db =
+            PeerDB::connect_memory(0x9abcdef0, 12345, 0, "http://foo.com".into(), &[], &[])
+                .unwrap();
+        {
+            let tx = imported_db.tx_begin().unwrap();
+            assert_eq!(PeerDB::import_peer_quality(&tx, &records).unwrap(), 1);
+
+            // importing again changes nothing
+            assert_eq!(PeerDB::import_peer_quality(&tx, &records).unwrap(), 0);
+            tx.commit().unwrap();
+        }
+
+        let imported_neighbor = PeerDB::get_peer(
+            imported_db.conn(),
+            0x9abcdef0,
+            &good_neighbor.addr.addrbytes,
+            good_neighbor.addr.port,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(imported_neighbor.public_key, good_neighbor.public_key);
+        assert_eq!(
+            PeerDB::get_peers_by_quality(imported_db.conn(), 0x9abcdef0, 10).unwrap(),
+            vec![(imported_neighbor, good_quality)]
+        );
+    }
+
+    /// Test that a version 2 peer DB gets the peer quality table
+    #[test]
+    fn test_schema_3_migration() {
+        let path = "/tmp/test-peerdb-schema-3-migration.db".to_string();
+        if fs::metadata(&path).is_ok() {
+            fs::remove_file(&path).unwrap();
+        }
+
+        let connect = || {
+            PeerDB::connect(
+                &path,
+                true,
+                0x80000000,
+                0,
+                None,
+                i64::MAX as u64,
+                PeerAddress::from_ipv4(127, 0, 0, 1),
+                12345,
+                UrlString::try_from("http://foo.com").unwrap(),
+                &vec![],
+                None,
+                &[],
+            )
+            .unwrap()
+        };
+
+        let mut db = connect();
+        {
+            let tx = db.tx_begin().unwrap();
+            tx.execute_batch("DROP TABLE peer_quality; UPDATE db_config SET version = 2;")
+                .unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(PeerDB::get_schema_version(db.conn()).unwrap(), "2");
+        drop(db);
+
+        let db = connect();
+        assert_eq!(PeerDB::get_schema_version(db.conn()).unwrap(), "3");
+        assert_eq!(
+            PeerDB::get_peer_quality(db.conn(), 0x80000000, &PeerAddress([0u8; 16]), 0).unwrap(),
+            None
+        );
+    }
+
     /// Test DB instantiation -- it must work.
     #[test]
     fn test_db_instantiation() {
@@ -14,11 +14,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
 use std::io::{Read, Write};
 use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
 use std::sync::mpsc::{
     sync_channel, Receiver, RecvError, RecvTimeoutError, SyncSender, TryRecvError, TrySendError,
 };
@@ -75,6 +77,10 @@ pub const BLOCK_REREQUEST_INTERVAL: u64 = 60;
 #[cfg(test)]
 pub const BLOCK_REREQUEST_INTERVAL: u64 = 30;
 
+/// When adaptively tuning the number of concurrent requests, never go above this many times the
+/// configured number.
+pub const ADAPTIVE_MAX_INFLIGHT_FACTOR: u64 = 4;
+
 /// This module is responsible for downloading blocks and microblocks from other peers, using block
 /// inventory state (see src/net/inv.rs)
 
@@ -175,6 +181,40 @@ impl std::fmt::Display for BlockRequestKey {
     }
 }
 
+/// How the downloader orders the peers it can ask for a given block or microblock stream.  The
+/// first peer is asked first, and the rest are fallbacks for the next download passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockDownloadPeerSelection {
+    /// Random order
+    Random,
+    /// Peers that served blocks fastest first.  Peers we have not downloaded from yet go first, so
+    /// they can be measured.
+    Latency,
+    /// Peers with the highest burnchain tip first
+    Height,
+}
+
+impl FromStr for BlockDownloadPeerSelection {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "latency" => Ok(Self::Latency),
+            "height" => Ok(Self::Height),
+            _ => Err("Unknown block download peer selection strategy"),
+        }
+    }
+}
+
+/// How well a peer served block and microblock stream requests
+#[derive(Debug, Clone, Default, PartialEq)]
+struct PeerDownloadResults {
+    served: u64,
+    failed: u64,
+    /// the peer's smoothed latency when it last served a request
+    latency_ms: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum BlockDownloaderState {
     DNSLookupBegin,
@@ -212,6 +252,26 @@ pub struct BlockDownloader {
 
     /// Maximum number of concurrent requests
     max_inflight_requests: u64,
+    /// Maximum number of concurrent requests to the same peer (0 for no limit)
+    max_inflight_requests_per_peer: u64,
+    /// Whether or not to tune `max_inflight_requests` to how well download passes go, and the
+    /// highest it may go
+    adaptive_inflight_requests: bool,
+    max_inflight_requests_ceiling: u64,
+    /// How to order the peers to ask for each block
+    peer_selection: BlockDownloadPeerSelection,
+    /// Smoothed time, in milliseconds, each peer's data URL took to serve a block or microblock
+    /// stream
+    peer_latencies: HashMap<UrlString, u64>,
+    /// How well each peer has served blocks and microblock streams since this was last saved to
+    /// the peer DB
+    peer_download_results: HashMap<NeighborKey, PeerDownloadResults>,
+    /// When the current batch of block or microblock requests began, in milliseconds
+    requests_began_at: u128,
+    /// How many block requests the current download pass made, and how many of its requests
+    /// failed
+    num_block_requests: u64,
+    num_failed_requests: u64,
 
     /// Block requests to try, grouped by block, keyed by sortition height
     blocks_to_try: HashMap<u64, VecDeque<BlockRequestKey>>,
@@ -251,6 +311,9 @@ impl BlockDownloader {
         dns_timeout: u128,
         download_interval: u64,
         max_inflight_requests: u64,
+        max_inflight_requests_per_peer: u64,
+        adaptive_inflight_requests: bool,
+        peer_selection: BlockDownloadPeerSelection,
     ) -> BlockDownloader {
         BlockDownloader {
             state: BlockDownloaderState::DNSLookupBegin,
@@ -269,6 +332,16 @@ impl BlockDownloader {
             last_inv_update_at: 0,
 
             max_inflight_requests: max_inflight_requests,
+            max_inflight_requests_per_peer,
+            adaptive_inflight_requests,
+            max_inflight_requests_ceiling: max_inflight_requests
+                .saturating_mul(ADAPTIVE_MAX_INFLIGHT_FACTOR),
+            peer_selection,
+            peer_latencies: HashMap::new(),
+            peer_download_results: HashMap::new(),
+            requests_began_at: 0,
+            num_block_requests: 0,
+            num_failed_requests: 0,
             blocks_to_try: HashMap::new(),
             microblocks_to_try: HashMap::new(),
 
@@ -423,6 +496,9 @@ impl BlockDownloader {
         assert_eq!(self.state, BlockDownloaderState::GetBlocksBegin);
 
         // don't touch blocks-to-try -- that's managed by the peer network directly.
+        self.num_block_requests = requests.len() as u64;
+        self.num_failed_requests = 0;
+        self.requests_began_at = get_epoch_time_ms();
         self.getblock_requests = requests;
         self.state = BlockDownloaderState::GetBlocksFinish;
     }
@@ -510,6 +586,16 @@ impl BlockDownloader {
                                                 &block_key.consensus_hash,
                                                 block.block_hash()
                                             );
+                                            BlockDownloader::record_peer_latency(
+                                                &mut self.peer_latencies,
+                                                self.requests_began_at,
+                                                &block_key.data_url,
+                                            );
+                                            BlockDownloader::record_peer_served(
+                                                &mut self.peer_download_results,
+                                                &self.peer_latencies,
+                                                &block_key,
+                                            );
                                             self.blocks.insert(block_key, block);
                                         }
                                     }
@@ -552,6 +638,7 @@ impl BlockDownloader {
     pub fn getmicroblocks_begin(&mut self, requests: HashMap<BlockRequestKey, usize>) -> () {
         assert_eq!(self.state, BlockDownloaderState::GetMicroblocksBegin);
 
+        self.requests_began_at = get_epoch_time_ms();
         self.getmicroblocks_requests = requests;
         self.state = BlockDownloaderState::GetMicroblocksFinish;
     }
@@ -639,6 +726,16 @@ impl BlockDownloader {
                                                 &block_key.index_block_hash,
                                                 microblocks[0].block_hash()
                                             );
+                                            BlockDownloader::record_peer_latency(
+                                                &mut self.peer_latencies,
+                                                self.requests_began_at,
+                                                &block_key.data_url,
+                                            );
+                                            BlockDownloader::record_peer_served(
+                                                &mut self.peer_download_results,
+                                                &self.peer_latencies,
+                                                &block_key,
+                                            );
                                             self.microblocks.insert(block_key, microblocks);
                                         }
                                     }
@@ -882,12 +979,72 @@ impl BlockDownloader {
         Ok(neighbors)
     }
 
+    /// Fold how long a peer took to serve a block or microblock stream, since requests began at
+    /// `requests_began_at`, into its latency
+    fn record_peer_latency(
+        peer_latencies: &mut HashMap<UrlString, u64>,
+        requests_began_at: u128,
+        data_url: &UrlString,
+    ) {
+        let latency = get_epoch_time_ms().saturating_sub(requests_began_at) as u64;
+        let smoothed = match peer_latencies.get(data_url) {
+            Some(last_latency) => (last_latency.saturating_mul(3) + latency) / 4,
+            None => latency,
+        };
+        peer_latencies.insert(data_url.clone(), smoothed);
+    }
+
+    /// Count a block or microblock stream that a peer served us
+    fn record_peer_served(
+        peer_download_results: &mut HashMap<NeighborKey, PeerDownloadResults>,
+        peer_latencies: &HashMap<UrlString, u64>,
+        block_key: &BlockRequestKey,
+    ) {
+        let results = peer_download_results
+            .entry(block_key.neighbor.clone())
+            .or_default();
+        results.served += 1;
+        results.latency_ms = peer_latencies.get(&block_key.data_url).cloned();
+    }
+
+    /// If adaptive, tune the number of concurrent requests to the download pass that just
+    /// finished: back off by half if any peer failed us, and ask for one more block at a time if
+    /// every block request of a full pass succeeded.
+    fn tune_max_inflight_requests(&mut self) {
+        if !self.adaptive_inflight_requests {
+            return;
+        }
+        let num_failed_requests =
+            self.num_failed_requests + (self.dead_peers.len() + self.broken_peers.len()) as u64;
+        if num_failed_requests > 0 {
+            self.max_inflight_requests = cmp::max(1, self.max_inflight_requests / 2);
+        } else if self.num_block_requests >= self.max_inflight_requests
+            && (self.blocks.len() as u64) >= self.num_block_requests
+        {
+            self.max_inflight_requests = cmp::min(
+                self.max_inflight_requests_ceiling,
+                self.max_inflight_requests + 1,
+            );
+        }
+        debug!(
+            "Downloader will make up to {} concurrent requests",
+            self.max_inflight_requests
+        );
+    }
+
     /// Clear out broken peers that told us they had blocks, but didn't serve them.
     fn clear_broken_peers(&mut self) -> (Vec<usize>, Vec<NeighborKey>) {
         // remove dead/broken peers
         let mut disconnect = vec![];
         let mut disconnect_neighbors = vec![];
 
+        self.num_failed_requests += (self.broken_peers.len() + self.dead_peers.len()) as u64;
+        for neighbor in self.broken_neighbors.iter() {
+            self.peer_download_results
+                .entry(neighbor.clone())
+                .or_default()
+                .failed += 1;
+        }
         disconnect.append(&mut self.broken_peers);
         disconnect.append(&mut self.dead_peers);
         disconnect_neighbors.append(&mut self.broken_neighbors);
@@ -1440,7 +1597,7 @@ impl PeerNetwork {
                 neighbors.len()
             );
 
-            (&mut neighbors[..]).shuffle(&mut thread_rng());
+            self.order_download_peers(downloader, &mut neighbors);
 
             let mut requests = VecDeque::new();
             for nk in neighbors.drain(..) {
@@ -1528,6 +1685,31 @@ impl PeerNetwork {
         Ok(blocks_to_try)
     }
 
+    /// Order the neighbors to ask for a block or microblock stream by the downloader's peer
+    /// selection strategy.  Ties are broken randomly.
+    fn order_download_peers(&self, downloader: &BlockDownloader, neighbors: &mut Vec<NeighborKey>) {
+        (&mut neighbors[..]).shuffle(&mut thread_rng());
+        match downloader.peer_selection {
+            BlockDownloadPeerSelection::Random => {}
+            BlockDownloadPeerSelection::Latency => {
+                neighbors.sort_by_key(|nk| {
+                    self.get_data_url(nk)
+                        .and_then(|data_url| downloader.peer_latencies.get(&data_url).cloned())
+                        .unwrap_or(0)
+                });
+            }
+            BlockDownloadPeerSelection::Height => {
+                neighbors.sort_by_key(|nk| {
+                    cmp::Reverse(
+                        self.get_neighbor_convo(nk)
+                            .map(|convo| convo.get_burnchain_tip_height())
+                            .unwrap_or(0),
+                    )
+                });
+            }
+        }
+    }
+
     /// Make requests for missing anchored blocks
     fn make_block_requests(
         &mut self,
@@ -1972,17 +2154,53 @@ impl PeerNetwork {
         None
     }
 
+    /// Rotate `keys` so that the first one is to a peer with fewer than
+    /// `max_inflight_requests_per_peer` requests in flight (0 for no limit).  Returns false if
+    /// there is no such peer.
+    fn rotate_to_available_peer(
+        keys: &mut VecDeque<BlockRequestKey>,
+        inflight_per_peer: &HashMap<UrlString, u64>,
+        max_inflight_requests_per_peer: u64,
+    ) -> bool {
+        if max_inflight_requests_per_peer == 0 {
+            return true;
+        }
+        for _ in 0..keys.len() {
+            if let Some(key) = keys.front() {
+                let inflight = inflight_per_peer.get(&key.data_url).cloned().unwrap_or(0);
+                if inflight < max_inflight_requests_per_peer {
+                    return true;
+                }
+            }
+            keys.rotate_left(1);
+        }
+        false
+    }
+
     /// Start fetching blocks
     pub fn block_getblocks_begin(&mut self) -> Result<(), net_error> {
         test_debug!("{:?}: block_getblocks_begin", &self.local_peer);
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
             let mut priority = PeerNetwork::prioritize_requests(&downloader.blocks_to_try);
             let mut requests = HashMap::new();
+            let mut inflight_per_peer = HashMap::new();
             for sortition_height in priority.drain(..) {
                 match downloader.blocks_to_try.get_mut(&sortition_height) {
                     Some(ref mut keys) => {
+                        if !PeerNetwork::rotate_to_available_peer(
+                            keys,
+                            &inflight_per_peer,
+                            downloader.max_inflight_requests_per_peer,
+                        ) {
+                            debug!(
+                                "{:?}: All peers with the block at sortition height {} are busy",
+                                &network.local_peer, sortition_height
+                            );
+                            continue;
+                        }
                         match PeerNetwork::begin_request(network, &downloader.dns_lookups, keys) {
                             Some((key, handle)) => {
+                                *inflight_per_peer.entry(key.data_url.clone()).or_insert(0) += 1;
                                 requests.insert(key.clone(), handle);
                             }
                             None => {}
@@ -2016,11 +2234,24 @@ impl PeerNetwork {
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
             let mut priority = PeerNetwork::prioritize_requests(&downloader.microblocks_to_try);
             let mut requests = HashMap::new();
+            let mut inflight_per_peer = HashMap::new();
             for sortition_height in priority.drain(..) {
                 match downloader.microblocks_to_try.get_mut(&sortition_height) {
                     Some(ref mut keys) => {
+                        if !PeerNetwork::rotate_to_available_peer(
+                            keys,
+                            &inflight_per_peer,
+                            downloader.max_inflight_requests_per_peer,
+                        ) {
+                            debug!(
+                                "{:?}: All peers with the microblock stream at sortition height {} are busy",
+                                &network.local_peer, sortition_height
+                            );
+                            continue;
+                        }
                         match PeerNetwork::begin_request(network, &downloader.dns_lookups, keys) {
                             Some((key, handle)) => {
+                                *inflight_per_peer.entry(key.data_url.clone()).or_insert(0) += 1;
                                 requests.insert(key.clone(), handle);
                             }
                             None => {}
@@ -2317,6 +2548,9 @@ impl PeerNetwork {
             self.connection_opts.dns_timeout,
             self.connection_opts.download_interval,
             self.connection_opts.max_inflight_blocks,
+            self.connection_opts.max_inflight_blocks_per_peer,
+            self.connection_opts.adaptive_max_inflight_blocks,
+            self.connection_opts.block_download_peer_selection,
         ));
     }
 
@@ -2325,6 +2559,33 @@ impl PeerNetwork {
         self.attachments_downloader = Some(AttachmentsDownloader::new(initial_batch));
     }
 
+    /// Save how well peers served this download pass's requests to the peer DB, so that a
+    /// restarted node can reconnect to the peers that served it well
+    fn save_peer_download_results(&mut self) -> Result<(), net_error> {
+        let peer_download_results = match self.block_downloader {
+            Some(ref mut downloader) => std::mem::take(&mut downloader.peer_download_results),
+            None => {
+                return Ok(());
+            }
+        };
+        if peer_download_results.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.peerdb.tx_begin()?;
+        for (neighbor, results) in peer_download_results.iter() {
+            PeerDB::record_peer_block_results(
+                &tx,
+                neighbor,
+                results.served,
+                results.failed,
+                results.latency_ms,
+            )?;
+        }
+        tx.commit().map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
     /// Process block downloader lifetime.  Returns the new blocks and microblocks if we get
     /// anything.
     /// Returns:
@@ -2452,6 +2713,10 @@ impl PeerNetwork {
                 }
                 BlockDownloaderState::Done => {
                     // did a pass.
+                    if let Some(ref mut downloader) = self.block_downloader {
+                        downloader.tune_max_inflight_requests();
+                    }
+
                     // do we have more requests?
                     let (
                         blocks_done,
@@ -2483,6 +2748,10 @@ impl PeerNetwork {
             None => (vec![], vec![]),
         };
 
+        if let Err(e) = self.save_peer_download_results() {
+            warn!("Failed to save peer download results: {:?}", &e);
+        }
+
         if done {
             // reset state if we're done
             match self.block_downloader {
@@ -2521,6 +2790,7 @@ pub mod test {
     use crate::burnchains::tests::TestMiner;
     use crate::chainstate::burn::db::sortdb::*;
     use crate::chainstate::burn::operations::*;
+    use crate::chainstate::stacks::db::blocks::test::make_empty_coinbase_block;
     use crate::chainstate::stacks::db::blocks::MINIMUM_TX_FEE_RATE_PER_BYTE;
     use crate::chainstate::stacks::miner::*;
     use crate::chainstate::stacks::tests::*;
@@ -4015,4 +4285,205 @@ pub mod test {
             );
         })
     }
+
+    #[test]
+    #[ignore]
+    pub fn test_get_blocks_and_microblocks_5_peers_star_tuned() {
+        with_timeout(600, || {
+            run_get_blocks_and_microblocks(
+                function_name!(),
+                3270,
+                5,
+                |ref mut peer_configs| {
+                    // a star with peers[0] at the center, with all the blocks.  The other peers
+                    // download from peers[0] no more than two blocks at a time, but tune how
+                    // many blocks they download at once.
+                    assert_eq!(peer_configs.len(), 5);
+                    let mut neighbors = vec![];
+
+                    for p in peer_configs.iter_mut() {
+                        p.connection_opts.disable_block_advertisement = true;
+                        p.connection_opts.max_clients_per_host = 30;
+                        p.connection_opts.max_inflight_blocks_per_peer = 2;
+                        p.connection_opts.adaptive_max_inflight_blocks = true;
+                        p.connection_opts.block_download_peer_selection =
+                            BlockDownloadPeerSelection::Latency;
+                    }
+
+                    let peer_0 = peer_configs[0].to_neighbor();
+                    for i in 1..peer_configs.len() {
+                        neighbors.push(peer_configs[i].to_neighbor());
+                        peer_configs[i].add_neighbor(&peer_0);
+                    }
+
+                    for n in neighbors.drain(..) {
+                        peer_configs[0].add_neighbor(&n);
+                    }
+                },
+                |num_blocks, ref mut peers| {
+                    // build up block data to replicate
+                    let mut block_data = vec![];
+                    for _ in 0..num_blocks {
+                        let (mut burn_ops, stacks_block, microblocks) =
+                            peers[0].make_default_tenure();
+
+                        let (_, burn_header_hash, consensus_hash) =
+                            peers[0].next_burnchain_block(burn_ops.clone());
+                        peers[0].process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+                        TestPeer::set_ops_burn_header_hash(&mut burn_ops, &burn_header_hash);
+
+                        for i in 1..peers.len() {
+                            peers[i].next_burnchain_block_raw(burn_ops.clone());
+                        }
+
+                        let sn = SortitionDB::get_canonical_burn_chain_tip(
+                            &peers[0].sortdb.as_ref().unwrap().conn(),
+                        )
+                        .unwrap();
+                        block_data.push((
+                            sn.consensus_hash.clone(),
+                            Some(stacks_block),
+                            Some(microblocks),
+                        ));
+                    }
+                    block_data
+                },
+                |_| {},
+                |peer| {
+                    // check peer health
+                    // nothing should break
+                    match peer.network.block_downloader {
+                        Some(ref dl) => {
+                            assert_eq!(dl.broken_peers.len(), 0);
+                            assert_eq!(dl.dead_peers.len(), 0);
+                            assert!(dl.max_inflight_requests <= dl.max_inflight_requests_ceiling);
+                        }
+                        None => {}
+                    }
+                    true
+                },
+                |_| true,
+            );
+        })
+    }
+
+    fn make_test_request_key(data_url: &str, sortition_height: u64) -> BlockRequestKey {
+        BlockRequestKey::new(
+            NeighborKey {
+                peer_version: 0,
+                network_id: 0,
+                addrbytes: PeerAddress([0u8; 16]),
+                port: 0,
+            },
+            UrlString::try_from(data_url).unwrap(),
+            ConsensusHash([0u8; 20]),
+            BlockHeaderHash([0u8; 32]),
+            StacksBlockId([0u8; 32]),
+            None,
+            None,
+            sortition_height,
+            BlockRequestKeyKind::Block,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_rotate_to_available_peer() {
+        let mut keys: VecDeque<_> = ["http://peer-1", "http://peer-2", "http://peer-3"]
+            .iter()
+            .map(|url| make_test_request_key(url, 1))
+            .collect();
+        let mut inflight_per_peer = HashMap::new();
+        inflight_per_peer.insert(UrlString::try_from("http://peer-1").unwrap(), 2);
+        inflight_per_peer.insert(UrlString::try_from("http://peer-2").unwrap(), 1);
+
+        // no limit
+        assert!(PeerNetwork::rotate_to_available_peer(
+            &mut keys,
+            &inflight_per_peer,
+            0
+        ));
+        assert_eq!(keys[0].data_url.as_str(), "http://peer-1");
+
+        assert!(PeerNetwork::rotate_to_available_peer(
+            &mut keys,
+            &inflight_per_peer,
+            2
+        ));
+        assert_eq!(keys[0].data_url.as_str(), "http://peer-2");
+        assert_eq!(keys.len(), 3);
+
+        inflight_per_peer.insert(UrlString::try_from("http://peer-3").unwrap(), 1);
+        assert!(!PeerNetwork::rotate_to_available_peer(
+            &mut keys,
+            &inflight_per_peer,
+            1
+        ));
+        assert_eq!(keys.len(), 3);
+    }
+
+    #[test]
+    fn test_tune_max_inflight_requests() {
+        let mut downloader =
+            BlockDownloader::new(0, 0, 2, 0, true, BlockDownloadPeerSelection::Random);
+        assert_eq!(downloader.max_inflight_requests_ceiling, 8);
+
+        // every request of a full pass succeeded
+        let mut requests = HashMap::new();
+        for i in 0..2 {
+            let key = make_test_request_key("http://peer-1", i);
+            requests.insert(key.clone(), i as usize);
+            downloader
+                .blocks
+                .insert(key, make_empty_coinbase_block(&StacksPrivateKey::new()));
+        }
+        downloader.state = BlockDownloaderState::GetBlocksBegin;
+        downloader.getblocks_begin(requests);
+        downloader.tune_max_inflight_requests();
+        assert_eq!(downloader.max_inflight_requests, 3);
+
+        // a request failed
+        downloader.dead_peers.push(1);
+        downloader.tune_max_inflight_requests();
+        assert_eq!(downloader.max_inflight_requests, 1);
+
+        // never goes above the ceiling
+        downloader.dead_peers.clear();
+        downloader.num_failed_requests = 0;
+        downloader.max_inflight_requests = 8;
+        downloader.num_block_requests = 8;
+        for i in 2..8 {
+            downloader.blocks.insert(
+                make_test_request_key("http://peer-1", i),
+                make_empty_coinbase_block(&StacksPrivateKey::new()),
+            );
+        }
+        downloader.tune_max_inflight_requests();
+        assert_eq!(downloader.max_inflight_requests, 8);
+
+        // not adaptive
+        let mut downloader =
+            BlockDownloader::new(0, 0, 2, 0, false, BlockDownloadPeerSelection::Random);
+        downloader.dead_peers.push(1);
+        downloader.tune_max_inflight_requests();
+        assert_eq!(downloader.max_inflight_requests, 2);
+    }
+
+    #[test]
+    fn test_parse_block_download_peer_selection() {
+        assert_eq!(
+            "random".parse::<BlockDownloadPeerSelection>(),
+            Ok(BlockDownloadPeerSelection::Random)
+        );
+        assert_eq!(
+            "latency".parse::<BlockDownloadPeerSelection>(),
+            Ok(BlockDownloadPeerSelection::Latency)
+        );
+        assert_eq!(
+            "height".parse::<BlockDownloadPeerSelection>(),
+            Ok(BlockDownloadPeerSelection::Height)
+        );
+        assert!("fastest".parse::<BlockDownloadPeerSelection>().is_err());
+    }
 }
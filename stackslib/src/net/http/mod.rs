@@ -24,7 +24,7 @@ pub mod stream;
 mod tests;
 
 use std::collections::BTreeMap;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::str::FromStr;
 use std::{fmt, io};
 
@@ -47,7 +47,7 @@ pub use crate::net::http::request::{
 pub use crate::net::http::response::{
     HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
 };
-pub use crate::net::http::stream::HttpChunkGenerator;
+pub use crate::net::http::stream::{HttpChunkGenerator, HttpGzipChunkGenerator};
 
 #[derive(Debug)]
 pub enum Error {
@@ -190,6 +190,112 @@ impl FromStr for HttpContentType {
     }
 }
 
+/// supported HTTP content encodings
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum HttpContentEncoding {
+    Identity,
+    Gzip,
+}
+
+impl fmt::Display for HttpContentEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl HttpContentEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            HttpContentEncoding::Identity => "identity",
+            HttpContentEncoding::Gzip => "gzip",
+        }
+    }
+
+    /// Pick the content encoding to use for a response, given the value of the request's
+    /// `Accept-Encoding` header.  Codings with a `q=0` weight are never chosen, and we fall back
+    /// to `identity` if the client does not accept any coding we support.
+    pub fn negotiate(accept_encoding: &str) -> HttpContentEncoding {
+        let mut best = HttpContentEncoding::Identity;
+        let mut best_weight = 0.0f64;
+        for coding in accept_encoding.split(',') {
+            let mut parts = coding.split(';');
+            let name = parts.next().unwrap_or("").trim().to_lowercase();
+            let mut weight = 1.0f64;
+            for param in parts {
+                let param = param.trim().to_lowercase();
+                if let Some(q) = param.strip_prefix("q=") {
+                    weight = q.trim().parse::<f64>().unwrap_or(0.0);
+                }
+            }
+            if weight <= 0.0 {
+                continue;
+            }
+            let encoding = match name.as_str() {
+                "identity" => HttpContentEncoding::Identity,
+                "gzip" | "x-gzip" | "*" => HttpContentEncoding::Gzip,
+                _ => continue,
+            };
+            if weight > best_weight {
+                best = encoding;
+                best_weight = weight;
+            }
+        }
+        best
+    }
+
+    /// Encode a byte buffer with this content encoding
+    pub fn encode(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match *self {
+            HttpContentEncoding::Identity => Ok(bytes.to_vec()),
+            HttpContentEncoding::Gzip => {
+                let mut encoder =
+                    libflate::gzip::Encoder::new(Vec::new()).map_err(Error::WriteError)?;
+                encoder.write_all(bytes).map_err(Error::WriteError)?;
+                encoder.finish().into_result().map_err(Error::WriteError)
+            }
+        }
+    }
+
+    /// Decode a byte buffer that was encoded with this content encoding, reading at most
+    /// `max_len` decoded bytes.
+    pub fn decode(&self, bytes: &[u8], max_len: u64) -> Result<Vec<u8>, Error> {
+        match *self {
+            HttpContentEncoding::Identity => Ok(bytes.to_vec()),
+            HttpContentEncoding::Gzip => {
+                let decoder = libflate::gzip::Decoder::new(bytes).map_err(Error::ReadError)?;
+                let mut decoded = vec![];
+                decoder
+                    .take(max_len.saturating_add(1))
+                    .read_to_end(&mut decoded)
+                    .map_err(Error::ReadError)?;
+                if decoded.len() as u64 > max_len {
+                    return Err(Error::DecodeError(
+                        "Decoded HTTP body is too big".to_string(),
+                    ));
+                }
+                Ok(decoded)
+            }
+        }
+    }
+}
+
+impl FromStr for HttpContentEncoding {
+    type Err = CodecError;
+
+    fn from_str(header: &str) -> Result<HttpContentEncoding, CodecError> {
+        let s = header.trim().to_lowercase();
+        if s == "identity" {
+            Ok(HttpContentEncoding::Identity)
+        } else if s == "gzip" || s == "x-gzip" {
+            Ok(HttpContentEncoding::Gzip)
+        } else {
+            Err(CodecError::DeserializeError(
+                "Unsupported HTTP content encoding".to_string(),
+            ))
+        }
+    }
+}
+
 /// Write out a set of HTTP headers to the given Write implementation
 pub fn write_headers<W: Write>(
     fd: &mut W,
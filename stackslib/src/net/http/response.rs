@@ -33,8 +33,8 @@ use crate::net::http::common::{
     HttpReservedHeader, HTTP_PREAMBLE_MAX_ENCODED_SIZE, HTTP_PREAMBLE_MAX_NUM_HEADERS,
 };
 use crate::net::http::request::{HttpRequestContents, HttpRequestPreamble};
-use crate::net::http::stream::HttpChunkGenerator;
-use crate::net::http::{write_headers, Error, HttpContentType, HttpVersion};
+use crate::net::http::stream::{HttpChunkGenerator, HttpGzipChunkGenerator};
+use crate::net::http::{write_headers, Error, HttpContentEncoding, HttpContentType, HttpVersion};
 
 /// HTTP response preamble.  This captures all HTTP header information, but in a way that
 /// certain fields that nodes rely on are guaranteed to have correct, sensible values.
@@ -111,6 +111,23 @@ impl HttpResponseContents {
         }
     }
 
+    /// Encode these response contents with the given content encoding.
+    /// In-RAM contents are encoded in one go; streamed contents are encoded as they are
+    /// generated.
+    pub fn try_encode(self, encoding: HttpContentEncoding) -> Result<HttpResponseContents, Error> {
+        match (self, encoding) {
+            (contents, HttpContentEncoding::Identity) => Ok(contents),
+            (HttpResponseContents::RAM(bytes), encoding) => {
+                Ok(HttpResponseContents::from_ram(encoding.encode(&bytes)?))
+            }
+            (HttpResponseContents::Stream(stream), HttpContentEncoding::Gzip) => {
+                let generator =
+                    HttpGzipChunkGenerator::new(stream.generator).map_err(Error::WriteError)?;
+                Ok(HttpResponseContents::from_stream(Box::new(generator)))
+            }
+        }
+    }
+
     /// Write data for this to a pipe writer, which buffers it up.
     /// Return Ok(Some(..)) if there is mroe data to send.
     /// Once all data is sent, return Ok(None)
@@ -324,6 +341,15 @@ impl HttpResponsePreamble {
         }
     }
 
+    /// Get the content encoding of this response's body.
+    /// Returns Err(..) if the `Content-Encoding` header names an encoding we don't support.
+    pub fn content_encoding(&self) -> Result<HttpContentEncoding, Error> {
+        match self.headers.get("content-encoding") {
+            Some(value) => Ok(value.parse::<HttpContentEncoding>()?),
+            None => Ok(HttpContentEncoding::Identity),
+        }
+    }
+
     pub fn add_CORS_headers(&mut self) -> () {
         self.headers
             .insert("Access-Control-Allow-Origin".to_string(), "*".to_string());
@@ -75,3 +75,59 @@ pub trait Streamer {
     /// how much data has been sent so far.
     fn add_bytes(&mut self, nw: u64);
 }
+
+/// Chunk generator that gzip-compresses the output of another chunk generator, so that streamed
+/// responses (e.g. blocks) can be sent with `Content-Encoding: gzip`.
+pub struct HttpGzipChunkGenerator {
+    inner: Box<dyn HttpChunkGenerator>,
+    encoder: Option<libflate::gzip::Encoder<Vec<u8>>>,
+}
+
+impl HttpGzipChunkGenerator {
+    pub fn new(inner: Box<dyn HttpChunkGenerator>) -> Result<HttpGzipChunkGenerator, io::Error> {
+        Ok(HttpGzipChunkGenerator {
+            inner,
+            encoder: Some(libflate::gzip::Encoder::new(Vec::new())?),
+        })
+    }
+}
+
+impl HttpChunkGenerator for HttpGzipChunkGenerator {
+    fn hint_chunk_size(&self) -> usize {
+        self.inner.hint_chunk_size()
+    }
+
+    /// Compress the inner generator's chunks.  The encoder buffers its input, so this keeps
+    /// pulling chunks until it has compressed output to hand back (an empty chunk would
+    /// otherwise signal the end of the stream).
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        loop {
+            let encoder = match self.encoder.as_mut() {
+                Some(encoder) => encoder,
+                None => {
+                    // already finished
+                    return Ok(vec![]);
+                }
+            };
+
+            let chunk = self.inner.generate_next_chunk()?;
+            if chunk.is_empty() {
+                // inner stream is done; flush the encoder and write the gzip trailer
+                let encoder = self.encoder.take().expect("FATAL: encoder state poisoned");
+                return encoder
+                    .finish()
+                    .into_result()
+                    .map_err(|e| format!("Failed to finish gzip stream: {:?}", &e));
+            }
+
+            encoder
+                .write_all(&chunk)
+                .map_err(|e| format!("Failed to gzip chunk: {:?}", &e))?;
+
+            let compressed = std::mem::take(encoder.as_inner_mut());
+            if !compressed.is_empty() {
+                return Ok(compressed);
+            }
+        }
+    }
+}
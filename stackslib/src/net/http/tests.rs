@@ -19,7 +19,9 @@ use stacks_common::types::net::{PeerAddress, PeerHost};
 
 use crate::net::http::common::{HTTP_PREAMBLE_MAX_ENCODED_SIZE, HTTP_PREAMBLE_MAX_NUM_HEADERS};
 use crate::net::http::{
-    HttpContentType, HttpRequestPreamble, HttpReservedHeader, HttpResponsePreamble, HttpVersion,
+    HttpChunkGenerator, HttpContentEncoding, HttpContentType, HttpGzipChunkGenerator,
+    HttpRequestPreamble, HttpReservedHeader, HttpResponseContents, HttpResponsePreamble,
+    HttpVersion,
 };
 
 #[test]
@@ -666,3 +668,109 @@ fn test_http_live_headers() {
         );
     }
 }
+
+#[test]
+fn test_http_content_encoding_negotiate() {
+    let tests = vec![
+        ("", HttpContentEncoding::Identity),
+        ("identity", HttpContentEncoding::Identity),
+        ("gzip", HttpContentEncoding::Gzip),
+        ("GZIP", HttpContentEncoding::Gzip),
+        ("deflate, gzip", HttpContentEncoding::Gzip),
+        ("br, zstd", HttpContentEncoding::Identity),
+        ("*", HttpContentEncoding::Gzip),
+        ("gzip;q=0", HttpContentEncoding::Identity),
+        ("gzip; q=0.5, identity", HttpContentEncoding::Identity),
+        ("identity;q=0.1, gzip;q=0.9", HttpContentEncoding::Gzip),
+        ("gzip;q=garbage", HttpContentEncoding::Identity),
+    ];
+
+    for (accept_encoding, expected) in tests.into_iter() {
+        assert_eq!(
+            HttpContentEncoding::negotiate(accept_encoding),
+            expected,
+            "Accept-Encoding: {}",
+            accept_encoding
+        );
+    }
+}
+
+#[test]
+fn test_http_content_encoding_gzip_ram() {
+    let body = "hello world ".repeat(1000).into_bytes();
+    let contents = HttpResponseContents::from_ram(body.clone())
+        .try_encode(HttpContentEncoding::Gzip)
+        .unwrap();
+
+    let encoded = match contents {
+        HttpResponseContents::RAM(bytes) => bytes,
+        _ => panic!("Expected RAM contents"),
+    };
+    assert!(encoded.len() < body.len());
+
+    let decoded = HttpContentEncoding::Gzip
+        .decode(&encoded, body.len() as u64)
+        .unwrap();
+    assert_eq!(decoded, body);
+
+    // decoding is bounded
+    assert!(HttpContentEncoding::Gzip
+        .decode(&encoded, (body.len() - 1) as u64)
+        .is_err());
+
+    // a response that names an encoding is parsed as such
+    let mut preamble = HttpResponsePreamble::raw_ok_json(HttpVersion::Http11, true);
+    assert_eq!(
+        preamble.content_encoding().unwrap(),
+        HttpContentEncoding::Identity
+    );
+    preamble.add_header("Content-Encoding".to_string(), "gzip".to_string());
+    assert_eq!(
+        preamble.content_encoding().unwrap(),
+        HttpContentEncoding::Gzip
+    );
+    preamble.add_header("Content-Encoding".to_string(), "zstd".to_string());
+    assert!(preamble.content_encoding().is_err());
+}
+
+struct TestChunkGenerator {
+    chunks: Vec<Vec<u8>>,
+}
+
+impl HttpChunkGenerator for TestChunkGenerator {
+    fn hint_chunk_size(&self) -> usize {
+        4096
+    }
+
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        if self.chunks.is_empty() {
+            return Ok(vec![]);
+        }
+        Ok(self.chunks.remove(0))
+    }
+}
+
+#[test]
+fn test_http_content_encoding_gzip_stream() {
+    let chunks: Vec<Vec<u8>> = (0..100u8).map(|i| vec![i; 4096]).collect();
+    let body: Vec<u8> = chunks.iter().flatten().cloned().collect();
+
+    let mut generator =
+        HttpGzipChunkGenerator::new(Box::new(TestChunkGenerator { chunks })).unwrap();
+    let mut encoded = vec![];
+    loop {
+        let chunk = generator.generate_next_chunk().unwrap();
+        if chunk.is_empty() {
+            break;
+        }
+        encoded.extend_from_slice(&chunk);
+    }
+
+    // stays finished
+    assert!(generator.generate_next_chunk().unwrap().is_empty());
+
+    let decoded = HttpContentEncoding::Gzip
+        .decode(&encoded, body.len() as u64)
+        .unwrap();
+    assert_eq!(decoded, body);
+}
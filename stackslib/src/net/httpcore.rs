@@ -45,9 +45,10 @@ use crate::core::{MemPoolDB, StacksEpoch};
 use crate::net::connection::ConnectionOptions;
 use crate::net::http::common::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
 use crate::net::http::{
-    http_reason, Error as HttpError, HttpBadRequest, HttpContentType, HttpErrorResponse,
-    HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
-    HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
+    http_reason, Error as HttpError, HttpBadRequest, HttpContentEncoding, HttpContentType,
+    HttpErrorResponse, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+    HttpVersion,
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::server::HttpPeer;
@@ -58,6 +59,9 @@ const CHUNK_BUF_LEN: usize = 32768;
 /// canonical stacks tip height header
 pub const STACKS_HEADER_HEIGHT: &'static str = "X-Canonical-Stacks-Tip-Height";
 
+/// header identifying the Stacks block whose state a response reflects
+pub const STACKS_HEADER_TIP: &'static str = "X-Stacks-Tip";
+
 /// request ID header
 pub const STACKS_REQUEST_ID: &'static str = "X-Request-Id";
 
@@ -67,7 +71,9 @@ pub const STACKS_REQUEST_ID: &'static str = "X-Request-Id";
 /// from non-Stacks nodes (like Gaia hubs, CDNs, vanilla HTTP servers, and so on).
 pub const HTTP_REQUEST_ID_RESERVED: u32 = 0;
 
-/// All representations of the `tip=` query parameter value
+/// All representations of the `tip=` query parameter value.
+/// `tip=unconfirmed` (or its older alias `tip=latest`) selects the node's unconfirmed state, i.e.
+/// the canonical anchored tip plus any microblocks built on it.
 #[derive(Debug, Clone, PartialEq)]
 pub enum TipRequest {
     UseLatestAnchoredTip,
@@ -89,7 +95,7 @@ impl ToString for TipRequest {
 
 impl From<&str> for TipRequest {
     fn from(s: &str) -> TipRequest {
-        if s == "latest" {
+        if s == "latest" || s == "unconfirmed" {
             TipRequest::UseLatestUnconfirmedTip
         } else if let Ok(block_id) = StacksBlockId::from_hex(s) {
             TipRequest::SpecificTip(block_id)
@@ -109,6 +115,10 @@ pub trait HttpPreambleExtensions {
     fn get_canonical_stacks_tip_height(&self) -> Option<u32>;
     /// Get the request ID
     fn get_request_id(&self) -> Option<u32>;
+    /// Set the Stacks block whose state the message reflects
+    fn set_stacks_tip(&mut self, tip: &StacksBlockId);
+    /// Get the Stacks block whose state the message reflects
+    fn get_stacks_tip(&self) -> Option<StacksBlockId>;
 }
 
 impl HttpPreambleExtensions for HttpRequestPreamble {
@@ -140,6 +150,17 @@ impl HttpPreambleExtensions for HttpRequestPreamble {
         self.get_header("X-Request-Id".to_string())
             .and_then(|req| req.parse::<u32>().ok())
     }
+
+    /// Set the Stacks block whose state the message reflects
+    fn set_stacks_tip(&mut self, tip: &StacksBlockId) {
+        self.add_header(STACKS_HEADER_TIP.into(), format!("{}", tip));
+    }
+
+    /// Get the Stacks block whose state the message reflects
+    fn get_stacks_tip(&self) -> Option<StacksBlockId> {
+        self.get_header(STACKS_HEADER_TIP.to_string())
+            .and_then(|hdr| StacksBlockId::from_hex(&hdr).ok())
+    }
 }
 
 impl HttpPreambleExtensions for HttpResponsePreamble {
@@ -171,6 +192,17 @@ impl HttpPreambleExtensions for HttpResponsePreamble {
         self.get_header("X-Request-Id".to_string())
             .and_then(|req| req.parse::<u32>().ok())
     }
+
+    /// Set the Stacks block whose state the message reflects
+    fn set_stacks_tip(&mut self, tip: &StacksBlockId) {
+        self.add_header(STACKS_HEADER_TIP.into(), format!("{}", tip));
+    }
+
+    /// Get the Stacks block whose state the message reflects
+    fn get_stacks_tip(&self) -> Option<StacksBlockId> {
+        self.get_header(STACKS_HEADER_TIP.to_string())
+            .and_then(|hdr| StacksBlockId::from_hex(&hdr).ok())
+    }
 }
 
 /// This module contains request helpers for decoding common data found in the request path regex captures.
@@ -339,6 +371,20 @@ impl HttpRequestContentsExtensions for HttpRequestContents {
     }
 }
 
+/// Groups of RPC endpoints that can each be gated behind an auth token, so a node can expose
+/// some of its endpoints without exposing the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCEndpointGroup {
+    /// Endpoints that only read chain, mempool or StackerDB state
+    PublicRead,
+    /// Transaction submission
+    TxSubmission,
+    /// Block, microblock and StackerDB chunk submission
+    BlockProposal,
+    /// Endpoints that reveal this node's own peers, miner, and health
+    Admin,
+}
+
 /// Work around Clone blanket implementations not being object-safe
 pub trait RPCRequestHandlerClone {
     fn clone_rpc_handler_box(&self) -> Box<dyn RPCRequestHandler>;
@@ -364,6 +410,10 @@ pub trait RPCRequestHandler: HttpRequest + HttpResponse + RPCRequestHandlerClone
     /// Reset the RPC handler.  This clears any internal state this handler stored between calls to
     /// `try_handle_request()`
     fn restart(&mut self);
+    /// Which endpoint group this handler belongs to, for access control
+    fn endpoint_group(&self) -> RPCEndpointGroup {
+        RPCEndpointGroup::PublicRead
+    }
     /// Instantiate the HTTP response headers and body from a request
     fn try_handle_request(
         &mut self,
@@ -872,6 +922,14 @@ pub struct StacksHttp {
     pub maximum_call_argument_size: u32,
     /// Maximum execution budget of a read-only call
     pub read_only_call_limit: ExecutionCost,
+    /// Maximum depth below the canonical tip at which a read-only call may be evaluated
+    pub read_only_call_max_tip_depth: u64,
+    /// Path prefixes of the endpoints whose responses may be compressed
+    compression_endpoints: Vec<String>,
+    /// In-RAM responses smaller than this are never compressed
+    compression_min_size: u32,
+    /// Auth tokens required by each gated endpoint group
+    rpc_auth_tokens: HashMap<RPCEndpointGroup, String>,
 }
 
 impl StacksHttp {
@@ -887,6 +945,10 @@ impl StacksHttp {
             request_handlers: vec![],
             maximum_call_argument_size: conn_opts.maximum_call_argument_size,
             read_only_call_limit: conn_opts.read_only_call_limit.clone(),
+            read_only_call_max_tip_depth: conn_opts.read_only_call_max_tip_depth,
+            compression_endpoints: conn_opts.http_compression_endpoints.clone(),
+            compression_min_size: conn_opts.http_compression_min_size,
+            rpc_auth_tokens: conn_opts.rpc_auth_tokens.clone(),
         };
         http.register_rpc_methods();
         http
@@ -990,6 +1052,17 @@ impl StacksHttp {
                 continue;
             };
 
+            if let Some(auth_token) = self.rpc_auth_tokens.get(&request.endpoint_group()) {
+                let authorized = preamble
+                    .get_header("authorization".to_string())
+                    .map(|header| auth_token_matches(&header, auth_token))
+                    .unwrap_or(false);
+                if !authorized {
+                    info!("Rejecting unauthorized HTTP request"; "verb" => %verb, "peer_addr" => %self.peer_addr, "path" => %decoded_path);
+                    return Err(NetError::Http(HttpError::Http(401, "Unauthorized".into())));
+                }
+            }
+
             let payload = match request.try_parse_request(
                 preamble,
                 &captures,
@@ -1069,7 +1142,13 @@ impl StacksHttp {
             .request_handlers
             .get(request_handler_index)
             .expect("FATAL: tried to use nonexistent response handler");
-        let payload = parser.try_parse_response(preamble, body)?;
+        let payload = match preamble.content_encoding()? {
+            HttpContentEncoding::Identity => parser.try_parse_response(preamble, body)?,
+            encoding => {
+                let decoded_body = encoding.decode(body, MAX_MESSAGE_LEN as u64)?;
+                parser.try_parse_response(preamble, &decoded_body)?
+            }
+        };
         let response = StacksHttpResponse::new(preamble.clone(), payload);
         return Ok(response);
     }
@@ -1110,7 +1189,7 @@ impl StacksHttp {
         request_handler.restart();
 
         let (response_preamble, response_contents) = match request_result {
-            Ok((rp, rc)) => (rp, rc),
+            Ok((rp, rc)) => self.try_compress_response(&request_preamble, &decoded_path, rp, rc)?,
             Err(NetError::Http(e)) => {
                 return StacksHttpResponse::new_error(&request_preamble, &*e.into_http_error())
                     .try_into_contents()
@@ -1123,6 +1202,48 @@ impl StacksHttp {
         Ok((response_preamble, response_contents))
     }
 
+    /// Compress a successful response to a request, if the endpoint allows it and the client
+    /// accepts an encoding we support.  Small in-RAM responses are sent as-is.
+    pub fn try_compress_response(
+        &self,
+        request_preamble: &HttpRequestPreamble,
+        decoded_path: &str,
+        mut response_preamble: HttpResponsePreamble,
+        response_contents: HttpResponseContents,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        if response_preamble.status_code < 200
+            || response_preamble.status_code >= 300
+            || response_preamble.headers.contains_key("content-encoding")
+        {
+            return Ok((response_preamble, response_contents));
+        }
+        if !self
+            .compression_endpoints
+            .iter()
+            .any(|prefix| decoded_path.starts_with(prefix.as_str()))
+        {
+            return Ok((response_preamble, response_contents));
+        }
+        let encoding = match request_preamble.get_header("accept-encoding".to_string()) {
+            Some(accept_encoding) => HttpContentEncoding::negotiate(&accept_encoding),
+            None => HttpContentEncoding::Identity,
+        };
+        if encoding == HttpContentEncoding::Identity {
+            return Ok((response_preamble, response_contents));
+        }
+        if let Some(len) = response_contents.content_length() {
+            if len < self.compression_min_size {
+                return Ok((response_preamble, response_contents));
+            }
+        }
+
+        let response_contents = response_contents.try_encode(encoding)?;
+        response_preamble.content_length = response_contents.content_length();
+        response_preamble.add_header("Content-Encoding".to_string(), encoding.to_string());
+        response_preamble.add_header("Vary".to_string(), "Accept-Encoding".to_string());
+        Ok((response_preamble, response_contents))
+    }
+
     #[cfg(test)]
     pub fn num_pending(&self) -> usize {
         self.reply.as_ref().map(|_| 1).unwrap_or(0)
@@ -1606,6 +1727,21 @@ impl PeerNetwork {
     }
 }
 
+/// Check an `Authorization:` header value against an endpoint group's auth token.
+/// The header must be exactly the token, with no scheme such as `Bearer`.
+/// The comparison takes the same time wherever the two differ, so a client can't discover the
+/// token a byte at a time by timing rejected requests.
+fn auth_token_matches(header: &str, auth_token: &str) -> bool {
+    if header.len() != auth_token.len() {
+        return false;
+    }
+    header
+        .bytes()
+        .zip(auth_token.bytes())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
 /// Given a raw path, decode it (i.e. if it's url-encoded)
 /// Return the (decoded-path, query-string) on success
 pub fn decode_request_path(path: &str) -> Result<(String, String), NetError> {
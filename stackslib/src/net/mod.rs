@@ -24,6 +24,7 @@ use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use std::{error, fmt, io};
 
 use clarity::vm::analysis::contract_interface_builder::ContractInterface;
@@ -69,6 +70,7 @@ use crate::chainstate::coordinator::Error as coordinator_error;
 use crate::chainstate::stacks::db::blocks::MemPoolRejection;
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::index::Error as marf_error;
+use crate::chainstate::stacks::miner::MinerStatus;
 use crate::chainstate::stacks::{
     Error as chainstate_error, Error as chain_error, StacksBlock, StacksBlockHeader,
     StacksMicroblock, StacksPublicKey, StacksTransaction, TransactionPayload,
@@ -130,6 +132,9 @@ pub mod p2p;
 pub mod poll;
 pub mod prune;
 pub mod relay;
+/// Implements `PeerReputation`, which scores peers on protocol misbehavior so that low-scoring
+/// peers can be deprioritized or banned.
+pub mod reputation;
 pub mod rpc;
 pub mod server;
 pub mod stackerdb;
@@ -618,6 +623,12 @@ pub struct RPCHandlerArgs<'a> {
     pub fee_estimator: Option<&'a dyn FeeEstimator>,
     /// tx runtime cost metric
     pub cost_metric: Option<&'a dyn CostMetric>,
+    /// The burnchain addresses this node's miner sends block-commits from, if it is a miner
+    pub miner_addresses: Vec<String>,
+    /// The height of the burnchain tip, as far as this node has downloaded headers
+    pub bitcoin_tip_height: Option<u64>,
+    /// The status of this node's miner thread, if it is a miner
+    pub miner_status: Option<Arc<Mutex<MinerStatus>>>,
 }
 
 impl<'a> RPCHandlerArgs<'a> {
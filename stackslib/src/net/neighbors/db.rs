@@ -425,19 +425,52 @@ impl NeighborWalkDB for PeerDBNeighborWalk {
         network: &PeerNetwork,
         ibd: bool,
     ) -> Result<Vec<Neighbor>, net_error> {
-        let allowed_peers = if ibd {
-            // only get bootstrap peers (will be randomized)
-            PeerDB::get_bootstrap_peers(
-                &network.peerdb_conn(),
-                network.get_local_peer().network_id,
-            )?
+        let network_id = network.get_local_peer().network_id;
+        let mut allowed_peers = if ibd {
+            // only get bootstrap peers (will be randomized), plus the peers that served us best
+            // before, if so configured
+            let mut bootstrap_peers =
+                PeerDB::get_bootstrap_peers(&network.peerdb_conn(), network_id)?;
+            let max_known_peers = network.get_connection_opts().max_known_bootstrap_peers;
+            if max_known_peers > 0 {
+                let known_peers = PeerDB::get_peers_by_quality(
+                    network.peerdb_conn(),
+                    network_id,
+                    usize::try_from(max_known_peers).unwrap_or(usize::MAX),
+                )?;
+                for (known_peer, _) in known_peers.into_iter() {
+                    if !bootstrap_peers
+                        .iter()
+                        .any(|peer| peer.addr == known_peer.addr)
+                    {
+                        bootstrap_peers.push(known_peer);
+                    }
+                }
+            }
+            bootstrap_peers
         } else {
             // can be any peer marked 'always-allowed' (will be randomized)
-            PeerDB::get_always_allowed_peers(
+            PeerDB::get_always_allowed_peers(network.peerdb_conn(), network_id)?
+        };
+
+        // the caller walks to the last peer, so put the peers that served us best last.  Peers
+        // with the same quality stay in random order.
+        let mut scores = HashMap::new();
+        for peer in allowed_peers.iter() {
+            let quality = PeerDB::get_peer_quality(
                 network.peerdb_conn(),
-                network.get_local_peer().network_id,
+                network_id,
+                &peer.addr.addrbytes,
+                peer.addr.port,
             )?
-        };
+            .unwrap_or_default();
+            scores.insert(peer.addr.clone(), quality.score());
+        }
+        allowed_peers.sort_by(|peer_1, peer_2| {
+            scores[&peer_1.addr]
+                .partial_cmp(&scores[&peer_2.addr])
+                .unwrap_or(cmp::Ordering::Equal)
+        });
         Ok(allowed_peers)
     }
 
@@ -59,6 +59,7 @@ use crate::net::neighbors::*;
 use crate::net::poll::{NetworkPollState, NetworkState};
 use crate::net::prune::*;
 use crate::net::relay::{RelayerStats, *, *};
+use crate::net::reputation::{PeerMisbehavior, PeerReputation};
 use crate::net::server::*;
 use crate::net::stackerdb::{StackerDBConfig, StackerDBSync, StackerDBTx, StackerDBs};
 use crate::net::{Error as net_error, Neighbor, NeighborKey, RPCHandlerArgs, *};
@@ -237,6 +238,7 @@ pub struct PeerNetwork {
     // ongoing messages the network is sending via the p2p interface
     pub relay_handles: HashMap<usize, VecDeque<ReplyHandleP2P>>,
     pub relayer_stats: RelayerStats,
+    pub reputation: PeerReputation,
 
     // handles for other threads to send/receive data to peers
     handles: VecDeque<NetworkHandleServer>,
@@ -420,6 +422,7 @@ impl PeerNetwork {
 
             relay_handles: HashMap::new(),
             relayer_stats: RelayerStats::new(),
+            reputation: PeerReputation::new(),
 
             handles: VecDeque::new(),
             network: None,
@@ -1136,6 +1139,13 @@ impl PeerNetwork {
             RELAY_DUPLICATE_INFERENCE_WARMUP,
         );
 
+        // peers with a poor reputation get the smallest possible chance of being picked
+        for (nk, rank) in outbound_dist.iter_mut().chain(inbound_dist.iter_mut()) {
+            if self.is_peer_deprioritized(nk) {
+                *rank = 1;
+            }
+        }
+
         let mut relay_pubkhs = HashSet::new();
         for rhint in relay_hints {
             relay_pubkhs.insert(rhint.peer.public_key_hash.clone());
@@ -1197,15 +1207,10 @@ impl PeerNetwork {
     pub fn dispatch_request(&mut self, request: NetworkRequest) -> Result<(), net_error> {
         match request {
             NetworkRequest::Ban(neighbor_keys) => {
+                // the relayer asks us to ban peers that sent it invalid blocks
                 for neighbor_key in neighbor_keys.iter() {
                     debug!("Request to ban {:?}", neighbor_key);
-                    match self.events.get(neighbor_key) {
-                        Some(event_id) => {
-                            debug!("Will ban {:?} (event {})", neighbor_key, event_id);
-                            self.bans.insert(*event_id);
-                        }
-                        None => {}
-                    }
+                    self.record_peer_misbehavior(neighbor_key, PeerMisbehavior::InvalidBlock);
                 }
                 Ok(())
             }
@@ -1359,7 +1364,11 @@ impl PeerNetwork {
             disconnect.push(event_id);
 
             let now = get_epoch_time_secs();
-            let penalty = if let Some(neighbor_info) = neighbor_info_opt {
+            let reputation_ban = self.reputation.get_banned_until(&neighbor_key, now);
+            let penalty = if let Some(banned_until) = reputation_ban {
+                // banned for a low reputation score
+                banned_until
+            } else if let Some(neighbor_info) = neighbor_info_opt {
                 if neighbor_info.denied < 0
                     || (neighbor_info.denied as u64) < now + DENY_MIN_BAN_DURATION
                 {
@@ -1702,6 +1711,30 @@ impl PeerNetwork {
             }
         }
 
+        // remember how this outbound session went, so a restarted node can reconnect to the
+        // neighbors that stayed connected
+        let session_opt = self.peers.get(&event_id).and_then(|convo| {
+            if convo.stats.outbound {
+                Some((
+                    convo.stats.last_handshake_time > 0,
+                    convo
+                        .stats
+                        .last_contact_time
+                        .saturating_sub(convo.stats.first_contact_time),
+                ))
+            } else {
+                None
+            }
+        });
+        if let Some((handshake, connected_secs)) = session_opt {
+            if let Err(e) = self.record_peer_sessions(&nk_remove, handshake, connected_secs) {
+                warn!(
+                    "{:?}: Failed to record session with event {}: {:?}",
+                    &self.local_peer, event_id, &e
+                );
+            }
+        }
+
         for nk in nk_remove.into_iter() {
             // remove event state
             self.events.remove(&nk);
@@ -1738,6 +1771,24 @@ impl PeerNetwork {
         self.pending_messages.remove(&event_id);
     }
 
+    /// Record the end of an outbound session with the given neighbors in the peer DB
+    fn record_peer_sessions(
+        &mut self,
+        neighbor_keys: &[NeighborKey],
+        handshake: bool,
+        connected_secs: u64,
+    ) -> Result<(), db_error> {
+        if neighbor_keys.is_empty() {
+            return Ok(());
+        }
+        let tx = self.peerdb.tx_begin()?;
+        for nk in neighbor_keys.iter() {
+            PeerDB::record_peer_session(&tx, nk, handshake, connected_secs)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Deregister by neighbor key
     pub fn deregister_neighbor(&mut self, neighbor_key: &NeighborKey) -> () {
         debug!("Disconnect from {:?}", neighbor_key);
@@ -1750,6 +1801,41 @@ impl PeerNetwork {
         self.deregister_peer(event_id);
     }
 
+    /// Penalize a neighbor's reputation score for protocol misbehavior, and ban it if its score
+    /// drops too low
+    pub fn record_peer_misbehavior(
+        &mut self,
+        neighbor_key: &NeighborKey,
+        misbehavior: PeerMisbehavior,
+    ) -> () {
+        let now = get_epoch_time_secs();
+        let score = self.reputation.record(neighbor_key, misbehavior, now);
+        debug!(
+            "{:?}: Peer {:?} misbehaved ({:?}); score is now {}",
+            &self.local_peer, neighbor_key, &misbehavior, score
+        );
+        if score > self.connection_opts.peer_ban_score {
+            return;
+        }
+
+        let banned_until = now + self.connection_opts.peer_score_ban_duration;
+        info!(
+            "{:?}: Ban peer {:?} until {} for its reputation score of {}",
+            &self.local_peer, neighbor_key, banned_until, score
+        );
+        self.reputation.ban(neighbor_key, banned_until, now);
+        if let Some(event_id) = self.events.get(neighbor_key) {
+            self.bans.insert(*event_id);
+        }
+    }
+
+    /// Is a neighbor's reputation score low enough that we should avoid relaying through it?
+    pub fn is_peer_deprioritized(&self, neighbor_key: &NeighborKey) -> bool {
+        self.reputation
+            .get_score(neighbor_key, get_epoch_time_secs())
+            <= self.connection_opts.peer_deprioritize_score
+    }
+
     /// Deregister and ban a neighbor
     pub fn deregister_and_ban_neighbor(&mut self, neighbor: &NeighborKey) -> () {
         debug!("Disconnect from and ban {:?}", neighbor);
@@ -1966,6 +2052,12 @@ impl PeerNetwork {
                 Ok(unhandled_messages) => unhandled_messages,
             };
 
+            // score any misbehavior the conversation noticed
+            let neighbor_key = convo.to_neighbor_key();
+            for misbehavior in convo.stats.misbehavior.drain(..) {
+                network.record_peer_misbehavior(&neighbor_key, misbehavior);
+            }
+
             if !convo_dead {
                 // (continue) sending out data in this conversation, if the conversation is still
                 // ongoing
@@ -4307,6 +4399,10 @@ impl PeerNetwork {
                         }
                         // not ahead of us -- it's a bad consensus hash
                         debug!("{:?}: Unrecognized consensus hash {}; assuming that {} has a different chain view", &self.local_peer, consensus_hash, outbound_neighbor_key);
+                        self.record_peer_misbehavior(
+                            outbound_neighbor_key,
+                            PeerMisbehavior::StaleData,
+                        );
                         return Ok(None);
                     }
                     Err(net_error::InvalidMessage) => {
@@ -5741,6 +5837,12 @@ impl PeerNetwork {
                     StacksNodeState::new(net, sortdb, chainstate, mempool, handler_args);
                 http.run(network_state, &mut node_state, http_poll_state)
             });
+            // chunks written to hot StackerDB slots go out right away
+            for msg in http_stacks_msgs.iter() {
+                if let StacksMessageType::StackerDBPushChunk(ref chunk_data) = msg {
+                    network.push_stackerdb_hot_chunk(chunk_data, None);
+                }
+            }
             network_result.consume_http_uploads(http_stacks_msgs);
             Ok(())
         })
@@ -1606,17 +1606,18 @@ impl Relayer {
         }
 
         // garbage-collect
-        if chain_height > MEMPOOL_MAX_TRANSACTION_AGE {
-            let min_height = chain_height.saturating_sub(MEMPOOL_MAX_TRANSACTION_AGE);
-            let mut mempool_tx = mempool.tx_begin()?;
-
+        let max_tx_age = mempool.gc_policy.max_tx_age;
+        let mut mempool_tx = mempool.tx_begin()?;
+        if chain_height > max_tx_age {
+            let min_height = chain_height.saturating_sub(max_tx_age);
             debug!(
                 "Remove all transactions beneath block height {}",
                 min_height
             );
             MemPoolDB::garbage_collect(&mut mempool_tx, min_height, event_observer)?;
-            mempool_tx.commit()?;
         }
+        MemPoolDB::enforce_size_limit(&mut mempool_tx, event_observer)?;
+        mempool_tx.commit()?;
         update_stacks_tip_height(chain_height as i64);
 
         Ok(ret)
@@ -0,0 +1,179 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use crate::net::NeighborKey;
+
+/// The score a peer starts with, and the most it can recover to
+pub const PEER_SCORE_MAX: i64 = 100;
+/// A peer's score recovers by one point for each of these many seconds
+pub const PEER_SCORE_RECOVERY_INTERVAL: u64 = 60;
+
+/// Protocol misbehavior that costs a peer some of its reputation score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PeerMisbehavior {
+    /// The peer sent us an invalid block or microblock
+    InvalidBlock,
+    /// The peer sent us a message that violates the protocol
+    MalformedMessage,
+    /// The peer sent us data about a chain view we do not share
+    StaleData,
+    /// The peer pushed us more data than our bandwidth limits allow
+    UnsolicitedFlood,
+}
+
+impl PeerMisbehavior {
+    /// How many points this misbehavior costs
+    pub fn penalty(&self) -> i64 {
+        match self {
+            PeerMisbehavior::InvalidBlock => PEER_SCORE_MAX,
+            PeerMisbehavior::MalformedMessage => 20,
+            PeerMisbehavior::UnsolicitedFlood => 10,
+            PeerMisbehavior::StaleData => 5,
+        }
+    }
+}
+
+/// Reputation of a single peer
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScore {
+    /// score as of `last_update`
+    score: i64,
+    /// when the score was last penalized or reset
+    pub last_update: u64,
+    pub invalid_blocks: u64,
+    pub malformed_messages: u64,
+    pub stale_data: u64,
+    pub unsolicited_floods: u64,
+    /// when the peer's ban for a low score expires, if it was ever banned for one
+    pub banned_until: Option<u64>,
+}
+
+impl PeerScore {
+    fn new(now: u64) -> PeerScore {
+        PeerScore {
+            score: PEER_SCORE_MAX,
+            last_update: now,
+            invalid_blocks: 0,
+            malformed_messages: 0,
+            stale_data: 0,
+            unsolicited_floods: 0,
+            banned_until: None,
+        }
+    }
+
+    /// Get the score at time `now`, including what the peer has recovered since its last update
+    pub fn get_score(&self, now: u64) -> i64 {
+        let recovered = now.saturating_sub(self.last_update) / PEER_SCORE_RECOVERY_INTERVAL;
+        self.score
+            .saturating_add(i64::try_from(recovered).unwrap_or(i64::MAX))
+            .min(PEER_SCORE_MAX)
+    }
+
+    /// Is the peer banned for its score at time `now`?
+    pub fn is_banned(&self, now: u64) -> bool {
+        self.banned_until.map(|until| until > now).unwrap_or(false)
+    }
+
+    fn penalize(&mut self, misbehavior: PeerMisbehavior, now: u64) {
+        self.score = self.get_score(now).saturating_sub(misbehavior.penalty());
+        self.last_update = now;
+        match misbehavior {
+            PeerMisbehavior::InvalidBlock => self.invalid_blocks += 1,
+            PeerMisbehavior::MalformedMessage => self.malformed_messages += 1,
+            PeerMisbehavior::StaleData => self.stale_data += 1,
+            PeerMisbehavior::UnsolicitedFlood => self.unsolicited_floods += 1,
+        }
+    }
+}
+
+/// Reputation scores of the peers that have misbehaved.  Peers that are not tracked here have a
+/// perfect score.
+#[derive(Debug, Clone, Default)]
+pub struct PeerReputation {
+    scores: HashMap<NeighborKey, PeerScore>,
+}
+
+impl PeerReputation {
+    pub fn new() -> PeerReputation {
+        PeerReputation {
+            scores: HashMap::new(),
+        }
+    }
+
+    /// Penalize a peer for misbehaving at time `now`.
+    /// Returns the peer's new score.
+    pub fn record(
+        &mut self,
+        neighbor_key: &NeighborKey,
+        misbehavior: PeerMisbehavior,
+        now: u64,
+    ) -> i64 {
+        self.prune(now);
+        let peer_score = self
+            .scores
+            .entry(neighbor_key.clone())
+            .or_insert_with(|| PeerScore::new(now));
+        peer_score.penalize(misbehavior, now);
+        peer_score.get_score(now)
+    }
+
+    /// Get a peer's score at time `now`
+    pub fn get_score(&self, neighbor_key: &NeighborKey, now: u64) -> i64 {
+        self.scores
+            .get(neighbor_key)
+            .map(|peer_score| peer_score.get_score(now))
+            .unwrap_or(PEER_SCORE_MAX)
+    }
+
+    /// Get a peer's reputation, if it has misbehaved recently
+    pub fn get(&self, neighbor_key: &NeighborKey) -> Option<&PeerScore> {
+        self.scores.get(neighbor_key)
+    }
+
+    /// Iterate over the reputations of all peers that have misbehaved recently
+    pub fn iter(&self) -> impl Iterator<Item = (&NeighborKey, &PeerScore)> {
+        self.scores.iter()
+    }
+
+    /// Record that a peer is banned until `until`.  Its score is reset, so it starts over once
+    /// the ban expires.
+    pub fn ban(&mut self, neighbor_key: &NeighborKey, until: u64, now: u64) {
+        let peer_score = self
+            .scores
+            .entry(neighbor_key.clone())
+            .or_insert_with(|| PeerScore::new(now));
+        peer_score.score = PEER_SCORE_MAX;
+        peer_score.last_update = now;
+        peer_score.banned_until = Some(until);
+    }
+
+    /// Get the time at which a peer's ban for a low score expires, if it is banned at time `now`
+    pub fn get_banned_until(&self, neighbor_key: &NeighborKey, now: u64) -> Option<u64> {
+        self.scores
+            .get(neighbor_key)
+            .filter(|peer_score| peer_score.is_banned(now))
+            .and_then(|peer_score| peer_score.banned_until)
+    }
+
+    /// Forget peers that have recovered a perfect score and are not banned
+    pub fn prune(&mut self, now: u64) {
+        self.scores.retain(|_, peer_score| {
+            peer_score.is_banned(now) || peer_score.get_score(now) < PEER_SCORE_MAX
+        });
+    }
+}
@@ -29,6 +29,9 @@ use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::secp256k1::MessageSignature;
 
 use crate::chainstate::stacks::address::PoxAddress;
+use crate::monitoring::{
+    increment_stackerdb_chunk_reads_counter, increment_stackerdb_chunk_writes_counter,
+};
 use crate::net::stackerdb::{StackerDBConfig, StackerDBTx, StackerDBs, STACKERDB_INV_MAX};
 use crate::net::{Error as net_error, StackerDBChunkData, StackerDBHandshakeData};
 use crate::util_lib::db::{
@@ -94,6 +97,19 @@ pub struct SlotValidation {
     pub write_time: u64,
 }
 
+/// A slot's metadata, along with who may write it and when it was last written
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SlotInfo {
+    pub slot_id: u32,
+    pub slot_version: u32,
+    /// The address whose signature is required to write this slot
+    pub signer: StacksAddress,
+    pub data_hash: Sha512Trunc256Sum,
+    pub signature: MessageSignature,
+    /// UNIX timestamp of the last write to this slot, or 0 if it has not been written
+    pub write_time: u64,
+}
+
 impl FromRow<SlotMetadata> for SlotMetadata {
     fn from_row(row: &Row) -> Result<SlotMetadata, db_error> {
         let slot_id: u32 = row.get_unwrap("slot_id");
@@ -132,6 +148,22 @@ impl FromRow<SlotValidation> for SlotValidation {
     }
 }
 
+impl FromRow<SlotInfo> for SlotInfo {
+    fn from_row(row: &Row) -> Result<SlotInfo, db_error> {
+        let metadata = SlotMetadata::from_row(row)?;
+        let validation = SlotValidation::from_row(row)?;
+
+        Ok(SlotInfo {
+            slot_id: metadata.slot_id,
+            slot_version: metadata.slot_version,
+            signer: validation.signer,
+            data_hash: metadata.data_hash,
+            signature: metadata.signature,
+            write_time: validation.write_time,
+        })
+    }
+}
+
 impl FromRow<StackerDBChunkData> for StackerDBChunkData {
     fn from_row(row: &Row) -> Result<StackerDBChunkData, db_error> {
         let slot_id: u32 = row.get_unwrap("slot_id");
@@ -394,6 +426,22 @@ impl<'a> StackerDBTx<'a> {
         smart_contract: &QualifiedContractIdentifier,
         slot_desc: &SlotMetadata,
         chunk: &[u8],
+    ) -> Result<(), net_error> {
+        let res = self.inner_try_replace_chunk(smart_contract, slot_desc, chunk);
+        let result = match &res {
+            Ok(()) => "accepted",
+            Err(net_error::StaleChunk { .. }) => "stale",
+            Err(_) => "rejected",
+        };
+        increment_stackerdb_chunk_writes_counter(&smart_contract.to_string(), result);
+        res
+    }
+
+    fn inner_try_replace_chunk(
+        &self,
+        smart_contract: &QualifiedContractIdentifier,
+        slot_desc: &SlotMetadata,
+        chunk: &[u8],
     ) -> Result<(), net_error> {
         if chunk.len() > STACKERDB_MAX_CHUNK_SIZE as usize {
             return Err(net_error::StackerDBChunkTooBig(chunk.len()));
@@ -558,6 +606,18 @@ impl StackerDBs {
         query_rows(&self.conn, &sql, args).map_err(|e| e.into())
     }
 
+    /// Get the metadata, signer, and last write time of every slot in the DB
+    /// (used for RPC)
+    pub fn get_db_slot_info(
+        &self,
+        smart_contract: &QualifiedContractIdentifier,
+    ) -> Result<Vec<SlotInfo>, net_error> {
+        let stackerdb_id = inner_get_stackerdb_id(&self.conn, smart_contract)?;
+        let sql = "SELECT slot_id,version,signer,data_hash,signature,write_time FROM chunks WHERE stackerdb_id = ?1 ORDER BY slot_id ASC";
+        let args: &[&dyn ToSql] = &[&stackerdb_id];
+        query_rows(&self.conn, &sql, args).map_err(|e| e.into())
+    }
+
     /// Get a slot's validation data
     pub fn get_slot_validation(
         &self,
@@ -602,10 +662,14 @@ impl StackerDBs {
         let qry = "SELECT data FROM chunks WHERE stackerdb_id = ?1 AND slot_id = ?2";
         let args: &[&dyn ToSql] = &[&stackerdb_id, &slot_id];
 
-        self.conn
+        let chunk: Option<Vec<u8>> = self
+            .conn
             .query_row(qry, args, |row| row.get(0))
-            .optional()
-            .map_err(|e| e.into())
+            .optional()?;
+        if chunk.is_some() {
+            increment_stackerdb_chunk_reads_counter(&smart_contract.to_string());
+        }
+        Ok(chunk)
     }
 
     /// Get a versioned chunk out of this database.  If the version is not present, then None will
@@ -619,6 +683,10 @@ impl StackerDBs {
         let stackerdb_id = self.get_stackerdb_id(smart_contract)?;
         let qry = "SELECT slot_id,version,signature,data FROM chunks WHERE stackerdb_id = ?1 AND slot_id = ?2 AND version = ?3";
         let args: &[&dyn ToSql] = &[&stackerdb_id, &slot_id, &slot_version];
-        query_row(&self.conn, &qry, args).map_err(|e| e.into())
+        let chunk: Option<StackerDBChunkData> = query_row(&self.conn, &qry, args)?;
+        if chunk.is_some() {
+            increment_stackerdb_chunk_reads_counter(&smart_contract.to_string());
+        }
+        Ok(chunk)
     }
 }
@@ -122,7 +122,7 @@ use std::collections::{HashMap, HashSet};
 
 use clarity::vm::types::QualifiedContractIdentifier;
 use libstackerdb::{SlotMetadata, STACKERDB_MAX_CHUNK_SIZE};
-use stacks_common::types::chainstate::{ConsensusHash, StacksAddress};
+use stacks_common::types::chainstate::{ConsensusHash, StacksAddress, StacksPrivateKey};
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::secp256k1::MessageSignature;
@@ -139,6 +139,45 @@ use crate::util_lib::db::{DBConn, DBTx};
 /// maximum chunk inventory size
 pub const STACKERDB_INV_MAX: u32 = 4096;
 
+/// Make the signed slot metadata for writing `data` to `slot_id` at `slot_version`, as the
+/// slot's signer `privkey`.  External writers send this alongside the data.
+pub fn sign_slot_metadata(
+    privkey: &StacksPrivateKey,
+    slot_id: u32,
+    slot_version: u32,
+    data: &[u8],
+) -> Result<SlotMetadata, net_error> {
+    let mut metadata =
+        SlotMetadata::new_unsigned(slot_id, slot_version, Sha512Trunc256Sum::from_data(data));
+    metadata.sign(privkey)?;
+    Ok(metadata)
+}
+
+/// Make a chunk that writes `data` to `slot_id` at `slot_version`, signed by the slot's signer
+/// `privkey`, ready to be posted to a replica
+pub fn make_signed_chunk(
+    privkey: &StacksPrivateKey,
+    slot_id: u32,
+    slot_version: u32,
+    data: Vec<u8>,
+) -> Result<StackerDBChunkData, net_error> {
+    let mut chunk = StackerDBChunkData::new(slot_id, slot_version, data);
+    chunk.sign(privkey)?;
+    Ok(chunk)
+}
+
+/// Check that `metadata` describes `data` and was signed by `signer`
+pub fn verify_slot_metadata(
+    metadata: &SlotMetadata,
+    data: &[u8],
+    signer: &StacksAddress,
+) -> Result<bool, net_error> {
+    if metadata.data_hash != Sha512Trunc256Sum::from_data(data) {
+        return Ok(false);
+    }
+    Ok(metadata.verify(signer)?)
+}
+
 /// Final result of synchronizing state with a remote set of DB replicas
 pub struct StackerDBSyncResult {
     /// which contract this is a replica for
@@ -191,6 +230,21 @@ impl StackerDBConfig {
     }
 }
 
+/// Node-local replication settings for a StackerDB, which let an operator favor some DBs (such
+/// as the signers' DBs) over others
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct StackerDBReplicationSettings {
+    /// DBs with a higher priority are synced first in each pass
+    pub priority: u32,
+    /// minimum time between anti-entropy syncs for this DB, in seconds.  Overrides the node-wide
+    /// `stackerdb_sync_interval` if set.  The DB's own `write_freq` is used instead if it is
+    /// longer.
+    pub sync_interval: Option<u64>,
+    /// push new chunks in every slot to connected replicas as soon as they arrive, as if they
+    /// were all hot slots
+    pub push_all_slots: bool,
+}
+
 /// This is the set of replicated chunks in all stacker DBs that this node subscribes to.
 ///
 /// Callers can query chunks from individual stacker DBs by supplying the smart contract address.
@@ -305,7 +359,13 @@ impl PeerNetwork {
             .expect("FATAL: did not replace stacker dbs");
         let stacker_db_configs = self.stacker_db_configs.clone();
 
-        for (sc, stacker_db_sync) in stacker_db_syncs.iter_mut() {
+        let mut contract_ids: Vec<_> = stacker_db_syncs.keys().cloned().collect();
+        self.sort_stackerdbs_by_priority(&mut contract_ids);
+
+        for sc in contract_ids.iter() {
+            let stacker_db_sync = stacker_db_syncs
+                .get_mut(sc)
+                .expect("FATAL: no sync state machine for StackerDB");
             if let Some(config) = stacker_db_configs.get(sc) {
                 match stacker_db_sync.run(self, config) {
                     Ok(Some(result)) => {
@@ -338,6 +398,41 @@ impl PeerNetwork {
         Ok(results)
     }
 
+    /// Get the operator's replication settings for the StackerDB `contract_id`, if any
+    pub fn get_stackerdb_replication_settings(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Option<&StackerDBReplicationSettings> {
+        self.connection_opts.stackerdb_replication.get(contract_id)
+    }
+
+    /// Order StackerDBs so that higher-priority DBs are synced first.  DBs with the same priority
+    /// are ordered by contract ID.
+    pub fn sort_stackerdbs_by_priority(&self, contract_ids: &mut Vec<QualifiedContractIdentifier>) {
+        contract_ids.sort_by(|a, b| {
+            let priority = |contract_id: &QualifiedContractIdentifier| {
+                self.get_stackerdb_replication_settings(contract_id)
+                    .map(|settings| settings.priority)
+                    .unwrap_or(0)
+            };
+            priority(b).cmp(&priority(a)).then_with(|| a.cmp(b))
+        });
+    }
+
+    /// Minimum number of seconds between anti-entropy syncs of the StackerDB `contract_id`.
+    /// This is the DB's `write_freq`, or the configured sync interval if it is longer.
+    pub fn get_stackerdb_sync_interval(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        config: &StackerDBConfig,
+    ) -> u64 {
+        let sync_interval = self
+            .get_stackerdb_replication_settings(contract_id)
+            .and_then(|settings| settings.sync_interval)
+            .unwrap_or(self.connection_opts.stackerdb_sync_interval);
+        config.write_freq.max(sync_interval).max(1)
+    }
+
     /// Create a StackerDBChunksInv, or a Nack if the requested DB isn't replicated here
     pub fn make_StackerDBChunksInv_or_Nack(
         &self,
@@ -484,6 +579,9 @@ impl PeerNetwork {
                         stackerdb_sync.wakeup();
                     }
                 }
+
+                // pass it along right away if it's in a hot slot
+                self.push_stackerdb_hot_chunk(chunk_data, Some(event_id));
             }
             _ => {}
         }
@@ -498,4 +596,69 @@ impl PeerNetwork {
         self.add_relay_handle(event_id, handle);
         Ok(true)
     }
+
+    /// Is `slot_id` a hot slot in the StackerDB `contract_id`?  New chunks in hot slots are
+    /// pushed to connected replicas as soon as they arrive.  Every slot is hot in a DB whose
+    /// replication settings push all slots.
+    pub fn is_stackerdb_hot_slot(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        slot_id: u32,
+    ) -> bool {
+        if self
+            .get_stackerdb_replication_settings(contract_id)
+            .map(|settings| settings.push_all_slots)
+            .unwrap_or(false)
+        {
+            return true;
+        }
+        self.connection_opts
+            .stackerdb_hot_slots
+            .get(contract_id)
+            .map(|slot_ids| slot_ids.contains(&slot_id))
+            .unwrap_or(false)
+    }
+
+    /// Push a new chunk to every authenticated peer that replicates its StackerDB, if it is in
+    /// a hot slot.  The peer at `from_event_id` (i.e. the one that sent us the chunk) is skipped.
+    ///
+    /// Peers only accept a pushed chunk if it is newer than their copy, so a chunk stops
+    /// propagating once every replica has it.
+    ///
+    /// Returns the number of peers the chunk was pushed to.
+    pub fn push_stackerdb_hot_chunk(
+        &mut self,
+        chunk_data: &StackerDBPushChunkData,
+        from_event_id: Option<usize>,
+    ) -> usize {
+        if !self.is_stackerdb_hot_slot(&chunk_data.contract_id, chunk_data.chunk_data.slot_id) {
+            return 0;
+        }
+        let recipients: Vec<NeighborKey> = self
+            .peers
+            .iter()
+            .filter(|(event_id, convo)| {
+                Some(**event_id) != from_event_id
+                    && convo.is_authenticated()
+                    && convo.replicates_stackerdb(&chunk_data.contract_id)
+            })
+            .map(|(_, convo)| convo.to_neighbor_key())
+            .collect();
+
+        debug!(
+            "{:?}: Push hot StackerDB chunk to {} replicas", &self.local_peer, recipients.len();
+            "stackerdb_contract_id" => %chunk_data.contract_id,
+            "slot_id" => chunk_data.chunk_data.slot_id,
+            "slot_version" => chunk_data.chunk_data.slot_version
+        );
+        let num_recipients = recipients.len();
+        if num_recipients > 0 {
+            self.broadcast_message(
+                recipients,
+                vec![],
+                StacksMessageType::StackerDBPushChunk(chunk_data.clone()),
+            );
+        }
+        num_recipients
+    }
 }
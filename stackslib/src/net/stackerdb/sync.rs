@@ -1157,13 +1157,14 @@ impl<NC: NeighborComms> StackerDBSync<NC> {
         network: &mut PeerNetwork,
         config: &StackerDBConfig,
     ) -> Result<Option<StackerDBSyncResult>, net_error> {
-        // throttle to write_freq
-        if self.last_run_ts + config.write_freq.max(1) > get_epoch_time_secs() {
+        // throttle to write_freq, or to the configured sync interval if it's longer
+        let sync_interval = network.get_stackerdb_sync_interval(&self.smart_contract_id, config);
+        if self.last_run_ts + sync_interval > get_epoch_time_secs() {
             debug!(
                 "{:?}: stacker DB sync for {} is throttled until {}",
                 network.get_local_peer(),
                 &self.smart_contract_id,
-                self.last_run_ts + config.write_freq
+                self.last_run_ts + sync_interval
             );
             return Ok(None);
         }
@@ -30,7 +30,9 @@ use stacks_common::util::hash::{Hash160, Sha512Trunc256Sum};
 use stacks_common::util::secp256k1::MessageSignature;
 
 use crate::net::stackerdb::db::SlotValidation;
-use crate::net::stackerdb::{StackerDBConfig, StackerDBs};
+use crate::net::stackerdb::{
+    make_signed_chunk, sign_slot_metadata, verify_slot_metadata, StackerDBConfig, StackerDBs,
+};
 use crate::net::{Error as net_error, StackerDBChunkData};
 
 fn setup_test_path(path: &str) {
@@ -569,6 +571,19 @@ fn test_stackerdb_insert_query_chunks() {
     for ts in timestamps {
         assert!(ts > 0);
     }
+
+    let slot_info = db.get_db_slot_info(&sc).unwrap();
+    assert_eq!(slot_info.len(), addrs.len());
+    for (i, info) in slot_info.iter().enumerate() {
+        assert_eq!(info.slot_id, i as u32);
+        assert_eq!(info.slot_version, 1);
+        assert_eq!(info.signer, addrs[i]);
+        assert_eq!(
+            info.data_hash,
+            Sha512Trunc256Sum::from_data(&vec![i as u8; 128])
+        );
+        assert!(info.write_time > 0);
+    }
 }
 
 /// Verify that we can reconfigure the database by changing its slots
@@ -724,4 +739,62 @@ fn test_reconfigure_stackerdb() {
     }
 }
 
+/// Test that chunks signed with the external-writer helpers are accepted, and that the slot
+/// metadata checks both the signer and the data
+#[test]
+fn test_stackerdb_signed_chunk_helpers() {
+    let path = "/tmp/test_stackerdb_signed_chunk_helpers.sqlite";
+    setup_test_path(path);
+
+    let sc = QualifiedContractIdentifier::new(
+        StacksAddress {
+            version: 0x01,
+            bytes: Hash160([0x01; 20]),
+        }
+        .into(),
+        ContractName::try_from("db1").unwrap(),
+    );
+
+    let mut db = StackerDBs::connect(path, true).unwrap();
+    let tx = db.tx_begin(StackerDBConfig::noop()).unwrap();
+
+    let pks: Vec<_> = (0..2).map(|_| StacksPrivateKey::new()).collect();
+    let addrs: Vec<_> = pks
+        .iter()
+        .map(|pk| {
+            StacksAddress::from_public_keys(
+                C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+                &AddressHashMode::SerializeP2PKH,
+                1,
+                &vec![StacksPublicKey::from_private(&pk)],
+            )
+            .unwrap()
+        })
+        .collect();
+    tx.create_stackerdb(&sc, &[(addrs[0].clone(), 1), (addrs[1].clone(), 1)])
+        .unwrap();
+
+    let data = vec![0x11; 64];
+    let metadata = sign_slot_metadata(&pks[0], 0, 1, &data).unwrap();
+    assert!(verify_slot_metadata(&metadata, &data, &addrs[0]).unwrap());
+    assert!(!verify_slot_metadata(&metadata, &data, &addrs[1]).unwrap());
+    assert!(!verify_slot_metadata(&metadata, &[0x22; 64], &addrs[0]).unwrap());
+
+    let chunk = make_signed_chunk(&pks[0], 0, 1, data.clone()).unwrap();
+    assert_eq!(chunk.get_slot_metadata(), metadata);
+    tx.try_replace_chunk(&sc, &chunk.get_slot_metadata(), &chunk.data)
+        .unwrap();
+
+    // signed by the wrong signer
+    let chunk = make_signed_chunk(&pks[0], 1, 1, data).unwrap();
+    if let Err(net_error::BadSlotSigner(..)) =
+        tx.try_replace_chunk(&sc, &chunk.get_slot_metadata(), &chunk.data)
+    {
+    } else {
+        panic!("Did not get BadSlotSigner");
+    }
+
+    tx.commit().unwrap();
+}
+
 // TODO: max chunk size
@@ -34,9 +34,9 @@ use stacks_common::util::secp256k1::{MessageSignature, Secp256k1PrivateKey};
 use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::net::relay::Relayer;
 use crate::net::stackerdb::db::SlotValidation;
-use crate::net::stackerdb::{StackerDBConfig, StackerDBs};
+use crate::net::stackerdb::{StackerDBConfig, StackerDBReplicationSettings, StackerDBs};
 use crate::net::test::{TestPeer, TestPeerConfig};
-use crate::net::{Error as net_error, StackerDBChunkData};
+use crate::net::{Error as net_error, StackerDBChunkData, StackerDBPushChunkData};
 use crate::util_lib::test::with_timeout;
 
 const BASE_PORT: u16 = 33000;
@@ -729,3 +729,221 @@ fn inner_test_stackerdb_10_replicas_10_neighbors_line_10_chunks(push_only: bool,
         debug!("Completed stacker DB sync in {} step(s)", step_count);
     })
 }
+
+#[test]
+fn test_stackerdb_push_hot_slot() {
+    with_timeout(600, || {
+        std::env::set_var("STACKS_TEST_DISABLE_EDGE_TRIGGER_TEST", "1");
+        let mut peer_1_config = TestPeerConfig::from_port(BASE_PORT + 108);
+        let mut peer_2_config = TestPeerConfig::from_port(BASE_PORT + 110);
+
+        peer_1_config.allowed = -1;
+        peer_2_config.allowed = -1;
+
+        // short-lived walks...
+        peer_1_config.connection_opts.walk_max_duration = 10;
+        peer_2_config.connection_opts.walk_max_duration = 10;
+
+        // no anti-entropy syncs will happen during the test
+        peer_1_config.connection_opts.stackerdb_sync_interval = 3600;
+        peer_2_config.connection_opts.stackerdb_sync_interval = 3600;
+
+        // peer 1 crawls peer 2, and peer 2 crawls peer 1
+        peer_1_config.add_neighbor(&peer_2_config.to_neighbor());
+        peer_2_config.add_neighbor(&peer_1_config.to_neighbor());
+
+        // set up stacker DBs for both peers
+        let idx_1 = add_stackerdb(&mut peer_1_config, Some(StackerDBConfig::template()));
+        let idx_2 = add_stackerdb(&mut peer_2_config, Some(StackerDBConfig::template()));
+
+        // slot 0 is hot on both peers
+        let contract_id = peer_1_config.stacker_dbs[idx_1].clone();
+        for config in [&mut peer_1_config, &mut peer_2_config] {
+            config
+                .connection_opts
+                .stackerdb_hot_slots
+                .insert(contract_id.clone(), [0].iter().cloned().collect());
+        }
+
+        let mut peer_1 = TestPeer::new(peer_1_config);
+        let mut peer_2 = TestPeer::new(peer_2_config);
+
+        // neither peer has any data
+        setup_stackerdb(&mut peer_1, idx_1, false, 2);
+        setup_stackerdb(&mut peer_2, idx_2, false, 2);
+
+        assert!(peer_1.network.is_stackerdb_hot_slot(&contract_id, 0));
+        assert!(!peer_1.network.is_stackerdb_hot_slot(&contract_id, 1));
+
+        let peer_1_db_configs = peer_1.config.get_stacker_db_configs();
+        let peer_2_db_configs = peer_2.config.get_stacker_db_configs();
+
+        let step = |peer: &mut TestPeer, db_configs: &_| {
+            peer.network.stacker_db_configs = Clone::clone(db_configs);
+            if let Ok(mut res) = peer.step_with_ibd(false) {
+                Relayer::process_stacker_db_chunks(
+                    &mut peer.network.stackerdbs,
+                    db_configs,
+                    res.stacker_db_sync_results,
+                    None,
+                )
+                .unwrap();
+                Relayer::process_pushed_stacker_db_chunks(
+                    &mut peer.network.stackerdbs,
+                    db_configs,
+                    &mut res.unhandled_messages,
+                    None,
+                )
+                .unwrap();
+            }
+        };
+
+        // wait for the peers to connect
+        loop {
+            step(&mut peer_1, &peer_1_db_configs);
+            step(&mut peer_2, &peer_2_db_configs);
+
+            let peer_1_has_replica =
+                peer_1.network.peers.values().any(|convo| {
+                    convo.is_authenticated() && convo.replicates_stackerdb(&contract_id)
+                });
+            if peer_1_has_replica {
+                break;
+            }
+        }
+
+        // slot 0's signer is the first key generated by `setup_stackerdb()`
+        let mut k: u64 = 0;
+        let pk = loop {
+            let h = Sha512Trunc256Sum::from_data(&k.to_be_bytes());
+            k += 1;
+            if let Ok(pk) = Secp256k1PrivateKey::from_slice(&h.0) {
+                break pk;
+            }
+        };
+        let mut chunk_data = StackerDBChunkData::new(0, 1, vec![0x01; 16]);
+        chunk_data.sign(&pk).unwrap();
+
+        // peer 1 gets a new chunk for the hot slot
+        let tx = peer_1
+            .network
+            .stackerdbs
+            .tx_begin(peer_1.config.stacker_db_configs[idx_1].clone().unwrap())
+            .unwrap();
+        tx.try_replace_chunk(
+            &contract_id,
+            &chunk_data.get_slot_metadata(),
+            &chunk_data.data,
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let push_chunk_data = StackerDBPushChunkData {
+            contract_id: contract_id.clone(),
+            rc_consensus_hash: peer_1.network.get_chain_view().rc_consensus_hash.clone(),
+            chunk_data: chunk_data.clone(),
+        };
+
+        // chunks in other slots are not pushed
+        let mut cold_chunk_data = push_chunk_data.clone();
+        cold_chunk_data.chunk_data.slot_id = 1;
+        assert_eq!(
+            peer_1
+                .network
+                .push_stackerdb_hot_chunk(&cold_chunk_data, None),
+            0
+        );
+
+        assert_eq!(
+            peer_1
+                .network
+                .push_stackerdb_hot_chunk(&push_chunk_data, None),
+            1
+        );
+
+        // peer 2 gets the chunk without waiting for its next sync
+        loop {
+            step(&mut peer_1, &peer_1_db_configs);
+            step(&mut peer_2, &peer_2_db_configs);
+
+            let db2 = load_stackerdb(&peer_2, idx_2);
+            if db2[0].0.slot_version == 1 {
+                assert_eq!(db2[0].1, chunk_data.data);
+                break;
+            }
+        }
+    })
+}
+
+#[test]
+fn test_stackerdb_replication_settings() {
+    let mut peer_config = TestPeerConfig::from_port(BASE_PORT + 112);
+    peer_config.connection_opts.stackerdb_sync_interval = 60;
+
+    let mut template = StackerDBConfig::template();
+    template.write_freq = 0;
+    let idx_1 = add_stackerdb(&mut peer_config, Some(template.clone()));
+    let idx_2 = add_stackerdb(&mut peer_config, Some(template.clone()));
+    let idx_3 = add_stackerdb(&mut peer_config, Some(template.clone()));
+
+    let contract_1 = peer_config.stacker_dbs[idx_1].clone();
+    let contract_2 = peer_config.stacker_dbs[idx_2].clone();
+    let contract_3 = peer_config.stacker_dbs[idx_3].clone();
+
+    // DB 3 is the most important, followed by DB 2
+    peer_config.connection_opts.stackerdb_replication.insert(
+        contract_3.clone(),
+        StackerDBReplicationSettings {
+            priority: 10,
+            sync_interval: Some(5),
+            push_all_slots: true,
+        },
+    );
+    peer_config.connection_opts.stackerdb_replication.insert(
+        contract_2.clone(),
+        StackerDBReplicationSettings {
+            priority: 1,
+            ..StackerDBReplicationSettings::default()
+        },
+    );
+
+    let peer = TestPeer::new(peer_config);
+
+    let mut contract_ids = vec![contract_1.clone(), contract_2.clone(), contract_3.clone()];
+    peer.network.sort_stackerdbs_by_priority(&mut contract_ids);
+    assert_eq!(
+        contract_ids,
+        vec![contract_3.clone(), contract_2.clone(), contract_1.clone()]
+    );
+
+    // only DB 3 overrides the node-wide sync interval
+    assert_eq!(
+        peer.network
+            .get_stackerdb_sync_interval(&contract_3, &template),
+        5
+    );
+    assert_eq!(
+        peer.network
+            .get_stackerdb_sync_interval(&contract_2, &template),
+        60
+    );
+    assert_eq!(
+        peer.network
+            .get_stackerdb_sync_interval(&contract_1, &template),
+        60
+    );
+
+    // the DB's write_freq still applies if it is longer
+    template.write_freq = 10;
+    assert_eq!(
+        peer.network
+            .get_stackerdb_sync_interval(&contract_3, &template),
+        10
+    );
+
+    // every slot in DB 3 is hot
+    assert!(peer.network.is_stackerdb_hot_slot(&contract_3, 0));
+    assert!(peer.network.is_stackerdb_hot_slot(&contract_3, 1));
+    assert!(!peer.network.is_stackerdb_hot_slot(&contract_2, 0));
+    assert!(!peer.network.is_stackerdb_hot_slot(&contract_1, 0));
+}
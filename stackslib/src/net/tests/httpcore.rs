@@ -41,8 +41,8 @@ use crate::net::http::{
     HttpVersion, HTTP_PREAMBLE_MAX_NUM_HEADERS,
 };
 use crate::net::httpcore::{
-    HttpPreambleExtensions, HttpRequestContentsExtensions, StacksHttp, StacksHttpMessage,
-    StacksHttpPreamble, StacksHttpRequest, StacksHttpResponse,
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCEndpointGroup, StacksHttp,
+    StacksHttpMessage, StacksHttpPreamble, StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::rpc::ConversationHttp;
 use crate::net::{ProtocolFamily, TipRequest};
@@ -398,6 +398,43 @@ fn test_http_request_type_codec_err() {
     }
 }
 
+#[test]
+fn test_http_request_auth_tokens() {
+    let mut conn_opts = ConnectionOptions::default();
+    conn_opts
+        .rpc_auth_tokens
+        .insert(RPCEndpointGroup::Admin, "admin-secret".to_string());
+
+    let requests = vec![
+        // gated, and no token given
+        ("GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\n\r\n", Some(401)),
+        // gated, and the wrong token given
+        ("GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nAuthorization: public-secret\r\n\r\n", Some(401)),
+        // gated, and a token of the same length given
+        ("GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nAuthorization: admin-secreT\r\n\r\n", Some(401)),
+        // gated, and the token given with a scheme
+        ("GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nAuthorization: Bearer admin-secret\r\n\r\n", Some(401)),
+        // gated, and the right token given
+        ("GET /v2/neighbors HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nAuthorization: admin-secret\r\n\r\n", None),
+        // not gated
+        ("GET /v2/info HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\n\r\n", None),
+    ];
+    for (request, expected_error) in requests {
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap(), &conn_opts);
+        let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+        let (message, _) = http
+            .read_payload(&preamble, &request.as_bytes()[offset..])
+            .unwrap();
+        match (message, expected_error) {
+            (StacksHttpMessage::Error(_, response), Some(status_code)) => {
+                assert_eq!(response.preamble().status_code, status_code);
+            }
+            (StacksHttpMessage::Request(_), None) => {}
+            (message, _) => panic!("Unexpected message for {}: {:?}", request, &message),
+        }
+    }
+}
+
 #[test]
 fn test_http_response_type_codec() {
     let test_neighbors_info = RPCNeighborsInfo {
@@ -991,6 +1028,14 @@ fn test_http_parse_proof_tip_query() {
         _ => panic!(),
     }
 
+    // unconfirmed state, by either name
+    for query_txt_unconfirmed in ["tip=unconfirmed", "tip=latest"] {
+        let tip_req = HttpRequestContents::new()
+            .query_string(Some(query_txt_unconfirmed))
+            .tip_request();
+        assert_eq!(tip_req, TipRequest::UseLatestUnconfirmedTip);
+    }
+
     // tip can be skipped
     let query_txt_bad = "tip=bad";
     let tip_req = HttpRequestContents::new()
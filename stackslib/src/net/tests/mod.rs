@@ -16,3 +16,4 @@
 
 pub mod httpcore;
 pub mod neighbors;
+pub mod reputation;
@@ -0,0 +1,99 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::net::PeerAddress;
+
+use crate::net::reputation::*;
+use crate::net::NeighborKey;
+
+fn make_neighbor_key(i: u8) -> NeighborKey {
+    NeighborKey {
+        peer_version: 0x18000000,
+        network_id: 0x80000000,
+        addrbytes: PeerAddress::from_ipv4(127, 0, 0, i),
+        port: 20444,
+    }
+}
+
+#[test]
+fn test_peer_reputation_penalties_and_recovery() {
+    let mut reputation = PeerReputation::new();
+    let nk = make_neighbor_key(1);
+    let now = 1_000_000;
+
+    assert_eq!(reputation.get_score(&nk, now), PEER_SCORE_MAX);
+    assert!(reputation.get(&nk).is_none());
+
+    assert_eq!(
+        reputation.record(&nk, PeerMisbehavior::MalformedMessage, now),
+        PEER_SCORE_MAX - 20
+    );
+    assert_eq!(
+        reputation.record(&nk, PeerMisbehavior::StaleData, now),
+        PEER_SCORE_MAX - 25
+    );
+    let peer_score = reputation.get(&nk).unwrap();
+    assert_eq!(peer_score.malformed_messages, 1);
+    assert_eq!(peer_score.stale_data, 1);
+    assert_eq!(peer_score.invalid_blocks, 0);
+    assert_eq!(peer_score.unsolicited_floods, 0);
+
+    // the score recovers a point per interval, up to the maximum
+    assert_eq!(
+        reputation.get_score(&nk, now + 10 * PEER_SCORE_RECOVERY_INTERVAL + 1),
+        PEER_SCORE_MAX - 15
+    );
+    assert_eq!(
+        reputation.get_score(&nk, now + 100 * PEER_SCORE_RECOVERY_INTERVAL),
+        PEER_SCORE_MAX
+    );
+
+    // penalties apply to the recovered score
+    let later = now + 10 * PEER_SCORE_RECOVERY_INTERVAL;
+    assert_eq!(
+        reputation.record(&nk, PeerMisbehavior::UnsolicitedFlood, later),
+        PEER_SCORE_MAX - 25
+    );
+
+    // fully-recovered peers are forgotten
+    reputation.prune(later + 25 * PEER_SCORE_RECOVERY_INTERVAL);
+    assert!(reputation.get(&nk).is_none());
+}
+
+#[test]
+fn test_peer_reputation_bans() {
+    let mut reputation = PeerReputation::new();
+    let nk = make_neighbor_key(1);
+    let other_nk = make_neighbor_key(2);
+    let now = 1_000_000;
+
+    assert!(reputation.record(&nk, PeerMisbehavior::InvalidBlock, now) <= 0);
+    assert_eq!(reputation.get_banned_until(&nk, now), None);
+
+    reputation.ban(&nk, now + 3600, now);
+    assert_eq!(reputation.get_banned_until(&nk, now), Some(now + 3600));
+    assert_eq!(reputation.get_banned_until(&other_nk, now), None);
+
+    // the score starts over once banned
+    assert_eq!(reputation.get_score(&nk, now), PEER_SCORE_MAX);
+
+    // banned peers are remembered until the ban expires
+    reputation.prune(now + 3599);
+    assert!(reputation.get(&nk).unwrap().is_banned(now + 3599));
+    assert_eq!(reputation.get_banned_until(&nk, now + 3600), None);
+    reputation.prune(now + 3600);
+    assert!(reputation.get(&nk).is_none());
+}
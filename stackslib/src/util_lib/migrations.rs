@@ -0,0 +1,271 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Resumable schema migrations.
+//!
+//! A `Migration` is the list of steps that brings a DB from one schema version to the next.  When
+//! a migration is run against an existing DB, each step is committed in its own transaction,
+//! along with a row in `schema_migration_steps` that records that the step finished.  If the node
+//! is interrupted, the next run skips the finished steps and picks up where it left off, instead
+//! of leaving the DB half-migrated or redoing hours of work.
+
+use std::collections::HashSet;
+
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+use stacks_common::util::{get_epoch_time_ms, get_epoch_time_secs};
+
+use crate::chainstate::stacks::index::marf::MARF;
+use crate::chainstate::stacks::index::MarfTrieId;
+use crate::util_lib::db::{query_rows, tx_begin_immediate, u64_to_sql, DBTx, Error};
+
+const SCHEMA_MIGRATION_STEPS_TABLE: &'static str = r#"
+    CREATE TABLE IF NOT EXISTS schema_migration_steps(
+        -- schema version the step migrates to
+        version TEXT NOT NULL,
+        -- index of the step in its migration
+        step INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        -- when the step was committed, in seconds since the epoch
+        completed_at INTEGER NOT NULL,
+        PRIMARY KEY(version, step)
+    );"#;
+
+/// A DB that a migration can open write transactions on
+pub trait MigrationDB {
+    fn migration_tx<'a>(&'a mut self) -> Result<DBTx<'a>, Error>;
+}
+
+impl MigrationDB for Connection {
+    fn migration_tx<'a>(&'a mut self) -> Result<DBTx<'a>, Error> {
+        tx_begin_immediate(self)
+    }
+}
+
+impl<T: MarfTrieId> MigrationDB for MARF<T> {
+    fn migration_tx<'a>(&'a mut self) -> Result<DBTx<'a>, Error> {
+        self.storage_tx()
+    }
+}
+
+/// One step of a migration.  A step runs in a single transaction, so it either completes or
+/// has no effect.
+pub struct MigrationStep<'a> {
+    name: String,
+    run: Box<dyn Fn(&DBTx) -> Result<(), Error> + 'a>,
+}
+
+/// The steps that migrate a DB to a schema version
+pub struct Migration<'a> {
+    /// name of the DB, for logging
+    db_name: String,
+    /// schema version this migration migrates to
+    version: String,
+    steps: Vec<MigrationStep<'a>>,
+}
+
+impl<'a> Migration<'a> {
+    pub fn new(db_name: &str, version: &str) -> Migration<'a> {
+        Migration {
+            db_name: db_name.to_string(),
+            version: version.to_string(),
+            steps: vec![],
+        }
+    }
+
+    /// The schema version this migration migrates to
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Add a step that runs `run`
+    pub fn step<F>(mut self, name: &str, run: F) -> Migration<'a>
+    where
+        F: Fn(&DBTx) -> Result<(), Error> + 'a,
+    {
+        self.steps.push(MigrationStep {
+            name: name.to_string(),
+            run: Box::new(run),
+        });
+        self
+    }
+
+    /// Add a step for each of the given SQL statements
+    pub fn sql_steps(mut self, stmts: &'a [&'a str]) -> Migration<'a> {
+        for (i, stmt) in stmts.iter().enumerate() {
+            self = self.step(&format!("sql-{}", i), move |tx| {
+                tx.execute_batch(stmt)?;
+                Ok(())
+            });
+        }
+        self
+    }
+
+    /// Apply every step of the migration within the given transaction, without recording
+    /// progress.  Used when instantiating a new DB, where there is nothing to resume.
+    pub fn apply(&self, tx: &DBTx) -> Result<(), Error> {
+        for step in self.steps.iter() {
+            (step.run)(tx)?;
+        }
+        Ok(())
+    }
+
+    /// Get the indexes of the steps of the migration to `version` that have been committed
+    pub fn get_completed_steps(conn: &Connection, version: &str) -> Result<HashSet<u64>, Error> {
+        let sql = "SELECT step FROM schema_migration_steps WHERE version = ?1";
+        let steps: Vec<u64> = query_rows(conn, sql, &[version])?;
+        Ok(steps.into_iter().collect())
+    }
+
+    /// Run the migration, committing each step in its own transaction and skipping steps that a
+    /// previous, interrupted run already committed.
+    pub fn run<D: MigrationDB>(&self, db: &mut D) -> Result<(), Error> {
+        let completed = {
+            let tx = db.migration_tx()?;
+            tx.execute_batch(SCHEMA_MIGRATION_STEPS_TABLE)?;
+            let completed = Migration::get_completed_steps(&tx, &self.version)?;
+            tx.commit()?;
+            completed
+        };
+
+        let num_steps = self.steps.len();
+        if completed.len() > 0 {
+            info!(
+                "Resuming {} DB migration to schema version {}: {}/{} steps already done",
+                &self.db_name,
+                &self.version,
+                completed.len(),
+                num_steps
+            );
+        } else {
+            info!(
+                "Migrating {} DB to schema version {} ({} steps)",
+                &self.db_name, &self.version, num_steps
+            );
+        }
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let step_index = i as u64;
+            if completed.contains(&step_index) {
+                continue;
+            }
+
+            let start_time = get_epoch_time_ms();
+            let tx = db.migration_tx()?;
+            (step.run)(&tx)?;
+
+            let args: &[&dyn ToSql] = &[
+                &self.version,
+                &u64_to_sql(step_index)?,
+                &step.name,
+                &u64_to_sql(get_epoch_time_secs())?,
+            ];
+            tx.execute(
+                "INSERT INTO schema_migration_steps (version, step, name, completed_at) VALUES (?1, ?2, ?3, ?4)",
+                args,
+            )?;
+            tx.commit()?;
+
+            info!(
+                "Migrating {} DB to schema version {}: {}% done",
+                &self.db_name,
+                &self.version,
+                (i + 1) * 100 / num_steps;
+                "step" => &step.name,
+                "step_index" => i + 1,
+                "num_steps" => num_steps,
+                "duration_ms" => get_epoch_time_ms().saturating_sub(start_time)
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::util_lib::db::query_row;
+
+    #[test]
+    fn test_migration_resumes_after_failure() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE foo(a INTEGER NOT NULL);")
+            .unwrap();
+
+        let fail = Cell::new(true);
+        let num_inserts = Cell::new(0);
+        let migration = Migration::new("test", "2")
+            .sql_steps(&["ALTER TABLE foo ADD COLUMN b INTEGER NOT NULL DEFAULT 0;"])
+            .step("insert", |tx| {
+                tx.execute("INSERT INTO foo (a, b) VALUES (1, 2)", rusqlite::NO_PARAMS)?;
+                num_inserts.set(num_inserts.get() + 1);
+                Ok(())
+            })
+            .step("maybe fail", |_tx| {
+                if fail.get() {
+                    return Err(Error::Corruption);
+                }
+                Ok(())
+            });
+
+        // the first two steps are committed before the third fails
+        assert!(migration.run(&mut conn).is_err());
+        assert_eq!(
+            Migration::get_completed_steps(&conn, "2").unwrap(),
+            vec![0, 1].into_iter().collect::<HashSet<u64>>()
+        );
+
+        // resuming does not rerun the committed steps (re-adding the column would fail)
+        fail.set(false);
+        migration.run(&mut conn).unwrap();
+        assert_eq!(num_inserts.get(), 1);
+        assert_eq!(Migration::get_completed_steps(&conn, "2").unwrap().len(), 3);
+        let count: Option<i64> =
+            query_row(&conn, "SELECT COUNT(*) FROM foo", rusqlite::NO_PARAMS).unwrap();
+        assert_eq!(count, Some(1));
+
+        // running it again is a no-op
+        migration.run(&mut conn).unwrap();
+        assert_eq!(num_inserts.get(), 1);
+    }
+
+    #[test]
+    fn test_migration_apply() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let migration = Migration::new("test", "1").sql_steps(&[
+            "CREATE TABLE foo(a INTEGER NOT NULL);",
+            "INSERT INTO foo (a) VALUES (1);",
+        ]);
+
+        let tx = conn.migration_tx().unwrap();
+        migration.apply(&tx).unwrap();
+        tx.commit().unwrap();
+
+        // applying a migration does not record its steps
+        let exists: Option<i64> = query_row(
+            &conn,
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_migration_steps'",
+            rusqlite::NO_PARAMS,
+        )
+        .unwrap();
+        assert!(exists.is_none());
+        let count: Option<i64> =
+            query_row(&conn, "SELECT COUNT(*) FROM foo", rusqlite::NO_PARAMS).unwrap();
+        assert_eq!(count, Some(1));
+    }
+}
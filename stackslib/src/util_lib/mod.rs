@@ -2,6 +2,7 @@
 pub mod db;
 pub mod bloom;
 pub mod boot;
+pub mod migrations;
 pub mod strings;
 
 #[cfg(test)]
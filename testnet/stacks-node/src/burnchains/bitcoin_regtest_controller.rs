@@ -1,7 +1,9 @@
 use std::cmp;
+use std::collections::HashMap;
 use std::io::Cursor;
+use std::net::SocketAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 use async_h1::client;
@@ -36,7 +38,11 @@ use stacks::chainstate::coordinator::comm::CoordinatorChannels;
 #[cfg(test)]
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::core::{StacksEpoch, StacksEpochId};
-use stacks::monitoring::{increment_btc_blocks_received_counter, increment_btc_ops_sent_counter};
+use stacks::monitoring::{
+    increment_btc_blocks_received_counter, increment_btc_ops_sent_counter,
+    increment_btc_utxo_cache_hits_counter, increment_btc_utxo_cache_misses_counter,
+    instrument_btc_rpc_call,
+};
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::deps_common::bitcoin::blockdata::opcodes;
 use stacks_common::deps_common::bitcoin::blockdata::script::{Builder, Script};
@@ -51,11 +57,14 @@ use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
 use stacks_common::types::chainstate::BurnchainHeaderHash;
 use stacks_common::util::hash::{hex_bytes, Hash160};
 use stacks_common::util::secp256k1::Secp256k1PublicKey;
-use stacks_common::util::sleep_ms;
+use stacks_common::util::{get_epoch_time_ms, sleep_ms};
 
 use super::super::operations::BurnchainOpSigner;
 use super::super::Config;
-use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
+use super::{
+    make_wallet_backend, BurnchainController, BurnchainTip, Error as BurnchainControllerError,
+    WalletBackend,
+};
 
 /// The number of bitcoin blocks that can have
 ///  passed since the UTXO cache was last refreshed before
@@ -74,6 +83,9 @@ pub struct BitcoinRegtestController {
     ongoing_block_commit: Option<OngoingBlockCommit>,
     should_keep_running: Option<Arc<AtomicBool>>,
     allow_rbf: bool,
+    wallet: Box<dyn WalletBackend>,
+    /// The UTXOs last fetched from the wallet, until the next burn block or broadcast
+    utxo_cache: Mutex<Option<UTXOCache>>,
 }
 
 #[derive(Clone)]
@@ -82,12 +94,56 @@ pub struct OngoingBlockCommit {
     utxos: UTXOSet,
     fees: LeaderBlockCommitFees,
     txids: Vec<Txid>,
+    /// When the first transaction in `txids` was built
+    first_attempt_ms: u64,
+    /// When the last transaction in `txids` was built
+    last_attempt_ms: u64,
+}
+
+/// What to do about a block-commit that has not been confirmed yet
+#[derive(Debug, Clone, PartialEq)]
+pub enum RBFDecision {
+    /// Automatic fee bumping is disabled
+    Disabled,
+    /// Give the commit more time to confirm
+    Wait,
+    /// Re-broadcast the commit with a higher fee
+    Bump,
+    /// It is too late in the burn block for a bump to help
+    PastDeadline,
+    /// A bump would exceed the maximum fee rate or total spend
+    OverBudget,
 }
 
 impl OngoingBlockCommit {
     fn sum_utxos(&self) -> u64 {
         self.utxos.total_available()
     }
+
+    /// Decide whether to bump the fee of this commit at `now_ms`, per the `rbf_*` burnchain
+    /// settings
+    fn rbf_decision(&self, config: &Config, now_ms: u64) -> RBFDecision {
+        let Some(bump_interval_ms) = config.burnchain.rbf_bump_interval_ms else {
+            return RBFDecision::Disabled;
+        };
+        if now_ms.saturating_sub(self.last_attempt_ms) < bump_interval_ms {
+            return RBFDecision::Wait;
+        }
+        if now_ms.saturating_sub(self.first_attempt_ms) >= config.burnchain.rbf_bump_deadline_ms {
+            return RBFDecision::PastDeadline;
+        }
+        if self.fees.fee_rate + get_rbf_fee_increment(config)
+            > get_satoshis_per_byte(config) * get_max_rbf(config) / 100
+        {
+            return RBFDecision::OverBudget;
+        }
+        if let Some(max_total_spend) = config.burnchain.rbf_max_total_spend {
+            if self.fees.total_spent_after_bump(config) > max_total_spend {
+                return RBFDecision::OverBudget;
+            }
+        }
+        RBFDecision::Bump
+    }
 }
 
 #[derive(Clone)]
@@ -232,6 +288,16 @@ impl LeaderBlockCommitFees {
             + self.sortition_fee
     }
 
+    /// What `total_spent()` would be after replacing the last attempt with one of the same size
+    /// at the next fee rate
+    pub fn total_spent_after_bump(&self, config: &Config) -> u64 {
+        (self.fee_rate + get_rbf_fee_increment(config)) * self.final_size
+            + cmp::max(1, self.spent_in_attempts)
+            + self.final_size
+            + self.sunset_fee
+            + self.sortition_fee
+    }
+
     pub fn amount_per_output(&self) -> u64 {
         self.sortition_fee / self.outputs_len
     }
@@ -314,6 +380,7 @@ impl BitcoinRegtestController {
             should_keep_running: should_keep_running.clone(),
         };
 
+        let wallet = make_wallet_backend(&config);
         Self {
             use_coordinator: coordinator_channel,
             config,
@@ -325,6 +392,8 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running,
             allow_rbf: true,
+            wallet,
+            utxo_cache: Mutex::new(None),
         }
     }
 
@@ -360,6 +429,7 @@ impl BitcoinRegtestController {
             should_keep_running: None,
         };
 
+        let wallet = make_wallet_backend(&config);
         Self {
             use_coordinator: None,
             config,
@@ -371,6 +441,8 @@ impl BitcoinRegtestController {
             ongoing_block_commit: None,
             should_keep_running: None,
             allow_rbf: true,
+            wallet,
+            utxo_cache: Mutex::new(None),
         }
     }
 
@@ -566,6 +638,14 @@ impl BitcoinRegtestController {
             received_at: Instant::now(),
         };
 
+        // UTXOs may have been spent or confirmed in a new burn block
+        let new_block = self.chain_tip.as_ref().map_or(true, |tip| {
+            tip.block_snapshot.burn_header_hash != burnchain_tip.block_snapshot.burn_header_hash
+        });
+        if new_block {
+            self.invalidate_utxo_cache();
+        }
+
         self.chain_tip = Some(burnchain_tip.clone());
         debug!("Done receiving blocks");
 
@@ -676,12 +756,60 @@ impl BitcoinRegtestController {
     /// Checks if the config-supplied wallet exists.
     /// If it does not exist, this function creates it.
     pub fn create_wallet_if_dne(&self) -> RPCResult<()> {
-        let wallets = BitcoinRPCRequest::list_wallets(&self.config)?;
+        self.wallet.create_wallet_if_dne()
+    }
 
-        if !wallets.contains(&self.config.burnchain.wallet_name) {
-            BitcoinRPCRequest::create_wallet(&self.config, &self.config.burnchain.wallet_name)?;
+    /// Forget the cached UTXOs, so that the next `get_utxos()` asks the wallet
+    fn invalidate_utxo_cache(&self) {
+        *self
+            .utxo_cache
+            .lock()
+            .expect("FATAL: UTXO cache lock poisoned") = None;
+    }
+
+    /// List the UTXOs of `filter_addresses` in the wallet, retrying until the wallet answers. A
+    /// listing of every UTXO is cached for later queries against the same burn block.
+    fn fetch_utxos(
+        &self,
+        filter_addresses: &[String],
+        total_required: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> UTXOSet {
+        loop {
+            let result = self.wallet.list_unspent(
+                filter_addresses,
+                !self.allow_rbf, // if RBF is disabled, then we can use 0-conf txs
+                total_required,
+                utxos_to_exclude,
+                block_height,
+            );
+
+            // Perform request
+            match result {
+                Ok(utxos) => {
+                    // only a complete listing can answer later queries that exclude other UTXOs
+                    if utxos_to_exclude.is_none() && !utxos.is_empty() {
+                        *self
+                            .utxo_cache
+                            .lock()
+                            .expect("FATAL: UTXO cache lock poisoned") = Some(UTXOCache {
+                            block_height,
+                            addresses: filter_addresses.to_vec(),
+                            include_unsafe: !self.allow_rbf,
+                            minimum_amount: total_required,
+                            utxos: utxos.clone(),
+                        });
+                    }
+                    return utxos;
+                }
+                Err(e) => {
+                    error!("Bitcoin RPC failure: error listing utxos {:?}", e);
+                    sleep_ms(5000);
+                    continue;
+                }
+            };
         }
-        Ok(())
     }
 
     pub fn get_utxos(
@@ -715,27 +843,31 @@ impl BitcoinRegtestController {
         );
         let filter_addresses = vec![addr2str(&address)];
 
-        let mut utxos = loop {
-            let result = BitcoinRPCRequest::list_unspent(
-                &self.config,
-                filter_addresses.clone(),
-                !self.allow_rbf, // if RBF is disabled, then we can use 0-conf txs
+        let cached = self
+            .utxo_cache
+            .lock()
+            .expect("FATAL: UTXO cache lock poisoned")
+            .as_ref()
+            .and_then(|cache| {
+                cache.lookup(
+                    &filter_addresses,
+                    !self.allow_rbf,
+                    total_required,
+                    &utxos_to_exclude,
+                    block_height,
+                )
+            });
+        let mut utxos = if let Some(utxos) = cached {
+            increment_btc_utxo_cache_hits_counter();
+            utxos
+        } else {
+            increment_btc_utxo_cache_misses_counter();
+            self.fetch_utxos(
+                &filter_addresses,
                 total_required,
                 &utxos_to_exclude,
                 block_height,
-            );
-
-            // Perform request
-            match result {
-                Ok(utxos) => {
-                    break utxos;
-                }
-                Err(e) => {
-                    error!("Bitcoin RPC failure: error listing utxos {:?}", e);
-                    sleep_ms(5000);
-                    continue;
-                }
-            };
+            )
         };
 
         let utxos = if utxos.is_empty() {
@@ -746,13 +878,12 @@ impl BitcoinRegtestController {
                     // Assuming that miners are in charge of correctly operating their bitcoind nodes sounds
                     // reasonable to me.
                     // $ bitcoin-cli importaddress mxVFsFW5N4mu1HPkxPttorvocvzeZ7KZyk
-                    let _result = BitcoinRPCRequest::import_public_key(&self.config, &pubk);
+                    let _result = self.wallet.import_public_key(&pubk);
                     sleep_ms(1000);
                 }
 
-                let result = BitcoinRPCRequest::list_unspent(
-                    &self.config,
-                    filter_addresses.clone(),
+                let result = self.wallet.list_unspent(
+                    &filter_addresses,
                     !self.allow_rbf, // if RBF is disabled, then we can use 0-conf txs
                     total_required,
                     &utxos_to_exclude,
@@ -1222,11 +1353,14 @@ impl BitcoinRegtestController {
         let txid = Txid::from_bytes(&txid[..]).unwrap();
         let mut txids = previous_txids.clone();
         txids.push(txid.clone());
+        let now_ms = get_epoch_time_ms() as u64;
         let ongoing_block_commit = OngoingBlockCommit {
             payload,
             utxos,
             fees: estimated_fees,
             txids,
+            first_attempt_ms: now_ms,
+            last_attempt_ms: now_ms,
         };
 
         info!(
@@ -1372,7 +1506,7 @@ impl BitcoinRegtestController {
         } else {
             // Case 2) ii): Attempt to RBF
             info!("Attempt to replace by fee an outdated leader block commit");
-            self.send_block_commit_operation(
+            let res = self.send_block_commit_operation(
                 epoch_id,
                 payload,
                 signer,
@@ -1380,7 +1514,9 @@ impl BitcoinRegtestController {
                 None,
                 Some(ongoing_op.fees.clone()),
                 &ongoing_op.txids,
-            )
+            );
+            self.carry_first_attempt(&ongoing_op, res.is_some());
+            res
         };
 
         if res.is_none() {
@@ -1390,6 +1526,87 @@ impl BitcoinRegtestController {
         res
     }
 
+    /// A replacement of `ongoing_op` is part of the same attempt to get a commit mined
+    fn carry_first_attempt(&mut self, ongoing_op: &OngoingBlockCommit, replaced: bool) {
+        if !replaced {
+            return;
+        }
+        if let Some(replacement) = self.ongoing_block_commit.as_mut() {
+            replacement.first_attempt_ms = ongoing_op.first_attempt_ms;
+        }
+    }
+
+    /// Re-broadcast the ongoing block-commit with a higher fee if it has gone unconfirmed for
+    /// longer than `burnchain.rbf_bump_interval_ms`, as long as the burnchain tip it was built
+    /// on is still the canonical tip. Returns the txid of the replacement, if one was sent.
+    pub fn bump_ongoing_block_commit(&mut self, signer: &mut BurnchainOpSigner) -> Option<Txid> {
+        if !self.allow_rbf || self.config.node.mock_mining {
+            return None;
+        }
+        let decision = self
+            .ongoing_block_commit
+            .as_ref()?
+            .rbf_decision(&self.config, get_epoch_time_ms() as u64);
+        match decision {
+            RBFDecision::Bump => {}
+            RBFDecision::PastDeadline | RBFDecision::OverBudget => {
+                debug!("Not bumping the fee of the ongoing block-commit"; "reason" => ?decision);
+                return None;
+            }
+            RBFDecision::Disabled | RBFDecision::Wait => return None,
+        }
+
+        let _ = self.sortdb_mut();
+        let burnchain_db = self.burnchain_db.as_ref().expect("BurnchainDB not opened");
+        let ongoing_op = self.ongoing_block_commit.as_ref()?;
+
+        for txid in ongoing_op.txids.iter() {
+            if burnchain_db
+                .find_burnchain_op(&self.indexer, txid)
+                .is_some()
+            {
+                debug!(
+                    "Ongoing block-commit {} was mined, no need to bump its fee",
+                    txid
+                );
+                return None;
+            }
+        }
+
+        // once a new burn block arrives, the next tenure sends a new commit instead
+        let burn_chain_tip = burnchain_db.get_canonical_chain_tip().ok()?;
+        if burn_chain_tip.block_hash != ongoing_op.utxos.bhh {
+            return None;
+        }
+        let epoch_id =
+            SortitionDB::get_stacks_epoch(self.sortdb_ref().conn(), burn_chain_tip.block_height)
+                .ok()??
+                .epoch_id;
+
+        let ongoing_op = self.ongoing_block_commit.take()?;
+        info!(
+            "Bumping the fee of an unconfirmed leader block commit";
+            "txid" => %ongoing_op.txids.last().map(|txid| txid.to_hex()).unwrap_or_default(),
+            "fee_rate" => ongoing_op.fees.fee_rate,
+            "attempts" => ongoing_op.txids.len(),
+        );
+        let res = self.send_block_commit_operation(
+            epoch_id,
+            ongoing_op.payload.clone(),
+            signer,
+            Some(ongoing_op.utxos.clone()),
+            None,
+            Some(ongoing_op.fees.clone()),
+            &ongoing_op.txids,
+        );
+        self.carry_first_attempt(&ongoing_op, res.is_some());
+        let Some(tx) = res else {
+            self.ongoing_block_commit = Some(ongoing_op);
+            return None;
+        };
+        self.send_transaction(SerializedTx::new(tx))
+    }
+
     pub(crate) fn get_miner_address(
         &self,
         epoch_id: StacksEpochId,
@@ -1651,10 +1868,12 @@ impl BitcoinRegtestController {
     /// failure.
     pub fn send_transaction(&self, transaction: SerializedTx) -> Option<Txid> {
         test_debug!("Send raw transaction: {}", transaction.to_hex());
-        let result = BitcoinRPCRequest::send_raw_transaction(&self.config, transaction.to_hex());
+        let result = self.wallet.send_raw_transaction(transaction.to_hex());
         match result {
             Ok(_) => {
                 test_debug!("Sent transaction {}", &transaction.txid);
+                // the transaction spent some of the cached UTXOs
+                self.invalidate_utxo_cache();
                 Some(transaction.txid())
             }
             Err(e) => {
@@ -1994,8 +2213,8 @@ impl BurnchainController for BitcoinRegtestController {
 
 #[derive(Debug, Clone)]
 pub struct UTXOSet {
-    bhh: BurnchainHeaderHash,
-    utxos: Vec<UTXO>,
+    pub bhh: BurnchainHeaderHash,
+    pub utxos: Vec<UTXO>,
 }
 
 impl UTXOSet {
@@ -2008,6 +2227,56 @@ impl UTXOSet {
     }
 }
 
+/// The result of the last `listunspent` query, which answers later queries for the same burn
+/// block that ask for a subset of it
+#[derive(Debug, Clone)]
+struct UTXOCache {
+    block_height: u64,
+    addresses: Vec<String>,
+    include_unsafe: bool,
+    minimum_amount: u64,
+    utxos: UTXOSet,
+}
+
+impl UTXOCache {
+    /// The cached UTXOs that the given query would return, or `None` if the query must go to
+    /// the wallet
+    fn lookup(
+        &self,
+        addresses: &[String],
+        include_unsafe: bool,
+        minimum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> Option<UTXOSet> {
+        if self.block_height != block_height
+            || self.addresses != addresses
+            || self.include_unsafe != include_unsafe
+            || self.minimum_amount > minimum_amount
+        {
+            return None;
+        }
+        let txids_to_filter = utxos_to_exclude
+            .as_ref()
+            .map(|excluded| excluded.utxos.iter().map(|utxo| utxo.txid).collect())
+            .unwrap_or(vec![]);
+        let utxos: Vec<_> = self
+            .utxos
+            .utxos
+            .iter()
+            .filter(|utxo| utxo.amount >= minimum_amount && !txids_to_filter.contains(&utxo.txid))
+            .cloned()
+            .collect();
+        if utxos.is_empty() {
+            return None;
+        }
+        Some(UTXOSet {
+            bhh: self.utxos.bhh.clone(),
+            utxos,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SerializedTx {
     pub bytes: Vec<u8>,
@@ -2143,19 +2412,28 @@ pub enum RPCError {
 type RPCResult<T> = Result<T, RPCError>;
 
 impl BitcoinRPCRequest {
-    fn build_rpc_request(config: &Config, payload: &BitcoinRPCRequest) -> Request {
+    /// Whether the RPC method must be called on the config-supplied wallet
+    fn needs_wallet(method: &str) -> bool {
+        match method {
+            "importaddress" | "listunspent" => true,
+            _ => false,
+        }
+    }
+
+    fn build_rpc_request(config: &Config, method: &str) -> Request {
         let url = {
             // some methods require a wallet ID
-            let wallet_id = match payload.method.as_str() {
-                "importaddress" | "listunspent" => Some(config.burnchain.wallet_name.clone()),
-                _ => None,
+            let wallet_id = if BitcoinRPCRequest::needs_wallet(method) {
+                Some(config.burnchain.wallet_name.clone())
+            } else {
+                None
             };
             let url = config.burnchain.get_rpc_url(wallet_id);
             Url::parse(&url).expect(&format!("Unable to parse {} as a URL", url))
         };
         debug!(
             "BitcoinRPC builder '{}': {:?}:{:?}@{}",
-            &payload.method, &config.burnchain.username, &config.burnchain.password, &url
+            method, &config.burnchain.username, &config.burnchain.password, &url
         );
 
         let mut req = Request::new(Method::Post, url);
@@ -2209,15 +2487,40 @@ impl BitcoinRPCRequest {
         utxos_to_exclude: &Option<UTXOSet>,
         block_height: u64,
     ) -> RPCResult<UTXOSet> {
-        let payload = BitcoinRPCRequest {
-            method: "getblockhash".to_string(),
-            params: vec![block_height.into()],
-            id: "stacks".to_string(),
-            jsonrpc: "2.0".to_string(),
-        };
+        let min_conf = 0i64;
+        let max_conf = 9999999i64;
+        let minimum_amount = ParsedUTXO::sat_to_serialized_btc(minimum_sum_amount);
 
-        let mut res = BitcoinRPCRequest::send(&config, payload)?;
-        let bhh = match res.as_object_mut() {
+        // fetch the block hash and the UTXOs in a single round trip
+        let payloads = vec![
+            BitcoinRPCRequest {
+                method: "getblockhash".to_string(),
+                params: vec![block_height.into()],
+                id: "stacks".to_string(),
+                jsonrpc: "2.0".to_string(),
+            },
+            BitcoinRPCRequest {
+                method: "listunspent".to_string(),
+                params: vec![
+                    min_conf.into(),
+                    max_conf.into(),
+                    addresses.into(),
+                    include_unsafe.into(),
+                    json!({ "minimumAmount": minimum_amount }),
+                ],
+                id: "stacks".to_string(),
+                jsonrpc: "2.0".to_string(),
+            },
+        ];
+        let mut responses = BitcoinRPCRequest::send_batch(&config, payloads)?;
+        let mut res = responses
+            .pop()
+            .expect("FATAL: missing listunspent response");
+        let mut bhh_res = responses
+            .pop()
+            .expect("FATAL: missing getblockhash response");
+
+        let bhh = match bhh_res.as_object_mut() {
             Some(res) => {
                 let res = res
                     .get("result")
@@ -2231,24 +2534,6 @@ impl BitcoinRPCRequest {
             _ => return Err(RPCError::Parsing("Failed to get UTXOs".to_string())),
         }?;
 
-        let min_conf = 0i64;
-        let max_conf = 9999999i64;
-        let minimum_amount = ParsedUTXO::sat_to_serialized_btc(minimum_sum_amount);
-
-        let payload = BitcoinRPCRequest {
-            method: "listunspent".to_string(),
-            params: vec![
-                min_conf.into(),
-                max_conf.into(),
-                addresses.into(),
-                include_unsafe.into(),
-                json!({ "minimumAmount": minimum_amount }),
-            ],
-            id: "stacks".to_string(),
-            jsonrpc: "2.0".to_string(),
-        };
-
-        let mut res = BitcoinRPCRequest::send(&config, payload)?;
         let txids_to_filter = if let Some(utxos_to_exclude) = utxos_to_exclude {
             utxos_to_exclude
                 .utxos
@@ -2360,21 +2645,29 @@ impl BitcoinRPCRequest {
             );
         }
 
-        for address in addresses.into_iter() {
+        let addresses: Vec<_> = addresses.iter().map(addr2str).collect();
+        for address in addresses.iter() {
             debug!(
                 "Import address {} for public key {}",
-                addr2str(&address),
+                address,
                 public_key.to_hex()
             );
+        }
 
-            let payload = BitcoinRPCRequest {
+        // get the checksum of every descriptor in one round trip, then import them all at once
+        let payloads = addresses
+            .iter()
+            .map(|address| BitcoinRPCRequest {
                 method: "getdescriptorinfo".to_string(),
-                params: vec![format!("addr({})", &addr2str(&address)).into()],
+                params: vec![format!("addr({})", address).into()],
                 id: "stacks".to_string(),
                 jsonrpc: "2.0".to_string(),
-            };
+            })
+            .collect();
+        let results = BitcoinRPCRequest::send_batch(&config, payloads)?;
 
-            let result = BitcoinRPCRequest::send(&config, payload)?;
+        let mut descriptors = vec![];
+        for (address, result) in addresses.iter().zip(results.iter()) {
             let checksum = result
                 .get(&"result".to_string())
                 .and_then(|res| res.as_object())
@@ -2382,20 +2675,19 @@ impl BitcoinRPCRequest {
                 .and_then(|checksum_val| checksum_val.as_str())
                 .ok_or(RPCError::Bitcoind(format!(
                     "Did not receive an object with `checksum` from `getdescriptorinfo \"{}\"`",
-                    &addr2str(&address)
+                    address
                 )))?;
+            descriptors.push(json!({ "desc": format!("addr({})#{}", address, checksum), "timestamp": 0, "internal": true }));
+        }
 
-            let payload = BitcoinRPCRequest {
-                method: "importdescriptors".to_string(),
-                params: vec![
-                    json!([{ "desc": format!("addr({})#{}", &addr2str(&address), &checksum), "timestamp": 0, "internal": true }]),
-                ],
-                id: "stacks".to_string(),
-                jsonrpc: "2.0".to_string(),
-            };
+        let payload = BitcoinRPCRequest {
+            method: "importdescriptors".to_string(),
+            params: vec![descriptors.into()],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
 
-            BitcoinRPCRequest::send(&config, payload)?;
-        }
+        BitcoinRPCRequest::send(&config, payload)?;
         Ok(())
     }
 
@@ -2451,48 +2743,117 @@ impl BitcoinRPCRequest {
     }
 
     fn send(config: &Config, payload: BitcoinRPCRequest) -> RPCResult<serde_json::Value> {
-        let mut request = BitcoinRPCRequest::build_rpc_request(&config, &payload);
+        let body = serde_json::to_vec(&json!(payload))
+            .map_err(|err| RPCError::Network(format!("RPC Error: {}", err)))?;
+        instrument_btc_rpc_call(&payload.method, || {
+            BitcoinRPCRequest::send_body(config, &payload.method, body)
+        })
+    }
 
-        let body = match serde_json::to_vec(&json!(payload)) {
-            Ok(body) => body,
-            Err(err) => {
-                return Err(RPCError::Network(format!("RPC Error: {}", err)));
+    /// Send several calls to bitcoind in a single JSON-RPC batch, so that they take one round
+    /// trip instead of one each. Returns the response to each call, in the order of `payloads`.
+    /// If any call needs the wallet, the whole batch is sent to the wallet endpoint.
+    fn send_batch(
+        config: &Config,
+        mut payloads: Vec<BitcoinRPCRequest>,
+    ) -> RPCResult<Vec<serde_json::Value>> {
+        if payloads.is_empty() {
+            return Ok(vec![]);
+        }
+        // responses to a batch may arrive in any order, so give each call its own id
+        for (i, payload) in payloads.iter_mut().enumerate() {
+            payload.id = format!("stacks-{}", i);
+        }
+        let methods: Vec<_> = payloads.iter().map(|p| p.method.as_str()).collect();
+        let endpoint_method = methods
+            .iter()
+            .find(|method| BitcoinRPCRequest::needs_wallet(method))
+            .unwrap_or(&methods[0]);
+        let label = format!("batch:{}", methods.join(","));
+        let body = serde_json::to_vec(&json!(payloads))
+            .map_err(|err| RPCError::Network(format!("RPC Error: {}", err)))?;
+        let res = instrument_btc_rpc_call(&label, || {
+            BitcoinRPCRequest::send_body(config, endpoint_method, body)
+        })?;
+
+        let mut responses = match res {
+            serde_json::Value::Array(responses) => responses,
+            _ => {
+                return Err(RPCError::Parsing(format!(
+                    "Bitcoin RPC: expected an array in response to {}",
+                    label
+                )))
             }
         };
+        let mut ordered = Vec::with_capacity(payloads.len());
+        for payload in payloads.iter() {
+            let pos = responses
+                .iter()
+                .position(|response| response.get("id") == Some(&json!(payload.id)))
+                .ok_or(RPCError::Parsing(format!(
+                    "Bitcoin RPC: no response to {} in {}",
+                    &payload.method, label
+                )))?;
+            ordered.push(responses.swap_remove(pos));
+        }
+        Ok(ordered)
+    }
 
-        request.append_header("Content-Type", "application/json");
-        request.set_body(body);
+    /// Send a request body to bitcoind's RPC endpoint for `method`, reusing an idle connection
+    /// to it if there is one
+    fn send_body(config: &Config, method: &str, body: Vec<u8>) -> RPCResult<serde_json::Value> {
+        let addr = config.burnchain.get_rpc_socket_addr();
 
-        let mut response = async_std::task::block_on(async move {
-            let stream = match TcpStream::connect(config.burnchain.get_rpc_socket_addr()).await {
-                Ok(stream) => stream,
-                Err(err) => {
-                    return Err(RPCError::Network(format!(
-                        "Bitcoin RPC: connection failed - {:?}",
-                        err
-                    )))
+        // bitcoind may have closed an idle connection, so a failure on one is retried once on a
+        // fresh connection
+        if let Some(stream) = RPCConnectionPool::take(&addr) {
+            let request = BitcoinRPCRequest::build_rpc_request(config, method);
+            match BitcoinRPCRequest::send_on(&addr, stream, request, body.clone()) {
+                Err(RPCError::Network(e)) => {
+                    debug!("Bitcoin RPC: pooled connection failed, reconnecting: {}", e);
                 }
-            };
+                res => return res,
+            }
+        }
+
+        let stream = async_std::task::block_on(TcpStream::connect(addr)).map_err(|err| {
+            RPCError::Network(format!("Bitcoin RPC: connection failed - {:?}", err))
+        })?;
+        let request = BitcoinRPCRequest::build_rpc_request(config, method);
+        BitcoinRPCRequest::send_on(&addr, stream, request, body)
+    }
+
+    /// Send the request over `stream`, and return the stream to the pool once the response is
+    /// read if bitcoind keeps the connection open
+    fn send_on(
+        addr: &SocketAddr,
+        stream: TcpStream,
+        mut request: Request,
+        body: Vec<u8>,
+    ) -> RPCResult<serde_json::Value> {
+        request.append_header("Content-Type", "application/json");
+        request.set_body(body);
 
-            match client::connect(stream, request).await {
-                Ok(response) => Ok(response),
+        let (status, keep_alive, res, buffer) = async_std::task::block_on(async {
+            let mut response = match client::connect(stream.clone(), request).await {
+                Ok(response) => response,
                 Err(err) => {
                     return Err(RPCError::Network(format!(
                         "Bitcoin RPC: invoking procedure failed - {:?}",
                         err
                     )))
                 }
-            }
-        })?;
-
-        let status = response.status();
-
-        let (res, buffer) = async_std::task::block_on(async move {
+            };
+            let status = response.status();
+            let keep_alive = response
+                .header("Connection")
+                .map(|value| !value.as_str().eq_ignore_ascii_case("close"))
+                .unwrap_or(true);
             let mut buffer = Vec::new();
             let mut body = response.take_body();
             let res = body.read_to_end(&mut buffer).await;
-            (res, buffer)
-        });
+            Ok((status, keep_alive, res, buffer))
+        })?;
 
         if !status.is_success() {
             return Err(RPCError::Network(format!(
@@ -2513,12 +2874,100 @@ impl BitcoinRPCRequest {
             )));
         }
 
+        if keep_alive {
+            RPCConnectionPool::put(addr, stream);
+        }
+
         let payload = serde_json::from_slice::<serde_json::Value>(&buffer[..])
             .map_err(|e| RPCError::Parsing(format!("Bitcoin RPC: {}", e)))?;
         Ok(payload)
     }
 }
 
+/// The most idle connections kept open to each bitcoind RPC endpoint
+const MAX_IDLE_RPC_CONNECTIONS: usize = 8;
+
+lazy_static! {
+    /// Idle keep-alive connections to each bitcoind RPC endpoint
+    static ref RPC_CONNECTION_POOL: Mutex<HashMap<SocketAddr, Vec<TcpStream>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Reuses bitcoind RPC connections across requests, so that back-to-back calls (e.g. during a
+/// UTXO scan) do not each pay for a new TCP handshake
+struct RPCConnectionPool;
+
+impl RPCConnectionPool {
+    /// Take an idle connection to `addr`, if there is one
+    fn take(addr: &SocketAddr) -> Option<TcpStream> {
+        RPC_CONNECTION_POOL
+            .lock()
+            .expect("FATAL: RPC connection pool lock poisoned")
+            .get_mut(addr)
+            .and_then(|idle| idle.pop())
+    }
+
+    /// Return a connection to `addr` that is ready for another request
+    fn put(addr: &SocketAddr, stream: TcpStream) {
+        let mut pool = RPC_CONNECTION_POOL
+            .lock()
+            .expect("FATAL: RPC connection pool lock poisoned");
+        let idle = pool.entry(addr.clone()).or_insert_with(Vec::new);
+        if idle.len() < MAX_IDLE_RPC_CONNECTIONS {
+            idle.push(stream);
+        }
+    }
+}
+
+/// Wallet backend that tracks the miner's UTXOs in a wallet of the bitcoind node, and broadcasts
+/// through it
+pub struct BitcoindWallet {
+    config: Config,
+}
+
+impl BitcoindWallet {
+    pub fn new(config: Config) -> BitcoindWallet {
+        BitcoindWallet { config }
+    }
+}
+
+impl WalletBackend for BitcoindWallet {
+    fn create_wallet_if_dne(&self) -> RPCResult<()> {
+        let wallets = BitcoinRPCRequest::list_wallets(&self.config)?;
+
+        if !wallets.contains(&self.config.burnchain.wallet_name) {
+            BitcoinRPCRequest::create_wallet(&self.config, &self.config.burnchain.wallet_name)?;
+        }
+        Ok(())
+    }
+
+    fn import_public_key(&self, public_key: &Secp256k1PublicKey) -> RPCResult<()> {
+        BitcoinRPCRequest::import_public_key(&self.config, public_key)
+    }
+
+    fn list_unspent(
+        &self,
+        addresses: &[String],
+        include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> RPCResult<UTXOSet> {
+        BitcoinRPCRequest::list_unspent(
+            &self.config,
+            addresses.to_vec(),
+            include_unsafe,
+            minimum_sum_amount,
+            utxos_to_exclude,
+            block_height,
+        )
+    }
+
+    fn send_raw_transaction(&self, tx: String) -> RPCResult<()> {
+        BitcoinRPCRequest::send_raw_transaction(&self.config, tx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::env::temp_dir;
@@ -2545,4 +2994,126 @@ mod tests {
 
         assert_eq!(get_satoshis_per_byte(&config), 51);
     }
+
+    #[test]
+    fn test_utxo_cache_lookup() {
+        let utxo = |txid_byte: u8, amount: u64| UTXO {
+            txid: Sha256dHash([txid_byte; 32]),
+            vout: 0,
+            script_pub_key: Script::new(),
+            amount,
+            confirmations: 1,
+        };
+        let addresses = vec!["mxVFsFW5N4mu1HPkxPttorvocvzeZ7KZyk".to_string()];
+        let cache = UTXOCache {
+            block_height: 100,
+            addresses: addresses.clone(),
+            include_unsafe: false,
+            minimum_amount: 1000,
+            utxos: UTXOSet {
+                bhh: BurnchainHeaderHash([0x01; 32]),
+                utxos: vec![utxo(1, 1000), utxo(2, 5000), utxo(3, 10000)],
+            },
+        };
+
+        // a query for a subset of the cached UTXOs is answered from the cache
+        let utxos = cache.lookup(&addresses, false, 5000, &None, 100).unwrap();
+        assert_eq!(utxos.bhh, BurnchainHeaderHash([0x01; 32]));
+        assert_eq!(utxos.utxos, vec![utxo(2, 5000), utxo(3, 10000)]);
+
+        let excluded = UTXOSet {
+            bhh: BurnchainHeaderHash([0x01; 32]),
+            utxos: vec![utxo(3, 10000)],
+        };
+        let utxos = cache
+            .lookup(&addresses, false, 1000, &Some(excluded.clone()), 100)
+            .unwrap();
+        assert_eq!(utxos.utxos, vec![utxo(1, 1000), utxo(2, 5000)]);
+
+        // anything else goes to the wallet
+        assert!(cache.lookup(&addresses, false, 1000, &None, 101).is_none());
+        assert!(cache.lookup(&addresses, true, 1000, &None, 100).is_none());
+        assert!(cache.lookup(&addresses, false, 500, &None, 100).is_none());
+        assert!(cache.lookup(&vec![], false, 1000, &None, 100).is_none());
+        assert!(cache
+            .lookup(&addresses, false, 10000, &Some(excluded), 100)
+            .is_none());
+    }
+
+    #[test]
+    fn test_rbf_decision() {
+        use stacks::burnchains::BurnchainSigner;
+        use stacks_common::types::chainstate::{BlockHeaderHash, VRFSeed};
+
+        let mut config = Config::default();
+        let payload = LeaderBlockCommitOp {
+            sunset_burn: 0,
+            block_header_hash: BlockHeaderHash([0xff; 32]),
+            burn_fee: 20_000,
+            input: (Txid([0; 32]), 0),
+            apparent_sender: BurnchainSigner("miner".to_string()),
+            key_block_ptr: 1,
+            key_vtxindex: 1,
+            memo: vec![],
+            new_seed: VRFSeed([0x11; 32]),
+            parent_block_ptr: 0,
+            parent_vtxindex: 0,
+            vtxindex: 0,
+            txid: Txid([0u8; 32]),
+            block_height: 0,
+            burn_header_hash: BurnchainHeaderHash::zero(),
+            burn_parent_modulus: 0,
+            commit_outs: vec![PoxAddress::standard_burn_address(false)],
+        };
+        let mut fees = LeaderBlockCommitFees::estimated_fees_from_payload(&payload, &config);
+        fees.register_replacement(350);
+        let mut ongoing = OngoingBlockCommit {
+            payload,
+            utxos: UTXOSet {
+                bhh: BurnchainHeaderHash([0x01; 32]),
+                utxos: vec![],
+            },
+            fees,
+            txids: vec![Txid([0x02; 32])],
+            first_attempt_ms: 1_000,
+            last_attempt_ms: 1_000,
+        };
+
+        assert_eq!(
+            ongoing.rbf_decision(&config, 1_000_000),
+            RBFDecision::Disabled
+        );
+
+        config.burnchain.rbf_bump_interval_ms = Some(60_000);
+        config.burnchain.rbf_bump_deadline_ms = 300_000;
+        assert_eq!(ongoing.rbf_decision(&config, 30_000), RBFDecision::Wait);
+        assert_eq!(ongoing.rbf_decision(&config, 61_000), RBFDecision::Bump);
+
+        // the interval counts from the last bump, the deadline from the first attempt
+        ongoing.last_attempt_ms = 250_000;
+        assert_eq!(ongoing.rbf_decision(&config, 300_000), RBFDecision::Wait);
+        assert_eq!(
+            ongoing.rbf_decision(&config, 310_000),
+            RBFDecision::PastDeadline
+        );
+        ongoing.last_attempt_ms = 1_000;
+
+        // a bump may not exceed the total spend cap...
+        let total_after_bump = ongoing.fees.total_spent_after_bump(&config);
+        config.burnchain.rbf_max_total_spend = Some(total_after_bump);
+        assert_eq!(ongoing.rbf_decision(&config, 61_000), RBFDecision::Bump);
+        config.burnchain.rbf_max_total_spend = Some(total_after_bump - 1);
+        assert_eq!(
+            ongoing.rbf_decision(&config, 61_000),
+            RBFDecision::OverBudget
+        );
+        config.burnchain.rbf_max_total_spend = None;
+
+        // ...or the maximum fee rate
+        ongoing.fees.fee_rate = get_satoshis_per_byte(&config) * get_max_rbf(&config) / 100;
+        assert_eq!(
+            ongoing.rbf_decision(&config, 61_000),
+            RBFDecision::OverBudget
+        );
+    }
 }
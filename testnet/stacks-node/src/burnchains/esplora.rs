@@ -0,0 +1,269 @@
+use async_h1::client;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, Url};
+use stacks::burnchains::bitcoin::address::{
+    BitcoinAddress, LegacyBitcoinAddress, LegacyBitcoinAddressType, SegwitBitcoinAddress,
+};
+use stacks::burnchains::Address;
+use stacks_common::deps_common::bitcoin::blockdata::script::Script;
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+use stacks_common::types::chainstate::BurnchainHeaderHash;
+use stacks_common::util::secp256k1::Secp256k1PublicKey;
+
+use super::bitcoin_regtest_controller::{RPCError, UTXOSet, UTXO};
+use super::WalletBackend;
+
+/// A UTXO, as listed by `GET /address/:address/utxo`
+#[derive(Debug, Clone, Deserialize)]
+struct EsploraUTXO {
+    txid: String,
+    vout: u32,
+    value: u64,
+    status: EsploraTxStatus,
+}
+
+/// Whether and where a transaction was mined
+#[derive(Debug, Clone, Deserialize)]
+struct EsploraTxStatus {
+    confirmed: bool,
+    block_height: Option<u64>,
+}
+
+/// Wallet backend that discovers UTXOs and broadcasts transactions through an Esplora HTTP API,
+/// such as the one served by electrs or a self-hosted mempool.space. Esplora indexes every
+/// address, so there is no wallet to create and no key to import.
+/// Only plain http:// endpoints are supported.
+pub struct EsploraWallet {
+    /// The base URL of the API, e.g. `http://127.0.0.1:3002/api`
+    base_url: String,
+}
+
+impl EsploraWallet {
+    pub fn new(base_url: String) -> EsploraWallet {
+        EsploraWallet { base_url }
+    }
+
+    /// Send a request to the API, and return the body of a successful response
+    fn request(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<String, RPCError> {
+        let url_str = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        let url = Url::parse(&url_str)
+            .map_err(|e| RPCError::Network(format!("Esplora: invalid URL {}: {:?}", url_str, e)))?;
+        let addr = url
+            .socket_addrs(|| Some(80))
+            .map_err(|e| RPCError::Network(format!("Esplora: failed to resolve {}: {:?}", url, e)))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| RPCError::Network(format!("Esplora: failed to resolve {}", url)))?;
+
+        let mut request = Request::new(method, url);
+        if let Some(body) = body {
+            request.append_header("Content-Type", "text/plain");
+            request.set_body(body);
+        }
+
+        let (status, body) = async_std::task::block_on(async move {
+            let stream = TcpStream::connect(addr).await.map_err(|err| {
+                RPCError::Network(format!("Esplora: connection failed - {:?}", err))
+            })?;
+            let mut response = client::connect(stream, request)
+                .await
+                .map_err(|err| RPCError::Network(format!("Esplora: request failed - {:?}", err)))?;
+            let body = response.body_string().await.map_err(|err| {
+                RPCError::Network(format!("Esplora: unable to read body - {:?}", err))
+            })?;
+            Ok((response.status(), body))
+        })?;
+
+        if !status.is_success() {
+            return Err(RPCError::Bitcoind(format!(
+                "Esplora: status({}) != success, body is '{}'",
+                status, body
+            )));
+        }
+        Ok(body)
+    }
+
+    fn get_block_hash(&self, block_height: u64) -> Result<BurnchainHeaderHash, RPCError> {
+        let body = self.request(Method::Get, &format!("block-height/{}", block_height), None)?;
+        BurnchainHeaderHash::from_hex(body.trim()).map_err(|_| {
+            RPCError::Parsing(format!("Esplora: invalid block hash '{}'", body.trim()))
+        })
+    }
+
+    fn get_tip_height(&self) -> Result<u64, RPCError> {
+        let body = self.request(Method::Get, "blocks/tip/height", None)?;
+        body.trim().parse().map_err(|_| {
+            RPCError::Parsing(format!("Esplora: invalid tip height '{}'", body.trim()))
+        })
+    }
+}
+
+/// The output script that pays `address`. Only the address types a miner spends from are
+/// supported.
+fn address_script_pubkey(address: &str) -> Result<Script, RPCError> {
+    match BitcoinAddress::from_string(address) {
+        Some(BitcoinAddress::Legacy(addr))
+            if addr.addrtype == LegacyBitcoinAddressType::PublicKeyHash =>
+        {
+            Ok(LegacyBitcoinAddress::to_p2pkh_tx_out(&addr.bytes, 0).script_pubkey)
+        }
+        Some(BitcoinAddress::Segwit(SegwitBitcoinAddress::P2WPKH(_, bytes))) => {
+            Ok(SegwitBitcoinAddress::to_p2wpkh_tx_out(&bytes, 0).script_pubkey)
+        }
+        _ => Err(RPCError::Parsing(format!(
+            "Esplora: unsupported miner address {}",
+            address
+        ))),
+    }
+}
+
+/// Turn the body of `GET /address/:address/utxo` into the UTXOs the miner may spend
+fn parse_utxos(
+    body: &str,
+    script_pub_key: &Script,
+    tip_height: u64,
+    include_unsafe: bool,
+    minimum_sum_amount: u64,
+    txids_to_filter: &[Sha256dHash],
+) -> Result<Vec<UTXO>, RPCError> {
+    let entries: Vec<EsploraUTXO> = serde_json::from_str(body)
+        .map_err(|e| RPCError::Parsing(format!("Esplora: failed parsing UTXOs: {}", e)))?;
+    let mut utxos = vec![];
+    for entry in entries.into_iter() {
+        let confirmations = match (entry.status.confirmed, entry.status.block_height) {
+            (true, Some(height)) => tip_height.saturating_sub(height).saturating_add(1) as u32,
+            _ => 0,
+        };
+        // Esplora cannot tell our own change apart from unconfirmed payments to us, so every
+        // unconfirmed UTXO is treated as unsafe
+        if confirmations == 0 && !include_unsafe {
+            continue;
+        }
+        if entry.value < minimum_sum_amount {
+            continue;
+        }
+        let txid = match Sha256dHash::from_hex(&entry.txid) {
+            Ok(txid) => txid,
+            Err(err) => {
+                warn!("Unable to get txid from UTXO {:?}", err);
+                continue;
+            }
+        };
+        // Exclude UTXOs that we want to filter
+        if txids_to_filter.contains(&txid) {
+            continue;
+        }
+        utxos.push(UTXO {
+            txid,
+            vout: entry.vout,
+            script_pub_key: script_pub_key.clone(),
+            amount: entry.value,
+            confirmations,
+        });
+    }
+    Ok(utxos)
+}
+
+impl WalletBackend for EsploraWallet {
+    fn create_wallet_if_dne(&self) -> Result<(), RPCError> {
+        Ok(())
+    }
+
+    fn import_public_key(&self, _public_key: &Secp256k1PublicKey) -> Result<(), RPCError> {
+        Ok(())
+    }
+
+    fn list_unspent(
+        &self,
+        addresses: &[String],
+        include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> Result<UTXOSet, RPCError> {
+        let bhh = self.get_block_hash(block_height)?;
+        let tip_height = self.get_tip_height()?;
+        let txids_to_filter = if let Some(utxos_to_exclude) = utxos_to_exclude {
+            utxos_to_exclude
+                .utxos
+                .iter()
+                .map(|utxo| utxo.txid)
+                .collect::<Vec<_>>()
+        } else {
+            vec![]
+        };
+
+        let mut utxos = vec![];
+        for address in addresses.iter() {
+            let script_pub_key = address_script_pubkey(address)?;
+            let body = self.request(Method::Get, &format!("address/{}/utxo", address), None)?;
+            utxos.extend(parse_utxos(
+                &body,
+                &script_pub_key,
+                tip_height,
+                include_unsafe,
+                minimum_sum_amount,
+                &txids_to_filter,
+            )?);
+        }
+        Ok(UTXOSet { bhh, utxos })
+    }
+
+    fn send_raw_transaction(&self, tx: String) -> Result<(), RPCError> {
+        self.request(Method::Post, "tx", Some(tx)).map_err(|e| {
+            error!("Error submitting transaction: {:?}", e);
+            e
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_utxos() {
+        let script_pub_key = address_script_pubkey("mxVFsFW5N4mu1HPkxPttorvocvzeZ7KZyk").unwrap();
+        let body = r#"[
+            {"txid":"4b9b5b2c6bbf8f0bd1dc4f6a2c5f3f9e1d4b1d9b3b8b1c7f0d2e4f6a8b0c2d4e","vout":0,"value":10000,"status":{"confirmed":true,"block_height":100,"block_hash":"00","block_time":0}},
+            {"txid":"5b9b5b2c6bbf8f0bd1dc4f6a2c5f3f9e1d4b1d9b3b8b1c7f0d2e4f6a8b0c2d4e","vout":1,"value":20000,"status":{"confirmed":false}},
+            {"txid":"6b9b5b2c6bbf8f0bd1dc4f6a2c5f3f9e1d4b1d9b3b8b1c7f0d2e4f6a8b0c2d4e","vout":2,"value":500,"status":{"confirmed":true,"block_height":101}},
+            {"txid":"7b9b5b2c6bbf8f0bd1dc4f6a2c5f3f9e1d4b1d9b3b8b1c7f0d2e4f6a8b0c2d4e","vout":3,"value":30000,"status":{"confirmed":true,"block_height":102}}
+        ]"#;
+        let excluded = Sha256dHash::from_hex(
+            "7b9b5b2c6bbf8f0bd1dc4f6a2c5f3f9e1d4b1d9b3b8b1c7f0d2e4f6a8b0c2d4e",
+        )
+        .unwrap();
+
+        // unconfirmed, dust, and excluded UTXOs are skipped
+        let utxos = parse_utxos(body, &script_pub_key, 105, false, 1000, &[excluded]).unwrap();
+        assert_eq!(utxos.len(), 1);
+        assert_eq!(utxos[0].vout, 0);
+        assert_eq!(utxos[0].amount, 10000);
+        assert_eq!(utxos[0].confirmations, 6);
+        assert_eq!(utxos[0].script_pub_key, script_pub_key);
+
+        let utxos = parse_utxos(body, &script_pub_key, 105, true, 1000, &[]).unwrap();
+        let vouts: Vec<_> = utxos.iter().map(|utxo| utxo.vout).collect();
+        assert_eq!(vouts, vec![0, 1, 3]);
+        assert_eq!(utxos[1].confirmations, 0);
+
+        assert!(parse_utxos("not json", &script_pub_key, 105, true, 0, &[]).is_err());
+    }
+
+    #[test]
+    fn test_address_script_pubkey() {
+        let script_pub_key = address_script_pubkey("mxVFsFW5N4mu1HPkxPttorvocvzeZ7KZyk").unwrap();
+        assert_eq!(script_pub_key.len(), 25);
+        let script_pub_key =
+            address_script_pubkey("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx").unwrap();
+        assert_eq!(script_pub_key.len(), 22);
+        assert!(address_script_pubkey("not-an-address").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod bitcoin_regtest_controller;
+pub mod esplora;
 pub mod mocknet_controller;
 
 use std::fmt;
@@ -10,10 +11,14 @@ use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::burn::operations::BlockstackOperationType;
 use stacks::chainstate::burn::BlockSnapshot;
 use stacks::core::{StacksEpoch, StacksEpochId};
+use stacks_common::util::secp256k1::Secp256k1PublicKey;
 
 pub use self::bitcoin_regtest_controller::{make_bitcoin_indexer, BitcoinRegtestController};
+use self::bitcoin_regtest_controller::{BitcoindWallet, RPCError, UTXOSet};
+use self::esplora::EsploraWallet;
 pub use self::mocknet_controller::MocknetController;
 use super::operations::BurnchainOpSigner;
+use crate::config::{Config, WalletBackendName};
 
 #[derive(Debug)]
 pub enum Error {
@@ -60,6 +65,44 @@ pub trait BurnchainController {
     fn bootstrap_chain(&mut self, blocks_count: u64);
 }
 
+/// The wallet operations a bitcoin burnchain controller delegates to its backend: discovering the
+/// miner's UTXOs and broadcasting the miner's transactions.
+/// Block and header sync do not go through the wallet backend.
+pub trait WalletBackend: Send {
+    /// Create the wallet that tracks the miner's UTXOs, if the backend needs one and it does not
+    /// exist yet
+    fn create_wallet_if_dne(&self) -> Result<(), RPCError>;
+    /// Start tracking the UTXOs of the addresses of the given public key
+    fn import_public_key(&self, public_key: &Secp256k1PublicKey) -> Result<(), RPCError>;
+    /// List the UTXOs of the given addresses worth at least `minimum_sum_amount` each, as of the
+    /// burnchain block at `block_height`. Unconfirmed UTXOs are only listed if `include_unsafe`
+    /// is set. UTXOs created by the transactions in `utxos_to_exclude` are skipped.
+    fn list_unspent(
+        &self,
+        addresses: &[String],
+        include_unsafe: bool,
+        minimum_sum_amount: u64,
+        utxos_to_exclude: &Option<UTXOSet>,
+        block_height: u64,
+    ) -> Result<UTXOSet, RPCError>;
+    /// Broadcast the given hex-encoded transaction
+    fn send_raw_transaction(&self, tx: String) -> Result<(), RPCError>;
+}
+
+/// Instantiate the wallet backend named in the burnchain config
+pub fn make_wallet_backend(config: &Config) -> Box<dyn WalletBackend> {
+    match config.burnchain.wallet_backend {
+        WalletBackendName::Bitcoind => Box::new(BitcoindWallet::new(config.clone())),
+        WalletBackendName::Esplora => {
+            let url =
+                config.burnchain.esplora_url.clone().expect(
+                    "FATAL: burnchain.esplora_url is required by the esplora wallet backend",
+                );
+            Box::new(EsploraWallet::new(url))
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BurnchainTip {
     pub block_snapshot: BlockSnapshot,
@@ -10,28 +10,36 @@ use clarity::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdenti
 use rand::RngCore;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{Burnchain, MagicBytes, BLOCKSTACK_MAGIC_MAINNET};
+use stacks::chainstate::stacks::index::cache::DEFAULT_LRU_CACHE_SIZE;
 use stacks::chainstate::stacks::index::marf::MARFOpenOpts;
 use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
 use stacks::chainstate::stacks::miner::{BlockBuilderSettings, MinerStatus};
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
-use stacks::core::mempool::{MemPoolWalkSettings, MemPoolWalkTxTypes};
+use stacks::core::mempool::{
+    MemPoolGCPolicy, MemPoolWalkSettings, MemPoolWalkStrategy, MemPoolWalkTxTypes,
+    MEMPOOL_MAX_TRANSACTION_AGE,
+};
 use stacks::core::{
     StacksEpoch, StacksEpochExtension, StacksEpochId, CHAIN_ID_MAINNET, CHAIN_ID_TESTNET,
     PEER_VERSION_MAINNET, PEER_VERSION_TESTNET,
 };
 use stacks::cost_estimates::fee_medians::WeightedMedianFeeRateEstimator;
+use stacks::cost_estimates::fee_percentiles::PercentileFeeRateEstimator;
 use stacks::cost_estimates::fee_rate_fuzzer::FeeRateFuzzer;
 use stacks::cost_estimates::fee_scalar::ScalarFeeRateEstimator;
 use stacks::cost_estimates::metrics::{CostMetric, ProportionalDotProduct};
 use stacks::cost_estimates::{CostEstimator, FeeEstimator, PessimisticEstimator};
 use stacks::net::atlas::AtlasConfig;
 use stacks::net::connection::ConnectionOptions;
+use stacks::net::httpcore::RPCEndpointGroup;
+use stacks::net::stackerdb::StackerDBReplicationSettings;
 use stacks::net::{Neighbor, NeighborKey};
 use stacks_common::types::chainstate::StacksAddress;
 use stacks_common::types::net::PeerAddress;
 use stacks_common::types::Address;
 use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::hash::hex_bytes;
+use stacks_common::util::log::LogFormat;
 use stacks_common::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
 
 use crate::chain_data::MinerStats;
@@ -39,6 +47,7 @@ use crate::chain_data::MinerStats;
 pub const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
+const DEFAULT_RBF_BUMP_DEADLINE_MS: u64 = 480_000; // 8 minutes
 const LEADER_KEY_TX_ESTIM_SIZE: u64 = 290;
 const BLOCK_COMMIT_TX_ESTIM_SIZE: u64 = 350;
 const INV_REWARD_CYCLES_TESTNET: u64 = 6;
@@ -54,6 +63,9 @@ pub struct ConfigFile {
     pub fee_estimation: Option<FeeEstimationConfigFile>,
     pub miner: Option<MinerConfigFile>,
     pub atlas: Option<AtlasConfigFile>,
+    pub logging: Option<LoggingConfigFile>,
+    pub stackerdb: Option<StackerDBConfigFile>,
+    pub rpc_auth: Option<RPCAuthConfigFile>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -172,6 +184,420 @@ mod tests {
             "ST2TFVBMRPS5SSNP98DQKQ5JNB2B6NZM91C4K3P7B"
         );
     }
+
+    #[test]
+    fn should_load_logging_format() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.logging.format, LogFormat::Text);
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [logging]
+                format = "json"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.logging.format, LogFormat::Json);
+
+        assert_eq!(
+            "Invalid log format 'xml'. Valid formats are: text, json.",
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [logging]
+                    format = "xml"
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+    }
+
+    fn config_with_epochs(epochs: &[(&str, i64)], extra: &str) -> Result<Config, String> {
+        let mut toml = format!("[burnchain]\nmode = \"krypton\"\n{}\n", extra);
+        for (name, start_height) in epochs {
+            toml.push_str(&format!(
+                "[[burnchain.epochs]]\nepoch_name = \"{}\"\nstart_height = {}\n",
+                name, start_height
+            ));
+        }
+        Config::from_config_file(ConfigFile::from_str(&toml).unwrap())
+    }
+
+    #[test]
+    fn should_load_epochs() {
+        let epochs = [
+            (EPOCH_CONFIG_1_0_0, 0),
+            (EPOCH_CONFIG_2_0_0, 0),
+            (EPOCH_CONFIG_2_0_5, 1),
+            (EPOCH_CONFIG_2_1_0, 2),
+            (EPOCH_CONFIG_2_2_0, 3),
+            (EPOCH_CONFIG_2_3_0, 4),
+            (EPOCH_CONFIG_2_4_0, 5),
+        ];
+        let config = config_with_epochs(&epochs, "").unwrap();
+        let configured = config.burnchain.epochs.unwrap();
+        assert_eq!(configured.len(), epochs.len());
+        assert_eq!(configured[3].epoch_id, StacksEpochId::Epoch21);
+        assert_eq!(configured[3].start_height, 2);
+        assert_eq!(configured[3].end_height, 3);
+        assert_eq!(configured[6].end_height, i64::MAX as u64);
+
+        // the order in the file does not matter
+        let mut reversed = epochs;
+        reversed.reverse();
+        assert_eq!(
+            config_with_epochs(&reversed, "").unwrap().burnchain.epochs,
+            config_with_epochs(&epochs, "").unwrap().burnchain.epochs
+        );
+
+        assert_eq!(
+            config_with_epochs(&epochs[..4], "").unwrap_err(),
+            "Configured epochs must include every epoch this node supports. Missing epochs: 2.2, 2.3, 2.4"
+        );
+
+        let mut duplicated = epochs.to_vec();
+        duplicated.push((EPOCH_CONFIG_2_1_0, 6));
+        assert_eq!(
+            config_with_epochs(&duplicated, "").unwrap_err(),
+            "Epoch 2.1 is configured more than once"
+        );
+
+        let mut out_of_order = epochs;
+        out_of_order[2].1 = 3;
+        assert_eq!(
+            config_with_epochs(&out_of_order, "").unwrap_err(),
+            "Configured epochs must have start heights in the correct epoch order. Epoch 2.1 starts at height 2, before epoch 2.05 at height 3"
+        );
+
+        let mut unknown = epochs.to_vec();
+        unknown.push(("3.0", 6));
+        assert_eq!(
+            config_with_epochs(&unknown, "").unwrap_err(),
+            "Unknown epoch name specified: 3.0"
+        );
+
+        // epoch 2.1 must start before the v1 unlock height
+        let mut late_pox_2 = epochs;
+        for (i, epoch) in late_pox_2.iter_mut().enumerate().skip(3) {
+            epoch.1 = 2_000_000 + i as i64;
+        }
+        assert!(config_with_epochs(&late_pox_2, "")
+            .unwrap_err()
+            .starts_with(
+                "Invalid burnchain.epochs: v1 unlock height occurs at or before pox-2 activation"
+            ));
+        config_with_epochs(&late_pox_2, "pox_2_activation = 2000010").unwrap();
+    }
+
+    #[test]
+    fn should_parse_contract_event_keys() {
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers-0-1",
+        )
+        .unwrap();
+        assert_eq!(
+            EventKeyType::from_string("stackerdb"),
+            Some(EventKeyType::StackerDBChunks)
+        );
+        assert_eq!(
+            EventKeyType::from_string(
+                "stackerdb::ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers-0-1"
+            ),
+            Some(EventKeyType::StackerDBContractChunks(contract_id.clone()))
+        );
+        assert_eq!(
+            EventKeyType::from_string("ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers-0-1::*"),
+            Some(EventKeyType::ContractEvents(contract_id.clone()))
+        );
+        assert_eq!(
+            EventKeyType::from_string(
+                "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers-0-1::print"
+            ),
+            Some(EventKeyType::SmartContractEvent((
+                contract_id,
+                "print".to_string()
+            )))
+        );
+        assert_eq!(EventKeyType::from_string("stackerdb::not-a-contract"), None);
+        assert_eq!(EventKeyType::from_string("not-a-contract::*"), None);
+    }
+
+    #[test]
+    fn should_load_wallet_backend() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.burnchain.wallet_backend, WalletBackendName::Bitcoind);
+        assert!(config.burnchain.esplora_url.is_none());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                wallet_backend = "esplora"
+                esplora_url = "http://127.0.0.1:3002/api"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.burnchain.wallet_backend, WalletBackendName::Esplora);
+        assert_eq!(
+            config.burnchain.esplora_url.as_deref(),
+            Some("http://127.0.0.1:3002/api")
+        );
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                wallet_backend = "esplora"
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [burnchain]
+                wallet_backend = "esplora"
+                esplora_url = "https://blockstream.info/api"
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_load_stackerdb_hot_slots() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.connection_options.stackerdb_sync_interval, 0);
+        assert!(config.connection_options.stackerdb_hot_slots.is_empty());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [stackerdb]
+                sync_interval = 30
+
+                [[stackerdb.hot_slots]]
+                contract = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers"
+                slot_ids = [0, 1]
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.connection_options.stackerdb_sync_interval, 30);
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers")
+                .unwrap();
+        assert_eq!(
+            config
+                .connection_options
+                .stackerdb_hot_slots
+                .get(&contract_id),
+            Some(&[0, 1].iter().cloned().collect())
+        );
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [[stackerdb.hot_slots]]
+                contract = "not-a-contract"
+                slot_ids = [0]
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_load_stackerdb_replication_settings() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert!(config.connection_options.stackerdb_replication.is_empty());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [[stackerdb.replication]]
+                contract = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers"
+                priority = 10
+                sync_interval = 5
+                push_all_slots = true
+
+                [[stackerdb.replication]]
+                contract = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.other"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let signers_id =
+            QualifiedContractIdentifier::parse("ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers")
+                .unwrap();
+        let other_id =
+            QualifiedContractIdentifier::parse("ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.other")
+                .unwrap();
+        assert_eq!(
+            config
+                .connection_options
+                .stackerdb_replication
+                .get(&signers_id),
+            Some(&StackerDBReplicationSettings {
+                priority: 10,
+                sync_interval: Some(5),
+                push_all_slots: true,
+            })
+        );
+        assert_eq!(
+            config
+                .connection_options
+                .stackerdb_replication
+                .get(&other_id),
+            Some(&StackerDBReplicationSettings::default())
+        );
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [[stackerdb.replication]]
+                contract = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers"
+                priority = 1
+
+                [[stackerdb.replication]]
+                contract = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2.signers"
+                priority = 2
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_load_snapshot_settings() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert!(config.node.snapshot_path.is_none());
+        assert!(config.node.snapshot_signer_pubkey.is_none());
+
+        let pubkey = Secp256k1PublicKey::from_private(&Secp256k1PrivateKey::new());
+        let config = Config::from_config_file(
+            ConfigFile::from_str(&format!(
+                r#"
+                [node]
+                snapshot_path = "/tmp/snapshot"
+                snapshot_signer_pubkey = "{}"
+                "#,
+                pubkey.to_hex()
+            ))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.node.snapshot_path, Some("/tmp/snapshot".to_string()));
+        assert_eq!(config.node.snapshot_signer_pubkey, Some(pubkey));
+
+        // a snapshot is only trusted if it is signed
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                snapshot_path = "/tmp/snapshot"
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                snapshot_path = "/tmp/snapshot"
+                snapshot_signer_pubkey = "not-a-key"
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_load_prune_reward_cycles() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert!(config.node.prune_reward_cycles.is_none());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                prune_reward_cycles = 6
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.node.prune_reward_cycles, Some(6));
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                prune_reward_cycles = 0
+                "#,
+            )
+            .unwrap()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn should_load_rpc_auth_tokens() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert!(config.connection_options.rpc_auth_tokens.is_empty());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [rpc_auth]
+                block_proposal_token = "proposal-secret"
+                admin_token = "admin-secret"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let tokens = &config.connection_options.rpc_auth_tokens;
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(
+            tokens.get(&RPCEndpointGroup::BlockProposal),
+            Some(&"proposal-secret".to_string())
+        );
+        assert_eq!(
+            tokens.get(&RPCEndpointGroup::Admin),
+            Some(&"admin-secret".to_string())
+        );
+        assert!(tokens.get(&RPCEndpointGroup::PublicRead).is_none());
+
+        assert_eq!(
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [rpc_auth]
+                    tx_submission_token = ""
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err(),
+            "rpc_auth.tx_submission_token must not be empty"
+        );
+    }
 }
 
 impl ConfigFile {
@@ -363,6 +789,7 @@ pub struct Config {
     pub miner: MinerConfig,
     pub estimation: FeeEstimationConfig,
     pub atlas: AtlasConfig,
+    pub logging: LoggingConfig,
 }
 
 lazy_static! {
@@ -514,34 +941,47 @@ impl Config {
         test_debug!("Validate epochs: {:#?}", epochs);
         let _ = StacksEpoch::validate_epochs(epochs);
 
+        if let Err(e) = Self::check_epoch_settings(burnchain, epochs) {
+            panic!("FATAL: {}\nburnchain: {:?}", e, burnchain);
+        }
+    }
+
+    /// Check that a burnchain's PoX constants are consistent with the list of epoch start and end
+    /// heights, which must already be contiguous and ordered.
+    pub fn check_epoch_settings(
+        burnchain: &Burnchain,
+        epochs: &[StacksEpoch],
+    ) -> Result<(), String> {
         // sanity check: v1_unlock_height must happen after pox-2 instantiation
         let epoch21_index = StacksEpoch::find_epoch_by_id(&epochs, StacksEpochId::Epoch21)
-            .expect("FATAL: no epoch 2.1 defined");
+            .ok_or("no epoch 2.1 defined")?;
 
         let epoch21 = &epochs[epoch21_index];
         let v1_unlock_height = burnchain.pox_constants.v1_unlock_height as u64;
 
-        assert!(
-            v1_unlock_height > epoch21.start_height,
-            "FATAL: v1 unlock height occurs at or before pox-2 activation: {} <= {}\nburnchain: {:?}", v1_unlock_height, epoch21.start_height, burnchain
-        );
+        if v1_unlock_height <= epoch21.start_height {
+            return Err(format!(
+                "v1 unlock height occurs at or before pox-2 activation: {} <= {}. Set burnchain.pox_2_activation after the start of epoch 2.1",
+                v1_unlock_height, epoch21.start_height
+            ));
+        }
 
         let epoch21_rc = burnchain
             .block_height_to_reward_cycle(epoch21.start_height)
-            .expect("FATAL: epoch 21 starts before the first burnchain block");
+            .ok_or("epoch 2.1 starts before the first burnchain block")?;
         let v1_unlock_rc = burnchain
             .block_height_to_reward_cycle(v1_unlock_height)
-            .expect("FATAL: v1 unlock height is before the first burnchain block");
+            .ok_or("v1 unlock height is before the first burnchain block")?;
 
-        if epoch21_rc + 1 == v1_unlock_rc {
+        if epoch21_rc + 1 == v1_unlock_rc && burnchain.is_reward_cycle_start(v1_unlock_height) {
             // if v1_unlock_height is in the reward cycle after epoch_21, then it must not fall on
             // the reward cycle boundary.
-            assert!(
-                !burnchain.is_reward_cycle_start(v1_unlock_height),
-                "FATAL: v1 unlock height is at a reward cycle boundary\nburnchain: {:?}",
-                burnchain
-            );
+            return Err(format!(
+                "v1 unlock height {} is at a reward cycle boundary",
+                v1_unlock_height
+            ));
         }
+        Ok(())
     }
 
     fn make_epochs(
@@ -557,7 +997,7 @@ impl Config {
             BitcoinNetworkType::Testnet => Ok(stacks::core::STACKS_EPOCHS_TESTNET.to_vec()),
             BitcoinNetworkType::Regtest => Ok(stacks::core::STACKS_EPOCHS_REGTEST.to_vec()),
         }?;
-        let mut matched_epochs = vec![];
+        let mut matched_epochs: Vec<(StacksEpochId, i64)> = vec![];
         for configured_epoch in conf_epochs.iter() {
             let epoch_name = &configured_epoch.epoch_name;
             let epoch_id = if epoch_name == EPOCH_CONFIG_1_0_0 {
@@ -577,37 +1017,34 @@ impl Config {
             } else {
                 Err(format!("Unknown epoch name specified: {}", epoch_name))
             }?;
+            if matched_epochs.iter().any(|(id, _)| *id == epoch_id) {
+                return Err(format!("Epoch {} is configured more than once", epoch_name));
+            }
             matched_epochs.push((epoch_id, configured_epoch.start_height));
         }
 
         matched_epochs.sort_by_key(|(epoch_id, _)| *epoch_id);
-        // epochs must be sorted the same both by start height and by epoch
-        let mut check_sort = matched_epochs.clone();
-        check_sort.sort_by_key(|(_, start)| *start);
-        if matched_epochs != check_sort {
-            return Err(
-                "Configured epochs must have start heights in the correct epoch order".to_string(),
-            );
-        }
 
-        // epochs must be a prefix of [1.0, 2.0, 2.05, 2.1]
-        let expected_list = [
-            StacksEpochId::Epoch10,
-            StacksEpochId::Epoch20,
-            StacksEpochId::Epoch2_05,
-            StacksEpochId::Epoch21,
-            StacksEpochId::Epoch22,
-            StacksEpochId::Epoch23,
-            StacksEpochId::Epoch24,
-        ];
-        for (expected_epoch, configured_epoch) in expected_list
+        // every epoch this node implements must be configured
+        let missing_epochs: Vec<_> = default_epochs
             .iter()
-            .zip(matched_epochs.iter().map(|(epoch_id, _)| epoch_id))
-        {
-            if expected_epoch != configured_epoch {
+            .filter(|epoch| !matched_epochs.iter().any(|(id, _)| *id == epoch.epoch_id))
+            .map(|epoch| epoch.epoch_id.to_string())
+            .collect();
+        if !missing_epochs.is_empty() {
+            return Err(format!(
+                "Configured epochs must include every epoch this node supports. Missing epochs: {}",
+                missing_epochs.join(", ")
+            ));
+        }
+        // epochs must start at non-decreasing heights, in epoch order
+        for window in matched_epochs.windows(2) {
+            let ((prev_id, prev_start), (id, start)) = (window[0], window[1]);
+            if start < prev_start {
                 return Err(format!(
-                                "Configured epochs may not skip an epoch. Expected epoch = {}, Found epoch = {}",
-                                expected_epoch, configured_epoch));
+                    "Configured epochs must have start heights in the correct epoch order. Epoch {} starts at height {}, before epoch {} at height {}",
+                    id, start, prev_id, prev_start
+                ));
             }
         }
 
@@ -616,13 +1053,7 @@ impl Config {
             return Err("Stacks 1.0 must start at height = 0".into());
         }
 
-        if matched_epochs.len() > default_epochs.len() {
-            return Err(format!(
-                "Cannot configure more epochs than support by this node. Supported epoch count: {}",
-                default_epochs.len()
-            ));
-        }
-        let mut out_epochs = default_epochs[..matched_epochs.len()].to_vec();
+        let mut out_epochs = default_epochs;
 
         for (i, (epoch_id, start_height)) in matched_epochs.iter().enumerate() {
             if epoch_id != &out_epochs[i].epoch_id {
@@ -709,10 +1140,21 @@ impl Config {
                         .wait_time_for_blocks
                         .unwrap_or(default_node_config.wait_time_for_blocks),
                     prometheus_bind: node.prometheus_bind,
+                    event_stream_bind: node.event_stream_bind,
                     marf_cache_strategy: node.marf_cache_strategy,
+                    marf_cache_size: node
+                        .marf_cache_size
+                        .unwrap_or(default_node_config.marf_cache_size),
                     marf_defer_hashing: node
                         .marf_defer_hashing
                         .unwrap_or(default_node_config.marf_defer_hashing),
+                    txindex: node.txindex.unwrap_or(default_node_config.txindex),
+                    account_txindex: node
+                        .account_txindex
+                        .unwrap_or(default_node_config.account_txindex),
+                    map_key_index: node
+                        .map_key_index
+                        .unwrap_or(default_node_config.map_key_index),
                     pox_sync_sample_secs: node
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
@@ -746,7 +1188,44 @@ impl Config {
                             QualifiedContractIdentifier::parse(contract_id).ok()
                         })
                         .collect(),
+                    mempool_checkpoint_interval_secs: node
+                        .mempool_checkpoint_interval_secs
+                        .unwrap_or(default_node_config.mempool_checkpoint_interval_secs),
+                    mempool_max_tx_age_stacks_blocks: node
+                        .mempool_max_tx_age_stacks_blocks
+                        .unwrap_or(default_node_config.mempool_max_tx_age_stacks_blocks),
+                    mempool_max_size_mb: node.mempool_max_size_mb,
+                    mempool_fee_floor_threshold_pct: node
+                        .mempool_fee_floor_threshold_pct
+                        .unwrap_or(default_node_config.mempool_fee_floor_threshold_pct),
+                    reference_nodes: node.reference_nodes.unwrap_or(vec![]),
+                    fork_monitor_poll_time_secs: node
+                        .fork_monitor_poll_time_secs
+                        .unwrap_or(default_node_config.fork_monitor_poll_time_secs),
+                    fork_divergence_threshold: node
+                        .fork_divergence_threshold
+                        .unwrap_or(default_node_config.fork_divergence_threshold),
+                    snapshot_signer_pubkey: match node.snapshot_signer_pubkey {
+                        Some(pubkey) => {
+                            Some(Secp256k1PublicKey::from_hex(&pubkey).map_err(|_e| {
+                                format!("node.snapshot_signer_pubkey should be a hex public key")
+                            })?)
+                        }
+                        None => None,
+                    },
+                    snapshot_path: node.snapshot_path,
+                    prune_reward_cycles: node.prune_reward_cycles,
                 };
+                if node_config.snapshot_path.is_some()
+                    && node_config.snapshot_signer_pubkey.is_none()
+                {
+                    return Err(
+                        "node.snapshot_path requires node.snapshot_signer_pubkey".to_string()
+                    );
+                }
+                if node_config.prune_reward_cycles == Some(0) {
+                    return Err("node.prune_reward_cycles must be at least 1".to_string());
+                }
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
             None => (default_node_config, None, None),
@@ -880,6 +1359,15 @@ impl Config {
                     rbf_fee_increment: burnchain
                         .rbf_fee_increment
                         .unwrap_or(default_burnchain_config.rbf_fee_increment),
+                    rbf_bump_interval_ms: burnchain
+                        .rbf_bump_interval_ms
+                        .or(default_burnchain_config.rbf_bump_interval_ms),
+                    rbf_bump_deadline_ms: burnchain
+                        .rbf_bump_deadline_ms
+                        .unwrap_or(default_burnchain_config.rbf_bump_deadline_ms),
+                    rbf_max_total_spend: burnchain
+                        .rbf_max_total_spend
+                        .or(default_burnchain_config.rbf_max_total_spend),
                     // will be overwritten below
                     epochs: default_burnchain_config.epochs,
                     ast_precheck_size_height: burnchain.ast_precheck_size_height,
@@ -893,8 +1381,29 @@ impl Config {
                     wallet_name: burnchain
                         .wallet_name
                         .unwrap_or(default_burnchain_config.wallet_name.clone()),
+                    wallet_backend: burnchain
+                        .wallet_backend
+                        .map(WalletBackendName::panic_parse)
+                        .unwrap_or(default_burnchain_config.wallet_backend),
+                    esplora_url: burnchain.esplora_url,
                 };
 
+                match (&result.wallet_backend, &result.esplora_url) {
+                    (WalletBackendName::Esplora, None) => {
+                        return Err(
+                            "burnchain.esplora_url is required when burnchain.wallet_backend is \"esplora\""
+                                .into(),
+                        );
+                    }
+                    (WalletBackendName::Esplora, Some(url)) if !url.starts_with("http://") => {
+                        return Err(format!(
+                            "Invalid burnchain.esplora_url: {}: only http:// URLs are supported",
+                            url
+                        ));
+                    }
+                    _ => {}
+                }
+
                 if let BitcoinNetworkType::Mainnet = result.get_bitcoin_network().1 {
                     // check that pox_2_activation hasn't been set in mainnet
                     if result.pox_2_activation.is_some()
@@ -995,6 +1504,16 @@ impl Config {
                     }
                 },
                 max_reorg_depth: miner.max_reorg_depth.unwrap_or(3),
+                mempool_walk_strategy: miner
+                    .mempool_walk_strategy
+                    .as_ref()
+                    .map(|strategy_str| match str::parse(strategy_str) {
+                        Ok(strategy) => strategy,
+                        Err(e) => {
+                            panic!("could not parse '{}': {}", strategy_str, &e);
+                        }
+                    })
+                    .unwrap_or(MemPoolWalkStrategy::GlobalFeeRate),
             },
             None => miner_default_config,
         };
@@ -1062,6 +1581,16 @@ impl Config {
                     observers.insert(EventObserverConfig {
                         endpoint,
                         events_keys,
+                        queue_max_size: observer
+                            .queue_max_size
+                            .unwrap_or(EVENT_OBSERVER_QUEUE_MAX_SIZE),
+                        queue_drop_policy: observer
+                            .queue_drop_policy
+                            .map(EventObserverDropPolicy::panic_parse)
+                            .unwrap_or_default(),
+                        retry_max_backoff_ms: observer
+                            .retry_max_backoff_ms
+                            .unwrap_or(EVENT_OBSERVER_RETRY_MAX_BACKOFF_MS),
                     });
                 }
                 observers
@@ -1075,13 +1604,14 @@ impl Config {
                 events_observers.insert(EventObserverConfig {
                     endpoint: val,
                     events_keys: vec![EventKeyType::AnyEvent],
+                    ..EventObserverConfig::default()
                 });
                 ()
             }
             _ => (),
         };
 
-        let connection_options = match config_file.connection_options {
+        let mut connection_options = match config_file.connection_options {
             Some(opts) => {
                 let ip_addr = match opts.public_ip_address {
                     Some(public_ip_address) => {
@@ -1185,6 +1715,26 @@ impl Config {
                             .max_inflight_blocks
                             .clone()
                     }),
+                    max_inflight_blocks_per_peer: opts.max_inflight_blocks_per_peer.unwrap_or_else(
+                        || {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                                .max_inflight_blocks_per_peer
+                                .clone()
+                        },
+                    ),
+                    adaptive_max_inflight_blocks: opts.adaptive_max_inflight_blocks.unwrap_or_else(
+                        || {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                                .adaptive_max_inflight_blocks
+                                .clone()
+                        },
+                    ),
+                    block_download_peer_selection: match opts.block_download_peer_selection {
+                        Some(ref strategy) => strategy.parse().map_err(|e| {
+                            format!("Bad block_download_peer_selection '{}': {}", strategy, e)
+                        })?,
+                        None => HELIUM_DEFAULT_CONNECTION_OPTIONS.block_download_peer_selection,
+                    },
                     max_inflight_attachments: opts.max_inflight_attachments.unwrap_or_else(|| {
                         HELIUM_DEFAULT_CONNECTION_OPTIONS
                             .max_inflight_attachments
@@ -1197,6 +1747,9 @@ impl Config {
                                 .clone()
                         },
                     ),
+                    read_only_call_max_tip_depth: opts.read_only_call_max_tip_depth.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.read_only_call_max_tip_depth,
+                    ),
                     download_interval: opts.download_interval.unwrap_or_else(|| {
                         HELIUM_DEFAULT_CONNECTION_OPTIONS.download_interval.clone()
                     }),
@@ -1225,13 +1778,42 @@ impl Config {
                     handshake_timeout: opts.handshake_timeout.unwrap_or(5),
                     max_sockets: opts.max_sockets.unwrap_or(800) as usize,
                     antientropy_public: opts.antientropy_public.unwrap_or(true),
+                    max_known_bootstrap_peers: opts.max_known_bootstrap_peers.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.max_known_bootstrap_peers,
+                    ),
                     private_neighbors: opts.private_neighbors.unwrap_or(true),
+                    http_compression_endpoints: opts.http_compression_endpoints.unwrap_or_else(
+                        || {
+                            HELIUM_DEFAULT_CONNECTION_OPTIONS
+                                .http_compression_endpoints
+                                .clone()
+                        },
+                    ),
+                    http_compression_min_size: opts.http_compression_min_size.unwrap_or_else(
+                        || HELIUM_DEFAULT_CONNECTION_OPTIONS.http_compression_min_size,
+                    ),
+                    peer_deprioritize_score: opts.peer_deprioritize_score.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_deprioritize_score
+                    }),
+                    peer_ban_score: opts
+                        .peer_ban_score
+                        .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_ban_score),
+                    peer_score_ban_duration: opts.peer_score_ban_duration.unwrap_or_else(|| {
+                        HELIUM_DEFAULT_CONNECTION_OPTIONS.peer_score_ban_duration
+                    }),
                     ..ConnectionOptions::default()
                 }
             }
             None => HELIUM_DEFAULT_CONNECTION_OPTIONS.clone(),
         };
 
+        if let Some(stackerdb) = config_file.stackerdb {
+            stackerdb.apply(&mut connection_options)?;
+        }
+        if let Some(rpc_auth) = config_file.rpc_auth {
+            rpc_auth.apply(&mut connection_options)?;
+        }
+
         let estimation = match config_file.fee_estimation {
             Some(f) => FeeEstimationConfig::from(f),
             None => FeeEstimationConfig::default(),
@@ -1247,7 +1829,12 @@ impl Config {
             .validate()
             .map_err(|e| format!("Atlas config error: {e}"))?;
 
-        Ok(Config {
+        let logging = match config_file.logging {
+            Some(f) => f.into_config()?,
+            None => LoggingConfig::default(),
+        };
+
+        let config = Config {
             config_path: config_file.__path,
             node,
             burnchain,
@@ -1257,7 +1844,17 @@ impl Config {
             estimation,
             miner,
             atlas,
-        })
+            logging,
+        };
+
+        // catch configured epochs that disagree with the PoX settings here, instead of when the
+        // run loop starts
+        if let Some(ref epochs) = config.burnchain.epochs {
+            Self::check_epoch_settings(&config.get_burnchain(), epochs)
+                .map_err(|e| format!("Invalid burnchain.epochs: {}", e))?;
+        }
+
+        Ok(config)
     }
 
     fn get_burnchain_path(&self) -> PathBuf {
@@ -1336,6 +1933,22 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    /// Returns the path `{working_dir}/{mode}/mempool_checkpoint.bin`.  This lives outside of the
+    /// chainstate directory, so it survives a chainstate re-sync.
+    pub fn get_mempool_checkpoint_file_path(&self) -> String {
+        let mut path = self.get_chainstate_path();
+        path.set_file_name("mempool_checkpoint.bin");
+        path.to_str().expect("Unable to produce path").to_string()
+    }
+
+    /// Returns the path `{working_dir}/{mode}/event_observers.sqlite`, which holds the payloads
+    /// not yet delivered to event observers.
+    pub fn get_event_observer_queue_file_path(&self) -> PathBuf {
+        let mut path = self.get_chainstate_path();
+        path.set_file_name("event_observers.sqlite");
+        path
+    }
+
     pub fn add_initial_balance(&mut self, address: String, amount: u64) {
         let new_balance = InitialBalance {
             address: PrincipalData::parse_standard_principal(&address)
@@ -1397,6 +2010,7 @@ impl Config {
                 candidate_retry_cache_size: miner_config.candidate_retry_cache_size,
                 txs_to_consider: miner_config.txs_to_consider,
                 filter_origins: miner_config.filter_origins,
+                strategy: miner_config.mempool_walk_strategy,
             },
             miner_status,
         }
@@ -1439,6 +2053,7 @@ impl std::default::Default for Config {
             estimation,
             miner: MinerConfig::default(),
             atlas: AtlasConfig::new(mainnet),
+            logging: LoggingConfig::default(),
         }
     }
 }
@@ -1467,6 +2082,16 @@ pub struct BurnchainConfig {
     pub leader_key_tx_estimated_size: u64,
     pub block_commit_tx_estimated_size: u64,
     pub rbf_fee_increment: u64,
+    /// If set, a block-commit that is still unconfirmed after this many milliseconds is
+    /// re-broadcast with its fee rate raised by `rbf_fee_increment`, and again after each
+    /// further interval
+    pub rbf_bump_interval_ms: Option<u64>,
+    /// No fee bumps are sent once this many milliseconds have passed since the block-commit was
+    /// first broadcast, since a late bump is unlikely to propagate before the next burn block
+    pub rbf_bump_deadline_ms: u64,
+    /// The most a block-commit may spend (burn plus fees, including replaced attempts) before
+    /// fee bumping stops
+    pub rbf_max_total_spend: Option<u64>,
     /// Custom override for the definitions of the epochs. This will only be applied for testnet and
     /// regtest nodes.
     pub epochs: Option<Vec<StacksEpoch>>,
@@ -1475,6 +2100,10 @@ pub struct BurnchainConfig {
     pub sunset_end: Option<u32>,
     pub wallet_name: String,
     pub ast_precheck_size_height: Option<u64>,
+    /// Where the miner discovers its UTXOs and broadcasts its transactions
+    pub wallet_backend: WalletBackendName,
+    /// The base URL of the Esplora HTTP API, if `wallet_backend` is Esplora
+    pub esplora_url: Option<String>,
 }
 
 impl BurnchainConfig {
@@ -1502,12 +2131,17 @@ impl BurnchainConfig {
             leader_key_tx_estimated_size: LEADER_KEY_TX_ESTIM_SIZE,
             block_commit_tx_estimated_size: BLOCK_COMMIT_TX_ESTIM_SIZE,
             rbf_fee_increment: DEFAULT_RBF_FEE_RATE_INCREMENT,
+            rbf_bump_interval_ms: None,
+            rbf_bump_deadline_ms: DEFAULT_RBF_BUMP_DEADLINE_MS,
+            rbf_max_total_spend: None,
             epochs: None,
             pox_2_activation: None,
             sunset_start: None,
             sunset_end: None,
             wallet_name: "".to_string(),
             ast_precheck_size_height: None,
+            wallet_backend: WalletBackendName::default(),
+            esplora_url: None,
         }
     }
     pub fn get_rpc_url(&self, wallet: Option<String>) -> String {
@@ -1582,12 +2216,17 @@ pub struct BurnchainConfigFile {
     pub block_commit_tx_estimated_size: Option<u64>,
     pub rbf_fee_increment: Option<u64>,
     pub max_rbf: Option<u64>,
+    pub rbf_bump_interval_ms: Option<u64>,
+    pub rbf_bump_deadline_ms: Option<u64>,
+    pub rbf_max_total_spend: Option<u64>,
     pub epochs: Option<Vec<StacksEpochConfigFile>>,
     pub pox_2_activation: Option<u32>,
     pub sunset_start: Option<u32>,
     pub sunset_end: Option<u32>,
     pub wallet_name: Option<String>,
     pub ast_precheck_size_height: Option<u64>,
+    pub wallet_backend: Option<String>,
+    pub esplora_url: Option<String>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1610,8 +2249,21 @@ pub struct NodeConfig {
     pub wait_time_for_microblocks: u64,
     pub wait_time_for_blocks: u64,
     pub prometheus_bind: Option<String>,
+    /// Address to serve the WebSocket event stream on, if any
+    pub event_stream_bind: Option<String>,
     pub marf_cache_strategy: Option<String>,
+    /// Byte budget of the MARF node cache, if `marf_cache_strategy` is "lru"
+    pub marf_cache_size: u64,
     pub marf_defer_hashing: bool,
+    /// Whether to log processed transactions, with their results and events, for
+    ///  `GET /v2/transactions/{txid}`
+    pub txindex: bool,
+    /// Whether to index the accounts each processed transaction involved, for
+    ///  `GET /v2/addresses/{principal}/transactions`
+    pub account_txindex: bool,
+    /// Whether to index the keys of the data maps written by each processed block, for
+    ///  `GET /v2/map_entries/{address}/{contract}/{map}`
+    pub map_key_index: bool,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
     pub always_use_affirmation_maps: bool,
@@ -1624,6 +2276,35 @@ pub struct NodeConfig {
     pub chain_liveness_poll_time_secs: u64,
     /// stacker DBs we replicate
     pub stacker_dbs: Vec<QualifiedContractIdentifier>,
+    /// How often, in seconds, the relayer checkpoints the mempool to disk so pending
+    ///  transactions can be restored on restart. 0 disables checkpointing. Defaults to 600s.
+    pub mempool_checkpoint_interval_secs: u64,
+    /// Transactions accepted more than this many Stacks blocks below the Stacks chain tip are
+    ///  dropped from the mempool. Defaults to 256.
+    pub mempool_max_tx_age_stacks_blocks: u64,
+    /// The most megabytes of transactions the mempool holds before evicting those with the
+    ///  lowest fee per byte. Unlimited by default.
+    pub mempool_max_size_mb: Option<u64>,
+    /// Once the mempool is this full (as a percentage of `mempool_max_size_mb`), new transactions
+    ///  must pay a higher fee per byte than the cheapest one it holds. Defaults to 90.
+    pub mempool_fee_floor_threshold_pct: u8,
+    /// RPC endpoints (`host:port`) of nodes whose canonical Stacks tip the fork monitor compares
+    ///  against ours. The fork monitor does not run if this is empty.
+    pub reference_nodes: Vec<String>,
+    /// How often, in seconds, the fork monitor polls the reference nodes. Defaults to 60s.
+    pub fork_monitor_poll_time_secs: u64,
+    /// How many blocks our canonical Stacks tip may diverge from a reference node's before the
+    ///  fork monitor raises an alert. Defaults to 3.
+    pub fork_divergence_threshold: u64,
+    /// Directory with a signed chainstate snapshot to bootstrap from, if the node has no
+    ///  chainstate yet
+    pub snapshot_path: Option<String>,
+    /// Public key that must have signed the snapshot's manifest
+    pub snapshot_signer_pubkey: Option<Secp256k1PublicKey>,
+    /// If set, only keep the bodies of blocks and microblocks from this many of the most recent
+    ///  reward cycles. Headers and the MARF are kept, but a pruned node cannot serve pruned blocks
+    ///  to its peers, and cannot reprocess them after a PoX reorg deeper than this window.
+    pub prune_reward_cycles: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -1635,6 +2316,7 @@ pub enum CostEstimatorName {
 pub enum FeeEstimatorName {
     ScalarFeeRate,
     FuzzedWeightedMedianFeeRate,
+    PercentileFeeRate,
 }
 
 #[derive(Clone, Debug)]
@@ -1642,6 +2324,35 @@ pub enum CostMetricName {
     ProportionDotProduct,
 }
 
+/// Where the miner discovers its UTXOs and broadcasts its transactions
+#[derive(Clone, Debug, PartialEq)]
+pub enum WalletBackendName {
+    /// The wallet RPC of the bitcoind node at `peer_host`
+    Bitcoind,
+    /// An Esplora HTTP API (e.g. electrs), so that the miner does not need its own wallet-enabled
+    /// bitcoind
+    Esplora,
+}
+
+impl Default for WalletBackendName {
+    fn default() -> Self {
+        WalletBackendName::Bitcoind
+    }
+}
+
+impl WalletBackendName {
+    fn panic_parse(s: String) -> WalletBackendName {
+        match s.to_lowercase().as_str() {
+            "bitcoind" => WalletBackendName::Bitcoind,
+            "esplora" => WalletBackendName::Esplora,
+            _ => panic!(
+                "Bad wallet backend name supplied in configuration file: {}",
+                s
+            ),
+        }
+    }
+}
+
 impl Default for CostEstimatorName {
     fn default() -> Self {
         CostEstimatorName::NaivePessimistic
@@ -1679,6 +2390,8 @@ impl FeeEstimatorName {
             FeeEstimatorName::ScalarFeeRate
         } else if &s.to_lowercase() == "fuzzed_weighted_median_fee_rate" {
             FeeEstimatorName::FuzzedWeightedMedianFeeRate
+        } else if &s.to_lowercase() == "percentile_fee_rate" {
+            FeeEstimatorName::PercentileFeeRate
         } else {
             panic!(
                 "Bad fee estimator name supplied in configuration file: {}",
@@ -1707,8 +2420,8 @@ pub struct FeeEstimationConfig {
     /// If using FeeRateFuzzer, the amount of random noise, as a percentage of the base value (in
     /// [0, 1]) to add for fuzz. See comments on FeeRateFuzzer.
     pub fee_rate_fuzzer_fraction: f64,
-    /// If using WeightedMedianFeeRateEstimator, the window size to use. See comments on
-    /// WeightedMedianFeeRateEstimator.
+    /// If using WeightedMedianFeeRateEstimator or PercentileFeeRateEstimator, the window size to
+    /// use. See comments on those estimators.
     pub fee_rate_window_size: u64,
 }
 
@@ -1793,6 +2506,9 @@ impl Config {
             FeeEstimatorName::FuzzedWeightedMedianFeeRate => self
                 .estimation
                 .make_fuzzed_weighted_median_fee_estimator(self.get_estimates_path(), metric),
+            FeeEstimatorName::PercentileFeeRate => self
+                .estimation
+                .make_percentile_fee_estimator(self.get_estimates_path(), metric),
         };
 
         Some(fee_estimator)
@@ -1854,6 +2570,28 @@ impl FeeEstimationConfig {
             panic!("BUG: Expected to configure a weighted median fee estimator");
         }
     }
+
+    pub fn make_percentile_fee_estimator<CM: CostMetric + 'static>(
+        &self,
+        mut estimates_path: PathBuf,
+        metric: CM,
+    ) -> Box<dyn FeeEstimator> {
+        if let Some(FeeEstimatorName::PercentileFeeRate) = self.fee_estimator.as_ref() {
+            estimates_path.push("fee_estimator_percentiles.sqlite");
+            Box::new(
+                PercentileFeeRateEstimator::open(
+                    &estimates_path,
+                    metric,
+                    self.fee_rate_window_size
+                        .try_into()
+                        .expect("Configured fee rate window size out of bounds."),
+                )
+                .expect("Error opening fee estimator"),
+            )
+        } else {
+            panic!("BUG: Expected to configure a percentile fee estimator");
+        }
+    }
 }
 
 impl NodeConfig {
@@ -1894,8 +2632,13 @@ impl NodeConfig {
             wait_time_for_microblocks: 30_000,
             wait_time_for_blocks: 30_000,
             prometheus_bind: None,
+            event_stream_bind: None,
             marf_cache_strategy: None,
+            marf_cache_size: DEFAULT_LRU_CACHE_SIZE,
             marf_defer_hashing: true,
+            txindex: false,
+            account_txindex: false,
+            map_key_index: false,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
             always_use_affirmation_maps: false,
@@ -1903,6 +2646,16 @@ impl NodeConfig {
             fault_injection_hide_blocks: false,
             chain_liveness_poll_time_secs: 300,
             stacker_dbs: vec![],
+            mempool_checkpoint_interval_secs: 600,
+            mempool_max_tx_age_stacks_blocks: MEMPOOL_MAX_TRANSACTION_AGE,
+            mempool_max_size_mb: None,
+            mempool_fee_floor_threshold_pct: 90,
+            reference_nodes: vec![],
+            fork_monitor_poll_time_secs: 60,
+            fork_divergence_threshold: 3,
+            snapshot_path: None,
+            snapshot_signer_pubkey: None,
+            prune_reward_cycles: None,
         }
     }
 
@@ -1989,14 +2742,26 @@ impl NodeConfig {
             TrieHashCalculationMode::Immediate
         };
 
-        MARFOpenOpts::new(
+        let mut opts = MARFOpenOpts::new(
             hash_mode,
             &self
                 .marf_cache_strategy
                 .as_ref()
                 .unwrap_or(&"noop".to_string()),
             false,
-        )
+        );
+        opts.cache_size = self.marf_cache_size;
+        opts
+    }
+
+    pub fn get_mempool_gc_policy(&self) -> MemPoolGCPolicy {
+        MemPoolGCPolicy {
+            max_tx_age: self.mempool_max_tx_age_stacks_blocks,
+            max_size_bytes: self
+                .mempool_max_size_mb
+                .map(|size_mb| size_mb.saturating_mul(1024 * 1024)),
+            fee_floor_threshold_pct: self.mempool_fee_floor_threshold_pct,
+        }
     }
 }
 
@@ -2042,6 +2807,8 @@ pub struct MinerConfig {
     /// When selecting the "nicest" tip, do not consider tips that are more than this many blocks
     /// behind the highest tip.
     pub max_reorg_depth: u64,
+    /// How to order the mempool transactions considered for a block
+    pub mempool_walk_strategy: MemPoolWalkStrategy,
 }
 
 impl MinerConfig {
@@ -2067,6 +2834,7 @@ impl MinerConfig {
             txs_to_consider: MemPoolWalkTxTypes::all(),
             filter_origins: HashSet::new(),
             max_reorg_depth: 3,
+            mempool_walk_strategy: MemPoolWalkStrategy::GlobalFeeRate,
         }
     }
 }
@@ -2095,6 +2863,10 @@ pub struct ConnectionOptionsFile {
     pub walk_interval: Option<u64>,
     pub dns_timeout: Option<u64>,
     pub max_inflight_blocks: Option<u64>,
+    pub max_inflight_blocks_per_peer: Option<u64>,
+    pub adaptive_max_inflight_blocks: Option<bool>,
+    /// One of `random`, `latency`, or `height`
+    pub block_download_peer_selection: Option<String>,
     pub max_inflight_attachments: Option<u64>,
     pub read_only_call_limit_write_length: Option<u64>,
     pub read_only_call_limit_read_length: Option<u64>,
@@ -2102,6 +2874,7 @@ pub struct ConnectionOptionsFile {
     pub read_only_call_limit_read_count: Option<u64>,
     pub read_only_call_limit_runtime: Option<u64>,
     pub maximum_call_argument_size: Option<u32>,
+    pub read_only_call_max_tip_depth: Option<u64>,
     pub download_interval: Option<u64>,
     pub inv_sync_interval: Option<u64>,
     pub full_inv_sync_interval: Option<u64>,
@@ -2112,7 +2885,20 @@ pub struct ConnectionOptionsFile {
     pub disable_block_download: Option<bool>,
     pub force_disconnect_interval: Option<u64>,
     pub antientropy_public: Option<bool>,
+    /// How many of the peers that served this node best before to walk to alongside the
+    /// bootstrap peers during initial block download
+    pub max_known_bootstrap_peers: Option<u64>,
     pub private_neighbors: Option<bool>,
+    /// RPC endpoint path prefixes whose responses may be compressed.  Set to an empty list to
+    /// disable response compression.
+    pub http_compression_endpoints: Option<Vec<String>>,
+    pub http_compression_min_size: Option<u32>,
+    /// Peers whose reputation score (out of 100) is at or below this are relayed to less often
+    pub peer_deprioritize_score: Option<i64>,
+    /// Peers whose reputation score is at or below this are banned
+    pub peer_ban_score: Option<i64>,
+    /// How long to ban a peer for once its reputation score is too low, in seconds
+    pub peer_score_ban_duration: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
@@ -2135,8 +2921,23 @@ pub struct NodeConfigFile {
     pub wait_time_for_microblocks: Option<u64>,
     pub wait_time_for_blocks: Option<u64>,
     pub prometheus_bind: Option<String>,
+    /// Address to serve the WebSocket event stream on. Disabled if not set.
+    pub event_stream_bind: Option<String>,
     pub marf_cache_strategy: Option<String>,
+    /// Byte budget of the MARF node cache, if `marf_cache_strategy` is "lru".
+    ///  Defaults to 256 MiB.
+    pub marf_cache_size: Option<u64>,
     pub marf_defer_hashing: Option<bool>,
+    /// Log processed transactions, with their results and events, so they can be looked up
+    ///  by txid via `GET /v2/transactions/{txid}`. Defaults to false.
+    pub txindex: Option<bool>,
+    /// Index the accounts each processed transaction involved, so that an account's history can
+    ///  be looked up via `GET /v2/addresses/{principal}/transactions`. Defaults to false.
+    pub account_txindex: Option<bool>,
+    /// Index the keys of the data maps written by each processed block, so that a map's entries
+    ///  can be listed via `GET /v2/map_entries/{address}/{contract}/{map}`. Only keys written
+    ///  while this is on are indexed, so enable it before syncing the chain. Defaults to false.
+    pub map_key_index: Option<bool>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
     pub always_use_affirmation_maps: Option<bool>,
@@ -2146,6 +2947,34 @@ pub struct NodeConfigFile {
     pub chain_liveness_poll_time_secs: Option<u64>,
     /// Stacker DBs we replicate
     pub stacker_dbs: Option<Vec<String>>,
+    /// How often, in seconds, the relayer checkpoints the mempool to disk so pending
+    ///  transactions can be restored on restart. 0 disables checkpointing. Defaults to 600s.
+    pub mempool_checkpoint_interval_secs: Option<u64>,
+    /// Transactions accepted more than this many Stacks blocks below the Stacks chain tip are
+    ///  dropped from the mempool. Defaults to 256.
+    pub mempool_max_tx_age_stacks_blocks: Option<u64>,
+    /// The most megabytes of transactions the mempool holds before evicting those with the
+    ///  lowest fee per byte. Unlimited by default.
+    pub mempool_max_size_mb: Option<u64>,
+    /// Once the mempool is this full (as a percentage of `mempool_max_size_mb`), new transactions
+    ///  must pay a higher fee per byte than the cheapest one it holds. Defaults to 90.
+    pub mempool_fee_floor_threshold_pct: Option<u8>,
+    /// RPC endpoints (`host:port`) of nodes whose canonical Stacks tip the fork monitor compares
+    ///  against ours. The fork monitor does not run if this is empty.
+    pub reference_nodes: Option<Vec<String>>,
+    /// How often, in seconds, the fork monitor polls the reference nodes. Defaults to 60s.
+    pub fork_monitor_poll_time_secs: Option<u64>,
+    /// How many blocks our canonical Stacks tip may diverge from a reference node's before the
+    ///  fork monitor raises an alert. Defaults to 3.
+    pub fork_divergence_threshold: Option<u64>,
+    /// Directory with a signed chainstate snapshot to bootstrap from, if the node has no
+    ///  chainstate yet
+    pub snapshot_path: Option<String>,
+    /// Hex-encoded public key that must have signed the snapshot's manifest
+    pub snapshot_signer_pubkey: Option<String>,
+    /// If set, only keep the bodies of blocks and microblocks from this many of the most recent
+    ///  reward cycles. Must be at least 1.
+    pub prune_reward_cycles: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
@@ -2180,6 +3009,7 @@ pub struct MinerConfigFile {
     pub txs_to_consider: Option<String>,
     pub filter_origins: Option<String>,
     pub max_reorg_depth: Option<u64>,
+    pub mempool_walk_strategy: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
@@ -2210,16 +3040,192 @@ impl AtlasConfigFile {
     }
 }
 
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct StackerDBConfigFile {
+    /// Minimum number of seconds between anti-entropy syncs of each StackerDB
+    pub sync_interval: Option<u64>,
+    pub hot_slots: Option<Vec<StackerDBHotSlotsConfigFile>>,
+    pub replication: Option<Vec<StackerDBReplicationConfigFile>>,
+}
+
+/// Slots of a StackerDB whose new chunks are pushed to subscribed peers as soon as they arrive
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct StackerDBHotSlotsConfigFile {
+    pub contract: String,
+    pub slot_ids: Vec<u32>,
+}
+
+/// Replication settings for a StackerDB that this node should favor, such as the signers' DBs
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct StackerDBReplicationConfigFile {
+    pub contract: String,
+    /// DBs with a higher priority are synced first
+    pub priority: Option<u32>,
+    /// Minimum number of seconds between anti-entropy syncs of this DB, in place of
+    /// `stackerdb.sync_interval`
+    pub sync_interval: Option<u64>,
+    /// Push new chunks in every slot to subscribed peers as soon as they arrive
+    pub push_all_slots: Option<bool>,
+}
+
+impl StackerDBConfigFile {
+    fn apply(self, connection_options: &mut ConnectionOptions) -> Result<(), String> {
+        if let Some(sync_interval) = self.sync_interval {
+            connection_options.stackerdb_sync_interval = sync_interval;
+        }
+        for hot_slots in self.hot_slots.unwrap_or(vec![]).into_iter() {
+            let contract_id =
+                QualifiedContractIdentifier::parse(&hot_slots.contract).map_err(|e| {
+                    format!(
+                        "Invalid StackerDB contract '{}': {:?}",
+                        &hot_slots.contract, &e
+                    )
+                })?;
+            connection_options
+                .stackerdb_hot_slots
+                .entry(contract_id)
+                .or_insert_with(HashSet::new)
+                .extend(hot_slots.slot_ids);
+        }
+        for replication in self.replication.unwrap_or(vec![]).into_iter() {
+            let contract_id =
+                QualifiedContractIdentifier::parse(&replication.contract).map_err(|e| {
+                    format!(
+                        "Invalid StackerDB contract '{}': {:?}",
+                        &replication.contract, &e
+                    )
+                })?;
+            let settings = StackerDBReplicationSettings {
+                priority: replication.priority.unwrap_or(0),
+                sync_interval: replication.sync_interval,
+                push_all_slots: replication.push_all_slots.unwrap_or(false),
+            };
+            if connection_options
+                .stackerdb_replication
+                .insert(contract_id, settings)
+                .is_some()
+            {
+                return Err(format!(
+                    "Duplicate StackerDB replication settings for '{}'",
+                    &replication.contract
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Auth tokens that RPC requests to each endpoint group must carry in their `Authorization:`
+/// header, as the raw token (not `Bearer <token>`). Groups without a token are open to anyone
+/// who can reach `rpc_bind`.
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct RPCAuthConfigFile {
+    pub public_read_token: Option<String>,
+    pub tx_submission_token: Option<String>,
+    pub block_proposal_token: Option<String>,
+    pub admin_token: Option<String>,
+}
+
+impl RPCAuthConfigFile {
+    fn apply(self, connection_options: &mut ConnectionOptions) -> Result<(), String> {
+        let tokens = [
+            (
+                "public_read_token",
+                RPCEndpointGroup::PublicRead,
+                self.public_read_token,
+            ),
+            (
+                "tx_submission_token",
+                RPCEndpointGroup::TxSubmission,
+                self.tx_submission_token,
+            ),
+            (
+                "block_proposal_token",
+                RPCEndpointGroup::BlockProposal,
+                self.block_proposal_token,
+            ),
+            ("admin_token", RPCEndpointGroup::Admin, self.admin_token),
+        ];
+        for (name, group, token) in tokens.into_iter() {
+            let token = match token {
+                Some(token) => token,
+                None => continue,
+            };
+            if token.is_empty() {
+                return Err(format!("rpc_auth.{} must not be empty", name));
+            }
+            connection_options.rpc_auth_tokens.insert(group, token);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, Deserialize, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
 pub struct EventObserverConfigFile {
     pub endpoint: String,
     pub events_keys: Vec<String>,
+    pub queue_max_size: Option<u64>,
+    pub queue_drop_policy: Option<String>,
+    pub retry_max_backoff_ms: Option<u64>,
 }
 
-#[derive(Clone, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
+/// Default maximum number of undelivered payloads kept for an event observer
+pub const EVENT_OBSERVER_QUEUE_MAX_SIZE: u64 = 100_000;
+/// Default upper bound on the delay between attempts to deliver a payload
+pub const EVENT_OBSERVER_RETRY_MAX_BACKOFF_MS: u64 = 60_000;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd)]
 pub struct EventObserverConfig {
     pub endpoint: String,
     pub events_keys: Vec<EventKeyType>,
+    /// Maximum number of undelivered payloads kept on disk for this observer
+    pub queue_max_size: u64,
+    /// What to do with a new payload when the observer's queue is full
+    pub queue_drop_policy: EventObserverDropPolicy,
+    /// Upper bound on the delay between attempts to deliver a payload
+    pub retry_max_backoff_ms: u64,
+}
+
+impl Default for EventObserverConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            events_keys: vec![],
+            queue_max_size: EVENT_OBSERVER_QUEUE_MAX_SIZE,
+            queue_drop_policy: EventObserverDropPolicy::default(),
+            retry_max_backoff_ms: EVENT_OBSERVER_RETRY_MAX_BACKOFF_MS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd)]
+pub enum EventObserverDropPolicy {
+    /// Wait for the queue to drain, blocking the thread producing the event
+    Block,
+    /// Discard the oldest undelivered payload
+    DropOldest,
+    /// Discard the new payload
+    DropNewest,
+}
+
+impl Default for EventObserverDropPolicy {
+    fn default() -> Self {
+        EventObserverDropPolicy::Block
+    }
+}
+
+impl EventObserverDropPolicy {
+    fn panic_parse(s: String) -> EventObserverDropPolicy {
+        match s.to_lowercase().as_str() {
+            "block" => EventObserverDropPolicy::Block,
+            "drop_oldest" => EventObserverDropPolicy::DropOldest,
+            "drop_newest" => EventObserverDropPolicy::DropNewest,
+            _ => panic!(
+                "Bad event observer queue drop policy supplied in configuration file: {}",
+                s
+            ),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd)]
@@ -2234,10 +3240,15 @@ pub enum EventKeyType {
     MinedBlocks,
     MinedMicroblocks,
     StackerDBChunks,
+    /// Chunks written to one StackerDB
+    StackerDBContractChunks(QualifiedContractIdentifier),
+    /// All smart contract and asset events emitted by one contract
+    ContractEvents(QualifiedContractIdentifier),
+    ForkDivergence,
 }
 
 impl EventKeyType {
-    fn from_string(raw_key: &str) -> Option<EventKeyType> {
+    pub fn from_string(raw_key: &str) -> Option<EventKeyType> {
         if raw_key == "*" {
             return Some(EventKeyType::AnyEvent);
         }
@@ -2262,6 +3273,22 @@ impl EventKeyType {
             return Some(EventKeyType::StackerDBChunks);
         }
 
+        if raw_key == "fork_divergence" {
+            return Some(EventKeyType::ForkDivergence);
+        }
+
+        if let Some(contract_id) = raw_key.strip_prefix("stackerdb::") {
+            return QualifiedContractIdentifier::parse(contract_id)
+                .ok()
+                .map(EventKeyType::StackerDBContractChunks);
+        }
+
+        if let Some(contract_id) = raw_key.strip_suffix("::*") {
+            return QualifiedContractIdentifier::parse(contract_id)
+                .ok()
+                .map(EventKeyType::ContractEvents);
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split(".").collect();
@@ -2310,3 +3337,24 @@ pub struct InitialBalanceFile {
     pub address: String,
     pub amount: u64,
 }
+
+#[derive(Clone, Debug, Default)]
+pub struct LoggingConfig {
+    /// Output format for the node's logs
+    pub format: LogFormat,
+}
+
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct LoggingConfigFile {
+    pub format: Option<String>,
+}
+
+impl LoggingConfigFile {
+    fn into_config(self) -> Result<LoggingConfig, String> {
+        let mut conf = LoggingConfig::default();
+        if let Some(format) = self.format {
+            conf.format = format.parse()?;
+        }
+        Ok(conf)
+    }
+}
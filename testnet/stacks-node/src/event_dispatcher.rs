@@ -1,16 +1,22 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread::sleep;
 use std::time::Duration;
+use std::{cmp, fs, thread};
 
 use async_h1::client;
 use async_std::net::TcpStream;
+#[cfg(unix)]
+use async_std::os::unix::net::UnixStream;
 use clarity::vm::analysis::contract_interface_builder::build_contract_interface;
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::events::{FTEventType, NFTEventType, STXEventType};
 use clarity::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
-use http_types::{Method, Request, Url};
+use http_types::{Method, Request, Response, Url};
 pub use libsigner::StackerDBChunksEvent;
+use libsigner::UNIX_ENDPOINT_PREFIX;
 use serde_json::json;
 use stacks::burnchains::{PoxConstants, Txid};
 use stacks::chainstate::burn::operations::BlockstackOperationType;
@@ -29,17 +35,22 @@ use stacks::chainstate::stacks::{
 };
 use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher};
 use stacks::libstackerdb::StackerDBChunkData;
+use stacks::monitoring::instrument_event_observer_delivery;
 use stacks::net::atlas::{Attachment, AttachmentInstance};
 use stacks::net::stackerdb::StackerDBEventDispatcher;
+use stacks::util_lib::db::{sqlite_open, tx_begin_immediate_sqlite};
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockId};
 use stacks_common::util::hash::bytes_to_hex;
 
-use super::config::{EventKeyType, EventObserverConfig};
+use super::config::{EventKeyType, EventObserverConfig, EventObserverDropPolicy};
+use crate::event_stream::EventStreamServer;
+use crate::fork_monitor::ForkDivergenceEvent;
 
 #[derive(Debug, Clone)]
 struct EventObserver {
     endpoint: String,
+    queue: Arc<EventDeliveryQueue>,
 }
 
 struct ReceiptPayloadInfo<'a> {
@@ -62,6 +73,7 @@ pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
 pub const PATH_MINED_BLOCK: &str = "mined_block";
 pub const PATH_MINED_MICROBLOCK: &str = "mined_microblock";
 pub const PATH_STACKERDB_CHUNKS: &str = "stackerdb_chunks";
+pub const PATH_FORK_DIVERGENCE: &str = "fork_divergence";
 pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
@@ -86,20 +98,209 @@ pub struct MinedMicroblockEvent {
     pub anchor_block: BlockHeaderHash,
 }
 
+const EVENT_QUEUE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS pending_payloads (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    endpoint TEXT NOT NULL,
+    path TEXT NOT NULL,
+    payload TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS pending_payloads_by_endpoint ON pending_payloads(endpoint, id);
+";
+
+/// Delay before the first retry of a failed delivery. It doubles on each failure, up to the
+/// observer's `retry_max_backoff_ms`.
+const EVENT_RETRY_INITIAL_BACKOFF_MS: u64 = 1_000;
+
+/// A durable queue of the payloads not yet delivered to one event observer.
+///
+/// Payloads are stored in a SQLite DB (shared by all of a node's observers) and delivered in
+/// order by a dedicated thread, so a slow or unreachable observer does not block the
+/// threads producing events, and payloads survive a node restart.
+#[derive(Debug)]
+struct EventDeliveryQueue {
+    endpoint: String,
+    max_size: u64,
+    drop_policy: EventObserverDropPolicy,
+    retry_max_backoff: Duration,
+    db: Mutex<rusqlite::Connection>,
+    /// Notified whenever a payload is queued or delivered
+    changed: Condvar,
+}
+
+impl EventDeliveryQueue {
+    /// Open the queue for `conf` in the DB at `db_path`, creating it if needed, and start
+    /// delivering any payloads left over from a previous run.
+    fn open(conf: &EventObserverConfig, db_path: &Path) -> Arc<EventDeliveryQueue> {
+        if let Some(parent) = db_path.parent() {
+            fs::create_dir_all(parent).expect(&format!(
+                "FATAL: failed to create directory for event observer queue at {}",
+                parent.display()
+            ));
+        }
+        let mut db = sqlite_open(
+            db_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_CREATE | rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE,
+            false,
+        )
+        .expect("FATAL: failed to open event observer queue");
+        let tx = tx_begin_immediate_sqlite(&mut db).expect("FATAL: event observer queue failure");
+        tx.execute_batch(EVENT_QUEUE_SCHEMA)
+            .expect("FATAL: failed to instantiate event observer queue");
+        tx.commit().expect("FATAL: event observer queue failure");
+
+        let queue = Arc::new(EventDeliveryQueue {
+            endpoint: conf.endpoint.clone(),
+            max_size: cmp::max(conf.queue_max_size, 1),
+            drop_policy: conf.queue_drop_policy,
+            retry_max_backoff: Duration::from_millis(cmp::max(
+                conf.retry_max_backoff_ms,
+                EVENT_RETRY_INITIAL_BACKOFF_MS,
+            )),
+            db: Mutex::new(db),
+            changed: Condvar::new(),
+        });
+
+        let pending = queue.len(&queue.db.lock().expect("FATAL: event queue lock poisoned"));
+        if pending > 0 {
+            info!("Event dispatcher: resuming delivery of queued payloads";
+                  "endpoint" => &conf.endpoint, "pending" => pending);
+        }
+
+        let delivery_queue = queue.clone();
+        thread::Builder::new()
+            .name(format!("event-observer:{}", &conf.endpoint))
+            .spawn(move || delivery_queue.run_delivery())
+            .expect("FATAL: failed to start event observer delivery thread");
+
+        queue
+    }
+
+    fn len(&self, db: &rusqlite::Connection) -> u64 {
+        db.query_row(
+            "SELECT COUNT(*) FROM pending_payloads WHERE endpoint = ?1",
+            rusqlite::params![&self.endpoint],
+            |row| row.get::<_, i64>(0),
+        )
+        .expect("FATAL: event observer queue failure") as u64
+    }
+
+    /// Queue `payload` for delivery to `path`, applying the drop policy if the queue is full.
+    fn push(&self, path: &str, payload: &str) {
+        let mut db = self.db.lock().expect("FATAL: event queue lock poisoned");
+        let mut pending = self.len(&db);
+        while pending >= self.max_size {
+            match self.drop_policy {
+                EventObserverDropPolicy::Block => {
+                    db = self
+                        .changed
+                        .wait(db)
+                        .expect("FATAL: event queue lock poisoned");
+                    pending = self.len(&db);
+                }
+                EventObserverDropPolicy::DropNewest => {
+                    warn!("Event dispatcher: queue full, dropping new payload";
+                          "endpoint" => &self.endpoint, "path" => path);
+                    return;
+                }
+                EventObserverDropPolicy::DropOldest => {
+                    warn!("Event dispatcher: queue full, dropping oldest payload";
+                          "endpoint" => &self.endpoint);
+                    db.execute(
+                        "DELETE FROM pending_payloads WHERE id = (SELECT MIN(id) FROM pending_payloads WHERE endpoint = ?1)",
+                        rusqlite::params![&self.endpoint],
+                    )
+                    .expect("FATAL: event observer queue failure");
+                    pending -= 1;
+                }
+            }
+        }
+        db.execute(
+            "INSERT INTO pending_payloads (endpoint, path, payload) VALUES (?1, ?2, ?3)",
+            rusqlite::params![&self.endpoint, path, payload],
+        )
+        .expect("FATAL: event observer queue failure");
+        self.changed.notify_all();
+    }
+
+    /// Wait for the oldest queued payload, without removing it
+    fn peek(&self) -> (i64, String, String) {
+        let mut db = self.db.lock().expect("FATAL: event queue lock poisoned");
+        loop {
+            let next = db.query_row(
+                "SELECT id, path, payload FROM pending_payloads WHERE endpoint = ?1 ORDER BY id ASC LIMIT 1",
+                rusqlite::params![&self.endpoint],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            );
+            match next {
+                Ok(next) => return next,
+                Err(rusqlite::Error::QueryReturnedNoRows) => {
+                    db = self
+                        .changed
+                        .wait(db)
+                        .expect("FATAL: event queue lock poisoned");
+                }
+                Err(e) => panic!("FATAL: event observer queue failure: {:?}", &e),
+            }
+        }
+    }
+
+    fn remove(&self, id: i64) {
+        let db = self.db.lock().expect("FATAL: event queue lock poisoned");
+        db.execute(
+            "DELETE FROM pending_payloads WHERE id = ?1",
+            rusqlite::params![id],
+        )
+        .expect("FATAL: event observer queue failure");
+        self.changed.notify_all();
+    }
+
+    /// Deliver queued payloads in order, forever. A payload is only removed from the queue once
+    /// the observer has acknowledged it, so a failed delivery is retried with exponential
+    /// backoff before any later payload is sent.
+    fn run_delivery(&self) {
+        let initial_backoff = Duration::from_millis(EVENT_RETRY_INITIAL_BACKOFF_MS);
+        let mut backoff = initial_backoff;
+        loop {
+            let (id, path, payload) = self.peek();
+            let delivered = instrument_event_observer_delivery(&self.endpoint, &path, || {
+                EventObserver::post_payload(&self.endpoint, &path, payload.into_bytes())
+            });
+            if delivered {
+                self.remove(id);
+                backoff = initial_backoff;
+            } else {
+                sleep(backoff);
+                backoff = cmp::min(backoff * 2, self.retry_max_backoff);
+            }
+        }
+    }
+}
+
 impl EventObserver {
+    /// Queue `payload` for delivery to this observer at `path`
     pub fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
+        let body = match serde_json::to_string(&payload) {
             Ok(body) => body,
             Err(err) => {
                 error!("Event dispatcher: serialization failed  - {:?}", err);
                 return;
             }
         };
+        self.queue.push(path, &body);
+    }
 
+    /// Make one attempt to POST `body` to `endpoint` at `path`.
+    /// `endpoint` is either `host:port`, or `unix:` followed by the path of a unix domain socket.
+    /// Returns true if the observer acknowledged it.
+    fn post_payload(endpoint: &str, path: &str, body: Vec<u8>) -> bool {
+        let socket_path = endpoint.strip_prefix(UNIX_ENDPOINT_PREFIX);
         let url = {
+            // a unix domain socket has no host, so any will do
+            let host = socket_path.map_or(endpoint, |_| "localhost");
             let joined_components = match path.starts_with("/") {
-                true => format!("{}{}", &self.endpoint, path),
-                false => format!("{}/{}", &self.endpoint, path),
+                true => format!("{}{}", host, path),
+                false => format!("{}/{}", host, path),
             };
             let url = format!("http://{}", joined_components);
             Url::parse(&url).expect(&format!(
@@ -108,48 +309,74 @@ impl EventObserver {
             ))
         };
 
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
-
-        loop {
-            let body = body.clone();
-            let mut req = Request::new(Method::Post, url.clone());
-            req.append_header("Content-Type", "application/json");
-            req.set_body(body);
-
-            let response = async_std::task::block_on(async {
-                let stream = match TcpStream::connect(self.endpoint.clone()).await {
-                    Ok(stream) => stream,
-                    Err(err) => {
-                        warn!("Event dispatcher: connection failed  - {:?}", err);
-                        return None;
-                    }
-                };
+        let mut req = Request::new(Method::Post, url.clone());
+        req.append_header("Content-Type", "application/json");
+        req.set_body(body);
 
-                match client::connect(stream, req).await {
-                    Ok(response) => Some(response),
-                    Err(err) => {
-                        warn!("Event dispatcher: rpc invocation failed  - {:?}", err);
-                        return None;
-                    }
+        let response = async_std::task::block_on(async {
+            if let Some(socket_path) = socket_path {
+                return Self::post_unix(socket_path, req).await;
+            }
+            let stream = match TcpStream::connect(endpoint).await {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Event dispatcher: connection failed  - {:?}", err);
+                    return None;
                 }
-            });
+            };
 
-            if let Some(response) = response {
-                if response.status().is_success() {
-                    debug!(
-                        "Event dispatcher: Successful POST"; "url" => %url
-                    );
-                    break;
-                } else {
-                    error!(
-                        "Event dispatcher: Failed POST"; "url" => %url, "err" => ?response
-                    );
+            match client::connect(stream, req).await {
+                Ok(response) => Some(response),
+                Err(err) => {
+                    warn!("Event dispatcher: rpc invocation failed  - {:?}", err);
+                    return None;
                 }
             }
-            sleep(backoff);
+        });
+
+        match response {
+            Some(response) if response.status().is_success() => {
+                debug!(
+                    "Event dispatcher: Successful POST"; "url" => %url
+                );
+                true
+            }
+            Some(response) => {
+                error!(
+                    "Event dispatcher: Failed POST"; "url" => %url, "err" => ?response
+                );
+                false
+            }
+            None => false,
         }
     }
 
+    /// POST `req` over the unix domain socket at `socket_path`
+    #[cfg(unix)]
+    async fn post_unix(socket_path: &str, req: Request) -> Option<Response> {
+        let stream = match UnixStream::connect(socket_path).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Event dispatcher: connection failed  - {:?}", err);
+                return None;
+            }
+        };
+        match client::connect(stream, req).await {
+            Ok(response) => Some(response),
+            Err(err) => {
+                warn!("Event dispatcher: rpc invocation failed  - {:?}", err);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn post_unix(socket_path: &str, _req: Request) -> Option<Response> {
+        warn!("Event dispatcher: unix domain sockets are not supported on this platform";
+              "path" => socket_path);
+        None
+    }
+
     fn make_new_mempool_txs_payload(transactions: Vec<StacksTransaction>) -> serde_json::Value {
         let raw_txs = transactions
             .into_iter()
@@ -277,6 +504,32 @@ impl EventObserver {
         })
     }
 
+    /// Returns the json payload with the cost breakdown of a transaction in a new block, so that
+    /// observers can compute fee rates without re-executing the transaction
+    fn make_tx_cost_payload(
+        receipt: &StacksTransactionReceipt,
+        tx_index: u32,
+    ) -> serde_json::Value {
+        // burnchain operations have neither a fee nor a Stacks encoding
+        let (tx_len, fee) = match &receipt.transaction {
+            TransactionOrigin::Stacks(tx) => (tx.serialize_to_vec().len() as u64, tx.get_tx_fee()),
+            TransactionOrigin::Burn(_) => (0, 0),
+        };
+
+        json!({
+            "txid": format!("0x{}", &receipt.transaction.txid()),
+            "tx_index": tx_index,
+            "runtime": receipt.execution_cost.runtime,
+            "read_count": receipt.execution_cost.read_count,
+            "read_length": receipt.execution_cost.read_length,
+            "write_count": receipt.execution_cost.write_count,
+            "write_length": receipt.execution_cost.write_length,
+            "tx_len": tx_len,
+            "fee": fee,
+            "microblock_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.block_hash())),
+        })
+    }
+
     fn make_new_attachment_payload(
         attachment: &(AttachmentInstance, Attachment),
     ) -> serde_json::Value {
@@ -300,16 +553,15 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
-    /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
-    fn send_new_microblocks(
-        &self,
+    /// Serializes new microblocks data into a JSON payload
+    fn make_new_microblocks_payload(
         parent_index_block_hash: StacksBlockId,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         serialized_txs: &Vec<serde_json::Value>,
         burn_block_hash: BurnchainHeaderHash,
         burn_block_height: u32,
         burn_block_timestamp: u64,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -320,16 +572,18 @@ impl EventObserver {
             })
             .collect();
 
-        let payload = json!({
+        json!({
             "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
             "events": serialized_events,
             "transactions": serialized_txs,
             "burn_block_hash": format!("0x{}", burn_block_hash),
             "burn_block_height": burn_block_height,
             "burn_block_timestamp": burn_block_timestamp,
-        });
+        })
+    }
 
-        self.send_payload(&payload, PATH_MICROBLOCK_SUBMIT);
+    fn send_new_microblocks(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MICROBLOCK_SUBMIT);
     }
 
     fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
@@ -348,12 +602,15 @@ impl EventObserver {
         self.send_payload(payload, PATH_STACKERDB_CHUNKS);
     }
 
+    fn send_fork_divergence(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_FORK_DIVERGENCE);
+    }
+
     fn send_new_burn_block(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
     fn make_new_block_processed_payload(
-        &self,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         block: &StacksBlock,
         metadata: &StacksHeaderInfo,
@@ -380,9 +637,11 @@ impl EventObserver {
 
         let mut tx_index: u32 = 0;
         let mut serialized_txs = vec![];
+        let mut tx_costs = vec![];
         for receipt in receipts.iter() {
             let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
             serialized_txs.push(payload);
+            tx_costs.push(EventObserver::make_tx_cost_payload(receipt, tx_index));
             tx_index += 1;
         }
 
@@ -402,6 +661,7 @@ impl EventObserver {
             "matured_miner_rewards": mature_rewards.clone(),
             "events": serialized_events,
             "transactions": serialized_txs,
+            "tx_costs": tx_costs,
             "parent_burn_block_hash":  format!("0x{}", parent_burn_block_hash),
             "parent_burn_block_height": parent_burn_block_height,
             "parent_burn_block_timestamp": parent_burn_block_timestamp,
@@ -426,6 +686,10 @@ pub struct EventDispatcher {
     miner_observers_lookup: HashSet<u16>,
     mined_microblocks_observers_lookup: HashSet<u16>,
     stackerdb_observers_lookup: HashSet<u16>,
+    stackerdb_contract_observers_lookup: HashMap<QualifiedContractIdentifier, HashSet<u16>>,
+    contract_observers_lookup: HashMap<QualifiedContractIdentifier, HashSet<u16>>,
+    fork_divergence_observers_lookup: HashSet<u16>,
+    event_stream: Option<Arc<EventStreamServer>>,
 }
 
 impl MemPoolEventDispatcher for EventDispatcher {
@@ -547,6 +811,10 @@ impl EventDispatcher {
             miner_observers_lookup: HashSet::new(),
             mined_microblocks_observers_lookup: HashSet::new(),
             stackerdb_observers_lookup: HashSet::new(),
+            stackerdb_contract_observers_lookup: HashMap::new(),
+            contract_observers_lookup: HashMap::new(),
+            fork_divergence_observers_lookup: HashSet::new(),
+            event_stream: None,
         }
     }
 
@@ -619,6 +887,11 @@ impl EventDispatcher {
                                 dispatch_matrix[*o_i as usize].insert(i);
                             }
                         }
+                        self.update_dispatch_matrix_if_contract_observed(
+                            &event_data.key.0,
+                            i,
+                            &mut dispatch_matrix,
+                        );
                     }
                     StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(_))
                     | StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(_))
@@ -703,7 +976,7 @@ impl EventDispatcher {
         let all_receipts = receipts.to_owned();
         let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&all_receipts);
 
-        if dispatch_matrix.len() > 0 {
+        if dispatch_matrix.len() > 0 || self.event_stream.is_some() {
             let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
                 mature_rewards
                     .iter()
@@ -733,9 +1006,30 @@ impl EventDispatcher {
                     .map(|event_id| (*event_id, &events[*event_id]))
                     .collect();
 
-                let payload = self.registered_observers[observer_id]
-                    .make_new_block_processed_payload(
-                        filtered_events,
+                let payload = EventObserver::make_new_block_processed_payload(
+                    filtered_events,
+                    block,
+                    metadata,
+                    receipts,
+                    parent_index_hash,
+                    &winner_txid,
+                    &mature_rewards,
+                    parent_burn_block_hash,
+                    parent_burn_block_height,
+                    parent_burn_block_timestamp,
+                    anchored_consumed,
+                    mblock_confirmed_consumed,
+                    pox_constants,
+                );
+
+                // Send payload
+                self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
+            }
+
+            if let Some(event_stream) = self.event_stream.as_ref() {
+                event_stream.publish(PATH_BLOCK_PROCESSED, |events_keys| {
+                    Some(EventObserver::make_new_block_processed_payload(
+                        Self::filter_events_for_stream(events_keys, &events),
                         block,
                         metadata,
                         receipts,
@@ -748,10 +1042,8 @@ impl EventDispatcher {
                         anchored_consumed,
                         mblock_confirmed_consumed,
                         pox_constants,
-                    );
-
-                // Send payload
-                self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
+                    ))
+                });
             }
         }
     }
@@ -774,7 +1066,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && self.event_stream.is_none() {
             return;
         }
         let flattened_receipts = processed_unconfirmed_state
@@ -805,7 +1097,7 @@ impl EventDispatcher {
                 .map(|event_id| (*event_id, &events[*event_id]))
                 .collect();
 
-            observer.send_new_microblocks(
+            let payload = EventObserver::make_new_microblocks_payload(
                 parent_index_block_hash,
                 filtered_events,
                 &serialized_txs,
@@ -813,6 +1105,25 @@ impl EventDispatcher {
                 processed_unconfirmed_state.burn_block_height,
                 processed_unconfirmed_state.burn_block_timestamp,
             );
+            observer.send_new_microblocks(&payload);
+        }
+
+        if let Some(event_stream) = self.event_stream.as_ref() {
+            event_stream.publish(PATH_MICROBLOCK_SUBMIT, |events_keys| {
+                if !events_keys.contains(&EventKeyType::Microblocks)
+                    && !events_keys.contains(&EventKeyType::AnyEvent)
+                {
+                    return None;
+                }
+                Some(EventObserver::make_new_microblocks_payload(
+                    parent_index_block_hash,
+                    Self::filter_events_for_stream(events_keys, &events),
+                    &serialized_txs,
+                    processed_unconfirmed_state.burn_block_hash,
+                    processed_unconfirmed_state.burn_block_height,
+                    processed_unconfirmed_state.burn_block_timestamp,
+                ))
+            });
         }
     }
 
@@ -827,7 +1138,7 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && self.event_stream.is_none() {
             return;
         }
 
@@ -836,6 +1147,18 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_mempool_txs(&payload);
         }
+
+        if let Some(event_stream) = self.event_stream.as_ref() {
+            event_stream.publish(PATH_MEMPOOL_TX_SUBMIT, |events_keys| {
+                if events_keys.contains(&EventKeyType::MemPoolTransactions)
+                    || events_keys.contains(&EventKeyType::AnyEvent)
+                {
+                    Some(payload.clone())
+                } else {
+                    None
+                }
+            });
+        }
     }
 
     pub fn process_mined_block_event(
@@ -920,12 +1243,18 @@ impl EventDispatcher {
             .enumerate()
             .filter(|(obs_id, _observer)| {
                 self.stackerdb_observers_lookup.contains(&(*obs_id as u16))
+                    || self
+                        .stackerdb_contract_observers_lookup
+                        .get(&contract_id)
+                        .map(|observer_indexes| observer_indexes.contains(&(*obs_id as u16)))
+                        .unwrap_or(false)
             })
             .collect();
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && self.event_stream.is_none() {
             return;
         }
 
+        let stackerdb_key = EventKeyType::StackerDBContractChunks(contract_id.clone());
         let payload = serde_json::to_value(StackerDBChunksEvent {
             contract_id,
             modified_slots: new_chunks,
@@ -935,6 +1264,18 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_stackerdb_chunks(&payload);
         }
+
+        if let Some(event_stream) = self.event_stream.as_ref() {
+            event_stream.publish(PATH_STACKERDB_CHUNKS, |events_keys| {
+                if events_keys.contains(&EventKeyType::StackerDBChunks)
+                    || events_keys.contains(&stackerdb_key)
+                {
+                    Some(payload.clone())
+                } else {
+                    None
+                }
+            });
+        }
     }
 
     pub fn process_dropped_mempool_txs(&self, txs: Vec<Txid>, reason: MemPoolDropReason) {
@@ -967,6 +1308,29 @@ impl EventDispatcher {
         }
     }
 
+    pub fn process_fork_divergence(&self, event: &ForkDivergenceEvent) {
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.fork_divergence_observers_lookup
+                    .contains(&(*obs_id as u16))
+                    || self.any_event_observers_lookup.contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload =
+            serde_json::to_value(event).expect("FATAL: failed to serialize ForkDivergenceEvent");
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_fork_divergence(&payload);
+        }
+    }
+
     pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
         let interested_observers: Vec<_> = self.registered_observers.iter().enumerate().collect();
         if interested_observers.len() < 1 {
@@ -995,12 +1359,108 @@ impl EventDispatcher {
                 dispatch_matrix[*o_i as usize].insert(event_index);
             }
         }
+        self.update_dispatch_matrix_if_contract_observed(
+            &asset_identifier.contract_identifier,
+            event_index,
+            dispatch_matrix,
+        );
+    }
+
+    fn update_dispatch_matrix_if_contract_observed(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        event_index: usize,
+        dispatch_matrix: &mut Vec<HashSet<usize>>,
+    ) {
+        if let Some(observer_indexes) = self.contract_observers_lookup.get(contract_identifier) {
+            for o_i in observer_indexes {
+                dispatch_matrix[*o_i as usize].insert(event_index);
+            }
+        }
+    }
+
+    /// The asset an NFT or FT event is about
+    fn event_asset_identifier(event: &StacksTransactionEvent) -> Option<&AssetIdentifier> {
+        match event {
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(event_data)) => {
+                Some(&event_data.asset_identifier)
+            }
+            StacksTransactionEvent::SmartContractEvent(_) | StacksTransactionEvent::STXEvent(_) => {
+                None
+            }
+        }
     }
 
-    pub fn register_observer(&mut self, conf: &EventObserverConfig) {
+    /// Does an event stream client subscribed to `events_keys` want `event`?
+    /// This matches what `create_dispatch_matrix_and_event_vector` sends to event observers.
+    fn stream_wants_event(events_keys: &[EventKeyType], event: &StacksTransactionEvent) -> bool {
+        events_keys
+            .iter()
+            .any(|event_key| match (event_key, event) {
+                (EventKeyType::AnyEvent, _) => true,
+                (
+                    EventKeyType::SmartContractEvent(event_key),
+                    StacksTransactionEvent::SmartContractEvent(event_data),
+                ) => *event_key == event_data.key,
+                (
+                    EventKeyType::ContractEvents(contract_id),
+                    StacksTransactionEvent::SmartContractEvent(event_data),
+                ) => *contract_id == event_data.key.0,
+                (EventKeyType::ContractEvents(contract_id), _) => {
+                    Self::event_asset_identifier(event)
+                        .map(|asset_identifier| {
+                            asset_identifier.contract_identifier == *contract_id
+                        })
+                        .unwrap_or(false)
+                }
+                (EventKeyType::STXEvent, StacksTransactionEvent::STXEvent(_)) => true,
+                (EventKeyType::AssetEvent(asset_identifier), _) => {
+                    Self::event_asset_identifier(event) == Some(asset_identifier)
+                }
+                _ => false,
+            })
+    }
+
+    fn filter_events_for_stream<'a, 'b>(
+        events_keys: &[EventKeyType],
+        events: &'a [(bool, Txid, &'b StacksTransactionEvent)],
+    ) -> Vec<(usize, &'a (bool, Txid, &'b StacksTransactionEvent))> {
+        events
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, event))| Self::stream_wants_event(events_keys, event))
+            .collect()
+    }
+
+    /// Start streaming event payloads to WebSocket clients connecting to `bind`
+    pub fn start_event_stream(&mut self, bind: &str) {
+        let event_stream = EventStreamServer::start(bind)
+            .expect(&format!("FATAL: failed to start event stream on {}", bind));
+        self.event_stream = Some(event_stream);
+    }
+
+    /// Register an event observer. Payloads for it that have not been delivered yet are queued
+    /// in the DB at `queue_db_path`.
+    pub fn register_observer(&mut self, conf: &EventObserverConfig, queue_db_path: &Path) {
         info!("Registering event observer at: {}", conf.endpoint);
         let event_observer = EventObserver {
             endpoint: conf.endpoint.clone(),
+            queue: EventDeliveryQueue::open(conf, queue_db_path),
         };
 
         let observer_index = self.registered_observers.len() as u16;
@@ -1059,6 +1519,21 @@ impl EventDispatcher {
                 EventKeyType::StackerDBChunks => {
                     self.stackerdb_observers_lookup.insert(observer_index);
                 }
+                EventKeyType::StackerDBContractChunks(contract_id) => {
+                    self.stackerdb_contract_observers_lookup
+                        .entry(contract_id.clone())
+                        .or_insert_with(HashSet::new)
+                        .insert(observer_index);
+                }
+                EventKeyType::ContractEvents(contract_id) => {
+                    self.contract_observers_lookup
+                        .entry(contract_id.clone())
+                        .or_insert_with(HashSet::new)
+                        .insert(observer_index);
+                }
+                EventKeyType::ForkDivergence => {
+                    self.fork_divergence_observers_lookup.insert(observer_index);
+                }
             }
         }
 
@@ -1069,19 +1544,23 @@ impl EventDispatcher {
 #[cfg(test)]
 mod test {
     use clarity::vm::costs::ExecutionCost;
+    use clarity::vm::events::{FTEventType, FTTransferEventData, SmartContractEventData};
+    use clarity::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier, Value};
     use stacks::burnchains::{PoxConstants, Txid};
     use stacks::chainstate::stacks::db::StacksHeaderInfo;
-    use stacks::chainstate::stacks::StacksBlock;
+    use stacks::chainstate::stacks::events::{StacksTransactionEvent, StacksTransactionReceipt};
+    use stacks::chainstate::stacks::{
+        CoinbasePayload, StacksBlock, StacksPrivateKey, StacksTransaction, TransactionAuth,
+        TransactionPayload, TransactionVersion,
+    };
+    use stacks_common::codec::StacksMessageCodec;
     use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
 
-    use crate::event_dispatcher::EventObserver;
+    use crate::config::EventKeyType;
+    use crate::event_dispatcher::{EventDispatcher, EventObserver};
 
     #[test]
     fn build_block_processed_event() {
-        let observer = EventObserver {
-            endpoint: "nowhere".to_string(),
-        };
-
         let filtered_events = vec![];
         let block = StacksBlock::genesis_block();
         let metadata = StacksHeaderInfo::regtest_genesis();
@@ -1096,7 +1575,7 @@ mod test {
         let mblock_confirmed_consumed = ExecutionCost::zero();
         let pox_constants = PoxConstants::testnet_default();
 
-        let payload = observer.make_new_block_processed_payload(
+        let payload = EventObserver::make_new_block_processed_payload(
             filtered_events,
             &block,
             &metadata,
@@ -1120,4 +1599,110 @@ mod test {
             pox_constants.v1_unlock_height as u64
         );
     }
+
+    #[test]
+    fn build_tx_cost_payload() {
+        let privk = StacksPrivateKey::new();
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::Coinbase(CoinbasePayload([0; 32]), None),
+        );
+        tx.set_tx_fee(123);
+        let mut receipt = StacksTransactionReceipt::from_coinbase(tx.clone());
+        receipt.execution_cost = ExecutionCost {
+            runtime: 1,
+            read_count: 2,
+            read_length: 3,
+            write_count: 4,
+            write_length: 5,
+        };
+
+        let payload = EventObserver::make_new_block_processed_payload(
+            vec![],
+            &StacksBlock::genesis_block(),
+            &StacksHeaderInfo::regtest_genesis(),
+            &[receipt],
+            &StacksBlockId([0; 32]),
+            &Txid([0; 32]),
+            &serde_json::Value::Array(vec![]),
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+        );
+        let tx_costs = payload.get("tx_costs").unwrap().as_array().unwrap();
+        assert_eq!(tx_costs.len(), 1);
+        let tx_cost = &tx_costs[0];
+        assert_eq!(tx_cost["txid"], format!("0x{}", tx.txid()));
+        assert_eq!(tx_cost["tx_index"], 0);
+        assert_eq!(tx_cost["runtime"], 1);
+        assert_eq!(tx_cost["read_count"], 2);
+        assert_eq!(tx_cost["read_length"], 3);
+        assert_eq!(tx_cost["write_count"], 4);
+        assert_eq!(tx_cost["write_length"], 5);
+        assert_eq!(tx_cost["tx_len"], tx.serialize_to_vec().len() as u64);
+        assert_eq!(tx_cost["fee"], 123);
+        assert!(tx_cost["microblock_hash"].is_null());
+    }
+
+    #[test]
+    fn stream_filters_events() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.token").unwrap();
+        let other_contract_id =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.other").unwrap();
+        let print_event = StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+            key: (contract_id.clone(), "print".to_string()),
+            value: Value::UInt(1),
+        });
+        let ft_event =
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(FTTransferEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: contract_id.clone(),
+                    asset_name: "token".into(),
+                },
+                sender: PrincipalData::from(contract_id.clone()),
+                recipient: PrincipalData::from(other_contract_id.clone()),
+                amount: 1,
+            }));
+        let events = vec![
+            (true, Txid([0; 32]), &print_event),
+            (true, Txid([1; 32]), &ft_event),
+        ];
+
+        let filtered_ids = |events_keys: &[EventKeyType]| -> Vec<usize> {
+            EventDispatcher::filter_events_for_stream(events_keys, &events)
+                .into_iter()
+                .map(|(event_id, _)| event_id)
+                .collect()
+        };
+
+        assert_eq!(filtered_ids(&[EventKeyType::AnyEvent]), vec![0, 1]);
+        assert_eq!(
+            filtered_ids(&[EventKeyType::ContractEvents(contract_id.clone())]),
+            vec![0, 1]
+        );
+        assert_eq!(
+            filtered_ids(&[EventKeyType::ContractEvents(other_contract_id)]),
+            Vec::<usize>::new()
+        );
+        assert_eq!(
+            filtered_ids(&[EventKeyType::SmartContractEvent((
+                contract_id.clone(),
+                "print".to_string()
+            ))]),
+            vec![0]
+        );
+        assert_eq!(
+            filtered_ids(&[EventKeyType::AssetEvent(AssetIdentifier {
+                contract_identifier: contract_id,
+                asset_name: "token".into(),
+            })]),
+            vec![1]
+        );
+        assert_eq!(filtered_ids(&[EventKeyType::STXEvent]), Vec::<usize>::new());
+    }
 }
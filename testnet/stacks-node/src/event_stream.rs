@@ -0,0 +1,300 @@
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{io, thread};
+
+use serde_json::json;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::frame::CloseFrame;
+use tungstenite::{Message, WebSocket};
+
+use super::config::EventKeyType;
+
+/// Number of payloads queued for a client before it is considered too slow and disconnected
+pub const EVENT_STREAM_CLIENT_QUEUE_SIZE: usize = 1024;
+/// How long a client has to complete the handshake and send its subscription
+const EVENT_STREAM_SUBSCRIBE_TIMEOUT_SECS: u64 = 30;
+/// How often a client's thread checks for queued payloads while waiting for client messages
+const EVENT_STREAM_POLL_INTERVAL_MS: u64 = 100;
+
+/// The first message a client sends, selecting the events it wants with the same keys as an
+/// `[[events_observer]]`'s `events_keys`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventStreamSubscription {
+    pub events_keys: Vec<String>,
+}
+
+struct EventStreamClient {
+    addr: SocketAddr,
+    events_keys: Vec<EventKeyType>,
+    sender: SyncSender<String>,
+}
+
+/// A WebSocket server which streams event payloads to connected clients.
+///
+/// Each message sent to a client is a JSON object `{"path": ..., "payload": ...}`, where `path`
+/// and `payload` are what an HTTP event observer would receive. Unlike event observers, clients
+/// are not guaranteed delivery: a client that falls `EVENT_STREAM_CLIENT_QUEUE_SIZE` payloads
+/// behind is disconnected.
+pub struct EventStreamServer {
+    local_addr: SocketAddr,
+    clients: Mutex<Vec<EventStreamClient>>,
+}
+
+impl EventStreamServer {
+    /// Bind to `bind` and start accepting clients
+    pub fn start(bind: &str) -> Result<Arc<EventStreamServer>, io::Error> {
+        let listener = TcpListener::bind(bind)?;
+        let server = Arc::new(EventStreamServer {
+            local_addr: listener.local_addr()?,
+            clients: Mutex::new(vec![]),
+        });
+
+        let accept_server = server.clone();
+        thread::Builder::new()
+            .name("event-stream".into())
+            .spawn(move || accept_server.run_accept(listener))?;
+
+        info!("Event stream: listening"; "addr" => %server.local_addr);
+        Ok(server)
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn num_clients(&self) -> usize {
+        self.clients
+            .lock()
+            .expect("FATAL: event stream lock poisoned")
+            .len()
+    }
+
+    /// Queue a message at `path` for each client. `make_payload` is given each client's
+    /// subscription and returns the payload to send it, or `None` if it isn't interested.
+    /// Clients which have disconnected or fallen too far behind are dropped.
+    pub fn publish<F>(&self, path: &str, make_payload: F)
+    where
+        F: Fn(&[EventKeyType]) -> Option<serde_json::Value>,
+    {
+        let mut clients = self
+            .clients
+            .lock()
+            .expect("FATAL: event stream lock poisoned");
+        clients.retain(|client| {
+            let payload = match make_payload(&client.events_keys) {
+                Some(payload) => payload,
+                None => return true,
+            };
+            let message = json!({
+                "path": path,
+                "payload": payload,
+            })
+            .to_string();
+            match client.sender.try_send(message) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Event stream: client is not keeping up, disconnecting"; "addr" => %client.addr);
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    debug!("Event stream: client disconnected"; "addr" => %client.addr);
+                    false
+                }
+            }
+        });
+    }
+
+    fn run_accept(self: Arc<Self>, listener: TcpListener) {
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    warn!("Event stream: failed to accept connection: {:?}", &e);
+                    continue;
+                }
+            };
+            let server = self.clone();
+            if let Err(e) = thread::Builder::new()
+                .name("event-stream-client".into())
+                .spawn(move || server.serve_client(stream))
+            {
+                warn!("Event stream: failed to start client thread: {:?}", &e);
+            }
+        }
+    }
+
+    /// Complete the WebSocket handshake, read the client's subscription, and then forward it
+    /// its payloads until it disconnects.
+    fn serve_client(&self, stream: TcpStream) {
+        let addr = match stream.peer_addr() {
+            Ok(addr) => addr,
+            Err(_) => return,
+        };
+        if let Err(e) = stream.set_read_timeout(Some(Duration::from_secs(
+            EVENT_STREAM_SUBSCRIBE_TIMEOUT_SECS,
+        ))) {
+            warn!("Event stream: failed to configure socket: {:?}", &e; "addr" => %addr);
+            return;
+        }
+        let mut socket = match tungstenite::accept(stream) {
+            Ok(socket) => socket,
+            Err(e) => {
+                debug!("Event stream: handshake failed: {:?}", &e; "addr" => %addr);
+                return;
+            }
+        };
+
+        let events_keys = match Self::read_subscription(&mut socket) {
+            Ok(events_keys) => events_keys,
+            Err(reason) => {
+                debug!("Event stream: rejecting client: {}", &reason; "addr" => %addr);
+                let _ = socket.close(Some(CloseFrame {
+                    code: CloseCode::Policy,
+                    reason: reason.into(),
+                }));
+                let _ = socket.write_pending();
+                return;
+            }
+        };
+
+        if let Err(e) = socket
+            .get_ref()
+            .set_read_timeout(Some(Duration::from_millis(EVENT_STREAM_POLL_INTERVAL_MS)))
+        {
+            warn!("Event stream: failed to configure socket: {:?}", &e; "addr" => %addr);
+            return;
+        }
+
+        let (sender, receiver) = sync_channel(EVENT_STREAM_CLIENT_QUEUE_SIZE);
+        info!("Event stream: client subscribed"; "addr" => %addr, "events_keys" => ?events_keys);
+        self.clients
+            .lock()
+            .expect("FATAL: event stream lock poisoned")
+            .push(EventStreamClient {
+                addr,
+                events_keys,
+                sender,
+            });
+
+        if let Err(e) = Self::forward_payloads(&mut socket, &receiver) {
+            debug!("Event stream: client connection closed: {:?}", &e; "addr" => %addr);
+        }
+    }
+
+    /// Read and parse the client's subscription message
+    fn read_subscription(socket: &mut WebSocket<TcpStream>) -> Result<Vec<EventKeyType>, String> {
+        let text = match socket.read_message() {
+            Ok(Message::Text(text)) => text,
+            Ok(_) => return Err("Expected a subscription message".into()),
+            Err(e) => return Err(format!("Failed to read subscription: {:?}", &e)),
+        };
+        let subscription: EventStreamSubscription =
+            serde_json::from_str(&text).map_err(|_e| "Failed to parse subscription".to_string())?;
+        subscription
+            .events_keys
+            .iter()
+            .map(|key| {
+                EventKeyType::from_string(key).ok_or_else(|| format!("Unknown event key: {}", key))
+            })
+            .collect()
+    }
+
+    /// Write queued payloads to the client, in between waiting for (and ignoring) its own
+    /// messages, until either side closes the connection.
+    fn forward_payloads(
+        socket: &mut WebSocket<TcpStream>,
+        receiver: &Receiver<String>,
+    ) -> Result<(), tungstenite::Error> {
+        loop {
+            loop {
+                match receiver.try_recv() {
+                    Ok(message) => socket.write_message(Message::Text(message))?,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        // the server dropped this client
+                        socket.close(Some(CloseFrame {
+                            code: CloseCode::Again,
+                            reason: "Too many undelivered payloads".into(),
+                        }))?;
+                        return socket.write_pending();
+                    }
+                }
+            }
+
+            match socket.read_message() {
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(e))
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tungstenite::Message;
+
+    use super::EventStreamServer;
+    use crate::config::EventKeyType;
+
+    #[test]
+    fn stream_filtered_payloads() {
+        let server = EventStreamServer::start("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", server.local_addr());
+        let (mut client, _) = tungstenite::connect(url.as_str()).unwrap();
+        client
+            .write_message(Message::Text(r#"{"events_keys": ["memtx"]}"#.into()))
+            .unwrap();
+        while server.num_clients() == 0 {
+            sleep(Duration::from_millis(10));
+        }
+
+        server.publish("new_burn_block", |events_keys| {
+            if events_keys.contains(&EventKeyType::BurnchainBlocks) {
+                Some(json!({ "sent_to": "new_burn_block" }))
+            } else {
+                None
+            }
+        });
+        server.publish("new_mempool_tx", |events_keys| {
+            if events_keys.contains(&EventKeyType::MemPoolTransactions) {
+                Some(json!({ "sent_to": "new_mempool_tx" }))
+            } else {
+                None
+            }
+        });
+
+        // only the subscribed payload is streamed
+        let message = match client.read_message().unwrap() {
+            Message::Text(text) => serde_json::from_str::<serde_json::Value>(&text).unwrap(),
+            other => panic!("Unexpected message: {:?}", other),
+        };
+        assert_eq!(message["path"], "new_mempool_tx");
+        assert_eq!(message["payload"]["sent_to"], "new_mempool_tx");
+    }
+
+    #[test]
+    fn reject_unknown_event_keys() {
+        let server = EventStreamServer::start("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", server.local_addr());
+        let (mut client, _) = tungstenite::connect(url.as_str()).unwrap();
+        client
+            .write_message(Message::Text(r#"{"events_keys": ["not-a-key"]}"#.into()))
+            .unwrap();
+
+        match client.read_message() {
+            Ok(Message::Close(_)) | Err(_) => {}
+            Ok(other) => panic!("Unexpected message: {:?}", other),
+        }
+        assert_eq!(server.num_clients(), 0);
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use async_h1::client;
+use async_std::net::TcpStream;
+use http_types::{Method, Request, Url};
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::chainstate::stacks::Error as ChainstateError;
+use stacks::monitoring::{
+    increment_fork_divergence_alerts_counter, update_reference_node_divergence,
+};
+use stacks_common::types::chainstate::{BlockHeaderHash, StacksBlockId};
+use stacks_common::util::{get_epoch_time_secs, sleep_ms};
+
+use crate::event_dispatcher::EventDispatcher;
+use crate::neon_node::Globals;
+use crate::Config;
+
+/// How long to wait for a reference node to answer
+const REFERENCE_NODE_TIMEOUT_SECS: u64 = 10;
+
+/// The fields of a reference node's `/v2/info` response that the fork monitor uses
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReferenceTip {
+    pub stacks_tip_height: u64,
+    pub stacks_tip: BlockHeaderHash,
+    pub stacks_tip_consensus_hash: ConsensusHash,
+}
+
+impl ReferenceTip {
+    pub fn index_block_hash(&self) -> StacksBlockId {
+        StacksBlockId::new(&self.stacks_tip_consensus_hash, &self.stacks_tip)
+    }
+}
+
+/// Sent to event observers when a reference node's tip starts (`diverged` is true) or stops
+/// diverging from ours by more than the threshold
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForkDivergenceEvent {
+    pub reference_node: String,
+    pub diverged: bool,
+    pub divergence: u64,
+    pub threshold: u64,
+    pub local_tip_height: u64,
+    pub local_tip: StacksBlockId,
+    pub reference_tip_height: u64,
+    pub reference_tip: StacksBlockId,
+}
+
+/// How many blocks our canonical tip diverges from a reference node's tip.
+/// * If the reference node is ahead of us, this is how far behind it we are (we may be stalled,
+/// or on a fork that is losing).
+/// * If it is not ahead of us, and its tip is in our canonical fork, we agree and this is 0.
+/// * Otherwise its tip is not in our fork, so none of our blocks from its tip height on are in
+/// its fork.
+pub fn divergence(
+    local_tip_height: u64,
+    reference_tip_height: u64,
+    reference_tip_in_local_fork: bool,
+) -> u64 {
+    if reference_tip_height > local_tip_height {
+        reference_tip_height - local_tip_height
+    } else if reference_tip_in_local_fork {
+        0
+    } else {
+        local_tip_height - reference_tip_height + 1
+    }
+}
+
+/// Remembers which reference nodes have diverged by more than the threshold, so alerts are only
+/// raised when a reference node starts or stops diverging
+pub struct DivergenceTracker {
+    threshold: u64,
+    diverged: HashSet<String>,
+}
+
+impl DivergenceTracker {
+    pub fn new(threshold: u64) -> DivergenceTracker {
+        DivergenceTracker {
+            threshold,
+            diverged: HashSet::new(),
+        }
+    }
+
+    /// Record a reference node's divergence. Returns `Some(true)` if it just exceeded the
+    /// threshold, `Some(false)` if it just came back within it, and `None` if nothing changed.
+    pub fn observe(&mut self, reference_node: &str, divergence: u64) -> Option<bool> {
+        if divergence > self.threshold {
+            if self.diverged.insert(reference_node.to_string()) {
+                return Some(true);
+            }
+        } else if self.diverged.remove(reference_node) {
+            return Some(false);
+        }
+        None
+    }
+}
+
+/// Ask a reference node for its canonical Stacks tip
+fn fetch_reference_tip(reference_node: &str) -> Result<ReferenceTip, String> {
+    let url = Url::parse(&format!("http://{}/v2/info", reference_node))
+        .map_err(|e| format!("invalid URL: {:?}", &e))?;
+    let req = Request::new(Method::Get, url);
+
+    let res = async_std::task::block_on(async_std::future::timeout(
+        Duration::from_secs(REFERENCE_NODE_TIMEOUT_SECS),
+        async {
+            let stream = TcpStream::connect(reference_node)
+                .await
+                .map_err(|e| format!("connection failed: {:?}", &e))?;
+            let mut response = client::connect(stream, req)
+                .await
+                .map_err(|e| format!("request failed: {:?}", &e))?;
+            if !response.status().is_success() {
+                return Err(format!("bad status: {}", response.status()));
+            }
+            response
+                .body_string()
+                .await
+                .map_err(|e| format!("failed to read body: {:?}", &e))
+        },
+    ))
+    .map_err(|_| "timed out".to_string())??;
+
+    serde_json::from_str(&res).map_err(|e| format!("malformed /v2/info response: {:?}", &e))
+}
+
+/// Periodically compares our canonical Stacks tip with those of the configured reference nodes,
+/// so operators find out quickly if this node is on a minority fork
+pub struct ForkMonitor {
+    reference_nodes: Vec<String>,
+    poll_time_secs: u64,
+    threshold: u64,
+    sortdb: SortitionDB,
+    chainstate: StacksChainState,
+    event_dispatcher: EventDispatcher,
+    tracker: DivergenceTracker,
+}
+
+impl ForkMonitor {
+    pub fn new(
+        config: &Config,
+        sortdb: SortitionDB,
+        chainstate: StacksChainState,
+        event_dispatcher: EventDispatcher,
+    ) -> ForkMonitor {
+        ForkMonitor {
+            reference_nodes: config.node.reference_nodes.clone(),
+            poll_time_secs: config.node.fork_monitor_poll_time_secs,
+            threshold: config.node.fork_divergence_threshold,
+            sortdb,
+            chainstate,
+            event_dispatcher,
+            tracker: DivergenceTracker::new(config.node.fork_divergence_threshold),
+        }
+    }
+
+    /// Our canonical Stacks tip's height and index block hash
+    fn local_tip(&self) -> Result<(u64, StacksBlockId), String> {
+        let (consensus_hash, block_hash) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(self.sortdb.conn())
+                .map_err(|e| format!("failed to load canonical Stacks tip: {:?}", &e))?;
+        let header = StacksChainState::get_anchored_block_header_info(
+            self.chainstate.db(),
+            &consensus_hash,
+            &block_hash,
+        )
+        .map_err(|e| format!("failed to load canonical Stacks tip header: {:?}", &e))?
+        .ok_or_else(|| "canonical Stacks tip header not found".to_string())?;
+        Ok((header.stacks_block_height, header.index_block_hash()))
+    }
+
+    /// Whether `block_id` is `tip`, or one of its ancestors
+    fn is_in_fork(&self, block_id: &StacksBlockId, tip: &StacksBlockId) -> bool {
+        let res = self.chainstate.index_conn().and_then(|conn| {
+            conn.get_ancestor_block_height(block_id, tip)
+                .map_err(ChainstateError::DBError)
+        });
+        match res {
+            Ok(height_opt) => height_opt.is_some(),
+            Err(e) => {
+                warn!("Fork monitor: failed to query block ancestry: {:?}", &e);
+                false
+            }
+        }
+    }
+
+    /// Compare our tip with each reference node's once
+    pub fn check_reference_nodes(&mut self) {
+        let (local_tip_height, local_tip) = match self.local_tip() {
+            Ok(tip) => tip,
+            Err(e) => {
+                debug!("Fork monitor: no local tip to compare: {}", &e);
+                return;
+            }
+        };
+
+        for reference_node in self.reference_nodes.clone().iter() {
+            let reference_tip = match fetch_reference_tip(reference_node) {
+                Ok(reference_tip) => reference_tip,
+                Err(e) => {
+                    warn!("Fork monitor: failed to query reference node: {}", &e; "reference_node" => reference_node);
+                    continue;
+                }
+            };
+            let reference_block_id = reference_tip.index_block_hash();
+            let divergence = divergence(
+                local_tip_height,
+                reference_tip.stacks_tip_height,
+                reference_tip.stacks_tip_height <= local_tip_height
+                    && self.is_in_fork(&reference_block_id, &local_tip),
+            );
+            debug!("Fork monitor: checked reference node";
+                   "reference_node" => reference_node,
+                   "divergence" => divergence,
+                   "local_tip_height" => local_tip_height,
+                   "reference_tip_height" => reference_tip.stacks_tip_height);
+            update_reference_node_divergence(
+                reference_node,
+                i64::try_from(divergence).unwrap_or(i64::MAX),
+            );
+
+            let Some(diverged) = self.tracker.observe(reference_node, divergence) else {
+                continue;
+            };
+            if diverged {
+                warn!("Fork monitor: canonical Stacks tip diverges from reference node's";
+                      "reference_node" => reference_node,
+                      "divergence" => divergence,
+                      "threshold" => self.threshold,
+                      "local_tip" => %local_tip,
+                      "local_tip_height" => local_tip_height,
+                      "reference_tip" => %reference_block_id,
+                      "reference_tip_height" => reference_tip.stacks_tip_height);
+                increment_fork_divergence_alerts_counter();
+            } else {
+                info!("Fork monitor: canonical Stacks tip agrees with reference node's again";
+                      "reference_node" => reference_node,
+                      "divergence" => divergence);
+            }
+            self.event_dispatcher
+                .process_fork_divergence(&ForkDivergenceEvent {
+                    reference_node: reference_node.clone(),
+                    diverged,
+                    divergence,
+                    threshold: self.threshold,
+                    local_tip_height,
+                    local_tip: local_tip.clone(),
+                    reference_tip_height: reference_tip.stacks_tip_height,
+                    reference_tip: reference_block_id,
+                });
+        }
+    }
+
+    /// Poll the reference nodes until the node shuts down
+    pub fn run(mut self, globals: Globals) {
+        debug!("Fork monitor thread start!");
+        let mut last_check_time = 0;
+        while globals.keep_running() {
+            if last_check_time + self.poll_time_secs <= get_epoch_time_secs() {
+                self.check_reference_nodes();
+                last_check_time = get_epoch_time_secs();
+            }
+            sleep_ms(1000);
+        }
+        debug!("Fork monitor thread exit!");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn divergence_from_reference_tip() {
+        // same tip, or the reference node is behind us on our fork
+        assert_eq!(divergence(100, 100, true), 0);
+        assert_eq!(divergence(100, 90, true), 0);
+        // we're behind the reference node
+        assert_eq!(divergence(90, 100, false), 10);
+        // same height, different tips
+        assert_eq!(divergence(100, 100, false), 1);
+        // the reference node's tip is on a different fork, below our tip
+        assert_eq!(divergence(100, 95, false), 6);
+    }
+
+    #[test]
+    fn tracker_alerts_on_threshold_crossings() {
+        let mut tracker = DivergenceTracker::new(3);
+        assert_eq!(tracker.observe("a:20443", 3), None);
+        assert_eq!(tracker.observe("a:20443", 4), Some(true));
+        // still diverged, so no new alert
+        assert_eq!(tracker.observe("a:20443", 10), None);
+        // other reference nodes are tracked on their own
+        assert_eq!(tracker.observe("b:20443", 5), Some(true));
+        assert_eq!(tracker.observe("a:20443", 0), Some(false));
+        assert_eq!(tracker.observe("a:20443", 1), None);
+        assert_eq!(tracker.observe("b:20443", 5), None);
+    }
+
+    #[test]
+    fn parse_reference_tip() {
+        let info = r#"{
+            "peer_version": 402653189,
+            "pox_consensus": "17f76e597bab45646956f38dd39573085d72cbc0",
+            "burn_block_height": 16,
+            "stable_pox_consensus": "8e0561978fc5506b68a589c402dad97e862edb59",
+            "stable_burn_block_height": 15,
+            "server_version": "blockstack-core 0.0.1",
+            "network_id": 2147483648,
+            "parent_network_id": 3669344250,
+            "stacks_tip_height": 15,
+            "stacks_tip": "b1807a2d3f7f8c7922f7c1d60d7c34145ade05d789640dc7dc9ec1021e07bb54",
+            "stacks_tip_consensus_hash": "17f76e597bab45646956f38dd39573085d72cbc0",
+            "unanchored_tip": "0000000000000000000000000000000000000000000000000000000000000000",
+            "exit_at_block_height": null
+        }"#;
+        let reference_tip: ReferenceTip = serde_json::from_str(info).unwrap();
+        assert_eq!(reference_tip.stacks_tip_height, 15);
+        assert_eq!(
+            reference_tip.index_block_hash(),
+            StacksBlockId::new(
+                &ConsensusHash::from_hex("17f76e597bab45646956f38dd39573085d72cbc0").unwrap(),
+                &BlockHeaderHash::from_hex(
+                    "b1807a2d3f7f8c7922f7c1d60d7c34145ade05d789640dc7dc9ec1021e07bb54"
+                )
+                .unwrap()
+            )
+        );
+    }
+}
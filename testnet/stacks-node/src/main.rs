@@ -20,6 +20,7 @@ extern crate slog;
 
 pub use stacks_common::util;
 use stacks_common::util::hash::hex_bytes;
+use stacks_common::util::log::set_log_format;
 
 pub mod monitoring;
 
@@ -27,6 +28,8 @@ pub mod burnchains;
 pub mod chain_data;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_stream;
+pub mod fork_monitor;
 pub mod genesis_data;
 pub mod keychain;
 pub mod neon_node;
@@ -422,6 +425,10 @@ fn main() {
             process::exit(1);
         }
     };
+    if let Err(e) = set_log_format(conf.logging.format) {
+        warn!("Invalid config: {}", e);
+        process::exit(1);
+    }
     debug!("node configuration {:?}", &conf.node);
     debug!("burnchain configuration {:?}", &conf.burnchain);
     debug!("connection configuration {:?}", &conf.connection_options);
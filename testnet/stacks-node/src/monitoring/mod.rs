@@ -1,6 +1,8 @@
 #![allow(unused_variables)]
 
-pub use stacks::monitoring::{increment_errors_emitted_counter, increment_warning_emitted_counter};
+pub use stacks::monitoring::{
+    increment_errors_emitted_counter, increment_warning_emitted_counter, set_network_label,
+};
 
 #[cfg(feature = "monitoring_prom")]
 mod prometheus;
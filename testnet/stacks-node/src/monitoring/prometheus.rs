@@ -2,7 +2,8 @@ use async_std::net::{TcpListener, TcpStream};
 use async_std::prelude::*;
 use async_std::task;
 use http_types::{Body, Response, StatusCode};
-use stacks::prometheus::{gather, Encoder, TextEncoder};
+use stacks::monitoring::gather_metrics;
+use stacks::prometheus::{Encoder, TextEncoder};
 
 pub fn start_serving_prometheus_metrics(bind_address: String) {
     let addr = bind_address.clone();
@@ -46,7 +47,7 @@ async fn accept(stream: TcpStream) -> http_types::Result<()> {
     debug!("Handle Prometheus polling ({})", stream.peer_addr()?);
     async_h1::accept(stream.clone(), |_| async {
         let encoder = TextEncoder::new();
-        let metric_families = gather();
+        let metric_families = gather_metrics();
         let mut buffer = vec![];
         encoder.encode(&metric_families, &mut buffer).unwrap();
 
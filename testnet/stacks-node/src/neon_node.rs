@@ -760,6 +760,15 @@ fn open_chainstate_with_faults(config: &Config) -> Result<StacksChainState, Chai
     )?;
 
     chainstate.fault_injection.hide_blocks = config.node.fault_injection_hide_blocks;
+    if config.node.txindex {
+        chainstate.txindex = true;
+    }
+    if config.node.account_txindex {
+        chainstate.account_txindex = true;
+    }
+    if config.node.map_key_index {
+        chainstate.set_map_key_index(true)?;
+    }
     Ok(chainstate)
 }
 
@@ -875,6 +884,10 @@ pub struct RelayerThread {
     /// if true, then the last time the miner thread was launched, it was used to mine a Stacks
     /// block (used to alternate between mining microblocks and Stacks blocks that confirm them)
     mined_stacks_block: bool,
+    /// last time we wrote (or restored) a mempool checkpoint, in secs
+    last_mempool_checkpoint_time: u64,
+    /// reward cycle of the burnchain tip when we last pruned old block data
+    last_prune_reward_cycle: Option<u64>,
 }
 
 pub(crate) struct BlockMinerThread {
@@ -2909,7 +2922,7 @@ impl RelayerThread {
             .make_cost_metric()
             .unwrap_or_else(|| Box::new(UnitMetric));
 
-        let mempool = MemPoolDB::open(
+        let mut mempool = MemPoolDB::open(
             is_mainnet,
             chain_id,
             &stacks_chainstate_path,
@@ -2917,6 +2930,7 @@ impl RelayerThread {
             metric,
         )
         .expect("Database failure opening mempool");
+        mempool.gc_policy = config.node.get_mempool_gc_policy();
 
         let keychain = Keychain::default(config.node.seed.clone());
         let bitcoin_controller = BitcoinRegtestController::new_dummy(config.clone());
@@ -2953,6 +2967,8 @@ impl RelayerThread {
 
             miner_thread: None,
             mined_stacks_block: false,
+            last_mempool_checkpoint_time: 0,
+            last_prune_reward_cycle: None,
         }
     }
 
@@ -3024,6 +3040,113 @@ impl RelayerThread {
         )
     }
 
+    /// Re-submit the transactions in the last mempool checkpoint, if checkpointing is enabled.
+    /// They are re-validated against the canonical Stacks tip, so anything mined or invalidated
+    /// while the node was down is dropped.
+    pub fn restore_mempool_checkpoint(&mut self) {
+        if self.config.node.mempool_checkpoint_interval_secs == 0 {
+            return;
+        }
+        let path = self.config.get_mempool_checkpoint_file_path();
+        let res = self.with_chainstate(|_, sortdb, chainstate, mempool| {
+            mempool.restore_checkpoint(chainstate, sortdb, &path)
+        });
+        match res {
+            Ok((0, 0)) => {}
+            Ok((num_restored, num_rejected)) => {
+                info!(
+                    "Relayer: Restored mempool checkpoint";
+                    "path" => &path,
+                    "restored" => num_restored,
+                    "rejected" => num_rejected
+                );
+            }
+            Err(e) => {
+                warn!("Relayer: Failed to restore mempool checkpoint: {:?}", &e; "path" => &path);
+            }
+        }
+        self.last_mempool_checkpoint_time = get_epoch_time_secs();
+    }
+
+    /// Write a mempool checkpoint if checkpointing is enabled, and either `force` is set (e.g. on
+    /// shutdown) or the checkpoint interval has elapsed since the last one.
+    pub fn checkpoint_mempool(&mut self, force: bool) {
+        let interval = self.config.node.mempool_checkpoint_interval_secs;
+        if interval == 0 {
+            return;
+        }
+        let now = get_epoch_time_secs();
+        if !force && now < self.last_mempool_checkpoint_time.saturating_add(interval) {
+            return;
+        }
+        self.last_mempool_checkpoint_time = now;
+
+        let path = self.config.get_mempool_checkpoint_file_path();
+        let mempool = self
+            .mempool
+            .as_ref()
+            .expect("FATAL: tried to access mempool while it was taken");
+        match mempool.write_checkpoint(&path) {
+            Ok(num_txs) => {
+                debug!("Relayer: Wrote mempool checkpoint"; "path" => &path, "num_txs" => num_txs);
+            }
+            Err(e) => {
+                warn!("Relayer: Failed to write mempool checkpoint: {:?}", &e; "path" => &path);
+            }
+        }
+    }
+
+    /// Drop the bodies of blocks and microblocks older than the configured number of reward
+    /// cycles, if pruning is enabled.  Only runs once per reward cycle.
+    pub fn prune_blocks(&mut self) {
+        let prune_reward_cycles = match self.config.node.prune_reward_cycles {
+            Some(n) => n,
+            None => {
+                return;
+            }
+        };
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(self.sortdb_ref().conn())
+            .expect("FATAL: failed to read current burnchain tip");
+        let reward_cycle = match self
+            .burnchain
+            .block_height_to_reward_cycle(burn_tip.block_height)
+        {
+            Some(rc) => rc,
+            None => {
+                return;
+            }
+        };
+        if self.last_prune_reward_cycle == Some(reward_cycle) {
+            return;
+        }
+        self.last_prune_reward_cycle = Some(reward_cycle);
+
+        let cutoff_reward_cycle = reward_cycle.saturating_sub(prune_reward_cycles);
+        if cutoff_reward_cycle == 0 {
+            return;
+        }
+        let cutoff_height = self
+            .burnchain
+            .reward_cycle_to_block_height(cutoff_reward_cycle);
+        let res = self.with_chainstate(|_relayer_thread, _sortdb, chainstate, _mempool| {
+            chainstate.prune_blocks(cutoff_height)
+        });
+        match res {
+            Ok((num_blocks, num_microblocks)) => {
+                info!(
+                    "Relayer: Pruned old block data";
+                    "reward_cycle" => reward_cycle,
+                    "burn_height" => cutoff_height,
+                    "num_blocks" => num_blocks,
+                    "num_microblocks" => num_microblocks
+                );
+            }
+            Err(e) => {
+                warn!("Relayer: Failed to prune old block data: {:?}", &e; "burn_height" => cutoff_height);
+            }
+        }
+    }
+
     /// Handle a NetworkResult from the p2p/http state machine.  Usually this is the act of
     /// * preprocessing and storing new blocks and microblocks
     /// * relaying blocks, microblocks, and transacctions
@@ -4245,8 +4368,31 @@ impl RelayerThread {
         if self.can_run_microblock_tenure() {
             self.microblock_miner_thread_try_start();
         }
+
+        // see if our block-commit needs a fee bump to get mined
+        self.try_bump_block_commit_fee();
         continue_running
     }
+
+    /// Bump the fee of our ongoing block-commit if it has gone unconfirmed for longer than
+    /// `burnchain.rbf_bump_interval_ms`.
+    /// This only happens between tenures, since a running miner thread has its own copy of the
+    /// ongoing commit and would replace it with a stale fee.
+    fn try_bump_block_commit_fee(&mut self) {
+        if !self.config.node.miner || self.config.burnchain.rbf_bump_interval_ms.is_none() {
+            return;
+        }
+        if self.miner_thread.is_some() {
+            return;
+        }
+        let mut op_signer = self.keychain.generate_op_signer();
+        if let Some(txid) = self
+            .bitcoin_controller
+            .bump_ongoing_block_commit(&mut op_signer)
+        {
+            info!("Relayer: Bumped the fee of the unconfirmed block-commit"; "txid" => %txid);
+        }
+    }
 }
 
 impl ParentStacksBlockInfo {
@@ -4384,6 +4530,8 @@ pub struct PeerThread {
     num_download_passes: u64,
     /// last burnchain block seen in the PeerNetwork's chain view since the last run
     last_burn_block_height: u64,
+    /// the burnchain addresses this node mines from, reported by the RPC interface
+    miner_addresses: Vec<String>,
 }
 
 impl PeerThread {
@@ -4397,7 +4545,7 @@ impl PeerThread {
             .make_cost_metric()
             .unwrap_or_else(|| Box::new(UnitMetric));
 
-        let mempool = MemPoolDB::open(
+        let mut mempool = MemPoolDB::open(
             config.is_mainnet(),
             config.burnchain.chain_id,
             &config.get_chainstate_path_str(),
@@ -4405,6 +4553,7 @@ impl PeerThread {
             metric,
         )
         .expect("Database failure opening mempool");
+        mempool.gc_policy = config.node.get_mempool_gc_policy();
 
         mempool
     }
@@ -4438,6 +4587,13 @@ impl PeerThread {
 
         let poll_timeout = cmp::min(5000, config.miner.first_attempt_time_ms / 2);
 
+        let miner_addresses = if config.node.miner {
+            let keychain = Keychain::default(config.node.seed.clone());
+            BlockMinerThread::get_miner_addrs(&config, &keychain)
+        } else {
+            vec![]
+        };
+
         PeerThread {
             config,
             net: Some(net),
@@ -4451,6 +4607,7 @@ impl PeerThread {
             num_inv_sync_passes: 0,
             num_download_passes: 0,
             last_burn_block_height: 0,
+            miner_addresses,
         }
     }
 
@@ -4546,6 +4703,13 @@ impl PeerThread {
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(cost_metric.as_ref()),
                 fee_estimator: fee_estimator.map(|boxed_estimator| boxed_estimator.as_ref()),
+                miner_addresses: p2p_thread.miner_addresses.clone(),
+                bitcoin_tip_height: p2p_thread.globals.sync_comms.get_remote_burnchain_height(),
+                miner_status: if p2p_thread.config.node.miner {
+                    Some(p2p_thread.globals.get_miner_status())
+                } else {
+                    None
+                },
                 ..RPCHandlerArgs::default()
             };
             p2p_thread.with_network(|_, net| {
@@ -4937,6 +5101,8 @@ impl StacksNode {
     /// Runs in a separate thread.
     /// Continuously receives
     pub fn relayer_main(mut relayer_thread: RelayerThread, relay_recv: Receiver<RelayerDirective>) {
+        relayer_thread.restore_mempool_checkpoint();
+
         while let Ok(directive) = relay_recv.recv() {
             if !relayer_thread.globals.keep_running() {
                 break;
@@ -4945,8 +5111,14 @@ impl StacksNode {
             if !relayer_thread.handle_directive(directive) {
                 break;
             }
+
+            relayer_thread.checkpoint_mempool(false);
+            relayer_thread.prune_blocks();
         }
 
+        // save pending transactions for the next time we start
+        relayer_thread.checkpoint_mempool(true);
+
         // kill miner if it's running
         signal_mining_blocked(relayer_thread.globals.get_miner_status());
 
@@ -342,7 +342,11 @@ impl Node {
         let mut event_dispatcher = EventDispatcher::new();
 
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher
+                .register_observer(observer, &config.get_event_observer_queue_file_path());
+        }
+        if let Some(event_stream_bind) = config.node.event_stream_bind.as_ref() {
+            event_dispatcher.start_event_stream(event_stream_bind);
         }
 
         let burnchain_config = config.get_burnchain();
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 #[cfg(test)]
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -8,6 +9,7 @@ use std::{cmp, thread};
 
 use libc;
 use stacks::burnchains::bitcoin::address::{BitcoinAddress, LegacyBitcoinAddressType};
+use stacks::burnchains::indexer::{BurnHeaderIPC, BurnchainIndexer};
 use stacks::burnchains::Burnchain;
 use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::burn::BlockSnapshot;
@@ -17,21 +19,26 @@ use stacks::chainstate::coordinator::{
     static_get_heaviest_affirmation_map, static_get_stacks_tip_affirmation_map, ChainsCoordinator,
     ChainsCoordinatorConfig, CoordinatorCommunication, Error as coord_error,
 };
+use stacks::chainstate::snapshot::{install_snapshot, verify_snapshot};
 use stacks::chainstate::stacks::db::{ChainStateBootData, StacksChainState};
 use stacks::chainstate::stacks::miner::{signal_mining_blocked, signal_mining_ready, MinerStatus};
 use stacks::core::StacksEpochId;
 use stacks::net::atlas::{AtlasConfig, AtlasDB, Attachment};
 use stacks::util_lib::db::Error as db_error;
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash as BitcoinSha256dHash;
 use stacks_common::deps_common::ctrlc as termination;
 use stacks_common::deps_common::ctrlc::SignalId;
+use stacks_common::types::chainstate::BurnchainHeaderHash;
 use stacks_common::types::PublicKey;
 use stacks_common::util::hash::Hash160;
+use stacks_common::util::log::update_log_context;
 use stacks_common::util::{get_epoch_time_secs, sleep_ms};
 use stx_genesis::GenesisData;
 
 use super::RunLoopCallbacks;
 use crate::burnchains::make_bitcoin_indexer;
-use crate::monitoring::start_serving_monitoring_metrics;
+use crate::fork_monitor::ForkMonitor;
+use crate::monitoring::{set_network_label, start_serving_monitoring_metrics};
 use crate::neon_node::{Globals, StacksNode, BLOCK_PROCESSOR_STACK_SIZE, RELAYER_MAX_BUFFER};
 use crate::node::{
     get_account_balances, get_account_lockups, get_names, get_namespaces,
@@ -176,7 +183,11 @@ impl RunLoop {
 
         let mut event_dispatcher = EventDispatcher::new();
         for observer in config.events_observers.iter() {
-            event_dispatcher.register_observer(observer);
+            event_dispatcher
+                .register_observer(observer, &config.get_event_observer_queue_file_path());
+        }
+        if let Some(event_stream_bind) = config.node.event_stream_bind.as_ref() {
+            event_dispatcher.start_event_stream(event_stream_bind);
         }
 
         Self {
@@ -373,6 +384,8 @@ impl RunLoop {
         // sanity check -- epoch data must be valid
         Config::assert_valid_epoch_settings(&burnchain, &epochs);
 
+        self.bootstrap_from_snapshot(&burnchain);
+
         // Upgrade chainstate databases if they exist already
         match migrate_chainstate_dbs(
             &epochs,
@@ -432,6 +445,78 @@ impl RunLoop {
         burnchain_controller
     }
 
+    /// If the node is configured with a chainstate snapshot and has no chainstate yet, verify
+    /// the snapshot and install it, so the node only syncs the chain from where the snapshot
+    /// left off.  The snapshot's burnchain block must be on the burnchain our own bitcoin node
+    /// sees, so a snapshot of a fork is rejected.
+    fn bootstrap_from_snapshot(&self, burnchain: &Burnchain) {
+        let (snapshot_path, signer_pubkey) = match (
+            self.config.node.snapshot_path.as_ref(),
+            self.config.node.snapshot_signer_pubkey.as_ref(),
+        ) {
+            (Some(snapshot_path), Some(signer_pubkey)) => {
+                (PathBuf::from(snapshot_path), signer_pubkey)
+            }
+            _ => return,
+        };
+        let chainstate_path = self.config.get_chainstate_path();
+        if chainstate_path.exists() {
+            info!("Chainstate exists already; not bootstrapping from snapshot";
+                  "snapshot_path" => %snapshot_path.display());
+            return;
+        }
+
+        info!("Verifying chainstate snapshot"; "snapshot_path" => %snapshot_path.display());
+        let manifest = verify_snapshot(
+            &snapshot_path,
+            signer_pubkey,
+            burnchain.pox_constants.clone(),
+            &self.config.node.get_marf_opts(),
+        )
+        .unwrap_or_else(|e| panic!("FATAL: chainstate snapshot is not valid: {:?}", &e));
+
+        // sync our own burnchain headers up to the snapshot's burnchain block
+        let mut indexer =
+            make_bitcoin_indexer(&self.config, Some(self.should_keep_running.clone()));
+        let burn_header_hash = indexer
+            .get_highest_header_height()
+            .and_then(|headers_height| {
+                indexer.sync_headers(headers_height, Some(manifest.burn_block_height + 1))
+            })
+            .and_then(|_| {
+                indexer.read_headers(manifest.burn_block_height, manifest.burn_block_height + 1)
+            })
+            .unwrap_or_else(|e| panic!("FATAL: failed to sync burnchain headers: {:?}", &e))
+            .pop()
+            .map(|hdr| {
+                BurnchainHeaderHash::from_bitcoin_hash(&BitcoinSha256dHash(hdr.header_hash()))
+            })
+            .unwrap_or_else(|| {
+                panic!(
+                    "FATAL: no burnchain header at snapshot height {}",
+                    manifest.burn_block_height
+                )
+            });
+        if let Err(e) = manifest.check_burnchain_header(&burn_header_hash) {
+            panic!(
+                "FATAL: chainstate snapshot is not on our burnchain: {:?} (snapshot has {}, we have {})",
+                &e, &manifest.burn_header_hash, &burn_header_hash
+            );
+        }
+
+        let dest_dir = chainstate_path
+            .parent()
+            .expect("FATAL: chainstate path has no parent directory");
+        if let Err(e) = install_snapshot(&snapshot_path, &manifest, dest_dir) {
+            panic!("FATAL: failed to install chainstate snapshot: {:?}", &e);
+        }
+        info!("Installed chainstate snapshot";
+              "burn_block_height" => manifest.burn_block_height,
+              "burn_header_hash" => %manifest.burn_header_hash,
+              "stacks_tip_consensus_hash" => %manifest.stacks_tip_consensus_hash,
+              "stacks_tip_block_hash" => %manifest.stacks_tip_block_hash);
+    }
+
     /// Boot up the stacks chainstate.
     /// Instantiate the chainstate and push out the boot receipts to observers
     /// This is only public so we can test it.
@@ -466,7 +551,7 @@ impl RunLoop {
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             self.config.is_mainnet(),
             self.config.burnchain.chain_id,
             &self.config.get_chainstate_path_str(),
@@ -474,6 +559,17 @@ impl RunLoop {
             Some(self.config.node.get_marf_opts()),
         )
         .unwrap();
+        if self.config.node.txindex {
+            chain_state_db.txindex = true;
+        }
+        if self.config.node.account_txindex {
+            chain_state_db.account_txindex = true;
+        }
+        if self.config.node.map_key_index {
+            chain_state_db
+                .set_map_key_index(true)
+                .expect("FATAL: failed to set up the data map key index");
+        }
         run_loop::announce_boot_receipts(
             &mut self.event_dispatcher,
             &chain_state_db,
@@ -570,6 +666,7 @@ impl RunLoop {
     fn start_prometheus(&mut self) {
         let prometheus_bind = self.config.node.prometheus_bind.clone();
         if let Some(prometheus_bind) = prometheus_bind {
+            set_network_label(&self.config.burnchain.mode);
             thread::Builder::new()
                 .name("prometheus".to_string())
                 .spawn(move || {
@@ -940,6 +1037,36 @@ impl RunLoop {
         liveness_thread_handle
     }
 
+    /// Spawn a thread to compare our canonical Stacks tip with the reference nodes', if any are
+    /// configured
+    fn spawn_fork_monitor_thread(&self, globals: Globals) -> Option<JoinHandle<()>> {
+        if self.config.node.reference_nodes.is_empty() {
+            return None;
+        }
+        let config = self.config.clone();
+        let sortdb = self
+            .get_burnchain()
+            .open_sortition_db(true)
+            .expect("FATAL: could not open sortition DB");
+
+        let (chain_state_db, _) = StacksChainState::open(
+            config.is_mainnet(),
+            config.burnchain.chain_id,
+            &config.get_chainstate_path_str(),
+            Some(config.node.get_marf_opts()),
+        )
+        .unwrap();
+
+        let fork_monitor =
+            ForkMonitor::new(&config, sortdb, chain_state_db, self.get_event_dispatcher());
+        let fork_monitor_thread_handle = thread::Builder::new()
+            .name(format!("fork-monitor-{}", config.node.rpc_bind))
+            .spawn(move || fork_monitor.run(globals))
+            .expect("FATAL: failed to spawn fork monitor thread");
+
+        Some(fork_monitor_thread_handle)
+    }
+
     /// Starts the node runloop.
     ///
     /// This function will block by looping infinitely.
@@ -1013,6 +1140,7 @@ impl RunLoop {
         // (it could be non-zero if the node is resuming from chainstate)
         let mut node = StacksNode::spawn(self, globals.clone(), relay_recv);
         let liveness_thread = self.spawn_chain_liveness_thread(globals.clone());
+        let fork_monitor_thread = self.spawn_fork_monitor_thread(globals.clone());
 
         // Wait for all pending sortitions to process
         let burnchain_db = burnchain_config
@@ -1063,12 +1191,17 @@ impl RunLoop {
                 coordinator_thread_handle.join().unwrap();
                 node.join();
                 liveness_thread.join().unwrap();
+                if let Some(fork_monitor_thread) = fork_monitor_thread {
+                    fork_monitor_thread.join().unwrap();
+                }
 
                 info!("Exiting stacks-node");
                 break;
             }
 
             let remote_chain_height = burnchain.get_headers_height() - 1;
+            self.pox_watchdog_comms
+                .set_remote_burnchain_height(remote_chain_height);
 
             // wait for the p2p state-machine to do at least one pass
             debug!("Runloop: Wait until Stacks block downloads reach a quiescent state before processing more burnchain blocks"; "remote_chain_height" => remote_chain_height, "local_chain_height" => burnchain_height);
@@ -1202,6 +1335,11 @@ impl RunLoop {
                     );
 
                     sortition_db_height = next_sortition_height;
+                    update_log_context(|ctx| {
+                        ctx.burn_height = Some(sortition_db_height);
+                        ctx.reward_cycle =
+                            burnchain_config.block_height_to_reward_cycle(sortition_db_height);
+                    });
                 } else if ibd {
                     // drive block processing after we reach the burnchain tip.
                     // we may have downloaded all the blocks already,
@@ -25,6 +25,8 @@ pub struct PoxSyncWatchdogComms {
     download_passes: Arc<AtomicU64>,
     /// What's our last IBD status?
     last_ibd: Arc<AtomicBool>,
+    /// Height of the burnchain tip, as far as we have downloaded headers (0 if not yet known)
+    remote_burnchain_height: Arc<AtomicU64>,
     /// Should keep running?
     should_keep_running: Arc<AtomicBool>,
 }
@@ -36,6 +38,7 @@ impl PoxSyncWatchdogComms {
             inv_sync_passes: Arc::new(AtomicU64::new(0)),
             download_passes: Arc::new(AtomicU64::new(0)),
             last_ibd: Arc::new(AtomicBool::new(true)),
+            remote_burnchain_height: Arc::new(AtomicU64::new(0)),
             should_keep_running,
         }
     }
@@ -56,6 +59,18 @@ impl PoxSyncWatchdogComms {
         self.last_ibd.load(Ordering::SeqCst)
     }
 
+    /// The height of the burnchain tip, as far as we have downloaded headers
+    pub fn get_remote_burnchain_height(&self) -> Option<u64> {
+        match self.remote_burnchain_height.load(Ordering::SeqCst) {
+            0 => None,
+            height => Some(height),
+        }
+    }
+
+    pub fn set_remote_burnchain_height(&mut self, height: u64) {
+        self.remote_burnchain_height.store(height, Ordering::SeqCst);
+    }
+
     /// Wait for at least one inv-sync state-machine passes
     pub fn wait_for_inv_sync_pass(&self, timeout: u64) -> Result<bool, burnchain_error> {
         let current = self.get_inv_sync_passes();
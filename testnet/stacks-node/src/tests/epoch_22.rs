@@ -138,6 +138,7 @@ fn disable_pox() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
@@ -670,6 +671,7 @@ fn pox_2_unlock_all() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
@@ -156,6 +156,7 @@ fn fix_to_pox_contract() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
@@ -794,6 +795,7 @@ fn verify_auto_unlock_behavior() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
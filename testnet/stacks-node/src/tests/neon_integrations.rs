@@ -781,6 +781,7 @@ fn bitcoind_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1114,6 +1115,7 @@ fn deep_contract() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -1218,6 +1220,7 @@ fn bad_microblock_pubkey() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1303,6 +1306,7 @@ fn liquid_ustx_integration() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -1431,6 +1435,7 @@ fn lockup_integration() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1547,6 +1552,7 @@ fn stx_transfer_btc_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -1816,6 +1822,7 @@ fn stx_delegate_btc_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2384,6 +2391,7 @@ fn microblock_fork_poison_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2615,6 +2623,7 @@ fn microblock_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3590,6 +3599,7 @@ fn size_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3786,6 +3796,7 @@ fn size_overflow_unconfirmed_stream_microblocks_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3980,6 +3991,7 @@ fn size_overflow_unconfirmed_invalid_stream_microblocks_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4246,6 +4258,7 @@ fn runtime_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4418,6 +4431,7 @@ fn block_replay_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4550,6 +4564,7 @@ fn cost_voting_integration() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -4872,6 +4887,7 @@ fn mining_events_integration_test() {
             EventKeyType::MinedBlocks,
             EventKeyType::MinedMicroblocks,
         ],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5139,6 +5155,7 @@ fn block_limit_hit_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5394,6 +5411,7 @@ fn microblock_limit_hit_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5544,6 +5562,7 @@ fn block_large_tx_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -5682,6 +5701,7 @@ fn microblock_large_tx_integration_test_FLAKY() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -5820,6 +5840,7 @@ fn pox_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let first_bal = 6_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -6357,6 +6378,7 @@ fn atlas_integration_test() {
         .insert(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.always_use_affirmation_maps = false;
@@ -6897,6 +6919,7 @@ fn antientropy_integration_test() {
         .insert(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.mine_microblocks = true;
@@ -7899,6 +7922,7 @@ fn fuzzed_median_fee_rate_estimation_test(window_size: u64, expected_final_value
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8079,6 +8103,7 @@ fn use_latest_tip_integration_test() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8473,6 +8498,7 @@ fn test_problematic_txs_are_not_stored() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8625,6 +8651,7 @@ fn spawn_follower_node(
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances = initial_conf.initial_balances.clone();
@@ -8724,6 +8751,7 @@ fn test_problematic_blocks_are_not_mined() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9082,6 +9110,7 @@ fn test_problematic_blocks_are_not_relayed_or_stored() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9476,6 +9505,7 @@ fn test_problematic_microblocks_are_not_mined() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9862,6 +9892,7 @@ fn test_problematic_microblocks_are_not_relayed_or_stored() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -10213,6 +10244,7 @@ fn push_boot_receipts() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let burnchain_config = Burnchain::regtest(&conf.get_burn_db_path());
@@ -10261,6 +10293,7 @@ fn run_with_custom_wallet() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     // custom wallet
@@ -10860,6 +10893,7 @@ fn min_txs() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.miner.min_tx_count = 4;
@@ -10966,6 +11000,7 @@ fn filter_txs_by_type() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.miner.min_tx_count = 4;
@@ -11082,6 +11117,7 @@ fn filter_txs_by_origin() {
     conf.events_observers.insert(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.miner.min_tx_count = 4;
@@ -73,6 +73,7 @@ fn setup_stx_btc_node(
         conf.events_observers.insert(EventObserverConfig {
             endpoint: format!("{}", signer_config.endpoint),
             events_keys: vec![EventKeyType::StackerDBChunks],
+            ..EventObserverConfig::default()
         });
     }
 